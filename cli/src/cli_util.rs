@@ -14,7 +14,9 @@
 
 use std::borrow::Cow;
 use std::cell::OnceCell;
+use std::cell::RefCell;
 use std::collections::BTreeMap;
+use std::collections::HashMap;
 use std::collections::HashSet;
 use std::env;
 use std::ffi::OsString;
@@ -30,6 +32,7 @@ use std::rc::Rc;
 use std::str;
 use std::str::FromStr;
 use std::sync::Arc;
+use std::time::Duration;
 use std::time::SystemTime;
 
 use bstr::ByteVec as _;
@@ -54,6 +57,7 @@ use jj_lib::backend::BackendResult;
 use jj_lib::backend::ChangeId;
 use jj_lib::backend::CommitId;
 use jj_lib::backend::MergedTreeId;
+use jj_lib::backend::Timestamp;
 use jj_lib::backend::TreeValue;
 use jj_lib::commit::Commit;
 use jj_lib::commit::CommitIteratorExt;
@@ -68,9 +72,11 @@ use jj_lib::conflicts::ConflictMarkerStyle;
 use jj_lib::fileset;
 use jj_lib::fileset::FilesetDiagnostics;
 use jj_lib::fileset::FilesetExpression;
-use jj_lib::gitignore::GitIgnoreError;
+use jj_lib::fileset::FilesetParseContext;
 use jj_lib::gitignore::GitIgnoreFile;
 use jj_lib::id_prefix::IdPrefixContext;
+use jj_lib::local_working_copy::DetectedChanges;
+use jj_lib::local_working_copy::LocalWorkingCopy;
 use jj_lib::matchers::Matcher;
 use jj_lib::merge::MergedTreeValue;
 use jj_lib::merged_tree::MergedTree;
@@ -92,6 +98,7 @@ use jj_lib::repo::Repo;
 use jj_lib::repo::RepoLoader;
 use jj_lib::repo::StoreFactories;
 use jj_lib::repo::StoreLoadError;
+use jj_lib::repo_format::FormatVersionError;
 use jj_lib::repo_path::RepoPath;
 use jj_lib::repo_path::RepoPathBuf;
 use jj_lib::repo_path::RepoPathUiConverter;
@@ -114,6 +121,7 @@ use jj_lib::rewrite::restore_tree;
 use jj_lib::settings::HumanByteSize;
 use jj_lib::settings::UserSettings;
 use jj_lib::str_util::StringPattern;
+use jj_lib::time_util;
 use jj_lib::transaction::Transaction;
 use jj_lib::view::View;
 use jj_lib::working_copy;
@@ -134,6 +142,7 @@ use jj_lib::workspace::Workspace;
 use jj_lib::workspace::WorkspaceLoadError;
 use jj_lib::workspace::WorkspaceLoader;
 use jj_lib::workspace::WorkspaceLoaderFactory;
+use jj_lib::workspace::WorkspaceSparsePatterns;
 use tracing::instrument;
 use tracing_chrome::ChromeLayerBuilder;
 use tracing_subscriber::prelude::*;
@@ -146,6 +155,7 @@ use crate::command_error::internal_error_with_message;
 use crate::command_error::print_parse_diagnostics;
 use crate::command_error::user_error;
 use crate::command_error::user_error_with_hint;
+use crate::command_error::user_error_with_message;
 use crate::command_error::CommandError;
 use crate::commit_templater::CommitTemplateLanguage;
 use crate::commit_templater::CommitTemplateLanguageExtension;
@@ -160,14 +170,17 @@ use crate::diff_util;
 use crate::diff_util::DiffFormat;
 use crate::diff_util::DiffFormatArgs;
 use crate::diff_util::DiffRenderer;
+use crate::diff_util::SimilarityThresholds;
 use crate::formatter::FormatRecorder;
 use crate::formatter::Formatter;
 use crate::formatter::PlainTextFormatter;
 use crate::merge_tools::DiffEditor;
 use crate::merge_tools::MergeEditor;
 use crate::merge_tools::MergeToolConfigError;
+use crate::operation_templater::OperationAnnotations;
 use crate::operation_templater::OperationTemplateLanguage;
 use crate::operation_templater::OperationTemplateLanguageExtension;
+use crate::progress::signing_progress;
 use crate::revset_util;
 use crate::revset_util::RevsetExpressionEvaluator;
 use crate::template_builder;
@@ -182,29 +195,39 @@ use crate::ui::Ui;
 
 const SHORT_CHANGE_ID_TEMPLATE_TEXT: &str = "format_short_change_id(self.change_id())";
 
+type ChromeTracingLayer = tracing_chrome::ChromeLayer<tracing_subscriber::Registry>;
+
+type ChromeTracingSubscriber = tracing_subscriber::layer::Layered<
+    tracing_subscriber::reload::Layer<Option<ChromeTracingLayer>, tracing_subscriber::Registry>,
+    tracing_subscriber::Registry,
+>;
+
+/// Handle to replace the Chrome tracing layer. The trace file is flushed when
+/// the layer is replaced or the last clone is dropped.
 #[derive(Clone)]
-struct ChromeTracingFlushGuard {
-    _inner: Option<Rc<tracing_chrome::FlushGuard>>,
+struct ChromeTracingHandle {
+    reload_layer: tracing_subscriber::reload::Handle<
+        Option<ChromeTracingLayer>,
+        tracing_subscriber::Registry,
+    >,
+    flush_guard: Rc<RefCell<Option<tracing_chrome::FlushGuard>>>,
 }
 
-impl Debug for ChromeTracingFlushGuard {
+/// Handle to initialize or change tracing subscription.
+#[derive(Clone)]
+pub struct TracingSubscription {
+    reload_log_filter:
+        tracing_subscriber::reload::Handle<tracing_subscriber::EnvFilter, ChromeTracingSubscriber>,
+    chrome_tracing: ChromeTracingHandle,
+}
+
+impl Debug for TracingSubscription {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let Self { _inner } = self;
-        f.debug_struct("ChromeTracingFlushGuard")
+        f.debug_struct("TracingSubscription")
             .finish_non_exhaustive()
     }
 }
 
-/// Handle to initialize or change tracing subscription.
-#[derive(Clone, Debug)]
-pub struct TracingSubscription {
-    reload_log_filter: tracing_subscriber::reload::Handle<
-        tracing_subscriber::EnvFilter,
-        tracing_subscriber::Registry,
-    >,
-    _chrome_tracing_flush_guard: ChromeTracingFlushGuard,
-}
-
 impl TracingSubscription {
     const ENV_VAR_NAME: &'static str = "JJ_LOG";
 
@@ -217,7 +240,8 @@ impl TracingSubscription {
             .from_env_lossy();
         let (filter, reload_log_filter) = tracing_subscriber::reload::Layer::new(filter);
 
-        let (chrome_tracing_layer, chrome_tracing_flush_guard) = match std::env::var("JJ_TRACE") {
+        let mut chrome_tracing_flush_guard = None;
+        let chrome_tracing_layer = match std::env::var("JJ_TRACE") {
             Ok(filename) => {
                 let filename = if filename.is_empty() {
                     format!(
@@ -230,35 +254,63 @@ impl TracingSubscription {
                 } else {
                     filename
                 };
-                let include_args = std::env::var("JJ_TRACE_INCLUDE_ARGS").is_ok();
-                let (layer, guard) = ChromeLayerBuilder::new()
-                    .file(filename)
-                    .include_args(include_args)
-                    .build();
-                (
-                    Some(layer),
-                    ChromeTracingFlushGuard {
-                        _inner: Some(Rc::new(guard)),
-                    },
-                )
+                let (layer, guard) = Self::build_chrome_tracing_layer(Path::new(&filename));
+                chrome_tracing_flush_guard = Some(guard);
+                Some(layer)
             }
-            Err(_) => (None, ChromeTracingFlushGuard { _inner: None }),
+            Err(_) => None,
+        };
+        let (chrome_tracing_layer, reload_chrome_tracing_layer) =
+            tracing_subscriber::reload::Layer::new(chrome_tracing_layer);
+        let chrome_tracing = ChromeTracingHandle {
+            reload_layer: reload_chrome_tracing_layer,
+            flush_guard: Rc::new(RefCell::new(chrome_tracing_flush_guard)),
         };
 
         tracing_subscriber::registry()
+            .with(chrome_tracing_layer)
             .with(
                 tracing_subscriber::fmt::Layer::default()
                     .with_writer(std::io::stderr)
                     .with_filter(filter),
             )
-            .with(chrome_tracing_layer)
             .init();
         TracingSubscription {
             reload_log_filter,
-            _chrome_tracing_flush_guard: chrome_tracing_flush_guard,
+            chrome_tracing,
         }
     }
 
+    fn build_chrome_tracing_layer(path: &Path) -> (ChromeTracingLayer, tracing_chrome::FlushGuard) {
+        let include_args = std::env::var("JJ_TRACE_INCLUDE_ARGS").is_ok();
+        ChromeLayerBuilder::new()
+            .file(path)
+            .include_args(include_args)
+            .build()
+    }
+
+    /// Starts writing spans to the given file in the Chrome trace event
+    /// format. Replaces the trace file specified by `$JJ_TRACE` if any.
+    pub fn enable_chrome_tracing(&self, path: &Path) -> Result<(), CommandError> {
+        // Check that the file can be created. The tracing layer would panic
+        // otherwise.
+        std::fs::File::create(path).map_err(|err| {
+            user_error_with_message(
+                format!("Failed to create trace file {}", path.display()),
+                err,
+            )
+        })?;
+        let (layer, guard) = Self::build_chrome_tracing_layer(path);
+        self.chrome_tracing
+            .reload_layer
+            .reload(Some(layer))
+            .map_err(|err| internal_error_with_message("failed to enable tracing", err))?;
+        // Flush the previous trace file if any
+        *self.chrome_tracing.flush_guard.borrow_mut() = Some(guard);
+        tracing::info!(path = %path.display(), "chrome tracing enabled");
+        Ok(())
+    }
+
     pub fn enable_debug_logging(&self) -> Result<(), CommandError> {
         self.reload_log_filter
             .modify(|filter| {
@@ -291,6 +343,7 @@ struct CommandHelperData {
     revset_extensions: Arc<RevsetExtensions>,
     commit_template_extensions: Vec<Arc<dyn CommitTemplateLanguageExtension>>,
     operation_template_extensions: Vec<Arc<dyn OperationTemplateLanguageExtension>>,
+    operation_annotations: OperationAnnotations,
     maybe_workspace_loader: Result<Box<dyn WorkspaceLoader>, CommandError>,
     store_factories: StoreFactories,
     working_copy_factories: WorkingCopyFactories,
@@ -612,7 +665,9 @@ impl CommandHelper {
     /// Returns true if the working copy to be loaded is writable, and therefore
     /// should usually be snapshotted.
     pub fn is_working_copy_writable(&self) -> bool {
-        self.is_at_head_operation() && !self.data.global_args.ignore_working_copy
+        self.is_at_head_operation()
+            && !self.data.global_args.ignore_working_copy
+            && self.data.global_args.at_workspace.is_none()
     }
 
     /// Returns true if the current operation is considered to be the head.
@@ -782,9 +837,11 @@ pub struct WorkspaceCommandEnvironment {
     template_aliases_map: TemplateAliasesMap,
     path_converter: RepoPathUiConverter,
     workspace_id: WorkspaceId,
+    workspace_sparse_patterns: HashMap<WorkspaceId, Vec<RepoPathBuf>>,
     immutable_heads_expression: Rc<UserRevsetExpression>,
     short_prefixes_expression: Option<Rc<UserRevsetExpression>>,
     conflict_marker_style: ConflictMarkerStyle,
+    undo_stash_retention: Option<Duration>,
 }
 
 impl WorkspaceCommandEnvironment {
@@ -803,10 +860,29 @@ impl WorkspaceCommandEnvironment {
             revset_aliases_map,
             template_aliases_map,
             path_converter,
-            workspace_id: workspace.workspace_id().to_owned(),
+            workspace_id: match &command.global_args().at_workspace {
+                Some(name) => WorkspaceId::new(name.clone()),
+                None => workspace.workspace_id().to_owned(),
+            },
+            workspace_sparse_patterns: WorkspaceSparsePatterns::load(workspace.repo_path())?
+                .to_map(),
             immutable_heads_expression: RevsetExpression::root(),
             short_prefixes_expression: None,
             conflict_marker_style: settings.get("ui.conflict-marker-style")?,
+            undo_stash_retention: settings.get_value_with(
+                "working-copy.undo-stash-days",
+                |value| -> Result<_, Box<dyn std::error::Error + Send + Sync>> {
+                    let days = value.as_integer().ok_or("Expected an integer")?;
+                    if days <= 0 {
+                        return Ok(None);
+                    }
+                    let secs = u64::try_from(days)
+                        .ok()
+                        .and_then(|days| days.checked_mul(86400))
+                        .ok_or("Number of days is too large")?;
+                    Ok(Some(Duration::from_secs(secs)))
+                },
+            )?,
         };
         env.immutable_heads_expression = env.load_immutable_heads_expression(ui)?;
         env.short_prefixes_expression = env.load_short_prefixes_expression(ui)?;
@@ -821,22 +897,42 @@ impl WorkspaceCommandEnvironment {
         &self.workspace_id
     }
 
-    pub(crate) fn revset_parse_context(&self) -> RevsetParseContext {
-        let workspace_context = RevsetWorkspaceContext {
+    pub(crate) fn fileset_parse_context(&self) -> FilesetParseContext<'_> {
+        FilesetParseContext {
             path_converter: &self.path_converter,
-            workspace_id: &self.workspace_id,
-        };
-        let now = if let Some(timestamp) = self.settings.commit_timestamp() {
+            workspace_sparse_patterns: Some(&self.workspace_sparse_patterns),
+        }
+    }
+
+    /// Current time used to interpret relative dates. This respects the
+    /// timestamp override in the settings.
+    fn now(&self) -> chrono::DateTime<chrono::Local> {
+        if let Some(timestamp) = self.settings.commit_timestamp() {
             chrono::Local
                 .timestamp_millis_opt(timestamp.timestamp.0)
                 .unwrap()
         } else {
             chrono::Local::now()
+        }
+    }
+
+    /// Parses an absolute or relative date given on the command line.
+    pub fn parse_timestamp(&self, date_str: &str) -> Result<Timestamp, CommandError> {
+        time_util::parse_timestamp(date_str, self.now()).map_err(|err| {
+            user_error_with_message(format!("Failed to parse date `{date_str}`"), err)
+        })
+    }
+
+    pub(crate) fn revset_parse_context(&self) -> RevsetParseContext {
+        let workspace_context = RevsetWorkspaceContext {
+            path_converter: &self.path_converter,
+            workspace_id: &self.workspace_id,
+            workspace_sparse_patterns: Some(&self.workspace_sparse_patterns),
         };
         RevsetParseContext::new(
             &self.revset_aliases_map,
             self.settings.user_email(),
-            now.into(),
+            self.now().into(),
             self.command.revset_extensions(),
             Some(workspace_context),
         )
@@ -1002,6 +1098,12 @@ impl WorkspaceCommandEnvironment {
     pub fn operation_template_extensions(&self) -> &[Arc<dyn OperationTemplateLanguageExtension>] {
         &self.command.data.operation_template_extensions
     }
+
+    /// Notes attached to operations, which are shared by all operation
+    /// templates of the command.
+    pub fn operation_annotations(&self) -> &OperationAnnotations {
+        &self.command.data.operation_annotations
+    }
 }
 
 /// Provides utilities for writing a command that works on a [`Workspace`]
@@ -1050,8 +1152,18 @@ impl WorkspaceCommandHelper {
         let settings = workspace.settings();
         let commit_summary_template_text = settings.get_string("templates.commit_summary")?;
         let op_summary_template_text = settings.get_string("templates.op_summary")?;
-        let may_update_working_copy =
-            loaded_at_head && !env.command.global_args().ignore_working_copy;
+        let global_args = env.command.global_args();
+        let may_update_working_copy = loaded_at_head
+            && !global_args.ignore_working_copy
+            && global_args.at_workspace.is_none();
+        if global_args.at_workspace.is_some()
+            && repo.view().get_wc_commit_id(env.workspace_id()).is_none()
+        {
+            return Err(user_error(format!(
+                "No such workspace: {}",
+                env.workspace_id().as_str()
+            )));
+        }
         let working_copy_shared_with_git =
             crate::git_util::is_colocated_git_workspace(&workspace, &repo);
 
@@ -1081,8 +1193,11 @@ impl WorkspaceCommandHelper {
         if self.may_update_working_copy {
             Ok(())
         } else {
-            let hint = if self.env.command.global_args().ignore_working_copy {
+            let global_args = self.env.command.global_args();
+            let hint = if global_args.ignore_working_copy {
                 "Don't use --ignore-working-copy."
+            } else if global_args.at_workspace.is_some() {
+                "Don't use --at-workspace."
             } else {
                 "Don't use --at-op."
             };
@@ -1131,6 +1246,40 @@ impl WorkspaceCommandHelper {
         Ok(())
     }
 
+    /// Detects changes in the working copy without snapshotting them, so no
+    /// operation is created. The changes will be recorded by the next command
+    /// that snapshots the working copy.
+    ///
+    /// Returns `None` if the changes can't be detected this way, e.g. because
+    /// the working copy isn't in sync with the working-copy commit. The working
+    /// copy should be snapshotted as usual in that case.
+    #[instrument(skip_all)]
+    pub fn detect_working_copy_changes(
+        &self,
+        ui: &Ui,
+    ) -> Result<Option<DetectedChanges>, CommandError> {
+        if !self.may_update_working_copy {
+            return Ok(None);
+        }
+        let Some(wc_commit_id) = self.get_wc_commit_id() else {
+            return Ok(None);
+        };
+        let wc_commit = self.repo().store().get_commit(wc_commit_id)?;
+        let Some(wc) = self
+            .working_copy()
+            .as_any()
+            .downcast_ref::<LocalWorkingCopy>()
+        else {
+            return Ok(None);
+        };
+        if wc.tree_id()? != wc_commit.tree_id() {
+            return Ok(None);
+        }
+        let auto_tracking_matcher = self.auto_tracking_matcher(ui)?;
+        let options = self.snapshot_options_with_start_tracking_matcher(&auto_tracking_matcher)?;
+        Ok(Some(wc.detect_changes(&options)?))
+    }
+
     /// Imports new HEAD from the colocated Git repo.
     ///
     /// If the Git HEAD has changed, this function checks out the new Git HEAD.
@@ -1141,8 +1290,9 @@ impl WorkspaceCommandHelper {
     #[instrument(skip_all)]
     fn import_git_head(&mut self, ui: &Ui) -> Result<(), CommandError> {
         assert!(self.may_update_working_copy);
+        let git_settings = self.settings().git_settings()?;
         let mut tx = self.start_transaction();
-        jj_lib::git::import_head(tx.repo_mut())?;
+        jj_lib::git::import_head(tx.repo_mut(), &git_settings)?;
         if !tx.repo().has_changes() {
             return Ok(());
         }
@@ -1209,7 +1359,10 @@ impl WorkspaceCommandHelper {
 
         let mut tx = tx.into_inner();
         // Rebase here to show slightly different status message.
-        let num_rebased = tx.repo_mut().rebase_descendants()?;
+        let num_rebased = {
+            let _progress = signing_progress(ui, tx.repo().store());
+            tx.repo_mut().rebase_descendants()?
+        };
         if num_rebased > 0 {
             writeln!(
                 ui.status(),
@@ -1247,6 +1400,7 @@ impl WorkspaceCommandHelper {
     pub fn checkout_options(&self) -> CheckoutOptions {
         CheckoutOptions {
             conflict_marker_style: self.env.conflict_marker_style(),
+            undo_stash_retention: self.env.undo_stash_retention,
         }
     }
 
@@ -1313,8 +1467,10 @@ to the current parents may contain changes from multiple commits.
         self.workspace.workspace_root()
     }
 
+    /// Returns the id of the workspace to operate on, which may be different
+    /// from the loaded workspace if `--at-workspace` is specified.
     pub fn workspace_id(&self) -> &WorkspaceId {
-        self.workspace.workspace_id()
+        self.env.workspace_id()
     }
 
     pub fn get_wc_commit_id(&self) -> Option<&CommitId> {
@@ -1358,12 +1514,18 @@ to the current parents may contain changes from multiple commits.
         file_args: &[String], // TODO: introduce FileArg newtype?
     ) -> Result<FilesetExpression, CommandError> {
         let mut diagnostics = FilesetDiagnostics::new();
+        let context = self.env.fileset_parse_context();
         let expressions: Vec<_> = file_args
             .iter()
-            .map(|arg| fileset::parse_maybe_bare(&mut diagnostics, arg, self.path_converter()))
+            .map(|arg| fileset::parse_maybe_bare(&mut diagnostics, arg, &context))
             .try_collect()?;
         print_parse_diagnostics(ui, "In fileset expression", &diagnostics)?;
-        Ok(FilesetExpression::union_all(expressions))
+        let expression = FilesetExpression::union_all(expressions);
+        if self.settings().get_bool("fileset.case-insensitive-globs")? {
+            Ok(expression.to_case_insensitive_globs())
+        } else {
+            Ok(expression)
+        }
     }
 
     pub fn auto_tracking_matcher(&self, ui: &Ui) -> Result<Box<dyn Matcher>, CommandError> {
@@ -1372,10 +1534,10 @@ to the current parents may contain changes from multiple commits.
         let expression = fileset::parse(
             &mut diagnostics,
             &pattern,
-            &RepoPathUiConverter::Fs {
+            &FilesetParseContext::new(&RepoPathUiConverter::Fs {
                 cwd: "".into(),
                 base: "".into(),
-            },
+            }),
         )?;
         print_parse_diagnostics(ui, "In `snapshot.auto-track`", &diagnostics)?;
         Ok(expression.to_matcher())
@@ -1394,6 +1556,7 @@ to the current parents may contain changes from multiple commits.
             max_new_file_size = u64::MAX;
         }
         let conflict_marker_style = self.env.conflict_marker_style();
+        let nested_repository_policy = self.settings().get("snapshot.nested-repositories")?;
         Ok(SnapshotOptions {
             base_ignores,
             fsmonitor_settings,
@@ -1401,6 +1564,7 @@ to the current parents may contain changes from multiple commits.
             start_tracking_matcher,
             max_new_file_size,
             conflict_marker_style,
+            nested_repository_policy,
         })
     }
 
@@ -1408,14 +1572,47 @@ to the current parents may contain changes from multiple commits.
         self.env.path_converter()
     }
 
-    #[cfg(not(feature = "git"))]
-    pub fn base_ignores(&self) -> Result<Arc<GitIgnoreFile>, GitIgnoreError> {
-        Ok(GitIgnoreFile::empty())
+    /// Loads the ignore patterns that apply to the whole workspace.
+    ///
+    /// In increasing order of precedence, the patterns are read from Git's
+    /// `core.excludesFile`, the `snapshot.excludes-file` setting, Git's
+    /// `info/exclude` file, and the workspace's `.jj/exclude` file. The
+    /// per-directory `.gitignore` and `.ignore` files take precedence over all
+    /// of these.
+    #[instrument(skip_all)]
+    pub fn base_ignores(&self) -> Result<Arc<GitIgnoreFile>, CommandError> {
+        let mut ignores = GitIgnoreFile::empty();
+        #[cfg(feature = "git")]
+        let git_backend = jj_lib::git::get_git_backend(self.repo().store()).ok();
+        #[cfg(feature = "git")]
+        if let Some(excludes_file_path) = self.git_excludes_file_path(git_backend) {
+            ignores = ignores.chain_with_file("", excludes_file_path)?;
+        }
+        if let Some(path) = self
+            .settings()
+            .get_string("snapshot.excludes-file")
+            .optional()?
+        {
+            let path = self
+                .workspace_root()
+                .join(jj_lib::file_util::expand_home_path(&path));
+            ignores = ignores.chain_with_file("", path)?;
+        }
+        #[cfg(feature = "git")]
+        if let Some(git_backend) = git_backend {
+            ignores = ignores
+                .chain_with_file("", git_backend.git_repo_path().join("info").join("exclude"))?;
+        }
+        ignores = ignores.chain_with_file("", self.workspace_root().join(".jj").join("exclude"))?;
+        Ok(ignores)
     }
 
+    /// Returns the path to Git's global excludes file.
     #[cfg(feature = "git")]
-    #[instrument(skip_all)]
-    pub fn base_ignores(&self) -> Result<Arc<GitIgnoreFile>, GitIgnoreError> {
+    fn git_excludes_file_path(
+        &self,
+        git_backend: Option<&jj_lib::git_backend::GitBackend>,
+    ) -> Option<PathBuf> {
         let get_excludes_file_path = |config: &gix::config::File| -> Option<PathBuf> {
             // TODO: maybe use path() and interpolate(), which can process non-utf-8
             // path on Unix.
@@ -1440,20 +1637,12 @@ to the current parents may contain changes from multiple commits.
             std::env::var("HOME").map(|x| Path::new(&x).join(".config"))
         }
 
-        let mut git_ignores = GitIgnoreFile::empty();
-        if let Ok(git_backend) = jj_lib::git::get_git_backend(self.repo().store()) {
-            let git_repo = git_backend.git_repo();
-            if let Some(excludes_file_path) = get_excludes_file_path(&git_repo.config_snapshot()) {
-                git_ignores = git_ignores.chain_with_file("", excludes_file_path)?;
-            }
-            git_ignores = git_ignores
-                .chain_with_file("", git_backend.git_repo_path().join("info").join("exclude"))?;
-        } else if let Ok(git_config) = gix::config::File::from_globals() {
-            if let Some(excludes_file_path) = get_excludes_file_path(&git_config) {
-                git_ignores = git_ignores.chain_with_file("", excludes_file_path)?;
-            }
+        if let Some(git_backend) = git_backend {
+            get_excludes_file_path(&git_backend.git_repo().config_snapshot())
+        } else {
+            let git_config = gix::config::File::from_globals().ok()?;
+            get_excludes_file_path(&git_config)
         }
-        Ok(git_ignores)
     }
 
     /// Creates textual diff renderer of the specified `formats`.
@@ -1472,7 +1661,9 @@ to the current parents may contain changes from multiple commits.
         args: &DiffFormatArgs,
     ) -> Result<DiffRenderer<'_>, CommandError> {
         let formats = diff_util::diff_formats_for(self.settings(), args)?;
-        Ok(self.diff_renderer(formats))
+        Ok(self
+            .diff_renderer(formats)
+            .with_similarity_thresholds(SimilarityThresholds::from_args(args)))
     }
 
     /// Loads textual diff renderer from the settings and log-like command
@@ -1484,7 +1675,10 @@ to the current parents may contain changes from multiple commits.
         patch: bool,
     ) -> Result<Option<DiffRenderer<'_>>, CommandError> {
         let formats = diff_util::diff_formats_for_log(self.settings(), args, patch)?;
-        Ok((!formats.is_empty()).then(|| self.diff_renderer(formats)))
+        Ok((!formats.is_empty()).then(|| {
+            self.diff_renderer(formats)
+                .with_similarity_thresholds(SimilarityThresholds::from_args(args))
+        }))
     }
 
     /// Loads diff editor from the settings.
@@ -1770,6 +1964,7 @@ to the current parents may contain changes from multiple commits.
         OperationTemplateLanguage::new(
             self.workspace.repo_loader(),
             Some(self.repo().op_id()),
+            self.env.operation_annotations(),
             self.env.operation_template_extensions(),
         )
     }
@@ -2068,7 +2263,10 @@ See https://jj-vcs.github.io/jj/latest/working-copy/#stale-working-copy \
             writeln!(ui.status(), "Nothing changed.")?;
             return Ok(());
         }
-        let num_rebased = tx.repo_mut().rebase_descendants()?;
+        let num_rebased = {
+            let _progress = signing_progress(ui, tx.repo().store());
+            tx.repo_mut().rebase_descendants()?
+        };
         if num_rebased > 0 {
             writeln!(ui.status(), "Rebased {num_rebased} descendant commits")?;
         }
@@ -2108,7 +2306,15 @@ See https://jj-vcs.github.io/jj/latest/working-copy/#stale-working-copy \
 
         #[cfg(feature = "git")]
         if self.working_copy_shared_with_git {
-            if let Some(wc_commit) = &maybe_new_wc_commit {
+            // Git HEAD belongs to the loaded workspace, which may be different
+            // from the workspace specified by --at-workspace.
+            let maybe_git_wc_commit = tx
+                .repo()
+                .view()
+                .get_wc_commit_id(self.workspace.workspace_id())
+                .map(|commit_id| tx.repo().store().get_commit(commit_id))
+                .transpose()?;
+            if let Some(wc_commit) = &maybe_git_wc_commit {
                 jj_lib::git::reset_head(tx.repo_mut(), wc_commit)?;
             }
             let refs = jj_lib::git::export_refs(tx.repo_mut())?;
@@ -2401,6 +2607,11 @@ impl WorkspaceCommandTransaction<'_> {
         self.tx.repo_mut()
     }
 
+    /// Records a key-value pair in the metadata of the operation.
+    pub fn set_tag(&mut self, key: String, value: String) {
+        self.tx.set_tag(key, value);
+    }
+
     pub fn check_out(&mut self, commit: &Commit) -> Result<Commit, CheckOutCommitError> {
         let workspace_id = self.helper.workspace_id().to_owned();
         self.id_prefix_context.take(); // invalidate
@@ -2533,6 +2744,21 @@ jj git init --colocate",
             err @ (StoreLoadError::ReadError { .. } | StoreLoadError::Backend(_)),
         ) => internal_error_with_message("The repository appears broken or inaccessible", err),
         WorkspaceLoadError::StoreLoadError(StoreLoadError::Signing(err)) => user_error(err),
+        WorkspaceLoadError::StoreLoadError(StoreLoadError::FormatVersion(
+            err @ FormatVersionError::Outdated { .. },
+        )) => user_error_with_hint(
+            err,
+            "Run `jj util upgrade-repo` to upgrade the repository to the current format.",
+        ),
+        WorkspaceLoadError::StoreLoadError(StoreLoadError::FormatVersion(
+            err @ FormatVersionError::TooNew { .. },
+        )) => user_error_with_hint(
+            err,
+            "The repository was written by a newer version of jj. Upgrade jj to use it.",
+        ),
+        WorkspaceLoadError::StoreLoadError(StoreLoadError::FormatVersion(
+            err @ (FormatVersionError::Read { .. } | FormatVersionError::Invalid { .. }),
+        )) => internal_error_with_message("The repository appears broken or inaccessible", err),
         WorkspaceLoadError::WorkingCopyState(err) => internal_error(err),
         WorkspaceLoadError::NonUnicodePath | WorkspaceLoadError::Path(_) => user_error(err),
     }
@@ -2954,7 +3180,7 @@ impl LogContentFormat {
         content_fn: impl FnOnce(&mut dyn Formatter) -> Result<(), E>,
     ) -> Result<(), E> {
         if self.word_wrap {
-            let mut recorder = FormatRecorder::new();
+            let mut recorder = FormatRecorder::new(formatter.maybe_color());
             content_fn(&mut recorder)?;
             text_util::write_wrapped(formatter, &recorder, self.width)?;
         } else {
@@ -3239,9 +3465,34 @@ pub struct GlobalArgs {
         add = ArgValueCandidates::new(complete::operations),
     )]
     pub at_operation: Option<String>,
+    /// Workspace whose working-copy commit to operate on
+    ///
+    /// By default, Jujutsu operates on the working-copy commit of the current
+    /// workspace. With `--at-workspace`, `@` refers to the working-copy commit
+    /// of the given workspace instead, and commands that move `@` check out
+    /// the new commit in that workspace. File paths are still resolved
+    /// relative to the current directory.
+    ///
+    /// No working copy is snapshotted or updated, as if
+    /// `--ignore-working-copy` had been specified. The files in the other
+    /// workspace are updated the next time a command is run in it.
+    #[arg(
+        long,
+        value_name = "WORKSPACE",
+        global = true,
+        add = ArgValueCandidates::new(complete::workspaces),
+    )]
+    pub at_workspace: Option<String>,
     /// Enable debug logging
     #[arg(long, global = true)]
     pub debug: bool,
+    /// Write a performance trace of the command to this file
+    ///
+    /// The trace is written in the Chrome trace event format, which can be
+    /// viewed in `chrome://tracing` or https://ui.perfetto.dev/. Attach it to
+    /// bug reports about slow commands.
+    #[arg(long, global = true, value_name = "FILE", value_hint = clap::ValueHint::FilePath)]
+    pub trace_output: Option<PathBuf>,
 
     #[command(flatten)]
     pub early_args: EarlyArgs,
@@ -3514,6 +3765,16 @@ fn parse_early_args(
     if args.no_pager.unwrap_or_default() {
         layer.set_value("ui.paginate", "never").unwrap();
     }
+    // `--keep-timestamps` of the rewrite commands
+    let mut command_matches = &early_matches;
+    while let Some((_, matches)) = command_matches.subcommand() {
+        command_matches = matches;
+    }
+    if let Ok(Some(&true)) = command_matches.try_get_one::<bool>("keep_timestamps") {
+        layer
+            .set_value("rewrite.update-committer-timestamp", false)
+            .unwrap();
+    }
     if !layer.is_empty() {
         config_layers.push(layer);
     }
@@ -3858,6 +4119,9 @@ impl CliRunner {
             // TODO: set up debug logging as early as possible
             self.tracing_subscription.enable_debug_logging()?;
         }
+        if let Some(path) = &args.global_args.trace_output {
+            self.tracing_subscription.enable_chrome_tracing(path)?;
+        }
         for process_global_args_fn in self.process_global_args_fns {
             process_global_args_fn(ui, &matches)?;
         }
@@ -3907,6 +4171,7 @@ impl CliRunner {
             revset_extensions: self.revset_extensions.into(),
             commit_template_extensions: self.commit_template_extensions,
             operation_template_extensions: self.operation_template_extensions,
+            operation_annotations: OperationAnnotations::new(),
             maybe_workspace_loader,
             store_factories: self.store_factories,
             working_copy_factories: self.working_copy_factories,
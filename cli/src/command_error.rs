@@ -32,7 +32,9 @@ use jj_lib::dsl_util::Diagnostics;
 use jj_lib::fileset::FilePatternParseError;
 use jj_lib::fileset::FilesetParseError;
 use jj_lib::fileset::FilesetParseErrorKind;
+use jj_lib::gc::GcError;
 use jj_lib::gitignore::GitIgnoreError;
+use jj_lib::local_working_copy::TreeStateError;
 use jj_lib::op_heads_store::OpHeadResolutionError;
 use jj_lib::op_heads_store::OpHeadsStoreError;
 use jj_lib::op_store::OpStoreError;
@@ -134,7 +136,7 @@ impl CommandError {
         &mut self,
         write: impl FnOnce(&mut dyn Formatter) -> io::Result<()>,
     ) {
-        let mut formatter = FormatRecorder::new();
+        let mut formatter = FormatRecorder::new(false);
         write(&mut formatter).expect("write() to FormatRecorder should never fail");
         self.add_formatted_hint(formatter);
     }
@@ -270,6 +272,10 @@ impl From<ConfigLoadError> for CommandError {
             ConfigLoadError::Parse { source_path, .. } => source_path
                 .as_ref()
                 .map(|path| format!("Check the config file: {}", path.display())),
+            ConfigLoadError::InvalidInclude { source_path } => {
+                Some(format!("Check the config file: {}", source_path.display()))
+            }
+            ConfigLoadError::IncludeCycle { .. } => None,
         };
         let mut cmd_err = config_error(err);
         cmd_err.extend_hints(hint);
@@ -317,6 +323,9 @@ impl From<BackendError> for CommandError {
     fn from(err: BackendError) -> Self {
         match &err {
             BackendError::Unsupported(_) => user_error(err),
+            _ if err.is_retriable() => {
+                user_error(err).hinted("The backend may be reachable again if you retry later.")
+            }
             _ => internal_error_with_message("Unexpected error from backend", err),
         }
     }
@@ -385,7 +394,27 @@ impl From<OpsetEvaluationError> for CommandError {
 
 impl From<SnapshotError> for CommandError {
     fn from(err: SnapshotError) -> Self {
-        internal_error_with_message("Failed to snapshot the working copy", err)
+        if let SnapshotError::NestedRepository { .. } = &err {
+            let hint = "Add the directory to .gitignore, or set `snapshot.nested-repositories` to \
+                        `ignore` or `submodule`";
+            return user_error_with_message("Failed to snapshot the working copy", err)
+                .hinted(hint);
+        }
+        let hint = find_corrupted_tree_state_hint(&err);
+        let mut cmd_err = internal_error_with_message("Failed to snapshot the working copy", err);
+        cmd_err.extend_hints(hint);
+        cmd_err
+    }
+}
+
+impl From<GcError> for CommandError {
+    fn from(err: GcError) -> Self {
+        match err {
+            GcError::Lock(_) => internal_error(err),
+            GcError::RepoLoader(err) => err.into(),
+            GcError::OpStore(err) => err.into(),
+            GcError::Backend(err) => err.into(),
+        }
     }
 }
 
@@ -481,10 +510,12 @@ impl From<TempTextEditError> for CommandError {
 #[cfg(feature = "git")]
 mod git {
     use jj_lib::git::GitConfigParseError;
+    use jj_lib::git::GitDeepenShallowError;
     use jj_lib::git::GitExportError;
     use jj_lib::git::GitFetchError;
     use jj_lib::git::GitFetchPrepareError;
     use jj_lib::git::GitImportError;
+    use jj_lib::git::GitPrefetchError;
     use jj_lib::git::GitPushError;
     use jj_lib::git::GitRemoteManagementError;
     use jj_lib::git::UnexpectedGitBackendError;
@@ -505,6 +536,7 @@ jj currently does not support partial clones. To use jj with this repository, tr
                 GitImportError::InternalBackend(_) => None,
                 GitImportError::InternalGitError(_) => None,
                 GitImportError::UnexpectedBackend(_) => None,
+                GitImportError::UnsupportedRefStorage(_) => Some(REF_STORAGE_HINT.to_string()),
             };
             let mut cmd_err =
                 user_error_with_message("Failed to import refs from underlying Git repo", err);
@@ -515,10 +547,20 @@ jj currently does not support partial clones. To use jj with this repository, tr
 
     impl From<GitExportError> for CommandError {
         fn from(err: GitExportError) -> Self {
+            if let GitExportError::UnsupportedRefStorage(_) = &err {
+                return user_error_with_message(
+                    "Failed to export refs to underlying Git repo",
+                    err,
+                )
+                .hinted(REF_STORAGE_HINT);
+            }
             internal_error_with_message("Failed to export refs to underlying Git repo", err)
         }
     }
 
+    const REF_STORAGE_HINT: &str = "jj can only access refs stored in the \"files\" format. Run \
+                                    `git refs migrate --ref-format=files` to convert the repository.";
+
     impl From<GitFetchError> for CommandError {
         fn from(err: GitFetchError) -> Self {
             if let GitFetchError::InvalidBranchPattern(pattern) = &err {
@@ -538,6 +580,11 @@ jj currently does not support partial clones. To use jj with this repository, tr
                 GitFetchError::InvalidBranchPattern(_) => user_error(err),
                 GitFetchError::InternalGitError(err) => map_git2_error(err),
                 GitFetchError::Subprocess(_) => user_error(err),
+                GitFetchError::NativeHttp(_) => user_error(err),
+                GitFetchError::ConflictingShallowOptions => user_error(err),
+                GitFetchError::UnsupportedShallowOptions => {
+                    user_error_with_hint(err, "Set `git.subprocess = true` to use the Git CLI.")
+                }
             }
         }
     }
@@ -551,6 +598,15 @@ jj currently does not support partial clones. To use jj with this repository, tr
         }
     }
 
+    impl From<GitPrefetchError> for CommandError {
+        fn from(err: GitPrefetchError) -> Self {
+            match err {
+                GitPrefetchError::UnexpectedBackend(err) => err.into(),
+                GitPrefetchError::Fetch(err) => err.into(),
+            }
+        }
+    }
+
     impl From<GitPushError> for CommandError {
         fn from(err: GitPushError) -> Self {
             match err {
@@ -569,8 +625,28 @@ jj currently does not support partial clones. To use jj with this repository, tr
                      it to be, and push again.",
                 ),
                 GitPushError::RefUpdateRejected(_) => user_error(err),
+                GitPushError::RemoteRejected(refs) => {
+                    let lines = refs.iter().map(|(name, reason)| match reason {
+                        Some(reason) => format!("\n  {name}: {reason}"),
+                        None => format!("\n  {name}"),
+                    });
+                    user_error_with_hint(
+                        format!(
+                            "The remote refused to update some refs:{}",
+                            lines.collect::<String>()
+                        ),
+                        "The output from the remote above, if any, may contain more details.",
+                    )
+                }
+                GitPushError::PartiallyPushed { .. } => user_error_with_hint(
+                    err,
+                    "The chunks pushed so far have been recorded. Run `jj git push` again to \
+                     push the remaining chunks.",
+                ),
                 GitPushError::InternalGitError(err) => map_git2_error(err),
                 GitPushError::Subprocess(_) => user_error(err),
+                GitPushError::NativeHttp(_) => user_error(err),
+                GitPushError::Backend(err) => err.into(),
                 GitPushError::UnexpectedBackend(_) => user_error(err),
             }
         }
@@ -582,6 +658,20 @@ jj currently does not support partial clones. To use jj with this repository, tr
         }
     }
 
+    impl From<GitDeepenShallowError> for CommandError {
+        fn from(err: GitDeepenShallowError) -> Self {
+            match &err {
+                GitDeepenShallowError::UnsupportedIndex(_)
+                | GitDeepenShallowError::UnexpectedBackend(_) => user_error(err),
+                GitDeepenShallowError::Index(_)
+                | GitDeepenShallowError::Backend(_)
+                | GitDeepenShallowError::RepoLoader(_) => {
+                    internal_error_with_message("Failed to update the deepened repository", err)
+                }
+            }
+        }
+    }
+
     impl From<GitConfigParseError> for CommandError {
         fn from(err: GitConfigParseError) -> Self {
             internal_error_with_message("Failed to parse Git config", err)
@@ -697,7 +787,10 @@ impl From<clap::Error> for CommandError {
 
 impl From<WorkingCopyStateError> for CommandError {
     fn from(err: WorkingCopyStateError) -> Self {
-        internal_error_with_message("Failed to access working copy state", err)
+        let hint = find_corrupted_tree_state_hint(&err);
+        let mut cmd_err = internal_error_with_message("Failed to access working copy state", err);
+        cmd_err.extend_hints(hint);
+        cmd_err
     }
 }
 
@@ -722,6 +815,21 @@ impl From<AbsorbError> for CommandError {
     }
 }
 
+fn find_corrupted_tree_state_hint(err: &(dyn error::Error + 'static)) -> Option<String> {
+    iter::successors(Some(err), |&err| err.source())
+        .any(|err| {
+            matches!(
+                err.downcast_ref(),
+                Some(TreeStateError::DecodeTreeState { .. })
+            )
+        })
+        .then(|| {
+            "The working copy state file may be corrupted. Run `jj workspace repair` to rebuild \
+             it from the working-copy commit."
+                .to_owned()
+        })
+}
+
 fn find_source_parse_error_hint(err: &dyn error::Error) -> Option<String> {
     let source = err.source()?;
     if let Some(source) = source.downcast_ref() {
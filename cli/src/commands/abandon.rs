@@ -29,6 +29,7 @@ use crate::cli_util::CommandHelper;
 use crate::cli_util::RevisionArg;
 use crate::command_error::CommandError;
 use crate::complete;
+use crate::git_util::back_up_hidden_commits;
 use crate::ui::Ui;
 
 /// Abandon a revision
@@ -166,6 +167,7 @@ pub(crate) fn cmd_abandon(
             to_abandon.len() - 1
         )
     };
+    back_up_hidden_commits(ui, &tx)?;
     tx.finish(ui, transaction_description)?;
     Ok(())
 }
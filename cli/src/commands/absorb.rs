@@ -35,7 +35,8 @@ use crate::ui::Ui;
 /// change will be left in the source revision.
 ///
 /// The source revision will be abandoned if all changes are absorbed into the
-/// destination revisions, and if the source revision has no description.
+/// destination revisions, and if the source revision has no description. This
+/// can be changed by the `rewrite.drop-empty` setting.
 ///
 /// The modification made by `jj absorb` can be reviewed by `jj op show -p`.
 #[derive(clap::Args, Clone, Debug)]
@@ -93,7 +94,13 @@ pub(crate) fn cmd_absorb(
     workspace_command.check_rewritable(selected_trees.target_commits.keys())?;
 
     let mut tx = workspace_command.start_transaction();
-    let stats = absorb_hunks(tx.repo_mut(), &source, selected_trees.target_commits)?;
+    let drop_empty = tx.settings().drop_empty();
+    let stats = absorb_hunks(
+        tx.repo_mut(),
+        &source,
+        selected_trees.target_commits,
+        drop_empty,
+    )?;
 
     if let Some(mut formatter) = ui.status_formatter() {
         if !stats.rewritten_destinations.is_empty() {
@@ -13,7 +13,8 @@
 // limitations under the License.
 
 use std::collections::HashSet;
-use std::rc::Rc;
+use std::io::Write as _;
+use std::iter;
 
 use clap_complete::ArgValueCandidates;
 use itertools::Itertools;
@@ -27,6 +28,10 @@ use crate::command_error::CommandError;
 use crate::commit_templater::CommitTemplateLanguage;
 use crate::commit_templater::RefName;
 use crate::complete;
+use crate::formatter::FormatRecorder;
+use crate::ref_list_util;
+use crate::ref_list_util::RefListItem;
+use crate::text_util;
 use crate::ui::Ui;
 
 /// List bookmarks and their targets
@@ -107,6 +112,28 @@ pub struct BookmarkListArgs {
     ///     https://jj-vcs.github.io/jj/latest/templates/
     #[arg(long, short = 'T', add = ArgValueCandidates::new(complete::template_aliases))]
     template: Option<String>,
+
+    /// Show when each local bookmark was created and last moved, and by whom
+    ///
+    /// This uses the `templates.bookmark_list_verbose` template instead of
+    /// `templates.bookmark_list`. Bookmarks last changed before this
+    /// information was recorded are listed without it.
+    #[arg(long, conflicts_with = "template")]
+    verbose: bool,
+
+    /// Group bookmarks by `/`-separated namespace
+    ///
+    /// For example, `feature/foo` and `feature/bar` are listed under a
+    /// `feature/` heading, indented by the depth of the namespace.
+    #[arg(long)]
+    tree: bool,
+
+    /// Print each bookmark as a JSON object, one per line
+    ///
+    /// The remote bookmarks which would be listed along with a local bookmark
+    /// are nested in its `tracked` array.
+    #[arg(long, conflicts_with_all = ["template", "verbose", "tree"])]
+    json: bool,
 }
 
 pub fn cmd_bookmark_list(
@@ -153,6 +180,9 @@ pub fn cmd_bookmark_list(
         let language = workspace_command.commit_template_language();
         let text = match &args.template {
             Some(value) => value.to_owned(),
+            None if args.verbose => workspace_command
+                .settings()
+                .get("templates.bookmark_list_verbose")?,
             None => workspace_command
                 .settings()
                 .get("templates.bookmark_list")?,
@@ -193,7 +223,8 @@ pub fn cmd_bookmark_list(
             let primary = RefName::local(
                 name,
                 local_target.clone(),
-                remote_refs.iter().map(|&(_, remote_ref)| remote_ref),
+                remote_refs.iter().copied(),
+                view.get_bookmark_metadata(name).cloned(),
             );
             let tracked = tracking_remote_refs
                 .iter()
@@ -216,10 +247,37 @@ pub fn cmd_bookmark_list(
 
     ui.request_pager();
     let mut formatter = ui.stdout_formatter();
-    bookmark_list_items
-        .iter()
-        .flat_map(|item| itertools::chain([&item.primary], &item.tracked))
-        .try_for_each(|ref_name| template.format(ref_name, formatter.as_mut()))?;
+    if args.json {
+        ref_list_util::write_json_lines(formatter.as_mut(), &bookmark_list_items, repo.as_ref())?;
+    } else if args.tree {
+        // Bookmarks in the same namespace are adjacent since they are sorted
+        // by name.
+        let mut current_namespace: Vec<&str> = vec![];
+        for item in &bookmark_list_items {
+            let mut namespace = item.primary.name().split('/').collect_vec();
+            namespace.pop();
+            let common_depth = iter::zip(&current_namespace, &namespace)
+                .take_while(|(a, b)| a == b)
+                .count();
+            for (depth, component) in namespace.iter().enumerate().skip(common_depth) {
+                write!(formatter, "{}", "  ".repeat(depth))?;
+                writeln!(formatter.labeled("namespace"), "{component}/")?;
+            }
+            let indent = "  ".repeat(namespace.len());
+            current_namespace = namespace;
+            let mut recorder = FormatRecorder::new(formatter.maybe_color());
+            item.refs()
+                .try_for_each(|ref_name| template.format(ref_name, &mut recorder))?;
+            text_util::write_indented(formatter.as_mut(), &recorder, |formatter| {
+                write!(formatter, "{indent}")
+            })?;
+        }
+    } else {
+        bookmark_list_items
+            .iter()
+            .flat_map(|item| item.refs())
+            .try_for_each(|ref_name| template.format(ref_name, formatter.as_mut()))?;
+    }
     drop(formatter);
 
     #[cfg(feature = "git")]
@@ -257,11 +315,3 @@ pub fn cmd_bookmark_list(
 
     Ok(())
 }
-
-#[derive(Clone, Debug)]
-struct RefListItem {
-    /// Local bookmark or untracked remote bookmark.
-    primary: Rc<RefName>,
-    /// Remote bookmarks tracked by the primary (or local) bookmark.
-    tracked: Vec<Rc<RefName>>,
-}
@@ -118,7 +118,12 @@ pub fn cmd_bookmark_track(
             let ref_name = RefName::local(
                 name,
                 local_target.clone(),
-                bookmark_target.remote_refs.iter().map(|x| x.1),
+                bookmark_target.remote_refs.iter().copied(),
+                workspace_command
+                    .repo()
+                    .view()
+                    .get_bookmark_metadata(name)
+                    .cloned(),
             );
             template.format(&ref_name, formatter.as_mut())?;
 
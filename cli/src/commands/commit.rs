@@ -25,6 +25,7 @@ use crate::complete;
 use crate::description_util::description_template;
 use crate::description_util::edit_description;
 use crate::description_util::join_message_paragraphs;
+use crate::description_util::DescriptionChecks;
 use crate::text_util::parse_author;
 use crate::ui::Ui;
 
@@ -40,6 +41,9 @@ pub(crate) struct CommitArgs {
     /// The change description to use (don't open editor)
     #[arg(long = "message", short, value_name = "MESSAGE")]
     message_paragraphs: Vec<String>,
+    /// Don't check the description against the `commit.checks` rules
+    #[arg(long)]
+    no_verify: bool,
     /// Put these paths in the first commit
     #[arg(
         value_name = "FILESETS", 
@@ -67,6 +71,17 @@ pub(crate) struct CommitArgs {
         value_parser = parse_author
     )]
     author: Option<(String, String)>,
+    /// Set the author date
+    ///
+    /// Accepts RFC 3339 dates (e.g. `2024-01-02T03:04:05+06:00`) as well as
+    /// relative dates (e.g. `yesterday` or `2 days ago`).
+    #[arg(long, value_name = "DATE")]
+    author_date: Option<String>,
+    /// Set the committer date
+    ///
+    /// Accepts the same formats as `--author-date`.
+    #[arg(long, value_name = "DATE")]
+    committer_date: Option<String>,
 }
 
 #[instrument(skip_all)]
@@ -85,6 +100,16 @@ pub(crate) fn cmd_commit(
         .parse_file_patterns(ui, &args.paths)?
         .to_matcher();
     let advanceable_bookmarks = workspace_command.get_advanceable_bookmarks(commit.parent_ids())?;
+    let author_date = args
+        .author_date
+        .as_deref()
+        .map(|date| workspace_command.env().parse_timestamp(date))
+        .transpose()?;
+    let committer_date = args
+        .committer_date
+        .as_deref()
+        .map(|date| workspace_command.env().parse_timestamp(date))
+        .transpose()?;
     let diff_selector =
         workspace_command.diff_selector(ui, args.tool.as_deref(), args.interactive)?;
     let text_editor = workspace_command.text_editor()?;
@@ -129,6 +154,12 @@ new working-copy commit.
         };
         commit_builder.set_author(new_author);
     }
+    if let Some(timestamp) = author_date {
+        commit_builder.set_author_timestamp(timestamp);
+    }
+    if let Some(timestamp) = committer_date {
+        commit_builder.set_committer_timestamp(timestamp);
+    }
 
     let description = if !args.message_paragraphs.is_empty() {
         join_message_paragraphs(&args.message_paragraphs)
@@ -140,6 +171,7 @@ new working-copy commit.
         let template = description_template(ui, &tx, "", &temp_commit)?;
         edit_description(&text_editor, &template)?
     };
+    DescriptionChecks::from_settings(tx.settings())?.check(ui, &description, args.no_verify)?;
     commit_builder.set_description(description);
     let new_commit = commit_builder.write(tx.repo_mut())?;
 
@@ -16,7 +16,9 @@ use std::io::Write as _;
 
 use clap_complete::ArgValueCandidates;
 use jj_lib::config::ConfigNamePathBuf;
+use jj_lib::config::ConfigSource;
 use jj_lib::config::ConfigValue;
+use jj_lib::config::StackedConfig;
 use tracing::instrument;
 
 use crate::cli_util::CommandHelper;
@@ -33,11 +35,23 @@ use crate::ui::Ui;
 /// user.name="Martin von Zweigbergk"
 /// $ jj config get user.name
 /// Martin von Zweigbergk
+///
+/// With `--resolve-origin`, every layer that defines the option is printed
+/// along with its source file and line, followed by the effective value. This
+/// helps to find out why a setting is ignored. For example:
+///
+/// $ jj config get --resolve-origin user.name
+/// user /home/martinvonz/.config/jj/config.toml:2: "Martin von Zweigbergk" (overridden)
+/// command-arg: "Martin"
+/// effective: "Martin"
 #[derive(clap::Args, Clone, Debug)]
 #[command(verbatim_doc_comment)]
 pub struct ConfigGetArgs {
     #[arg(required = true, add = ArgValueCandidates::new(complete::leaf_config_keys))]
     name: ConfigNamePathBuf,
+    /// Print the layers defining the option, and the effective value
+    #[arg(long)]
+    resolve_origin: bool,
 }
 
 #[instrument(skip_all)]
@@ -46,6 +60,9 @@ pub fn cmd_config_get(
     command: &CommandHelper,
     args: &ConfigGetArgs,
 ) -> Result<(), CommandError> {
+    if args.resolve_origin {
+        return print_origins(ui, command.settings().config(), &args.name);
+    }
     let stringified = command
         .settings()
         .get_value_with(&args.name, |value| match value {
@@ -67,3 +84,50 @@ pub fn cmd_config_get(
     writeln!(ui.stdout(), "{stringified}")?;
     Ok(())
 }
+
+fn print_origins(
+    ui: &Ui,
+    config: &StackedConfig,
+    name: &ConfigNamePathBuf,
+) -> Result<(), CommandError> {
+    // Look up the effective value first to report error if there's no value.
+    let effective_value = config.get_value(name)?;
+    let mut formatter = ui.stdout_formatter();
+    for origin in config.look_up_origins(name) {
+        let layer = origin.layer;
+        write!(formatter, "{}", source_label(layer.source))?;
+        if let Some(path) = &layer.path {
+            write!(formatter, " {}", path.display())?;
+            if let Some(line) = layer.look_up_line(name) {
+                write!(formatter, ":{line}")?;
+            }
+        }
+        let value = origin
+            .item
+            .clone()
+            .into_value()
+            .expect("Item::None should not exist in loaded tables");
+        write!(formatter, ": {}", value.decorated("", ""))?;
+        if origin.is_overridden {
+            write!(formatter, " (overridden)")?;
+        }
+        writeln!(formatter)?;
+    }
+    writeln!(
+        formatter,
+        "effective: {}",
+        effective_value.decorated("", "")
+    )?;
+    Ok(())
+}
+
+fn source_label(source: ConfigSource) -> &'static str {
+    match source {
+        ConfigSource::Default => "default",
+        ConfigSource::EnvBase => "env-base",
+        ConfigSource::User => "user",
+        ConfigSource::Repo => "repo",
+        ConfigSource::EnvOverrides => "env-overrides",
+        ConfigSource::CommandArg => "command-arg",
+    }
+}
@@ -0,0 +1,127 @@
+// Copyright 2025 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::fmt::Debug;
+use std::io::Write as _;
+use std::time::Duration;
+use std::time::Instant;
+
+use futures::StreamExt as _;
+use futures::TryStreamExt as _;
+use itertools::Itertools as _;
+use jj_lib::copies::CopyRecords;
+use jj_lib::matchers::EverythingMatcher;
+use jj_lib::repo::Repo as _;
+use pollster::FutureExt as _;
+
+use crate::cli_util::CommandHelper;
+use crate::cli_util::RevisionArg;
+use crate::command_error::CommandError;
+use crate::diff_util::DiffStatOptions;
+use crate::diff_util::DiffStats;
+use crate::ui::Ui;
+
+/// Measure how long it takes to evaluate a revset and diff two trees
+///
+/// The revset is evaluated and iterated over, then the trees of the two
+/// revisions are diffed, first without and then with file contents. Each phase
+/// is repeated and the fastest run is reported.
+#[derive(clap::Args, Clone, Debug)]
+pub struct DebugDiffBenchArgs {
+    /// Revset to evaluate
+    #[arg(long, short, default_value = "::@")]
+    revisions: RevisionArg,
+    /// Diff the tree of this revision
+    #[arg(long, default_value = "@-")]
+    from: RevisionArg,
+    /// Diff against the tree of this revision
+    #[arg(long, default_value = "@")]
+    to: RevisionArg,
+    /// Number of times to repeat each phase
+    #[arg(long, default_value_t = 3, value_parser = clap::value_parser!(u32).range(1..))]
+    iterations: u32,
+}
+
+pub fn cmd_debug_diff_bench(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    args: &DebugDiffBenchArgs,
+) -> Result<(), CommandError> {
+    let workspace_command = command.workspace_helper(ui)?;
+    let store = workspace_command.repo().store();
+    let revset_expression = workspace_command.parse_revset(ui, &args.revisions)?;
+    let from_tree = workspace_command
+        .resolve_single_rev(ui, &args.from)?
+        .tree()?;
+    let to_tree = workspace_command.resolve_single_rev(ui, &args.to)?.tree()?;
+    let stat_options = DiffStatOptions::from_settings(workspace_command.settings())?;
+    let conflict_marker_style = workspace_command.env().conflict_marker_style();
+    let copy_records = CopyRecords::default();
+
+    let (revset_time, commit_count) = measure(args.iterations, || {
+        let revset = revset_expression.evaluate()?;
+        let count = revset.iter().process_results(|ids| ids.count())?;
+        Ok(count)
+    })?;
+    let (tree_diff_time, path_count) = measure(args.iterations, || {
+        let count = from_tree
+            .diff_stream(&to_tree, &EverythingMatcher)
+            .map(|entry| entry.values.map(|_| ()))
+            .try_fold(0, |count, ()| async move { Ok(count + 1) })
+            .block_on()?;
+        Ok(count)
+    })?;
+    let (content_diff_time, stats) = measure(args.iterations, || {
+        let tree_diff =
+            from_tree.diff_stream_with_copies(&to_tree, &EverythingMatcher, &copy_records);
+        let stats = DiffStats::calculate(store, tree_diff, &stat_options, conflict_marker_style)
+            .block_on()?;
+        Ok(stats)
+    })?;
+
+    let mut formatter = ui.stdout_formatter();
+    writeln!(formatter, "Revset evaluation: {commit_count} commits")?;
+    writeln!(formatter, "  time: {revset_time:.1?}")?;
+    writeln!(formatter, "Tree diff: {path_count} paths")?;
+    writeln!(formatter, "  time: {tree_diff_time:.1?}")?;
+    writeln!(
+        formatter,
+        "Content diff: {} files, {} insertions(+), {} deletions(-)",
+        stats.entries().len(),
+        stats.count_total_added(),
+        stats.count_total_removed()
+    )?;
+    writeln!(formatter, "  time: {content_diff_time:.1?}")?;
+    Ok(())
+}
+
+/// Runs `f` the given number of times, and returns the shortest duration
+/// along with the result of the last run.
+fn measure<T>(
+    iterations: u32,
+    mut f: impl FnMut() -> Result<T, CommandError>,
+) -> Result<(Duration, T), CommandError> {
+    let mut best_time = Duration::MAX;
+    let mut result = None;
+    for _ in 0..iterations {
+        let start = Instant::now();
+        let value = f()?;
+        best_time = best_time.min(start.elapsed());
+        result = Some(value);
+    }
+    Ok((
+        best_time,
+        result.expect("at least one iteration should have run"),
+    ))
+}
@@ -36,10 +36,10 @@ pub fn cmd_debug_fileset(
     args: &DebugFilesetArgs,
 ) -> Result<(), CommandError> {
     let workspace_command = command.workspace_helper(ui)?;
-    let path_converter = workspace_command.path_converter();
+    let context = workspace_command.env().fileset_parse_context();
 
     let mut diagnostics = FilesetDiagnostics::new();
-    let expression = fileset::parse_maybe_bare(&mut diagnostics, &args.path, path_converter)?;
+    let expression = fileset::parse_maybe_bare(&mut diagnostics, &args.path, &context)?;
     print_parse_diagnostics(ui, "In fileset expression", &diagnostics)?;
     writeln!(ui.stdout(), "-- Parsed:")?;
     writeln!(ui.stdout(), "{expression:#?}")?;
@@ -0,0 +1,217 @@
+// Copyright 2025 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::HashSet;
+use std::collections::VecDeque;
+use std::fmt::Debug;
+use std::io::Write as _;
+
+use jj_lib::backend::CommitId;
+use jj_lib::object_id::ObjectId as _;
+use jj_lib::op_walk;
+use jj_lib::repo::Repo as _;
+
+use crate::cli_util::CommandHelper;
+use crate::command_error::user_error;
+use crate::command_error::CommandError;
+use crate::ui::Ui;
+
+/// Check the repository for corruption
+///
+/// Verifies that all commits reachable from the view are readable from the
+/// backend and known to the index, that the operation log and the refs in the
+/// current view are intact, and that the working-copy state refers to valid
+/// objects.
+///
+/// Each problem is printed on its own line, prefixed by a stable code that can
+/// be matched by scripts, and followed by a suggested repair.
+#[derive(clap::Args, Clone, Debug)]
+pub struct DebugFsckArgs {}
+
+/// Inconsistency found by `jj debug fsck`.
+#[derive(Debug)]
+struct FsckProblem {
+    /// Machine-readable identifier of the kind of problem.
+    code: &'static str,
+    message: String,
+    repair: &'static str,
+}
+
+pub fn cmd_debug_fsck(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    _args: &DebugFsckArgs,
+) -> Result<(), CommandError> {
+    let workspace_command = command.workspace_helper_no_snapshot(ui)?;
+    let repo = workspace_command.repo();
+    let store = repo.store();
+    let index = repo.index();
+    let mut problems = vec![];
+
+    // Walk the whole history reachable from the view by reading the commits
+    // from the backend, so commits missing from the index are found. Check the
+    // extras table first since reading a commit would import missing entries.
+    #[cfg(feature = "git")]
+    let mut git_backend = jj_lib::git::get_git_backend(store).ok();
+    let mut visited: HashSet<CommitId> = HashSet::new();
+    let mut queue: VecDeque<CommitId> = repo.view().all_referenced_commit_ids().cloned().collect();
+    while let Some(commit_id) = queue.pop_front() {
+        if commit_id == *store.root_commit_id() || !visited.insert(commit_id.clone()) {
+            continue;
+        }
+        #[cfg(feature = "git")]
+        if let Some(backend) = git_backend {
+            match backend.has_extra_metadata(&commit_id) {
+                Ok(true) => {}
+                Ok(false) => problems.push(FsckProblem {
+                    code: "missing-extras",
+                    message: format!(
+                        "Commit {} has no entry in the extras table",
+                        commit_id.hex()
+                    ),
+                    repair: "Run `jj git import` to re-import the commit from Git",
+                }),
+                Err(err) => {
+                    problems.push(FsckProblem {
+                        code: "unreadable-extras",
+                        message: format!(
+                            "Extras of commit {} cannot be read: {err}",
+                            commit_id.hex()
+                        ),
+                        repair: "Restore the `.jj/repo/store/extra` directory from a backup",
+                    });
+                    // The same error would be reported for all other commits.
+                    git_backend = None;
+                }
+            }
+        }
+        let commit = match store.get_commit(&commit_id) {
+            Ok(commit) => commit,
+            Err(err) => {
+                problems.push(FsckProblem {
+                    code: "unreadable-commit",
+                    message: format!("Commit {} cannot be read: {err}", commit_id.hex()),
+                    repair: "Restore an operation from before the commit was lost with `jj op \
+                             restore`",
+                });
+                continue;
+            }
+        };
+        for parent_id in commit.parent_ids() {
+            if !index.has_id(parent_id) {
+                problems.push(FsckProblem {
+                    code: "unindexed-parent",
+                    message: format!(
+                        "Parent {} of commit {} is missing from the index",
+                        parent_id.hex(),
+                        commit_id.hex()
+                    ),
+                    repair: "Rebuild the index with `jj debug reindex`",
+                });
+            }
+            queue.push_back(parent_id.clone());
+        }
+        if let Err(err) = store.get_root_tree(commit.tree_id()) {
+            problems.push(FsckProblem {
+                code: "unreadable-tree",
+                message: format!("Tree of commit {} cannot be read: {err}", commit_id.hex()),
+                repair: "Restore an operation from before the tree was lost with `jj op restore`",
+            });
+        }
+    }
+
+    // Refs in the current view must point to indexed commits.
+    for commit_id in repo.view().all_referenced_commit_ids() {
+        if !index.has_id(commit_id) {
+            problems.push(FsckProblem {
+                code: "dangling-view-ref",
+                message: format!(
+                    "Commit {} referenced by the view is missing from the index",
+                    commit_id.hex()
+                ),
+                repair: "Rebuild the index with `jj debug reindex`, or restore an earlier \
+                         operation with `jj op restore`",
+            });
+        }
+    }
+
+    // Every operation in the log must have a readable view.
+    for op in op_walk::walk_ancestors(std::slice::from_ref(repo.operation())) {
+        match op {
+            Ok(op) => {
+                if let Err(err) = op.view() {
+                    problems.push(FsckProblem {
+                        code: "unreadable-view",
+                        message: format!("View of operation {} cannot be read: {err}", op.id()),
+                        repair: "Abandon the affected operations with `jj op abandon`",
+                    });
+                }
+            }
+            Err(err) => {
+                problems.push(FsckProblem {
+                    code: "unreadable-operation",
+                    message: format!("Operation cannot be read: {err}"),
+                    repair: "Abandon the affected operations with `jj op abandon`",
+                });
+                break;
+            }
+        }
+    }
+
+    // The working-copy state must refer to an existing operation and tree.
+    let wc = workspace_command.working_copy();
+    if let Err(err) = repo.op_store().read_operation(wc.operation_id()) {
+        problems.push(FsckProblem {
+            code: "wc-unknown-operation",
+            message: format!(
+                "Working copy refers to operation {} which cannot be read: {err}",
+                wc.operation_id()
+            ),
+            repair: "Run `jj workspace update-stale` to reset the working-copy state",
+        });
+    }
+    match wc.tree_id() {
+        Ok(tree_id) => {
+            if let Err(err) = store.get_root_tree(tree_id) {
+                problems.push(FsckProblem {
+                    code: "wc-unreadable-tree",
+                    message: format!("Working-copy tree cannot be read: {err}"),
+                    repair: "Run `jj workspace update-stale` to reset the working-copy state",
+                });
+            }
+        }
+        Err(err) => {
+            problems.push(FsckProblem {
+                code: "wc-unreadable-state",
+                message: format!("Working-copy state cannot be read: {err}"),
+                repair: "Run `jj workspace update-stale` to reset the working-copy state",
+            });
+        }
+    }
+
+    if problems.is_empty() {
+        writeln!(ui.stdout(), "No problems found.")?;
+        return Ok(());
+    }
+    for problem in &problems {
+        writeln!(ui.stdout(), "{}: {}", problem.code, problem.message)?;
+        writeln!(ui.stdout(), "  repair: {}", problem.repair)?;
+    }
+    let noun = if problems.len() == 1 {
+        "problem"
+    } else {
+        "problems"
+    };
+    Err(user_error(format!("Found {} {noun}", problems.len())))
+}
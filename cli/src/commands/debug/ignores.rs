@@ -0,0 +1,111 @@
+// Copyright 2025 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::fmt::Debug;
+use std::io::Write as _;
+
+use jj_lib::gitignore::IgnoreMatch;
+use jj_lib::repo_path::RepoPath;
+
+use crate::cli_util::CommandHelper;
+use crate::command_error::CommandError;
+use crate::ui::Ui;
+
+/// Explain why paths are ignored
+///
+/// Ignore patterns are read from the following sources, in increasing order
+/// of precedence: Git's `core.excludesFile`, the `snapshot.excludes-file`
+/// setting, Git's `info/exclude` file, the workspace's `.jj/exclude` file, and
+/// the `.gitignore` and `.ignore` files in each directory.
+///
+/// Files that are already tracked are not affected by ignore patterns.
+#[derive(clap::Args, Clone, Debug)]
+pub struct DebugIgnoresArgs {
+    #[arg(required = true, value_hint = clap::ValueHint::AnyPath)]
+    paths: Vec<String>,
+}
+
+pub fn cmd_debug_ignores(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    args: &DebugIgnoresArgs,
+) -> Result<(), CommandError> {
+    let workspace_command = command.workspace_helper(ui)?;
+    let workspace_root = workspace_command.workspace_root();
+    let base_ignores = workspace_command.base_ignores()?;
+    for input in &args.paths {
+        let path = workspace_command.parse_file_path(input)?;
+        let mut ignores = base_ignores.clone();
+        let mut ignored_dir = None;
+        // Contents of an ignored directory are ignored regardless of the
+        // patterns in the directory, so stop at the first ignored ancestor.
+        let ancestors = path
+            .components()
+            .scan(RepoPath::root().to_owned(), |dir, name| {
+                let parent = dir.clone();
+                *dir = dir.join(name);
+                Some(parent)
+            });
+        for dir in ancestors {
+            if !dir.is_root() {
+                let matched = ignores.explain(&dir.to_internal_dir_string());
+                if let Some(matched) = matched.filter(|matched| matched.is_ignored) {
+                    ignored_dir = Some((dir, matched));
+                    break;
+                }
+            }
+            ignores = ignores.chain_with_dir(
+                &dir.to_internal_dir_string(),
+                &dir.to_fs_path_unchecked(workspace_root),
+            )?;
+        }
+
+        let formatted_path = workspace_command.format_file_path(&path);
+        if let Some((dir, matched)) = ignored_dir {
+            let formatted_dir = workspace_command.format_file_path(&dir);
+            write!(
+                ui.stdout(),
+                "{formatted_path}: ignored because {formatted_dir} is ignored: "
+            )?;
+            write_match(ui, &matched)?;
+            continue;
+        }
+        let mut internal_path = path.as_internal_file_string().to_owned();
+        if path.to_fs_path_unchecked(workspace_root).is_dir() {
+            internal_path.push('/');
+        }
+        match ignores.explain(&internal_path) {
+            Some(matched) if matched.is_ignored => {
+                write!(ui.stdout(), "{formatted_path}: ignored: ")?;
+                write_match(ui, &matched)?;
+            }
+            Some(matched) => {
+                write!(ui.stdout(), "{formatted_path}: not ignored: ")?;
+                write_match(ui, &matched)?;
+            }
+            None => writeln!(ui.stdout(), "{formatted_path}: not ignored")?,
+        }
+    }
+    Ok(())
+}
+
+fn write_match(ui: &Ui, matched: &IgnoreMatch) -> Result<(), CommandError> {
+    writeln!(
+        ui.stdout(),
+        "pattern {pattern:?} in {file}",
+        pattern = matched.pattern,
+        file = matched.ignore_file.display()
+    )?;
+    Ok(())
+}
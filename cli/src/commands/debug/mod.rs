@@ -13,7 +13,10 @@
 // limitations under the License.
 
 mod copy_detection;
+mod diff_bench;
 mod fileset;
+mod fsck;
+mod ignores;
 mod index;
 mod init_local;
 mod local_working_copy;
@@ -21,8 +24,10 @@ mod operation;
 mod reindex;
 mod revset;
 mod snapshot;
+mod snapshot_bench;
 mod template;
 mod tree;
+mod watch;
 mod watchman;
 mod working_copy;
 
@@ -34,8 +39,14 @@ use jj_lib::local_working_copy::LocalWorkingCopy;
 
 use self::copy_detection::cmd_debug_copy_detection;
 use self::copy_detection::CopyDetectionArgs;
+use self::diff_bench::cmd_debug_diff_bench;
+use self::diff_bench::DebugDiffBenchArgs;
 use self::fileset::cmd_debug_fileset;
 use self::fileset::DebugFilesetArgs;
+use self::fsck::cmd_debug_fsck;
+use self::fsck::DebugFsckArgs;
+use self::ignores::cmd_debug_ignores;
+use self::ignores::DebugIgnoresArgs;
 use self::index::cmd_debug_index;
 use self::index::DebugIndexArgs;
 use self::init_local::cmd_debug_init_local;
@@ -50,10 +61,14 @@ use self::revset::cmd_debug_revset;
 use self::revset::DebugRevsetArgs;
 use self::snapshot::cmd_debug_snapshot;
 use self::snapshot::DebugSnapshotArgs;
+use self::snapshot_bench::cmd_debug_snapshot_bench;
+use self::snapshot_bench::DebugSnapshotBenchArgs;
 use self::template::cmd_debug_template;
 use self::template::DebugTemplateArgs;
 use self::tree::cmd_debug_tree;
 use self::tree::DebugTreeArgs;
+use self::watch::cmd_debug_watch;
+use self::watch::DebugWatchArgs;
 use self::watchman::cmd_debug_watchman;
 use self::watchman::DebugWatchmanCommand;
 use self::working_copy::cmd_debug_working_copy;
@@ -68,7 +83,10 @@ use crate::ui::Ui;
 #[command(hide = true)]
 pub enum DebugCommand {
     CopyDetection(CopyDetectionArgs),
+    DiffBench(DebugDiffBenchArgs),
     Fileset(DebugFilesetArgs),
+    Fsck(DebugFsckArgs),
+    Ignores(DebugIgnoresArgs),
     Index(DebugIndexArgs),
     InitLocal(DebugInitLocalArgs),
     LocalWorkingCopy(DebugLocalWorkingCopyArgs),
@@ -77,8 +95,10 @@ pub enum DebugCommand {
     Reindex(DebugReindexArgs),
     Revset(DebugRevsetArgs),
     Snapshot(DebugSnapshotArgs),
+    SnapshotBench(DebugSnapshotBenchArgs),
     Template(DebugTemplateArgs),
     Tree(DebugTreeArgs),
+    Watch(DebugWatchArgs),
     #[command(subcommand)]
     Watchman(DebugWatchmanCommand),
     WorkingCopy(DebugWorkingCopyArgs),
@@ -91,7 +111,10 @@ pub fn cmd_debug(
 ) -> Result<(), CommandError> {
     match subcommand {
         DebugCommand::CopyDetection(args) => cmd_debug_copy_detection(ui, command, args),
+        DebugCommand::DiffBench(args) => cmd_debug_diff_bench(ui, command, args),
         DebugCommand::Fileset(args) => cmd_debug_fileset(ui, command, args),
+        DebugCommand::Fsck(args) => cmd_debug_fsck(ui, command, args),
+        DebugCommand::Ignores(args) => cmd_debug_ignores(ui, command, args),
         DebugCommand::Index(args) => cmd_debug_index(ui, command, args),
         DebugCommand::InitLocal(args) => cmd_debug_init_local(ui, command, args),
         DebugCommand::LocalWorkingCopy(args) => cmd_debug_local_working_copy(ui, command, args),
@@ -99,8 +122,10 @@ pub fn cmd_debug(
         DebugCommand::Reindex(args) => cmd_debug_reindex(ui, command, args),
         DebugCommand::Revset(args) => cmd_debug_revset(ui, command, args),
         DebugCommand::Snapshot(args) => cmd_debug_snapshot(ui, command, args),
+        DebugCommand::SnapshotBench(args) => cmd_debug_snapshot_bench(ui, command, args),
         DebugCommand::Template(args) => cmd_debug_template(ui, command, args),
         DebugCommand::Tree(args) => cmd_debug_tree(ui, command, args),
+        DebugCommand::Watch(args) => cmd_debug_watch(ui, command, args),
         DebugCommand::Watchman(args) => cmd_debug_watchman(ui, command, args),
         DebugCommand::WorkingCopy(args) => cmd_debug_working_copy(ui, command, args),
     }
@@ -0,0 +1,110 @@
+// Copyright 2025 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::fmt::Debug;
+use std::io::Write as _;
+use std::time::Duration;
+use std::time::Instant;
+
+use jj_lib::fsmonitor::FsmonitorSettings;
+use jj_lib::local_working_copy::TreeState;
+use jj_lib::matchers::EverythingMatcher;
+use jj_lib::repo::Repo as _;
+use jj_lib::working_copy::SnapshotTimings;
+
+use super::check_local_disk_wc;
+use crate::cli_util::CommandHelper;
+use crate::command_error::internal_error;
+use crate::command_error::CommandError;
+use crate::ui::Ui;
+
+/// Measure how long it takes to snapshot the working copy
+///
+/// Each iteration snapshots the working copy twice into a fresh, temporary
+/// working-copy state: first with no files known (so every file is read and
+/// hashed), then again with nothing changed. The fastest iteration is
+/// reported. The repository and the working-copy state are not modified,
+/// though the file contents are written to the backend store.
+///
+/// This command only works with a standard local-disk working copy.
+#[derive(clap::Args, Clone, Debug)]
+pub struct DebugSnapshotBenchArgs {
+    /// Number of times to repeat the measurement
+    #[arg(long, default_value_t = 3, value_parser = clap::value_parser!(u32).range(1..))]
+    iterations: u32,
+}
+
+pub fn cmd_debug_snapshot_bench(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    args: &DebugSnapshotBenchArgs,
+) -> Result<(), CommandError> {
+    let workspace_command = command.workspace_helper(ui)?;
+    check_local_disk_wc(workspace_command.working_copy().as_any())?;
+    let store = workspace_command.repo().store();
+    let workspace_root = workspace_command.workspace_root();
+    let mut options =
+        workspace_command.snapshot_options_with_start_tracking_matcher(&EverythingMatcher)?;
+    // A fresh state has no fsmonitor clock, so always walk the whole tree.
+    options.fsmonitor_settings = FsmonitorSettings::None;
+
+    let mut best_full: Option<(Duration, SnapshotTimings)> = None;
+    let mut best_incremental: Option<(Duration, SnapshotTimings)> = None;
+    for _ in 0..args.iterations {
+        let state_dir = tempfile::Builder::new()
+            .prefix("jj-snapshot-bench-")
+            .tempdir()?;
+        // Reload the initialized state so that its own mtime is known and
+        // unchanged files aren't considered racily modified.
+        TreeState::init(
+            store.clone(),
+            workspace_root.to_owned(),
+            state_dir.path().to_owned(),
+        )
+        .map_err(internal_error)?;
+        let mut tree_state = TreeState::load(
+            store.clone(),
+            workspace_root.to_owned(),
+            state_dir.path().to_owned(),
+        )
+        .map_err(internal_error)?;
+        for best in [&mut best_full, &mut best_incremental] {
+            let start = Instant::now();
+            let (_, stats) = tree_state.snapshot(&options)?;
+            let total = start.elapsed();
+            if best
+                .as_ref()
+                .is_none_or(|(best_total, _)| total < *best_total)
+            {
+                *best = Some((total, stats.timings));
+            }
+        }
+    }
+
+    let mut formatter = ui.stdout_formatter();
+    for (label, best) in [("Full", best_full), ("Incremental", best_incremental)] {
+        let (total, timings) = best.expect("at least one iteration should have run");
+        writeln!(formatter, "{label} snapshot:")?;
+        writeln!(formatter, "  traverse:     {:.1?}", timings.traverse)?;
+        writeln!(
+            formatter,
+            "  hash files:   {:.1?} ({} files, summed over threads)",
+            timings.hash_files, timings.hashed_file_count
+        )?;
+        writeln!(formatter, "  update state: {:.1?}", timings.update_state)?;
+        writeln!(formatter, "  write tree:   {:.1?}", timings.write_tree)?;
+        writeln!(formatter, "  total:        {total:.1?}")?;
+    }
+    Ok(())
+}
@@ -15,7 +15,9 @@
 use std::fmt::Debug;
 use std::io::Write as _;
 
+use itertools::Itertools as _;
 use jj_lib::backend::TreeId;
+use jj_lib::backend::TreeValue;
 use jj_lib::merged_tree::MergedTree;
 use jj_lib::repo::Repo;
 use jj_lib::repo_path::RepoPathBuf;
@@ -27,6 +29,9 @@ use crate::command_error::CommandError;
 use crate::ui::Ui;
 
 /// List the recursive entries of a tree.
+///
+/// Each entry is printed with the terms of its merge, which is useful for
+/// inspecting how conflicts are recorded.
 #[derive(clap::Args, Clone, Debug)]
 pub struct DebugTreeArgs {
     #[arg(long, short = 'r', value_name = "REVSET")]
@@ -35,6 +40,12 @@ pub struct DebugTreeArgs {
     id: Option<String>,
     #[arg(long, requires = "id")]
     dir: Option<String>,
+    /// Show only conflicted entries
+    #[arg(long)]
+    conflicts: bool,
+    /// Print each entry as a JSON object on its own line
+    #[arg(long)]
+    json: bool,
     #[arg(value_name = "FILESETS")]
     paths: Vec<String>,
     // TODO: Add an option to include trees that are ancestors of the matched paths
@@ -66,9 +77,37 @@ pub fn cmd_debug_tree(
         .parse_file_patterns(ui, &args.paths)?
         .to_matcher();
     for (path, value) in tree.entries_matching(matcher.as_ref()) {
+        if args.conflicts && value.as_ref().is_ok_and(|value| value.is_resolved()) {
+            continue;
+        }
         let ui_path = workspace_command.format_file_path(&path);
-        writeln!(ui.stdout(), "{ui_path}: {value:?}")?;
+        if args.json {
+            let value = value?;
+            let entry = serde_json::json!({
+                "path": ui_path,
+                "resolved": value.is_resolved(),
+                "removes": value.removes().map(tree_value_to_json).collect_vec(),
+                "adds": value.adds().map(tree_value_to_json).collect_vec(),
+            });
+            writeln!(ui.stdout(), "{entry}")?;
+        } else {
+            writeln!(ui.stdout(), "{ui_path}: {value:?}")?;
+        }
     }
 
     Ok(())
 }
+
+fn tree_value_to_json(value: &Option<TreeValue>) -> serde_json::Value {
+    let Some(value) = value else {
+        return serde_json::Value::Null;
+    };
+    let mut object = serde_json::json!({
+        "type": value.kind_name(),
+        "id": value.hex(),
+    });
+    if let TreeValue::File { executable, .. } = value {
+        object["executable"] = (*executable).into();
+    }
+    object
+}
@@ -0,0 +1,142 @@
+// Copyright 2025 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::fmt::Debug;
+use std::io::Write as _;
+use std::thread;
+use std::time::Duration;
+
+use itertools::Itertools as _;
+use jj_lib::object_id::ObjectId as _;
+use jj_lib::op_store::RefTarget;
+use jj_lib::op_walk;
+use jj_lib::operation::Operation;
+use jj_lib::refs::diff_named_ref_targets;
+use jj_lib::view::View;
+
+use crate::cli_util::CommandHelper;
+use crate::command_error::CommandError;
+use crate::ui::Ui;
+
+/// Watch the repo and print an event for each change
+///
+/// The repo is polled for new operations, and the working copy is snapshotted
+/// on each poll (using the configured filesystem monitor, if any). Each event
+/// is printed as a JSON object on a single line. The following event types are
+/// emitted, in this order for each new operation:
+///
+/// * `operation`: a new operation was added to the operation log
+/// * `working-copy`: the working-copy commit of a workspace changed
+/// * `bookmark`: a local bookmark was created, moved, or deleted
+#[derive(clap::Args, Clone, Debug)]
+pub struct DebugWatchArgs {
+    /// Also emit events for the operations after this one
+    ///
+    /// By default, only changes made after the command started are reported.
+    #[arg(long, value_name = "OPERATION")]
+    from: Option<String>,
+    /// Exit after printing this many events
+    #[arg(long, value_name = "N")]
+    limit: Option<usize>,
+    /// How often to poll the repo, in milliseconds
+    #[arg(long, value_name = "MILLISECONDS", default_value_t = 500)]
+    interval: u64,
+}
+
+pub fn cmd_debug_watch(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    args: &DebugWatchArgs,
+) -> Result<(), CommandError> {
+    let workspace_command = command.workspace_helper(ui)?;
+    let mut last_op = match &args.from {
+        Some(op_str) => workspace_command.resolve_single_op(op_str)?,
+        None => workspace_command.repo().operation().clone(),
+    };
+    let mut num_events = 0;
+    let mut head_op = workspace_command.repo().operation().clone();
+    loop {
+        if head_op.id() != last_op.id() {
+            let new_ops: Vec<Operation> = op_walk::walk_ancestors(&[head_op.clone()])
+                .take_while(|op| !matches!(op, Ok(op) if op.id() == last_op.id()))
+                .try_collect()?;
+            let mut last_view = last_op.view()?;
+            for op in new_ops.iter().rev() {
+                let view = op.view()?;
+                for event in operation_events(op, &last_view, &view) {
+                    if args.limit.is_some_and(|limit| num_events >= limit) {
+                        return Ok(());
+                    }
+                    writeln!(ui.stdout(), "{event}")?;
+                    num_events += 1;
+                }
+                last_view = view;
+            }
+            ui.stdout().flush()?;
+            last_op = head_op;
+        }
+        if args.limit.is_some_and(|limit| num_events >= limit) {
+            return Ok(());
+        }
+        thread::sleep(Duration::from_millis(args.interval));
+        // Reloading the workspace also snapshots the working copy.
+        let workspace_command = command.workspace_helper(ui)?;
+        head_op = workspace_command.repo().operation().clone();
+    }
+}
+
+fn operation_events(op: &Operation, old_view: &View, new_view: &View) -> Vec<serde_json::Value> {
+    let mut events = vec![serde_json::json!({
+        "type": "operation",
+        "id": op.id().hex(),
+        "description": op.metadata().description,
+    })];
+    let workspace_ids = old_view
+        .wc_commit_ids()
+        .keys()
+        .chain(new_view.wc_commit_ids().keys())
+        .sorted_by_key(|id| id.as_str())
+        .dedup();
+    for workspace_id in workspace_ids {
+        let old_id = old_view.get_wc_commit_id(workspace_id);
+        let new_id = new_view.get_wc_commit_id(workspace_id);
+        if old_id != new_id {
+            events.push(serde_json::json!({
+                "type": "working-copy",
+                "workspace": workspace_id.as_str(),
+                "commit_id": new_id.map(|id| id.hex()),
+            }));
+        }
+    }
+    for (name, (old_target, new_target)) in
+        diff_named_ref_targets(old_view.local_bookmarks(), new_view.local_bookmarks())
+    {
+        events.push(serde_json::json!({
+            "type": "bookmark",
+            "name": name,
+            "old": ref_target_to_json(old_target),
+            "new": ref_target_to_json(new_target),
+        }));
+    }
+    events
+}
+
+/// Returns the added commit ids of the target, or `null` if it's absent.
+fn ref_target_to_json(target: &RefTarget) -> serde_json::Value {
+    if target.is_absent() {
+        serde_json::Value::Null
+    } else {
+        target.added_ids().map(|id| id.hex()).collect_vec().into()
+    }
+}
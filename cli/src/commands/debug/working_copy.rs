@@ -15,23 +15,89 @@
 use std::fmt::Debug;
 use std::io::Write as _;
 
+use itertools::Itertools as _;
+use jj_lib::local_working_copy::FileType;
+use jj_lib::local_working_copy::LocalWorkingCopy;
+use jj_lib::object_id::ObjectId as _;
+use jj_lib::working_copy::WorkingCopy;
+
 use crate::cli_util::CommandHelper;
 use crate::command_error::CommandError;
 use crate::ui::Ui;
 
 /// Show information about the working copy state
 #[derive(clap::Args, Clone, Debug)]
-pub struct DebugWorkingCopyArgs {}
+pub struct DebugWorkingCopyArgs {
+    /// Print the state as a JSON object, including the recorded file states
+    ///
+    /// The working copy isn't snapshotted, so the `modified` field of each
+    /// file tells whether the file on disk appears to differ from the recorded
+    /// state. File states are only available for the standard local-disk
+    /// working copy.
+    #[arg(long)]
+    json: bool,
+}
 
 pub fn cmd_debug_working_copy(
     ui: &mut Ui,
     command: &CommandHelper,
-    _args: &DebugWorkingCopyArgs,
+    args: &DebugWorkingCopyArgs,
 ) -> Result<(), CommandError> {
     let workspace_command = command.workspace_helper_no_snapshot(ui)?;
     let wc = workspace_command.working_copy();
+    if args.json {
+        let value = working_copy_to_json(wc)?;
+        writeln!(ui.stdout(), "{value}")?;
+        return Ok(());
+    }
     writeln!(ui.stdout(), "Type: {:?}", wc.name())?;
     writeln!(ui.stdout(), "Current operation: {:?}", wc.operation_id())?;
     writeln!(ui.stdout(), "Current tree: {:?}", wc.tree_id()?)?;
     Ok(())
 }
+
+fn working_copy_to_json(wc: &dyn WorkingCopy) -> Result<serde_json::Value, CommandError> {
+    let tree_ids = wc.tree_id()?.to_merge();
+    let sparse_patterns = wc
+        .sparse_patterns()?
+        .iter()
+        .map(|path| path.as_internal_file_string())
+        .collect_vec();
+    let mut value = serde_json::json!({
+        "type": wc.name(),
+        "workspace": wc.workspace_id().as_str(),
+        "operation_id": wc.operation_id().hex(),
+        "tree_ids": tree_ids.iter().map(|id| id.hex()).collect_vec(),
+        "sparse_patterns": sparse_patterns,
+    });
+    if let Some(wc) = wc.as_any().downcast_ref::<LocalWorkingCopy>() {
+        let files: Vec<_> = wc
+            .file_states()?
+            .iter()
+            .map(|(path, state)| -> Result<_, CommandError> {
+                Ok(serde_json::json!({
+                    "path": path.as_internal_file_string(),
+                    "type": file_type_name(&state.file_type),
+                    "mtime": state.mtime.0,
+                    "size": state.size,
+                    "conflict_marker_len": state
+                        .materialized_conflict_data
+                        .map(|data| data.conflict_marker_len),
+                    "modified": wc.is_file_modified(path)?,
+                }))
+            })
+            .try_collect()?;
+        value["files"] = files.into();
+    }
+    Ok(value)
+}
+
+fn file_type_name(file_type: &FileType) -> &'static str {
+    match file_type {
+        #[cfg(unix)]
+        FileType::Normal { executable: true } => "executable",
+        FileType::Normal { .. } => "file",
+        FileType::Symlink => "symlink",
+        FileType::GitSubmodule => "git-submodule",
+    }
+}
@@ -32,6 +32,7 @@ use crate::description_util::description_template;
 use crate::description_util::edit_description;
 use crate::description_util::edit_multiple_descriptions;
 use crate::description_util::join_message_paragraphs;
+use crate::description_util::DescriptionChecks;
 use crate::description_util::ParsedBulkEditMessage;
 use crate::text_util::parse_author;
 use crate::ui::Ui;
@@ -84,6 +85,9 @@ pub(crate) struct DescribeArgs {
     /// allow the message to be edited afterwards.
     #[arg(long)]
     edit: bool,
+    /// Don't check the descriptions against the `commit.checks` rules
+    #[arg(long)]
+    no_verify: bool,
     /// Reset the author to the configured user
     ///
     /// This resets the author name, email, and timestamp.
@@ -238,6 +242,13 @@ pub(crate) fn cmd_describe(
         }
     };
 
+    let description_checks = DescriptionChecks::from_settings(tx.settings())?;
+    for (commit, new_description) in &commit_descriptions {
+        if new_description != commit.description() {
+            description_checks.check(ui, new_description, args.no_verify)?;
+        }
+    }
+
     // Filter out unchanged commits to avoid rebasing descendants in
     // `transform_descendants` below unnecessarily.
     let commit_descriptions: HashMap<_, _> = commit_descriptions
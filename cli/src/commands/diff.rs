@@ -15,18 +15,28 @@
 use clap_complete::ArgValueCandidates;
 use clap_complete::ArgValueCompleter;
 use itertools::Itertools;
+use jj_lib::backend::BackendError;
 use jj_lib::copies::CopyRecords;
+use jj_lib::fileset::FilesetExpression;
+use jj_lib::matchers::PrefixMatcher;
+use jj_lib::merge::Merge;
+use jj_lib::merged_tree::MergedTree;
+use jj_lib::merged_tree::MergedTreeBuilder;
 use jj_lib::repo::Repo;
+use jj_lib::repo_path::RepoPath;
+use jj_lib::repo_path::RepoPathBuf;
 use jj_lib::rewrite::merge_commit_trees;
 use tracing::instrument;
 
 use crate::cli_util::print_unmatched_explicit_paths;
 use crate::cli_util::CommandHelper;
 use crate::cli_util::RevisionArg;
+use crate::command_error::user_error;
 use crate::command_error::CommandError;
 use crate::complete;
 use crate::diff_util::get_copy_records;
 use crate::diff_util::DiffFormatArgs;
+use crate::diff_util::DiffPathLabels;
 use crate::ui::Ui;
 
 /// Compare file contents between two revisions
@@ -40,9 +50,19 @@ use crate::ui::Ui;
 /// given revisions. If either is left out, it defaults to the working-copy
 /// commit. For example, `jj diff --from main` shows the changes from "main"
 /// (perhaps a bookmark name) to the working-copy commit.
+///
+/// With the `--from-path` and `--to-path` options, compares the contents of
+/// one directory against another. For example, `jj diff --from-path
+/// vendor/foo --to-path third_party/foo` shows how the copy in `third_party`
+/// differs from the one in `vendor` within the working-copy commit. The
+/// directories are taken from the revision given by `-r`, or from the
+/// `--from` and `--to` revisions respectively. Paths are shown relative to the
+/// `--to-path` directory's location in the repo.
 #[derive(clap::Args, Clone, Debug)]
 #[command(mut_arg("ignore_all_space", |a| a.short('w')))]
 #[command(mut_arg("ignore_space_change", |a| a.short('b')))]
+#[command(mut_arg("find_renames", |a| a.short('M')))]
+#[command(mut_arg("find_copies", |a| a.short('C')))]
 pub(crate) struct DiffArgs {
     /// Show changes in this revision, compared to its parent(s)
     ///
@@ -74,6 +94,22 @@ pub(crate) struct DiffArgs {
         add = ArgValueCandidates::new(complete::all_revisions)
     )]
     to: Option<RevisionArg>,
+    /// Compare the contents of this directory against `--to-path`
+    #[arg(
+        long,
+        value_name = "PATH",
+        requires = "to_path",
+        value_hint = clap::ValueHint::DirPath,
+    )]
+    from_path: Option<String>,
+    /// Compare the contents of `--from-path` against this directory
+    #[arg(
+        long,
+        value_name = "PATH",
+        requires = "from_path",
+        value_hint = clap::ValueHint::DirPath,
+    )]
+    to_path: Option<String>,
     /// Restrict the diff to these paths
     #[arg(
         value_name = "FILESETS",
@@ -83,6 +119,23 @@ pub(crate) struct DiffArgs {
     paths: Vec<String>,
     #[command(flatten)]
     format: DiffFormatArgs,
+    /// Show the given source prefix instead of "a/" in the Git format
+    #[arg(long, value_name = "PREFIX", help_heading = "Diff Formatting Options")]
+    src_prefix: Option<String>,
+    /// Show the given destination prefix instead of "b/" in the Git format
+    #[arg(long, value_name = "PREFIX", help_heading = "Diff Formatting Options")]
+    dst_prefix: Option<String>,
+    /// Show only the changes in this directory, with paths relative to it
+    ///
+    /// Unlike the default, this also applies to the paths in the Git format,
+    /// which are otherwise shown relative to the workspace root.
+    #[arg(
+        long,
+        value_name = "DIR",
+        value_hint = clap::ValueHint::DirPath,
+        help_heading = "Diff Formatting Options",
+    )]
+    relative: Option<String>,
 }
 
 #[instrument(skip_all)]
@@ -93,7 +146,27 @@ pub(crate) fn cmd_diff(
 ) -> Result<(), CommandError> {
     let workspace_command = command.workspace_helper(ui)?;
     let repo = workspace_command.repo();
-    let fileset_expression = workspace_command.parse_file_patterns(ui, &args.paths)?;
+    let dir_paths = match (&args.from_path, &args.to_path) {
+        (Some(from_path), Some(to_path)) => Some((
+            workspace_command.parse_file_path(from_path)?,
+            workspace_command.parse_file_path(to_path)?,
+        )),
+        _ => None,
+    };
+    let relative_dir = args
+        .relative
+        .as_ref()
+        .map(|dir| workspace_command.parse_file_path(dir))
+        .transpose()?;
+    let mut fileset_expression = workspace_command.parse_file_patterns(ui, &args.paths)?;
+    if let Some((_, to_dir)) = &dir_paths {
+        fileset_expression =
+            FilesetExpression::prefix_path(to_dir.clone()).intersection(fileset_expression);
+    }
+    if let Some(dir) = &relative_dir {
+        fileset_expression =
+            FilesetExpression::prefix_path(dir.clone()).intersection(fileset_expression);
+    }
     let matcher = fileset_expression.to_matcher();
     let resolve_revision = |r: &Option<RevisionArg>| {
         workspace_command.resolve_single_rev(ui, r.as_ref().unwrap_or(&RevisionArg::AT))
@@ -102,7 +175,30 @@ pub(crate) fn cmd_diff(
     let from_tree;
     let to_tree;
     let mut copy_records = CopyRecords::default();
-    if args.from.is_some() || args.to.is_some() {
+    if let Some((from_dir, to_dir)) = &dir_paths {
+        let (from, to) = if args.from.is_some() || args.to.is_some() {
+            (resolve_revision(&args.from)?, resolve_revision(&args.to)?)
+        } else {
+            let commit = resolve_revision(&args.revision)?;
+            (commit.clone(), commit)
+        };
+        to_tree = to.tree()?;
+        if to_tree.sub_tree_recursive(to_dir)?.is_none() {
+            return Err(user_error(format!(
+                "No such directory in the target revision: {}",
+                workspace_command.format_file_path(to_dir)
+            )));
+        }
+        // Copy records refer to the original paths, so they are ignored.
+        from_tree =
+            remap_dir_tree(&from.tree()?, from_dir, &to_tree, to_dir).map_err(|err| match err {
+                RemapDirError::NotADirectory => user_error(format!(
+                    "No such directory in the source revision: {}",
+                    workspace_command.format_file_path(from_dir)
+                )),
+                RemapDirError::Backend(err) => err.into(),
+            })?;
+    } else if args.from.is_some() || args.to.is_some() {
         let from = resolve_revision(&args.from)?;
         let to = resolve_revision(&args.to)?;
         from_tree = from.tree()?;
@@ -122,7 +218,15 @@ pub(crate) fn cmd_diff(
         }
     }
 
-    let diff_renderer = workspace_command.diff_renderer_for(&args.format)?;
+    let default_labels = DiffPathLabels::default();
+    let path_labels = DiffPathLabels {
+        src_prefix: args.src_prefix.clone().unwrap_or(default_labels.src_prefix),
+        dst_prefix: args.dst_prefix.clone().unwrap_or(default_labels.dst_prefix),
+        relative_dir,
+    };
+    let diff_renderer = workspace_command
+        .diff_renderer_for(&args.format)?
+        .with_path_labels(path_labels);
     ui.request_pager();
     diff_renderer.show_diff(
         ui,
@@ -141,3 +245,51 @@ pub(crate) fn cmd_diff(
     )?;
     Ok(())
 }
+
+enum RemapDirError {
+    NotADirectory,
+    Backend(BackendError),
+}
+
+impl From<BackendError> for RemapDirError {
+    fn from(err: BackendError) -> Self {
+        RemapDirError::Backend(err)
+    }
+}
+
+/// Returns a copy of `base_tree` in which the contents of `target_dir` are
+/// replaced with the contents of `source_dir` in `source_tree`.
+fn remap_dir_tree(
+    source_tree: &MergedTree,
+    source_dir: &RepoPath,
+    base_tree: &MergedTree,
+    target_dir: &RepoPath,
+) -> Result<MergedTree, RemapDirError> {
+    if source_tree.sub_tree_recursive(source_dir)?.is_none() {
+        return Err(RemapDirError::NotADirectory);
+    }
+    let store = base_tree.store();
+    let mut tree_builder = MergedTreeBuilder::new(base_tree.id());
+    let target_matcher = PrefixMatcher::new([target_dir]);
+    for (path, _) in base_tree.entries_matching(&target_matcher) {
+        tree_builder.set_or_remove(path, Merge::absent());
+    }
+    let source_matcher = PrefixMatcher::new([source_dir]);
+    for (path, value) in source_tree.entries_matching(&source_matcher) {
+        let relative_path = path.strip_prefix(source_dir).unwrap();
+        let target_path = if target_dir.is_root() {
+            relative_path.to_owned()
+        } else if relative_path.is_root() {
+            target_dir.to_owned()
+        } else {
+            RepoPathBuf::from_internal_string(format!(
+                "{}/{}",
+                target_dir.as_internal_file_string(),
+                relative_path.as_internal_file_string()
+            ))
+        };
+        tree_builder.set_or_remove(target_path, value?);
+    }
+    let tree_id = tree_builder.write_tree(store)?;
+    Ok(store.get_root_tree(&tree_id)?)
+}
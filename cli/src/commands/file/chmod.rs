@@ -12,11 +12,13 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::io::Write as _;
+
 use clap_complete::ArgValueCandidates;
 use clap_complete::ArgValueCompleter;
 use jj_lib::backend::TreeValue;
-use jj_lib::merged_tree::MergedTreeBuilder;
 use jj_lib::object_id::ObjectId;
+use jj_lib::rewrite::rewrite_tree_values;
 use tracing::instrument;
 
 use crate::cli_util::print_unmatched_explicit_paths;
@@ -42,6 +44,11 @@ enum ChmodMode {
 ///
 /// Unlike the POSIX `chmod`, `jj file chmod` also works on Windows, on
 /// conflicted files, and on arbitrary revisions.
+///
+/// Directories are processed recursively, and filesets such as
+/// `'glob:scripts/**'` can be used to select the files. Each file whose
+/// executable bit is changed is reported. Symlinks don't have an executable
+/// bit, so it's an error to include one unless `--skip-symlinks` is given.
 #[derive(clap::Args, Clone, Debug)]
 pub(crate) struct FileChmodArgs {
     mode: ChmodMode,
@@ -61,6 +68,15 @@ pub(crate) struct FileChmodArgs {
         add = ArgValueCompleter::new(complete::all_revision_files),
     )]
     paths: Vec<String>,
+    /// Leave symlinks and conflicts including symlinks unchanged instead of
+    /// failing
+    #[arg(long)]
+    skip_symlinks: bool,
+}
+
+enum ChmodResult {
+    Changed,
+    SkippedSymlink,
 }
 
 #[instrument(skip_all)]
@@ -84,15 +100,12 @@ pub(crate) fn cmd_file_chmod(
     let matcher = fileset_expression.to_matcher();
     print_unmatched_explicit_paths(ui, &workspace_command, &fileset_expression, [&tree])?;
 
-    let mut tx = workspace_command.start_transaction();
-    let store = tree.store();
-    let mut tree_builder = MergedTreeBuilder::new(commit.tree_id().clone());
-    for (repo_path, result) in tree.entries_matching(matcher.as_ref()) {
-        let tree_value = result?;
+    let mut results = vec![];
+    let new_tree_id = rewrite_tree_values(&tree, matcher.as_ref(), |repo_path, tree_value| {
         let user_error_with_path = |msg: &str| {
             user_error(format!(
                 "{msg} at '{}'.",
-                tx.base_workspace_helper().format_file_path(&repo_path)
+                workspace_command.format_file_path(repo_path)
             ))
         };
         let all_files = tree_value
@@ -100,6 +113,22 @@ pub(crate) fn cmd_file_chmod(
             .flatten()
             .all(|tree_value| matches!(tree_value, TreeValue::File { .. }));
         if !all_files {
+            let has_symlink = tree_value
+                .adds()
+                .flatten()
+                .any(|tree_value| matches!(tree_value, TreeValue::Symlink(_)));
+            if has_symlink && args.skip_symlinks {
+                results.push((repo_path.to_owned(), ChmodResult::SkippedSymlink));
+                return Ok(None);
+            } else if has_symlink {
+                let message = if tree_value.is_resolved() {
+                    "Found a symlink"
+                } else {
+                    "Some of the sides of the conflict are symlinks"
+                };
+                return Err(user_error_with_path(message)
+                    .hinted("Use `--skip-symlinks` to leave symlinks unchanged."));
+            }
             let message = if tree_value.is_resolved() {
                 "Found neither a file nor a conflict"
             } else {
@@ -117,24 +146,37 @@ pub(crate) fn cmd_file_chmod(
             }
             value => value.clone(),
         });
-        tree_builder.set_or_remove(repo_path, new_tree_value);
+        if new_tree_value == tree_value {
+            return Ok(None);
+        }
+        results.push((repo_path.to_owned(), ChmodResult::Changed));
+        Ok(Some(new_tree_value))
+    })?;
+
+    let mode_name = if executable_bit {
+        "executable"
+    } else {
+        "non-executable"
+    };
+    for (repo_path, result) in &results {
+        let path = workspace_command.format_file_path(repo_path);
+        match result {
+            ChmodResult::Changed => writeln!(ui.status(), "Made {mode_name}: {path}")?,
+            ChmodResult::SkippedSymlink => writeln!(ui.status(), "Skipped symlink: {path}")?,
+        }
+    }
+    if new_tree_id == *commit.tree_id() {
+        writeln!(ui.status(), "Nothing changed.")?;
+        return Ok(());
     }
 
-    let new_tree_id = tree_builder.write_tree(store)?;
+    let mut tx = workspace_command.start_transaction();
     tx.repo_mut()
         .rewrite_commit(&commit)
         .set_tree_id(new_tree_id)
         .write()?;
     tx.finish(
         ui,
-        format!(
-            "make paths {} in commit {}",
-            if executable_bit {
-                "executable"
-            } else {
-                "non-executable"
-            },
-            commit.id().hex(),
-        ),
+        format!("make paths {mode_name} in commit {}", commit.id().hex()),
     )
 }
@@ -49,6 +49,12 @@ pub(crate) struct FileListArgs {
     #[arg(long, short = 'T')]
     template: Option<String>,
 
+    /// Print each file entry as a JSON object, one per line
+    ///
+    /// This is a shorthand for `-T builtin_file_list_json`.
+    #[arg(long, conflicts_with = "template")]
+    json: bool,
+
     /// Only list files matching these prefixes (instead of all files)
     #[arg(value_name = "FILESETS", value_hint = clap::ValueHint::AnyPath)]
     paths: Vec<String>,
@@ -70,6 +76,7 @@ pub(crate) fn cmd_file_list(
         let language = workspace_command.commit_template_language();
         let text = match &args.template {
             Some(value) => value.to_owned(),
+            None if args.json => "builtin_file_list_json".to_owned(),
             None => workspace_command.settings().get("templates.file_list")?,
         };
         workspace_command
@@ -29,6 +29,7 @@ use jj_lib::backend::TreeValue;
 use jj_lib::fileset;
 use jj_lib::fileset::FilesetDiagnostics;
 use jj_lib::fileset::FilesetExpression;
+use jj_lib::fileset::FilesetParseContext;
 use jj_lib::matchers::Matcher;
 use jj_lib::merged_tree::MergedTree;
 use jj_lib::merged_tree::MergedTreeBuilder;
@@ -470,10 +471,10 @@ fn get_tools_config(ui: &mut Ui, settings: &UserSettings) -> Result<ToolsConfig,
                         fileset::parse(
                             &mut diagnostics,
                             arg,
-                            &RepoPathUiConverter::Fs {
+                            &FilesetParseContext::new(&RepoPathUiConverter::Fs {
                                 cwd: "".into(),
                                 base: "".into(),
-                            },
+                            }),
                         )
                     })
                     .try_collect()?,
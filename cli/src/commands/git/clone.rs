@@ -15,17 +15,18 @@
 use std::fs;
 use std::io;
 use std::io::Write;
-use std::num::NonZeroU32;
 use std::path::Path;
 
 use jj_lib::git;
 use jj_lib::git::GitFetch;
+use jj_lib::git::GitShallowOptions;
 use jj_lib::refs::RemoteRefSymbol;
 use jj_lib::repo::Repo;
 use jj_lib::str_util::StringPattern;
 use jj_lib::workspace::Workspace;
 
 use super::write_repository_level_trunk_alias;
+use super::ShallowArgs;
 use crate::cli_util::CommandHelper;
 use crate::cli_util::WorkspaceCommandHelper;
 use crate::command_error::cli_error;
@@ -35,6 +36,7 @@ use crate::command_error::CommandError;
 use crate::commands::git::maybe_add_gitignore;
 use crate::git_util::absolute_git_url;
 use crate::git_util::print_git_import_stats;
+use crate::git_util::record_git_fetch_times;
 use crate::git_util::with_remote_git_callbacks;
 use crate::ui::Ui;
 
@@ -60,9 +62,24 @@ pub struct GitCloneArgs {
     /// Whether or not to colocate the Jujutsu repo with the git repo
     #[arg(long)]
     colocate: bool,
-    /// Create a shallow clone of the given depth
-    #[arg(long)]
-    depth: Option<NonZeroU32>,
+    #[command(flatten)]
+    shallow: ShallowArgs,
+    /// Create a partial clone which omits the objects matched by the filter
+    ///
+    /// For example, `blob:none` omits all file contents, which are then
+    /// fetched from the remote when they're needed. Only `blob:` filters are
+    /// supported (see `git help rev-list`), since omitted trees can't be
+    /// fetched on demand.
+    #[arg(long, value_name = "FILTER", value_parser = parse_partial_clone_filter)]
+    filter: Option<String>,
+}
+
+fn parse_partial_clone_filter(filter: &str) -> Result<String, String> {
+    if filter.starts_with("blob:") {
+        Ok(filter.to_owned())
+    } else {
+        Err("only `blob:` filters are supported".to_owned())
+    }
 }
 
 fn clone_destination_for_source(source: &str) -> Option<&str> {
@@ -117,8 +134,9 @@ pub fn cmd_git_clone(
     let clone_result = (|| -> Result<_, CommandError> {
         let workspace_command = init_workspace(ui, command, &canonical_wc_path, args.colocate)?;
         let mut workspace_command =
-            configure_remote(ui, command, workspace_command, remote_name, &source)?;
-        let default_branch = fetch_new_remote(ui, &mut workspace_command, remote_name, args.depth)?;
+            configure_remote(ui, command, workspace_command, remote_name, &source, args)?;
+        let shallow = args.shallow.to_options(&workspace_command)?;
+        let default_branch = fetch_new_remote(ui, &mut workspace_command, remote_name, &shallow)?;
         Ok((workspace_command, default_branch))
     })();
     if clone_result.is_err() {
@@ -191,10 +209,25 @@ fn configure_remote(
     workspace_command: WorkspaceCommandHelper,
     remote_name: &str,
     source: &str,
+    args: &GitCloneArgs,
 ) -> Result<WorkspaceCommandHelper, CommandError> {
     git::add_remote(workspace_command.repo().store(), remote_name, source)?;
     // Reload workspace to apply new remote configuration to
     // gix::ThreadSafeRepository behind the store.
+    let workspace_command = reload_workspace(ui, command, &workspace_command)?;
+    let Some(filter) = &args.filter else {
+        return Ok(workspace_command);
+    };
+    git::set_remote_partial_clone_filter(workspace_command.repo().store(), remote_name, filter)?;
+    // Reload again so the backend fetches the filtered objects on demand.
+    reload_workspace(ui, command, &workspace_command)
+}
+
+fn reload_workspace(
+    ui: &Ui,
+    command: &CommandHelper,
+    workspace_command: &WorkspaceCommandHelper,
+) -> Result<WorkspaceCommandHelper, CommandError> {
     let workspace = command.load_workspace_at(
         workspace_command.workspace_root(),
         workspace_command.settings(),
@@ -210,7 +243,7 @@ fn fetch_new_remote(
     ui: &Ui,
     workspace_command: &mut WorkspaceCommandHelper,
     remote_name: &str,
-    depth: Option<NonZeroU32>,
+    shallow: &GitShallowOptions,
 ) -> Result<Option<String>, CommandError> {
     writeln!(
         ui.status(),
@@ -221,12 +254,28 @@ fn fetch_new_remote(
     let mut fetch_tx = workspace_command.start_transaction();
     let mut git_fetch = GitFetch::new(fetch_tx.repo_mut(), &git_settings)?;
     with_remote_git_callbacks(ui, |cb| {
-        git_fetch.fetch(remote_name, &[StringPattern::everything()], cb, depth)
+        git_fetch.fetch(remote_name, &[StringPattern::everything()], cb, shallow)
     })?;
     let default_branch =
         with_remote_git_callbacks(ui, |cb| git_fetch.get_default_branch(remote_name, cb))?;
     let import_stats = git_fetch.import_refs()?;
     print_git_import_stats(ui, fetch_tx.repo(), &import_stats, true)?;
+    // The default branch is checked out, so import it even if it's excluded
+    // by the fetch filter.
+    if let (Some(name), Some(_)) = (&default_branch, &git_settings.fetch_ref_filter) {
+        let import_stats = git::import_remote_bookmarks(
+            fetch_tx.repo_mut(),
+            &git_settings,
+            &StringPattern::exact(remote_name),
+            &[StringPattern::exact(name)],
+        )?;
+        print_git_import_stats(ui, fetch_tx.repo(), &import_stats, true)?;
+    }
+    record_git_fetch_times(
+        fetch_tx.base_workspace_helper().repo_path(),
+        &[remote_name],
+        &[StringPattern::everything()],
+    )?;
     fetch_tx.finish(ui, "fetch from git remote into empty repo")?;
     Ok(default_branch)
 }
@@ -19,6 +19,7 @@ use itertools::Itertools;
 use jj_lib::config::ConfigGetResultExt as _;
 use jj_lib::git;
 use jj_lib::git::GitFetch;
+use jj_lib::git::GitShallowOptions;
 use jj_lib::repo::Repo;
 use jj_lib::str_util::StringPattern;
 
@@ -29,8 +30,10 @@ use crate::command_error::config_error;
 use crate::command_error::user_error;
 use crate::command_error::CommandError;
 use crate::commands::git::get_single_remote;
+use crate::commands::git::ShallowArgs;
 use crate::complete;
 use crate::git_util::print_git_import_stats;
+use crate::git_util::record_git_fetch_times;
 use crate::git_util::with_remote_git_callbacks;
 use crate::ui::Ui;
 
@@ -75,6 +78,8 @@ pub struct GitFetchArgs {
     /// Fetch from all remotes
     #[arg(long, conflicts_with = "remotes")]
     all_remotes: bool,
+    #[command(flatten)]
+    shallow: ShallowArgs,
 }
 
 #[tracing::instrument(skip(ui, command))]
@@ -127,12 +132,21 @@ pub fn cmd_git_fetch(
         .map(|r| r.as_str())
         .collect_vec();
 
+    let shallow = args.shallow.to_options(&workspace_command)?;
     let mut tx = workspace_command.start_transaction();
-    do_git_fetch(ui, &mut tx, &remotes, &args.branch)?;
+    do_git_fetch(ui, &mut tx, &remotes, &args.branch, &shallow)?;
     tx.finish(
         ui,
         format!("fetch from git remote(s) {}", remotes.iter().join(",")),
     )?;
+
+    // Commits at the shallow boundary may have got their parents back.
+    if !args.shallow.is_empty() {
+        let (_, stats) = git::deepen_shallow(workspace_command.repo())?;
+        if let Some(num_commits) = stats.num_indexed_commits {
+            writeln!(ui.status(), "Reindexed {num_commits} commits.")?;
+        }
+    }
     Ok(())
 }
 
@@ -174,17 +188,23 @@ fn do_git_fetch(
     tx: &mut WorkspaceCommandTransaction,
     remotes: &[&str],
     branch_names: &[StringPattern],
+    shallow: &GitShallowOptions,
 ) -> Result<(), CommandError> {
     let git_settings = tx.settings().git_settings()?;
     let mut git_fetch = GitFetch::new(tx.repo_mut(), &git_settings)?;
 
     for remote_name in remotes {
         with_remote_git_callbacks(ui, |callbacks| {
-            git_fetch.fetch(remote_name, branch_names, callbacks, None)
+            git_fetch.fetch(remote_name, branch_names, callbacks, shallow)
         })?;
     }
     let import_stats = git_fetch.import_refs()?;
     print_git_import_stats(ui, tx.repo(), &import_stats, true)?;
+    record_git_fetch_times(
+        tx.base_workspace_helper().repo_path(),
+        remotes,
+        branch_names,
+    )?;
     warn_if_branches_not_found(ui, tx, branch_names, remotes)
 }
 
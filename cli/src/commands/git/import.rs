@@ -13,6 +13,7 @@
 // limitations under the License.
 
 use jj_lib::git;
+use jj_lib::str_util::StringPattern;
 
 use crate::cli_util::CommandHelper;
 use crate::command_error::CommandError;
@@ -24,21 +25,41 @@ use crate::ui::Ui;
 /// If a working-copy commit gets abandoned, it will be given a new, empty
 /// commit. This is true in general; it is not specific to this command.
 #[derive(clap::Args, Clone, Debug)]
-pub struct GitImportArgs {}
+pub struct GitImportArgs {
+    /// Also import remote bookmarks excluded by `git.fetch-ref-filter`
+    ///
+    /// Once imported, the remote bookmarks are updated by the subsequent
+    /// fetches and imports.
+    ///
+    /// By default, the specified name matches exactly. Use `glob:` prefix to
+    /// select bookmarks by wildcard pattern. For details, see
+    /// https://jj-vcs.github.io/jj/latest/revsets#string-patterns.
+    #[arg(long, short, value_name = "BOOKMARK", value_parser = StringPattern::parse)]
+    bookmark: Vec<StringPattern>,
+}
 
 pub fn cmd_git_import(
     ui: &mut Ui,
     command: &CommandHelper,
-    _args: &GitImportArgs,
+    args: &GitImportArgs,
 ) -> Result<(), CommandError> {
     let mut workspace_command = command.workspace_helper(ui)?;
     let git_settings = workspace_command.settings().git_settings()?;
     let mut tx = workspace_command.start_transaction();
     // In non-colocated repo, Git HEAD will never be moved internally by jj.
     // That's why cmd_git_export() doesn't export the HEAD ref.
-    git::import_head(tx.repo_mut())?;
+    git::import_head(tx.repo_mut(), &git_settings)?;
     let stats = git::import_refs(tx.repo_mut(), &git_settings)?;
     print_git_import_stats(ui, tx.repo(), &stats, true)?;
+    if !args.bookmark.is_empty() {
+        let stats = git::import_remote_bookmarks(
+            tx.repo_mut(),
+            &git_settings,
+            &StringPattern::everything(),
+            &args.bookmark,
+        )?;
+        print_git_import_stats(ui, tx.repo(), &stats, true)?;
+    }
     tx.finish(ui, "import git refs")?;
     Ok(())
 }
@@ -19,14 +19,18 @@ mod import;
 mod init;
 mod push;
 mod remote;
+mod shallowinfo;
 mod submodule;
+mod unshallow;
 
+use std::num::NonZeroU32;
 use std::path::Path;
 
 use clap::Subcommand;
 use jj_lib::config::ConfigFile;
 use jj_lib::config::ConfigSource;
 use jj_lib::git;
+use jj_lib::git::GitShallowOptions;
 use jj_lib::git::UnexpectedGitBackendError;
 use jj_lib::refs::RemoteRefSymbol;
 use jj_lib::store::Store;
@@ -45,8 +49,12 @@ use self::push::cmd_git_push;
 use self::push::GitPushArgs;
 use self::remote::cmd_git_remote;
 use self::remote::RemoteCommand;
+use self::shallowinfo::cmd_git_shallowinfo;
+use self::shallowinfo::GitShallowinfoArgs;
 use self::submodule::cmd_git_submodule;
 use self::submodule::GitSubmoduleCommand;
+use self::unshallow::cmd_git_unshallow;
+use self::unshallow::GitUnshallowArgs;
 use crate::cli_util::CommandHelper;
 use crate::cli_util::WorkspaceCommandHelper;
 use crate::command_error::user_error_with_message;
@@ -72,8 +80,10 @@ pub enum GitCommand {
     Push(GitPushArgs),
     #[command(subcommand)]
     Remote(RemoteCommand),
+    Shallowinfo(GitShallowinfoArgs),
     #[command(subcommand, hide = true)]
     Submodule(GitSubmoduleCommand),
+    Unshallow(GitUnshallowArgs),
 }
 
 pub fn cmd_git(
@@ -89,7 +99,9 @@ pub fn cmd_git(
         GitCommand::Init(args) => cmd_git_init(ui, command, args),
         GitCommand::Push(args) => cmd_git_push(ui, command, args),
         GitCommand::Remote(args) => cmd_git_remote(ui, command, args),
+        GitCommand::Shallowinfo(args) => cmd_git_shallowinfo(ui, command, args),
         GitCommand::Submodule(args) => cmd_git_submodule(ui, command, args),
+        GitCommand::Unshallow(args) => cmd_git_unshallow(ui, command, args),
     }
 }
 
@@ -108,6 +120,47 @@ pub fn maybe_add_gitignore(workspace_command: &WorkspaceCommandHelper) -> Result
     }
 }
 
+/// Options to limit the history fetched from a Git remote
+#[derive(clap::Args, Clone, Debug)]
+struct ShallowArgs {
+    /// Fetch only the given number of commits from the tip of each branch
+    #[arg(long, conflicts_with_all = ["deepen_since", "deepen_not"])]
+    depth: Option<NonZeroU32>,
+    /// Fetch only the commits newer than the given date
+    ///
+    /// Accepts RFC 3339 dates (e.g. `2024-01-02T03:04:05+06:00`) as well as
+    /// relative dates (e.g. `2 weeks ago`).
+    #[arg(long, value_name = "DATE")]
+    deepen_since: Option<String>,
+    /// Don't fetch the commits reachable from the given remote branch or tag
+    ///
+    /// Can be repeated to exclude the history of multiple refs.
+    #[arg(long, value_name = "REF")]
+    deepen_not: Vec<String>,
+}
+
+impl ShallowArgs {
+    fn is_empty(&self) -> bool {
+        self.depth.is_none() && self.deepen_since.is_none() && self.deepen_not.is_empty()
+    }
+
+    fn to_options(
+        &self,
+        workspace_command: &WorkspaceCommandHelper,
+    ) -> Result<GitShallowOptions, CommandError> {
+        let deepen_since = self
+            .deepen_since
+            .as_deref()
+            .map(|date| workspace_command.env().parse_timestamp(date))
+            .transpose()?;
+        Ok(GitShallowOptions {
+            depth: self.depth,
+            deepen_since,
+            deepen_not: self.deepen_not.clone(),
+        })
+    }
+}
+
 fn get_single_remote(store: &Store) -> Result<Option<String>, UnexpectedGitBackendError> {
     let mut names = git::get_all_remote_names(store)?;
     Ok(match names.len() {
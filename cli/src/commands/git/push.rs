@@ -17,19 +17,26 @@ use std::collections::HashSet;
 use std::fmt;
 use std::io;
 use std::io::Write;
+use std::path::Path;
+use std::time::Duration;
 
 use clap::ArgGroup;
 use clap_complete::ArgValueCandidates;
 use indexmap::IndexSet;
 use itertools::Itertools;
 use jj_lib::backend::CommitId;
+use jj_lib::backend::Timestamp;
 use jj_lib::commit::Commit;
 use jj_lib::commit::CommitIteratorExt as _;
 use jj_lib::config::ConfigGetResultExt as _;
 use jj_lib::git;
 use jj_lib::git::GitBranchPushTargets;
+use jj_lib::git::GitFetchTimes;
+use jj_lib::git::GitPushError;
 use jj_lib::object_id::ObjectId;
 use jj_lib::op_store::RefTarget;
+use jj_lib::op_store::RemoteRef;
+use jj_lib::op_store::RemoteRefState;
 use jj_lib::refs::classify_bookmark_push_action;
 use jj_lib::refs::BookmarkPushAction;
 use jj_lib::refs::BookmarkPushUpdate;
@@ -40,6 +47,7 @@ use jj_lib::revset::RevsetExpression;
 use jj_lib::settings::UserSettings;
 use jj_lib::signing::SignBehavior;
 use jj_lib::str_util::StringPattern;
+use jj_lib::str_util::StringPatternParseError;
 use jj_lib::view::View;
 
 use crate::cli_util::short_change_hash;
@@ -48,12 +56,16 @@ use crate::cli_util::CommandHelper;
 use crate::cli_util::RevisionArg;
 use crate::cli_util::WorkspaceCommandHelper;
 use crate::cli_util::WorkspaceCommandTransaction;
+use crate::command_error::internal_error;
 use crate::command_error::user_error;
+use crate::command_error::user_error_with_hint;
 use crate::command_error::CommandError;
 use crate::commands::git::get_single_remote;
 use crate::complete;
+use crate::description_util::DescriptionChecks;
 use crate::formatter::Formatter;
 use crate::git_util::with_remote_git_callbacks;
+use crate::time_util;
 use crate::ui::Ui;
 
 /// Push to a Git remote
@@ -64,8 +76,8 @@ use crate::ui::Ui;
 /// bookmark names based on the change IDs of specific commits.
 ///
 /// Unlike in Git, the remote to push to is not derived from the tracked remote
-/// bookmarks. Use `--remote` to select the remote Git repository by name. There
-/// is no option to push to multiple remotes.
+/// bookmarks. Use `--remote` to select the remote Git repository by name. The
+/// option can be repeated to push to multiple remotes.
 ///
 /// Before the command actually moves, creates, or deletes a remote bookmark, it
 /// makes several [safety checks]. If there is a problem, you may need to run
@@ -82,27 +94,41 @@ use crate::ui::Ui;
 #[command(group(ArgGroup::new("specific").args(&["bookmark", "change", "revisions"]).multiple(true)))]
 #[command(group(ArgGroup::new("what").args(&["all", "deleted", "tracked"]).conflicts_with("specific")))]
 pub struct GitPushArgs {
-    /// The remote to push to (only named remotes are supported)
+    /// The remote to push to (only named remotes are supported, can be
+    /// repeated)
+    ///
+    /// The same bookmark updates are pushed to each remote. If pushing to one
+    /// of the remotes fails, the other remotes are still pushed to.
     ///
-    /// This defaults to the `git.push` setting. If that is not configured, and
-    /// if there are multiple remotes, the remote named "origin" will be used.
-    #[arg(long, add = ArgValueCandidates::new(complete::git_remotes))]
-    remote: Option<String>,
+    /// This defaults to the `git.push` setting, which can be a remote name or
+    /// a list of remote names. If that is not configured, and if there are
+    /// multiple remotes, the remote named "origin" will be used.
+    #[arg(
+        long = "remote",
+        value_name = "REMOTE",
+        add = ArgValueCandidates::new(complete::git_remotes),
+    )]
+    remotes: Vec<String>,
     /// Push only this bookmark, or bookmarks matching a pattern (can be
     /// repeated)
     ///
     /// By default, the specified name matches exactly. Use `glob:` prefix to
     /// select bookmarks by [wildcard pattern].
     ///
+    /// Use `<local>:<remote>` to push the local bookmark to a remote bookmark
+    /// with a different name. Mappings can also be configured by the
+    /// `git.push-remote-names` setting. The remote bookmark pushed under a
+    /// different name doesn't become tracked.
+    ///
     /// [wildcard pattern]:
     ///     https://jj-vcs.github.io/jj/latest/revsets#string-patterns
     #[arg(
         long, short,
         alias = "branch",
-        value_parser = StringPattern::parse,
+        value_parser = parse_bookmark_push_arg,
         add = ArgValueCandidates::new(complete::local_bookmarks),
     )]
-    bookmark: Vec<StringPattern>,
+    bookmark: Vec<BookmarkPushArg>,
     /// Push all bookmarks (including new and deleted bookmarks)
     #[arg(long)]
     all: bool,
@@ -140,6 +166,13 @@ pub struct GitPushArgs {
     /// commits are eligible to be pushed.
     #[arg(long)]
     allow_private: bool,
+    /// Don't check the descriptions of the commits to push against the
+    /// `commit.checks` rules
+    ///
+    /// Hooks on the remote still run. The skip is recorded in the operation
+    /// log.
+    #[arg(long)]
+    no_verify: bool,
     /// Push bookmarks pointing to these commits (can be repeated)
     #[arg(
         long,
@@ -171,6 +204,50 @@ pub struct GitPushArgs {
     /// Only display what will change on the remote
     #[arg(long)]
     dry_run: bool,
+    /// Refuse to push bookmarks that weren't fetched recently
+    ///
+    /// The push is refused if a bookmark hasn't been fetched from the remote
+    /// within the given duration, e.g. `30m`, `2h`, or `1d`, or if it has never
+    /// been fetched. If no duration is specified, it defaults to `1h`.
+    ///
+    /// This can also be turned on by the `git.push-require-fetched` setting.
+    #[arg(
+        long,
+        value_name = "DURATION",
+        num_args = 0..=1,
+        default_missing_value = "1h",
+        value_parser = time_util::parse_duration,
+    )]
+    require_fetched: Option<Duration>,
+}
+
+/// Bookmark pattern to push, optionally mapped to a different remote name.
+#[derive(Clone, Debug)]
+struct BookmarkPushArg {
+    pattern: StringPattern,
+    remote_name: Option<String>,
+}
+
+fn parse_bookmark_push_arg(src: &str) -> Result<BookmarkPushArg, String> {
+    match StringPattern::parse(src) {
+        Ok(pattern) => Ok(BookmarkPushArg {
+            pattern,
+            remote_name: None,
+        }),
+        // "<local>:<remote>" isn't a valid pattern since the local name isn't
+        // a pattern kind.
+        Err(StringPatternParseError::InvalidKind(_)) => {
+            let (local_name, remote_name) = src.split_once(':').unwrap();
+            if local_name.is_empty() || remote_name.is_empty() || remote_name.contains(':') {
+                return Err(format!("Invalid bookmark mapping `{src}`"));
+            }
+            Ok(BookmarkPushArg {
+                pattern: StringPattern::exact(local_name),
+                remote_name: Some(remote_name.to_owned()),
+            })
+        }
+        Err(err) => Err(err.to_string()),
+    }
 }
 
 fn make_bookmark_term(bookmark_names: &[impl fmt::Display]) -> String {
@@ -196,20 +273,275 @@ pub fn cmd_git_push(
 ) -> Result<(), CommandError> {
     let mut workspace_command = command.workspace_helper(ui)?;
 
-    let remote = if let Some(name) = &args.remote {
-        name.clone()
+    let remotes = if args.remotes.is_empty() {
+        get_default_push_remotes(ui, &workspace_command)?
     } else {
-        get_default_push_remote(ui, &workspace_command)?
+        args.remotes.iter().unique().cloned().collect_vec()
     };
 
     let mut tx = workspace_command.start_transaction();
+    // Process --change bookmarks first because matching bookmarks can be moved.
+    let bookmark_prefix = tx.settings().get_string("git.push-bookmark-prefix")?;
+    let change_bookmark_names =
+        update_change_bookmarks(ui, &mut tx, &args.change, &bookmark_prefix)?;
+
+    let mut remote_names: HashMap<String, String> = tx
+        .settings()
+        .get("git.push-remote-names")
+        .optional()?
+        .unwrap_or_default();
+    remote_names.extend(args.bookmark.iter().filter_map(|arg| {
+        let local_name = arg.pattern.as_exact()?;
+        Some((local_name.to_owned(), arg.remote_name.clone()?))
+    }));
+
+    let mut plans = vec![];
+    for remote in remotes {
+        plans.push(plan_bookmark_updates(
+            ui,
+            &tx,
+            args,
+            remote,
+            &change_bookmark_names,
+            &remote_names,
+        )?);
+    }
+    if plans.iter().all(|plan| plan.bookmark_updates.is_empty()) {
+        writeln!(ui.status(), "Nothing changed.")?;
+        return Ok(());
+    }
+    let is_multi_remote = plans.len() > 1;
+    if is_multi_remote {
+        for plan in plans.iter().filter(|plan| plan.bookmark_updates.is_empty()) {
+            writeln!(ui.status(), "Nothing to push to {}.", plan.remote)?;
+        }
+    }
+    plans.retain(|plan| !plan.bookmark_updates.is_empty());
+
+    let require_fetched = match args.require_fetched {
+        Some(max_age) => Some(max_age),
+        None => tx
+            .settings()
+            .get_value_with("git.push-require-fetched", |value| {
+                time_util::parse_duration(value.as_str().ok_or("Expected a string")?)
+                    .map_err(Box::<dyn std::error::Error + Send + Sync>::from)
+            })
+            .optional()?,
+    };
+    if let Some(max_age) = require_fetched {
+        let repo_path = tx.base_workspace_helper().repo_path();
+        check_bookmarks_fetched(&plans, repo_path, max_age)?;
+    }
+
+    let sign_behavior = if tx.settings().get_bool("git.sign-on-push")? {
+        Some(SignBehavior::Own)
+    } else {
+        None
+    };
+    // Commits are prepared once, even if they are pushed to multiple remotes.
+    let mut commits_to_sign = vec![];
+    for plan in &plans {
+        commits_to_sign.extend(validate_commits_ready_to_push(
+            ui,
+            &plan.bookmark_updates,
+            &plan.remote,
+            &tx,
+            args,
+            sign_behavior,
+        )?);
+    }
+    let commits_to_sign = commits_to_sign
+        .into_iter()
+        .unique_by(|commit| commit.id().clone())
+        .collect_vec();
+    if !args.dry_run && !commits_to_sign.is_empty() {
+        if let Some(sign_behavior) = sign_behavior {
+            let num_updated_signatures = commits_to_sign.len();
+            let (num_rebased_descendants, old_to_new_commits_map) =
+                sign_commits_before_push(&mut tx, commits_to_sign, sign_behavior)?;
+            for plan in &mut plans {
+                for (_, update) in &mut plan.bookmark_updates {
+                    if let Some(new_id) = update
+                        .new_target
+                        .as_ref()
+                        .and_then(|id| old_to_new_commits_map.get(id))
+                    {
+                        update.new_target = Some(new_id.clone());
+                    }
+                }
+            }
+            if let Some(mut formatter) = ui.status_formatter() {
+                writeln!(
+                    formatter,
+                    "Updated signatures of {num_updated_signatures} commits"
+                )?;
+                if num_rebased_descendants > 0 {
+                    writeln!(
+                        formatter,
+                        "Rebased {num_rebased_descendants} descendant commits"
+                    )?;
+                }
+            }
+        }
+    }
+
+    if let Some(mut formatter) = ui.status_formatter() {
+        for plan in &plans {
+            writeln!(formatter, "Changes to push to {}:", plan.remote)?;
+            print_commits_ready_to_push(formatter.as_mut(), tx.repo(), plan)?;
+        }
+    }
+
+    if args.dry_run {
+        writeln!(ui.status(), "Dry-run requested, not pushing.")?;
+        return Ok(());
+    }
+
+    if args.no_verify {
+        tx.set_tag("no-verify".to_owned(), "true".to_owned());
+    }
+    let git_settings = tx.settings().git_settings()?;
+    let mut tx_descriptions = vec![];
+    let mut failed_remotes = vec![];
+    for plan in plans {
+        let targets = GitBranchPushTargets {
+            branch_updates: plan.bookmark_updates,
+            remote_names: plan.remote_names,
+        };
+        let result = with_remote_git_callbacks(ui, |cb| {
+            git::push_branches(tx.repo_mut(), &git_settings, &plan.remote, &targets, cb)
+        });
+        if let Err(GitPushError::PartiallyPushed { .. }) = &result {
+            // The chunks pushed so far were recorded in the view, so that
+            // pushing again resumes from there.
+            tx_descriptions.push(format!("partially {}", plan.tx_description));
+        }
+        match result {
+            Ok(()) => tx_descriptions.push(plan.tx_description),
+            Err(err) if !is_multi_remote => {
+                if !tx_descriptions.is_empty() {
+                    tx.finish(ui, tx_descriptions.join("; "))?;
+                }
+                return Err(err.into());
+            }
+            Err(err) => {
+                // Keep pushing to the other remotes, and record the successful
+                // pushes before reporting the failures.
+                writeln!(
+                    ui.warning_default(),
+                    "Failed to push to {}: {err}",
+                    plan.remote
+                )?;
+                failed_remotes.push(plan.remote);
+            }
+        }
+    }
+    if !tx_descriptions.is_empty() {
+        tx.finish(ui, tx_descriptions.join("; "))?;
+    }
+    if !failed_remotes.is_empty() {
+        return Err(user_error(format!(
+            "Failed to push to {}",
+            failed_remotes.join(", ")
+        )));
+    }
+    Ok(())
+}
+
+/// Bookmark updates to push to a single remote.
+struct RemotePushPlan {
+    remote: String,
+    bookmark_updates: Vec<(String, BookmarkPushUpdate)>,
+    /// Remote bookmark names of the updates which are pushed under a
+    /// different name, keyed by the local bookmark name.
+    remote_names: HashMap<String, String>,
+    tx_description: String,
+}
+
+impl RemotePushPlan {
+    fn remote_symbol<'a>(&'a self, name: &'a str) -> RemoteRefSymbol<'a> {
+        renamed_remote_symbol(&self.remote_names, name, &self.remote)
+    }
+}
+
+/// Returns the remote bookmark to which the local bookmark is pushed.
+fn renamed_remote_symbol<'a>(
+    remote_names: &'a HashMap<String, String>,
+    name: &'a str,
+    remote: &'a str,
+) -> RemoteRefSymbol<'a> {
+    let name = remote_names.get(name).map_or(name, String::as_str);
+    RemoteRefSymbol { name, remote }
+}
+
+/// Returns the bookmark name to display, in the same `<local>:<remote>` form
+/// as accepted by `--bookmark` if pushed under a different name.
+fn display_bookmark_name(remote_names: &HashMap<String, String>, name: &str) -> String {
+    match remote_names.get(name) {
+        Some(remote_name) => format!("{name}:{remote_name}"),
+        None => name.to_owned(),
+    }
+}
+
+/// Checks that the bookmarks to push were fetched within `max_age`.
+fn check_bookmarks_fetched(
+    plans: &[RemotePushPlan],
+    repo_path: &Path,
+    max_age: Duration,
+) -> Result<(), CommandError> {
+    let fetch_times = GitFetchTimes::load(repo_path)?;
+    let now = Timestamp::now();
+    for plan in plans {
+        for (name, _) in &plan.bookmark_updates {
+            let symbol = plan.remote_symbol(name);
+            let message = match fetch_times.last_fetched(symbol) {
+                None => format!("Refusing to push bookmark {symbol}, which was never fetched"),
+                Some(time)
+                    if now.timestamp.0.saturating_sub(time.0) > max_age.as_millis() as i64 =>
+                {
+                    let time = Timestamp {
+                        timestamp: time,
+                        tz_offset: now.tz_offset,
+                    };
+                    let ago = time_util::format_duration(&time, &now, &timeago::Formatter::new())
+                        .map_err(internal_error)?;
+                    format!("Refusing to push bookmark {symbol}, which was last fetched {ago}")
+                }
+                Some(_) => continue,
+            };
+            return Err(user_error_with_hint(
+                message,
+                format!("Run `jj git fetch --remote {}` first.", plan.remote),
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Determines the bookmark updates to push to the `remote`.
+///
+/// Bookmarks selected by name or revision are pushed to the remote bookmarks
+/// specified in `remote_names`, if any.
+fn plan_bookmark_updates(
+    ui: &Ui,
+    tx: &WorkspaceCommandTransaction,
+    args: &GitPushArgs,
+    remote: String,
+    change_bookmark_names: &[String],
+    remote_names: &HashMap<String, String>,
+) -> Result<RemotePushPlan, CommandError> {
     let view = tx.repo().view();
     let tx_description;
     let mut bookmark_updates = vec![];
+    let mut plan_remote_names = HashMap::new();
     if args.all {
         for (bookmark_name, targets) in view.local_remote_bookmarks(&remote) {
+            let remote_symbol = RemoteRefSymbol {
+                name: bookmark_name,
+                remote: &remote,
+            };
             let allow_new = true; // implied by --all
-            match classify_bookmark_update(bookmark_name, &remote, targets, allow_new) {
+            match classify_bookmark_update(bookmark_name, remote_symbol, targets, allow_new) {
                 Ok(Some(update)) => bookmark_updates.push((bookmark_name.to_owned(), update)),
                 Ok(None) => {}
                 Err(reason) => reason.print(ui)?,
@@ -221,8 +553,12 @@ pub fn cmd_git_push(
             if !targets.remote_ref.is_tracking() {
                 continue;
             }
+            let remote_symbol = RemoteRefSymbol {
+                name: bookmark_name,
+                remote: &remote,
+            };
             let allow_new = false; // doesn't matter
-            match classify_bookmark_update(bookmark_name, &remote, targets, allow_new) {
+            match classify_bookmark_update(bookmark_name, remote_symbol, targets, allow_new) {
                 Ok(Some(update)) => bookmark_updates.push((bookmark_name.to_owned(), update)),
                 Ok(None) => {}
                 Err(reason) => reason.print(ui)?,
@@ -234,8 +570,12 @@ pub fn cmd_git_push(
             if targets.local_target.is_present() {
                 continue;
             }
+            let remote_symbol = RemoteRefSymbol {
+                name: bookmark_name,
+                remote: &remote,
+            };
             let allow_new = false; // doesn't matter
-            match classify_bookmark_update(bookmark_name, &remote, targets, allow_new) {
+            match classify_bookmark_update(bookmark_name, remote_symbol, targets, allow_new) {
                 Ok(Some(update)) => bookmark_updates.push((bookmark_name.to_owned(), update)),
                 Ok(None) => {}
                 Err(reason) => reason.print(ui)?,
@@ -245,47 +585,57 @@ pub fn cmd_git_push(
     } else {
         let mut seen_bookmarks: HashSet<&str> = HashSet::new();
 
-        // Process --change bookmarks first because matching bookmarks can be moved.
-        let bookmark_prefix = tx.settings().get_string("git.push-bookmark-prefix")?;
-        let change_bookmark_names =
-            update_change_bookmarks(ui, &mut tx, &args.change, &bookmark_prefix)?;
         let change_bookmarks = change_bookmark_names.iter().map(|bookmark_name| {
             let targets = LocalAndRemoteRef {
-                local_target: tx.repo().view().get_local_bookmark(bookmark_name),
-                remote_ref: tx.repo().view().get_remote_bookmark(RemoteRefSymbol {
+                local_target: view.get_local_bookmark(bookmark_name),
+                remote_ref: view.get_remote_bookmark(RemoteRefSymbol {
                     name: bookmark_name,
                     remote: &remote,
                 }),
             };
             (bookmark_name.as_ref(), targets)
         });
-        let view = tx.repo().view();
         for (bookmark_name, targets) in change_bookmarks {
             if !seen_bookmarks.insert(bookmark_name) {
                 continue;
             }
+            let remote_symbol = renamed_remote_symbol(remote_names, bookmark_name, &remote);
             let allow_new = true; // --change implies creation of remote bookmark
-            match classify_bookmark_update(bookmark_name, &remote, targets, allow_new) {
+            match classify_renamed_bookmark_update(
+                view,
+                bookmark_name,
+                remote_symbol,
+                targets,
+                allow_new,
+            ) {
                 Ok(Some(update)) => bookmark_updates.push((bookmark_name.to_owned(), update)),
                 Ok(None) => writeln!(
                     ui.status(),
-                    "Bookmark {bookmark_name}@{remote} already matches {bookmark_name}",
+                    "Bookmark {remote_symbol} already matches {bookmark_name}",
                 )?,
                 Err(reason) => return Err(reason.into()),
             }
         }
 
         let allow_new = args.allow_new || tx.settings().get("git.push-new-bookmarks")?;
-        let bookmarks_by_name = find_bookmarks_to_push(view, &args.bookmark, &remote)?;
+        let bookmark_patterns = args.bookmark.iter().map(|arg| &arg.pattern).collect_vec();
+        let bookmarks_by_name = find_bookmarks_to_push(view, &bookmark_patterns, &remote)?;
         for &(bookmark_name, targets) in &bookmarks_by_name {
             if !seen_bookmarks.insert(bookmark_name) {
                 continue;
             }
-            match classify_bookmark_update(bookmark_name, &remote, targets, allow_new) {
+            let remote_symbol = renamed_remote_symbol(remote_names, bookmark_name, &remote);
+            match classify_renamed_bookmark_update(
+                view,
+                bookmark_name,
+                remote_symbol,
+                targets,
+                allow_new,
+            ) {
                 Ok(Some(update)) => bookmark_updates.push((bookmark_name.to_owned(), update)),
                 Ok(None) => writeln!(
                     ui.status(),
-                    "Bookmark {bookmark_name}@{remote} already matches {bookmark_name}",
+                    "Bookmark {remote_symbol} already matches {bookmark_name}",
                 )?,
                 Err(reason) => return Err(reason.into()),
             }
@@ -304,80 +654,61 @@ pub fn cmd_git_push(
             if !seen_bookmarks.insert(bookmark_name) {
                 continue;
             }
-            match classify_bookmark_update(bookmark_name, &remote, targets, allow_new) {
+            let remote_symbol = renamed_remote_symbol(remote_names, bookmark_name, &remote);
+            match classify_renamed_bookmark_update(
+                view,
+                bookmark_name,
+                remote_symbol,
+                targets,
+                allow_new,
+            ) {
                 Ok(Some(update)) => bookmark_updates.push((bookmark_name.to_owned(), update)),
                 Ok(None) => {}
                 Err(reason) => reason.print(ui)?,
             }
         }
 
+        plan_remote_names.extend(bookmark_updates.iter().filter_map(|(bookmark_name, _)| {
+            let remote_name = remote_names.get(bookmark_name)?;
+            Some((bookmark_name.clone(), remote_name.clone()))
+        }));
         tx_description = format!(
             "push {} to git remote {}",
             make_bookmark_term(
                 &bookmark_updates
                     .iter()
-                    .map(|(bookmark, _)| bookmark.as_str())
+                    .map(|(bookmark_name, _)| display_bookmark_name(
+                        &plan_remote_names,
+                        bookmark_name
+                    ))
                     .collect_vec()
             ),
-            &remote
+            remote
         );
     }
-    if bookmark_updates.is_empty() {
-        writeln!(ui.status(), "Nothing changed.")?;
-        return Ok(());
-    }
 
-    let sign_behavior = if tx.settings().get_bool("git.sign-on-push")? {
-        Some(SignBehavior::Own)
-    } else {
-        None
+    let plan = RemotePushPlan {
+        remote,
+        bookmark_updates,
+        remote_names: plan_remote_names,
+        tx_description,
     };
-    let commits_to_sign =
-        validate_commits_ready_to_push(ui, &bookmark_updates, &remote, &tx, args, sign_behavior)?;
-    if !args.dry_run && !commits_to_sign.is_empty() {
-        if let Some(sign_behavior) = sign_behavior {
-            let num_updated_signatures = commits_to_sign.len();
-            let num_rebased_descendants;
-            (num_rebased_descendants, bookmark_updates) = sign_commits_before_push(
-                &mut tx,
-                commits_to_sign,
-                sign_behavior,
-                bookmark_updates,
-            )?;
-            if let Some(mut formatter) = ui.status_formatter() {
-                writeln!(
-                    formatter,
-                    "Updated signatures of {num_updated_signatures} commits"
-                )?;
-                if num_rebased_descendants > 0 {
-                    writeln!(
-                        formatter,
-                        "Rebased {num_rebased_descendants} descendant commits"
-                    )?;
-                }
-            }
-        }
-    }
-
-    if let Some(mut formatter) = ui.status_formatter() {
-        writeln!(formatter, "Changes to push to {remote}:")?;
-        print_commits_ready_to_push(formatter.as_mut(), tx.repo(), &bookmark_updates)?;
-    }
-
-    if args.dry_run {
-        writeln!(ui.status(), "Dry-run requested, not pushing.")?;
-        return Ok(());
+    if let Some(name) = plan
+        .bookmark_updates
+        .iter()
+        .map(|(bookmark_name, _)| plan.remote_symbol(bookmark_name).name)
+        .duplicates()
+        .next()
+    {
+        let symbol = RemoteRefSymbol {
+            name,
+            remote: &plan.remote,
+        };
+        return Err(user_error(format!(
+            "Cannot push multiple bookmarks to {symbol}"
+        )));
     }
-
-    let targets = GitBranchPushTargets {
-        branch_updates: bookmark_updates,
-    };
-    let git_settings = tx.settings().git_settings()?;
-    with_remote_git_callbacks(ui, |cb| {
-        git::push_branches(tx.repo_mut(), &git_settings, &remote, &targets, cb)
-    })?;
-    tx.finish(ui, tx_description)?;
-    Ok(())
+    Ok(plan)
 }
 
 /// Validates that the commits that will be pushed are ready (have authorship
@@ -410,6 +741,7 @@ fn validate_commits_ready_to_push(
         .range(&RevsetExpression::commits(new_heads));
 
     let settings = workspace_helper.settings();
+    let description_checks = DescriptionChecks::from_settings(settings)?;
     let private_revset_str = RevisionArg::from(settings.get_string("git.private-commits")?);
     let is_private = workspace_helper
         .parse_revset(ui, &private_revset_str)?
@@ -428,6 +760,7 @@ fn validate_commits_ready_to_push(
         .evaluate_to_commits()?
     {
         let commit = commit?;
+        description_checks.check(ui, commit.description(), args.no_verify)?;
         let mut reasons = vec![];
         if commit.description().is_empty() && !args.allow_empty_description {
             reasons.push("it has no description");
@@ -479,14 +812,13 @@ fn validate_commits_ready_to_push(
 
 /// Signs commits before pushing.
 ///
-/// Returns the number of commits with rebased descendants and the updated list
-/// of bookmark names and corresponding [`BookmarkPushUpdate`]s.
+/// Returns the number of commits with rebased descendants and the map from
+/// the old commits to the rewritten ones.
 fn sign_commits_before_push(
     tx: &mut WorkspaceCommandTransaction,
     commits_to_sign: Vec<Commit>,
     sign_behavior: SignBehavior,
-    bookmark_updates: Vec<(String, BookmarkPushUpdate)>,
-) -> Result<(usize, Vec<(String, BookmarkPushUpdate)>), CommandError> {
+) -> Result<(usize, HashMap<CommitId, CommitId>), CommandError> {
     let commit_ids: IndexSet<CommitId> = commits_to_sign.iter().ids().cloned().collect();
     let mut old_to_new_commits_map: HashMap<CommitId, CommitId> = HashMap::new();
     let mut num_rebased_descendants = 0;
@@ -507,28 +839,13 @@ fn sign_commits_before_push(
             Ok(())
         })?;
 
-    let bookmark_updates = bookmark_updates
-        .into_iter()
-        .map(|(bookmark_name, update)| {
-            (
-                bookmark_name,
-                BookmarkPushUpdate {
-                    old_target: update.old_target,
-                    new_target: update
-                        .new_target
-                        .map(|id| old_to_new_commits_map.get(&id).cloned().unwrap_or(id)),
-                },
-            )
-        })
-        .collect_vec();
-
-    Ok((num_rebased_descendants, bookmark_updates))
+    Ok((num_rebased_descendants, old_to_new_commits_map))
 }
 
 fn print_commits_ready_to_push(
     formatter: &mut dyn Formatter,
     repo: &dyn Repo,
-    bookmark_updates: &[(String, BookmarkPushUpdate)],
+    plan: &RemotePushPlan,
 ) -> io::Result<()> {
     let to_direction = |old_target: &CommitId, new_target: &CommitId| {
         assert_ne!(old_target, new_target);
@@ -541,7 +858,8 @@ fn print_commits_ready_to_push(
         }
     };
 
-    for (bookmark_name, update) in bookmark_updates {
+    for (bookmark_name, update) in &plan.bookmark_updates {
+        let bookmark_name = display_bookmark_name(&plan.remote_names, bookmark_name);
         match (&update.old_target, &update.new_target) {
             (Some(old_target), Some(new_target)) => {
                 let old = short_commit_hash(old_target);
@@ -587,13 +905,16 @@ fn print_commits_ready_to_push(
     Ok(())
 }
 
-fn get_default_push_remote(
+fn get_default_push_remotes(
     ui: &Ui,
     workspace_command: &WorkspaceCommandHelper,
-) -> Result<String, CommandError> {
+) -> Result<Vec<String>, CommandError> {
+    const KEY: &str = "git.push";
     let settings = workspace_command.settings();
-    if let Some(remote) = settings.get_string("git.push").optional()? {
-        Ok(remote)
+    if let Ok(remotes) = settings.get::<Vec<String>>(KEY) {
+        Ok(remotes.into_iter().unique().collect())
+    } else if let Some(remote) = settings.get_string(KEY).optional()? {
+        Ok(vec![remote])
     } else if let Some(remote) = get_single_remote(workspace_command.repo().store())? {
         // similar to get_default_fetch_remotes
         if remote != DEFAULT_REMOTE {
@@ -602,9 +923,9 @@ fn get_default_push_remote(
                 "Pushing to the only existing remote: {remote}"
             )?;
         }
-        Ok(remote)
+        Ok(vec![remote])
     } else {
-        Ok(DEFAULT_REMOTE.to_owned())
+        Ok(vec![DEFAULT_REMOTE.to_owned()])
     }
 }
 
@@ -635,7 +956,7 @@ impl From<RejectedBookmarkUpdateReason> for CommandError {
 
 fn classify_bookmark_update(
     bookmark_name: &str,
-    remote_name: &str,
+    remote_symbol: RemoteRefSymbol<'_>,
     targets: LocalAndRemoteRef,
     allow_new: bool,
 ) -> Result<Option<BookmarkPushUpdate>, RejectedBookmarkUpdateReason> {
@@ -650,21 +971,18 @@ fn classify_bookmark_update(
             ),
         }),
         BookmarkPushAction::RemoteConflicted => Err(RejectedBookmarkUpdateReason {
-            message: format!("Bookmark {bookmark_name}@{remote_name} is conflicted"),
+            message: format!("Bookmark {remote_symbol} is conflicted"),
             hint: Some("Run `jj git fetch` to update the conflicted remote bookmark.".to_owned()),
         }),
         BookmarkPushAction::RemoteUntracked => Err(RejectedBookmarkUpdateReason {
-            message: format!("Non-tracking remote bookmark {bookmark_name}@{remote_name} exists"),
+            message: format!("Non-tracking remote bookmark {remote_symbol} exists"),
             hint: Some(format!(
-                "Run `jj bookmark track {bookmark_name}@{remote_name}` to import the remote \
-                 bookmark."
+                "Run `jj bookmark track {remote_symbol}` to import the remote bookmark."
             )),
         }),
         BookmarkPushAction::Update(update) if update.old_target.is_none() && !allow_new => {
             Err(RejectedBookmarkUpdateReason {
-                message: format!(
-                    "Refusing to create new remote bookmark {bookmark_name}@{remote_name}"
-                ),
+                message: format!("Refusing to create new remote bookmark {remote_symbol}"),
                 hint: Some(
                     "Use --allow-new to push new bookmark. Use --remote to specify the remote to \
                      push to."
@@ -676,6 +994,31 @@ fn classify_bookmark_update(
     }
 }
 
+/// Classifies the update of the local bookmark, which may be pushed to a
+/// remote bookmark of a different name.
+fn classify_renamed_bookmark_update(
+    view: &View,
+    bookmark_name: &str,
+    remote_symbol: RemoteRefSymbol<'_>,
+    targets: LocalAndRemoteRef,
+    allow_new: bool,
+) -> Result<Option<BookmarkPushUpdate>, RejectedBookmarkUpdateReason> {
+    if remote_symbol.name == bookmark_name {
+        return classify_bookmark_update(bookmark_name, remote_symbol, targets, allow_new);
+    }
+    // The remote bookmark isn't tracked by the local bookmark of a different
+    // name, so compare the targets as if it were.
+    let remote_ref = RemoteRef {
+        target: view.get_remote_bookmark(remote_symbol).target.clone(),
+        state: RemoteRefState::Tracking,
+    };
+    let targets = LocalAndRemoteRef {
+        local_target: targets.local_target,
+        remote_ref: &remote_ref,
+    };
+    classify_bookmark_update(bookmark_name, remote_symbol, targets, allow_new)
+}
+
 /// Creates or moves bookmarks based on the change IDs.
 fn update_change_bookmarks(
     ui: &Ui,
@@ -724,7 +1067,7 @@ fn update_change_bookmarks(
 
 fn find_bookmarks_to_push<'a>(
     view: &'a View,
-    bookmark_patterns: &[StringPattern],
+    bookmark_patterns: &[&StringPattern],
     remote_name: &str,
 ) -> Result<Vec<(&'a str, LocalAndRemoteRef<'a>)>, CommandError> {
     let mut matching_bookmarks = vec![];
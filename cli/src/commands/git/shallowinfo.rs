@@ -0,0 +1,54 @@
+// Copyright 2025 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::io::Write as _;
+
+use jj_lib::git;
+use jj_lib::repo::Repo as _;
+
+use crate::cli_util::CommandHelper;
+use crate::command_error::CommandError;
+use crate::ui::Ui;
+
+/// Show the shallow boundary of the underlying Git repo
+///
+/// In a shallow clone, the parents of the boundary commits haven't been
+/// fetched, so these commits appear as root commits in jj. Use `jj git
+/// unshallow` to fetch the remaining history.
+#[derive(clap::Args, Clone, Debug)]
+pub struct GitShallowinfoArgs {}
+
+pub fn cmd_git_shallowinfo(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    _args: &GitShallowinfoArgs,
+) -> Result<(), CommandError> {
+    let workspace_command = command.workspace_helper(ui)?;
+    let repo = workspace_command.repo();
+    let shallow_commit_ids = git::get_git_backend(repo.store())?.shallow_commit_ids()?;
+    if shallow_commit_ids.is_empty() {
+        writeln!(ui.status(), "The repository is not shallow.")?;
+        return Ok(());
+    }
+
+    let template = workspace_command.commit_summary_template();
+    ui.request_pager();
+    let mut formatter = ui.stdout_formatter();
+    for commit_id in &shallow_commit_ids {
+        let commit = repo.store().get_commit(commit_id)?;
+        template.format(&commit, formatter.as_mut())?;
+        writeln!(formatter)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,90 @@
+// Copyright 2025 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::io::Write as _;
+
+use clap_complete::ArgValueCandidates;
+use jj_lib::git;
+use jj_lib::git::GitFetch;
+use jj_lib::repo::Repo as _;
+
+use crate::cli_util::CommandHelper;
+use crate::command_error::user_error;
+use crate::command_error::CommandError;
+use crate::commands::git::get_single_remote;
+use crate::complete;
+use crate::git_util::print_git_import_stats;
+use crate::git_util::with_remote_git_callbacks;
+use crate::ui::Ui;
+
+/// Fetch the full history of a shallow repository
+///
+/// All branches of the remote are fetched without depth limit. The commit
+/// index is then rebuilt if needed so that the commits at the former shallow
+/// boundary are connected to their newly fetched parents.
+#[derive(clap::Args, Clone, Debug)]
+pub struct GitUnshallowArgs {
+    /// The remote to fetch from
+    ///
+    /// If there are multiple remotes, the remote named "origin" will be used
+    /// by default.
+    #[arg(
+        long,
+        value_name = "REMOTE",
+        add = ArgValueCandidates::new(complete::git_remotes),
+    )]
+    remote: Option<String>,
+}
+
+pub fn cmd_git_unshallow(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    args: &GitUnshallowArgs,
+) -> Result<(), CommandError> {
+    let mut workspace_command = command.workspace_helper(ui)?;
+    let store = workspace_command.repo().store().clone();
+    if git::get_git_backend(&store)?
+        .shallow_commit_ids()?
+        .is_empty()
+    {
+        writeln!(ui.status(), "The repository is not shallow.")?;
+        return Ok(());
+    }
+    let remote = match &args.remote {
+        Some(remote) => remote.clone(),
+        None => get_single_remote(&store)?.unwrap_or_else(|| "origin".to_owned()),
+    };
+    if !git::get_all_remote_names(&store)?.contains(&remote) {
+        return Err(user_error(format!("No git remote named '{remote}'")));
+    }
+
+    let mut tx = workspace_command.start_transaction();
+    let git_settings = tx.settings().git_settings()?;
+    let mut git_fetch = GitFetch::new(tx.repo_mut(), &git_settings)?;
+    with_remote_git_callbacks(ui, |callbacks| git_fetch.unshallow(&remote, callbacks))?;
+    let import_stats = git_fetch.import_refs()?;
+    print_git_import_stats(ui, tx.repo(), &import_stats, true)?;
+    // The fetched history may not change the view at all.
+    if tx.repo().has_changes() {
+        tx.finish(ui, format!("unshallow from git remote {remote}"))?;
+    }
+
+    // The parents of the previously shallow commits have changed, so the index
+    // has to be rebuilt.
+    let (_, stats) = git::deepen_shallow(workspace_command.repo())?;
+    if let Some(num_commits) = stats.num_indexed_commits {
+        writeln!(ui.status(), "Reindexed {num_commits} commits.")?;
+    }
+    Ok(())
+}
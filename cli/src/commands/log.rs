@@ -12,6 +12,8 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::collections::HashMap;
+
 use clap_complete::ArgValueCandidates;
 use clap_complete::ArgValueCompleter;
 use itertools::Itertools as _;
@@ -21,8 +23,14 @@ use jj_lib::config::ConfigGetResultExt as _;
 use jj_lib::graph::reverse_graph;
 use jj_lib::graph::GraphEdge;
 use jj_lib::graph::GraphEdgeType;
+use jj_lib::graph::GraphNode;
 use jj_lib::graph::TopoGroupedGraphIterator;
+use jj_lib::matchers::FilesMatcher;
+use jj_lib::matchers::Matcher;
+use jj_lib::object_id::ObjectId as _;
 use jj_lib::repo::Repo;
+use jj_lib::repo_path::RepoPath;
+use jj_lib::revset::Revset;
 use jj_lib::revset::RevsetEvaluationError;
 use jj_lib::revset::RevsetExpression;
 use jj_lib::revset::RevsetFilterPredicate;
@@ -34,11 +42,18 @@ use crate::cli_util::format_template;
 use crate::cli_util::CommandHelper;
 use crate::cli_util::LogContentFormat;
 use crate::cli_util::RevisionArg;
+use crate::command_error::user_error;
 use crate::command_error::CommandError;
 use crate::commit_templater::CommitTemplateLanguage;
 use crate::complete;
+use crate::diff_util;
 use crate::diff_util::DiffFormatArgs;
+use crate::diff_util::DiffMerges;
+use crate::formatter::PlainTextFormatter;
 use crate::graphlog::get_graphlog;
+use crate::graphlog::GraphExportFormat;
+use crate::graphlog::GraphExportNode;
+use crate::graphlog::GraphExporter;
 use crate::graphlog::GraphStyle;
 use crate::ui::Ui;
 
@@ -84,6 +99,14 @@ pub(crate) struct LogArgs {
         add = ArgValueCompleter::new(complete::log_files),
     )]
     paths: Vec<String>,
+    /// Follow renames of the given file
+    ///
+    /// Revisions which modified the file under a previous name are shown too.
+    /// The patch of each revision is limited to the name the file had in that
+    /// revision. Renames are detected by the backend, which only the Git
+    /// backend supports.
+    #[arg(long, requires = "paths")]
+    follow: bool,
     /// Limit number of revisions to show
     ///
     /// Applied after revisions are filtered and reordered topologically, but
@@ -96,6 +119,13 @@ pub(crate) struct LogArgs {
     /// Don't show the graph, show a flat list of revisions
     #[arg(long)]
     no_graph: bool,
+    /// Export the graph in a machine-readable format
+    ///
+    /// Each revision is labeled with the text rendered by the template, without
+    /// colors. Edges point from revisions to their parents. Edges which skip
+    /// revisions not included in the graph are marked as elided.
+    #[arg(long, value_enum, value_name = "FORMAT", conflicts_with_all = ["no_graph", "reversed", "patch"])]
+    graph_format: Option<GraphExportFormat>,
     /// Render each revision using the given template
     ///
     /// Run `jj log -T` to list the built-in templates.
@@ -116,6 +146,12 @@ pub(crate) struct LogArgs {
     /// Show patch
     #[arg(long, short = 'p')]
     patch: bool,
+    /// How to show the changes of merge commits
+    ///
+    /// By default, merge commits are compared to the merged contents of their
+    /// parents.
+    #[arg(long, value_enum, value_name = "MODE")]
+    diff_merges: Option<DiffMerges>,
     #[command(flatten)]
     diff_format: DiffFormatArgs,
 }
@@ -130,6 +166,14 @@ pub(crate) fn cmd_log(
     let settings = workspace_command.settings();
 
     let fileset_expression = workspace_command.parse_file_patterns(ui, &args.paths)?;
+    let followed_path = if args.follow {
+        let [path] = args.paths.as_slice() else {
+            return Err(user_error("--follow requires exactly one file path"));
+        };
+        Some(workspace_command.parse_file_path(path)?)
+    } else {
+        None
+    };
     let revset_expression = {
         // only use default revset if neither revset nor path are specified
         let mut expression = if args.revisions.is_empty() && args.paths.is_empty() {
@@ -141,7 +185,7 @@ pub(crate) fn cmd_log(
             // a path was specified so we use all() and add path filter later
             workspace_command.attach_revset_evaluator(RevsetExpression::all())
         };
-        if !args.paths.is_empty() {
+        if !args.paths.is_empty() && followed_path.is_none() {
             // Beware that args.paths = ["root:."] is not identical to []. The
             // former will filter out empty commits.
             let predicate = RevsetFilterPredicate::File(fileset_expression.clone());
@@ -152,10 +196,27 @@ pub(crate) fn cmd_log(
 
     let repo = workspace_command.repo();
     let matcher = fileset_expression.to_matcher();
-    let revset = revset_expression.evaluate()?;
+    let mut followed_matchers = HashMap::new();
+    let revset = if let Some(path) = &followed_path {
+        followed_matchers =
+            follow_renames(repo.as_ref(), revset_expression.evaluate()?.iter(), path)?;
+        let commit_ids = followed_matchers.keys().cloned().collect();
+        workspace_command
+            .attach_revset_evaluator(RevsetExpression::commits(commit_ids))
+            .evaluate()?
+    } else {
+        revset_expression.evaluate()?
+    };
+    let commit_matcher = |commit_id: &CommitId| -> &dyn Matcher {
+        followed_matchers
+            .get(commit_id)
+            .map_or(matcher.as_ref(), |matcher| matcher)
+    };
 
     let store = repo.store();
-    let diff_renderer = workspace_command.diff_renderer_for_log(&args.diff_format, args.patch)?;
+    let diff_renderer = workspace_command
+        .diff_renderer_for_log(&args.diff_format, args.patch)?
+        .map(|renderer| renderer.with_diff_merges(args.diff_merges));
     let graph_style = GraphStyle::from_settings(settings)?;
 
     let use_elided_nodes = settings.get_bool("ui.log-synthetic-elided-nodes")?;
@@ -192,28 +253,44 @@ pub(crate) fn cmd_log(
         let mut formatter = ui.stdout_formatter();
         let formatter = formatter.as_mut();
 
-        if !args.no_graph {
+        if let Some(format) = args.graph_format {
+            let mut raw_output = formatter.raw()?;
+            let mut exporter = GraphExporter::new(format, raw_output.as_mut())?;
+            let iter = topo_order_graph(
+                revset.as_ref(),
+                workspace_command.get_wc_commit_id(),
+                args.limit,
+                false,
+            )?;
+            for node in iter {
+                let (commit_id, edges) = node?;
+                let commit = store.get_commit(&commit_id)?;
+                let mut label = vec![];
+                template.format(&commit, &mut PlainTextFormatter::new(&mut label))?;
+                let label = String::from_utf8_lossy(&label);
+                exporter.add_node(&GraphExportNode {
+                    commit_id: commit_id.hex(),
+                    change_id: commit.change_id().reverse_hex(),
+                    label: label.trim_end_matches('\n').to_owned(),
+                    edges: edges
+                        .into_iter()
+                        .map(|edge| GraphEdge {
+                            target: edge.target.hex(),
+                            edge_type: edge.edge_type,
+                        })
+                        .collect(),
+                })?;
+            }
+            exporter.finish()?;
+        } else if !args.no_graph {
             let mut raw_output = formatter.raw()?;
             let mut graph = get_graphlog(graph_style, raw_output.as_mut());
-            let iter: Box<dyn Iterator<Item = _>> = {
-                let mut forward_iter = TopoGroupedGraphIterator::new(revset.iter_graph());
-                // Emit the working-copy branch first, which is usually most
-                // interesting. This also helps stabilize output order.
-                if let Some(id) = workspace_command.get_wc_commit_id() {
-                    let has_commit = revset.containing_fn();
-                    if has_commit(id)? {
-                        forward_iter.prioritize_branch(id.clone());
-                    }
-                }
-                // The input to TopoGroupedGraphIterator shouldn't be truncated
-                // because the prioritized commit must exist in the input set.
-                let forward_iter = forward_iter.take(args.limit.unwrap_or(usize::MAX));
-                if args.reversed {
-                    Box::new(reverse_graph(forward_iter, |id| id)?.into_iter().map(Ok))
-                } else {
-                    Box::new(forward_iter)
-                }
-            };
+            let iter = topo_order_graph(
+                revset.as_ref(),
+                workspace_command.get_wc_commit_id(),
+                args.limit,
+                args.reversed,
+            )?;
             for node in iter {
                 let (commit_id, edges) = node?;
 
@@ -262,7 +339,7 @@ pub(crate) fn cmd_log(
                         ui,
                         formatter.as_mut(),
                         &commit,
-                        matcher.as_ref(),
+                        commit_matcher(commit.id()),
                         within_graph.width(),
                     )?;
                 }
@@ -309,7 +386,8 @@ pub(crate) fn cmd_log(
                     .write(formatter, |formatter| template.format(&commit, formatter))?;
                 if let Some(renderer) = &diff_renderer {
                     let width = ui.term_width();
-                    renderer.show_patch(ui, formatter, &commit, matcher.as_ref(), width)?;
+                    let matcher = commit_matcher(commit.id());
+                    renderer.show_patch(ui, formatter, &commit, matcher, width)?;
                 }
             }
         }
@@ -342,6 +420,76 @@ pub(crate) fn cmd_log(
     Ok(())
 }
 
+type BoxedGraphIterator<'a> =
+    Box<dyn Iterator<Item = Result<GraphNode<CommitId>, RevsetEvaluationError>> + 'a>;
+
+/// Iterates over the graph of the `revset` in topological order, emitting the
+/// branch of the working-copy commit first.
+fn topo_order_graph<'a>(
+    revset: &'a dyn Revset,
+    wc_commit_id: Option<&CommitId>,
+    limit: Option<usize>,
+    reversed: bool,
+) -> Result<BoxedGraphIterator<'a>, CommandError> {
+    let mut forward_iter = TopoGroupedGraphIterator::new(revset.iter_graph());
+    // Emit the working-copy branch first, which is usually most
+    // interesting. This also helps stabilize output order.
+    if let Some(id) = wc_commit_id {
+        let has_commit = revset.containing_fn();
+        if has_commit(id)? {
+            forward_iter.prioritize_branch(id.clone());
+        }
+    }
+    // The input to TopoGroupedGraphIterator shouldn't be truncated
+    // because the prioritized commit must exist in the input set.
+    let forward_iter = forward_iter.take(limit.unwrap_or(usize::MAX));
+    if reversed {
+        Ok(Box::new(
+            reverse_graph(forward_iter, |id| id)?.into_iter().map(Ok),
+        ))
+    } else {
+        Ok(Box::new(forward_iter))
+    }
+}
+
+/// Walks the `commit_ids` from newest to oldest, following renames of the file
+/// at `path`. Returns the commits which modified the file, along with matchers
+/// for the names the file had in these commits.
+fn follow_renames(
+    repo: &dyn Repo,
+    commit_ids: impl Iterator<Item = Result<CommitId, RevsetEvaluationError>>,
+    path: &RepoPath,
+) -> Result<HashMap<CommitId, FilesMatcher>, CommandError> {
+    let store = repo.store();
+    let mut current_path = path.to_owned();
+    let mut matchers = HashMap::new();
+    for commit_id in commit_ids {
+        let commit = store.get_commit(&commit_id?)?;
+        let parent_tree = commit.parent_tree(repo)?;
+        let before = parent_tree.path_value(&current_path)?;
+        let after = commit.tree()?.path_value(&current_path)?;
+        if before == after {
+            continue;
+        }
+        let mut paths = vec![current_path.clone()];
+        // Only a newly added file can be the target of a rename, so we don't
+        // need to run the (expensive) copy detection for other commits.
+        if before.is_absent() {
+            let target_matcher = FilesMatcher::new([&current_path]);
+            for parent_id in commit.parent_ids() {
+                let mut records =
+                    diff_util::get_copy_records(store, parent_id, commit.id(), &target_matcher)?;
+                if let Some(record) = records.next().transpose()? {
+                    paths.push(record.source);
+                }
+            }
+        }
+        current_path = paths.last().unwrap().clone();
+        matchers.insert(commit.id().clone(), FilesMatcher::new(&paths));
+    }
+    Ok(matchers)
+}
+
 pub fn get_node_template(
     style: GraphStyle,
     settings: &UserSettings,
@@ -0,0 +1,216 @@
+// Copyright 2025 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::io::Write as _;
+
+use clap_complete::ArgValueCandidates;
+
+use crate::cli_util::short_change_hash;
+use crate::cli_util::CommandHelper;
+use crate::cli_util::RevisionArg;
+use crate::command_error::user_error;
+use crate::command_error::CommandError;
+use crate::complete;
+use crate::ui::Ui;
+
+/// Manage metadata attached to changes
+///
+/// Metadata is a set of key-value pairs attached to a change, such as the URL
+/// of a code review, a ticket number, or a CI status recorded by an
+/// integration. It is stored in the repo view and is never exported to Git.
+/// Since metadata refers to the change rather than the commit, it is kept when
+/// the change is rewritten.
+///
+/// The metadata can be shown in templates by the `metadata(key)` commit
+/// method.
+#[derive(clap::Subcommand, Clone, Debug)]
+pub enum MetaCommand {
+    Get(MetaGetArgs),
+    #[command(visible_alias("l"))]
+    List(MetaListArgs),
+    #[command(visible_alias("s"))]
+    Set(MetaSetArgs),
+    #[command(visible_alias("u"))]
+    Unset(MetaUnsetArgs),
+}
+
+/// Print the value of a metadata key of a change
+#[derive(clap::Args, Clone, Debug)]
+pub struct MetaGetArgs {
+    /// The revision whose change to read the metadata of
+    #[arg(
+        long, short,
+        value_name = "REVSET",
+        default_value = "@",
+        add = ArgValueCandidates::new(complete::all_revisions),
+    )]
+    revision: RevisionArg,
+
+    /// The metadata key
+    key: String,
+}
+
+/// List the metadata of a change
+#[derive(clap::Args, Clone, Debug)]
+pub struct MetaListArgs {
+    /// The revision whose change to list the metadata of
+    #[arg(
+        long, short,
+        value_name = "REVSET",
+        default_value = "@",
+        add = ArgValueCandidates::new(complete::all_revisions),
+    )]
+    revision: RevisionArg,
+}
+
+/// Set a metadata key of a change
+#[derive(clap::Args, Clone, Debug)]
+pub struct MetaSetArgs {
+    /// The revision whose change to attach the metadata to
+    #[arg(
+        long, short,
+        value_name = "REVSET",
+        default_value = "@",
+        add = ArgValueCandidates::new(complete::all_revisions),
+    )]
+    revision: RevisionArg,
+
+    /// The metadata key
+    key: String,
+
+    /// The value to set
+    value: String,
+}
+
+/// Remove a metadata key from a change
+#[derive(clap::Args, Clone, Debug)]
+pub struct MetaUnsetArgs {
+    /// The revision whose change to remove the metadata from
+    #[arg(
+        long, short,
+        value_name = "REVSET",
+        default_value = "@",
+        add = ArgValueCandidates::new(complete::all_revisions),
+    )]
+    revision: RevisionArg,
+
+    /// The metadata key
+    key: String,
+}
+
+pub fn cmd_meta(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    subcommand: &MetaCommand,
+) -> Result<(), CommandError> {
+    match subcommand {
+        MetaCommand::Get(args) => cmd_meta_get(ui, command, args),
+        MetaCommand::List(args) => cmd_meta_list(ui, command, args),
+        MetaCommand::Set(args) => cmd_meta_set(ui, command, args),
+        MetaCommand::Unset(args) => cmd_meta_unset(ui, command, args),
+    }
+}
+
+fn cmd_meta_get(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    args: &MetaGetArgs,
+) -> Result<(), CommandError> {
+    let workspace_command = command.workspace_helper(ui)?;
+    let commit = workspace_command.resolve_single_rev(ui, &args.revision)?;
+    let key = parse_metadata_key(&args.key)?;
+    let value = workspace_command
+        .repo()
+        .view()
+        .get_change_metadata(commit.change_id())
+        .and_then(|values| values.get(key))
+        .ok_or_else(|| {
+            user_error(format!(
+                "No metadata {key} on change {}",
+                short_change_hash(commit.change_id())
+            ))
+        })?;
+    writeln!(ui.stdout(), "{value}")?;
+    Ok(())
+}
+
+fn cmd_meta_list(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    args: &MetaListArgs,
+) -> Result<(), CommandError> {
+    let workspace_command = command.workspace_helper(ui)?;
+    let commit = workspace_command.resolve_single_rev(ui, &args.revision)?;
+    let repo = workspace_command.repo();
+    let Some(values) = repo.view().get_change_metadata(commit.change_id()) else {
+        return Ok(());
+    };
+    ui.request_pager();
+    let mut formatter = ui.stdout_formatter();
+    for (key, value) in values {
+        write!(formatter.labeled("metadata_key"), "{key}")?;
+        writeln!(formatter, ": {value}")?;
+    }
+    Ok(())
+}
+
+fn cmd_meta_set(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    args: &MetaSetArgs,
+) -> Result<(), CommandError> {
+    let mut workspace_command = command.workspace_helper(ui)?;
+    let commit = workspace_command.resolve_single_rev(ui, &args.revision)?;
+    let key = parse_metadata_key(&args.key)?;
+    let change_id = commit.change_id();
+
+    let mut tx = workspace_command.start_transaction();
+    if tx.repo().get_change_metadata(change_id, key).as_ref() == Some(&args.value) {
+        writeln!(ui.status(), "Nothing changed.")?;
+        return Ok(());
+    }
+    tx.repo_mut()
+        .set_change_metadata(change_id, key, Some(args.value.clone()));
+    let change_hash = short_change_hash(change_id);
+    tx.finish(ui, format!("set metadata {key} on change {change_hash}"))?;
+    Ok(())
+}
+
+fn cmd_meta_unset(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    args: &MetaUnsetArgs,
+) -> Result<(), CommandError> {
+    let mut workspace_command = command.workspace_helper(ui)?;
+    let commit = workspace_command.resolve_single_rev(ui, &args.revision)?;
+    let key = parse_metadata_key(&args.key)?;
+    let change_id = commit.change_id();
+
+    let mut tx = workspace_command.start_transaction();
+    if tx.repo().get_change_metadata(change_id, key).is_none() {
+        writeln!(ui.status(), "Nothing changed.")?;
+        return Ok(());
+    }
+    tx.repo_mut().set_change_metadata(change_id, key, None);
+    let change_hash = short_change_hash(change_id);
+    tx.finish(ui, format!("unset metadata {key} on change {change_hash}"))?;
+    Ok(())
+}
+
+fn parse_metadata_key(key: &str) -> Result<&str, CommandError> {
+    if key.is_empty() || key.contains(char::is_whitespace) {
+        return Err(user_error(format!("Invalid metadata key: {key:?}")));
+    }
+    Ok(key)
+}
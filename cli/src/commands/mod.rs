@@ -34,16 +34,20 @@ mod git;
 mod help;
 mod interdiff;
 mod log;
+mod meta;
 mod new;
 mod next;
 mod operation;
 mod parallelize;
+mod patch;
+mod pick;
 mod prev;
 mod rebase;
 mod resolve;
 mod restore;
 mod root;
 mod run;
+mod shelve;
 mod show;
 mod sign;
 mod simplify_parents;
@@ -52,6 +56,8 @@ mod split;
 mod squash;
 mod status;
 mod tag;
+mod topic;
+mod unshelve;
 mod unsign;
 mod util;
 mod version;
@@ -117,12 +123,17 @@ enum Command {
     Help(help::HelpArgs),
     Interdiff(interdiff::InterdiffArgs),
     Log(log::LogArgs),
+    #[command(subcommand)]
+    Meta(meta::MetaCommand),
     New(new::NewArgs),
     Next(next::NextArgs),
     #[command(subcommand)]
     #[command(visible_alias = "op")]
     Operation(operation::OperationCommand),
     Parallelize(parallelize::ParallelizeArgs),
+    #[command(subcommand)]
+    Patch(patch::PatchCommand),
+    Pick(pick::PickArgs),
     Prev(prev::PrevArgs),
     Rebase(rebase::RebaseArgs),
     Resolve(resolve::ResolveArgs),
@@ -131,6 +142,7 @@ enum Command {
     #[command(hide = true)]
     // TODO: Flesh out.
     Run(run::RunArgs),
+    Shelve(shelve::ShelveArgs),
     Show(show::ShowArgs),
     Sign(sign::SignArgs),
     SimplifyParents(simplify_parents::SimplifyParentsArgs),
@@ -142,9 +154,12 @@ enum Command {
     #[command(subcommand)]
     Tag(tag::TagCommand),
     #[command(subcommand)]
+    Topic(topic::TopicCommand),
+    #[command(subcommand)]
     Util(util::UtilCommand),
     /// Undo an operation (shortcut for `jj op undo`)
     Undo(operation::undo::OperationUndoArgs),
+    Unshelve(unshelve::UnshelveArgs),
     Unsign(unsign::UnsignArgs),
     Version(version::VersionArgs),
     #[command(subcommand)]
@@ -157,6 +172,11 @@ pub fn default_app() -> clap::Command {
 
 #[instrument(skip_all)]
 pub fn run_command(ui: &mut Ui, command_helper: &CommandHelper) -> Result<(), CommandError> {
+    dispatch_command(ui, command_helper)?;
+    util::maybe_run_auto_maintenance(ui, command_helper)
+}
+
+fn dispatch_command(ui: &mut Ui, command_helper: &CommandHelper) -> Result<(), CommandError> {
     let subcommand = Command::from_arg_matches(command_helper.matches()).unwrap();
     match &subcommand {
         Command::Abandon(args) => abandon::cmd_abandon(ui, command_helper, args),
@@ -184,11 +204,14 @@ pub fn run_command(ui: &mut Ui, command_helper: &CommandHelper) -> Result<(), Co
         Command::Help(args) => help::cmd_help(ui, command_helper, args),
         Command::Interdiff(args) => interdiff::cmd_interdiff(ui, command_helper, args),
         Command::Log(args) => log::cmd_log(ui, command_helper, args),
+        Command::Meta(args) => meta::cmd_meta(ui, command_helper, args),
         Command::New(args) => new::cmd_new(ui, command_helper, args),
         Command::Next(args) => next::cmd_next(ui, command_helper, args),
         Command::Evolog(args) => evolog::cmd_evolog(ui, command_helper, args),
         Command::Operation(args) => operation::cmd_operation(ui, command_helper, args),
         Command::Parallelize(args) => parallelize::cmd_parallelize(ui, command_helper, args),
+        Command::Patch(args) => patch::cmd_patch(ui, command_helper, args),
+        Command::Pick(args) => pick::cmd_pick(ui, command_helper, args),
         Command::Prev(args) => prev::cmd_prev(ui, command_helper, args),
         Command::Rebase(args) => rebase::cmd_rebase(ui, command_helper, args),
         Command::Resolve(args) => resolve::cmd_resolve(ui, command_helper, args),
@@ -198,6 +221,7 @@ pub fn run_command(ui: &mut Ui, command_helper: &CommandHelper) -> Result<(), Co
         Command::SimplifyParents(args) => {
             simplify_parents::cmd_simplify_parents(ui, command_helper, args)
         }
+        Command::Shelve(args) => shelve::cmd_shelve(ui, command_helper, args),
         Command::Show(args) => show::cmd_show(ui, command_helper, args),
         Command::Sign(args) => sign::cmd_sign(ui, command_helper, args),
         Command::Sparse(args) => sparse::cmd_sparse(ui, command_helper, args),
@@ -205,7 +229,9 @@ pub fn run_command(ui: &mut Ui, command_helper: &CommandHelper) -> Result<(), Co
         Command::Squash(args) => squash::cmd_squash(ui, command_helper, args),
         Command::Status(args) => status::cmd_status(ui, command_helper, args),
         Command::Tag(args) => tag::cmd_tag(ui, command_helper, args),
+        Command::Topic(args) => topic::cmd_topic(ui, command_helper, args),
         Command::Undo(args) => operation::undo::cmd_op_undo(ui, command_helper, args),
+        Command::Unshelve(args) => unshelve::cmd_unshelve(ui, command_helper, args),
         Command::Unsign(args) => unsign::cmd_unsign(ui, command_helper, args),
         Command::Util(args) => util::cmd_util(ui, command_helper, args),
         Command::Version(args) => version::cmd_version(ui, command_helper, args),
@@ -18,17 +18,22 @@ use std::io::Write;
 use clap_complete::ArgValueCandidates;
 use itertools::Itertools;
 use jj_lib::backend::CommitId;
+use jj_lib::backend::Signature;
 use jj_lib::commit::CommitIteratorExt;
 use jj_lib::rewrite::merge_commit_trees;
 use jj_lib::rewrite::rebase_commit;
+use jj_lib::rewrite::rebase_onto_inserted_commit;
 use tracing::instrument;
 
 use crate::cli_util::compute_commit_location;
+use crate::cli_util::short_commit_hash;
 use crate::cli_util::CommandHelper;
 use crate::cli_util::RevisionArg;
+use crate::command_error::user_error;
 use crate::command_error::CommandError;
 use crate::complete;
 use crate::description_util::join_message_paragraphs;
+use crate::text_util::parse_author;
 use crate::ui::Ui;
 
 /// Create a new, empty change and (by default) edit it in the working copy
@@ -84,6 +89,35 @@ pub(crate) struct NewArgs {
         add = ArgValueCandidates::new(complete::mutable_revisions),
     )]
     insert_before: Option<Vec<RevisionArg>>,
+    /// Only insert the new change on the edges from the given parent(s)
+    ///
+    /// By default, `--insert-before` inserts the new change on all parent
+    /// edges of the given commits, so the new change becomes a merge of all
+    /// their parents. If some of those commits are merges, this can be used to
+    /// choose which of their parents the new change should be inserted after.
+    /// The other parents are left as they are.
+    #[arg(
+        long,
+        requires = "insert_before",
+        conflicts_with = "insert_after",
+        value_name = "REVSETS",
+        add = ArgValueCandidates::new(complete::all_revisions),
+    )]
+    edge: Option<Vec<RevisionArg>>,
+    /// Set author to the provided string
+    #[arg(long, value_parser = parse_author)]
+    author: Option<(String, String)>,
+    /// Set the author date
+    ///
+    /// Accepts RFC 3339 dates (e.g. `2024-01-02T03:04:05+06:00`) as well as
+    /// relative dates (e.g. `yesterday` or `2 days ago`).
+    #[arg(long, value_name = "DATE")]
+    author_date: Option<String>,
+    /// Set the committer date
+    ///
+    /// Accepts the same formats as `--author-date`.
+    #[arg(long, value_name = "DATE")]
+    committer_date: Option<String>,
 }
 
 #[instrument(skip_all)]
@@ -109,6 +143,36 @@ pub(crate) fn cmd_new(
         args.insert_before.as_deref(),
         "new commit",
     )?;
+    let parent_commits = if let Some(edges) = &args.edge {
+        let edge_ids: HashSet<CommitId> = workspace_command
+            .parse_union_revsets(ui, edges)?
+            .evaluate_to_commit_ids()?
+            .try_collect()?;
+        if let Some(edge_id) = edge_ids
+            .iter()
+            .find(|id| !parent_commits.iter().any(|commit| commit.id() == *id))
+        {
+            return Err(user_error(format!(
+                "Commit {} is not a parent of any of the --insert-before commits",
+                short_commit_hash(edge_id),
+            )));
+        }
+        if let Some(child) = children_commits
+            .iter()
+            .find(|commit| !commit.parent_ids().iter().any(|id| edge_ids.contains(id)))
+        {
+            return Err(user_error(format!(
+                "None of the parents of commit {} were selected by --edge",
+                short_commit_hash(child.id()),
+            )));
+        }
+        parent_commits
+            .into_iter()
+            .filter(|commit| edge_ids.contains(commit.id()))
+            .collect_vec()
+    } else {
+        parent_commits
+    };
     let parent_commit_ids = parent_commits.iter().ids().cloned().collect_vec();
     let mut advance_bookmarks_target = None;
     let mut advanceable_bookmarks = vec![];
@@ -123,26 +187,62 @@ pub(crate) fn cmd_new(
     };
 
     let parent_commit_ids_set: HashSet<CommitId> = parent_commit_ids.iter().cloned().collect();
+    let author_date = args
+        .author_date
+        .as_deref()
+        .map(|date| workspace_command.env().parse_timestamp(date))
+        .transpose()?;
+    let committer_date = args
+        .committer_date
+        .as_deref()
+        .map(|date| workspace_command.env().parse_timestamp(date))
+        .transpose()?;
 
     let mut tx = workspace_command.start_transaction();
     let merged_tree = merge_commit_trees(tx.repo(), &parent_commits)?;
-    let new_commit = tx
+    let mut commit_builder = tx
         .repo_mut()
         .new_commit(parent_commit_ids, merged_tree.id())
-        .set_description(join_message_paragraphs(&args.message_paragraphs))
-        .write()?;
+        .set_description(join_message_paragraphs(&args.message_paragraphs));
+    if let Some((name, email)) = args.author.clone() {
+        let new_author = Signature {
+            name,
+            email,
+            timestamp: commit_builder.author().timestamp,
+        };
+        commit_builder = commit_builder.set_author(new_author);
+    }
+    if let Some(timestamp) = author_date {
+        commit_builder = commit_builder.set_author_timestamp(timestamp);
+    }
+    if let Some(timestamp) = committer_date {
+        commit_builder = commit_builder.set_committer_timestamp(timestamp);
+    }
+    let new_commit = commit_builder.write()?;
 
     let mut num_rebased = 0;
-    for child_commit in children_commits {
-        let new_parent_ids = child_commit
-            .parent_ids()
-            .iter()
-            .filter(|id| !parent_commit_ids_set.contains(id))
-            .cloned()
-            .chain(std::iter::once(new_commit.id().clone()))
-            .collect_vec();
-        rebase_commit(tx.repo_mut(), child_commit, new_parent_ids)?;
-        num_rebased += 1;
+    if args.edge.is_some() {
+        // Replace the selected parent edges in place so the order of the other
+        // parents is preserved.
+        num_rebased += rebase_onto_inserted_commit(
+            tx.repo_mut(),
+            children_commits,
+            &parent_commit_ids_set,
+            new_commit.id(),
+        )?
+        .len();
+    } else {
+        for child_commit in children_commits {
+            let new_parent_ids = child_commit
+                .parent_ids()
+                .iter()
+                .filter(|id| !parent_commit_ids_set.contains(id))
+                .cloned()
+                .chain(std::iter::once(new_commit.id().clone()))
+                .collect_vec();
+            rebase_commit(tx.repo_mut(), child_commit, new_parent_ids)?;
+            num_rebased += 1;
+        }
     }
     num_rebased += tx.repo_mut().rebase_descendants()?;
 
@@ -0,0 +1,67 @@
+// Copyright 2025 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::io::Write as _;
+
+use clap_complete::ArgValueCandidates;
+use jj_lib::object_id::ObjectId as _;
+
+use crate::cli_util::short_operation_hash;
+use crate::cli_util::CommandHelper;
+use crate::command_error::CommandError;
+use crate::complete;
+use crate::operation_templater::ANNOTATED_OPERATION_TAG;
+use crate::operation_templater::OPERATION_ANNOTATION_TAG;
+use crate::ui::Ui;
+
+/// Attach a note to an operation
+///
+/// Operations can't be modified, so the note is recorded by a new operation
+/// that doesn't change the repo. The note is shown with the described
+/// operation in `jj op log`, which helps to find it in a long operation
+/// history, e.g. to restore the repo to the state before a big rebase.
+///
+/// Describing an operation again replaces the note. An empty message removes
+/// it.
+#[derive(clap::Args, Clone, Debug)]
+pub struct OperationDescribeArgs {
+    /// The operation to describe
+    #[arg(default_value = "@", add = ArgValueCandidates::new(complete::operations))]
+    operation: String,
+    /// The note to attach to the operation
+    #[arg(long = "message", short, value_name = "MESSAGE", required = true)]
+    message: String,
+}
+
+pub fn cmd_op_describe(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    args: &OperationDescribeArgs,
+) -> Result<(), CommandError> {
+    let mut workspace_command = command.workspace_helper(ui)?;
+    let op = workspace_command.resolve_single_op(&args.operation)?;
+    let op_hash = short_operation_hash(op.id());
+    let mut tx = workspace_command.start_transaction();
+    tx.set_tag(ANNOTATED_OPERATION_TAG.to_owned(), op.id().hex());
+    tx.set_tag(OPERATION_ANNOTATION_TAG.to_owned(), args.message.clone());
+    // The view isn't changed, so the transaction is committed as is.
+    tx.into_inner()
+        .commit(format!("describe operation {op_hash}"))?;
+    if args.message.is_empty() {
+        writeln!(ui.status(), "Removed the note from operation {op_hash}")?;
+    } else {
+        writeln!(ui.status(), "Described operation {op_hash}")?;
+    }
+    Ok(())
+}
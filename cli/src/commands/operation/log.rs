@@ -12,24 +12,37 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::collections::HashSet;
 use std::slice;
+use std::sync::Arc;
 
 use clap_complete::ArgValueCandidates;
 use itertools::Itertools as _;
+use jj_lib::backend::CommitId;
 use jj_lib::config::ConfigGetError;
 use jj_lib::config::ConfigGetResultExt as _;
 use jj_lib::graph::reverse_graph;
 use jj_lib::graph::GraphEdge;
 use jj_lib::op_store::OpStoreError;
+use jj_lib::op_store::OpStoreResult;
+use jj_lib::op_store::OperationId;
+use jj_lib::op_store::RefTarget;
 use jj_lib::op_walk;
 use jj_lib::operation::Operation;
+use jj_lib::repo::ReadonlyRepo;
+use jj_lib::repo::Repo as _;
 use jj_lib::repo::RepoLoader;
 use jj_lib::settings::UserSettings;
+use jj_lib::str_util::StringPattern;
+use jj_lib::view::View;
 
 use super::diff::show_op_diff;
 use crate::cli_util::format_template;
 use crate::cli_util::CommandHelper;
 use crate::cli_util::LogContentFormat;
+use crate::cli_util::RevisionArg;
 use crate::cli_util::WorkspaceCommandEnvironment;
 use crate::command_error::CommandError;
 use crate::commit_templater::CommitTemplateLanguage;
@@ -85,6 +98,34 @@ pub struct OperationLogArgs {
     /// contaminated by unrelated changes.
     #[arg(long, short = 'p')]
     patch: bool,
+    /// Show only operations that affected the given commit
+    ///
+    /// An operation affects a commit if it made the commit visible or hidden,
+    /// or moved a bookmark, tag, or working-copy commit to or away from it.
+    #[arg(
+        long,
+        value_name = "REVSET",
+        add = ArgValueCandidates::new(complete::all_revisions),
+    )]
+    affects_commit: Option<RevisionArg>,
+    /// Show only operations that affected bookmarks matching the given name
+    /// pattern
+    ///
+    /// An operation affects a bookmark if it changed the local bookmark or any
+    /// remote bookmark of the same name.
+    ///
+    /// By default, the specified name matches exactly. Use `glob:` prefix to
+    /// select bookmarks by [wildcard pattern].
+    ///
+    /// [wildcard pattern]:
+    ///     https://jj-vcs.github.io/jj/latest/revsets/#string-patterns
+    #[arg(
+        long,
+        value_name = "BOOKMARK",
+        value_parser = StringPattern::parse,
+        add = ArgValueCandidates::new(complete::bookmarks),
+    )]
+    affects_bookmark: Option<StringPattern>,
     #[command(flatten)]
     diff_format: DiffFormatArgs,
 }
@@ -98,7 +139,39 @@ pub fn cmd_op_log(
         let workspace_command = command.workspace_helper(ui)?;
         let current_op = workspace_command.repo().operation();
         let repo_loader = workspace_command.workspace().repo_loader();
-        do_op_log(ui, workspace_command.env(), repo_loader, current_op, args)
+        let affects_commit = args
+            .affects_commit
+            .as_ref()
+            .map(|arg| -> Result<_, CommandError> {
+                let commit = workspace_command.resolve_single_rev(ui, arg)?;
+                Ok((workspace_command.repo().clone(), commit.id().clone()))
+            })
+            .transpose()?;
+        let filter = OperationFilter::new(affects_commit, args.affects_bookmark.clone());
+        do_op_log(
+            ui,
+            workspace_command.env(),
+            repo_loader,
+            current_op,
+            filter.as_ref(),
+            args,
+        )
+    } else if let Some(arg) = &args.affects_commit {
+        // The repo is needed to resolve the commit and its ancestry.
+        let workspace_command = command.workspace_helper_no_snapshot(ui)?;
+        let current_op = workspace_command.repo().operation();
+        let repo_loader = workspace_command.workspace().repo_loader();
+        let commit = workspace_command.resolve_single_rev(ui, arg)?;
+        let affects_commit = (workspace_command.repo().clone(), commit.id().clone());
+        let filter = OperationFilter::new(Some(affects_commit), args.affects_bookmark.clone());
+        do_op_log(
+            ui,
+            workspace_command.env(),
+            repo_loader,
+            current_op,
+            filter.as_ref(),
+            args,
+        )
     } else {
         // Don't load the repo so that the operation history can be inspected
         // even with a corrupted repo state. For example, you can find the first
@@ -107,7 +180,15 @@ pub fn cmd_op_log(
         let workspace_env = command.workspace_environment(ui, &workspace)?;
         let repo_loader = workspace.repo_loader();
         let current_op = command.resolve_operation(ui, workspace.repo_loader())?;
-        do_op_log(ui, &workspace_env, repo_loader, &current_op, args)
+        let filter = OperationFilter::new(None, args.affects_bookmark.clone());
+        do_op_log(
+            ui,
+            &workspace_env,
+            repo_loader,
+            &current_op,
+            filter.as_ref(),
+            args,
+        )
     }
 }
 
@@ -116,6 +197,7 @@ fn do_op_log(
     workspace_env: &WorkspaceCommandEnvironment,
     repo_loader: &RepoLoader,
     current_op: &Operation,
+    filter: Option<&OperationFilter>,
     args: &OperationLogArgs,
 ) -> Result<(), CommandError> {
     let settings = repo_loader.settings();
@@ -128,6 +210,7 @@ fn do_op_log(
         let language = OperationTemplateLanguage::new(
             repo_loader,
             Some(current_op.id()),
+            workspace_env.operation_annotations(),
             workspace_env.operation_template_extensions(),
         );
         let text = match &args.template {
@@ -207,16 +290,31 @@ fn do_op_log(
     ui.request_pager();
     let mut formatter = ui.stdout_formatter();
     let formatter = formatter.as_mut();
-    let iter =
-        op_walk::walk_ancestors(slice::from_ref(current_op)).take(args.limit.unwrap_or(usize::MAX));
+    let iter = op_walk::walk_ancestors(slice::from_ref(current_op));
+    let iter: Box<dyn Iterator<Item = _>> = if let Some(filter) = filter {
+        Box::new(iter.filter_map(
+            |op| match op.as_ref().map_or(Ok(true), |op| filter.matches(op)) {
+                Ok(true) => Some(op),
+                Ok(false) => None,
+                Err(err) => Some(Err(err)),
+            },
+        ))
+    } else {
+        Box::new(iter)
+    };
+    let iter = iter.take(args.limit.unwrap_or(usize::MAX));
 
     if !args.no_graph {
         let mut raw_output = formatter.raw()?;
         let mut graph = get_graphlog(graph_style, raw_output.as_mut());
         let iter = iter.map(|op| -> Result<_, OpStoreError> {
             let op = op?;
-            let ids = op.parent_ids();
-            let edges = ids.iter().cloned().map(GraphEdge::direct).collect();
+            let edges = if let Some(filter) = filter {
+                filter.matching_ancestor_edges(&op)?
+            } else {
+                let ids = op.parent_ids();
+                ids.iter().cloned().map(GraphEdge::direct).collect()
+            };
             Ok((op, edges))
         });
         let iter_nodes: Box<dyn Iterator<Item = _>> = if args.reversed {
@@ -273,3 +371,146 @@ fn get_node_template(style: GraphStyle, settings: &UserSettings) -> Result<Strin
     };
     Ok(symbol.unwrap_or_else(|| default.to_owned()))
 }
+
+/// Selects operations which changed the state of a commit or bookmarks
+/// compared to their parent operations.
+struct OperationFilter {
+    affects_commit: Option<(Arc<ReadonlyRepo>, CommitId)>,
+    affects_bookmark: Option<StringPattern>,
+    matched: RefCell<HashMap<OperationId, bool>>,
+}
+
+impl OperationFilter {
+    fn new(
+        affects_commit: Option<(Arc<ReadonlyRepo>, CommitId)>,
+        affects_bookmark: Option<StringPattern>,
+    ) -> Option<Self> {
+        (affects_commit.is_some() || affects_bookmark.is_some()).then(|| OperationFilter {
+            affects_commit,
+            affects_bookmark,
+            matched: RefCell::new(HashMap::new()),
+        })
+    }
+
+    /// Returns true if the `op` changed the state of interest compared to any
+    /// of its parents. The root operation never matches.
+    fn matches(&self, op: &Operation) -> OpStoreResult<bool> {
+        if let Some(&matched) = self.matched.borrow().get(op.id()) {
+            return Ok(matched);
+        }
+        let view = op.view()?;
+        let mut matched = false;
+        for parent in op.parents() {
+            if self.view_changed(&parent?.view()?, &view) {
+                matched = true;
+                break;
+            }
+        }
+        self.matched.borrow_mut().insert(op.id().clone(), matched);
+        Ok(matched)
+    }
+
+    fn view_changed(&self, old_view: &View, new_view: &View) -> bool {
+        if let Some(pattern) = &self.affects_bookmark {
+            if !itertools::equal(
+                old_view.local_bookmarks_matching(pattern),
+                new_view.local_bookmarks_matching(pattern),
+            ) || !itertools::equal(
+                old_view.remote_bookmarks_matching(pattern, &StringPattern::everything()),
+                new_view.remote_bookmarks_matching(pattern, &StringPattern::everything()),
+            ) {
+                return true;
+            }
+        }
+        if let Some((repo, commit_id)) = &self.affects_commit {
+            // Compare the cheaper ref targets first.
+            if refs_pointing_to(old_view, commit_id) != refs_pointing_to(new_view, commit_id) {
+                return true;
+            }
+            let index = repo.index();
+            let is_visible = |view: &View| {
+                index.has_id(commit_id)
+                    && view
+                        .heads()
+                        .iter()
+                        .any(|head| index.has_id(head) && index.is_ancestor(commit_id, head))
+            };
+            if is_visible(old_view) != is_visible(new_view) {
+                return true;
+            }
+        }
+        false
+    }
+
+    /// Returns edges from `op` to its nearest matching ancestors. Ancestors
+    /// are only visited up to the first matching operation on each path.
+    fn matching_ancestor_edges(
+        &self,
+        op: &Operation,
+    ) -> OpStoreResult<Vec<GraphEdge<OperationId>>> {
+        let mut edges = vec![];
+        let mut visited = HashSet::new();
+        let mut stack: Vec<(Operation, bool)> =
+            op.parents().map_ok(|parent| (parent, true)).try_collect()?;
+        stack.reverse();
+        while let Some((ancestor, is_parent)) = stack.pop() {
+            if !visited.insert(ancestor.id().clone()) {
+                continue;
+            }
+            if self.matches(&ancestor)? {
+                let id = ancestor.id().clone();
+                edges.push(if is_parent {
+                    GraphEdge::direct(id)
+                } else {
+                    GraphEdge::indirect(id)
+                });
+            } else {
+                let parents: Vec<_> = ancestor.parents().try_collect()?;
+                stack.extend(parents.into_iter().rev().map(|parent| (parent, false)));
+            }
+        }
+        Ok(edges)
+    }
+}
+
+/// Lists the refs and working copies pointing to the `commit_id`.
+fn refs_pointing_to(view: &View, commit_id: &CommitId) -> Vec<(&'static str, String)> {
+    let has_id = |target: &RefTarget| {
+        target
+            .added_ids()
+            .chain(target.removed_ids())
+            .contains(commit_id)
+    };
+    let local_bookmarks = view
+        .local_bookmarks()
+        .filter(|(_, target)| has_id(target))
+        .map(|(name, _)| ("bookmark", name.to_owned()));
+    let remote_bookmarks = view
+        .all_remote_bookmarks()
+        .filter(|(_, remote_ref)| has_id(&remote_ref.target))
+        .map(|(symbol, _)| ("remote bookmark", symbol.to_string()));
+    let tags = view
+        .tags()
+        .iter()
+        .filter(|(_, target)| has_id(target))
+        .map(|(name, _)| ("tag", name.clone()));
+    let git_refs = view
+        .git_refs()
+        .iter()
+        .filter(|(_, target)| has_id(target))
+        .map(|(name, _)| ("git ref", name.clone()));
+    let git_head = has_id(view.git_head()).then(|| ("git head", String::new()));
+    let working_copies = view
+        .workspaces_for_wc_commit_id(commit_id)
+        .into_iter()
+        .map(|workspace_id| ("working copy", workspace_id.as_str().to_owned()));
+    itertools::chain!(
+        local_bookmarks,
+        remote_bookmarks,
+        tags,
+        git_refs,
+        git_head,
+        working_copies
+    )
+    .collect()
+}
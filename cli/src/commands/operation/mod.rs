@@ -13,8 +13,11 @@
 // limitations under the License.
 
 mod abandon;
+mod describe;
 mod diff;
 mod log;
+#[cfg(feature = "git")]
+mod recover;
 mod restore;
 mod show;
 pub mod undo;
@@ -22,10 +25,17 @@ pub mod undo;
 use abandon::cmd_op_abandon;
 use abandon::OperationAbandonArgs;
 use clap::Subcommand;
+use describe::cmd_op_describe;
+use describe::OperationDescribeArgs;
 use diff::cmd_op_diff;
 use diff::OperationDiffArgs;
+use jj_lib::op_store::WorkspaceId;
 use log::cmd_op_log;
 use log::OperationLogArgs;
+#[cfg(feature = "git")]
+use recover::cmd_op_recover;
+#[cfg(feature = "git")]
+use recover::OperationRecoverArgs;
 use restore::cmd_op_restore;
 use restore::OperationRestoreArgs;
 use show::cmd_op_show;
@@ -46,8 +56,11 @@ use crate::ui::Ui;
 #[derive(Subcommand, Clone, Debug)]
 pub enum OperationCommand {
     Abandon(OperationAbandonArgs),
+    Describe(OperationDescribeArgs),
     Diff(OperationDiffArgs),
     Log(OperationLogArgs),
+    #[cfg(feature = "git")]
+    Recover(OperationRecoverArgs),
     Restore(OperationRestoreArgs),
     Show(OperationShowArgs),
     Undo(OperationUndoArgs),
@@ -60,8 +73,11 @@ pub fn cmd_operation(
 ) -> Result<(), CommandError> {
     match subcommand {
         OperationCommand::Abandon(args) => cmd_op_abandon(ui, command, args),
+        OperationCommand::Describe(args) => cmd_op_describe(ui, command, args),
         OperationCommand::Diff(args) => cmd_op_diff(ui, command, args),
         OperationCommand::Log(args) => cmd_op_log(ui, command, args),
+        #[cfg(feature = "git")]
+        OperationCommand::Recover(args) => cmd_op_recover(ui, command, args),
         OperationCommand::Restore(args) => cmd_op_restore(ui, command, args),
         OperationCommand::Show(args) => cmd_op_show(ui, command, args),
         OperationCommand::Undo(args) => cmd_op_undo(ui, command, args),
@@ -75,18 +91,32 @@ enum UndoWhatToRestore {
     /// The remote-tracking bookmarks. Do not restore these if you'd like to
     /// push after the undo
     RemoteTracking,
+    /// The local bookmarks only
+    LocalBookmarks,
+    /// The working-copy commits of the workspaces only
+    WorkingCopies,
 }
 
 const DEFAULT_UNDO_WHAT: [UndoWhatToRestore; 2] =
     [UndoWhatToRestore::Repo, UndoWhatToRestore::RemoteTracking];
 
 /// Restore only the portions of the view specified by the `what` argument
+///
+/// If `workspace_ids` is non-empty, only the working-copy commits of these
+/// workspaces are restored.
 fn view_with_desired_portions_restored(
     view_being_restored: &jj_lib::op_store::View,
     current_view: &jj_lib::op_store::View,
     what: &[UndoWhatToRestore],
+    workspace_ids: &[WorkspaceId],
 ) -> jj_lib::op_store::View {
-    let repo_source = if what.contains(&UndoWhatToRestore::Repo) {
+    let restore_repo = what.contains(&UndoWhatToRestore::Repo);
+    let repo_source = if restore_repo {
+        view_being_restored
+    } else {
+        current_view
+    };
+    let bookmark_source = if restore_repo || what.contains(&UndoWhatToRestore::LocalBookmarks) {
         view_being_restored
     } else {
         current_view
@@ -96,13 +126,45 @@ fn view_with_desired_portions_restored(
     } else {
         current_view
     };
+    let mut wc_commit_ids = current_view.wc_commit_ids.clone();
+    if restore_repo || what.contains(&UndoWhatToRestore::WorkingCopies) {
+        if workspace_ids.is_empty() {
+            wc_commit_ids = view_being_restored.wc_commit_ids.clone();
+        } else {
+            for workspace_id in workspace_ids {
+                match view_being_restored.wc_commit_ids.get(workspace_id) {
+                    Some(commit_id) => {
+                        wc_commit_ids.insert(workspace_id.clone(), commit_id.clone());
+                    }
+                    None => {
+                        wc_commit_ids.remove(workspace_id);
+                    }
+                }
+            }
+        }
+    }
+    // The commits referenced by the partially restored view may have been
+    // hidden in the other view, so make sure they stay visible.
+    let mut head_ids = repo_source.head_ids.clone();
+    head_ids.extend(wc_commit_ids.values().cloned());
+    head_ids.extend(
+        bookmark_source
+            .local_bookmarks
+            .values()
+            .flat_map(|target| target.added_ids())
+            .cloned(),
+    );
     jj_lib::op_store::View {
-        head_ids: repo_source.head_ids.clone(),
-        local_bookmarks: repo_source.local_bookmarks.clone(),
+        head_ids,
+        local_bookmarks: bookmark_source.local_bookmarks.clone(),
         tags: repo_source.tags.clone(),
         remote_views: remote_source.remote_views.clone(),
         git_refs: current_view.git_refs.clone(),
         git_head: current_view.git_head.clone(),
-        wc_commit_ids: repo_source.wc_commit_ids.clone(),
+        wc_commit_ids,
+        topics: repo_source.topics.clone(),
+        bookmark_metadata: bookmark_source.bookmark_metadata.clone(),
+        shelves: repo_source.shelves.clone(),
+        change_metadata: repo_source.change_metadata.clone(),
     }
 }
@@ -0,0 +1,82 @@
+// Copyright 2025 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::io::Write as _;
+
+use itertools::Itertools as _;
+use jj_lib::git;
+use jj_lib::repo::Repo as _;
+use jj_lib::revset::RevsetExpression;
+
+use crate::cli_util::CommandHelper;
+use crate::command_error::user_error;
+use crate::command_error::CommandError;
+use crate::ui::Ui;
+
+/// Recover commits hidden by a destructive operation
+///
+/// If the `backup.enabled` setting is on, the commits hidden by a large `jj
+/// abandon`, by `jj op restore`, or by `jj util gc` are backed up in the Git
+/// repo. This makes the commits of a backup visible again, even after their
+/// operations have been garbage-collected.
+#[derive(clap::Args, Clone, Debug)]
+pub struct OperationRecoverArgs {
+    /// Recover the commits of the backup with the given number
+    ///
+    /// Defaults to the most recent backup.
+    #[arg(long, value_name = "NUMBER", required = true)]
+    from_backup: Option<Option<u64>>,
+}
+
+pub fn cmd_op_recover(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    args: &OperationRecoverArgs,
+) -> Result<(), CommandError> {
+    let mut workspace_command = command.workspace_helper(ui)?;
+    let git_backend = git::get_git_backend(workspace_command.repo().store())?;
+    let backups = git_backend.backups()?;
+    let backup = match args.from_backup.flatten() {
+        Some(number) => backups
+            .iter()
+            .find(|backup| backup.number == number)
+            .ok_or_else(|| user_error(format!("No backup numbered {number}")))?,
+        None => backups
+            .last()
+            .ok_or_else(|| user_error("There are no backups"))?,
+    };
+    git_backend.import_head_commits(&backup.heads)?;
+    let heads: Vec<_> = backup
+        .heads
+        .iter()
+        .map(|id| workspace_command.repo().store().get_commit(id))
+        .try_collect()?;
+
+    let mut tx = workspace_command.start_transaction();
+    tx.repo_mut().add_heads(&heads)?;
+    let old_heads = tx.base_repo().view().heads().iter().cloned().collect();
+    let recovered_count = RevsetExpression::commits(backup.heads.clone())
+        .ancestors()
+        .minus(&RevsetExpression::commits(old_heads).ancestors())
+        .evaluate(tx.repo())?
+        .iter()
+        .process_results(|iter| iter.count())?;
+    writeln!(
+        ui.status(),
+        "Recovered {recovered_count} commits from backup {}",
+        backup.number
+    )?;
+    tx.finish(ui, format!("recover commits from backup {}", backup.number))?;
+    Ok(())
+}
@@ -14,13 +14,16 @@
 
 use clap_complete::ArgValueCandidates;
 use jj_lib::object_id::ObjectId;
+use jj_lib::op_store::WorkspaceId;
 
 use super::view_with_desired_portions_restored;
 use super::UndoWhatToRestore;
 use super::DEFAULT_UNDO_WHAT;
 use crate::cli_util::CommandHelper;
+use crate::command_error::user_error;
 use crate::command_error::CommandError;
 use crate::complete;
+use crate::git_util::back_up_hidden_commits;
 use crate::ui::Ui;
 
 /// Create a new operation that restores the repo to an earlier state
@@ -42,6 +45,19 @@ pub struct OperationRestoreArgs {
     /// This option is EXPERIMENTAL.
     #[arg(long, value_enum, default_values_t = DEFAULT_UNDO_WHAT)]
     what: Vec<UndoWhatToRestore>,
+
+    /// Only restore the working-copy commit of these workspaces (can be
+    /// repeated)
+    ///
+    /// The working-copy commits of other workspaces are left as they are. Use
+    /// with `--what=working-copies` to restore nothing but the working-copy
+    /// commit.
+    #[arg(
+        long = "workspace",
+        value_name = "WORKSPACE",
+        add = ArgValueCandidates::new(complete::workspaces),
+    )]
+    workspaces: Vec<String>,
 }
 
 pub fn cmd_op_restore(
@@ -51,11 +67,28 @@ pub fn cmd_op_restore(
 ) -> Result<(), CommandError> {
     let mut workspace_command = command.workspace_helper(ui)?;
     let target_op = workspace_command.resolve_single_op(&args.operation)?;
+    let target_view = target_op.view()?;
+    let workspace_ids = args
+        .workspaces
+        .iter()
+        .map(|name| {
+            let workspace_id = WorkspaceId::new(name.clone());
+            let current_view = workspace_command.repo().view();
+            if current_view.get_wc_commit_id(&workspace_id).is_some()
+                || target_view.get_wc_commit_id(&workspace_id).is_some()
+            {
+                Ok(workspace_id)
+            } else {
+                Err(user_error(format!("No such workspace: {name}")))
+            }
+        })
+        .collect::<Result<Vec<_>, _>>()?;
     let mut tx = workspace_command.start_transaction();
     let new_view = view_with_desired_portions_restored(
-        target_op.view()?.store_view(),
+        target_view.store_view(),
         tx.base_repo().view().store_view(),
         &args.what,
+        &workspace_ids,
     );
     tx.repo_mut().set_view(new_view);
     if let Some(mut formatter) = ui.status_formatter() {
@@ -64,6 +97,7 @@ pub fn cmd_op_restore(
         template.format(&target_op, formatter.as_mut())?;
         writeln!(formatter)?;
     }
+    back_up_hidden_commits(ui, &tx)?;
     tx.finish(ui, format!("restore to operation {}", target_op.id().hex()))?;
 
     Ok(())
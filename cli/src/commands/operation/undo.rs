@@ -80,6 +80,7 @@ pub fn cmd_op_undo(
         tx.repo().view().store_view(),
         tx.base_repo().view().store_view(),
         &args.what,
+        &[],
     );
     tx.repo_mut().set_view(new_view);
     if let Some(mut formatter) = ui.status_formatter() {
@@ -0,0 +1,336 @@
+// Copyright 2025 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::io::Read as _;
+use std::io::Write as _;
+use std::sync::Arc;
+
+use clap_complete::ArgValueCandidates;
+use itertools::Itertools as _;
+use jj_lib::backend::BackendError;
+use jj_lib::backend::ChangeId;
+use jj_lib::backend::MergedTreeId;
+use jj_lib::backend::TreeValue;
+use jj_lib::description;
+use jj_lib::hex_util::to_forward_hex;
+use jj_lib::merge::Merge;
+use jj_lib::merged_tree::MergedTree;
+use jj_lib::merged_tree::MergedTreeBuilder;
+use jj_lib::object_id::ObjectId as _;
+use jj_lib::patch;
+use jj_lib::patch::FilePatch;
+use jj_lib::patch::PatchApplyError;
+use jj_lib::patch::PatchEmail;
+use jj_lib::patch::PatchFileMode;
+use jj_lib::repo::Repo as _;
+use jj_lib::repo_path::RepoPathBuf;
+use jj_lib::store::Store;
+use pollster::FutureExt as _;
+use thiserror::Error;
+
+use crate::cli_util::CommandHelper;
+use crate::cli_util::RevisionArg;
+use crate::command_error::user_error_with_message;
+use crate::command_error::CommandError;
+use crate::complete;
+use crate::ui::Ui;
+
+/// What to do with a patch that doesn't apply.
+#[derive(clap::ValueEnum, Clone, Copy, Debug, Eq, PartialEq)]
+enum OnConflict {
+    /// Stop without creating any commits
+    Abort,
+    /// Skip the patch and apply the next one on top of the previous commit
+    Skip,
+}
+
+/// Create commits from patches in mbox files
+///
+/// Each email, such as one written by `git format-patch`, becomes a commit on
+/// top of the commit created for the previous email. The author, author date,
+/// and description are taken from the email. The working copy isn't moved.
+///
+/// A patch applies if the lines it changes are found in the files, possibly
+/// at other line numbers. Binary patches are not supported.
+#[derive(clap::Args, Clone, Debug)]
+pub(crate) struct PatchImportArgs {
+    /// The mbox files to import
+    #[arg(required = true, value_hint = clap::ValueHint::FilePath)]
+    paths: Vec<String>,
+    /// The revision to create the first commit on top of
+    #[arg(
+        long,
+        short,
+        default_value = "@",
+        value_name = "REVSET",
+        add = ArgValueCandidates::new(complete::all_revisions)
+    )]
+    onto: RevisionArg,
+    /// What to do with a patch that doesn't apply
+    #[arg(long, value_enum, default_value_t = OnConflict::Abort)]
+    on_conflict: OnConflict,
+    /// Use the change ID from a `Change-Id` trailer for the new commit
+    ///
+    /// The trailer is removed from the description. Trailers that aren't jj
+    /// change IDs (such as Gerrit's) are kept as is.
+    #[arg(long)]
+    keep_change_ids: bool,
+}
+
+/// Reason a patch doesn't apply.
+#[derive(Debug, Error)]
+enum PatchImportError {
+    #[error("{path}: {error}")]
+    Hunk {
+        path: String,
+        error: PatchApplyError,
+    },
+    #[error("{path}: File doesn't exist")]
+    Missing { path: String },
+    #[error("{path}: File already exists")]
+    AlreadyExists { path: String },
+    #[error("{path}: File is not a regular file or symlink")]
+    UnsupportedType { path: String },
+    #[error("{path}: Invalid path")]
+    InvalidPath { path: String },
+    #[error(transparent)]
+    Backend(#[from] BackendError),
+}
+
+pub(crate) fn cmd_patch_import(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    args: &PatchImportArgs,
+) -> Result<(), CommandError> {
+    let mut workspace_command = command.workspace_helper(ui)?;
+    let onto = workspace_command.resolve_single_rev(ui, &args.onto)?;
+    let mut emails = vec![];
+    for path in &args.paths {
+        let data = std::fs::read(command.cwd().join(path)).map_err(|err| {
+            user_error_with_message(format!("Failed to read patch file {path}"), err)
+        })?;
+        let parsed = patch::parse_mbox(&data).map_err(|err| {
+            user_error_with_message(format!("Failed to parse patch file {path}"), err)
+        })?;
+        emails.extend(parsed);
+    }
+
+    let mut tx = workspace_command.start_transaction();
+    let store = tx.repo().store().clone();
+    let mut parent = onto;
+    let mut imported_commits = vec![];
+    for (index, email) in emails.iter().enumerate() {
+        let number = index + 1;
+        let subject = description::subject(&email.description);
+        let tree_id = match apply_email(&store, &parent.tree()?, email) {
+            Ok(tree_id) => tree_id,
+            Err(PatchImportError::Backend(err)) => return Err(err.into()),
+            Err(err) => match args.on_conflict {
+                OnConflict::Abort => {
+                    return Err(user_error_with_message(
+                        format!("Failed to apply patch {number} ({subject})"),
+                        err,
+                    ));
+                }
+                OnConflict::Skip => {
+                    writeln!(
+                        ui.warning_default(),
+                        "Skipped patch {number} ({subject}): {err}"
+                    )?;
+                    continue;
+                }
+            },
+        };
+        let mut description = email.description.clone();
+        let mut change_id = None;
+        if args.keep_change_ids {
+            if let Some((id, trailer_line)) = find_change_id_trailer(&store, &description) {
+                description = remove_line(&description, &trailer_line);
+                change_id = Some(id);
+            }
+        }
+        let mut commit_builder = tx
+            .repo_mut()
+            .new_commit(vec![parent.id().clone()], tree_id)
+            .set_description(description);
+        let mut author = commit_builder.author().clone();
+        author.name = email.author_name.clone();
+        author.email = email.author_email.clone();
+        if let Some(timestamp) = email.author_date {
+            author.timestamp = timestamp;
+        }
+        commit_builder = commit_builder.set_author(author);
+        if let Some(change_id) = change_id {
+            commit_builder = commit_builder.set_change_id(change_id);
+        }
+        let commit = commit_builder.write()?;
+        imported_commits.push(commit.clone());
+        parent = commit;
+    }
+
+    if imported_commits.is_empty() {
+        writeln!(ui.status(), "No patches imported.")?;
+        return Ok(());
+    }
+    if let Some(mut formatter) = ui.status_formatter() {
+        writeln!(formatter, "Imported {} commits:", imported_commits.len())?;
+        for commit in &imported_commits {
+            write!(formatter, "  ")?;
+            tx.write_commit_summary(formatter.as_mut(), commit)?;
+            writeln!(formatter)?;
+        }
+    }
+    tx.finish(ui, format!("import {} patches", imported_commits.len()))?;
+    Ok(())
+}
+
+/// Applies the file patches of the `email` to the `tree`.
+fn apply_email(
+    store: &Arc<Store>,
+    tree: &MergedTree,
+    email: &PatchEmail,
+) -> Result<MergedTreeId, PatchImportError> {
+    let mut tree_builder = MergedTreeBuilder::new(tree.id());
+    for file in &email.files {
+        let FilePatch {
+            old_path,
+            new_path,
+            is_copy,
+            new_mode,
+            hunks,
+        } = file;
+        let old_path = old_path.as_deref().map(parse_path).transpose()?;
+        let new_path = new_path.as_deref().map(parse_path).transpose()?;
+        let (old_contents, old_mode) = match &old_path {
+            Some(path) => read_file(store, tree, path)?,
+            None => (vec![], None),
+        };
+        if old_path.is_none() {
+            if let Some(path) = &new_path {
+                if tree.path_value(path)?.is_present() {
+                    return Err(PatchImportError::AlreadyExists {
+                        path: path.as_internal_file_string().to_owned(),
+                    });
+                }
+            }
+        }
+        let new_contents =
+            patch::apply_hunks(&old_contents, hunks).map_err(|error| PatchImportError::Hunk {
+                path: new_path
+                    .as_ref()
+                    .or(old_path.as_ref())
+                    .unwrap()
+                    .as_internal_file_string()
+                    .to_owned(),
+                error,
+            })?;
+        if let Some(path) = &old_path {
+            if !is_copy && Some(path) != new_path.as_ref() {
+                tree_builder.set_or_remove(path.clone(), Merge::absent());
+            }
+        }
+        if let Some(path) = new_path {
+            let mode = new_mode.or(old_mode).unwrap_or(PatchFileMode::Regular);
+            let value = match mode {
+                PatchFileMode::Symlink => {
+                    let target = String::from_utf8_lossy(&new_contents);
+                    let id = store.write_symlink(&path, &target).block_on()?;
+                    TreeValue::Symlink(id)
+                }
+                PatchFileMode::Regular | PatchFileMode::Executable => {
+                    let id = store
+                        .write_file(&path, &mut new_contents.as_slice())
+                        .block_on()?;
+                    TreeValue::File {
+                        id,
+                        executable: mode == PatchFileMode::Executable,
+                    }
+                }
+            };
+            tree_builder.set_or_remove(path, Merge::normal(value));
+        }
+    }
+    Ok(tree_builder.write_tree(store)?)
+}
+
+fn parse_path(path: &str) -> Result<RepoPathBuf, PatchImportError> {
+    RepoPathBuf::from_relative_path(path).map_err(|_| PatchImportError::InvalidPath {
+        path: path.to_owned(),
+    })
+}
+
+/// Reads the contents and mode of the file at `path`.
+fn read_file(
+    store: &Store,
+    tree: &MergedTree,
+    path: &RepoPathBuf,
+) -> Result<(Vec<u8>, Option<PatchFileMode>), PatchImportError> {
+    let path_string = || path.as_internal_file_string().to_owned();
+    let value = tree.path_value(path)?;
+    match value.into_resolved() {
+        Ok(Some(TreeValue::File { id, executable })) => {
+            let mut contents = vec![];
+            store
+                .read_file(path, &id)?
+                .read_to_end(&mut contents)
+                .map_err(|err| BackendError::ReadFile {
+                    path: path.clone(),
+                    id: id.clone(),
+                    source: err.into(),
+                })?;
+            let mode = if executable {
+                PatchFileMode::Executable
+            } else {
+                PatchFileMode::Regular
+            };
+            Ok((contents, Some(mode)))
+        }
+        Ok(Some(TreeValue::Symlink(id))) => {
+            let target = store.read_symlink(path, &id)?;
+            Ok((target.into_bytes(), Some(PatchFileMode::Symlink)))
+        }
+        Ok(None) => Err(PatchImportError::Missing {
+            path: path_string(),
+        }),
+        Ok(Some(_)) | Err(_) => Err(PatchImportError::UnsupportedType {
+            path: path_string(),
+        }),
+    }
+}
+
+/// Finds a `Change-Id` trailer containing a valid change ID. Returns the ID
+/// and the trailer line.
+fn find_change_id_trailer(store: &Store, description: &str) -> Option<(ChangeId, String)> {
+    description::trailers(description)
+        .into_iter()
+        .rev()
+        .filter(|trailer| trailer.key.eq_ignore_ascii_case("Change-Id"))
+        .find_map(|trailer| {
+            let hex = to_forward_hex(&trailer.value)?;
+            let change_id = ChangeId::try_from_hex(&hex).ok()?;
+            (change_id.as_bytes().len() == store.change_id_length())
+                .then(|| (change_id, format!("{}: {}", trailer.key, trailer.value)))
+        })
+}
+
+/// Removes the last line equal to `line` from the `description`, along with
+/// any trailing blank lines.
+fn remove_line(description: &str, line: &str) -> String {
+    let mut lines = description.lines().collect_vec();
+    if let Some(pos) = lines.iter().rposition(|l| l.trim() == line) {
+        lines.remove(pos);
+    }
+    let text = lines.join("\n");
+    format!("{}\n", text.trim_end())
+}
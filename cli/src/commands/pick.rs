@@ -0,0 +1,137 @@
+// Copyright 2025 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::io::Write as _;
+
+use clap_complete::ArgValueCandidates;
+use clap_complete::ArgValueCompleter;
+use indoc::formatdoc;
+use jj_lib::object_id::ObjectId as _;
+use tracing::instrument;
+
+use crate::cli_util::CommandHelper;
+use crate::cli_util::RevisionArg;
+use crate::command_error::CommandError;
+use crate::complete;
+use crate::ui::Ui;
+
+/// Apply the changes to some paths from a revision onto another revision
+///
+/// The changes made in the source revision (`-r`) compared to its parent(s) are
+/// merged into the destination (`--into`), which defaults to the working copy.
+/// Unlike `jj duplicate`, no new revision is created, and only the changes to
+/// the given paths are applied. Unlike `jj restore`, changes made in the
+/// destination to the same paths are preserved unless they conflict.
+///
+/// If the changes can't be applied cleanly, the destination will contain
+/// conflicts.
+#[derive(clap::Args, Clone, Debug)]
+pub(crate) struct PickArgs {
+    /// Apply only the changes to these paths (instead of all paths)
+    #[arg(
+        value_name = "FILESETS",
+        value_hint = clap::ValueHint::AnyPath,
+        add = ArgValueCompleter::new(complete::modified_revision_files),
+    )]
+    paths: Vec<String>,
+    /// The revision to take the changes from
+    #[arg(
+        long, short,
+        value_name = "REVSET",
+        add = ArgValueCandidates::new(complete::all_revisions),
+    )]
+    revision: RevisionArg,
+    /// The revision to apply the changes to
+    #[arg(
+        long, short = 't',
+        default_value = "@",
+        value_name = "REVSET",
+        add = ArgValueCandidates::new(complete::mutable_revisions),
+    )]
+    into: RevisionArg,
+    /// Interactively choose which parts to apply
+    #[arg(long, short)]
+    interactive: bool,
+    /// Specify diff editor to be used (implies --interactive)
+    #[arg(long, value_name = "NAME")]
+    tool: Option<String>,
+}
+
+#[instrument(skip_all)]
+pub(crate) fn cmd_pick(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    args: &PickArgs,
+) -> Result<(), CommandError> {
+    let mut workspace_command = command.workspace_helper(ui)?;
+    let source_commit = workspace_command.resolve_single_rev(ui, &args.revision)?;
+    let destination_commit = workspace_command.resolve_single_rev(ui, &args.into)?;
+    workspace_command.check_rewritable([destination_commit.id()])?;
+
+    let matcher = workspace_command
+        .parse_file_patterns(ui, &args.paths)?
+        .to_matcher();
+    let diff_selector =
+        workspace_command.diff_selector(ui, args.tool.as_deref(), args.interactive)?;
+    let base_tree = source_commit.parent_tree(workspace_command.repo().as_ref())?;
+    let source_tree = source_commit.tree()?;
+    let format_instructions = || {
+        formatdoc! {"
+            You are picking changes from: {source_commit}
+            into commit: {destination_commit}
+
+            The left side of the diff shows the contents of the parent(s) of the
+            source commit. The right side initially shows the contents of the source
+            commit. Adjust the right side until the diff shows the changes you want
+            to apply to the destination commit.
+            ",
+            source_commit = workspace_command.format_commit_summary(&source_commit),
+            destination_commit = workspace_command.format_commit_summary(&destination_commit),
+        }
+    };
+    let selected_tree_id =
+        diff_selector.select(&base_tree, &source_tree, &matcher, format_instructions)?;
+    let selected_tree = base_tree.store().get_root_tree(&selected_tree_id)?;
+    let destination_tree = destination_commit.tree()?;
+    let new_tree = destination_tree.merge(&base_tree, &selected_tree)?;
+    if new_tree.id() == *destination_commit.tree_id() {
+        writeln!(ui.status(), "Nothing changed.")?;
+        return Ok(());
+    }
+
+    let mut tx = workspace_command.start_transaction();
+    let new_commit = tx
+        .repo_mut()
+        .rewrite_commit(&destination_commit)
+        .set_tree_id(new_tree.id())
+        .write()?;
+    let num_rebased = tx.repo_mut().rebase_descendants()?;
+    if let Some(mut formatter) = ui.status_formatter() {
+        write!(formatter, "Created ")?;
+        tx.write_commit_summary(formatter.as_mut(), &new_commit)?;
+        writeln!(formatter)?;
+        if num_rebased > 0 {
+            writeln!(formatter, "Rebased {num_rebased} descendant commits")?;
+        }
+    }
+    tx.finish(
+        ui,
+        format!(
+            "pick changes from commit {} into commit {}",
+            source_commit.id().hex(),
+            destination_commit.id().hex()
+        ),
+    )?;
+    Ok(())
+}
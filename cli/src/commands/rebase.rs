@@ -27,6 +27,7 @@ use jj_lib::repo::Repo;
 use jj_lib::revset::RevsetExpression;
 use jj_lib::revset::RevsetIteratorExt;
 use jj_lib::rewrite::move_commits;
+use jj_lib::rewrite::DropEmptyPolicy;
 use jj_lib::rewrite::EmptyBehaviour;
 use jj_lib::rewrite::MoveCommitsStats;
 use jj_lib::rewrite::MoveCommitsTarget;
@@ -43,6 +44,7 @@ use crate::command_error::cli_error;
 use crate::command_error::user_error;
 use crate::command_error::CommandError;
 use crate::complete;
+use crate::progress::signing_progress;
 use crate::ui::Ui;
 
 /// Move revisions to different parent(s)
@@ -323,8 +325,17 @@ pub(crate) struct RebaseArgs {
     /// abandoned. It will not be abandoned if it was already empty before the
     /// rebase. Will never skip merge commits with multiple non-empty
     /// parents.
+    ///
+    /// This is the default if `rewrite.drop-empty = "always"` is set.
     #[arg(long)]
     skip_emptied: bool,
+
+    /// Keep the committer timestamps of the rebased commits
+    ///
+    /// This is the same as setting `rewrite.update-committer-timestamp =
+    /// false`.
+    #[arg(long)]
+    keep_timestamps: bool,
 }
 
 #[derive(clap::Args, Clone, Debug)]
@@ -375,17 +386,19 @@ pub(crate) fn cmd_rebase(
         ));
     }
 
+    let mut workspace_command = command.workspace_helper(ui)?;
+    let drop_empty = workspace_command.settings().drop_empty();
     let rebase_options = RebaseOptions {
-        empty: match args.skip_emptied {
-            true => EmptyBehaviour::AbandonNewlyEmpty,
-            false => EmptyBehaviour::Keep,
+        empty: if args.skip_emptied || drop_empty == DropEmptyPolicy::Always {
+            EmptyBehaviour::AbandonNewlyEmpty
+        } else {
+            EmptyBehaviour::Keep
         },
         rewrite_refs: RewriteRefsOptions {
             delete_abandoned_bookmarks: false,
         },
         simplify_ancestor_merge: false,
     };
-    let mut workspace_command = command.workspace_helper(ui)?;
     if !args.revisions.is_empty() {
         rebase_revisions(
             ui,
@@ -571,13 +584,16 @@ fn rebase_descendants_transaction(
         )
     };
 
-    let stats = move_commits(
-        tx.repo_mut(),
-        new_parent_ids,
-        new_children,
-        &MoveCommitsTarget::Roots(target_roots),
-        rebase_options,
-    )?;
+    let stats = {
+        let _progress = signing_progress(ui, tx.repo().store());
+        move_commits(
+            tx.repo_mut(),
+            new_parent_ids,
+            new_children,
+            &MoveCommitsTarget::Roots(target_roots),
+            rebase_options,
+        )?
+    };
     print_move_commits_stats(ui, &stats)?;
     tx.finish(ui, tx_description)
 }
@@ -607,13 +623,16 @@ fn rebase_revisions_transaction(
         )
     };
 
-    let stats = move_commits(
-        tx.repo_mut(),
-        new_parent_ids,
-        new_children,
-        &MoveCommitsTarget::Commits(target_commits),
-        rebase_options,
-    )?;
+    let stats = {
+        let _progress = signing_progress(ui, tx.repo().store());
+        move_commits(
+            tx.repo_mut(),
+            new_parent_ids,
+            new_children,
+            &MoveCommitsTarget::Commits(target_commits),
+            rebase_options,
+        )?
+    };
     print_move_commits_stats(ui, &stats)?;
     tx.finish(ui, tx_description)
 }
@@ -116,6 +116,18 @@ pub(crate) fn cmd_resolve(
         .rewrite_commit(&commit)
         .set_tree_id(new_tree_id)
         .write()?;
+    let new_tree = new_commit.tree()?;
+    let mut num_resolved = 0;
+    for path in &repo_paths {
+        if new_tree.path_value(path)?.is_resolved() {
+            num_resolved += 1;
+        }
+    }
+    writeln!(
+        ui.status(),
+        "Resolved conflicts in {num_resolved} of {} files",
+        repo_paths.len()
+    )?;
     tx.finish(
         ui,
         format!("Resolve conflicts in commit {}", commit.id().hex()),
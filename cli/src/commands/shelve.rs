@@ -0,0 +1,165 @@
+// Copyright 2025 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::io::Write as _;
+
+use clap_complete::ArgValueCompleter;
+use indoc::formatdoc;
+use jj_lib::repo::Repo as _;
+use tracing::instrument;
+
+use crate::cli_util::CommandHelper;
+use crate::cli_util::RevisionArg;
+use crate::cli_util::WorkspaceCommandHelper;
+use crate::command_error::user_error;
+use crate::command_error::CommandError;
+use crate::complete;
+use crate::ui::Ui;
+
+/// Move changes out of the working copy into a shelf
+///
+/// The selected changes are removed from the working-copy commit and saved in
+/// a hidden commit on top of its parents, similar to `git stash`. Use `jj
+/// unshelve` to apply them to the working copy again.
+///
+/// Shelves are stored in the repo view, so `jj undo` restores them too.
+#[derive(clap::Args, Clone, Debug)]
+pub(crate) struct ShelveArgs {
+    /// Shelve only these paths (instead of all paths)
+    #[arg(
+        value_name = "FILESETS",
+        value_hint = clap::ValueHint::AnyPath,
+        add = ArgValueCompleter::new(complete::modified_files),
+    )]
+    paths: Vec<String>,
+    /// Name of the shelf
+    ///
+    /// Defaults to the smallest positive number not used by another shelf.
+    #[arg(long, short)]
+    name: Option<String>,
+    /// Description of the shelved changes
+    ///
+    /// Defaults to the description of the working-copy commit.
+    #[arg(long = "message", short, value_name = "MESSAGE")]
+    message: Option<String>,
+    /// Interactively choose which changes to shelve
+    #[arg(long, short)]
+    interactive: bool,
+    /// Specify diff editor to be used (implies --interactive)
+    #[arg(long, value_name = "NAME")]
+    tool: Option<String>,
+    /// List the shelves instead of shelving changes
+    #[arg(
+        long, short,
+        conflicts_with_all = ["paths", "name", "message", "interactive", "tool"],
+    )]
+    list: bool,
+}
+
+#[instrument(skip_all)]
+pub(crate) fn cmd_shelve(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    args: &ShelveArgs,
+) -> Result<(), CommandError> {
+    let mut workspace_command = command.workspace_helper(ui)?;
+    if args.list {
+        return list_shelves(ui, &workspace_command);
+    }
+
+    let wc_commit = workspace_command.resolve_single_rev(ui, &RevisionArg::AT)?;
+    workspace_command.check_rewritable([wc_commit.id()])?;
+    let view = workspace_command.repo().view();
+    let name = if let Some(name) = &args.name {
+        if view.get_shelf(name).is_some() {
+            return Err(user_error(format!("Shelf {name} already exists")));
+        }
+        name.clone()
+    } else {
+        (1..)
+            .map(|n: u32| n.to_string())
+            .find(|name| view.get_shelf(name).is_none())
+            .unwrap()
+    };
+
+    let matcher = workspace_command
+        .parse_file_patterns(ui, &args.paths)?
+        .to_matcher();
+    let diff_selector =
+        workspace_command.diff_selector(ui, args.tool.as_deref(), args.interactive)?;
+    let parent_tree = wc_commit.parent_tree(workspace_command.repo().as_ref())?;
+    let wc_tree = wc_commit.tree()?;
+    let format_instructions = || {
+        formatdoc! {"
+            You are shelving changes from: {wc_commit}
+
+            The diff initially shows all changes in the working copy. Adjust the right
+            side until it shows the changes you want to shelve.
+            ",
+            wc_commit = workspace_command.format_commit_summary(&wc_commit),
+        }
+    };
+    let selected_tree_id =
+        diff_selector.select(&parent_tree, &wc_tree, &matcher, format_instructions)?;
+    if selected_tree_id == parent_tree.id() {
+        writeln!(ui.status(), "No changes to shelve.")?;
+        return Ok(());
+    }
+    let selected_tree = workspace_command
+        .repo()
+        .store()
+        .get_root_tree(&selected_tree_id)?;
+    // Remove the shelved changes from the working copy.
+    let remaining_tree = wc_tree.merge(&selected_tree, &parent_tree)?;
+
+    let mut tx = workspace_command.start_transaction();
+    let description = match &args.message {
+        Some(message) => message.clone(),
+        None => wc_commit.description().to_owned(),
+    };
+    let shelf_commit = tx
+        .repo_mut()
+        .new_commit(wc_commit.parent_ids().to_vec(), selected_tree_id)
+        .set_description(description)
+        .write()?;
+    // The shelved changes shouldn't show up in the log.
+    tx.repo_mut().remove_head(shelf_commit.id());
+    tx.repo_mut()
+        .set_shelf(&name, Some(shelf_commit.id().clone()));
+    tx.repo_mut()
+        .rewrite_commit(&wc_commit)
+        .set_tree_id(remaining_tree.id())
+        .write()?;
+    tx.repo_mut().rebase_descendants()?;
+    writeln!(ui.status(), "Shelved changes as {name}")?;
+    tx.finish(ui, format!("shelve changes as {name}"))?;
+    Ok(())
+}
+
+fn list_shelves(
+    ui: &mut Ui,
+    workspace_command: &WorkspaceCommandHelper,
+) -> Result<(), CommandError> {
+    let repo = workspace_command.repo();
+    ui.request_pager();
+    let mut formatter = ui.stdout_formatter();
+    for (name, commit_id) in repo.view().shelves() {
+        let commit = repo.store().get_commit(commit_id)?;
+        write!(formatter.labeled("shelf"), "{name}")?;
+        write!(formatter, ": ")?;
+        workspace_command.write_commit_summary(formatter.as_mut(), &commit)?;
+        writeln!(formatter)?;
+    }
+    Ok(())
+}
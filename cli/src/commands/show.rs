@@ -21,6 +21,7 @@ use crate::cli_util::RevisionArg;
 use crate::command_error::CommandError;
 use crate::complete;
 use crate::diff_util::DiffFormatArgs;
+use crate::diff_util::DiffMerges;
 use crate::ui::Ui;
 
 /// Show commit description and changes in a revision
@@ -48,6 +49,12 @@ pub(crate) struct ShowArgs {
     ///     https://jj-vcs.github.io/jj/latest/templates/
     #[arg(long, short = 'T', add = ArgValueCandidates::new(complete::template_aliases))]
     template: Option<String>,
+    /// How to show the changes of merge commits
+    ///
+    /// By default, merge commits are compared to the merged contents of their
+    /// parents.
+    #[arg(long, value_enum, value_name = "MODE")]
+    diff_merges: Option<DiffMerges>,
     #[command(flatten)]
     format: DiffFormatArgs,
 }
@@ -65,7 +72,9 @@ pub(crate) fn cmd_show(
         None => workspace_command.settings().get_string("templates.show")?,
     };
     let template = workspace_command.parse_commit_template(ui, &template_string)?;
-    let diff_renderer = workspace_command.diff_renderer_for(&args.format)?;
+    let diff_renderer = workspace_command
+        .diff_renderer_for(&args.format)?
+        .with_diff_merges(args.diff_merges);
     ui.request_pager();
     let mut formatter = ui.stdout_formatter();
     let formatter = formatter.as_mut();
@@ -25,6 +25,7 @@ use crate::cli_util::RevisionArg;
 use crate::command_error::user_error_with_hint;
 use crate::command_error::CommandError;
 use crate::complete;
+use crate::progress::signing_progress;
 use crate::ui::Ui;
 
 /// Cryptographically sign a revision
@@ -86,6 +87,7 @@ pub fn cmd_sign(ui: &mut Ui, command: &CommandHelper, args: &SignArgs) -> Result
     let mut signed_commits = vec![];
     let mut num_reparented = 0;
 
+    let progress = signing_progress(ui, tx.repo().store());
     tx.repo_mut().transform_descendants(
         to_sign.iter().ids().cloned().collect_vec(),
         |rewriter| {
@@ -110,6 +112,7 @@ pub fn cmd_sign(ui: &mut Ui, command: &CommandHelper, args: &SignArgs) -> Result
             Ok(())
         },
     )?;
+    drop(progress);
 
     if let Some(mut formatter) = ui.status_formatter() {
         if !signed_commits.is_empty() {
@@ -17,8 +17,15 @@ mod list;
 mod reset;
 mod set;
 
+use std::path::Path;
+
 use clap::Subcommand;
+use itertools::Itertools as _;
+use jj_lib::config::ConfigGetResultExt as _;
+use jj_lib::op_store::WorkspaceId;
 use jj_lib::repo_path::RepoPathBuf;
+use jj_lib::settings::UserSettings;
+use jj_lib::workspace::WorkspaceSparsePatterns;
 use tracing::instrument;
 
 use self::edit::cmd_sparse_edit;
@@ -32,7 +39,9 @@ use self::set::SparseSetArgs;
 use crate::cli_util::print_checkout_stats;
 use crate::cli_util::CommandHelper;
 use crate::cli_util::WorkspaceCommandHelper;
+use crate::command_error::config_error_with_message;
 use crate::command_error::internal_error_with_message;
+use crate::command_error::user_error;
 use crate::command_error::CommandError;
 use crate::ui::Ui;
 
@@ -70,10 +79,50 @@ fn update_sparse_patterns_with(
     let new_patterns = f(ui, locked_ws.locked_wc().sparse_patterns()?)?;
     let stats = locked_ws
         .locked_wc()
-        .set_sparse_patterns(new_patterns, &checkout_options)
+        .set_sparse_patterns(new_patterns.clone(), &checkout_options)
         .map_err(|err| internal_error_with_message("Failed to update working copy paths", err))?;
     let operation_id = locked_ws.locked_wc().old_operation_id().clone();
     locked_ws.finish(operation_id)?;
+    record_sparse_patterns(
+        workspace_command.repo_path(),
+        workspace_command.workspace_id(),
+        &new_patterns,
+    )?;
     print_checkout_stats(ui, stats, &wc_commit)?;
     Ok(())
 }
+
+/// Records the sparse patterns of the workspace in the repo so they can be
+/// referenced by `workspace_sparse()` from other workspaces.
+pub(crate) fn record_sparse_patterns(
+    repo_path: &Path,
+    workspace_id: &WorkspaceId,
+    patterns: &[RepoPathBuf],
+) -> Result<(), CommandError> {
+    let mut workspace_patterns = WorkspaceSparsePatterns::load(repo_path)?;
+    workspace_patterns.record(workspace_id, patterns);
+    workspace_patterns.save(repo_path)?;
+    Ok(())
+}
+
+/// Returns the patterns of the sparse profile defined as
+/// `sparse.profiles.<name>`.
+pub(crate) fn sparse_profile_patterns(
+    settings: &UserSettings,
+    name: &str,
+) -> Result<Vec<RepoPathBuf>, CommandError> {
+    let Some(paths) = settings
+        .get::<Vec<String>>(["sparse", "profiles", name])
+        .optional()?
+    else {
+        return Err(user_error(format!("No such sparse profile: {name}")));
+    };
+    paths
+        .iter()
+        .map(|path| {
+            RepoPathBuf::from_relative_path(path).map_err(|err| {
+                config_error_with_message(format!("Invalid path in sparse profile {name}"), err)
+            })
+        })
+        .process_results(|patterns| patterns.sorted_unstable().dedup().collect())
+}
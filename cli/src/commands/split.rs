@@ -32,6 +32,7 @@ use crate::command_error::CommandError;
 use crate::complete;
 use crate::description_util::description_template;
 use crate::description_util::edit_description;
+use crate::description_util::DescriptionChecks;
 use crate::ui::Ui;
 
 /// Split a revision in two
@@ -73,6 +74,9 @@ pub(crate) struct SplitArgs {
     /// child
     #[arg(long, short)]
     parallel: bool,
+    /// Don't check the descriptions against the `commit.checks` rules
+    #[arg(long)]
+    no_verify: bool,
     /// Files matching any of these filesets are put in the first commit
     #[arg(
         value_name = "FILESETS",
@@ -139,6 +143,7 @@ pub(crate) fn cmd_split(
         parallel,
     } = args.resolve(ui, &workspace_command)?;
     let text_editor = workspace_command.text_editor()?;
+    let description_checks = DescriptionChecks::from_settings(workspace_command.settings())?;
     let mut tx = workspace_command.start_transaction();
 
     // Prompt the user to select the changes they want for the first commit.
@@ -159,6 +164,7 @@ pub(crate) fn cmd_split(
             &temp_commit,
         )?;
         let description = edit_description(&text_editor, &template)?;
+        description_checks.check(ui, &description, args.no_verify)?;
         commit_builder.set_description(description);
         commit_builder.write(tx.repo_mut())?
     };
@@ -201,6 +207,7 @@ pub(crate) fn cmd_split(
             )?;
             edit_description(&text_editor, &template)?
         };
+        description_checks.check(ui, &description, args.no_verify)?;
         commit_builder.set_description(description);
         commit_builder.write(tx.repo_mut())?
     };
@@ -23,6 +23,8 @@ use jj_lib::object_id::ObjectId;
 use jj_lib::repo::Repo;
 use jj_lib::rewrite;
 use jj_lib::rewrite::CommitWithSelection;
+use jj_lib::rewrite::DropEmptyPolicy;
+use jj_lib::rewrite::SquashDescriptionMode;
 use tracing::instrument;
 
 use crate::cli_util::CommandHelper;
@@ -37,7 +39,6 @@ use crate::description_util::combine_messages_for_editing;
 use crate::description_util::description_template;
 use crate::description_util::edit_description;
 use crate::description_util::join_message_paragraphs;
-use crate::description_util::try_combine_messages;
 use crate::ui::Ui;
 
 /// Move changes from a revision into another revision
@@ -57,7 +58,9 @@ use crate::ui::Ui;
 ///
 /// If the source was abandoned and both the source and destination had a
 /// non-empty description, you will be asked for the combined description. If
-/// either was empty, then the other one will be used.
+/// either was empty, then the other one will be used. The descriptions can
+/// instead be combined automatically by setting `squash.combine-descriptions`
+/// to `"concatenate"` or `"destination"`.
 ///
 /// If a working-copy commit gets abandoned, it will be given a new, empty
 /// commit. This is true in general; it is not specific to this command.
@@ -110,8 +113,16 @@ pub(crate) struct SquashArgs {
     )]
     paths: Vec<String>,
     /// The source revision will not be abandoned
+    ///
+    /// This overrides `rewrite.drop-empty = "always"`.
     #[arg(long, short)]
     keep_emptied: bool,
+    /// Keep the committer timestamps of the rewritten revisions
+    ///
+    /// This is the same as setting `rewrite.update-committer-timestamp =
+    /// false`.
+    #[arg(long)]
+    keep_timestamps: bool,
 }
 
 #[instrument(skip_all)]
@@ -162,6 +173,10 @@ pub(crate) fn cmd_squash(
         workspace_command.diff_selector(ui, args.tool.as_deref(), args.interactive)?;
     let text_editor = workspace_command.text_editor()?;
     let description = SquashedDescription::from_args(args);
+    let settings = workspace_command.settings();
+    let description_mode: SquashDescriptionMode = settings.get("squash.combine-descriptions")?;
+    let description_separator = settings.get_string("squash.description-separator")?;
+    let drop_empty = settings.drop_empty();
     workspace_command
         .check_rewritable(sources.iter().chain(std::iter::once(&destination)).ids())?;
 
@@ -172,17 +187,19 @@ pub(crate) fn cmd_squash(
         tx.repo_mut(),
         &source_commits,
         &destination,
-        args.keep_emptied,
+        args.keep_emptied || drop_empty == DropEmptyPolicy::Never,
     )? {
+        let combined_description =
+            squashed.combine_descriptions(&destination, description_mode, &description_separator);
         let mut commit_builder = squashed.commit_builder.detach();
         let new_description = match description {
             SquashedDescription::Exact(description) => description,
             SquashedDescription::UseDestination => destination.description().to_owned(),
             SquashedDescription::Combine => {
-                let abandoned_commits = &squashed.abandoned_commits;
-                if let Some(description) = try_combine_messages(abandoned_commits, &destination) {
+                if let Some(description) = combined_description {
                     description
                 } else {
+                    let abandoned_commits = &squashed.abandoned_commits;
                     let intro = "Enter a description for the combined commit.";
                     let combined = combine_messages_for_editing(abandoned_commits, &destination);
                     // It's weird that commit.description() contains "JJ: " lines, but works.
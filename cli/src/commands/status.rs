@@ -12,32 +12,47 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::collections::BTreeSet;
 use std::io;
 
+use futures::StreamExt as _;
 use itertools::Itertools;
+use jj_lib::commit::Commit;
 use jj_lib::copies::CopyRecords;
+use jj_lib::local_working_copy::DetectedChanges;
+use jj_lib::matchers::Matcher;
+use jj_lib::merged_tree::MergedTree;
+use jj_lib::merged_tree::TreeDiffEntry;
 use jj_lib::repo::Repo;
+use jj_lib::repo_path::RepoPathBuf;
+use jj_lib::repo_path::RepoPathUiConverter;
 use jj_lib::revset::RevsetExpression;
 use jj_lib::revset::RevsetFilterPredicate;
+use jj_lib::working_copy::SnapshotStats;
+use pollster::FutureExt as _;
 use tracing::instrument;
 
 use crate::cli_util::print_conflicted_paths;
 use crate::cli_util::print_snapshot_stats;
 use crate::cli_util::CommandHelper;
+use crate::cli_util::WorkspaceCommandHelper;
 use crate::command_error::CommandError;
 use crate::diff_util::get_copy_records;
 use crate::diff_util::DiffFormat;
+use crate::formatter::Formatter;
 use crate::ui::Ui;
 
 /// Show high-level repo status
 ///
 /// This includes:
 ///
+///  * Unresolved conflicts in the working copy commit and [conflicted
+///    bookmarks], which are listed first
 ///  * The working copy commit and its (first) parent, and a summary of the
 ///    changes between them
-///  * [Conflicted bookmarks]
+///  * Hints about the next steps
 ///
-/// [Conflicted bookmarks]:
+/// [conflicted bookmarks]:
 ///     https://jj-vcs.github.io/jj/latest/bookmarks/#conflicts
 #[derive(clap::Args, Clone, Debug)]
 #[command(visible_alias = "st")]
@@ -53,7 +68,26 @@ pub(crate) fn cmd_status(
     command: &CommandHelper,
     args: &StatusArgs,
 ) -> Result<(), CommandError> {
-    let (workspace_command, snapshot_stats) = command.workspace_helper_with_stats(ui)?;
+    let (workspace_command, snapshot_stats, detected_changes) =
+        if command.settings().get_bool("snapshot.defer-in-status")? {
+            let workspace_command = command.workspace_helper_no_snapshot(ui)?;
+            match workspace_command.detect_working_copy_changes(ui)? {
+                Some(changes) => {
+                    let stats = SnapshotStats {
+                        untracked_paths: changes.untracked_paths.clone(),
+                        ..Default::default()
+                    };
+                    (workspace_command, stats, Some(changes))
+                }
+                None => {
+                    let (workspace_command, stats) = command.workspace_helper_with_stats(ui)?;
+                    (workspace_command, stats, None)
+                }
+            }
+        } else {
+            let (workspace_command, stats) = command.workspace_helper_with_stats(ui)?;
+            (workspace_command, stats, None)
+        };
     print_snapshot_stats(
         ui,
         &snapshot_stats,
@@ -71,58 +105,9 @@ pub(crate) fn cmd_status(
     let mut formatter = ui.stdout_formatter();
     let formatter = formatter.as_mut();
 
+    // Conflicts are printed first since they block most other work.
+    let mut has_conflicted_ancestors = false;
     if let Some(wc_commit) = &maybe_wc_commit {
-        let parent_tree = wc_commit.parent_tree(repo.as_ref())?;
-        let tree = wc_commit.tree()?;
-
-        let wc_has_changes = tree.id() != parent_tree.id();
-        let wc_has_untracked = !snapshot_stats.untracked_paths.is_empty();
-        if !wc_has_changes && !wc_has_untracked {
-            writeln!(formatter, "The working copy has no changes.")?;
-        } else {
-            if wc_has_changes {
-                writeln!(formatter, "Working copy changes:")?;
-                let mut copy_records = CopyRecords::default();
-                for parent in wc_commit.parent_ids() {
-                    let records = get_copy_records(repo.store(), parent, wc_commit.id(), &matcher)?;
-                    copy_records.add_records(records)?;
-                }
-                let diff_renderer = workspace_command.diff_renderer(vec![DiffFormat::Summary]);
-                let width = ui.term_width();
-                diff_renderer.show_diff(
-                    ui,
-                    formatter,
-                    &parent_tree,
-                    &tree,
-                    &matcher,
-                    &copy_records,
-                    width,
-                )?;
-            }
-
-            if wc_has_untracked {
-                writeln!(formatter, "Untracked paths:")?;
-                formatter.with_label("diff", |formatter| {
-                    for path in snapshot_stats.untracked_paths.keys() {
-                        let ui_path = workspace_command.path_converter().format_file_path(path);
-                        writeln!(formatter.labeled("untracked"), "? {ui_path}")?;
-                    }
-                    io::Result::Ok(())
-                })?;
-            }
-        }
-
-        let template = workspace_command.commit_summary_template();
-        write!(formatter, "Working copy : ")?;
-        formatter.with_label("working_copy", |fmt| template.format(wc_commit, fmt))?;
-        writeln!(formatter)?;
-        for parent in wc_commit.parents() {
-            let parent = parent?;
-            write!(formatter, "Parent commit: ")?;
-            template.format(&parent, formatter)?;
-            writeln!(formatter)?;
-        }
-
         if wc_commit.has_conflict()? {
             // TODO: Conflicts should also be filtered by the `matcher`. See the related
             // TODO on `MergedTree::conflicts()`.
@@ -147,23 +132,10 @@ pub(crate) fn cmd_status(
                 .evaluate_to_commit_ids()?
                 .try_collect()?;
 
+            has_conflicted_ancestors = !ancestors_conflicts.is_empty();
             workspace_command.report_repo_conflicts(formatter, repo, ancestors_conflicts)?;
-        } else {
-            for parent in wc_commit.parents() {
-                let parent = parent?;
-                if parent.has_conflict()? {
-                    writeln!(
-                        formatter.labeled("hint").with_heading("Hint: "),
-                        "Conflict in parent commit has been resolved in working copy"
-                    )?;
-                    break;
-                }
-            }
         }
-    } else {
-        writeln!(formatter, "No working copy")?;
     }
-
     let conflicted_local_bookmarks = repo
         .view()
         .local_bookmarks()
@@ -208,5 +180,281 @@ pub(crate) fn cmd_status(
         )?;
     }
 
+    if let Some(wc_commit) = &maybe_wc_commit {
+        let parent_tree = wc_commit.parent_tree(repo.as_ref())?;
+        let tree = wc_commit.tree()?;
+
+        let has_detected_changes = detected_changes.as_ref().is_some_and(|changes| {
+            changes
+                .changed_paths
+                .iter()
+                .chain(&changes.deleted_paths)
+                .any(|path| matcher.matches(path))
+        });
+        let wc_has_changes = tree.id() != parent_tree.id() || has_detected_changes;
+        let wc_has_untracked = !snapshot_stats.untracked_paths.is_empty();
+        if !wc_has_changes && !wc_has_untracked {
+            writeln!(formatter, "The working copy has no changes.")?;
+        } else {
+            if let Some(changes) = detected_changes.as_ref().filter(|_| has_detected_changes) {
+                writeln!(formatter, "Working copy changes (not snapshotted yet):")?;
+                show_summary_with_detected_changes(
+                    formatter,
+                    &parent_tree,
+                    &tree,
+                    changes,
+                    &matcher,
+                    workspace_command.path_converter(),
+                )?;
+            } else if wc_has_changes {
+                writeln!(formatter, "Working copy changes:")?;
+                let mut copy_records = CopyRecords::default();
+                for parent in wc_commit.parent_ids() {
+                    let records = get_copy_records(repo.store(), parent, wc_commit.id(), &matcher)?;
+                    copy_records.add_records(records)?;
+                }
+                let diff_renderer = workspace_command.diff_renderer(vec![DiffFormat::Summary]);
+                let width = ui.term_width();
+                diff_renderer.show_diff(
+                    ui,
+                    formatter,
+                    &parent_tree,
+                    &tree,
+                    &matcher,
+                    &copy_records,
+                    width,
+                )?;
+            }
+
+            if wc_has_untracked {
+                writeln!(formatter, "Untracked paths:")?;
+                formatter.with_label("diff", |formatter| {
+                    for path in snapshot_stats.untracked_paths.keys() {
+                        let ui_path = workspace_command.path_converter().format_file_path(path);
+                        writeln!(formatter.labeled("untracked"), "? {ui_path}")?;
+                    }
+                    io::Result::Ok(())
+                })?;
+            }
+        }
+
+        let template = workspace_command.commit_summary_template();
+        write!(formatter, "Working copy : ")?;
+        formatter.with_label("working_copy", |fmt| template.format(wc_commit, fmt))?;
+        writeln!(formatter)?;
+        for parent in wc_commit.parents() {
+            let parent = parent?;
+            write!(formatter, "Parent commit: ")?;
+            template.format(&parent, formatter)?;
+            writeln!(formatter)?;
+        }
+
+        if !wc_commit.has_conflict()? {
+            for parent in wc_commit.parents() {
+                let parent = parent?;
+                if parent.has_conflict()? {
+                    writeln!(
+                        formatter.labeled("hint").with_heading("Hint: "),
+                        "Conflict in parent commit has been resolved in working copy"
+                    )?;
+                    break;
+                }
+            }
+        }
+    } else {
+        writeln!(formatter, "No working copy")?;
+    }
+
+    if let Some(wc_commit) = &maybe_wc_commit {
+        let hints = collect_status_hints(
+            &workspace_command,
+            wc_commit,
+            has_conflicted_ancestors,
+            command.is_at_head_operation(),
+        )?;
+        for hint in hints {
+            if !workspace_command
+                .settings()
+                .get_bool(["hints", hint.name])?
+            {
+                continue;
+            }
+            writeln!(
+                formatter.labeled("hint").with_heading("Hint: "),
+                "{}",
+                hint.message
+            )?;
+        }
+    }
+
     Ok(())
 }
+
+/// Prints a summary of the changes from the `parent_tree` to the working copy,
+/// which is the snapshotted `tree` with the `detected` changes applied.
+///
+/// Unlike the diff summary, copies and renames aren't detected.
+fn show_summary_with_detected_changes(
+    formatter: &mut dyn Formatter,
+    parent_tree: &MergedTree,
+    tree: &MergedTree,
+    detected: &DetectedChanges,
+    matcher: &dyn Matcher,
+    path_converter: &RepoPathUiConverter,
+) -> Result<(), CommandError> {
+    let mut paths: BTreeSet<RepoPathBuf> = parent_tree
+        .diff_stream(tree, matcher)
+        .map(|TreeDiffEntry { path, .. }| path)
+        .collect()
+        .block_on();
+    paths.extend(
+        detected
+            .changed_paths
+            .iter()
+            .chain(&detected.deleted_paths)
+            .filter(|path| matcher.matches(path))
+            .cloned(),
+    );
+    formatter.with_label("diff", |formatter| {
+        for path in &paths {
+            let was_present = parent_tree.path_value(path)?.is_present();
+            let is_present = if detected.changed_paths.contains(path) {
+                true
+            } else if detected.deleted_paths.contains(path) {
+                false
+            } else {
+                tree.path_value(path)?.is_present()
+            };
+            let (label, sigil) = match (was_present, is_present) {
+                (true, true) => ("modified", 'M'),
+                (false, true) => ("added", 'A'),
+                (true, false) => ("removed", 'D'),
+                (false, false) => continue, // added and then deleted
+            };
+            let ui_path = path_converter.format_file_path(path);
+            writeln!(formatter.labeled(label), "{sigil} {ui_path}")?;
+        }
+        Ok(())
+    })
+}
+
+/// Suggested next step printed at the end of `jj status`.
+#[derive(Debug)]
+struct StatusHint {
+    /// Name of the `hints.<name>` config key that can disable this hint.
+    name: &'static str,
+    /// Hints with lower values are printed first.
+    priority: u8,
+    message: String,
+}
+
+/// Inspects the repo state around the working-copy commit and returns the
+/// applicable hints ordered by priority.
+///
+/// If `has_conflicted_ancestors` is true, instructions for resolving the
+/// conflicts have already been printed. The working copy is only checked for
+/// staleness if `at_head_operation` is true.
+fn collect_status_hints(
+    workspace_command: &WorkspaceCommandHelper,
+    wc_commit: &Commit,
+    has_conflicted_ancestors: bool,
+    at_head_operation: bool,
+) -> Result<Vec<StatusHint>, CommandError> {
+    let repo = workspace_command.repo().as_ref();
+    let mut hints = vec![];
+
+    if wc_commit.has_conflict()? && !has_conflicted_ancestors {
+        hints.push(StatusHint {
+            name: "status-conflicts",
+            priority: 0,
+            message: "Use `jj resolve` to resolve the conflicts with a merge tool, or edit the \
+                      conflict markers in the files directly."
+                .to_owned(),
+        });
+    }
+
+    let num_divergent = repo
+        .resolve_change_id(wc_commit.change_id())
+        .map_or(0, |ids| ids.len());
+    if num_divergent > 1 {
+        hints.push(StatusHint {
+            name: "status-divergent-change",
+            priority: 1,
+            message: format!(
+                "The working-copy change is divergent ({num_divergent} visible commits share its \
+                 change ID). Use `jj log` to inspect them and `jj abandon` to drop the unwanted \
+                 ones."
+            ),
+        });
+    }
+
+    // The working copy is snapshotted unless --ignore-working-copy is given, in
+    // which case its tree may lag behind the working-copy commit.
+    if at_head_operation && workspace_command.working_copy().tree_id()? != wc_commit.tree_id() {
+        hints.push(StatusHint {
+            name: "status-stale-working-copy",
+            priority: 2,
+            message: "The working copy is stale (not updated since the working-copy commit was \
+                      rewritten). Use `jj workspace update-stale` to update it."
+                .to_owned(),
+        });
+    }
+
+    #[cfg(feature = "git")]
+    {
+        let candidate_ids =
+            itertools::chain([wc_commit.id()], wc_commit.parent_ids()).collect_vec();
+        let unpushed_bookmarks = repo
+            .view()
+            .bookmarks()
+            .filter(|(_, target)| {
+                target
+                    .local_target
+                    .added_ids()
+                    .any(|id| candidate_ids.contains(&id))
+            })
+            .filter(|(_, target)| {
+                target.remote_refs.iter().any(|(remote_name, remote_ref)| {
+                    !jj_lib::git::is_special_git_remote(remote_name)
+                        && remote_ref.is_tracking()
+                        && remote_ref.target != *target.local_target
+                })
+            })
+            .map(|(name, _)| name)
+            .collect_vec();
+        if !unpushed_bookmarks.is_empty() {
+            hints.push(StatusHint {
+                name: "status-unpushed-bookmarks",
+                priority: 3,
+                message: format!(
+                    "These bookmarks have changes not pushed to their tracked remotes: {}. Use \
+                     `jj git push` to push them.",
+                    unpushed_bookmarks.join(", ")
+                ),
+            });
+        }
+
+        // Git's HEAD is detached at the working-copy commit's parent in
+        // colocated repos.
+        if workspace_command.working_copy_shared_with_git() {
+            if let [parent_id] = wc_commit.parent_ids() {
+                let has_bookmark = [wc_commit.id(), parent_id]
+                    .into_iter()
+                    .any(|id| repo.view().local_bookmarks_for_commit(id).next().is_some());
+                if parent_id != repo.store().root_commit_id() && !has_bookmark {
+                    hints.push(StatusHint {
+                        name: "status-detached-head",
+                        priority: 4,
+                        message: "Git's HEAD is detached at the parent of the working-copy \
+                                  commit, which has no bookmark. Use `jj bookmark create <name> \
+                                  -r @-` to name it for Git tools."
+                            .to_owned(),
+                    });
+                }
+            }
+        }
+    }
+
+    hints.sort_by_key(|hint| hint.priority);
+    Ok(hints)
+}
@@ -13,6 +13,7 @@
 // limitations under the License.
 
 use clap_complete::ArgValueCandidates;
+use itertools::Itertools as _;
 use jj_lib::str_util::StringPattern;
 
 use crate::cli_util::CommandHelper;
@@ -20,6 +21,8 @@ use crate::command_error::CommandError;
 use crate::commit_templater::CommitTemplateLanguage;
 use crate::commit_templater::RefName;
 use crate::complete;
+use crate::ref_list_util;
+use crate::ref_list_util::RefListItem;
 use crate::ui::Ui;
 
 /// Manage tags.
@@ -54,6 +57,9 @@ pub struct TagListArgs {
     ///     https://jj-vcs.github.io/jj/latest/templates/
     #[arg(long, short = 'T', add = ArgValueCandidates::new(complete::template_aliases))]
     template: Option<String>,
+    /// Print each tag as a JSON object, one per line
+    #[arg(long, conflicts_with = "template")]
+    json: bool,
 }
 
 pub fn cmd_tag(
@@ -86,15 +92,23 @@ fn cmd_tag_list(
             .labeled("tag_list")
     };
 
+    let tag_list_items = view
+        .tags()
+        .iter()
+        .filter(|(name, _)| {
+            args.names.is_empty() || args.names.iter().any(|pattern| pattern.matches(name))
+        })
+        .map(|(name, target)| RefListItem::local_only(RefName::local_only(name, target.clone())))
+        .collect_vec();
+
     ui.request_pager();
     let mut formatter = ui.stdout_formatter();
-
-    for (name, target) in view.tags() {
-        if !args.names.is_empty() && !args.names.iter().any(|pattern| pattern.matches(name)) {
-            continue;
+    if args.json {
+        ref_list_util::write_json_lines(formatter.as_mut(), &tag_list_items, repo.as_ref())?;
+    } else {
+        for item in &tag_list_items {
+            template.format(&item.primary, formatter.as_mut())?;
         }
-        let ref_name = RefName::local_only(name, target.clone());
-        template.format(&ref_name, formatter.as_mut())?;
     }
 
     Ok(())
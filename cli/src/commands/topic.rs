@@ -0,0 +1,253 @@
+// Copyright 2025 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::io::Write as _;
+
+use clap_complete::ArgValueCandidates;
+use itertools::Itertools as _;
+use jj_lib::commit::Commit;
+use jj_lib::repo::Repo as _;
+use jj_lib::str_util::StringPattern;
+
+use crate::cli_util::CommandHelper;
+use crate::cli_util::RevisionArg;
+use crate::command_error::user_error;
+use crate::command_error::CommandError;
+use crate::complete;
+use crate::ui::Ui;
+
+/// Manage topics
+///
+/// A topic is a label attached to one or more changes. Unlike bookmarks,
+/// topics are stored only in the repo view and are never exported to Git.
+/// Since a topic refers to changes rather than commits, it follows the
+/// changes when they are rewritten.
+///
+/// Commits labeled with a topic can be selected by the `topic(NAME)` revset.
+#[derive(clap::Subcommand, Clone, Debug)]
+pub enum TopicCommand {
+    #[command(visible_alias("d"))]
+    Delete(TopicDeleteArgs),
+    #[command(visible_alias("l"))]
+    List(TopicListArgs),
+    #[command(visible_alias("s"))]
+    Set(TopicSetArgs),
+    #[command(visible_alias("u"))]
+    Unset(TopicUnsetArgs),
+}
+
+/// Delete topics
+#[derive(clap::Args, Clone, Debug)]
+pub struct TopicDeleteArgs {
+    /// The topics to delete
+    ///
+    /// By default, the specified name matches exactly. Use `glob:` prefix to
+    /// select topics by [wildcard pattern].
+    ///
+    /// [wildcard pattern]:
+    ///     https://jj-vcs.github.io/jj/latest/revsets/#string-patterns
+    #[arg(required = true, value_parser = StringPattern::parse)]
+    names: Vec<StringPattern>,
+}
+
+/// List topics and the commits labeled with them
+#[derive(clap::Args, Clone, Debug)]
+pub struct TopicListArgs {
+    /// Show topics whose name matches
+    ///
+    /// By default, the specified name matches exactly. Use `glob:` prefix to
+    /// select topics by [wildcard pattern].
+    ///
+    /// [wildcard pattern]:
+    ///     https://jj-vcs.github.io/jj/latest/revsets/#string-patterns
+    #[arg(value_parser = StringPattern::parse)]
+    names: Vec<StringPattern>,
+}
+
+/// Label revisions with a topic
+#[derive(clap::Args, Clone, Debug)]
+pub struct TopicSetArgs {
+    /// The revisions to label
+    #[arg(
+        long, short,
+        value_name = "REVSETS",
+        default_value = "@",
+        add = ArgValueCandidates::new(complete::all_revisions),
+    )]
+    revisions: Vec<RevisionArg>,
+
+    /// The topic name
+    name: String,
+}
+
+/// Remove a topic label from revisions
+#[derive(clap::Args, Clone, Debug)]
+pub struct TopicUnsetArgs {
+    /// The revisions to remove the label from
+    #[arg(
+        long, short,
+        value_name = "REVSETS",
+        default_value = "@",
+        add = ArgValueCandidates::new(complete::all_revisions),
+    )]
+    revisions: Vec<RevisionArg>,
+
+    /// The topic name
+    name: String,
+}
+
+pub fn cmd_topic(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    subcommand: &TopicCommand,
+) -> Result<(), CommandError> {
+    match subcommand {
+        TopicCommand::Delete(args) => cmd_topic_delete(ui, command, args),
+        TopicCommand::List(args) => cmd_topic_list(ui, command, args),
+        TopicCommand::Set(args) => cmd_topic_set(ui, command, args),
+        TopicCommand::Unset(args) => cmd_topic_unset(ui, command, args),
+    }
+}
+
+fn cmd_topic_delete(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    args: &TopicDeleteArgs,
+) -> Result<(), CommandError> {
+    let mut workspace_command = command.workspace_helper(ui)?;
+    let view = workspace_command.repo().view();
+    let mut names = vec![];
+    for pattern in &args.names {
+        let matched = view
+            .topics_matching(pattern)
+            .map(|(name, _)| name.to_owned())
+            .collect_vec();
+        if matched.is_empty() {
+            writeln!(
+                ui.warning_default(),
+                "No matching topics for pattern: {pattern}"
+            )?;
+        }
+        names.extend(matched);
+    }
+    let names = names.into_iter().unique().collect_vec();
+    if names.is_empty() {
+        writeln!(ui.status(), "Nothing changed.")?;
+        return Ok(());
+    }
+
+    let mut tx = workspace_command.start_transaction();
+    for name in &names {
+        tx.repo_mut().set_topic_change_ids(name, Default::default());
+    }
+    writeln!(ui.status(), "Deleted {} topics.", names.len())?;
+    tx.finish(ui, format!("delete topic {}", names.join(", ")))?;
+    Ok(())
+}
+
+fn cmd_topic_list(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    args: &TopicListArgs,
+) -> Result<(), CommandError> {
+    let workspace_command = command.workspace_helper(ui)?;
+    let repo = workspace_command.repo();
+    let template = workspace_command.commit_summary_template();
+
+    ui.request_pager();
+    let mut formatter = ui.stdout_formatter();
+    for (name, change_ids) in repo.view().topics() {
+        if !args.names.is_empty() && !args.names.iter().any(|pattern| pattern.matches(name)) {
+            continue;
+        }
+        writeln!(formatter.labeled("topics"), "{name}:")?;
+        let commit_ids = change_ids
+            .iter()
+            .filter_map(|change_id| repo.resolve_change_id(change_id))
+            .flatten();
+        for commit_id in commit_ids {
+            let commit = repo.store().get_commit(&commit_id)?;
+            write!(formatter, "  ")?;
+            template.format(&commit, formatter.as_mut())?;
+            writeln!(formatter)?;
+        }
+    }
+    Ok(())
+}
+
+fn cmd_topic_set(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    args: &TopicSetArgs,
+) -> Result<(), CommandError> {
+    let mut workspace_command = command.workspace_helper(ui)?;
+    let commits: Vec<Commit> = workspace_command
+        .parse_union_revsets(ui, &args.revisions)?
+        .evaluate_to_commits()?
+        .try_collect()?;
+    let name = parse_topic_name(&args.name)?;
+
+    let mut tx = workspace_command.start_transaction();
+    let mut change_ids = tx.repo().get_topic(name);
+    let num_added = commits
+        .iter()
+        .filter(|commit| change_ids.insert(commit.change_id().clone()))
+        .count();
+    if num_added == 0 {
+        writeln!(ui.status(), "Nothing changed.")?;
+        return Ok(());
+    }
+    tx.repo_mut().set_topic_change_ids(name, change_ids);
+    writeln!(ui.status(), "Added {num_added} changes to topic {name}")?;
+    tx.finish(ui, format!("set topic {name}"))?;
+    Ok(())
+}
+
+fn cmd_topic_unset(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    args: &TopicUnsetArgs,
+) -> Result<(), CommandError> {
+    let mut workspace_command = command.workspace_helper(ui)?;
+    let commits: Vec<Commit> = workspace_command
+        .parse_union_revsets(ui, &args.revisions)?
+        .evaluate_to_commits()?
+        .try_collect()?;
+    let name = parse_topic_name(&args.name)?;
+
+    let mut tx = workspace_command.start_transaction();
+    let mut change_ids = tx.repo().get_topic(name);
+    let num_removed = commits
+        .iter()
+        .filter(|commit| change_ids.remove(commit.change_id()))
+        .count();
+    if num_removed == 0 {
+        writeln!(ui.status(), "Nothing changed.")?;
+        return Ok(());
+    }
+    tx.repo_mut().set_topic_change_ids(name, change_ids);
+    writeln!(
+        ui.status(),
+        "Removed {num_removed} changes from topic {name}"
+    )?;
+    tx.finish(ui, format!("unset topic {name}"))?;
+    Ok(())
+}
+
+fn parse_topic_name(name: &str) -> Result<&str, CommandError> {
+    if name.is_empty() || name.contains(char::is_whitespace) {
+        return Err(user_error(format!("Invalid topic name: {name:?}")));
+    }
+    Ok(name)
+}
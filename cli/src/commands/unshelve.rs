@@ -0,0 +1,75 @@
+// Copyright 2025 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::io::Write as _;
+
+use jj_lib::repo::Repo as _;
+use tracing::instrument;
+
+use crate::cli_util::CommandHelper;
+use crate::cli_util::RevisionArg;
+use crate::command_error::user_error_with_hint;
+use crate::command_error::CommandError;
+use crate::ui::Ui;
+
+/// Apply shelved changes to the working copy
+///
+/// The changes saved by `jj shelve` are merged into the working-copy commit,
+/// and the shelf is deleted. If the working copy has changed since the
+/// changes were shelved, they are applied with a 3-way merge, and any
+/// conflicts are recorded in the working-copy commit.
+#[derive(clap::Args, Clone, Debug)]
+pub(crate) struct UnshelveArgs {
+    /// The shelf to apply
+    name: String,
+    /// Keep the shelf after applying it
+    #[arg(long)]
+    keep: bool,
+}
+
+#[instrument(skip_all)]
+pub(crate) fn cmd_unshelve(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    args: &UnshelveArgs,
+) -> Result<(), CommandError> {
+    let mut workspace_command = command.workspace_helper(ui)?;
+    let wc_commit = workspace_command.resolve_single_rev(ui, &RevisionArg::AT)?;
+    workspace_command.check_rewritable([wc_commit.id()])?;
+    let repo = workspace_command.repo();
+    let Some(shelf_id) = repo.view().get_shelf(&args.name) else {
+        return Err(user_error_with_hint(
+            format!("No such shelf: {}", args.name),
+            "Use `jj shelve --list` to see the available shelves.",
+        ));
+    };
+    let shelf_commit = repo.store().get_commit(shelf_id)?;
+    let new_tree = wc_commit.tree()?.merge(
+        &shelf_commit.parent_tree(repo.as_ref())?,
+        &shelf_commit.tree()?,
+    )?;
+
+    let mut tx = workspace_command.start_transaction();
+    tx.repo_mut()
+        .rewrite_commit(&wc_commit)
+        .set_tree_id(new_tree.id())
+        .write()?;
+    if !args.keep {
+        tx.repo_mut().set_shelf(&args.name, None);
+    }
+    tx.repo_mut().rebase_descendants()?;
+    writeln!(ui.status(), "Applied shelf {}", args.name)?;
+    tx.finish(ui, format!("unshelve {}", args.name))?;
+    Ok(())
+}
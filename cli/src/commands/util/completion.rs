@@ -40,11 +40,20 @@ Apply it by running one of these:
      compinit
      source <(jj util completion zsh)
      ```
+
+With `--dynamic`, the generated script calls back into `jj` to complete
+argument values such as bookmark names, remotes, workspace names, revisions and
+changed file paths. This is equivalent to running `jj` with the `COMPLETE`
+environment variable set to the shell name. Dynamic completion isn't supported
+for Nushell.
 "#]
 #[derive(clap::Args, Clone, Debug)]
 #[command(verbatim_doc_comment)]
 pub struct UtilCompletionArgs {
     shell: Option<ShellCompletion>,
+    /// Generate a script that completes values by invoking `jj` dynamically
+    #[arg(long)]
+    dynamic: bool,
     /// Deprecated. Use the SHELL positional argument instead.
     #[arg(long, hide = true)]
     bash: bool,
@@ -97,7 +106,11 @@ pub fn cmd_util_completion(
         }
     };
 
-    let buf = shell.generate(&mut app);
+    let buf = if args.dynamic {
+        shell.generate_dynamic(&app)?
+    } else {
+        shell.generate(&mut app)
+    };
     ui.stdout().write_all(&buf)?;
     Ok(())
 }
@@ -134,4 +147,29 @@ impl ShellCompletion {
 
         buf
     }
+
+    fn generate_dynamic(&self, cmd: &Command) -> Result<Vec<u8>, CommandError> {
+        use clap_complete::env::Bash;
+        use clap_complete::env::Elvish;
+        use clap_complete::env::EnvCompleter;
+        use clap_complete::env::Fish;
+        use clap_complete::env::Powershell;
+        use clap_complete::env::Zsh;
+
+        let shell: &dyn EnvCompleter = match self {
+            Self::Bash => &Bash,
+            Self::Elvish => &Elvish,
+            Self::Fish => &Fish,
+            Self::Nushell => {
+                return Err(user_error("Dynamic completion isn't supported for Nushell"));
+            }
+            Self::PowerShell => &Powershell,
+            Self::Zsh => &Zsh,
+        };
+
+        let mut buf = Vec::new();
+        let bin_name = "jj";
+        shell.write_registration("COMPLETE", cmd.get_name(), bin_name, bin_name, &mut buf)?;
+        Ok(buf)
+    }
 }
@@ -12,6 +12,8 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use jj_lib::object_id::ObjectId as _;
+
 use crate::cli_util::CommandHelper;
 use crate::command_error::user_error;
 use crate::command_error::user_error_with_message;
@@ -59,9 +61,20 @@ use crate::ui::Ui;
 /// # This last empty string will become "$0" in bash, so your actual arguments
 /// # are all included in "$@" and start at "$1" as expected.
 /// ```
+///
+/// When run inside a repo, the working copy is snapshotted first and the
+/// following environment variables are set for the command:
+///
+/// * `JJ_WORKSPACE_ROOT`: the root directory of the workspace
+/// * `JJ_WORKSPACE_NAME`: the name of the workspace
+/// * `JJ_OP_ID`: the id of the operation the repo was loaded at
+/// * `JJ_WORKING_COPY_COMMIT_ID`: the id of the working-copy commit
 #[derive(clap::Args, Clone, Debug)]
 #[command(verbatim_doc_comment)]
 pub(crate) struct UtilExecArgs {
+    /// Don't snapshot the working copy before running the command
+    #[arg(long)]
+    no_snapshot: bool,
     /// External command to execute
     command: String,
     /// Arguments to pass to the external command
@@ -69,19 +82,35 @@ pub(crate) struct UtilExecArgs {
 }
 
 pub fn cmd_util_exec(
-    _ui: &mut Ui,
-    _command: &CommandHelper,
+    ui: &mut Ui,
+    command: &CommandHelper,
     args: &UtilExecArgs,
 ) -> Result<(), CommandError> {
-    let status = std::process::Command::new(&args.command)
-        .args(&args.args)
-        .status()
-        .map_err(|err| {
-            user_error_with_message(
-                format!("Failed to execute external command '{}'", &args.command),
-                err,
+    let mut cmd = std::process::Command::new(&args.command);
+    cmd.args(&args.args);
+    if command.workspace_loader().is_ok() {
+        let workspace_command = if args.no_snapshot {
+            command.workspace_helper_no_snapshot(ui)?
+        } else {
+            command.workspace_helper(ui)?
+        };
+        let repo = workspace_command.repo();
+        cmd.env("JJ_WORKSPACE_ROOT", workspace_command.workspace_root())
+            .env(
+                "JJ_WORKSPACE_NAME",
+                workspace_command.workspace_id().as_str(),
             )
-        })?;
+            .env("JJ_OP_ID", repo.op_id().hex());
+        if let Some(wc_commit_id) = workspace_command.get_wc_commit_id() {
+            cmd.env("JJ_WORKING_COPY_COMMIT_ID", wc_commit_id.hex());
+        }
+    }
+    let status = cmd.status().map_err(|err| {
+        user_error_with_message(
+            format!("Failed to execute external command '{}'", &args.command),
+            err,
+        )
+    })?;
     if !status.success() {
         let error_msg = if let Some(exit_code) = status.code() {
             format!("External command exited with {exit_code}")
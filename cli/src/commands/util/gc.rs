@@ -12,15 +12,15 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use std::slice;
 use std::time::Duration;
 use std::time::SystemTime;
 
-use jj_lib::repo::Repo as _;
+use jj_lib::gc;
 
 use crate::cli_util::CommandHelper;
 use crate::command_error::user_error;
 use crate::command_error::CommandError;
+use crate::git_util::back_up_gc_candidates;
 use crate::ui::Ui;
 
 /// Run backend-dependent garbage collection.
@@ -30,6 +30,10 @@ use crate::ui::Ui;
 ///
 /// Previous versions of a change that are reachable via the evolution log are
 /// not garbage-collected.
+///
+/// It's safe to run this while other jj processes are writing to the repo.
+/// Only one garbage collection can run at a time, and objects that have become
+/// reachable from new operations in the meantime are kept.
 #[derive(clap::Args, Clone, Debug)]
 pub struct UtilGcArgs {
     /// Time threshold
@@ -60,10 +64,16 @@ pub fn cmd_util_gc(
         _ => return Err(user_error("--expire only accepts 'now'")),
     };
     let workspace_command = command.workspace_helper(ui)?;
-
-    let repo = workspace_command.repo();
-    repo.op_store()
-        .gc(slice::from_ref(repo.op_id()), keep_newer)?;
-    repo.store().gc(repo.index(), keep_newer)?;
+    back_up_gc_candidates(
+        ui,
+        workspace_command.settings(),
+        workspace_command.repo(),
+        keep_newer,
+    )?;
+    gc::gc_repo(
+        workspace_command.repo_path(),
+        workspace_command.repo().loader(),
+        keep_newer,
+    )?;
     Ok(())
 }
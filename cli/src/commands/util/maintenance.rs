@@ -0,0 +1,381 @@
+// Copyright 2025 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::io::Write as _;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+use std::time::SystemTime;
+
+use clap::Subcommand;
+use jj_lib::config::ConfigFile;
+use jj_lib::config::ConfigSource;
+use jj_lib::default_index::DefaultIndexStore;
+use jj_lib::file_util::IoResultExt as _;
+use jj_lib::file_util::PathError;
+use jj_lib::gc;
+use jj_lib::lock::FileLock;
+use jj_lib::repo::ReadonlyRepo;
+use jj_lib::repo::Repo as _;
+use jj_lib::settings::UserSettings;
+use jj_lib::workspace::Workspace;
+use tempfile::NamedTempFile;
+
+use crate::cli_util::CommandHelper;
+use crate::command_error::internal_error;
+use crate::command_error::user_error;
+use crate::command_error::CommandError;
+use crate::git_util::back_up_gc_candidates;
+use crate::ui::Ui;
+
+/// Run repository maintenance tasks
+///
+/// Maintenance tasks keep the repository fast and small. They can be run on
+/// demand with `jj util maintenance run`, or automatically after other
+/// commands once enabled with `jj util maintenance register`.
+///
+/// The tasks are configured in the `maintenance` table:
+///
+/// * `gc`: Prunes unreachable operations and objects, like `jj util gc`.
+///
+/// * `index`: Rebuilds the commit index into a single compact file.
+///
+/// * `prefetch`: Downloads the branches of all Git remotes in the background,
+///   without updating the remote-tracking bookmarks, so that the next `jj git
+///   fetch` has less to download.
+///
+/// Each task has an `enabled` flag and an `interval-hours` setting, which is
+/// the minimum time between automatic runs of the task. The time each task
+/// was last run is recorded in the repository.
+#[derive(Subcommand, Clone, Debug)]
+pub enum UtilMaintenanceCommand {
+    Register(UtilMaintenanceRegisterArgs),
+    Run(UtilMaintenanceRunArgs),
+}
+
+/// Run maintenance tasks automatically after each command in this repository
+///
+/// This sets `maintenance.auto = true` in the repository config. Only the
+/// tasks that are due according to their `interval-hours` setting are run, and
+/// they are skipped if another process is already running maintenance.
+#[derive(clap::Args, Clone, Debug)]
+pub struct UtilMaintenanceRegisterArgs {}
+
+/// Run maintenance tasks now
+///
+/// By default, all enabled tasks are run regardless of when they were last
+/// run.
+#[derive(clap::Args, Clone, Debug)]
+pub struct UtilMaintenanceRunArgs {
+    /// Run only these tasks, even if they are disabled in the config
+    #[arg(long = "task", value_name = "TASK")]
+    tasks: Vec<MaintenanceTask>,
+    /// Only run the tasks that are due, and do nothing if another process is
+    /// running maintenance
+    #[arg(long)]
+    auto: bool,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, clap::ValueEnum)]
+enum MaintenanceTask {
+    Gc,
+    Index,
+    Prefetch,
+}
+
+impl MaintenanceTask {
+    const ALL: [Self; 3] = [Self::Gc, Self::Index, Self::Prefetch];
+
+    fn name(self) -> &'static str {
+        match self {
+            Self::Gc => "gc",
+            Self::Index => "index",
+            Self::Prefetch => "prefetch",
+        }
+    }
+
+    fn is_enabled(self, settings: &UserSettings) -> Result<bool, CommandError> {
+        Ok(settings.get_bool(["maintenance", self.name(), "enabled"])?)
+    }
+
+    fn interval(self, settings: &UserSettings) -> Result<Duration, CommandError> {
+        let hours: u64 = settings.get(["maintenance", self.name(), "interval-hours"])?;
+        Ok(Duration::from_secs(hours * 3600))
+    }
+}
+
+/// Times at which maintenance tasks were last run.
+///
+/// This is stored in the repo directory instead of the operation log since
+/// undoing operations doesn't undo the maintenance.
+#[derive(Clone, Debug, Default, serde::Deserialize, serde::Serialize)]
+struct MaintenanceTimes {
+    /// Map of task name to milliseconds since epoch.
+    tasks: BTreeMap<String, u64>,
+}
+
+impl MaintenanceTimes {
+    const FILE_NAME: &str = "maintenance.json";
+
+    fn load(repo_path: &Path) -> Result<Self, PathError> {
+        let path = repo_path.join(Self::FILE_NAME);
+        match fs::read(&path) {
+            Ok(data) => serde_json::from_slice(&data)
+                .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+                .context(&path),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(err) => Err(err).context(&path),
+        }
+    }
+
+    fn save(&self, repo_path: &Path) -> Result<(), PathError> {
+        let path = repo_path.join(Self::FILE_NAME);
+        let data = serde_json::to_vec(self).expect("maintenance times should be serializable");
+        let mut temp_file = NamedTempFile::new_in(repo_path).context(repo_path)?;
+        temp_file.write_all(&data).context(temp_file.path())?;
+        temp_file
+            .persist(&path)
+            .map_err(|err| err.error)
+            .context(&path)?;
+        Ok(())
+    }
+
+    fn last_run(&self, task: MaintenanceTask) -> Option<SystemTime> {
+        let millis = *self.tasks.get(task.name())?;
+        Some(SystemTime::UNIX_EPOCH + Duration::from_millis(millis))
+    }
+
+    fn record(&mut self, task: MaintenanceTask, time: SystemTime) {
+        let millis = time
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .map_or(0, |duration| {
+                duration.as_millis().try_into().unwrap_or(u64::MAX)
+            });
+        self.tasks.insert(task.name().to_owned(), millis);
+    }
+
+    fn is_due(&self, task: MaintenanceTask, interval: Duration, now: SystemTime) -> bool {
+        self.last_run(task).is_none_or(|last_run| {
+            now.duration_since(last_run)
+                .is_ok_and(|elapsed| elapsed >= interval)
+        })
+    }
+}
+
+pub fn cmd_util_maintenance(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    subcommand: &UtilMaintenanceCommand,
+) -> Result<(), CommandError> {
+    match subcommand {
+        UtilMaintenanceCommand::Register(args) => cmd_util_maintenance_register(ui, command, args),
+        UtilMaintenanceCommand::Run(args) => cmd_util_maintenance_run(ui, command, args),
+    }
+}
+
+fn cmd_util_maintenance_register(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    _args: &UtilMaintenanceRegisterArgs,
+) -> Result<(), CommandError> {
+    let workspace = command.load_workspace()?;
+    let path = workspace.repo_path().join("config.toml");
+    let mut file = ConfigFile::load_or_empty(ConfigSource::Repo, path)?;
+    file.set_value(["maintenance", "auto"], true)
+        .map_err(|err| user_error(format!("Failed to set maintenance.auto: {err}")))?;
+    file.save()?;
+    writeln!(
+        ui.status(),
+        "Maintenance tasks will run automatically in this repository."
+    )?;
+    Ok(())
+}
+
+fn cmd_util_maintenance_run(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    args: &UtilMaintenanceRunArgs,
+) -> Result<(), CommandError> {
+    if !command.is_at_head_operation() {
+        return Err(user_error(
+            "Cannot run maintenance from a non-head operation",
+        ));
+    }
+    // Load the repo without snapshotting the working copy. Maintenance
+    // doesn't create operations.
+    let workspace = command.load_workspace()?;
+    let tasks = if args.tasks.is_empty() {
+        enabled_tasks(command.settings())?
+    } else {
+        args.tasks.clone()
+    };
+    if args.auto {
+        run_due_tasks(ui, command, &workspace, &tasks)
+    } else {
+        let Some(_lock) = try_lock_maintenance(&workspace)? else {
+            return Err(user_error("Another process is already running maintenance"));
+        };
+        let repo = workspace.repo_loader().load_at_head()?;
+        let mut times = MaintenanceTimes::load(workspace.repo_path())?;
+        for task in tasks {
+            run_task(ui, command, &workspace, &repo, task)?;
+            times.record(task, SystemTime::now());
+            times.save(workspace.repo_path())?;
+            writeln!(ui.status(), "Finished maintenance task {}", task.name())?;
+        }
+        Ok(())
+    }
+}
+
+/// Runs the maintenance tasks that are due if `maintenance.auto` is enabled.
+///
+/// Errors are reported as warnings since the command itself has succeeded.
+pub fn maybe_run_auto_maintenance(
+    ui: &mut Ui,
+    command: &CommandHelper,
+) -> Result<(), CommandError> {
+    let settings = command.settings();
+    if !settings.get_bool("maintenance.auto").unwrap_or(false)
+        || !command.is_at_head_operation()
+        || command.workspace_loader().is_err()
+    {
+        return Ok(());
+    }
+    let result = enabled_tasks(settings).and_then(|tasks| {
+        let workspace = command.load_workspace()?;
+        run_due_tasks(ui, command, &workspace, &tasks)
+    });
+    if let Err(err) = result {
+        writeln!(
+            ui.warning_default(),
+            "Failed to run maintenance: {}",
+            err.error
+        )?;
+    }
+    Ok(())
+}
+
+fn enabled_tasks(settings: &UserSettings) -> Result<Vec<MaintenanceTask>, CommandError> {
+    let mut tasks = vec![];
+    for task in MaintenanceTask::ALL {
+        if task.is_enabled(settings)? {
+            tasks.push(task);
+        }
+    }
+    Ok(tasks)
+}
+
+fn try_lock_maintenance(workspace: &Workspace) -> Result<Option<FileLock>, CommandError> {
+    FileLock::try_lock(workspace.repo_path().join("maintenance.lock")).map_err(internal_error)
+}
+
+/// Runs the `tasks` that are due, unless another process holds the lock.
+fn run_due_tasks(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    workspace: &Workspace,
+    tasks: &[MaintenanceTask],
+) -> Result<(), CommandError> {
+    let settings = command.settings();
+    let now = SystemTime::now();
+    let mut times = MaintenanceTimes::load(workspace.repo_path())?;
+    let mut due_tasks = vec![];
+    for &task in tasks {
+        if times.is_due(task, task.interval(settings)?, now) {
+            due_tasks.push(task);
+        }
+    }
+    if due_tasks.is_empty() {
+        return Ok(());
+    }
+    let Some(_lock) = try_lock_maintenance(workspace)? else {
+        tracing::info!("skipping maintenance since another process holds the lock");
+        return Ok(());
+    };
+    // Another process may have run the tasks while we were checking.
+    times = MaintenanceTimes::load(workspace.repo_path())?;
+    let repo = workspace.repo_loader().load_at_head()?;
+    for task in due_tasks {
+        if !times.is_due(task, task.interval(settings)?, now) {
+            continue;
+        }
+        tracing::info!(task = task.name(), "running maintenance task");
+        // Record the attempt even if the task fails so a broken task isn't
+        // retried after every command.
+        times.record(task, SystemTime::now());
+        times.save(workspace.repo_path())?;
+        if let Err(err) = run_task(ui, command, workspace, &repo, task) {
+            writeln!(
+                ui.warning_default(),
+                "Maintenance task {} failed: {}",
+                task.name(),
+                err.error
+            )?;
+        }
+    }
+    Ok(())
+}
+
+fn run_task(
+    ui: &Ui,
+    command: &CommandHelper,
+    workspace: &Workspace,
+    repo: &Arc<ReadonlyRepo>,
+    task: MaintenanceTask,
+) -> Result<(), CommandError> {
+    match task {
+        MaintenanceTask::Gc => {
+            let keep_newer = SystemTime::now() - Duration::from_secs(14 * 86400);
+            back_up_gc_candidates(ui, command.settings(), repo, keep_newer)?;
+            gc::gc_repo(workspace.repo_path(), workspace.repo_loader(), keep_newer)?;
+        }
+        MaintenanceTask::Index => {
+            let repo_loader = workspace.repo_loader();
+            let index_store = repo_loader.index_store();
+            let Some(default_index_store) =
+                index_store.as_any().downcast_ref::<DefaultIndexStore>()
+            else {
+                return Ok(());
+            };
+            default_index_store.reinit().map_err(internal_error)?;
+            default_index_store
+                .build_index_at_operation(repo.operation(), repo_loader.store())
+                .map_err(internal_error)?;
+        }
+        MaintenanceTask::Prefetch => prefetch(command, repo)?,
+    }
+    Ok(())
+}
+
+#[cfg(feature = "git")]
+fn prefetch(command: &CommandHelper, repo: &Arc<ReadonlyRepo>) -> Result<(), CommandError> {
+    use jj_lib::git;
+
+    if git::get_git_backend(repo.store()).is_err() {
+        return Ok(());
+    }
+    let git_settings = command.settings().git_settings()?;
+    for remote_name in git::get_all_remote_names(repo.store())? {
+        git::prefetch(repo.store(), &git_settings, &remote_name)?;
+    }
+    Ok(())
+}
+
+#[cfg(not(feature = "git"))]
+fn prefetch(_command: &CommandHelper, _repo: &Arc<ReadonlyRepo>) -> Result<(), CommandError> {
+    Ok(())
+}
@@ -17,7 +17,9 @@ mod config_schema;
 mod exec;
 mod gc;
 mod install_man_pages;
+mod maintenance;
 mod markdown_help;
+mod upgrade_repo;
 
 use clap::Subcommand;
 use tracing::instrument;
@@ -32,8 +34,13 @@ use self::gc::cmd_util_gc;
 use self::gc::UtilGcArgs;
 use self::install_man_pages::cmd_util_install_man_pages;
 use self::install_man_pages::UtilInstallManPagesArgs;
+use self::maintenance::cmd_util_maintenance;
+pub(crate) use self::maintenance::maybe_run_auto_maintenance;
+use self::maintenance::UtilMaintenanceCommand;
 use self::markdown_help::cmd_util_markdown_help;
 use self::markdown_help::UtilMarkdownHelp;
+use self::upgrade_repo::cmd_util_upgrade_repo;
+use self::upgrade_repo::UtilUpgradeRepoArgs;
 use crate::cli_util::CommandHelper;
 use crate::command_error::CommandError;
 use crate::ui::Ui;
@@ -46,7 +53,10 @@ pub(crate) enum UtilCommand {
     Exec(UtilExecArgs),
     Gc(UtilGcArgs),
     InstallManPages(UtilInstallManPagesArgs),
+    #[command(subcommand)]
+    Maintenance(UtilMaintenanceCommand),
     MarkdownHelp(UtilMarkdownHelp),
+    UpgradeRepo(UtilUpgradeRepoArgs),
 }
 
 #[instrument(skip_all)]
@@ -61,6 +71,8 @@ pub(crate) fn cmd_util(
         UtilCommand::Exec(args) => cmd_util_exec(ui, command, args),
         UtilCommand::Gc(args) => cmd_util_gc(ui, command, args),
         UtilCommand::InstallManPages(args) => cmd_util_install_man_pages(ui, command, args),
+        UtilCommand::Maintenance(subcommand) => cmd_util_maintenance(ui, command, subcommand),
         UtilCommand::MarkdownHelp(args) => cmd_util_markdown_help(ui, command, args),
+        UtilCommand::UpgradeRepo(args) => cmd_util_upgrade_repo(ui, command, args),
     }
 }
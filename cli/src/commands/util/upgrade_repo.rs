@@ -0,0 +1,88 @@
+// Copyright 2025 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::io::Write as _;
+use std::path::PathBuf;
+
+use jj_lib::file_util;
+use jj_lib::repo_format;
+use jj_lib::repo_format::FormatVersionError;
+use jj_lib::repo_format::RepoUpgradeError;
+
+use crate::cli_util::CommandHelper;
+use crate::command_error::internal_error_with_message;
+use crate::command_error::user_error;
+use crate::command_error::user_error_with_hint;
+use crate::command_error::CommandError;
+use crate::ui::Ui;
+
+/// Upgrade the repository to the current storage format
+///
+/// Stores in a format that is older than the one used by this version of jj
+/// are never migrated when the repository is loaded. Stores that are still
+/// readable are used as is, and the others can't be used until they have been
+/// upgraded by this command. The stores are migrated in place. Before that,
+/// each store to be upgraded is copied to a backup directory.
+#[derive(clap::Args, Clone, Debug)]
+pub struct UtilUpgradeRepoArgs {
+    /// Directory to back up the stores to
+    ///
+    /// Defaults to `.jj/repo/upgrade-backup`. The directory must not exist.
+    #[arg(long, value_hint = clap::ValueHint::DirPath)]
+    backup_dir: Option<PathBuf>,
+}
+
+pub fn cmd_util_upgrade_repo(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    args: &UtilUpgradeRepoArgs,
+) -> Result<(), CommandError> {
+    let repo_path = command.workspace_loader()?.repo_path().to_owned();
+    let backup_path = match &args.backup_dir {
+        Some(path) => command.cwd().join(path),
+        None => repo_path.join("upgrade-backup"),
+    };
+    let upgrades =
+        repo_format::upgrade_repo(&repo_path, &backup_path).map_err(|err| match err {
+            RepoUpgradeError::BackupExists(_) => user_error_with_hint(
+                err,
+                "Remove it, or specify another directory with `--backup-dir`.",
+            ),
+            RepoUpgradeError::FormatVersion(FormatVersionError::TooNew { .. }) => user_error(err),
+            _ => internal_error_with_message("Failed to upgrade the repository", err),
+        })?;
+    if upgrades.is_empty() {
+        writeln!(
+            ui.status(),
+            "The repository is already in the current format."
+        )?;
+        return Ok(());
+    }
+    for upgrade in &upgrades {
+        writeln!(
+            ui.status(),
+            "Upgraded {} store from format version {} to {}",
+            upgrade.kind.description(),
+            upgrade.from,
+            upgrade.to
+        )?;
+    }
+    let relative_backup_path = file_util::relative_path(command.cwd(), &backup_path);
+    writeln!(
+        ui.status(),
+        "Backed up the previous stores to \"{}\"",
+        relative_backup_path.display()
+    )?;
+    Ok(())
+}
@@ -16,6 +16,7 @@ use std::fs;
 
 use itertools::Itertools;
 use jj_lib::commit::CommitIteratorExt;
+use jj_lib::config::ConfigGetResultExt as _;
 use jj_lib::file_util;
 use jj_lib::file_util::IoResultExt;
 use jj_lib::op_store::WorkspaceId;
@@ -29,6 +30,8 @@ use crate::cli_util::RevisionArg;
 use crate::command_error::internal_error_with_message;
 use crate::command_error::user_error;
 use crate::command_error::CommandError;
+use crate::commands::sparse::record_sparse_patterns;
+use crate::commands::sparse::sparse_profile_patterns;
 use crate::ui::Ui;
 
 /// How to handle sparse patterns when creating a new workspace.
@@ -45,7 +48,10 @@ enum SparseInheritance {
 /// Add a workspace
 ///
 /// By default, the new workspace inherits the sparse patterns of the current
-/// workspace. You can override this with the `--sparse-patterns` option.
+/// workspace. You can override this with the `--sparse-patterns` option, or
+/// apply one of the sparse profiles defined under `[sparse.profiles]` with the
+/// `--sparse-profile` option. If `sparse.default-profile` is set, that profile
+/// is applied unless one of the options is given.
 #[derive(clap::Args, Clone, Debug)]
 pub struct WorkspaceAddArgs {
     /// Where to create the new workspace
@@ -59,10 +65,11 @@ pub struct WorkspaceAddArgs {
     /// A list of parent revisions for the working-copy commit of the newly
     /// created workspace. You may specify nothing, or any number of parents.
     ///
-    /// If no revisions are specified, the new workspace will be created, and
-    /// its working-copy commit will exist on top of the parent(s) of the
-    /// working-copy commit in the current workspace, i.e. they will share the
-    /// same parent(s).
+    /// If no revisions are specified, this defaults to the
+    /// `revsets.workspace-add` setting. If that isn't set either, the new
+    /// workspace will be created, and its working-copy commit will exist on
+    /// top of the parent(s) of the working-copy commit in the current
+    /// workspace, i.e. they will share the same parent(s).
     ///
     /// If any revisions are specified, the new workspace will be created, and
     /// the new working-copy commit will be created with all these revisions as
@@ -71,8 +78,16 @@ pub struct WorkspaceAddArgs {
     #[arg(long, short, value_name = "REVSETS")]
     revision: Vec<RevisionArg>,
     /// How to handle sparse patterns when creating a new workspace.
-    #[arg(long, value_enum, default_value_t = SparseInheritance::Copy)]
-    sparse_patterns: SparseInheritance,
+    ///
+    /// Defaults to `copy` unless `sparse.default-profile` is set.
+    #[arg(long, value_enum)]
+    sparse_patterns: Option<SparseInheritance>,
+    /// Use the patterns of the named sparse profile in the new workspace
+    ///
+    /// Sparse profiles are lists of paths defined in the config, for example
+    /// `sparse.profiles.backend = ["crates/backend", "libs/common"]`.
+    #[arg(long, value_name = "NAME", conflicts_with = "sparse_patterns")]
+    sparse_profile: Option<String>,
 }
 
 #[instrument(skip_all)]
@@ -82,6 +97,34 @@ pub fn cmd_workspace_add(
     args: &WorkspaceAddArgs,
 ) -> Result<(), CommandError> {
     let old_workspace_command = command.workspace_helper(ui)?;
+    let settings = old_workspace_command.settings();
+    let sparse_profile = match &args.sparse_profile {
+        Some(name) => Some(name.clone()),
+        None if args.sparse_patterns.is_none() => {
+            settings.get_string("sparse.default-profile").optional()?
+        }
+        None => None,
+    };
+    let sparsity = if let Some(name) = &sparse_profile {
+        Some(sparse_profile_patterns(settings, name)?)
+    } else {
+        match args
+            .sparse_patterns
+            .as_ref()
+            .unwrap_or(&SparseInheritance::Copy)
+        {
+            SparseInheritance::Full => None,
+            SparseInheritance::Empty => Some(vec![]),
+            SparseInheritance::Copy => {
+                let sparse_patterns = old_workspace_command
+                    .working_copy()
+                    .sparse_patterns()?
+                    .to_vec();
+                Some(sparse_patterns)
+            }
+        }
+    };
+
     let destination_path = command.cwd().join(&args.destination);
     if destination_path.exists() {
         return Err(user_error("Workspace already exists"));
@@ -134,18 +177,6 @@ pub fn cmd_workspace_add(
 
     let mut new_workspace_command = command.for_workable_repo(ui, new_workspace, repo)?;
 
-    let sparsity = match args.sparse_patterns {
-        SparseInheritance::Full => None,
-        SparseInheritance::Empty => Some(vec![]),
-        SparseInheritance::Copy => {
-            let sparse_patterns = old_workspace_command
-                .working_copy()
-                .sparse_patterns()?
-                .to_vec();
-            Some(sparse_patterns)
-        }
-    };
-
     if let Some(sparse_patterns) = sparsity {
         let checkout_options = new_workspace_command.checkout_options();
         let (mut locked_ws, _wc_commit) = new_workspace_command.start_working_copy_mutation()?;
@@ -156,12 +187,27 @@ pub fn cmd_workspace_add(
         let operation_id = locked_ws.locked_wc().old_operation_id().clone();
         locked_ws.finish(operation_id)?;
     }
+    record_sparse_patterns(
+        new_workspace_command.repo_path(),
+        new_workspace_command.workspace_id(),
+        new_workspace_command.working_copy().sparse_patterns()?,
+    )?;
 
     let mut tx = new_workspace_command.start_transaction();
 
-    // If no parent revisions are specified, create a working-copy commit based
-    // on the parent of the current working-copy commit.
-    let parents = if args.revision.is_empty() {
+    // If no parent revisions are specified or configured, create a
+    // working-copy commit based on the parent of the current working-copy
+    // commit.
+    let revision_args = if args.revision.is_empty() {
+        settings
+            .get_string("revsets.workspace-add")
+            .optional()?
+            .map(|revset| vec![RevisionArg::from(revset)])
+            .unwrap_or_default()
+    } else {
+        args.revision.clone()
+    };
+    let parents = if revision_args.is_empty() {
         // Check out parents of the current workspace's working-copy commit, or the
         // root if there is no working-copy commit in the current workspace.
         if let Some(old_wc_commit_id) = tx
@@ -179,7 +225,7 @@ pub fn cmd_workspace_add(
         }
     } else {
         old_workspace_command
-            .resolve_some_revsets_default_single(ui, &args.revision)?
+            .resolve_some_revsets_default_single(ui, &revision_args)?
             .into_iter()
             .collect_vec()
     };
@@ -15,6 +15,7 @@
 use clap_complete::ArgValueCandidates;
 use itertools::Itertools;
 use jj_lib::op_store::WorkspaceId;
+use jj_lib::workspace::WorkspaceSparsePatterns;
 use tracing::instrument;
 
 use crate::cli_util::CommandHelper;
@@ -78,5 +79,12 @@ pub fn cmd_workspace_forget(
     };
 
     tx.finish(ui, description)?;
+
+    let repo_path = workspace_command.repo_path();
+    let mut sparse_patterns = WorkspaceSparsePatterns::load(repo_path)?;
+    for ws in &wss {
+        sparse_patterns.forget(ws);
+    }
+    sparse_patterns.save(repo_path)?;
     Ok(())
 }
@@ -16,7 +16,9 @@ mod add;
 mod forget;
 mod list;
 mod rename;
+mod repair;
 mod root;
+mod undo_files;
 mod update_stale;
 
 use clap::Subcommand;
@@ -30,8 +32,12 @@ use self::list::cmd_workspace_list;
 use self::list::WorkspaceListArgs;
 use self::rename::cmd_workspace_rename;
 use self::rename::WorkspaceRenameArgs;
+use self::repair::cmd_workspace_repair;
+use self::repair::WorkspaceRepairArgs;
 use self::root::cmd_workspace_root;
 use self::root::WorkspaceRootArgs;
+use self::undo_files::cmd_workspace_undo_files;
+use self::undo_files::WorkspaceUndoFilesArgs;
 use self::update_stale::cmd_workspace_update_stale;
 use self::update_stale::WorkspaceUpdateStaleArgs;
 use crate::cli_util::CommandHelper;
@@ -55,7 +61,9 @@ pub(crate) enum WorkspaceCommand {
     Forget(WorkspaceForgetArgs),
     List(WorkspaceListArgs),
     Rename(WorkspaceRenameArgs),
+    Repair(WorkspaceRepairArgs),
     Root(WorkspaceRootArgs),
+    UndoFiles(WorkspaceUndoFilesArgs),
     UpdateStale(WorkspaceUpdateStaleArgs),
 }
 
@@ -70,7 +78,9 @@ pub(crate) fn cmd_workspace(
         WorkspaceCommand::Forget(args) => cmd_workspace_forget(ui, command, args),
         WorkspaceCommand::List(args) => cmd_workspace_list(ui, command, args),
         WorkspaceCommand::Rename(args) => cmd_workspace_rename(ui, command, args),
+        WorkspaceCommand::Repair(args) => cmd_workspace_repair(ui, command, args),
         WorkspaceCommand::Root(args) => cmd_workspace_root(ui, command, args),
+        WorkspaceCommand::UndoFiles(args) => cmd_workspace_undo_files(ui, command, args),
         WorkspaceCommand::UpdateStale(args) => cmd_workspace_update_stale(ui, command, args),
     }
 }
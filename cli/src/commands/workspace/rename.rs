@@ -13,6 +13,7 @@
 // limitations under the License.
 
 use jj_lib::op_store::WorkspaceId;
+use jj_lib::workspace::WorkspaceSparsePatterns;
 use tracing::instrument;
 
 use crate::cli_util::CommandHelper;
@@ -74,5 +75,14 @@ pub fn cmd_workspace_rename(
     ))?;
     locked_ws.finish(repo.op_id().clone())?;
 
+    let repo_path = workspace_command.repo_path();
+    let mut sparse_patterns = WorkspaceSparsePatterns::load(repo_path)?;
+    sparse_patterns.forget(&old_workspace_id);
+    sparse_patterns.record(
+        &WorkspaceId::new(args.new_workspace_name.clone()),
+        workspace_command.working_copy().sparse_patterns()?,
+    );
+    sparse_patterns.save(repo_path)?;
+
     Ok(())
 }
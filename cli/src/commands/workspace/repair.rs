@@ -0,0 +1,105 @@
+// Copyright 2025 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use jj_lib::local_working_copy::LocalWorkingCopy;
+use jj_lib::object_id::ObjectId as _;
+use jj_lib::repo::Repo as _;
+use jj_lib::repo_path::RepoPathBuf;
+use tracing::instrument;
+
+use crate::cli_util::print_snapshot_stats;
+use crate::cli_util::CommandHelper;
+use crate::command_error::user_error;
+use crate::command_error::user_error_with_hint;
+use crate::command_error::CommandError;
+use crate::ui::Ui;
+
+/// Rebuild the working copy state of the current workspace
+///
+/// Use this if the working copy state file has been corrupted, e.g. after a
+/// power loss. The state is rebuilt from the working-copy commit recorded by
+/// the last operation the workspace was updated to, and then the working copy
+/// is snapshotted by scanning all files. Changes made to files on disk are
+/// preserved.
+///
+/// The sparse patterns of the workspace are kept if they can still be read
+/// from the state file. Otherwise, they have to be specified by
+/// `--sparse-pattern`.
+#[derive(clap::Args, Clone, Debug)]
+pub struct WorkspaceRepairArgs {
+    /// Sparse patterns of the workspace (can be repeated)
+    ///
+    /// Use `--sparse-pattern .` to include all files. Files outside of the
+    /// patterns are expected to be missing on disk.
+    #[arg(
+        long = "sparse-pattern",
+        value_name = "PATTERN",
+        value_hint = clap::ValueHint::AnyPath,
+        value_parser = |s: &str| RepoPathBuf::from_relative_path(s),
+    )]
+    sparse_patterns: Vec<RepoPathBuf>,
+}
+
+#[instrument(skip_all)]
+pub fn cmd_workspace_repair(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    args: &WorkspaceRepairArgs,
+) -> Result<(), CommandError> {
+    // Load the workspace without reading the tree state.
+    let workspace = command.load_workspace()?;
+    let local_wc: &LocalWorkingCopy = workspace
+        .working_copy()
+        .as_any()
+        .downcast_ref()
+        .ok_or_else(|| user_error("This command requires a standard local-disk working copy"))?;
+    let repo_loader = workspace.repo_loader();
+    let op = repo_loader.load_operation(workspace.working_copy().operation_id())?;
+    let repo = repo_loader.load_at(&op)?;
+    let wc_commit_id = repo
+        .view()
+        .get_wc_commit_id(workspace.workspace_id())
+        .ok_or_else(|| {
+            user_error(format!(
+                "Workspace '{}' has no working-copy commit at operation {}",
+                workspace.workspace_id().as_str(),
+                op.id().hex()
+            ))
+        })?;
+    let wc_commit = repo.store().get_commit(wc_commit_id)?;
+    let sparse_patterns = if args.sparse_patterns.is_empty() {
+        local_wc.read_sparse_patterns().map_err(|err| {
+            user_error_with_hint(
+                err,
+                "Specify the sparse patterns of the workspace with `--sparse-pattern`, or use \
+                 `--sparse-pattern .` to include all files.",
+            )
+        })?
+    } else {
+        args.sparse_patterns.clone()
+    };
+    local_wc.repair_tree_state(&wc_commit, sparse_patterns)?;
+
+    if let Some(mut formatter) = ui.status_formatter() {
+        let workspace_command = command.workspace_helper_no_snapshot(ui)?;
+        write!(formatter, "Rebuilt working copy state from commit ")?;
+        workspace_command.write_commit_summary(formatter.as_mut(), &wc_commit)?;
+        writeln!(formatter)?;
+    }
+
+    // Snapshot the changes made while the state was unreadable.
+    let (workspace_command, stats) = command.workspace_helper_with_stats(ui)?;
+    print_snapshot_stats(ui, &stats, workspace_command.env().path_converter())?;
+    Ok(())
+}
@@ -0,0 +1,121 @@
+// Copyright 2025 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::io::Write as _;
+
+use chrono::Offset as _;
+use chrono::TimeZone as _;
+use jj_lib::backend::Timestamp;
+use jj_lib::local_working_copy::LocalWorkingCopy;
+use jj_lib::undo_stash::UndoStashEntry;
+use tracing::instrument;
+
+use crate::cli_util::CommandHelper;
+use crate::command_error::user_error;
+use crate::command_error::user_error_with_message;
+use crate::command_error::CommandError;
+use crate::time_util::format_absolute_timestamp;
+use crate::ui::Ui;
+
+/// Restore files that were replaced when the working copy was updated
+///
+/// If `working-copy.undo-stash-days` is set, files with unsnapshotted changes
+/// are copied to the undo stash before they are overwritten or deleted by
+/// checkout. This command copies them back to the working copy.
+///
+/// By default, the files stashed by the most recent checkout are restored.
+/// The current contents of the working copy are snapshotted first, so no
+/// changes are lost by restoring.
+#[derive(clap::Args, Clone, Debug)]
+pub struct WorkspaceUndoFilesArgs {
+    /// The stash entry to restore (default: the most recent entry)
+    #[arg(conflicts_with = "list")]
+    entry: Option<String>,
+    /// List the stash entries and the files in them instead of restoring
+    #[arg(long, short)]
+    list: bool,
+}
+
+#[instrument(skip_all)]
+pub fn cmd_workspace_undo_files(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    args: &WorkspaceUndoFilesArgs,
+) -> Result<(), CommandError> {
+    let workspace_command = command.workspace_helper(ui)?;
+    let Some(working_copy) = workspace_command
+        .working_copy()
+        .as_any()
+        .downcast_ref::<LocalWorkingCopy>()
+    else {
+        return Err(user_error(
+            "This command requires a standard local-disk working copy",
+        ));
+    };
+    let undo_stash = working_copy.undo_stash();
+    let stash_error = |err| user_error_with_message("Failed to read undo stash", err);
+
+    if args.list {
+        let mut formatter = ui.stdout_formatter();
+        for entry in undo_stash.entries().map_err(stash_error)? {
+            writeln!(formatter, "{}: {}", entry.id(), format_entry_time(&entry))?;
+            for path in entry.files().map_err(stash_error)? {
+                writeln!(formatter, "  {}", workspace_command.format_file_path(&path))?;
+            }
+        }
+        return Ok(());
+    }
+
+    let entry = if let Some(id) = &args.entry {
+        undo_stash
+            .get_entry(id)
+            .map_err(stash_error)?
+            .ok_or_else(|| user_error(format!("No such undo stash entry: {id}")))?
+    } else {
+        undo_stash
+            .entries()
+            .map_err(stash_error)?
+            .into_iter()
+            .next()
+            .ok_or_else(|| user_error("The undo stash is empty"))?
+    };
+    let restored = entry
+        .restore(workspace_command.workspace_root())
+        .map_err(|err| user_error_with_message("Failed to restore files", err))?;
+    if let Some(mut formatter) = ui.status_formatter() {
+        writeln!(
+            formatter,
+            "Restored {} files from undo stash entry {}:",
+            restored.len(),
+            entry.id()
+        )?;
+        for path in &restored {
+            writeln!(formatter, "  {}", workspace_command.format_file_path(path))?;
+        }
+    }
+    Ok(())
+}
+
+fn format_entry_time(entry: &UndoStashEntry) -> String {
+    let millis = entry.time();
+    let tz_offset = chrono::Local
+        .timestamp_millis_opt(millis.0)
+        .single()
+        .map_or(0, |time| time.offset().fix().local_minus_utc() / 60);
+    let timestamp = Timestamp {
+        timestamp: millis,
+        tz_offset,
+    };
+    format_absolute_timestamp(&timestamp).unwrap_or_else(|_| "<out-of-range date>".to_owned())
+}
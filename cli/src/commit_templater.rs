@@ -18,6 +18,7 @@ use std::cmp::Ordering;
 use std::collections::HashMap;
 use std::io;
 use std::rc::Rc;
+use std::slice;
 
 use bstr::BString;
 use futures::stream::BoxStream;
@@ -37,12 +38,14 @@ use jj_lib::extensions_map::ExtensionsMap;
 use jj_lib::fileset;
 use jj_lib::fileset::FilesetDiagnostics;
 use jj_lib::fileset::FilesetExpression;
+use jj_lib::fileset::FilesetParseContext;
 use jj_lib::id_prefix::IdPrefixContext;
 use jj_lib::id_prefix::IdPrefixIndex;
 use jj_lib::matchers::Matcher;
 use jj_lib::merge::MergedTreeValue;
 use jj_lib::merged_tree::MergedTree;
 use jj_lib::object_id::ObjectId as _;
+use jj_lib::op_store::BookmarkMetadata;
 use jj_lib::op_store::RefTarget;
 use jj_lib::op_store::RemoteRef;
 use jj_lib::op_store::WorkspaceId;
@@ -266,6 +269,18 @@ impl<'repo> TemplateLanguage<'repo> for CommitTemplateLanguage<'repo> {
                     function,
                 )
             }
+            CommitTemplatePropertyKind::RepoPathList(property) => {
+                // TODO: migrate to table?
+                template_builder::build_formattable_list_method(
+                    self,
+                    diagnostics,
+                    build_ctx,
+                    property,
+                    function,
+                    Self::wrap_repo_path,
+                    Self::wrap_repo_path_list,
+                )
+            }
             CommitTemplatePropertyKind::CommitOrChangeId(property) => {
                 let table = &self.build_fn_table.commit_or_change_id_methods;
                 let build = template_parser::lookup_method(type_name, table, function)?;
@@ -401,6 +416,12 @@ impl<'repo> CommitTemplateLanguage<'repo> {
         CommitTemplatePropertyKind::RepoPathOpt(Box::new(property))
     }
 
+    pub fn wrap_repo_path_list(
+        property: impl TemplateProperty<Output = Vec<RepoPathBuf>> + 'repo,
+    ) -> CommitTemplatePropertyKind<'repo> {
+        CommitTemplatePropertyKind::RepoPathList(Box::new(property))
+    }
+
     pub fn wrap_commit_or_change_id(
         property: impl TemplateProperty<Output = CommitOrChangeId> + 'repo,
     ) -> CommitTemplatePropertyKind<'repo> {
@@ -466,6 +487,7 @@ pub enum CommitTemplatePropertyKind<'repo> {
     RefNameList(Box<dyn TemplateProperty<Output = Vec<Rc<RefName>>> + 'repo>),
     RepoPath(Box<dyn TemplateProperty<Output = RepoPathBuf> + 'repo>),
     RepoPathOpt(Box<dyn TemplateProperty<Output = Option<RepoPathBuf>> + 'repo>),
+    RepoPathList(Box<dyn TemplateProperty<Output = Vec<RepoPathBuf>> + 'repo>),
     CommitOrChangeId(Box<dyn TemplateProperty<Output = CommitOrChangeId> + 'repo>),
     ShortestIdPrefix(Box<dyn TemplateProperty<Output = ShortestIdPrefix> + 'repo>),
     TreeDiff(Box<dyn TemplateProperty<Output = TreeDiff> + 'repo>),
@@ -491,6 +513,7 @@ impl<'repo> IntoTemplateProperty<'repo> for CommitTemplatePropertyKind<'repo> {
             CommitTemplatePropertyKind::RefNameList(_) => "List<RefName>",
             CommitTemplatePropertyKind::RepoPath(_) => "RepoPath",
             CommitTemplatePropertyKind::RepoPathOpt(_) => "Option<RepoPath>",
+            CommitTemplatePropertyKind::RepoPathList(_) => "List<RepoPath>",
             CommitTemplatePropertyKind::CommitOrChangeId(_) => "CommitOrChangeId",
             CommitTemplatePropertyKind::ShortestIdPrefix(_) => "ShortestIdPrefix",
             CommitTemplatePropertyKind::TreeDiff(_) => "TreeDiff",
@@ -526,6 +549,9 @@ impl<'repo> IntoTemplateProperty<'repo> for CommitTemplatePropertyKind<'repo> {
             CommitTemplatePropertyKind::RepoPathOpt(property) => {
                 Some(Box::new(property.map(|opt| opt.is_some())))
             }
+            CommitTemplatePropertyKind::RepoPathList(property) => {
+                Some(Box::new(property.map(|l| !l.is_empty())))
+            }
             CommitTemplatePropertyKind::CommitOrChangeId(_) => None,
             CommitTemplatePropertyKind::ShortestIdPrefix(_) => None,
             // TODO: boolean cast could be implemented, but explicit
@@ -572,6 +598,7 @@ impl<'repo> IntoTemplateProperty<'repo> for CommitTemplatePropertyKind<'repo> {
             CommitTemplatePropertyKind::RefNameList(property) => Some(property.into_template()),
             CommitTemplatePropertyKind::RepoPath(property) => Some(property.into_template()),
             CommitTemplatePropertyKind::RepoPathOpt(property) => Some(property.into_template()),
+            CommitTemplatePropertyKind::RepoPathList(property) => Some(property.into_template()),
             CommitTemplatePropertyKind::CommitOrChangeId(property) => {
                 Some(property.into_template())
             }
@@ -602,6 +629,7 @@ impl<'repo> IntoTemplateProperty<'repo> for CommitTemplatePropertyKind<'repo> {
             (CommitTemplatePropertyKind::RefNameList(_), _) => None,
             (CommitTemplatePropertyKind::RepoPath(_), _) => None,
             (CommitTemplatePropertyKind::RepoPathOpt(_), _) => None,
+            (CommitTemplatePropertyKind::RepoPathList(_), _) => None,
             (CommitTemplatePropertyKind::CommitOrChangeId(_), _) => None,
             (CommitTemplatePropertyKind::ShortestIdPrefix(_), _) => None,
             (CommitTemplatePropertyKind::TreeDiff(_), _) => None,
@@ -631,6 +659,7 @@ impl<'repo> IntoTemplateProperty<'repo> for CommitTemplatePropertyKind<'repo> {
             (CommitTemplatePropertyKind::RefNameList(_), _) => None,
             (CommitTemplatePropertyKind::RepoPath(_), _) => None,
             (CommitTemplatePropertyKind::RepoPathOpt(_), _) => None,
+            (CommitTemplatePropertyKind::RepoPathList(_), _) => None,
             (CommitTemplatePropertyKind::CommitOrChangeId(_), _) => None,
             (CommitTemplatePropertyKind::ShortestIdPrefix(_), _) => None,
             (CommitTemplatePropertyKind::TreeDiff(_), _) => None,
@@ -943,6 +972,37 @@ fn builtin_commit_methods<'repo>() -> CommitTemplateBuildMethodFnMap<'repo, Comm
             Ok(L::wrap_ref_name_list(out_property))
         },
     );
+    map.insert(
+        "topics",
+        |language, _diagnostics, _build_ctx, self_property, function| {
+            function.expect_no_arguments()?;
+            let repo = language.repo;
+            let out_property = self_property.map(|commit| {
+                repo.view()
+                    .topics_for_change_id(commit.change_id())
+                    .map(ToOwned::to_owned)
+                    .collect()
+            });
+            Ok(L::wrap_string_list(out_property))
+        },
+    );
+    map.insert(
+        "metadata",
+        |language, _diagnostics, _build_ctx, self_property, function| {
+            let [key_node] = function.expect_exact_arguments()?;
+            let key = template_parser::expect_string_literal_with(key_node, |key, _span| {
+                Ok(key.to_owned())
+            })?;
+            let repo = language.repo;
+            let out_property = self_property.map(move |commit| {
+                repo.view()
+                    .get_change_metadata(commit.change_id())
+                    .and_then(|values| values.get(&key).cloned())
+                    .unwrap_or_default()
+            });
+            Ok(L::wrap_string(out_property))
+        },
+    );
     map.insert(
         "git_refs",
         |language, _diagnostics, _build_ctx, self_property, function| {
@@ -1012,6 +1072,33 @@ fn builtin_commit_methods<'repo>() -> CommitTemplateBuildMethodFnMap<'repo, Comm
             Ok(L::wrap_boolean(out_property))
         },
     );
+    map.insert(
+        "fork_point",
+        |language, diagnostics, _build_ctx, self_property, function| {
+            let [revset_node] = function.expect_exact_arguments()?;
+
+            let other_ids: Vec<CommitId> =
+                template_parser::expect_string_literal_with(revset_node, |revset, span| {
+                    let revset = evaluate_user_revset(language, diagnostics, span, revset)?;
+                    revset.iter().try_collect().map_err(|err| {
+                        TemplateParseError::expression("Failed to evaluate revset", span)
+                            .with_source(err)
+                    })
+                })?;
+
+            let repo = language.repo;
+            let out_property = self_property.and_then(move |commit| {
+                let ids = repo
+                    .index()
+                    .common_ancestors(slice::from_ref(commit.id()), &other_ids);
+                Ok(ids
+                    .iter()
+                    .map(|id| repo.store().get_commit(id))
+                    .try_collect()?)
+            });
+            Ok(L::wrap_commit_list(out_property))
+        },
+    );
     map.insert(
         "conflict",
         |_language, _diagnostics, _build_ctx, self_property, function| {
@@ -1047,6 +1134,28 @@ fn builtin_commit_methods<'repo>() -> CommitTemplateBuildMethodFnMap<'repo, Comm
             Ok(L::wrap_tree_diff(out_property))
         },
     );
+    map.insert(
+        "touched_paths",
+        |language, diagnostics, _build_ctx, self_property, function| {
+            let [files_node] = function.expect_exact_arguments()?;
+            let files = expect_fileset_literal(diagnostics, files_node, language.path_converter)?;
+            let repo = language.repo;
+            let matcher: Rc<dyn Matcher> = files.to_matcher().into();
+            // Only the paths are needed, so file contents and copies aren't
+            // looked up.
+            let out_property = self_property.and_then(move |commit| {
+                let from_tree = commit.parent_tree(repo)?;
+                let to_tree = commit.tree()?;
+                let paths: Vec<_> = from_tree
+                    .diff_stream(&to_tree, &*matcher)
+                    .map(|entry| entry.values.map(|_| entry.path))
+                    .try_collect()
+                    .block_on()?;
+                Ok(paths)
+            });
+            Ok(L::wrap_repo_path_list(out_property))
+        },
+    );
     map.insert(
         "root",
         |language, _diagnostics, _build_ctx, self_property, function| {
@@ -1082,10 +1191,14 @@ fn expect_fileset_literal(
 ) -> Result<FilesetExpression, TemplateParseError> {
     template_parser::expect_string_literal_with(node, |text, span| {
         let mut inner_diagnostics = FilesetDiagnostics::new();
-        let expression =
-            fileset::parse(&mut inner_diagnostics, text, path_converter).map_err(|err| {
-                TemplateParseError::expression("In fileset expression", span).with_source(err)
-            })?;
+        let expression = fileset::parse(
+            &mut inner_diagnostics,
+            text,
+            &FilesetParseContext::new(path_converter),
+        )
+        .map_err(|err| {
+            TemplateParseError::expression("In fileset expression", span).with_source(err)
+        })?;
         diagnostics.extend_with(inner_diagnostics, |diag| {
             TemplateParseError::expression("In fileset expression", span).with_source(diag)
         });
@@ -1145,9 +1258,13 @@ pub struct RefName {
     target: RefTarget,
     /// Local ref metadata which tracks this remote ref.
     tracking_ref: Option<TrackingRef>,
+    /// Remote refs tracked by this local ref.
+    tracked_remote_refs: Option<TrackedRemoteRefs>,
     /// Local ref is synchronized with all tracking remotes, or tracking remote
     /// ref is synchronized with the local.
     synced: bool,
+    /// Creation and last-move metadata of the local bookmark.
+    metadata: Option<BookmarkMetadata>,
 }
 
 #[derive(Debug)]
@@ -1160,6 +1277,20 @@ struct TrackingRef {
     behind_count: OnceCell<SizeHint>,
 }
 
+#[derive(Debug)]
+struct TrackedRemoteRefs {
+    /// Remote refs tracked by the local ref, excluding the Git-tracking refs.
+    refs: Vec<Rc<RefName>>,
+    /// Number of commits ahead of the tracked remote refs.
+    ahead_count: OnceCell<SizeHint>,
+    /// Number of commits behind of the tracked remote refs.
+    behind_count: OnceCell<SizeHint>,
+}
+
+/// Maximum number of commits counted by `RefName::ahead_count()` and
+/// `RefName::behind_count()`. Larger counts are reported as lower bounds.
+const AHEAD_BEHIND_COUNT_LIMIT: usize = 1000;
+
 impl RefName {
     // RefName is wrapped by Rc<T> to make it cheaply cloned and share
     // lazy-evaluation results across clones.
@@ -1169,23 +1300,47 @@ impl RefName {
     pub fn local<'a>(
         name: impl Into<String>,
         target: RefTarget,
-        remote_refs: impl IntoIterator<Item = &'a RemoteRef>,
+        remote_refs: impl IntoIterator<Item = (&'a str, &'a RemoteRef)>,
+        metadata: Option<BookmarkMetadata>,
     ) -> Rc<Self> {
-        let synced = remote_refs
+        let name = name.into();
+        let tracking_remote_refs = remote_refs
             .into_iter()
-            .all(|remote_ref| !remote_ref.is_tracking() || remote_ref.target == target);
+            .filter(|(_, remote_ref)| remote_ref.is_tracking())
+            .collect_vec();
+        let synced = tracking_remote_refs
+            .iter()
+            .all(|(_, remote_ref)| remote_ref.target == target);
+        let refs = tracking_remote_refs
+            .iter()
+            .filter(|&&(remote_name, _)| !jj_lib::git::is_special_git_remote(remote_name))
+            .map(|&(remote_name, remote_ref)| {
+                RefName::remote(&name, remote_name, remote_ref.clone(), &target)
+            })
+            .collect();
+        let count = if synced {
+            OnceCell::from((0, Some(0))) // fast path for synced remotes
+        } else {
+            OnceCell::new()
+        };
         Rc::new(RefName {
-            name: name.into(),
+            name,
             remote: None,
             target,
             tracking_ref: None,
+            tracked_remote_refs: Some(TrackedRemoteRefs {
+                refs,
+                ahead_count: count.clone(),
+                behind_count: count,
+            }),
             synced,
+            metadata,
         })
     }
 
     /// Creates local ref representation which doesn't track any remote refs.
     pub fn local_only(name: impl Into<String>, target: RefTarget) -> Rc<Self> {
-        Self::local(name, target, [])
+        Self::local(name, target, [], None)
     }
 
     /// Creates remote ref representation which might be tracked by a local ref
@@ -1214,7 +1369,9 @@ impl RefName {
             remote: Some(remote_name.into()),
             target: remote_ref.target,
             tracking_ref,
+            tracked_remote_refs: None,
             synced,
+            metadata: None,
         })
     }
 
@@ -1229,7 +1386,9 @@ impl RefName {
             remote: Some(remote_name.into()),
             target,
             tracking_ref: None,
+            tracked_remote_refs: None,
             synced: false, // has no local counterpart
+            metadata: None,
         })
     }
 
@@ -1288,7 +1447,7 @@ impl RefName {
     }
 
     /// Number of commits ahead of the tracking local ref.
-    fn tracking_ahead_count(&self, repo: &dyn Repo) -> Result<SizeHint, TemplatePropertyError> {
+    pub fn tracking_ahead_count(&self, repo: &dyn Repo) -> Result<SizeHint, TemplatePropertyError> {
         let Some(tracking) = &self.tracking_ref else {
             return Err(TemplatePropertyError("Not a tracked remote ref".into()));
         };
@@ -1303,7 +1462,10 @@ impl RefName {
     }
 
     /// Number of commits behind of the tracking local ref.
-    fn tracking_behind_count(&self, repo: &dyn Repo) -> Result<SizeHint, TemplatePropertyError> {
+    pub fn tracking_behind_count(
+        &self,
+        repo: &dyn Repo,
+    ) -> Result<SizeHint, TemplatePropertyError> {
         let Some(tracking) = &self.tracking_ref else {
             return Err(TemplatePropertyError("Not a tracked remote ref".into()));
         };
@@ -1316,6 +1478,69 @@ impl RefName {
             })
             .copied()
     }
+
+    /// Remote refs tracked by this local ref.
+    fn tracked_remote_refs(&self) -> Result<&[Rc<RefName>], TemplatePropertyError> {
+        let Some(tracked) = &self.tracked_remote_refs else {
+            return Err(TemplatePropertyError("Not a local ref".into()));
+        };
+        Ok(&tracked.refs)
+    }
+
+    /// Number of commits ahead of the tracked remote refs.
+    fn ahead_count(&self, repo: &dyn Repo) -> Result<SizeHint, TemplatePropertyError> {
+        let tracked = self.tracked_remote_refs_to_count()?;
+        tracked
+            .ahead_count
+            .get_or_try_init(|| {
+                let remote_ids = tracked_remote_ids(&tracked.refs);
+                let self_ids = self.target.added_ids().cloned().collect_vec();
+                count_commits_capped(revset::walk_revs(repo, &self_ids, &remote_ids)?.as_ref())
+            })
+            .copied()
+    }
+
+    /// Number of commits behind of the tracked remote refs.
+    fn behind_count(&self, repo: &dyn Repo) -> Result<SizeHint, TemplatePropertyError> {
+        let tracked = self.tracked_remote_refs_to_count()?;
+        tracked
+            .behind_count
+            .get_or_try_init(|| {
+                let remote_ids = tracked_remote_ids(&tracked.refs);
+                let self_ids = self.target.added_ids().cloned().collect_vec();
+                count_commits_capped(revset::walk_revs(repo, &remote_ids, &self_ids)?.as_ref())
+            })
+            .copied()
+    }
+
+    fn tracked_remote_refs_to_count(&self) -> Result<&TrackedRemoteRefs, TemplatePropertyError> {
+        match &self.tracked_remote_refs {
+            None => Err(TemplatePropertyError("Not a local ref".into())),
+            Some(tracked) if tracked.refs.is_empty() => {
+                Err(TemplatePropertyError("Not tracking any remote refs".into()))
+            }
+            Some(tracked) => Ok(tracked),
+        }
+    }
+}
+
+fn tracked_remote_ids(refs: &[Rc<RefName>]) -> Vec<CommitId> {
+    refs.iter()
+        .flat_map(|ref_name| ref_name.target.added_ids())
+        .cloned()
+        .collect()
+}
+
+fn count_commits_capped(revset: &dyn Revset) -> Result<SizeHint, TemplatePropertyError> {
+    let count = revset
+        .iter()
+        .take(AHEAD_BEHIND_COUNT_LIMIT + 1)
+        .process_results(|iter| iter.count())?;
+    if count > AHEAD_BEHIND_COUNT_LIMIT {
+        Ok((AHEAD_BEHIND_COUNT_LIMIT, None))
+    } else {
+        Ok((count, Some(count)))
+    }
 }
 
 // If wrapping with Rc<T> becomes common, add generic impl for Rc<T>.
@@ -1453,6 +1678,65 @@ fn builtin_ref_name_methods<'repo>() -> CommitTemplateBuildMethodFnMap<'repo, Rc
             Ok(L::wrap_size_hint(out_property))
         },
     );
+    map.insert(
+        "tracked_remote_refs",
+        |_language, _diagnostics, _build_ctx, self_property, function| {
+            function.expect_no_arguments()?;
+            let out_property =
+                self_property.and_then(|ref_name| Ok(ref_name.tracked_remote_refs()?.to_vec()));
+            Ok(L::wrap_ref_name_list(out_property))
+        },
+    );
+    map.insert(
+        "created",
+        |_language, _diagnostics, _build_ctx, self_property, function| {
+            function.expect_no_arguments()?;
+            let out_property =
+                self_property.map(|ref_name| ref_name.metadata.as_ref().and_then(|m| m.created));
+            Ok(L::wrap_timestamp_opt(out_property))
+        },
+    );
+    map.insert(
+        "moved",
+        |_language, _diagnostics, _build_ctx, self_property, function| {
+            function.expect_no_arguments()?;
+            let out_property =
+                self_property.map(|ref_name| ref_name.metadata.as_ref().map(|m| m.moved));
+            Ok(L::wrap_timestamp_opt(out_property))
+        },
+    );
+    map.insert(
+        "moved_by",
+        |_language, _diagnostics, _build_ctx, self_property, function| {
+            function.expect_no_arguments()?;
+            let out_property = self_property.map(|ref_name| {
+                ref_name
+                    .metadata
+                    .as_ref()
+                    .map(|m| m.moved_by.clone())
+                    .unwrap_or_default()
+            });
+            Ok(L::wrap_string(out_property))
+        },
+    );
+    map.insert(
+        "ahead_count",
+        |language, _diagnostics, _build_ctx, self_property, function| {
+            function.expect_no_arguments()?;
+            let repo = language.repo;
+            let out_property = self_property.and_then(|ref_name| ref_name.ahead_count(repo));
+            Ok(L::wrap_size_hint(out_property))
+        },
+    );
+    map.insert(
+        "behind_count",
+        |language, _diagnostics, _build_ctx, self_property, function| {
+            function.expect_no_arguments()?;
+            let repo = language.repo;
+            let out_property = self_property.and_then(|ref_name| ref_name.behind_count(repo));
+            Ok(L::wrap_size_hint(out_property))
+        },
+    );
     map
 }
 
@@ -1484,7 +1768,8 @@ fn build_bookmarks_index(repo: &dyn Repo) -> RefNamesIndex {
             let ref_name = RefName::local(
                 bookmark_name,
                 local_target.clone(),
-                remote_refs.iter().map(|&(_, remote_ref)| remote_ref),
+                remote_refs.clone(),
+                repo.view().get_bookmark_metadata(bookmark_name).cloned(),
             );
             index.insert(local_target.added_ids(), ref_name);
         }
@@ -1514,6 +1799,12 @@ impl Template for RepoPathBuf {
     }
 }
 
+impl Template for Vec<RepoPathBuf> {
+    fn format(&self, formatter: &mut TemplateFormatter) -> io::Result<()> {
+        templater::format_joined(formatter, self, " ")
+    }
+}
+
 fn builtin_repo_path_methods<'repo>() -> CommitTemplateBuildMethodFnMap<'repo, RepoPathBuf> {
     type L<'repo> = CommitTemplateLanguage<'repo>;
     // Not using maplit::hashmap!{} or custom declarative macro here because
@@ -1889,6 +2180,7 @@ fn builtin_tree_diff_methods<'repo>() -> CommitTemplateBuildMethodFnMap<'repo, T
                             store,
                             tree_diff,
                             &options,
+                            &diff_util::DiffPathLabels::default(),
                             conflict_marker_style,
                         )
                     })
@@ -1912,8 +2204,11 @@ fn builtin_tree_diff_methods<'repo>() -> CommitTemplateBuildMethodFnMap<'repo, T
                 })
                 .transpose()?;
             let path_converter = language.path_converter;
-            // No user configuration exists for diff stat.
-            let options = diff_util::DiffStatOptions::default();
+            let options =
+                diff_util::DiffStatOptions::from_settings(language.settings()).map_err(|err| {
+                    let message = "Failed to load diff settings";
+                    TemplateParseError::expression(message, function.name_span).with_source(err)
+                })?;
             let conflict_marker_style = language.conflict_marker_style;
             // TODO: cache and reuse stats within the current evaluation?
             let out_property = (self_property, width_property).and_then(move |(diff, width)| {
@@ -2080,6 +2375,49 @@ fn builtin_tree_entry_methods<'repo>() -> CommitTemplateBuildMethodFnMap<'repo,
             Ok(L::wrap_boolean(out_property))
         },
     );
+    map.insert(
+        "size",
+        |language, _diagnostics, _build_ctx, self_property, function| {
+            function.expect_no_arguments()?;
+            let store = language.repo.store().clone();
+            let out_property = self_property.and_then(move |entry| {
+                let Some(Some(TreeValue::File { id, .. })) = entry.value.as_resolved() else {
+                    return Ok(None);
+                };
+                let size = store.file_size(&entry.path, id)?;
+                Ok(Some(i64::try_from(size)?))
+            });
+            Ok(L::wrap_integer_opt(out_property))
+        },
+    );
+    map.insert(
+        "symlink_target",
+        |language, _diagnostics, _build_ctx, self_property, function| {
+            function.expect_no_arguments()?;
+            let store = language.repo.store().clone();
+            let out_property = self_property.and_then(move |entry| {
+                let Some(Some(TreeValue::Symlink(id))) = entry.value.as_resolved() else {
+                    return Ok(String::new());
+                };
+                Ok(store.read_symlink(&entry.path, id)?)
+            });
+            Ok(L::wrap_string(out_property))
+        },
+    );
+    map.insert(
+        "object_id",
+        |_language, _diagnostics, _build_ctx, self_property, function| {
+            function.expect_no_arguments()?;
+            let out_property = self_property.map(|entry| match entry.value.as_resolved() {
+                Some(Some(TreeValue::File { id, .. })) => id.hex(),
+                Some(Some(TreeValue::Symlink(id))) => id.hex(),
+                Some(Some(TreeValue::Tree(id))) => id.hex(),
+                Some(Some(TreeValue::GitSubmodule(id))) => id.hex(),
+                Some(None) | None | Some(Some(TreeValue::Conflict(_))) => String::new(),
+            });
+            Ok(L::wrap_string(out_property))
+        },
+    );
     map
 }
 
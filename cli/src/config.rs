@@ -502,6 +502,9 @@ pub fn default_config_layers() -> Vec<ConfigLayer> {
     if cfg!(windows) {
         layers.push(parse(include_str!("config/windows.toml")));
     }
+    if cfg!(target_os = "macos") {
+        layers.push(parse(include_str!("config/macos.toml")));
+    }
     layers
 }
 
@@ -573,7 +576,10 @@ pub fn parse_config_args(
             }
             ConfigArgKind::File => {
                 for (_, path) in chunk {
-                    layers.push(ConfigLayer::load_from_file(source, path.into())?);
+                    layers.extend(ConfigLayer::load_from_file_with_includes(
+                        source,
+                        path.into(),
+                    )?);
                 }
             }
         }
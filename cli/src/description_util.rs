@@ -14,13 +14,18 @@ use itertools::Itertools;
 use jj_lib::backend::CommitId;
 use jj_lib::commit::Commit;
 use jj_lib::config::ConfigGetError;
+use jj_lib::description;
 use jj_lib::file_util::IoResultExt as _;
 use jj_lib::file_util::PathError;
 use jj_lib::settings::UserSettings;
+use regex::Regex;
+use serde::Deserialize;
 use thiserror::Error;
 
 use crate::cli_util::short_commit_hash;
 use crate::cli_util::WorkspaceCommandTransaction;
+use crate::command_error::config_error_with_message;
+use crate::command_error::user_error_with_hint;
 use crate::command_error::CommandError;
 use crate::config::CommandNameAndArgs;
 use crate::formatter::PlainTextFormatter;
@@ -283,22 +288,6 @@ where
     })
 }
 
-/// Combines the descriptions from the input commits. If only one is non-empty,
-/// then that one is used.
-pub fn try_combine_messages(sources: &[Commit], destination: &Commit) -> Option<String> {
-    let non_empty = sources
-        .iter()
-        .chain(std::iter::once(destination))
-        .filter(|c| !c.description().is_empty())
-        .take(2)
-        .collect_vec();
-    match *non_empty.as_slice() {
-        [] => Some(String::new()),
-        [commit] => Some(commit.description().to_owned()),
-        [_, _, ..] => None,
-    }
-}
-
 /// Produces a combined description with "JJ: " comment lines.
 ///
 /// This includes empty descriptins too, so the user doesn't have to wonder why
@@ -327,6 +316,121 @@ pub fn join_message_paragraphs(paragraphs: &[String]) -> String {
         .join("\n")
 }
 
+/// How violations of the `[commit.checks]` rules are reported.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+pub enum DescriptionCheckLevel {
+    /// Print a warning and use the description anyway.
+    Warn,
+    /// Refuse to use the description.
+    Error,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+struct DescriptionChecksConfig {
+    level: DescriptionCheckLevel,
+    #[serde(default)]
+    max_subject_length: Option<usize>,
+    #[serde(default)]
+    required_trailers: Vec<String>,
+    #[serde(default)]
+    subject_pattern: Option<String>,
+}
+
+/// Rules that new descriptions are checked against, configured under
+/// `[commit.checks]`.
+#[derive(Clone, Debug)]
+pub struct DescriptionChecks {
+    level: DescriptionCheckLevel,
+    max_subject_length: Option<usize>,
+    required_trailers: Vec<String>,
+    subject_pattern: Option<Regex>,
+}
+
+impl DescriptionChecks {
+    pub fn from_settings(settings: &UserSettings) -> Result<Self, CommandError> {
+        let config: DescriptionChecksConfig = settings.get("commit.checks")?;
+        let subject_pattern = config
+            .subject_pattern
+            .map(|pattern| {
+                Regex::new(&pattern).map_err(|err| {
+                    config_error_with_message("Invalid regex in commit.checks.subject-pattern", err)
+                })
+            })
+            .transpose()?;
+        Ok(DescriptionChecks {
+            level: config.level,
+            max_subject_length: config.max_subject_length,
+            required_trailers: config.required_trailers,
+            subject_pattern,
+        })
+    }
+
+    /// Returns the list of rules the description violates.
+    ///
+    /// Empty descriptions are never checked.
+    pub fn violations(&self, description: &str) -> Vec<String> {
+        let mut violations = vec![];
+        if description.trim().is_empty() {
+            return violations;
+        }
+        let subject = description.lines().next().unwrap_or_default();
+        if let Some(max_length) = self.max_subject_length {
+            if subject.chars().count() > max_length {
+                violations.push(format!(
+                    "The subject line is longer than {max_length} characters"
+                ));
+            }
+        }
+        if let Some(pattern) = &self.subject_pattern {
+            if !pattern.is_match(subject) {
+                violations.push(format!(
+                    "The subject line does not match the pattern {:?}",
+                    pattern.as_str()
+                ));
+            }
+        }
+        let trailers = description::trailers(description);
+        for key in &self.required_trailers {
+            if !trailers.iter().any(|trailer| trailer.key == *key) {
+                violations.push(format!("The trailer {key:?} is missing"));
+            }
+        }
+        violations
+    }
+
+    /// Checks the description against the configured rules.
+    ///
+    /// Violations are reported as warnings, or as an error if
+    /// `commit.checks.level` is `"error"`. Nothing is checked if `no_verify`
+    /// is set.
+    pub fn check(&self, ui: &Ui, description: &str, no_verify: bool) -> Result<(), CommandError> {
+        if no_verify {
+            return Ok(());
+        }
+        let violations = self.violations(description);
+        if violations.is_empty() {
+            return Ok(());
+        }
+        let subject = description.lines().next().unwrap_or_default();
+        let message = format!(
+            "The description {subject:?} does not pass the commit checks:\n{}",
+            violations.iter().map(|v| format!("  {v}")).join("\n")
+        );
+        match self.level {
+            DescriptionCheckLevel::Warn => {
+                writeln!(ui.warning_default(), "{message}")?;
+                Ok(())
+            }
+            DescriptionCheckLevel::Error => Err(user_error_with_hint(
+                message,
+                "Use --no-verify to skip the checks.",
+            )),
+        }
+    }
+}
+
 /// Renders commit description template, which will be edited by user.
 pub fn description_template(
     ui: &Ui,
@@ -362,7 +466,11 @@ mod tests {
     use indoc::indoc;
     use maplit::hashmap;
 
+    use regex::Regex;
+
     use super::parse_bulk_edit_message;
+    use crate::description_util::DescriptionCheckLevel;
+    use crate::description_util::DescriptionChecks;
     use crate::description_util::ParseBulkEditMessageError;
 
     #[test]
@@ -515,4 +623,40 @@ mod tests {
         assert!(result.duplicates.is_empty());
         assert!(result.unexpected.is_empty());
     }
+
+    #[test]
+    fn test_description_check_violations() {
+        let checks = DescriptionChecks {
+            level: DescriptionCheckLevel::Warn,
+            max_subject_length: Some(10),
+            required_trailers: vec!["Signed-off-by".to_owned()],
+            subject_pattern: Some(Regex::new("^[a-z]+: ").unwrap()),
+        };
+        assert!(checks.violations("").is_empty());
+        assert!(checks
+            .violations("cli: foo\n\nSigned-off-by: me\n")
+            .is_empty());
+        assert_eq!(
+            checks.violations("cli: foo bar\n\nSigned-off-by: me\n"),
+            ["The subject line is longer than 10 characters"]
+        );
+        assert_eq!(
+            checks.violations("foo\n\nSigned-off-by: me\n"),
+            [r#"The subject line does not match the pattern "^[a-z]+: ""#]
+        );
+        // The subject paragraph doesn't contain trailers
+        assert_eq!(
+            checks.violations("Signed-off-by: me\n"),
+            [
+                "The subject line is longer than 10 characters",
+                r#"The subject line does not match the pattern "^[a-z]+: ""#,
+                r#"The trailer "Signed-off-by" is missing"#,
+            ]
+        );
+        // Trailers must be in the last paragraph
+        assert_eq!(
+            checks.violations("cli: foo\n\nSigned-off-by: me\n\nbody\n"),
+            [r#"The trailer "Signed-off-by" is missing"#]
+        );
+    }
 }
@@ -14,15 +14,19 @@
 
 use std::borrow::Borrow;
 use std::cmp::max;
+use std::collections::BTreeSet;
 use std::io;
 use std::iter;
 use std::mem;
 use std::ops::Range;
 use std::path::Path;
 use std::path::PathBuf;
+use std::slice;
 
 use bstr::BStr;
+use bstr::BString;
 use futures::executor::block_on_stream;
+use futures::future;
 use futures::stream::BoxStream;
 use futures::StreamExt as _;
 use futures::TryStreamExt as _;
@@ -36,6 +40,7 @@ use jj_lib::commit::Commit;
 use jj_lib::config::ConfigGetError;
 use jj_lib::config::ConfigGetResultExt as _;
 use jj_lib::conflicts::materialize_merge_result_to_bytes;
+use jj_lib::conflicts::materialize_tree_value;
 use jj_lib::conflicts::materialized_diff_stream;
 use jj_lib::conflicts::ConflictMarkerStyle;
 use jj_lib::conflicts::MaterializedTreeDiffEntry;
@@ -52,9 +57,12 @@ use jj_lib::diff::Diff;
 use jj_lib::diff::DiffHunk;
 use jj_lib::diff::DiffHunkContentVec;
 use jj_lib::diff::DiffHunkKind;
+use jj_lib::diff::DiffHunkRange;
+use jj_lib::diff::DiffOptions;
 use jj_lib::files::DiffLineHunkSide;
 use jj_lib::files::DiffLineIterator;
 use jj_lib::files::DiffLineNumber;
+use jj_lib::matchers::FilesMatcher;
 use jj_lib::matchers::Matcher;
 use jj_lib::merge::MergedTreeValue;
 use jj_lib::merged_tree::MergedTree;
@@ -62,6 +70,7 @@ use jj_lib::object_id::ObjectId;
 use jj_lib::repo::Repo;
 use jj_lib::repo_path::InvalidRepoPathError;
 use jj_lib::repo_path::RepoPath;
+use jj_lib::repo_path::RepoPathBuf;
 use jj_lib::repo_path::RepoPathUiConverter;
 use jj_lib::rewrite::rebase_to_dest_parent;
 use jj_lib::settings::UserSettings;
@@ -71,6 +80,7 @@ use thiserror::Error;
 use tracing::instrument;
 use unicode_width::UnicodeWidthStr as _;
 
+use crate::cli_util::short_commit_hash;
 use crate::config::CommandNameAndArgs;
 use crate::formatter::Formatter;
 use crate::merge_tools;
@@ -85,10 +95,13 @@ use crate::ui::Ui;
 
 #[derive(clap::Args, Clone, Debug)]
 #[command(next_help_heading = "Diff Formatting Options")]
-#[command(group(clap::ArgGroup::new("short-format").args(&["summary", "stat", "types", "name_only"])))]
-#[command(group(clap::ArgGroup::new("long-format").args(&["git", "color_words", "tool"])))]
+#[command(group(clap::ArgGroup::new("short-format").args(&["summary", "stat", "types", "name_only", "name_status"])))]
+#[command(group(clap::ArgGroup::new("long-format").args(&["git", "color_words", "word_diff", "json", "tool"])))]
 pub struct DiffFormatArgs {
     /// For each path, show only whether it was modified, added, or deleted
+    ///
+    /// A path whose type changed between a regular file, a symlink, and a Git
+    /// submodule is shown as 'T'.
     #[arg(long, short)]
     pub summary: bool,
     /// Show a histogram of the changes
@@ -109,12 +122,33 @@ pub struct DiffFormatArgs {
     ///    `jj diff -r @- --name-only | xargs perl -pi -e's/OLD/NEW/g`
     #[arg(long)]
     pub name_only: bool,
+    /// For each path, show its status and path in the same format as `git
+    /// diff --name-status`
+    ///
+    /// Each line consists of a status letter and the path, separated by a tab.
+    /// For copied and renamed paths, the letter is followed by a similarity
+    /// score and both the source and target paths, e.g. `R100<TAB>old<TAB>new`.
+    #[arg(long)]
+    pub name_status: bool,
     /// Show a Git-format diff
     #[arg(long)]
     pub git: bool,
     /// Show a word-level diff with changes indicated only by color
     #[arg(long)]
     pub color_words: bool,
+    /// Show a Git-format diff with changed words marked inline
+    ///
+    /// Removed words are enclosed in `[-` and `-]`, and added words in `{+`
+    /// and `+}`, as in `git diff --word-diff=plain`. Unlike `--color-words`,
+    /// the changes are marked even if the output isn't colored.
+    #[arg(long)]
+    pub word_diff: bool,
+    /// Print each changed file as a JSON object, one per line
+    ///
+    /// The object contains the status, the source and target paths and modes,
+    /// and the hunks with their line ranges and lines.
+    #[arg(long)]
+    pub json: bool,
     /// Generate diff by external command
     #[arg(long)]
     pub tool: Option<String>,
@@ -129,6 +163,64 @@ pub struct DiffFormatArgs {
     /// Ignore changes in amount of whitespace when comparing lines.
     #[arg(long, conflicts_with = "ignore_all_space")] // short = 'b'
     ignore_space_change: bool,
+    /// Ignore changes whose removed and added lines all match the given
+    /// regular expression
+    ///
+    /// Useful for hiding changes in timestamps, version headers, and the like.
+    /// Files whose changes are all ignored aren't shown by the formats that
+    /// compare file contents. Overrides the `diff.ignore-matching-lines` setting.
+    #[arg(long, value_name = "REGEX", value_parser = regex::bytes::Regex::new)]
+    ignore_matching_lines: Option<regex::bytes::Regex>,
+    /// Keep lines starting with the given text unchanged if possible
+    ///
+    /// A line is used as an anchor only if it occurs exactly once in both
+    /// sides. This can be repeated to specify multiple anchors.
+    #[arg(long, value_name = "TEXT")]
+    anchored: Vec<String>,
+    /// Only show renames whose contents are at least this similar
+    ///
+    /// The threshold is a percentage like `90%`, or digits taken as a
+    /// fraction like Git's `-M` option (`9` and `90` both mean 90%). Renames
+    /// below the threshold are shown as a deletion and an addition. Rename
+    /// detection by the backend considers files at least 50% similar, so
+    /// lower thresholds have no effect.
+    #[arg(
+        long,
+        value_name = "N",
+        num_args = 0..=1,
+        default_missing_value = "50%",
+        value_parser = parse_similarity_threshold,
+    )] // short = 'M'
+    find_renames: Option<usize>,
+    /// Only show copies whose contents are at least this similar
+    ///
+    /// The threshold is specified as for `--find-renames`. Copies below the
+    /// threshold are shown as an addition.
+    #[arg(
+        long,
+        value_name = "N",
+        num_args = 0..=1,
+        default_missing_value = "50%",
+        value_parser = parse_similarity_threshold,
+    )] // short = 'C'
+    find_copies: Option<usize>,
+}
+
+/// Parses similarity threshold in the format of Git's `-M<n>` option.
+fn parse_similarity_threshold(s: &str) -> Result<usize, String> {
+    let percent = if let Some(digits) = s.strip_suffix('%') {
+        digits
+            .parse()
+            .ok()
+            .filter(|&n: &usize| n <= 100 && digits.bytes().all(|b| b.is_ascii_digit()))
+    } else if !s.is_empty() && s.bytes().all(|b| b.is_ascii_digit()) {
+        // Digits are the fractional part of a decimal number: "9" and "90"
+        // mean 90%, "09" means 9%.
+        format!("{s:0<2}")[..2].parse().ok()
+    } else {
+        None
+    };
+    percent.ok_or_else(|| format!("Invalid similarity threshold: {s}"))
 }
 
 #[derive(Clone, Debug, Eq, PartialEq)]
@@ -138,8 +230,11 @@ pub enum DiffFormat {
     Stat(Box<DiffStatOptions>),
     Types,
     NameOnly,
+    NameStatus,
     Git(Box<UnifiedDiffOptions>),
     ColorWords(Box<ColorWordsDiffOptions>),
+    WordDiff(Box<UnifiedDiffOptions>),
+    Json(Box<UnifiedDiffOptions>),
     Tool(Box<ExternalMergeTool>),
 }
 
@@ -186,6 +281,9 @@ fn diff_formats_from_args(
     if args.name_only {
         formats.push(DiffFormat::NameOnly);
     }
+    if args.name_status {
+        formats.push(DiffFormat::NameStatus);
+    }
     if args.git {
         let mut options = UnifiedDiffOptions::from_settings(settings)?;
         options.merge_args(args);
@@ -196,8 +294,18 @@ fn diff_formats_from_args(
         options.merge_args(args);
         formats.push(DiffFormat::ColorWords(Box::new(options)));
     }
+    if args.word_diff {
+        let mut options = UnifiedDiffOptions::from_settings(settings)?;
+        options.merge_args(args);
+        formats.push(DiffFormat::WordDiff(Box::new(options)));
+    }
+    if args.json {
+        let mut options = UnifiedDiffOptions::from_settings(settings)?;
+        options.merge_args(args);
+        formats.push(DiffFormat::Json(Box::new(options)));
+    }
     if args.stat {
-        let mut options = DiffStatOptions::default();
+        let mut options = DiffStatOptions::from_settings(settings)?;
         options.merge_args(args);
         formats.push(DiffFormat::Stat(Box::new(options)));
     }
@@ -234,6 +342,7 @@ fn default_diff_format(
         "summary" => Ok(DiffFormat::Summary),
         "types" => Ok(DiffFormat::Types),
         "name-only" => Ok(DiffFormat::NameOnly),
+        "name-status" => Ok(DiffFormat::NameStatus),
         "git" => {
             let mut options = UnifiedDiffOptions::from_settings(settings)?;
             options.merge_args(args);
@@ -244,8 +353,13 @@ fn default_diff_format(
             options.merge_args(args);
             Ok(DiffFormat::ColorWords(Box::new(options)))
         }
+        "word-diff" => {
+            let mut options = UnifiedDiffOptions::from_settings(settings)?;
+            options.merge_args(args);
+            Ok(DiffFormat::WordDiff(Box::new(options)))
+        }
         "stat" => {
-            let mut options = DiffStatOptions::default();
+            let mut options = DiffStatOptions::from_settings(settings)?;
             options.merge_args(args);
             Ok(DiffFormat::Stat(Box::new(options)))
         }
@@ -274,12 +388,80 @@ pub enum DiffRenderError {
     Io(#[from] io::Error),
 }
 
+/// Minimum similarity percentages of copies and renames to be reported.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct SimilarityThresholds {
+    pub rename: Option<usize>,
+    pub copy: Option<usize>,
+}
+
+impl SimilarityThresholds {
+    pub fn from_args(args: &DiffFormatArgs) -> Self {
+        SimilarityThresholds {
+            rename: args.find_renames,
+            copy: args.find_copies,
+        }
+    }
+}
+
+/// How to show the changes of merge commits.
+#[derive(clap::ValueEnum, Clone, Copy, Debug, Eq, PartialEq)]
+pub enum DiffMerges {
+    /// Show the changes compared to the first parent
+    FirstParent,
+    /// Show the changes compared to each parent separately
+    All,
+    /// Show only the changes that differ from all parents, in a single diff
+    ///
+    /// The Git format shows a combined diff like `git diff --cc`. Other formats
+    /// show the changes compared to the merged parents, limited to the paths
+    /// that differ from all parents.
+    Combined,
+}
+
+/// How paths are labeled in the diff output.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct DiffPathLabels {
+    /// Prefix of the source paths in the Git format.
+    pub src_prefix: String,
+    /// Prefix of the destination paths in the Git format.
+    pub dst_prefix: String,
+    /// Directory which paths are shown relative to. Paths are shown relative
+    /// to the repo root in the Git format, and to the current directory in
+    /// the other formats if unset.
+    pub relative_dir: Option<RepoPathBuf>,
+}
+
+impl DiffPathLabels {
+    /// Returns the path string shown in the Git format.
+    fn git_path_string<'p>(&self, path: &'p RepoPath) -> &'p str {
+        self.relative_dir
+            .as_ref()
+            .and_then(|dir| path.strip_prefix(dir))
+            .unwrap_or(path)
+            .as_internal_file_string()
+    }
+}
+
+impl Default for DiffPathLabels {
+    fn default() -> Self {
+        DiffPathLabels {
+            src_prefix: "a/".to_owned(),
+            dst_prefix: "b/".to_owned(),
+            relative_dir: None,
+        }
+    }
+}
+
 /// Configuration and environment to render textual diff.
 pub struct DiffRenderer<'a> {
     repo: &'a dyn Repo,
     path_converter: &'a RepoPathUiConverter,
     conflict_marker_style: ConflictMarkerStyle,
     formats: Vec<DiffFormat>,
+    similarity_thresholds: SimilarityThresholds,
+    diff_merges: Option<DiffMerges>,
+    path_labels: DiffPathLabels,
 }
 
 impl<'a> DiffRenderer<'a> {
@@ -294,11 +476,34 @@ impl<'a> DiffRenderer<'a> {
             path_converter,
             conflict_marker_style,
             formats,
+            similarity_thresholds: SimilarityThresholds::default(),
+            diff_merges: None,
+            path_labels: DiffPathLabels::default(),
         }
     }
 
+    /// Sets the minimum similarity of copies and renames to be reported.
+    pub fn with_similarity_thresholds(mut self, thresholds: SimilarityThresholds) -> Self {
+        self.similarity_thresholds = thresholds;
+        self
+    }
+
+    /// Sets how the changes of merge commits are shown. If unset, merge
+    /// commits are compared to their merged parents.
+    pub fn with_diff_merges(mut self, diff_merges: Option<DiffMerges>) -> Self {
+        self.diff_merges = diff_merges;
+        self
+    }
+
+    /// Sets how paths are labeled in the diff output.
+    pub fn with_path_labels(mut self, path_labels: DiffPathLabels) -> Self {
+        self.path_labels = path_labels;
+        self
+    }
+
     /// Generates diff between `from_tree` and `to_tree`.
     #[expect(clippy::too_many_arguments)]
+    #[instrument(skip_all)]
     pub fn show_diff(
         &self,
         ui: &Ui, // TODO: remove Ui dependency if possible
@@ -309,10 +514,42 @@ impl<'a> DiffRenderer<'a> {
         copy_records: &CopyRecords,
         width: usize,
     ) -> Result<(), DiffRenderError> {
+        self.show_diff_with_formats(
+            ui,
+            formatter,
+            &self.formats,
+            from_tree,
+            to_tree,
+            matcher,
+            copy_records,
+            width,
+        )
+    }
+
+    #[expect(clippy::too_many_arguments)]
+    fn show_diff_with_formats(
+        &self,
+        ui: &Ui,
+        formatter: &mut dyn Formatter,
+        formats: &[DiffFormat],
+        from_tree: &MergedTree,
+        to_tree: &MergedTree,
+        matcher: &dyn Matcher,
+        copy_records: &CopyRecords,
+        width: usize,
+    ) -> Result<(), DiffRenderError> {
+        let filtered_records;
+        let copy_records = if self.similarity_thresholds == SimilarityThresholds::default() {
+            copy_records
+        } else {
+            filtered_records = self.filter_copy_records(from_tree, to_tree, copy_records)?;
+            &filtered_records
+        };
         formatter.with_label("diff", |formatter| {
             self.show_diff_inner(
                 ui,
                 formatter,
+                formats,
                 from_tree,
                 to_tree,
                 matcher,
@@ -322,11 +559,44 @@ impl<'a> DiffRenderer<'a> {
         })
     }
 
+    /// Drops copy records whose contents are less similar than the threshold.
+    fn filter_copy_records(
+        &self,
+        from_tree: &MergedTree,
+        to_tree: &MergedTree,
+        copy_records: &CopyRecords,
+    ) -> Result<CopyRecords, DiffRenderError> {
+        let store = self.repo.store();
+        let mut filtered = CopyRecords::default();
+        for record in copy_records.iter() {
+            let threshold = if to_tree.path_value(&record.source)?.is_absent() {
+                self.similarity_thresholds.rename
+            } else {
+                self.similarity_thresholds.copy
+            };
+            if let Some(threshold) = threshold {
+                let read_content = |tree: &MergedTree, path: &RepoPath| {
+                    let value =
+                        materialize_tree_value(store, path, tree.path_value(path)?).block_on()?;
+                    diff_content(path, value, self.conflict_marker_style)
+                };
+                let left = read_content(from_tree, &record.source)?;
+                let right = read_content(to_tree, &record.target)?;
+                if similarity_score(&left.contents, &right.contents) < threshold {
+                    continue;
+                }
+            }
+            filtered.add_records([Ok(record.clone())])?;
+        }
+        Ok(filtered)
+    }
+
     #[expect(clippy::too_many_arguments)]
     fn show_diff_inner(
         &self,
         ui: &Ui,
         formatter: &mut dyn Formatter,
+        formats: &[DiffFormat],
         from_tree: &MergedTree,
         to_tree: &MergedTree,
         matcher: &dyn Matcher,
@@ -334,8 +604,18 @@ impl<'a> DiffRenderer<'a> {
         width: usize,
     ) -> Result<(), DiffRenderError> {
         let store = self.repo.store();
-        let path_converter = self.path_converter;
-        for format in &self.formats {
+        let relative_path_converter;
+        let path_converter = if let Some(dir) = &self.path_labels.relative_dir {
+            let RepoPathUiConverter::Fs { base, .. } = self.path_converter;
+            relative_path_converter = RepoPathUiConverter::Fs {
+                cwd: dir.to_fs_path_unchecked(base),
+                base: base.clone(),
+            };
+            &relative_path_converter
+        } else {
+            self.path_converter
+        };
+        for format in formats {
             match format {
                 DiffFormat::Summary => {
                     let tree_diff =
@@ -345,6 +625,7 @@ impl<'a> DiffRenderer<'a> {
                 DiffFormat::Stat(options) => {
                     let tree_diff =
                         from_tree.diff_stream_with_copies(to_tree, matcher, copy_records);
+                    let tree_diff = filter_ignored_changes(store, tree_diff, &options.line_diff);
                     let stats =
                         DiffStats::calculate(store, tree_diff, options, self.conflict_marker_style)
                             .block_on()?;
@@ -360,20 +641,34 @@ impl<'a> DiffRenderer<'a> {
                         from_tree.diff_stream_with_copies(to_tree, matcher, copy_records);
                     show_names(formatter, tree_diff, path_converter)?;
                 }
+                DiffFormat::NameStatus => {
+                    let tree_diff =
+                        from_tree.diff_stream_with_copies(to_tree, matcher, copy_records);
+                    show_name_status(
+                        formatter,
+                        store,
+                        tree_diff,
+                        path_converter,
+                        self.conflict_marker_style,
+                    )?;
+                }
                 DiffFormat::Git(options) => {
                     let tree_diff =
                         from_tree.diff_stream_with_copies(to_tree, matcher, copy_records);
+                    let tree_diff = filter_ignored_changes(store, tree_diff, &options.line_diff);
                     show_git_diff(
                         formatter,
                         store,
                         tree_diff,
                         options,
+                        &self.path_labels,
                         self.conflict_marker_style,
                     )?;
                 }
                 DiffFormat::ColorWords(options) => {
                     let tree_diff =
                         from_tree.diff_stream_with_copies(to_tree, matcher, copy_records);
+                    let tree_diff = filter_ignored_changes(store, tree_diff, &options.line_diff);
                     show_color_words_diff(
                         formatter,
                         store,
@@ -383,6 +678,31 @@ impl<'a> DiffRenderer<'a> {
                         self.conflict_marker_style,
                     )?;
                 }
+                DiffFormat::WordDiff(options) => {
+                    let tree_diff =
+                        from_tree.diff_stream_with_copies(to_tree, matcher, copy_records);
+                    let tree_diff = filter_ignored_changes(store, tree_diff, &options.line_diff);
+                    show_word_diff(
+                        formatter,
+                        store,
+                        tree_diff,
+                        options,
+                        &self.path_labels,
+                        self.conflict_marker_style,
+                    )?;
+                }
+                DiffFormat::Json(options) => {
+                    let tree_diff =
+                        from_tree.diff_stream_with_copies(to_tree, matcher, copy_records);
+                    let tree_diff = filter_ignored_changes(store, tree_diff, &options.line_diff);
+                    show_json_diff(
+                        formatter,
+                        store,
+                        tree_diff,
+                        options,
+                        self.conflict_marker_style,
+                    )?;
+                }
                 DiffFormat::Tool(tool) => {
                     match tool.diff_invocation_mode {
                         DiffToolMode::FileByFile => {
@@ -445,6 +765,8 @@ impl<'a> DiffRenderer<'a> {
     }
 
     /// Generates diff of the given `commit` compared to its parents.
+    ///
+    /// Merge commits are shown as configured by [`Self::with_diff_merges()`].
     pub fn show_patch(
         &self,
         ui: &Ui,
@@ -453,6 +775,34 @@ impl<'a> DiffRenderer<'a> {
         matcher: &dyn Matcher,
         width: usize,
     ) -> Result<(), DiffRenderError> {
+        if commit.parent_ids().len() > 1 {
+            match self.diff_merges {
+                Some(DiffMerges::FirstParent) => {
+                    let parent_id = &commit.parent_ids()[0];
+                    return self
+                        .show_patch_from_parent(ui, formatter, commit, parent_id, matcher, width);
+                }
+                Some(DiffMerges::All) => {
+                    for parent_id in commit.parent_ids() {
+                        formatter.with_label("diff", |formatter| {
+                            writeln!(
+                                formatter.labeled("header"),
+                                "Changes from parent {}:",
+                                short_commit_hash(parent_id)
+                            )
+                        })?;
+                        self.show_patch_from_parent(
+                            ui, formatter, commit, parent_id, matcher, width,
+                        )?;
+                    }
+                    return Ok(());
+                }
+                Some(DiffMerges::Combined) => {
+                    return self.show_combined_patch(ui, formatter, commit, matcher, width);
+                }
+                None => {}
+            }
+        }
         let from_tree = commit.parent_tree(self.repo)?;
         let to_tree = commit.tree()?;
         let mut copy_records = CopyRecords::default();
@@ -470,6 +820,92 @@ impl<'a> DiffRenderer<'a> {
             width,
         )
     }
+
+    /// Generates diff of the given `commit` compared to one of its parents.
+    fn show_patch_from_parent(
+        &self,
+        ui: &Ui,
+        formatter: &mut dyn Formatter,
+        commit: &Commit,
+        parent_id: &CommitId,
+        matcher: &dyn Matcher,
+        width: usize,
+    ) -> Result<(), DiffRenderError> {
+        let from_tree = self.repo.store().get_commit(parent_id)?.tree()?;
+        let to_tree = commit.tree()?;
+        let mut copy_records = CopyRecords::default();
+        let records = get_copy_records(self.repo.store(), parent_id, commit.id(), matcher)?;
+        copy_records.add_records(records)?;
+        self.show_diff(
+            ui,
+            formatter,
+            &from_tree,
+            &to_tree,
+            matcher,
+            &copy_records,
+            width,
+        )
+    }
+
+    /// Generates combined diff of the given merge `commit`, which only
+    /// includes the paths that differ from all parents.
+    fn show_combined_patch(
+        &self,
+        ui: &Ui,
+        formatter: &mut dyn Formatter,
+        commit: &Commit,
+        matcher: &dyn Matcher,
+        width: usize,
+    ) -> Result<(), DiffRenderError> {
+        let to_tree = commit.tree()?;
+        let parent_trees: Vec<MergedTree> = commit
+            .parents()
+            .map(|parent| parent.and_then(|parent| parent.tree()))
+            .try_collect()?;
+        let mut paths: Option<BTreeSet<RepoPathBuf>> = None;
+        for parent_tree in &parent_trees {
+            let changed_paths: BTreeSet<RepoPathBuf> = parent_tree
+                .diff_stream(&to_tree, matcher)
+                .map(|entry| entry.path)
+                .collect()
+                .block_on();
+            paths = Some(match paths {
+                Some(paths) => paths.intersection(&changed_paths).cloned().collect(),
+                None => changed_paths,
+            });
+        }
+        let paths = paths.unwrap_or_default();
+        let combined_matcher = FilesMatcher::new(&paths);
+        let from_tree = commit.parent_tree(self.repo)?;
+        for format in &self.formats {
+            if let DiffFormat::Git(options) = format {
+                formatter.with_label("diff", |formatter| {
+                    show_combined_git_diff(
+                        formatter,
+                        self.repo.store(),
+                        &parent_trees,
+                        &to_tree,
+                        &paths,
+                        options,
+                        &self.path_labels,
+                        self.conflict_marker_style,
+                    )
+                })?;
+            } else {
+                self.show_diff_with_formats(
+                    ui,
+                    formatter,
+                    slice::from_ref(format),
+                    &from_tree,
+                    &to_tree,
+                    &combined_matcher,
+                    &CopyRecords::default(),
+                    width,
+                )?;
+            }
+        }
+        Ok(())
+    }
 }
 
 pub fn get_copy_records<'a>(
@@ -484,14 +920,42 @@ pub fn get_copy_records<'a>(
     Ok(block_on_stream(stream).filter_ok(|record| matcher.matches(&record.target)))
 }
 
-#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[derive(Clone, Debug, Default)]
 pub struct LineDiffOptions {
     /// How equivalence of lines is tested.
     pub compare_mode: LineCompareMode,
+    /// Changed hunks are ignored if all removed and added lines match this
+    /// pattern.
+    pub ignore_matching_lines: Option<regex::bytes::Regex>,
+    /// How unchanged lines are found.
+    pub diff_options: DiffOptions,
     // TODO: add --ignore-blank-lines, etc. which aren't mutually exclusive.
 }
 
 impl LineDiffOptions {
+    pub fn from_settings(settings: &UserSettings) -> Result<Self, ConfigGetError> {
+        let name = "diff.ignore-matching-lines";
+        let ignore_matching_lines = settings
+            .get_string(name)
+            .optional()?
+            .map(|pattern| {
+                regex::bytes::Regex::new(&pattern).map_err(|err| ConfigGetError::Type {
+                    name: name.to_owned(),
+                    error: err.into(),
+                    source_path: None,
+                })
+            })
+            .transpose()?;
+        Ok(LineDiffOptions {
+            compare_mode: LineCompareMode::default(),
+            ignore_matching_lines,
+            diff_options: DiffOptions {
+                algorithm: settings.get("diff.algorithm")?,
+                anchors: vec![],
+            },
+        })
+    }
+
     fn merge_args(&mut self, args: &DiffFormatArgs) {
         self.compare_mode = if args.ignore_all_space {
             LineCompareMode::IgnoreAllSpace
@@ -500,9 +964,40 @@ impl LineDiffOptions {
         } else {
             LineCompareMode::Exact
         };
+        if let Some(pattern) = &args.ignore_matching_lines {
+            self.ignore_matching_lines = Some(pattern.clone());
+        }
+        self.diff_options.anchors = args
+            .anchored
+            .iter()
+            .map(|text| BString::from(text.as_str()))
+            .collect();
+    }
+
+    /// Returns true if all lines in `text` match the ignore pattern.
+    fn is_ignored_text(&self, text: &[u8]) -> bool {
+        let Some(pattern) = &self.ignore_matching_lines else {
+            return false;
+        };
+        text.split_inclusive(|b| *b == b'\n').all(|line| {
+            let line = line.strip_suffix(b"\n").unwrap_or(line);
+            pattern.is_match(line)
+        })
+    }
+}
+
+// Regex doesn't implement Eq, so compare patterns by source string.
+impl PartialEq for LineDiffOptions {
+    fn eq(&self, other: &Self) -> bool {
+        self.compare_mode == other.compare_mode
+            && self.ignore_matching_lines.as_ref().map(|p| p.as_str())
+                == other.ignore_matching_lines.as_ref().map(|p| p.as_str())
+            && self.diff_options == other.diff_options
     }
 }
 
+impl Eq for LineDiffOptions {}
+
 #[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
 pub enum LineCompareMode {
     /// Compares lines literally.
@@ -522,17 +1017,131 @@ fn diff_by_line<'input, T: AsRef<[u8]> + ?Sized + 'input>(
     // blank lines to the preceding range. Maybe it can also be implemented as a
     // post-process (similar to refine_changed_regions()) that expands unchanged
     // regions across blank lines.
+    let diff_options = &options.diff_options;
     match options.compare_mode {
-        LineCompareMode::Exact => {
-            Diff::for_tokenizer(inputs, find_line_ranges, CompareBytesExactly)
-        }
-        LineCompareMode::IgnoreAllSpace => {
-            Diff::for_tokenizer(inputs, find_line_ranges, CompareBytesIgnoreAllWhitespace)
+        LineCompareMode::Exact => Diff::for_tokenizer_with_options(
+            inputs,
+            find_line_ranges,
+            CompareBytesExactly,
+            diff_options,
+        ),
+        LineCompareMode::IgnoreAllSpace => Diff::for_tokenizer_with_options(
+            inputs,
+            find_line_ranges,
+            CompareBytesIgnoreAllWhitespace,
+            diff_options,
+        ),
+        LineCompareMode::IgnoreSpaceChange => Diff::for_tokenizer_with_options(
+            inputs,
+            find_line_ranges,
+            CompareBytesIgnoreWhitespaceAmount,
+            diff_options,
+        ),
+    }
+}
+
+/// Computes line-level diff hunks of `left` and `right`.
+///
+/// Changed hunks ignored by the `options` are turned into matching hunks, and
+/// merged with the adjacent matching hunks. The sides of a matching hunk may
+/// therefore differ.
+fn diff_hunks_by_line<'input>(
+    left: &'input [u8],
+    right: &'input [u8],
+    options: &LineDiffOptions,
+) -> Vec<DiffHunk<'input>> {
+    let diff = diff_by_line([left, right], options);
+    if options.ignore_matching_lines.is_none() {
+        return diff.hunks().collect();
+    }
+    let mut hunk_ranges: Vec<DiffHunkRange> = Vec::new();
+    for mut hunk_range in diff.hunk_ranges() {
+        let [left_range, right_range] = &hunk_range.ranges[..] else {
+            panic!("hunk should have exactly two inputs");
+        };
+        if hunk_range.kind == DiffHunkKind::Different
+            && options.is_ignored_text(&left[left_range.clone()])
+            && options.is_ignored_text(&right[right_range.clone()])
+        {
+            hunk_range.kind = DiffHunkKind::Matching;
         }
-        LineCompareMode::IgnoreSpaceChange => {
-            Diff::for_tokenizer(inputs, find_line_ranges, CompareBytesIgnoreWhitespaceAmount)
+        match hunk_ranges.last_mut() {
+            Some(last) if last.kind == DiffHunkKind::Matching && hunk_range.kind == last.kind => {
+                last.ranges[0].end = left_range.end;
+                last.ranges[1].end = right_range.end;
+            }
+            _ => hunk_ranges.push(hunk_range),
         }
     }
+    hunk_ranges
+        .into_iter()
+        .map(|DiffHunkRange { kind, ranges }| {
+            let contents = [&left[ranges[0].clone()], &right[ranges[1].clone()]];
+            match kind {
+                DiffHunkKind::Matching => DiffHunk::matching(contents),
+                DiffHunkKind::Different => DiffHunk::different(contents),
+            }
+        })
+        .collect()
+}
+
+/// Drops the entries of `tree_diff` whose content changes are all ignored by
+/// the `options`, so that the files aren't shown at all.
+fn filter_ignored_changes<'a>(
+    store: &'a Store,
+    tree_diff: BoxStream<'a, CopiesTreeDiffEntry>,
+    options: &'a LineDiffOptions,
+) -> BoxStream<'a, CopiesTreeDiffEntry> {
+    if options.ignore_matching_lines.is_none() {
+        return tree_diff;
+    }
+    tree_diff
+        .filter(move |entry| future::ready(!has_only_ignored_changes(store, entry, options)))
+        .boxed()
+}
+
+/// Returns true if `entry` modifies the contents of a text file, and all
+/// changed lines are ignored by the `options`.
+fn has_only_ignored_changes(
+    store: &Store,
+    entry: &CopiesTreeDiffEntry,
+    options: &LineDiffOptions,
+) -> bool {
+    // Copies and renames are shown even if the contents are the same.
+    if entry.path.source.is_some() {
+        return false;
+    }
+    let Ok((left_value, right_value)) = &entry.values else {
+        return false;
+    };
+    let (
+        Some(Some(TreeValue::File {
+            id: left_id,
+            executable: left_executable,
+        })),
+        Some(Some(TreeValue::File {
+            id: right_id,
+            executable: right_executable,
+        })),
+    ) = (left_value.as_resolved(), right_value.as_resolved())
+    else {
+        return false;
+    };
+    if left_executable != right_executable {
+        return false;
+    }
+    // Errors are reported when the entry is shown.
+    let path = &entry.path.target;
+    let read_text = |id| {
+        let mut reader = store.read_file(path, id).ok()?;
+        let content = file_content_for_diff(&mut reader).ok()?;
+        (!content.is_binary).then_some(content.contents)
+    };
+    let (Some(left), Some(right)) = (read_text(left_id), read_text(right_id)) else {
+        return false;
+    };
+    let hunks = diff_hunks_by_line(&left, &right, options);
+    hunks.iter().all(|hunk| hunk.kind == DiffHunkKind::Matching)
 }
 
 #[derive(Clone, Debug, Eq, PartialEq)]
@@ -560,7 +1169,7 @@ impl ColorWordsDiffOptions {
         };
         Ok(ColorWordsDiffOptions {
             context: settings.get("diff.color-words.context")?,
-            line_diff: LineDiffOptions::default(),
+            line_diff: LineDiffOptions::from_settings(settings)?,
             max_inline_alternation,
         })
     }
@@ -579,7 +1188,7 @@ fn show_color_words_diff_hunks(
     right: &[u8],
     options: &ColorWordsDiffOptions,
 ) -> io::Result<()> {
-    let line_diff = diff_by_line([left, right], &options.line_diff);
+    let line_diff_hunks = diff_hunks_by_line(left, right, &options.line_diff);
     let mut line_number = DiffLineNumber { left: 1, right: 1 };
     // Matching entries shouldn't appear consecutively in diff of two inputs.
     // However, if the inputs have conflicts, there may be a hunk that can be
@@ -587,7 +1196,7 @@ fn show_color_words_diff_hunks(
     let mut contexts = Vec::new();
     let mut emitted = false;
 
-    for hunk in line_diff.hunks() {
+    for hunk in line_diff_hunks {
         match hunk.kind {
             DiffHunkKind::Matching => contexts.push(hunk.contents),
             DiffHunkKind::Different => {
@@ -1048,8 +1657,6 @@ pub fn show_color_words_diff(
                         )
                     }
                 };
-                let left_content = diff_content(left_path, left_value, conflict_marker_style)?;
-                let right_content = diff_content(right_path, right_value, conflict_marker_style)?;
                 if left_path == right_path {
                     writeln!(
                         formatter.labeled("header"),
@@ -1061,6 +1668,50 @@ pub fn show_color_words_diff(
                         "{description} {right_ui_path} ({left_ui_path} => {right_ui_path}):"
                     )?;
                 }
+                if is_type_transition(&left_value, &right_value) {
+                    // Contents of different types can't be compared line by
+                    // line, so show each side on its own.
+                    for (path, value, is_left) in [
+                        (left_path, left_value, true),
+                        (right_path, right_value, false),
+                    ] {
+                        show_color_words_transition_side(
+                            formatter,
+                            path,
+                            value,
+                            is_left,
+                            options,
+                            conflict_marker_style,
+                        )?;
+                    }
+                    continue;
+                }
+                if let (
+                    MaterializedTreeValue::Symlink { .. },
+                    MaterializedTreeValue::Symlink { .. },
+                ) = (&left_value, &right_value)
+                {
+                    formatter.with_label("symlink-target", |formatter| {
+                        for (path, value, is_left) in [
+                            (left_path, left_value, true),
+                            (right_path, right_value, false),
+                        ] {
+                            show_color_words_transition_side(
+                                formatter,
+                                path,
+                                value,
+                                is_left,
+                                options,
+                                conflict_marker_style,
+                            )?;
+                        }
+                        Ok::<_, DiffRenderError>(())
+                    })?;
+                    continue;
+                }
+                show_color_words_mode_change(formatter, &left_value, &right_value)?;
+                let left_content = diff_content(left_path, left_value, conflict_marker_style)?;
+                let right_content = diff_content(right_path, right_value, conflict_marker_style)?;
                 if left_content.is_binary || right_content.is_binary {
                     writeln!(formatter.labeled("binary"), "    (binary)")?;
                 } else {
@@ -1092,6 +1743,100 @@ pub fn show_color_words_diff(
     .block_on()
 }
 
+/// Returns true if the path changed between a file, a symlink, and a Git
+/// submodule.
+fn is_type_transition(left: &MaterializedTreeValue, right: &MaterializedTreeValue) -> bool {
+    let is_simple_type = |value: &MaterializedTreeValue| {
+        matches!(
+            value,
+            MaterializedTreeValue::File { .. }
+                | MaterializedTreeValue::Symlink { .. }
+                | MaterializedTreeValue::GitSubmodule(_)
+        )
+    };
+    is_simple_type(left)
+        && is_simple_type(right)
+        && mem::discriminant(left) != mem::discriminant(right)
+}
+
+/// Shows a line describing the change of the executable bit, if any.
+fn show_color_words_mode_change(
+    formatter: &mut dyn Formatter,
+    left: &MaterializedTreeValue,
+    right: &MaterializedTreeValue,
+) -> io::Result<()> {
+    if let (
+        MaterializedTreeValue::File {
+            executable: left_executable,
+            ..
+        },
+        MaterializedTreeValue::File {
+            executable: right_executable,
+            ..
+        },
+    ) = (left, right)
+    {
+        match (left_executable, right_executable) {
+            (false, true) => {
+                writeln!(formatter.labeled("mode-change"), "    (executable bit set)")?;
+            }
+            (true, false) => {
+                writeln!(
+                    formatter.labeled("mode-change"),
+                    "    (executable bit unset)"
+                )?;
+            }
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+/// Shows one side of a type transition. File contents are shown as removed or
+/// added lines, and symlink targets and submodule commits are described.
+fn show_color_words_transition_side(
+    formatter: &mut dyn Formatter,
+    path: &RepoPath,
+    value: MaterializedTreeValue,
+    is_left: bool,
+    options: &ColorWordsDiffOptions,
+    conflict_marker_style: ConflictMarkerStyle,
+) -> Result<(), DiffRenderError> {
+    let (label, tense) = if is_left {
+        ("removed", "was")
+    } else {
+        ("added", "is")
+    };
+    match value {
+        MaterializedTreeValue::Symlink { target, .. } => {
+            writeln!(
+                formatter.labeled(label),
+                "    (symlink target {tense} {target})"
+            )?;
+        }
+        MaterializedTreeValue::GitSubmodule(id) => {
+            writeln!(
+                formatter.labeled(label),
+                "    (submodule commit {tense} {})",
+                id.hex()
+            )?;
+        }
+        value => {
+            let content = diff_content(path, value, conflict_marker_style)?;
+            if content.is_empty() {
+                writeln!(formatter.labeled("empty"), "    (empty)")?;
+            } else if content.is_binary {
+                writeln!(formatter.labeled("binary"), "    (binary)")?;
+            } else if is_left {
+                show_color_words_diff_hunks(formatter, &content.contents, &[], options)?;
+            } else {
+                show_color_words_diff_hunks(formatter, &[], &content.contents, options)?;
+            }
+        }
+    }
+    Ok(())
+}
+
 pub fn show_file_by_file_diff(
     ui: &Ui,
     formatter: &mut dyn Formatter,
@@ -1262,7 +2007,7 @@ impl UnifiedDiffOptions {
     pub fn from_settings(settings: &UserSettings) -> Result<Self, ConfigGetError> {
         Ok(UnifiedDiffOptions {
             context: settings.get("diff.git.context")?,
-            line_diff: LineDiffOptions::default(),
+            line_diff: LineDiffOptions::from_settings(settings)?,
         })
     }
 
@@ -1293,6 +2038,8 @@ struct UnifiedDiffHunk<'content> {
     left_line_range: Range<usize>,
     right_line_range: Range<usize>,
     lines: Vec<(DiffLineType, DiffTokenVec<'content>)>,
+    /// Whether the hunk contains changes not ignored by the options.
+    has_unignored_changes: bool,
 }
 
 impl<'content> UnifiedDiffHunk<'content> {
@@ -1331,7 +2078,10 @@ fn unified_diff_hunks<'content>(
         left_line_range: 0..0,
         right_line_range: 0..0,
         lines: vec![],
+        has_unignored_changes: false,
     };
+    // Ignored changes are still emitted as changes so that the hunks form a
+    // valid patch. Hunks consisting only of ignored changes are omitted.
     let diff = diff_by_line([left_content, right_content], &options.line_diff);
     let mut diff_hunks = diff.hunks().peekable();
     while let Some(hunk) = diff_hunks.next() {
@@ -1362,12 +2112,19 @@ fn unified_diff_hunks<'content>(
                         left_line_range: left_start..left_start,
                         right_line_range: right_start..right_start,
                         lines: vec![],
+                        has_unignored_changes: false,
                     };
                 }
                 // The next hunk should be of DiffHunk::Different type if any.
                 current_hunk.extend_context_lines(before_lines.into_iter().rev());
             }
             DiffHunkKind::Different => {
+                let [left, right] = hunk.contents[..].try_into().unwrap();
+                if !(options.line_diff.is_ignored_text(left)
+                    && options.line_diff.is_ignored_text(right))
+                {
+                    current_hunk.has_unignored_changes = true;
+                }
                 let (left_lines, right_lines) =
                     unzip_diff_hunks_to_lines(Diff::by_word(hunk.contents).hunks());
                 current_hunk.extend_removed_lines(left_lines);
@@ -1378,6 +2135,7 @@ fn unified_diff_hunks<'content>(
     if !current_hunk.lines.is_empty() {
         hunks.push(current_hunk);
     }
+    hunks.retain(|hunk| hunk.has_unignored_changes);
     hunks
 }
 
@@ -1438,12 +2196,8 @@ where
     (left_lines, right_lines)
 }
 
-fn show_unified_diff_hunks(
-    formatter: &mut dyn Formatter,
-    left_content: &[u8],
-    right_content: &[u8],
-    options: &UnifiedDiffOptions,
-) -> io::Result<()> {
+/// Returns the start line number of the hunk range as shown in the hunk header.
+fn unified_hunk_start_line(range: Range<usize>) -> usize {
     // "If the chunk size is 0, the first number is one lower than one would
     // expect." - https://www.artima.com/weblogs/viewpost.jsp?thread=164293
     //
@@ -1451,23 +2205,35 @@ fn show_unified_diff_hunks(
     // shall be the number of the preceding line, or 0 if the range is at the
     // start of the file."
     // - https://pubs.opengroup.org/onlinepubs/9799919799/utilities/diff.html
-    fn to_line_number(range: Range<usize>) -> usize {
-        if range.is_empty() {
-            range.start
-        } else {
-            range.start + 1
-        }
+    if range.is_empty() {
+        range.start
+    } else {
+        range.start + 1
     }
+}
 
+fn write_unified_hunk_header(
+    formatter: &mut dyn Formatter,
+    hunk: &UnifiedDiffHunk,
+) -> io::Result<()> {
+    writeln!(
+        formatter.labeled("hunk_header"),
+        "@@ -{},{} +{},{} @@",
+        unified_hunk_start_line(hunk.left_line_range.clone()),
+        hunk.left_line_range.len(),
+        unified_hunk_start_line(hunk.right_line_range.clone()),
+        hunk.right_line_range.len()
+    )
+}
+
+fn show_unified_diff_hunks(
+    formatter: &mut dyn Formatter,
+    left_content: &[u8],
+    right_content: &[u8],
+    options: &UnifiedDiffOptions,
+) -> io::Result<()> {
     for hunk in unified_diff_hunks(left_content, right_content, options) {
-        writeln!(
-            formatter.labeled("hunk_header"),
-            "@@ -{},{} +{},{} @@",
-            to_line_number(hunk.left_line_range.clone()),
-            hunk.left_line_range.len(),
-            to_line_number(hunk.right_line_range.clone()),
-            hunk.right_line_range.len()
-        )?;
+        write_unified_hunk_header(formatter, &hunk)?;
         for (line_type, tokens) in &hunk.lines {
             let (label, sigil) = match line_type {
                 DiffLineType::Context => ("context", " "),
@@ -1487,6 +2253,82 @@ fn show_unified_diff_hunks(
     Ok(())
 }
 
+/// Prints hunks in the format of `git diff --word-diff=plain`.
+fn show_word_diff_hunks(
+    formatter: &mut dyn Formatter,
+    left_content: &[u8],
+    right_content: &[u8],
+    options: &UnifiedDiffOptions,
+) -> io::Result<()> {
+    for hunk in unified_diff_hunks(left_content, right_content, options) {
+        write_unified_hunk_header(formatter, &hunk)?;
+        let line_groups = hunk
+            .lines
+            .iter()
+            .chunk_by(|(line_type, _)| *line_type == DiffLineType::Context);
+        for (is_context, lines) in &line_groups {
+            if is_context {
+                for (_, tokens) in lines {
+                    formatter.with_label("context", |formatter| {
+                        show_diff_line_tokens(formatter, tokens)
+                    })?;
+                }
+                continue;
+            }
+            let mut contents = [vec![], vec![]];
+            for (line_type, tokens) in lines {
+                let side = if *line_type == DiffLineType::Removed {
+                    0
+                } else {
+                    1
+                };
+                contents[side].extend(tokens.iter().flat_map(|(_, content)| *content));
+            }
+            for hunk in Diff::by_word(&contents).hunks() {
+                match hunk.kind {
+                    DiffHunkKind::Matching => formatter.write_all(hunk.contents[0])?,
+                    DiffHunkKind::Different => {
+                        let [left, right] = hunk.contents[..].try_into().unwrap();
+                        show_word_diff_tokens(formatter, left, "removed", "[-", "-]")?;
+                        show_word_diff_tokens(formatter, right, "added", "{+", "+}")?;
+                    }
+                }
+            }
+        }
+        let (_, last_tokens) = hunk.lines.last().expect("hunk must not be empty");
+        let (_, content) = last_tokens.last().expect("hunk line must not be empty");
+        if !content.ends_with(b"\n") {
+            write!(formatter, "\n\\ No newline at end of file\n")?;
+        }
+    }
+    Ok(())
+}
+
+/// Prints changed words enclosed in markers. Markers don't span lines.
+fn show_word_diff_tokens(
+    formatter: &mut dyn Formatter,
+    content: &[u8],
+    label: &str,
+    start_marker: &str,
+    end_marker: &str,
+) -> io::Result<()> {
+    for line in content.split_inclusive(|b| *b == b'\n') {
+        let (words, newline) = match line.strip_suffix(b"\n") {
+            Some(words) => (words, "\n"),
+            None => (line, ""),
+        };
+        if !words.is_empty() {
+            formatter.with_label(label, |formatter| {
+                write!(formatter, "{start_marker}")?;
+                formatter.with_label("token", |formatter| formatter.write_all(words))?;
+                write!(formatter, "{end_marker}")
+            })?;
+        }
+        write!(formatter, "{newline}")?;
+    }
+    Ok(())
+}
+
 fn show_diff_line_tokens(
     formatter: &mut dyn Formatter,
     tokens: &[(DiffTokenType, &[u8])],
@@ -1507,15 +2349,64 @@ pub fn show_git_diff(
     store: &Store,
     tree_diff: BoxStream<CopiesTreeDiffEntry>,
     options: &UnifiedDiffOptions,
+    path_labels: &DiffPathLabels,
     conflict_marker_style: ConflictMarkerStyle,
 ) -> Result<(), DiffRenderError> {
+    show_git_diff_with_hunks(
+        formatter,
+        store,
+        tree_diff,
+        options,
+        path_labels,
+        conflict_marker_style,
+        show_unified_diff_hunks,
+    )
+}
+
+/// Shows a Git-format diff whose changed words are marked inline as in
+/// `git diff --word-diff=plain`.
+pub fn show_word_diff(
+    formatter: &mut dyn Formatter,
+    store: &Store,
+    tree_diff: BoxStream<CopiesTreeDiffEntry>,
+    options: &UnifiedDiffOptions,
+    path_labels: &DiffPathLabels,
+    conflict_marker_style: ConflictMarkerStyle,
+) -> Result<(), DiffRenderError> {
+    show_git_diff_with_hunks(
+        formatter,
+        store,
+        tree_diff,
+        options,
+        path_labels,
+        conflict_marker_style,
+        show_word_diff_hunks,
+    )
+}
+
+type ShowHunksFn = fn(&mut dyn Formatter, &[u8], &[u8], &UnifiedDiffOptions) -> io::Result<()>;
+
+fn show_git_diff_with_hunks(
+    formatter: &mut dyn Formatter,
+    store: &Store,
+    tree_diff: BoxStream<CopiesTreeDiffEntry>,
+    options: &UnifiedDiffOptions,
+    path_labels: &DiffPathLabels,
+    conflict_marker_style: ConflictMarkerStyle,
+    show_hunks: ShowHunksFn,
+) -> Result<(), DiffRenderError> {
+    let DiffPathLabels {
+        src_prefix,
+        dst_prefix,
+        ..
+    } = path_labels;
     let mut diff_stream = materialized_diff_stream(store, tree_diff);
     async {
         while let Some(MaterializedTreeDiffEntry { path, values }) = diff_stream.next().await {
             let left_path = path.source();
             let right_path = path.target();
-            let left_path_string = left_path.as_internal_file_string();
-            let right_path_string = right_path.as_internal_file_string();
+            let left_path_string = path_labels.git_path_string(left_path);
+            let right_path_string = path_labels.git_path_string(right_path);
             let (left_value, right_value) = values?;
 
             let left_part = git_diff_part(left_path, left_value, conflict_marker_style)?;
@@ -1524,7 +2415,7 @@ pub fn show_git_diff(
             formatter.with_label("file_header", |formatter| {
                 writeln!(
                     formatter,
-                    "diff --git a/{left_path_string} b/{right_path_string}"
+                    "diff --git {src_prefix}{left_path_string} {dst_prefix}{right_path_string}"
                 )?;
                 let left_hash = &left_part.hash;
                 let right_hash = &right_part.hash;
@@ -1543,7 +2434,11 @@ pub fn show_git_diff(
                                 CopyOperation::Copy => "copy",
                                 CopyOperation::Rename => "rename",
                             };
-                            // TODO: include similarity index?
+                            let score = similarity_score(
+                                &left_part.content.contents,
+                                &right_part.content.contents,
+                            );
+                            writeln!(formatter, "similarity index {score}%")?;
                             writeln!(formatter, "{operation} from {left_path_string}")?;
                             writeln!(formatter, "{operation} to {right_path_string}")?;
                         }
@@ -1567,11 +2462,11 @@ pub fn show_git_diff(
             }
 
             let left_path = match left_part.mode {
-                Some(_) => format!("a/{left_path_string}"),
+                Some(_) => format!("{src_prefix}{left_path_string}"),
                 None => "/dev/null".to_owned(),
             };
             let right_path = match right_part.mode {
-                Some(_) => format!("b/{right_path_string}"),
+                Some(_) => format!("{dst_prefix}{right_path_string}"),
                 None => "/dev/null".to_owned(),
             };
             if left_part.content.is_binary || right_part.content.is_binary {
@@ -1586,7 +2481,7 @@ pub fn show_git_diff(
                     writeln!(formatter, "+++ {right_path}")?;
                     io::Result::Ok(())
                 })?;
-                show_unified_diff_hunks(
+                show_hunks(
                     formatter,
                     &left_part.content.contents,
                     &right_part.content.contents,
@@ -1599,6 +2494,317 @@ pub fn show_git_diff(
     .block_on()
 }
 
+/// Prints each changed file as a JSON object, one per line.
+pub fn show_json_diff(
+    formatter: &mut dyn Formatter,
+    store: &Store,
+    tree_diff: BoxStream<CopiesTreeDiffEntry>,
+    options: &UnifiedDiffOptions,
+    conflict_marker_style: ConflictMarkerStyle,
+) -> Result<(), DiffRenderError> {
+    let mut diff_stream = materialized_diff_stream(store, tree_diff);
+    async {
+        while let Some(MaterializedTreeDiffEntry { path, values }) = diff_stream.next().await {
+            let (left_value, right_value) = values?;
+            let left_part = git_diff_part(path.source(), left_value, conflict_marker_style)?;
+            let right_part = git_diff_part(path.target(), right_value, conflict_marker_style)?;
+            let status = match (path.copy_operation(), left_part.mode, right_part.mode) {
+                (Some(CopyOperation::Copy), _, _) => "copied",
+                (Some(CopyOperation::Rename), _, _) => "renamed",
+                (None, None, Some(_)) => "added",
+                (None, Some(_), None) => "removed",
+                (None, _, _) => "modified",
+            };
+            let is_binary = left_part.content.is_binary || right_part.content.is_binary;
+            let hunks = if is_binary {
+                vec![]
+            } else {
+                unified_diff_hunks(
+                    &left_part.content.contents,
+                    &right_part.content.contents,
+                    options,
+                )
+                .iter()
+                .map(unified_diff_hunk_to_json)
+                .collect()
+            };
+            let value = serde_json::json!({
+                "path": path.target().as_internal_file_string(),
+                "source_path": path.source().as_internal_file_string(),
+                "status": status,
+                "source_mode": left_part.mode,
+                "target_mode": right_part.mode,
+                "binary": is_binary,
+                "hunks": hunks,
+            });
+            writeln!(formatter, "{value}")?;
+        }
+        Ok(())
+    }
+    .block_on()
+}
+
+fn unified_diff_hunk_to_json(hunk: &UnifiedDiffHunk) -> serde_json::Value {
+    let lines: Vec<_> = hunk
+        .lines
+        .iter()
+        .map(|(line_type, tokens)| {
+            let line_type = match line_type {
+                DiffLineType::Context => "context",
+                DiffLineType::Removed => "removed",
+                DiffLineType::Added => "added",
+            };
+            let content: Vec<u8> = tokens
+                .iter()
+                .flat_map(|(_, content)| *content)
+                .copied()
+                .collect();
+            serde_json::json!({
+                "type": line_type,
+                "content": String::from_utf8_lossy(&content),
+            })
+        })
+        .collect();
+    serde_json::json!({
+        "source_start": unified_hunk_start_line(hunk.left_line_range.clone()),
+        "source_lines": hunk.left_line_range.len(),
+        "target_start": unified_hunk_start_line(hunk.right_line_range.clone()),
+        "target_lines": hunk.right_line_range.len(),
+        "lines": lines,
+    })
+}
+
+/// Shows the changes of `to_tree` compared to all of the `from_trees` in the
+/// combined diff format of `git diff --cc`.
+///
+/// Each line is prefixed with one column per parent. Only the hunks which
+/// contain changes from all parents are shown.
+#[expect(clippy::too_many_arguments)]
+pub fn show_combined_git_diff(
+    formatter: &mut dyn Formatter,
+    store: &Store,
+    from_trees: &[MergedTree],
+    to_tree: &MergedTree,
+    paths: &BTreeSet<RepoPathBuf>,
+    options: &UnifiedDiffOptions,
+    path_labels: &DiffPathLabels,
+    conflict_marker_style: ConflictMarkerStyle,
+) -> Result<(), DiffRenderError> {
+    let DiffPathLabels {
+        src_prefix,
+        dst_prefix,
+        ..
+    } = path_labels;
+    async {
+        for path in paths {
+            let path_string = path_labels.git_path_string(path);
+            let mut from_parts = Vec::with_capacity(from_trees.len());
+            for from_tree in from_trees {
+                let value =
+                    materialize_tree_value(store, path, from_tree.path_value(path)?).await?;
+                from_parts.push(git_diff_part(path, value, conflict_marker_style)?);
+            }
+            let to_value = materialize_tree_value(store, path, to_tree.path_value(path)?).await?;
+            let to_part = git_diff_part(path, to_value, conflict_marker_style)?;
+
+            formatter.with_label("file_header", |formatter| {
+                writeln!(formatter, "diff --cc {path_string}")?;
+                let from_hashes = from_parts.iter().map(|part| &part.hash).join(",");
+                writeln!(formatter, "index {from_hashes}..{}", to_part.hash)
+            })?;
+            if to_part.content.is_binary || from_parts.iter().any(|part| part.content.is_binary) {
+                writeln!(formatter, "Binary files differ")?;
+                continue;
+            }
+            let to_path = match to_part.mode {
+                Some(_) => format!("{dst_prefix}{path_string}"),
+                None => "/dev/null".to_owned(),
+            };
+            formatter.with_label("file_header", |formatter| {
+                writeln!(formatter, "--- {src_prefix}{path_string}")?;
+                writeln!(formatter, "+++ {to_path}")
+            })?;
+            let from_contents = from_parts
+                .iter()
+                .map(|part| &part.content.contents[..])
+                .collect_vec();
+            show_combined_diff_hunks(
+                formatter,
+                &from_contents,
+                &to_part.content.contents,
+                options,
+            )?;
+        }
+        Ok(())
+    }
+    .block_on()
+}
+
+#[derive(Clone, Debug)]
+enum CombinedDiffLine<'content> {
+    /// Line which was removed from the parents at the given indices.
+    Removed(Vec<usize>, &'content [u8]),
+    /// Line of the new content at the given line index.
+    New(usize, &'content [u8]),
+}
+
+fn show_combined_diff_hunks(
+    formatter: &mut dyn Formatter,
+    from_contents: &[&[u8]],
+    to_content: &[u8],
+    options: &UnifiedDiffOptions,
+) -> io::Result<()> {
+    let to_lines = to_content.split_inclusive(|b| *b == b'\n').collect_vec();
+    // `added[i][j]` tells whether the new line `j` was added compared to the
+    // parent `i`, and `removed[i][j]` are the lines of the parent `i` that were
+    // removed right before the new line `j`.
+    let mut added = vec![vec![false; to_lines.len()]; from_contents.len()];
+    let mut removed: Vec<Vec<Vec<&[u8]>>> =
+        vec![vec![vec![]; to_lines.len() + 1]; from_contents.len()];
+    for (i, from_content) in from_contents.iter().enumerate() {
+        let mut j = 0;
+        for hunk in diff_hunks_by_line(from_content, to_content, &options.line_diff) {
+            let [left, right] = hunk.contents[..].try_into().unwrap();
+            let num_right_lines = right.split_inclusive(|b| *b == b'\n').count();
+            if hunk.kind == DiffHunkKind::Different {
+                removed[i][j].extend(left.split_inclusive(|b| *b == b'\n'));
+                added[i][j..j + num_right_lines].fill(true);
+            }
+            j += num_right_lines;
+        }
+    }
+
+    let mut lines = vec![];
+    for j in 0..=to_lines.len() {
+        // Lines removed from multiple parents are shown once if they appear
+        // in the same order.
+        let mut removed_lines: Vec<(Vec<usize>, &[u8])> = vec![];
+        for (i, parent_removed) in removed.iter().enumerate() {
+            let mut pos = 0;
+            for &line in &parent_removed[j] {
+                match removed_lines[pos..].iter().position(|(_, l)| *l == line) {
+                    Some(offset) => {
+                        removed_lines[pos + offset].0.push(i);
+                        pos += offset + 1;
+                    }
+                    None => {
+                        removed_lines.insert(pos, (vec![i], line));
+                        pos += 1;
+                    }
+                }
+            }
+        }
+        lines.extend(
+            removed_lines
+                .into_iter()
+                .map(|(parents, line)| CombinedDiffLine::Removed(parents, line)),
+        );
+        if let Some(line) = to_lines.get(j) {
+            lines.push(CombinedDiffLine::New(j, line));
+        }
+    }
+    let is_changed_from = |line: &CombinedDiffLine, i: usize| match line {
+        CombinedDiffLine::Removed(parents, _) => parents.contains(&i),
+        CombinedDiffLine::New(j, _) => added[i][*j],
+    };
+    let is_in_parent = |line: &CombinedDiffLine, i: usize| match line {
+        CombinedDiffLine::Removed(parents, _) => parents.contains(&i),
+        CombinedDiffLine::New(j, _) => !added[i][*j],
+    };
+    let is_context = |line: &CombinedDiffLine| match *line {
+        CombinedDiffLine::Removed(..) => false,
+        CombinedDiffLine::New(j, _) => added.iter().all(|added| !added[j]),
+    };
+
+    let mut hunk_ranges: Vec<Range<usize>> = vec![];
+    for (index, _) in lines
+        .iter()
+        .enumerate()
+        .filter(|(_, line)| !is_context(line))
+    {
+        let start = index.saturating_sub(options.context);
+        let end = (index + 1 + options.context).min(lines.len());
+        match hunk_ranges.last_mut() {
+            Some(last) if start <= last.end => last.end = end,
+            _ => hunk_ranges.push(start..end),
+        }
+    }
+
+    // Same as the unified diff, the start line of an empty range is the
+    // number of the preceding line.
+    let to_line_range = |start: usize, len: usize| {
+        if len == 0 {
+            format!("{start},0")
+        } else {
+            format!("{},{len}", start + 1)
+        }
+    };
+    let markers = "@".repeat(from_contents.len() + 1);
+    for range in hunk_ranges {
+        let hunk_lines = &lines[range.clone()];
+        // Hunks that match one of the parents aren't interesting.
+        if !(0..from_contents.len()).all(|i| hunk_lines.iter().any(|line| is_changed_from(line, i)))
+        {
+            continue;
+        }
+        write!(formatter.labeled("hunk_header"), "{markers}")?;
+        for i in 0..from_contents.len() {
+            let start = lines[..range.start]
+                .iter()
+                .filter(|line| is_in_parent(line, i))
+                .count();
+            let len = hunk_lines
+                .iter()
+                .filter(|line| is_in_parent(line, i))
+                .count();
+            write!(
+                formatter.labeled("hunk_header"),
+                " -{}",
+                to_line_range(start, len)
+            )?;
+        }
+        let start = lines[..range.start]
+            .iter()
+            .filter(|line| matches!(line, CombinedDiffLine::New(..)))
+            .count();
+        let len = hunk_lines
+            .iter()
+            .filter(|line| matches!(line, CombinedDiffLine::New(..)))
+            .count();
+        writeln!(
+            formatter.labeled("hunk_header"),
+            " +{} {markers}",
+            to_line_range(start, len)
+        )?;
+        for line in hunk_lines {
+            let (label, sigils, content) = match *line {
+                CombinedDiffLine::Removed(ref parents, content) => {
+                    let sigils: String = (0..from_contents.len())
+                        .map(|i| if parents.contains(&i) { '-' } else { ' ' })
+                        .collect();
+                    ("removed", sigils, content)
+                }
+                CombinedDiffLine::New(j, content) => {
+                    let sigils: String = added
+                        .iter()
+                        .map(|added| if added[j] { '+' } else { ' ' })
+                        .collect();
+                    let label = if is_context(line) { "context" } else { "added" };
+                    (label, sigils, content)
+                }
+            };
+            formatter.with_label(label, |formatter| {
+                write!(formatter, "{sigils}")?;
+                formatter.write_all(content)
+            })?;
+            if !content.ends_with(b"\n") {
+                write!(formatter, "\n\\ No newline at end of file\n")?;
+            }
+        }
+    }
+    Ok(())
+}
+
 #[instrument(skip_all)]
 pub fn show_diff_summary(
     formatter: &mut dyn Formatter,
@@ -1621,6 +2827,65 @@ pub fn show_diff_summary(
     .block_on()
 }
 
+/// Prints the status and path of each entry like `git diff --name-status`.
+///
+/// Copies and renames are followed by a similarity score, which requires
+/// reading the contents of both sides.
+pub fn show_name_status(
+    formatter: &mut dyn Formatter,
+    store: &Store,
+    tree_diff: BoxStream<CopiesTreeDiffEntry>,
+    path_converter: &RepoPathUiConverter,
+    conflict_marker_style: ConflictMarkerStyle,
+) -> Result<(), DiffRenderError> {
+    let mut diff_stream = materialized_diff_stream(store, tree_diff);
+    async {
+        while let Some(MaterializedTreeDiffEntry { path, values }) = diff_stream.next().await {
+            let (left, right) = values?;
+            let target = path_converter.format_file_path(path.target());
+            let Some(op) = path.copy_operation() else {
+                let (label, sigil) = match (left.is_absent(), right.is_absent()) {
+                    (false, false) => ("modified", 'M'),
+                    (true, false) => ("added", 'A'),
+                    (false, true) => ("removed", 'D'),
+                    (true, true) => panic!("values pair must differ"),
+                };
+                writeln!(formatter.labeled(label), "{sigil}\t{target}")?;
+                continue;
+            };
+            let (label, sigil) = match op {
+                CopyOperation::Copy => ("copied", 'C'),
+                CopyOperation::Rename => ("renamed", 'R'),
+            };
+            let source = path_converter.format_file_path(path.source());
+            let left_content = diff_content(path.source(), left, conflict_marker_style)?;
+            let right_content = diff_content(path.target(), right, conflict_marker_style)?;
+            let score = similarity_score(&left_content.contents, &right_content.contents);
+            writeln!(
+                formatter.labeled(label),
+                "{sigil}{score:03}\t{source}\t{target}"
+            )?;
+        }
+        Ok(())
+    }
+    .block_on()
+}
+
+/// Percentage of the contents that is unchanged between the two sides, which
+/// approximates Git's similarity index.
+fn similarity_score(left: &[u8], right: &[u8]) -> usize {
+    let max_len = left.len().max(right.len());
+    if left == right || max_len == 0 {
+        return 100;
+    }
+    let matching_len: usize = diff_by_line([left, right], &LineDiffOptions::default())
+        .hunks()
+        .filter(|hunk| hunk.kind == DiffHunkKind::Matching)
+        .map(|hunk| hunk.contents[0].len())
+        .sum();
+    matching_len * 100 / max_len
+}
+
 pub fn diff_status_label_and_char(
     path: &CopiesTreeDiffEntryPath,
     before: &MergedTreeValue,
@@ -1633,6 +2898,7 @@ pub fn diff_status_label_and_char(
         }
     } else {
         match (before.is_present(), after.is_present()) {
+            (true, true) if is_type_change(before, after) => ("modified", 'T'),
             (true, true) => ("modified", 'M'),
             (false, true) => ("added", 'A'),
             (true, false) => ("removed", 'D'),
@@ -1648,6 +2914,12 @@ pub struct DiffStatOptions {
 }
 
 impl DiffStatOptions {
+    pub fn from_settings(settings: &UserSettings) -> Result<Self, ConfigGetError> {
+        Ok(DiffStatOptions {
+            line_diff: LineDiffOptions::from_settings(settings)?,
+        })
+    }
+
     fn merge_args(&mut self, args: &DiffFormatArgs) {
         self.line_diff.merge_args(args);
     }
@@ -1711,13 +2983,14 @@ fn get_diff_stat_entry(
     // TODO: this matches git's behavior, which is to count the number of newlines
     // in the file. but that behavior seems unhelpful; no one really cares how
     // many `0x0a` characters are in an image.
-    let diff = diff_by_line(
-        [&left_content.contents, &right_content.contents],
+    let diff_hunks = diff_hunks_by_line(
+        &left_content.contents,
+        &right_content.contents,
         &options.line_diff,
     );
     let mut added = 0;
     let mut removed = 0;
-    for hunk in diff.hunks() {
+    for hunk in diff_hunks {
         match hunk.kind {
             DiffHunkKind::Matching => {}
             DiffHunkKind::Different => {
@@ -1826,6 +3099,16 @@ pub fn show_types(
     .block_on()
 }
 
+/// Returns true if the path changed between a file, a symlink, and a Git
+/// submodule. Conflicts aren't considered types of their own here.
+fn is_type_change(before: &MergedTreeValue, after: &MergedTreeValue) -> bool {
+    let before_char = diff_summary_char(before);
+    let after_char = diff_summary_char(after);
+    matches!(before_char, 'F' | 'L' | 'G')
+        && matches!(after_char, 'F' | 'L' | 'G')
+        && before_char != after_char
+}
+
 fn diff_summary_char(value: &MergedTreeValue) -> char {
     match value.as_resolved() {
         Some(None) => '-',
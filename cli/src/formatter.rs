@@ -41,6 +41,10 @@ pub trait Formatter: Write {
     /// already formatted, such as in the graphical log.
     fn raw(&mut self) -> io::Result<Box<dyn Write + '_>>;
 
+    /// Returns true if the output may be colorized, and therefore other escape
+    /// sequences such as hyperlinks can be emitted.
+    fn maybe_color(&self) -> bool;
+
     fn push_label(&mut self, label: &str) -> io::Result<()>;
 
     fn pop_label(&mut self) -> io::Result<()>;
@@ -212,6 +216,10 @@ impl<W: Write> Formatter for PlainTextFormatter<W> {
         Ok(Box::new(self.output.by_ref()))
     }
 
+    fn maybe_color(&self) -> bool {
+        false
+    }
+
     fn push_label(&mut self, _label: &str) -> io::Result<()> {
         Ok(())
     }
@@ -247,6 +255,10 @@ impl<W: Write> Formatter for SanitizingFormatter<W> {
         Ok(Box::new(self.output.by_ref()))
     }
 
+    fn maybe_color(&self) -> bool {
+        false
+    }
+
     fn push_label(&mut self, _label: &str) -> io::Result<()> {
         Ok(())
     }
@@ -560,6 +572,10 @@ impl<W: Write> Formatter for ColorFormatter<W> {
         Ok(Box::new(self.output.by_ref()))
     }
 
+    fn maybe_color(&self) -> bool {
+        true
+    }
+
     fn push_label(&mut self, label: &str) -> io::Result<()> {
         self.labels.push(label.to_owned());
         Ok(())
@@ -594,6 +610,7 @@ impl<W: Write> Drop for ColorFormatter<W> {
 pub struct FormatRecorder {
     data: Vec<u8>,
     ops: Vec<(usize, FormatOp)>,
+    maybe_color: bool,
 }
 
 #[derive(Clone, Debug, Eq, PartialEq)]
@@ -604,8 +621,14 @@ enum FormatOp {
 }
 
 impl FormatRecorder {
-    pub fn new() -> Self {
-        FormatRecorder::default()
+    /// Creates new empty buffer. `maybe_color` should be set if the recorded
+    /// data is going to be replayed to a formatter that may emit colors.
+    pub fn new(maybe_color: bool) -> Self {
+        FormatRecorder {
+            data: vec![],
+            ops: vec![],
+            maybe_color,
+        }
     }
 
     /// Creates new buffer containing the given `data`.
@@ -613,6 +636,7 @@ impl FormatRecorder {
         FormatRecorder {
             data: data.into(),
             ops: vec![],
+            maybe_color: false,
         }
     }
 
@@ -686,6 +710,10 @@ impl Formatter for FormatRecorder {
         Ok(Box::new(RawEscapeSequenceRecorder(self)))
     }
 
+    fn maybe_color(&self) -> bool {
+        self.maybe_color
+    }
+
     fn push_label(&mut self, label: &str) -> io::Result<()> {
         self.push_op(FormatOp::PushLabel(label.to_owned()));
         Ok(())
@@ -1285,7 +1313,7 @@ mod tests {
 
     #[test]
     fn test_format_recorder() {
-        let mut recorder = FormatRecorder::new();
+        let mut recorder = FormatRecorder::new(false);
         write!(recorder, " outer1 ").unwrap();
         recorder.push_label("inner").unwrap();
         write!(recorder, " inner1 ").unwrap();
@@ -1325,7 +1353,7 @@ mod tests {
     #[test]
     fn test_raw_format_recorder() {
         // Note: similar to test_format_recorder above
-        let mut recorder = FormatRecorder::new();
+        let mut recorder = FormatRecorder::new(false);
         write!(recorder.raw().unwrap(), " outer1 ").unwrap();
         recorder.push_label("inner").unwrap();
         write!(recorder.raw().unwrap(), " inner1 ").unwrap();
@@ -25,25 +25,35 @@ use std::path::PathBuf;
 use std::process::Stdio;
 use std::time::Duration;
 use std::time::Instant;
+use std::time::SystemTime;
 
 use crossterm::terminal::Clear;
 use crossterm::terminal::ClearType;
 use indoc::writedoc;
 use itertools::Itertools;
+use jj_lib::backend::CommitId;
+use jj_lib::backend::Timestamp;
+use jj_lib::config::ConfigGetError;
 use jj_lib::fmt_util::binary_prefix;
 use jj_lib::git;
 use jj_lib::git::FailedRefExport;
 use jj_lib::git::FailedRefExportReason;
+use jj_lib::git::GitFetchTimes;
 use jj_lib::git::GitImportStats;
 use jj_lib::git::RefName;
+use jj_lib::git_backend::GitBackend;
 use jj_lib::op_store::RefTarget;
 use jj_lib::op_store::RemoteRef;
 use jj_lib::repo::ReadonlyRepo;
 use jj_lib::repo::Repo;
+use jj_lib::revset::RevsetExpression;
+use jj_lib::settings::UserSettings;
+use jj_lib::str_util::StringPattern;
 use jj_lib::workspace::Workspace;
 use unicode_width::UnicodeWidthStr;
 
 use crate::cleanup_guard::CleanupGuard;
+use crate::cli_util::WorkspaceCommandTransaction;
 use crate::command_error::cli_error;
 use crate::command_error::user_error;
 use crate::command_error::CommandError;
@@ -273,12 +283,32 @@ pub fn with_remote_git_callbacks<T>(ui: &Ui, f: impl FnOnce(git::RemoteCallbacks
     let mut get_user_pw =
         |url: &str| Some((terminal_get_username(ui, url)?, terminal_get_pw(ui, url)?));
     callbacks.get_username_password = Some(&mut get_user_pw);
+    let mut push_chunk = |index: usize, total: usize| {
+        _ = writeln!(ui.status(), "Pushing chunk {index} of {total}");
+    };
+    callbacks.push_chunk = Some(&mut push_chunk);
 
     let result = f(callbacks);
     _ = sideband_progress_writer.flush(ui);
     result
 }
 
+/// Records that the bookmarks matching the `patterns` were just fetched from
+/// the `remotes`.
+pub fn record_git_fetch_times(
+    repo_path: &Path,
+    remotes: &[&str],
+    patterns: &[StringPattern],
+) -> Result<(), CommandError> {
+    let mut fetch_times = GitFetchTimes::load(repo_path)?;
+    let now = Timestamp::now().timestamp;
+    for remote in remotes {
+        fetch_times.record(remote, patterns, now);
+    }
+    fetch_times.save(repo_path)?;
+    Ok(())
+}
+
 pub fn print_git_import_stats(
     ui: &Ui,
     repo: &dyn Repo,
@@ -610,10 +640,12 @@ pub fn print_failed_git_export(
             writeln!(formatter)?;
         }
         drop(formatter);
-        if failed_refs
-            .iter()
-            .any(|failed| matches!(failed.reason, FailedRefExportReason::FailedToSet(_)))
-        {
+        if failed_refs.iter().any(|failed| {
+            matches!(
+                failed.reason,
+                FailedRefExportReason::FailedToSet(_) | FailedRefExportReason::ConflictingName(_)
+            )
+        }) {
             writeln!(
                 ui.hint_default(),
                 r#"Git doesn't allow a branch name that looks like a parent directory of
@@ -625,6 +657,123 @@ export or their "parent" bookmarks."#,
     Ok(())
 }
 
+struct BackupSettings {
+    min_commits: usize,
+    keep: usize,
+}
+
+impl BackupSettings {
+    /// Returns `None` unless backups are enabled.
+    fn from_settings(settings: &UserSettings) -> Result<Option<Self>, ConfigGetError> {
+        if !settings.get_bool("backup.enabled")? {
+            return Ok(None);
+        }
+        Ok(Some(BackupSettings {
+            min_commits: settings.get("backup.min-commits")?,
+            keep: settings.get("backup.keep")?,
+        }))
+    }
+}
+
+/// Backs up the commits which the transaction hides if there are at least
+/// `backup.min-commits` of them and backups are enabled.
+pub fn back_up_hidden_commits(
+    ui: &Ui,
+    tx: &WorkspaceCommandTransaction,
+) -> Result<(), CommandError> {
+    let Some(backup_settings) = BackupSettings::from_settings(tx.settings())? else {
+        return Ok(());
+    };
+    let Ok(git_backend) = git::get_git_backend(tx.repo().store()) else {
+        return Ok(());
+    };
+    let index = tx.repo().index();
+    // The restored view may refer to commits which aren't indexed yet. They
+    // can be ignored since they aren't hidden.
+    let new_heads = tx
+        .repo()
+        .view()
+        .heads()
+        .iter()
+        .filter(|id| index.has_id(id))
+        .cloned()
+        .collect();
+    let hidden_expression =
+        RevsetExpression::commits(tx.base_repo().view().heads().iter().cloned().collect())
+            .ancestors()
+            .minus(&RevsetExpression::commits(new_heads).ancestors());
+    let hidden_count = hidden_expression
+        .clone()
+        .evaluate(tx.repo())?
+        .iter()
+        .process_results(|iter| iter.count())?;
+    if hidden_count < backup_settings.min_commits.max(1) {
+        return Ok(());
+    }
+    let hidden_heads: Vec<_> = hidden_expression
+        .heads()
+        .evaluate(tx.repo())?
+        .iter()
+        .try_collect()?;
+    let what = format!("{hidden_count} hidden commits");
+    write_backup(ui, git_backend, &hidden_heads, &what, &backup_settings)
+}
+
+/// Backs up the commits which garbage collection would delete if backups are
+/// enabled.
+pub fn back_up_gc_candidates(
+    ui: &Ui,
+    settings: &UserSettings,
+    repo: &ReadonlyRepo,
+    keep_newer: SystemTime,
+) -> Result<(), CommandError> {
+    let Some(backup_settings) = BackupSettings::from_settings(settings)? else {
+        return Ok(());
+    };
+    let Ok(git_backend) = git::get_git_backend(repo.store()) else {
+        return Ok(());
+    };
+    let heads = git_backend.gc_candidate_commit_ids(repo.index(), keep_newer)?;
+    if heads.is_empty() {
+        return Ok(());
+    }
+    write_backup(
+        ui,
+        git_backend,
+        &heads,
+        "commits to be garbage-collected",
+        &backup_settings,
+    )
+}
+
+fn write_backup(
+    ui: &Ui,
+    git_backend: &GitBackend,
+    heads: &[CommitId],
+    what: &str,
+    backup_settings: &BackupSettings,
+) -> Result<(), CommandError> {
+    let message = format!("backup of {what} by jj");
+    let backup = git_backend.write_backup(heads, &message)?;
+    writeln!(
+        ui.status(),
+        "Created backup {} of the {what}",
+        backup.number
+    )?;
+    writeln!(
+        ui.hint_default(),
+        "Run `jj op recover --from-backup={}` to recover them.",
+        backup.number
+    )?;
+    // Prune the oldest backups
+    let backups = git_backend.backups()?;
+    let excess_count = backups.len().saturating_sub(backup_settings.keep.max(1));
+    for backup in &backups[..excess_count] {
+        git_backend.remove_backup(backup)?;
+    }
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use std::path::MAIN_SEPARATOR;
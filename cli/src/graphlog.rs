@@ -130,3 +130,117 @@ pub fn get_graphlog<'a, K: Clone + Eq + Hash + 'a>(
         }
     }
 }
+
+/// Machine-readable format of the exported graph.
+#[derive(clap::ValueEnum, Clone, Copy, Debug, Eq, PartialEq)]
+pub enum GraphExportFormat {
+    /// Graphviz DOT digraph with an edge from each commit to its parents
+    Dot,
+    /// JSON object per commit, one per line, listing the edges to its parents
+    Json,
+}
+
+/// Commit in the exported graph.
+#[derive(Clone, Debug)]
+pub struct GraphExportNode {
+    pub commit_id: String,
+    pub change_id: String,
+    pub label: String,
+    pub edges: Vec<GraphEdge<String>>,
+}
+
+/// Writes the graph in a format which can be consumed by other tools.
+pub struct GraphExporter<'writer> {
+    format: GraphExportFormat,
+    writer: &'writer mut dyn Write,
+}
+
+impl<'writer> GraphExporter<'writer> {
+    pub fn new(format: GraphExportFormat, writer: &'writer mut dyn Write) -> io::Result<Self> {
+        match format {
+            GraphExportFormat::Dot => writeln!(writer, "digraph {{")?,
+            GraphExportFormat::Json => {}
+        }
+        Ok(GraphExporter { format, writer })
+    }
+
+    pub fn add_node(&mut self, node: &GraphExportNode) -> io::Result<()> {
+        match self.format {
+            GraphExportFormat::Dot => self.write_dot_node(node),
+            GraphExportFormat::Json => {
+                let edges: Vec<_> = node
+                    .edges
+                    .iter()
+                    .map(|edge| {
+                        serde_json::json!({
+                            "target": edge.target,
+                            "type": edge_type_name(edge.edge_type),
+                        })
+                    })
+                    .collect();
+                let value = serde_json::json!({
+                    "commit_id": node.commit_id,
+                    "change_id": node.change_id,
+                    "label": node.label,
+                    "edges": edges,
+                });
+                writeln!(self.writer, "{value}")
+            }
+        }
+    }
+
+    fn write_dot_node(&mut self, node: &GraphExportNode) -> io::Result<()> {
+        let id = dot_quote(&node.commit_id);
+        // Terminate the last line so all lines are left-justified.
+        let label = dot_quote(&format!("{}\n", node.label));
+        writeln!(self.writer, "  {id} [label={label}];")?;
+        for edge in &node.edges {
+            let target = dot_quote(&edge.target);
+            match edge.edge_type {
+                GraphEdgeType::Direct => writeln!(self.writer, "  {id} -> {target};")?,
+                GraphEdgeType::Indirect => {
+                    writeln!(self.writer, "  {id} -> {target} [style=dashed];")?;
+                }
+                GraphEdgeType::Missing => {
+                    // The target isn't part of the graph, so it's rendered as
+                    // a placeholder.
+                    writeln!(self.writer, "  {target} [label=\"~\", shape=plaintext];")?;
+                    writeln!(self.writer, "  {id} -> {target} [style=dotted];")?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    pub fn finish(self) -> io::Result<()> {
+        match self.format {
+            GraphExportFormat::Dot => writeln!(self.writer, "}}"),
+            GraphExportFormat::Json => Ok(()),
+        }
+    }
+}
+
+fn edge_type_name(edge_type: GraphEdgeType) -> &'static str {
+    match edge_type {
+        GraphEdgeType::Direct => "direct",
+        GraphEdgeType::Indirect => "elided",
+        GraphEdgeType::Missing => "missing",
+    }
+}
+
+/// Quotes the string as a DOT ID.
+fn dot_quote(s: &str) -> String {
+    let mut quoted = String::with_capacity(s.len() + 2);
+    quoted.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            // Left-justified line break
+            '\n' => quoted.push_str("\\l"),
+            _ => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
@@ -31,18 +31,42 @@ pub mod git_util;
 /// A stub module that provides a no-op implementation of some of the functions
 /// in the `git` module.
 pub mod git_util {
+    use std::time::SystemTime;
+
     use jj_lib::repo::ReadonlyRepo;
+    use jj_lib::settings::UserSettings;
     use jj_lib::workspace::Workspace;
 
+    use crate::cli_util::WorkspaceCommandTransaction;
+    use crate::command_error::CommandError;
+    use crate::ui::Ui;
+
     pub fn is_colocated_git_workspace(_workspace: &Workspace, _repo: &ReadonlyRepo) -> bool {
         false
     }
+
+    pub fn back_up_hidden_commits(
+        _ui: &Ui,
+        _tx: &WorkspaceCommandTransaction,
+    ) -> Result<(), CommandError> {
+        Ok(())
+    }
+
+    pub fn back_up_gc_candidates(
+        _ui: &Ui,
+        _settings: &UserSettings,
+        _repo: &ReadonlyRepo,
+        _keep_newer: SystemTime,
+    ) -> Result<(), CommandError> {
+        Ok(())
+    }
 }
 pub mod graphlog;
 pub mod merge_tools;
 pub mod movement_util;
 pub mod operation_templater;
 mod progress;
+pub mod ref_list_util;
 pub mod revset_util;
 pub mod template_builder;
 pub mod template_parser;
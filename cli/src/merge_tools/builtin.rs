@@ -1,4 +1,6 @@
 use std::borrow::Cow;
+use std::io;
+use std::mem;
 use std::path::Path;
 use std::sync::Arc;
 
@@ -20,6 +22,7 @@ use jj_lib::files;
 use jj_lib::files::MergeResult;
 use jj_lib::matchers::Matcher;
 use jj_lib::merge::Merge;
+use jj_lib::merge::MergedTreeValue;
 use jj_lib::merged_tree::MergedTree;
 use jj_lib::merged_tree::MergedTreeBuilder;
 use jj_lib::merged_tree::TreeDiffEntry;
@@ -30,6 +33,7 @@ use jj_lib::store::Store;
 use pollster::FutureExt;
 use thiserror::Error;
 
+use super::builtin_text::select_hunks;
 use super::MergeToolFile;
 
 #[derive(Debug, Error)]
@@ -51,6 +55,8 @@ pub enum BuiltinToolError {
     Unimplemented { item: &'static str, id: String },
     #[error("Backend error")]
     BackendError(#[from] jj_lib::backend::BackendError),
+    #[error("Failed to prompt for changes")]
+    Prompt(#[source] std::io::Error),
 }
 
 #[derive(Clone, Debug)]
@@ -242,6 +248,31 @@ fn make_section_changed_lines(
         .collect()
 }
 
+/// Splits the contents of an added file into hunks which can be selected
+/// individually. Each hunk ends with the blank lines following a paragraph.
+fn make_added_file_sections(contents: &str) -> Vec<scm_record::Section<'static>> {
+    let mut sections = Vec::new();
+    let mut lines = Vec::new();
+    let mut ends_with_blank_line = false;
+    for line in contents.split_inclusive('\n') {
+        let is_blank = line.trim().is_empty();
+        if ends_with_blank_line && !is_blank {
+            sections.push(mem::take(&mut lines));
+        }
+        lines.push(line);
+        ends_with_blank_line = is_blank;
+    }
+    if !lines.is_empty() {
+        sections.push(lines);
+    }
+    sections
+        .into_iter()
+        .map(|lines| scm_record::Section::Changed {
+            lines: make_section_changed_lines(&lines.concat(), scm_record::ChangeType::Added),
+        })
+        .collect()
+}
+
 fn make_diff_sections(
     left_contents: &str,
     right_contents: &str,
@@ -302,6 +333,33 @@ fn should_render_mode_section(left: &FileInfo, right: &FileInfo) -> bool {
     }
 }
 
+/// Returns a section to select the executable bit of an added file separately
+/// from its contents.
+fn make_added_file_mode_section(
+    left: &FileInfo,
+    right: &FileInfo,
+) -> Option<scm_record::Section<'static>> {
+    let is_added_file = left.is_empty().is_none() && right.is_empty() == Some(false);
+    (is_added_file && right.file_mode == scm_record::FileMode(mode::EXECUTABLE)).then_some(
+        scm_record::Section::FileMode {
+            is_checked: false,
+            before: scm_record::FileMode(mode::NORMAL),
+            after: right.file_mode,
+        },
+    )
+}
+
+/// Sets the executable bit if the value is a resolved file.
+fn with_executable(value: MergedTreeValue, executable: bool) -> MergedTreeValue {
+    match value.into_resolved() {
+        Ok(Some(TreeValue::File { id, executable: _ })) => {
+            Merge::normal(TreeValue::File { id, executable })
+        }
+        Ok(value) => Merge::resolved(value),
+        Err(value) => value,
+    }
+}
+
 pub fn make_diff_files(
     store: &Arc<Store>,
     left_tree: &MergedTree,
@@ -323,6 +381,7 @@ pub fn make_diff_files(
                 after: right_info.file_mode,
             });
         }
+        sections.extend(make_added_file_mode_section(&left_info, &right_info));
 
         match (left_info.contents, right_info.contents) {
             (FileContents::Absent, FileContents::Absent) => {}
@@ -353,9 +412,7 @@ pub fn make_diff_files(
                     hash: _,
                     num_bytes: _,
                 },
-            ) => sections.push(scm_record::Section::Changed {
-                lines: make_section_changed_lines(&contents, scm_record::ChangeType::Added),
-            }),
+            ) => sections.extend(make_added_file_sections(&contents)),
 
             (FileContents::Absent, FileContents::Binary { hash, num_bytes }) => {
                 sections.push(scm_record::Section::Binary {
@@ -477,7 +534,19 @@ pub fn apply_diff_builtin(
                     old_mode.is_some() && old_mode != Some(scm_record::FileMode::absent());
                 let file_exists_now =
                     new_mode.is_some() && new_mode != Some(scm_record::FileMode::absent());
-                let new_empty_file = !file_existed_previously && file_exists_now;
+                // The mode of an added file may also be set by selecting its
+                // executable bit, which doesn't create the file by itself.
+                let file_created = file.sections.iter().any(|section| {
+                    matches!(
+                        section,
+                        scm_record::Section::FileMode {
+                            is_checked: true,
+                            before,
+                            after: _,
+                        } if *before == scm_record::FileMode::absent()
+                    )
+                });
+                let new_empty_file = !file_existed_previously && file_created;
                 let file_deleted = file_existed_previously && !file_exists_now;
 
                 if new_empty_file {
@@ -489,14 +558,23 @@ pub fn apply_diff_builtin(
                 // Else: the file is empty and nothing changed.
             }
             scm_record::SelectedContents::Unchanged => {
-                // Do nothing.
+                // The contents are unchanged, but the mode change may have been
+                // selected.
+                let new_mode = file.get_file_mode();
+                if new_mode != file.file_mode {
+                    let value = left_tree.path_value(&path)?;
+                    let executable = new_mode == Some(scm_record::FileMode(mode::EXECUTABLE));
+                    tree_builder.set_or_remove(path, with_executable(value, executable));
+                }
             }
             scm_record::SelectedContents::Binary {
                 old_description: _,
                 new_description: _,
             } => {
                 let value = right_tree.path_value(&path)?;
-                tree_builder.set_or_remove(path, value);
+                let executable =
+                    file.get_file_mode() == Some(scm_record::FileMode(mode::EXECUTABLE));
+                tree_builder.set_or_remove(path, with_executable(value, executable));
             }
             scm_record::SelectedContents::Present { contents } => {
                 let file_id = store
@@ -523,6 +601,57 @@ pub fn edit_diff_builtin(
     right_tree: &MergedTree,
     matcher: &dyn Matcher,
     conflict_marker_style: ConflictMarkerStyle,
+) -> Result<MergedTreeId, BuiltinToolError> {
+    edit_diff_with(
+        left_tree,
+        right_tree,
+        matcher,
+        conflict_marker_style,
+        |files| {
+            let mut input = scm_record::helpers::CrosstermInput;
+            let recorder = scm_record::Recorder::new(
+                scm_record::RecordState {
+                    is_read_only: false,
+                    files,
+                    commits: Default::default(),
+                },
+                &mut input,
+            );
+            let result = recorder.run().map_err(BuiltinToolError::Record)?;
+            Ok(result.files)
+        },
+    )
+}
+
+/// Like [`edit_diff_builtin()`], but prompts for each hunk on stdin instead
+/// of starting the TUI.
+pub fn edit_diff_builtin_text(
+    left_tree: &MergedTree,
+    right_tree: &MergedTree,
+    matcher: &dyn Matcher,
+    conflict_marker_style: ConflictMarkerStyle,
+) -> Result<MergedTreeId, BuiltinToolError> {
+    edit_diff_with(
+        left_tree,
+        right_tree,
+        matcher,
+        conflict_marker_style,
+        |mut files| {
+            select_hunks(&mut files, &mut io::stdin().lock(), &mut io::stderr())
+                .map_err(BuiltinToolError::Prompt)?;
+            Ok(files)
+        },
+    )
+}
+
+fn edit_diff_with(
+    left_tree: &MergedTree,
+    right_tree: &MergedTree,
+    matcher: &dyn Matcher,
+    conflict_marker_style: ConflictMarkerStyle,
+    select: impl FnOnce(
+        Vec<scm_record::File<'static>>,
+    ) -> Result<Vec<scm_record::File<'static>>, BuiltinToolError>,
 ) -> Result<MergedTreeId, BuiltinToolError> {
     let store = left_tree.store().clone();
     // TODO: handle copy tracking
@@ -538,17 +667,8 @@ pub fn edit_diff_builtin(
         &changed_files,
         conflict_marker_style,
     )?;
-    let mut input = scm_record::helpers::CrosstermInput;
-    let recorder = scm_record::Recorder::new(
-        scm_record::RecordState {
-            is_read_only: false,
-            files,
-            commits: Default::default(),
-        },
-        &mut input,
-    );
-    let result = recorder.run().map_err(BuiltinToolError::Record)?;
-    let tree_id = apply_diff_builtin(&store, left_tree, right_tree, changed_files, &result.files)
+    let files = select(files)?;
+    let tree_id = apply_diff_builtin(&store, left_tree, right_tree, changed_files, &files)
         .map_err(BuiltinToolError::BackendError)?;
     Ok(tree_id)
 }
@@ -681,8 +801,8 @@ pub fn edit_merge_builtin(
 
 #[cfg(test)]
 mod tests {
+    use assert_matches::assert_matches;
     use jj_lib::conflicts::extract_as_single_hunk;
-    use jj_lib::merge::MergedTreeValue;
     use jj_lib::repo::Repo;
     use testutils::TestRepo;
 
@@ -1061,6 +1181,172 @@ mod tests {
         );
     }
 
+    fn create_executable_file_tree(
+        test_repo: &TestRepo,
+        path: &RepoPath,
+        contents: &str,
+    ) -> MergedTree {
+        let store = test_repo.repo.store();
+        let mut tree_builder = store.tree_builder(store.empty_tree_id().clone());
+        testutils::write_executable_file(&mut tree_builder, path, contents);
+        let id = tree_builder.write_tree().unwrap();
+        MergedTree::resolved(store.get_tree(RepoPathBuf::root(), &id).unwrap())
+    }
+
+    #[test]
+    fn test_edit_diff_builtin_add_executable_file() {
+        let test_repo = TestRepo::init();
+        let store = test_repo.repo.store();
+
+        let added_path = RepoPath::from_internal_string("script");
+        let left_tree = testutils::create_tree(&test_repo.repo, &[]);
+        let right_tree =
+            create_executable_file_tree(&test_repo, added_path, "first\n\nsecond\nthird\n");
+
+        let changed_files = vec![added_path.to_owned()];
+        let files = make_diff_files(
+            store,
+            &left_tree,
+            &right_tree,
+            &changed_files,
+            ConflictMarkerStyle::Diff,
+        )
+        .unwrap();
+        insta::assert_debug_snapshot!(files, @r#"
+        [
+            File {
+                old_path: None,
+                path: "script",
+                file_mode: Some(
+                    FileMode(
+                        0,
+                    ),
+                ),
+                sections: [
+                    FileMode {
+                        is_checked: false,
+                        before: FileMode(
+                            33188,
+                        ),
+                        after: FileMode(
+                            33261,
+                        ),
+                    },
+                    Changed {
+                        lines: [
+                            SectionChangedLine {
+                                is_checked: false,
+                                change_type: Added,
+                                line: "first\n",
+                            },
+                            SectionChangedLine {
+                                is_checked: false,
+                                change_type: Added,
+                                line: "\n",
+                            },
+                        ],
+                    },
+                    Changed {
+                        lines: [
+                            SectionChangedLine {
+                                is_checked: false,
+                                change_type: Added,
+                                line: "second\n",
+                            },
+                            SectionChangedLine {
+                                is_checked: false,
+                                change_type: Added,
+                                line: "third\n",
+                            },
+                        ],
+                    },
+                ],
+            },
+        ]
+        "#);
+
+        // Select the second hunk without the executable bit
+        let mut files = files;
+        files[0].sections[2].set_checked(true);
+        let partial_tree_id = apply_diff_builtin(
+            store,
+            &left_tree,
+            &right_tree,
+            changed_files.clone(),
+            &files,
+        )
+        .unwrap();
+        let expected_tree =
+            testutils::create_tree(&test_repo.repo, &[(added_path, "second\nthird\n")]);
+        assert_eq!(partial_tree_id, expected_tree.id());
+
+        // The executable bit alone doesn't create the file
+        files[0].sections[2].set_checked(false);
+        files[0].sections[0].set_checked(true);
+        let mode_only_tree_id = apply_diff_builtin(
+            store,
+            &left_tree,
+            &right_tree,
+            changed_files.clone(),
+            &files,
+        )
+        .unwrap();
+        assert_eq!(mode_only_tree_id, left_tree.id());
+
+        for section in &mut files[0].sections {
+            section.set_checked(true);
+        }
+        let all_changes_tree_id =
+            apply_diff_builtin(store, &left_tree, &right_tree, changed_files, &files).unwrap();
+        assert_eq!(all_changes_tree_id, right_tree.id());
+    }
+
+    #[test]
+    fn test_edit_diff_builtin_change_binary_file_mode() {
+        let test_repo = TestRepo::init();
+        let store = test_repo.repo.store();
+
+        let binary_path = RepoPath::from_internal_string("binary");
+        let left_tree = testutils::create_tree(&test_repo.repo, &[(binary_path, "\0old")]);
+        let right_tree = create_executable_file_tree(&test_repo, binary_path, "\0new");
+        let mode_only_tree = create_executable_file_tree(&test_repo, binary_path, "\0old");
+        let contents_only_tree = testutils::create_tree(&test_repo.repo, &[(binary_path, "\0new")]);
+
+        let changed_files = vec![binary_path.to_owned()];
+        let mut files = make_diff_files(
+            store,
+            &left_tree,
+            &right_tree,
+            &changed_files,
+            ConflictMarkerStyle::Diff,
+        )
+        .unwrap();
+        assert_matches!(
+            files[0].sections[..],
+            [
+                scm_record::Section::FileMode { .. },
+                scm_record::Section::Binary { .. }
+            ]
+        );
+
+        files[0].sections[0].set_checked(true);
+        let tree_id = apply_diff_builtin(
+            store,
+            &left_tree,
+            &right_tree,
+            changed_files.clone(),
+            &files,
+        )
+        .unwrap();
+        assert_eq!(tree_id, mode_only_tree.id());
+
+        files[0].sections[0].set_checked(false);
+        files[0].sections[1].set_checked(true);
+        let tree_id =
+            apply_diff_builtin(store, &left_tree, &right_tree, changed_files, &files).unwrap();
+        assert_eq!(tree_id, contents_only_tree.id());
+    }
+
     #[test]
     fn test_make_merge_sections() {
         let test_repo = TestRepo::init();
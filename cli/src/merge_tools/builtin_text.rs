@@ -0,0 +1,305 @@
+//! Line-based hunk selector of the `:builtin-text` diff editor.
+
+use std::io;
+use std::io::BufRead;
+use std::io::Write;
+
+use itertools::Itertools as _;
+use scm_record::ChangeType;
+use scm_record::FileMode;
+use scm_record::Section;
+
+/// Number of unchanged lines printed before and after each hunk.
+const NUM_CONTEXT_LINES: usize = 3;
+
+const HELP: &str = "\
+y - select this hunk
+n - do not select this hunk
+a - select this hunk and all later hunks in the file
+d - do not select this hunk or any later hunks in the file
+q - quit; do not select this hunk or any remaining ones
+? - print help
+";
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Answer {
+    Yes,
+    No,
+    AllInFile,
+    NoneInFile,
+    Quit,
+}
+
+/// Asks the user which hunks of the `files` to select, one hunk at a time.
+///
+/// Unlike the TUI of the `:builtin` diff editor, this only reads lines from
+/// the `input`, so it works on dumb terminals and with scripted input. The end
+/// of the input is treated like quitting.
+pub fn select_hunks(
+    files: &mut [scm_record::File],
+    input: &mut impl BufRead,
+    output: &mut impl Write,
+) -> io::Result<()> {
+    let mut quit = false;
+    for file in files {
+        let hunk_indices = file
+            .sections
+            .iter()
+            .positions(|section| section.is_editable())
+            .collect_vec();
+        let mut rest_of_file = None;
+        for (hunk_number, &index) in (1..).zip(&hunk_indices) {
+            let is_selected = if quit {
+                false
+            } else if let Some(is_selected) = rest_of_file {
+                is_selected
+            } else {
+                writeln!(
+                    output,
+                    "@@ {} ({hunk_number}/{}) @@",
+                    file.path.display(),
+                    hunk_indices.len()
+                )?;
+                write_hunk(output, &file.sections, index)?;
+                match prompt_answer(input, output)? {
+                    Answer::Yes => true,
+                    Answer::No => false,
+                    Answer::AllInFile => {
+                        rest_of_file = Some(true);
+                        true
+                    }
+                    Answer::NoneInFile => {
+                        rest_of_file = Some(false);
+                        false
+                    }
+                    Answer::Quit => {
+                        quit = true;
+                        false
+                    }
+                }
+            };
+            file.sections[index].set_checked(is_selected);
+        }
+    }
+    Ok(())
+}
+
+fn prompt_answer(input: &mut impl BufRead, output: &mut impl Write) -> io::Result<Answer> {
+    loop {
+        write!(output, "Select this hunk? [y,n,a,d,q,?] ")?;
+        output.flush()?;
+        let mut line = String::new();
+        if input.read_line(&mut line)? == 0 {
+            writeln!(output)?;
+            return Ok(Answer::Quit);
+        }
+        match line.trim() {
+            "y" => return Ok(Answer::Yes),
+            "n" => return Ok(Answer::No),
+            "a" => return Ok(Answer::AllInFile),
+            "d" => return Ok(Answer::NoneInFile),
+            "q" => return Ok(Answer::Quit),
+            "?" => write!(output, "{HELP}")?,
+            _ => writeln!(output, "Unrecognized response; type ? for help")?,
+        }
+    }
+}
+
+fn write_hunk(output: &mut impl Write, sections: &[Section], index: usize) -> io::Result<()> {
+    match &sections[index] {
+        Section::Unchanged { .. } => {}
+        Section::Changed { lines } => {
+            if let Some(Section::Unchanged { lines }) = index.checked_sub(1).map(|i| &sections[i]) {
+                let start = lines.len().saturating_sub(NUM_CONTEXT_LINES);
+                for line in &lines[start..] {
+                    write_line(output, ' ', line)?;
+                }
+            }
+            for line in lines {
+                let prefix = match line.change_type {
+                    ChangeType::Added => '+',
+                    ChangeType::Removed => '-',
+                };
+                write_line(output, prefix, &line.line)?;
+            }
+            if let Some(Section::Unchanged { lines }) = sections.get(index + 1) {
+                for line in lines.iter().take(NUM_CONTEXT_LINES) {
+                    write_line(output, ' ', line)?;
+                }
+            }
+        }
+        Section::FileMode { before, after, .. } => {
+            if *before == FileMode::absent() {
+                writeln!(output, "Added file with mode {after}")?;
+            } else if *after == FileMode::absent() {
+                writeln!(output, "Deleted file with mode {before}")?;
+            } else {
+                writeln!(output, "Changed file mode from {before} to {after}")?;
+            }
+        }
+        Section::Binary {
+            old_description,
+            new_description,
+            ..
+        } => {
+            let old = old_description.as_deref().unwrap_or("absent");
+            let new = new_description.as_deref().unwrap_or("absent");
+            writeln!(output, "Binary file changed: {old} -> {new}")?;
+        }
+    }
+    Ok(())
+}
+
+fn write_line(output: &mut impl Write, prefix: char, line: &str) -> io::Result<()> {
+    write!(output, "{prefix}{line}")?;
+    if !line.ends_with('\n') {
+        writeln!(output)?;
+        writeln!(output, "\\ No newline at end of file")?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::borrow::Cow;
+    use std::path::Path;
+
+    use scm_record::SectionChangedLine;
+
+    use super::*;
+
+    fn changed_line(change_type: ChangeType, line: &'static str) -> SectionChangedLine<'static> {
+        SectionChangedLine {
+            is_checked: false,
+            change_type,
+            line: Cow::Borrowed(line),
+        }
+    }
+
+    fn make_files() -> Vec<scm_record::File<'static>> {
+        let file1 = scm_record::File {
+            old_path: None,
+            path: Cow::Borrowed(Path::new("file1")),
+            file_mode: Some(FileMode(0o100644)),
+            sections: vec![
+                Section::Changed {
+                    lines: vec![
+                        changed_line(ChangeType::Removed, "a\n"),
+                        changed_line(ChangeType::Added, "A\n"),
+                    ],
+                },
+                Section::Unchanged {
+                    lines: ["b\n", "c\n", "d\n", "e\n"].map(Cow::Borrowed).to_vec(),
+                },
+                Section::Changed {
+                    lines: vec![changed_line(ChangeType::Added, "f")],
+                },
+            ],
+        };
+        let file2 = scm_record::File {
+            old_path: None,
+            path: Cow::Borrowed(Path::new("file2")),
+            file_mode: Some(FileMode::absent()),
+            sections: vec![
+                Section::FileMode {
+                    is_checked: false,
+                    before: FileMode::absent(),
+                    after: FileMode(0o100755),
+                },
+                Section::Binary {
+                    is_checked: false,
+                    old_description: None,
+                    new_description: Some(Cow::Borrowed("1234567890 (10 bytes)")),
+                },
+            ],
+        };
+        vec![file1, file2]
+    }
+
+    fn select(input: &str) -> (String, Vec<Vec<bool>>) {
+        let mut files = make_files();
+        let mut output = vec![];
+        select_hunks(&mut files, &mut input.as_bytes(), &mut output).unwrap();
+        let selection = files
+            .iter()
+            .map(|file| {
+                file.sections
+                    .iter()
+                    .filter(|section| section.is_editable())
+                    .map(|section| match section {
+                        Section::Unchanged { .. } => unreachable!(),
+                        Section::Changed { lines } => lines.iter().all(|line| line.is_checked),
+                        Section::FileMode { is_checked, .. }
+                        | Section::Binary { is_checked, .. } => *is_checked,
+                    })
+                    .collect()
+            })
+            .collect();
+        (String::from_utf8(output).unwrap(), selection)
+    }
+
+    #[test]
+    fn test_select_hunks() {
+        let (output, selection) = select("n\ny\ny\nn\n");
+        insta::assert_snapshot!(output, @r"
+        @@ file1 (1/2) @@
+        -a
+        +A
+         b
+         c
+         d
+        Select this hunk? [y,n,a,d,q,?] @@ file1 (2/2) @@
+         c
+         d
+         e
+        +f
+        \ No newline at end of file
+        Select this hunk? [y,n,a,d,q,?] @@ file2 (1/2) @@
+        Added file with mode 100755
+        Select this hunk? [y,n,a,d,q,?] @@ file2 (2/2) @@
+        Binary file changed: absent -> 1234567890 (10 bytes)
+        Select this hunk? [y,n,a,d,q,?]
+        ");
+        assert_eq!(selection, [[false, true], [true, false]]);
+    }
+
+    #[test]
+    fn test_select_hunks_rest_of_file() {
+        let (_, selection) = select("a\nd\n");
+        assert_eq!(selection, [[true, true], [false, false]]);
+        let (_, selection) = select("d\ny\nn\n");
+        assert_eq!(selection, [[false, false], [true, false]]);
+    }
+
+    #[test]
+    fn test_select_hunks_quit() {
+        let (_, selection) = select("y\nq\n");
+        assert_eq!(selection, [[true, false], [false, false]]);
+
+        // The end of input also quits
+        let (output, selection) = select("x\n?\ny\n");
+        insta::assert_snapshot!(output, @r"
+        @@ file1 (1/2) @@
+        -a
+        +A
+         b
+         c
+         d
+        Select this hunk? [y,n,a,d,q,?] Unrecognized response; type ? for help
+        Select this hunk? [y,n,a,d,q,?] y - select this hunk
+        n - do not select this hunk
+        a - select this hunk and all later hunks in the file
+        d - do not select this hunk or any later hunks in the file
+        q - quit; do not select this hunk or any remaining ones
+        ? - print help
+        Select this hunk? [y,n,a,d,q,?] @@ file1 (2/2) @@
+         c
+         d
+         e
+        +f
+        \ No newline at end of file
+        Select this hunk? [y,n,a,d,q,?]
+        ");
+        assert_eq!(selection, [[true, false], [false, false]]);
+    }
+}
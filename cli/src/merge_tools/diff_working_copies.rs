@@ -21,6 +21,7 @@ use jj_lib::repo_path::RepoPathBuf;
 use jj_lib::store::Store;
 use jj_lib::working_copy::CheckoutError;
 use jj_lib::working_copy::CheckoutOptions;
+use jj_lib::working_copy::NestedRepositoryPolicy;
 use jj_lib::working_copy::SnapshotOptions;
 use pollster::FutureExt;
 use tempfile::TempDir;
@@ -299,6 +300,7 @@ diff editing in mind and be a little inaccurate.
             start_tracking_matcher: &EverythingMatcher,
             max_new_file_size: u64::MAX,
             conflict_marker_style,
+            nested_repository_policy: NestedRepositoryPolicy::Ignore,
         })?;
         Ok(output_tree_state.current_tree_id().clone())
     }
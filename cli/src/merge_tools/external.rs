@@ -15,6 +15,8 @@ use jj_lib::conflicts::choose_materialized_conflict_marker_len;
 use jj_lib::conflicts::materialize_merge_result_to_bytes_with_marker_len;
 use jj_lib::conflicts::ConflictMarkerStyle;
 use jj_lib::conflicts::MIN_CONFLICT_MARKER_LEN;
+use jj_lib::files;
+use jj_lib::files::MergeResult;
 use jj_lib::gitignore::GitIgnoreFile;
 use jj_lib::matchers::Matcher;
 use jj_lib::merge::Merge;
@@ -79,6 +81,11 @@ pub struct ExternalMergeTool {
     /// file are parsed and taken to mean that the conflict was only partially
     /// resolved.
     pub merge_tool_edits_conflict_markers: bool,
+    /// If true, regions of the file which can be merged automatically are
+    /// resolved before the merge tool is started. The `$base`, `$left`, and
+    /// `$right` files then only differ in the conflicted regions, so the tool
+    /// is presented with a smaller conflict.
+    pub premerge: bool,
     /// If provided, overrides the normal conflict marker style setting. This is
     /// useful if a tool parses conflict markers, and so it requires a specific
     /// format, or if a certain format is more readable than another.
@@ -109,6 +116,7 @@ impl Default for ExternalMergeTool {
             merge_args: vec![],
             merge_conflict_exit_codes: vec![],
             merge_tool_edits_conflict_markers: false,
+            premerge: false,
             conflict_marker_style: None,
             diff_invocation_mode: DiffToolMode::Dir,
         }
@@ -174,6 +182,40 @@ pub enum ExternalToolError {
     Io(#[source] std::io::Error),
 }
 
+/// Resolves the regions of the conflict which can be merged automatically, and
+/// returns the full contents of each term with the resolved regions filled in.
+///
+/// The returned terms only differ from each other in the regions which are
+/// still conflicted.
+fn premerge_content(content: &Merge<BString>) -> Merge<BString> {
+    let mut terms = vec![BString::default(); content.as_slice().len()];
+    match files::merge(content) {
+        MergeResult::Resolved(resolved) => {
+            for term in &mut terms {
+                term.extend_from_slice(&resolved);
+            }
+        }
+        MergeResult::Conflict(hunks) => {
+            for hunk in hunks {
+                if let Some(resolved) = hunk.as_resolved() {
+                    for term in &mut terms {
+                        term.extend_from_slice(resolved);
+                    }
+                } else {
+                    for (term, hunk_term) in terms.iter_mut().zip(hunk.iter()) {
+                        term.extend_from_slice(hunk_term);
+                    }
+                }
+            }
+        }
+    }
+    Merge::from_vec(terms)
+}
+
+fn has_conflict_markers(content: &[u8]) -> bool {
+    conflicts::parse_conflict(content, 2, MIN_CONFLICT_MARKER_LEN).is_some()
+}
+
 fn run_mergetool_external_single_file(
     editor: &ExternalMergeTool,
     store: &Store,
@@ -213,6 +255,13 @@ fn run_mergetool_external_single_file(
         BString::default()
     };
     assert_eq!(content.num_sides(), 2);
+    let premerged_content;
+    let content = if editor.premerge {
+        premerged_content = premerge_content(content);
+        &premerged_content
+    } else {
+        content
+    };
     let files: HashMap<&str, &[u8]> = maplit::hashmap! {
         "base" => content.get_remove(0).unwrap().as_slice(),
         "left" => content.get_add(0).unwrap().as_slice(),
@@ -288,6 +337,19 @@ fn run_mergetool_external_single_file(
         )
         .block_on()?
     } else {
+        // The output is recorded as a resolution as-is, so conflict markers
+        // left by the tool would end up in the file unnoticed. Markers which
+        // already exist in the inputs are assumed to be part of the content.
+        if has_conflict_markers(&output_file_contents)
+            && !merge_tool_file
+                .content
+                .iter()
+                .any(|content| has_conflict_markers(content))
+        {
+            return Err(ConflictResolveError::LeftoverConflictMarkers(
+                repo_path.clone(),
+            ));
+        }
         let new_file_id = store
             .write_file(repo_path, &mut output_file_contents.as_slice())
             .block_on()?;
@@ -379,6 +441,7 @@ pub fn edit_diff_external(
         .unwrap_or(default_conflict_marker_style);
     let options = CheckoutOptions {
         conflict_marker_style,
+        undo_stash_retention: None,
     };
 
     let got_output_field = find_all_variables(&editor.edit_args).contains(&"output");
@@ -427,6 +490,7 @@ pub fn generate_diff(
         .unwrap_or(default_conflict_marker_style);
     let options = CheckoutOptions {
         conflict_marker_style,
+        undo_stash_retention: None,
     };
     let store = left_tree.store();
     let diff_wc = check_out_trees(store, left_tree, right_tree, matcher, None, &options)?;
@@ -539,4 +603,41 @@ mod tests {
             ["left", "right", "1", "2", "output", "right"],
         );
     }
+
+    #[test]
+    fn test_premerge_content() {
+        let to_strings = |merge: Merge<BString>| merge.map(|term| term.to_string());
+
+        // Non-conflicting changes are applied to all terms
+        let content = Merge::from_vec(vec![
+            BString::from("a\nleft\nc\nd\ne2\n"),
+            BString::from("a\nb\nc\nd\ne\n"),
+            BString::from("a2\nright\nc\nd\ne\n"),
+        ]);
+        insta::assert_debug_snapshot!(to_strings(premerge_content(&content)), @r#"
+        Conflicted(
+            [
+                "a\nleft\nc\nd\ne2\n",
+                "a\nb\nc\nd\ne2\n",
+                "a2\nright\nc\nd\ne2\n",
+            ],
+        )
+        "#);
+
+        // Lines which look like conflict markers are preserved as-is
+        let content = Merge::from_vec(vec![
+            BString::from("<<<<<<<\nleft\n=======\nx\n>>>>>>>\n"),
+            BString::from("<<<<<<<\nbase\n=======\nx\n>>>>>>>\n"),
+            BString::from("<<<<<<<\nright\n=======\ny\n>>>>>>>\n"),
+        ]);
+        insta::assert_debug_snapshot!(to_strings(premerge_content(&content)), @r#"
+        Conflicted(
+            [
+                "<<<<<<<\nleft\n=======\ny\n>>>>>>>\n",
+                "<<<<<<<\nbase\n=======\ny\n>>>>>>>\n",
+                "<<<<<<<\nright\n=======\ny\n>>>>>>>\n",
+            ],
+        )
+        "#);
+    }
 }
@@ -13,9 +13,11 @@
 // limitations under the License.
 
 mod builtin;
+mod builtin_text;
 mod diff_working_copies;
 mod external;
 
+use std::ptr;
 use std::sync::Arc;
 
 use bstr::BString;
@@ -42,6 +44,7 @@ use pollster::FutureExt;
 use thiserror::Error;
 
 use self::builtin::edit_diff_builtin;
+use self::builtin::edit_diff_builtin_text;
 use self::builtin::edit_merge_builtin;
 use self::builtin::BuiltinToolError;
 pub(crate) use self::diff_working_copies::new_utf8_temp_dir;
@@ -56,6 +59,7 @@ use crate::config::CommandNameAndArgs;
 use crate::ui::Ui;
 
 const BUILTIN_EDITOR_NAME: &str = ":builtin";
+const BUILTIN_TEXT_EDITOR_NAME: &str = ":builtin-text";
 
 #[derive(Debug, Error)]
 pub enum DiffEditError {
@@ -103,6 +107,11 @@ pub enum ConflictResolveError {
          see the exact invocation)."
     )]
     EmptyOrUnchanged,
+    #[error(
+        "The output file for {0:?} contains conflict markers, but the merge tool isn't configured \
+         to leave conflicts unresolved"
+    )]
+    LeftoverConflictMarkers(RepoPathBuf),
     #[error(transparent)]
     Backend(#[from] jj_lib::backend::BackendError),
     #[error(transparent)]
@@ -127,6 +136,8 @@ pub enum MergeToolConfigError {
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub enum MergeTool {
     Builtin,
+    /// Builtin diff editor which prompts for each hunk without the TUI.
+    BuiltinText,
     // Boxed because ExternalMergeTool is big compared to the Builtin variant.
     External(Box<ExternalMergeTool>),
 }
@@ -167,11 +178,37 @@ fn get_tool_config(
 ) -> Result<Option<MergeTool>, ConfigGetError> {
     if name == BUILTIN_EDITOR_NAME {
         Ok(Some(MergeTool::Builtin))
+    } else if name == BUILTIN_TEXT_EDITOR_NAME {
+        Ok(Some(MergeTool::BuiltinText))
     } else {
         Ok(get_external_tool_config(settings, name)?.map(MergeTool::external))
     }
 }
 
+/// Resolves the merge tool of the given name, which may be a program name if
+/// no options are configured for it.
+fn get_merge_tool(settings: &UserSettings, name: &str) -> Result<MergeTool, MergeToolConfigError> {
+    let tool = get_tool_config(settings, name)?
+        .unwrap_or_else(|| MergeTool::external(ExternalMergeTool::with_program(name)));
+    check_merge_tool(name, &tool)?;
+    Ok(tool)
+}
+
+fn check_merge_tool(name: impl ToString, tool: &MergeTool) -> Result<(), MergeToolConfigError> {
+    let is_merge_tool = match tool {
+        MergeTool::Builtin => true,
+        MergeTool::BuiltinText => false,
+        MergeTool::External(mergetool) => !mergetool.merge_args.is_empty(),
+    };
+    if is_merge_tool {
+        Ok(())
+    } else {
+        Err(MergeToolConfigError::MergeArgsNotConfigured {
+            tool_name: name.to_string(),
+        })
+    }
+}
+
 /// Loads external diff/merge tool options from `[merge-tools.<name>]`.
 pub fn get_external_tool_config(
     settings: &UserSettings,
@@ -263,6 +300,13 @@ impl DiffEditor {
                         .map_err(Box::new)?,
                 )
             }
+            MergeTool::BuiltinText => Ok(edit_diff_builtin_text(
+                left_tree,
+                right_tree,
+                matcher,
+                self.conflict_marker_style,
+            )
+            .map_err(Box::new)?),
             MergeTool::External(editor) => {
                 let instructions = self.use_instructions.then(format_instructions);
                 edit_diff_external(
@@ -320,10 +364,68 @@ impl MergeToolFile {
     }
 }
 
+#[derive(Clone, Debug, serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+struct MergeToolRouteConfig {
+    pattern: String,
+    tool: String,
+}
+
+/// Merge tool to be used for the files matching a glob pattern.
+#[derive(Clone, Debug)]
+struct MergeToolRoute {
+    pattern: glob::Pattern,
+    tool: MergeTool,
+}
+
+impl MergeToolRoute {
+    /// Returns true if the `pattern` matches the file name, or the whole path
+    /// if the pattern contains `/`.
+    fn matches(&self, repo_path: &RepoPath) -> bool {
+        let options = glob::MatchOptions {
+            require_literal_separator: true,
+            ..Default::default()
+        };
+        if self.pattern.as_str().contains('/') {
+            self.pattern
+                .matches_with(repo_path.as_internal_file_string(), options)
+        } else if let Some(name) = repo_path.components().next_back() {
+            self.pattern.matches_with(name.as_internal_str(), options)
+        } else {
+            false
+        }
+    }
+}
+
+/// Loads the merge tools to be used for specific files from
+/// `merge-tools.routes`.
+fn merge_tool_routes_from_settings(
+    settings: &UserSettings,
+) -> Result<Vec<MergeToolRoute>, MergeToolConfigError> {
+    let name = "merge-tools.routes";
+    let Some(configs) = settings.get::<Vec<MergeToolRouteConfig>>(name).optional()? else {
+        return Ok(vec![]);
+    };
+    configs
+        .into_iter()
+        .map(|config| {
+            let pattern =
+                glob::Pattern::new(&config.pattern).map_err(|err| ConfigGetError::Type {
+                    name: name.to_owned(),
+                    error: err.into(),
+                    source_path: None,
+                })?;
+            let tool = get_merge_tool(settings, &config.tool)?;
+            Ok(MergeToolRoute { pattern, tool })
+        })
+        .collect()
+}
+
 /// Configured 3-way merge editor.
 #[derive(Clone, Debug)]
 pub struct MergeEditor {
     tool: MergeTool,
+    routes: Vec<MergeToolRoute>,
     path_converter: RepoPathUiConverter,
     conflict_marker_style: ConflictMarkerStyle,
 }
@@ -337,12 +439,17 @@ impl MergeEditor {
         path_converter: RepoPathUiConverter,
         conflict_marker_style: ConflictMarkerStyle,
     ) -> Result<Self, MergeToolConfigError> {
-        let tool = get_tool_config(settings, name)?
-            .unwrap_or_else(|| MergeTool::external(ExternalMergeTool::with_program(name)));
-        Self::new_inner(name, tool, path_converter, conflict_marker_style)
+        let tool = get_merge_tool(settings, name)?;
+        Ok(MergeEditor {
+            tool,
+            routes: vec![],
+            path_converter,
+            conflict_marker_style,
+        })
     }
 
-    /// Loads the default 3-way merge editor from the settings.
+    /// Loads the default 3-way merge editor, and the editors to be used for
+    /// specific files, from the settings.
     pub fn from_settings(
         ui: &Ui,
         settings: &UserSettings,
@@ -356,27 +463,25 @@ impl MergeEditor {
             None
         }
         .unwrap_or_else(|| MergeTool::external(ExternalMergeTool::with_merge_args(&args)));
-        Self::new_inner(&args, tool, path_converter, conflict_marker_style)
-    }
-
-    fn new_inner(
-        name: impl ToString,
-        tool: MergeTool,
-        path_converter: RepoPathUiConverter,
-        conflict_marker_style: ConflictMarkerStyle,
-    ) -> Result<Self, MergeToolConfigError> {
-        if matches!(&tool, MergeTool::External(mergetool) if mergetool.merge_args.is_empty()) {
-            return Err(MergeToolConfigError::MergeArgsNotConfigured {
-                tool_name: name.to_string(),
-            });
-        }
+        check_merge_tool(&args, &tool)?;
+        let routes = merge_tool_routes_from_settings(settings)?;
         Ok(MergeEditor {
             tool,
+            routes,
             path_converter,
             conflict_marker_style,
         })
     }
 
+    /// Returns the tool to resolve conflicts in the `repo_path` with. The
+    /// first matching route takes precedence over the default tool.
+    fn tool_for_path(&self, repo_path: &RepoPath) -> &MergeTool {
+        self.routes
+            .iter()
+            .find(|route| route.matches(repo_path))
+            .map_or(&self.tool, |route| &route.tool)
+    }
+
     /// Starts a merge editor for the specified files.
     pub fn edit_files(
         &self,
@@ -389,17 +494,61 @@ impl MergeEditor {
             .map(|&repo_path| MergeToolFile::from_tree_and_path(tree, repo_path))
             .try_collect()?;
 
-        match &self.tool {
+        // Files routed to the same tool are resolved together, in the order in
+        // which the tools are first needed.
+        let mut file_groups: Vec<(&MergeTool, Vec<MergeToolFile>)> = vec![];
+        for file in merge_tool_files {
+            let tool = self.tool_for_path(&file.repo_path);
+            if let Some((_, files)) = file_groups.iter_mut().find(|(t, _)| ptr::eq(*t, tool)) {
+                files.push(file);
+            } else {
+                file_groups.push((tool, vec![file]));
+            }
+        }
+
+        let mut tree = tree.clone();
+        let mut resolved_count = 0;
+        for (tool, files) in &file_groups {
+            let (tree_id, partial_resolution_error) = match self.run_tool(ui, tool, &tree, files) {
+                Ok(result) => result,
+                Err(err) if resolved_count == 0 => return Err(err),
+                Err(err) => {
+                    let partial_resolution_error = MergeToolPartialResolutionError {
+                        source: err,
+                        resolved_count,
+                    };
+                    return Ok((tree.id(), Some(partial_resolution_error)));
+                }
+            };
+            if let Some(mut err) = partial_resolution_error {
+                err.resolved_count += resolved_count;
+                return Ok((tree_id, Some(err)));
+            }
+            tree = tree.store().get_root_tree(&tree_id)?;
+            resolved_count += files.len();
+        }
+        Ok((tree.id(), None))
+    }
+
+    fn run_tool(
+        &self,
+        ui: &Ui,
+        tool: &MergeTool,
+        tree: &MergedTree,
+        merge_tool_files: &[MergeToolFile],
+    ) -> Result<(MergedTreeId, Option<MergeToolPartialResolutionError>), ConflictResolveError> {
+        match tool {
             MergeTool::Builtin => {
-                let tree_id = edit_merge_builtin(tree, &merge_tool_files).map_err(Box::new)?;
+                let tree_id = edit_merge_builtin(tree, merge_tool_files).map_err(Box::new)?;
                 Ok((tree_id, None))
             }
+            MergeTool::BuiltinText => unreachable!("rejected by check_merge_tool()"),
             MergeTool::External(editor) => external::run_mergetool_external(
                 ui,
                 &self.path_converter,
                 editor,
                 tree,
-                &merge_tool_files,
+                merge_tool_files,
                 self.conflict_marker_style,
             ),
         }
@@ -437,6 +586,7 @@ mod tests {
         };
 
         insta::assert_debug_snapshot!(get(":builtin", "").unwrap(), @"Builtin");
+        insta::assert_debug_snapshot!(get(":builtin-text", "").unwrap(), @"BuiltinText");
 
         // Just program name, edit_args are filled by default
         insta::assert_debug_snapshot!(get("my diff", "").unwrap(), @r#"
@@ -458,6 +608,7 @@ mod tests {
                 merge_args: [],
                 merge_conflict_exit_codes: [],
                 merge_tool_edits_conflict_markers: false,
+                premerge: false,
                 conflict_marker_style: None,
             },
         )
@@ -490,6 +641,7 @@ mod tests {
                 merge_args: [],
                 merge_conflict_exit_codes: [],
                 merge_tool_edits_conflict_markers: false,
+                premerge: false,
                 conflict_marker_style: None,
             },
         )
@@ -534,6 +686,7 @@ mod tests {
                 merge_args: [],
                 merge_conflict_exit_codes: [],
                 merge_tool_edits_conflict_markers: false,
+                premerge: false,
                 conflict_marker_style: None,
             },
         )
@@ -562,6 +715,7 @@ mod tests {
                 merge_args: [],
                 merge_conflict_exit_codes: [],
                 merge_tool_edits_conflict_markers: false,
+                premerge: false,
                 conflict_marker_style: None,
             },
         )
@@ -589,6 +743,7 @@ mod tests {
                 merge_args: [],
                 merge_conflict_exit_codes: [],
                 merge_tool_edits_conflict_markers: false,
+                premerge: false,
                 conflict_marker_style: None,
             },
         )
@@ -622,6 +777,7 @@ mod tests {
                 merge_args: [],
                 merge_conflict_exit_codes: [],
                 merge_tool_edits_conflict_markers: false,
+                premerge: false,
                 conflict_marker_style: None,
             },
         )
@@ -653,6 +809,7 @@ mod tests {
                 merge_args: [],
                 merge_conflict_exit_codes: [],
                 merge_tool_edits_conflict_markers: false,
+                premerge: false,
                 conflict_marker_style: None,
             },
         )
@@ -678,6 +835,7 @@ mod tests {
                 merge_args: [],
                 merge_conflict_exit_codes: [],
                 merge_tool_edits_conflict_markers: false,
+                premerge: false,
                 conflict_marker_style: None,
             },
         )
@@ -701,6 +859,11 @@ mod tests {
         };
 
         insta::assert_debug_snapshot!(get(":builtin", "").unwrap(), @"Builtin");
+        insta::assert_debug_snapshot!(get(":builtin-text", "").unwrap_err(), @r#"
+        MergeArgsNotConfigured {
+            tool_name: ":builtin-text",
+        }
+        "#);
 
         // Just program name
         insta::assert_debug_snapshot!(get("my diff", "").unwrap_err(), @r#"
@@ -739,6 +902,7 @@ mod tests {
                 ],
                 merge_conflict_exit_codes: [],
                 merge_tool_edits_conflict_markers: false,
+                premerge: false,
                 conflict_marker_style: None,
             },
         )
@@ -795,6 +959,7 @@ mod tests {
                 ],
                 merge_conflict_exit_codes: [],
                 merge_tool_edits_conflict_markers: false,
+                premerge: false,
                 conflict_marker_style: None,
             },
         )
@@ -828,6 +993,7 @@ mod tests {
                 ],
                 merge_conflict_exit_codes: [],
                 merge_tool_edits_conflict_markers: false,
+                premerge: false,
                 conflict_marker_style: None,
             },
         )
@@ -864,6 +1030,7 @@ mod tests {
                 ],
                 merge_conflict_exit_codes: [],
                 merge_tool_edits_conflict_markers: false,
+                premerge: false,
                 conflict_marker_style: None,
             },
         )
@@ -880,4 +1047,52 @@ mod tests {
         // Invalid type
         assert!(get(r#"ui.merge-editor.k = 0"#).is_err());
     }
+
+    #[test]
+    fn test_merge_editor_routes() {
+        let get = |text| {
+            let config = config_from_string(text);
+            let ui = Ui::with_config(&config).unwrap();
+            let settings = UserSettings::from_config(config).unwrap();
+            let path_converter = RepoPathUiConverter::Fs {
+                cwd: "".into(),
+                base: "".into(),
+            };
+            MergeEditor::from_settings(&ui, &settings, path_converter, ConflictMarkerStyle::Diff)
+        };
+        let tool_name = |editor: &MergeEditor, path: &str| match editor
+            .tool_for_path(RepoPath::from_internal_string(path))
+        {
+            MergeTool::Builtin => ":builtin".to_owned(),
+            MergeTool::BuiltinText => ":builtin-text".to_owned(),
+            MergeTool::External(tool) => tool.program.clone(),
+        };
+
+        let editor = get(indoc::indoc! {r#"
+            merge-tools.routes = [
+              { pattern = "*.ipynb", tool = "meld" },
+              { pattern = "docs/*.md", tool = "kdiff3" },
+              { pattern = "*.md", tool = "vimdiff" },
+            ]
+        "#})
+        .unwrap();
+        assert_eq!(tool_name(&editor, "a.ipynb"), "meld");
+        assert_eq!(tool_name(&editor, "dir/a.ipynb"), "meld");
+        assert_eq!(tool_name(&editor, "docs/a.md"), "kdiff3");
+        assert_eq!(tool_name(&editor, "docs/dir/a.md"), "vim");
+        assert_eq!(tool_name(&editor, "a.md"), "vim");
+        assert_eq!(tool_name(&editor, "a.txt"), ":builtin");
+
+        // Invalid pattern
+        assert!(get(r#"merge-tools.routes = [{ pattern = "[", tool = "meld" }]"#).is_err());
+
+        // Tool which can't merge
+        insta::assert_debug_snapshot!(
+            get(r#"merge-tools.routes = [{ pattern = "*", tool = "my-merge" }]"#).unwrap_err(),
+            @r#"
+            MergeArgsNotConfigured {
+                tool_name: "my-merge",
+            }
+            "#);
+    }
 }
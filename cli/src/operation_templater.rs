@@ -13,14 +13,18 @@
 // limitations under the License.
 
 use std::any::Any;
+use std::cell::OnceCell;
 use std::cmp::Ordering;
 use std::collections::HashMap;
+use std::error;
 use std::io;
+use std::rc::Rc;
 
 use itertools::Itertools as _;
 use jj_lib::extensions_map::ExtensionsMap;
 use jj_lib::object_id::ObjectId;
 use jj_lib::op_store::OperationId;
+use jj_lib::op_walk;
 use jj_lib::operation::Operation;
 use jj_lib::repo::RepoLoader;
 use jj_lib::settings::UserSettings;
@@ -36,6 +40,7 @@ use crate::template_builder::TemplateLanguage;
 use crate::template_parser;
 use crate::template_parser::FunctionCallNode;
 use crate::template_parser::TemplateDiagnostics;
+use crate::template_parser::TemplateParseError;
 use crate::template_parser::TemplateParseResult;
 use crate::templater::PlainTextFormattedProperty;
 use crate::templater::Template;
@@ -44,17 +49,75 @@ use crate::templater::TemplateProperty;
 use crate::templater::TemplatePropertyExt as _;
 use crate::templater::TimestampRange;
 
+/// Tag of an operation attaching a note to another operation, which holds the
+/// id of the described operation.
+pub const ANNOTATED_OPERATION_TAG: &str = "annotated-operation";
+/// Tag of an operation attaching a note to another operation, which holds the
+/// note.
+pub const OPERATION_ANNOTATION_TAG: &str = "annotation";
+
 pub trait OperationTemplateLanguageExtension {
     fn build_fn_table(&self) -> OperationTemplateBuildFnTable;
 
     fn build_cache_extensions(&self, extensions: &mut ExtensionsMap);
 }
 
+/// Index of the latest notes attached to operations, which is built on first
+/// use. Clones share the index, so the operation log is only walked once per
+/// command.
+#[derive(Clone, Debug, Default)]
+pub struct OperationAnnotations {
+    index: Rc<OnceCell<HashMap<OperationId, String>>>,
+}
+
+impl OperationAnnotations {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn ensure_loaded(
+        &self,
+        repo_loader: &RepoLoader,
+    ) -> Result<(), Box<dyn error::Error + Send + Sync>> {
+        if self.index.get().is_some() {
+            return Ok(());
+        }
+        let head_ops: Vec<_> = repo_loader
+            .op_heads_store()
+            .get_op_heads()?
+            .iter()
+            .map(|id| repo_loader.load_operation(id))
+            .try_collect()?;
+        let mut index = HashMap::new();
+        // Descendant operations are visited first, so the latest note wins.
+        for op in op_walk::walk_ancestors(&head_ops) {
+            let op = op?;
+            let tags = &op.metadata().tags;
+            let (Some(id), Some(annotation)) = (
+                tags.get(ANNOTATED_OPERATION_TAG),
+                tags.get(OPERATION_ANNOTATION_TAG),
+            ) else {
+                continue;
+            };
+            if let Ok(id) = OperationId::try_from_hex(id) {
+                index.entry(id).or_insert_with(|| annotation.clone());
+            }
+        }
+        self.index.get_or_init(|| index);
+        Ok(())
+    }
+
+    fn get(&self, id: &OperationId) -> Option<&str> {
+        self.index.get()?.get(id).map(String::as_str)
+    }
+}
+
 pub struct OperationTemplateLanguage {
     repo_loader: RepoLoader,
     current_op_id: Option<OperationId>,
     build_fn_table: OperationTemplateBuildFnTable,
     cache_extensions: ExtensionsMap,
+    annotations: OperationAnnotations,
 }
 
 impl OperationTemplateLanguage {
@@ -63,6 +126,7 @@ impl OperationTemplateLanguage {
     pub fn new(
         repo_loader: &RepoLoader,
         current_op_id: Option<&OperationId>,
+        annotations: &OperationAnnotations,
         extensions: &[impl AsRef<dyn OperationTemplateLanguageExtension>],
     ) -> Self {
         let mut build_fn_table = OperationTemplateBuildFnTable::builtin();
@@ -81,6 +145,7 @@ impl OperationTemplateLanguage {
             current_op_id: current_op_id.cloned(),
             build_fn_table,
             cache_extensions,
+            annotations: annotations.clone(),
         }
     }
 }
@@ -294,6 +359,22 @@ fn builtin_operation_methods() -> OperationTemplateBuildMethodFnMap<Operation> {
             Ok(L::wrap_operation_id(out_property))
         },
     );
+    map.insert(
+        "annotation",
+        |language, _diagnostics, _build_ctx, self_property, function| {
+            function.expect_no_arguments()?;
+            let annotations = language.annotations.clone();
+            annotations
+                .ensure_loaded(&language.repo_loader)
+                .map_err(|err| {
+                    let message = "Failed to load operation notes";
+                    TemplateParseError::expression(message, function.name_span).with_source(err)
+                })?;
+            let out_property = self_property
+                .map(move |op| annotations.get(op.id()).unwrap_or_default().to_owned());
+            Ok(L::wrap_string(out_property))
+        },
+    );
     map.insert(
         "tags",
         |_language, _diagnostics, _build_ctx, self_property, function| {
@@ -303,6 +384,10 @@ fn builtin_operation_methods() -> OperationTemplateBuildMethodFnMap<Operation> {
                 op.metadata()
                     .tags
                     .iter()
+                    // Notes are shown by the annotation method instead.
+                    .filter(|(key, _)| {
+                        ![ANNOTATED_OPERATION_TAG, OPERATION_ANNOTATION_TAG].contains(&key.as_str())
+                    })
                     .map(|(key, value)| format!("{key}: {value}"))
                     .join("\n")
             });
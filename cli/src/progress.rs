@@ -1,4 +1,5 @@
 use std::path::Path;
+use std::sync::Arc;
 use std::sync::Mutex;
 use std::time::Duration;
 use std::time::Instant;
@@ -6,6 +7,7 @@ use std::time::Instant;
 use crossterm::terminal::Clear;
 use crossterm::terminal::ClearType;
 use jj_lib::repo_path::RepoPath;
+use jj_lib::store::Store;
 
 use crate::text_util;
 use crate::ui::OutputGuard;
@@ -64,3 +66,63 @@ pub fn snapshot_progress(ui: &Ui) -> Option<impl Fn(&RepoPath) + use<>> {
         _ = state.output.flush();
     })
 }
+
+/// Displays the number of signed commits until dropped.
+pub struct SigningProgress {
+    store: Arc<Store>,
+}
+
+impl Drop for SigningProgress {
+    fn drop(&mut self) {
+        self.store.signer().clear_progress();
+    }
+}
+
+/// Shows how many commits have been signed while rewriting commits in the
+/// `store`, which may take a while for large stacks.
+pub fn signing_progress(ui: &Ui, store: &Arc<Store>) -> Option<SigningProgress> {
+    struct State {
+        guard: Option<OutputGuard>,
+        output: ProgressOutput<std::io::Stderr>,
+        next_display_time: Instant,
+    }
+
+    if !store.signer().can_sign() {
+        return None;
+    }
+    let output = ui.progress_output()?;
+
+    // Don't clutter the output during fast operations.
+    let next_display_time = Instant::now() + INITIAL_DELAY;
+    let state = Mutex::new(State {
+        guard: None,
+        output,
+        next_display_time,
+    });
+
+    store.signer().set_progress(Box::new(move |count| {
+        let mut state = state.lock().unwrap();
+        let now = Instant::now();
+        if now < state.next_display_time {
+            return;
+        }
+        state.next_display_time = now + Duration::from_secs(1) / UPDATE_HZ;
+
+        if state.guard.is_none() {
+            state.guard = Some(
+                state
+                    .output
+                    .output_guard(format!("\r{}", Clear(ClearType::CurrentLine))),
+            );
+        }
+        _ = write!(
+            state.output,
+            "\r{}Signed {count} commits",
+            Clear(ClearType::CurrentLine),
+        );
+        _ = state.output.flush();
+    }));
+    Some(SigningProgress {
+        store: store.clone(),
+    })
+}
@@ -0,0 +1,106 @@
+// Copyright 2025 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Data model shared by the bookmark and tag listing commands.
+
+use std::rc::Rc;
+
+use itertools::Itertools as _;
+use jj_lib::object_id::ObjectId as _;
+use jj_lib::repo::Repo;
+
+use crate::command_error::internal_error;
+use crate::command_error::CommandError;
+use crate::commit_templater::RefName;
+use crate::formatter::Formatter;
+
+/// Listed ref and the remote refs shown along with it.
+#[derive(Clone, Debug)]
+pub struct RefListItem {
+    /// Local ref or untracked remote ref.
+    pub primary: Rc<RefName>,
+    /// Remote refs tracked by the primary (or local) ref.
+    pub tracked: Vec<Rc<RefName>>,
+}
+
+impl RefListItem {
+    /// Creates item for the local ref which doesn't track any remote refs.
+    pub fn local_only(primary: Rc<RefName>) -> Self {
+        RefListItem {
+            primary,
+            tracked: vec![],
+        }
+    }
+
+    /// Iterates over the primary ref followed by the tracked remote refs.
+    pub fn refs(&self) -> impl Iterator<Item = &Rc<RefName>> {
+        itertools::chain([&self.primary], &self.tracked)
+    }
+
+    /// Serializes the item as JSON object.
+    ///
+    /// The remote refs are nested in the `tracked` array of the primary ref.
+    pub fn to_json(&self, repo: &dyn Repo) -> Result<serde_json::Value, CommandError> {
+        let mut value = ref_name_to_json(&self.primary);
+        let tracked: Vec<_> = self
+            .tracked
+            .iter()
+            .map(|ref_name| tracked_ref_name_to_json(ref_name, repo))
+            .try_collect()?;
+        value["tracked"] = tracked.into();
+        Ok(value)
+    }
+}
+
+fn ref_name_to_json(ref_name: &RefName) -> serde_json::Value {
+    let target = ref_name.target();
+    serde_json::json!({
+        "name": ref_name.name(),
+        "remote": ref_name.remote_name(),
+        "present": target.is_present(),
+        "conflict": target.has_conflict(),
+        "added_targets": target.added_ids().map(|id| id.hex()).collect::<Vec<_>>(),
+        "removed_targets": target.removed_ids().map(|id| id.hex()).collect::<Vec<_>>(),
+    })
+}
+
+fn tracked_ref_name_to_json(
+    ref_name: &RefName,
+    repo: &dyn Repo,
+) -> Result<serde_json::Value, CommandError> {
+    let mut value = ref_name_to_json(ref_name);
+    if ref_name.is_tracked() {
+        let (ahead_count, _) = ref_name
+            .tracking_ahead_count(repo)
+            .map_err(|err| internal_error(err.0))?;
+        let (behind_count, _) = ref_name
+            .tracking_behind_count(repo)
+            .map_err(|err| internal_error(err.0))?;
+        value["tracking_ahead_count"] = ahead_count.into();
+        value["tracking_behind_count"] = behind_count.into();
+    }
+    Ok(value)
+}
+
+/// Writes the items as JSON Lines, one object per item.
+pub fn write_json_lines(
+    formatter: &mut dyn Formatter,
+    items: &[RefListItem],
+    repo: &dyn Repo,
+) -> Result<(), CommandError> {
+    for item in items {
+        writeln!(formatter, "{}", item.to_json(repo)?)?;
+    }
+    Ok(())
+}
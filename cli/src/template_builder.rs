@@ -22,6 +22,8 @@ use jj_lib::backend::Signature;
 use jj_lib::backend::Timestamp;
 use jj_lib::config::ConfigNamePathBuf;
 use jj_lib::config::ConfigValue;
+use jj_lib::description;
+use jj_lib::description::Trailer;
 use jj_lib::dsl_util::AliasExpandError as _;
 use jj_lib::settings::UserSettings;
 use jj_lib::time_util::DatePattern;
@@ -46,6 +48,8 @@ use crate::templater::CoalesceTemplate;
 use crate::templater::ConcatTemplate;
 use crate::templater::ConditionalTemplate;
 use crate::templater::Email;
+use crate::templater::HyperlinkTemplate;
+use crate::templater::IfColorTemplate;
 use crate::templater::LabelTemplate;
 use crate::templater::ListPropertyTemplate;
 use crate::templater::ListTemplate;
@@ -85,9 +89,16 @@ pub trait TemplateLanguage<'a> {
     fn wrap_email(property: impl TemplateProperty<Output = Email> + 'a) -> Self::Property;
     fn wrap_size_hint(property: impl TemplateProperty<Output = SizeHint> + 'a) -> Self::Property;
     fn wrap_timestamp(property: impl TemplateProperty<Output = Timestamp> + 'a) -> Self::Property;
+    fn wrap_timestamp_opt(
+        property: impl TemplateProperty<Output = Option<Timestamp>> + 'a,
+    ) -> Self::Property;
     fn wrap_timestamp_range(
         property: impl TemplateProperty<Output = TimestampRange> + 'a,
     ) -> Self::Property;
+    fn wrap_trailer(property: impl TemplateProperty<Output = Trailer> + 'a) -> Self::Property;
+    fn wrap_trailer_list(
+        property: impl TemplateProperty<Output = Vec<Trailer>> + 'a,
+    ) -> Self::Property;
 
     fn wrap_template(template: Box<dyn Template + 'a>) -> Self::Property;
     fn wrap_list_template(template: Box<dyn ListTemplate + 'a>) -> Self::Property;
@@ -135,7 +146,10 @@ macro_rules! impl_core_wrap_property_fns {
                 wrap_email($crate::templater::Email) => Email,
                 wrap_size_hint($crate::templater::SizeHint) => SizeHint,
                 wrap_timestamp(jj_lib::backend::Timestamp) => Timestamp,
+                wrap_timestamp_opt(Option<jj_lib::backend::Timestamp>) => TimestampOpt,
                 wrap_timestamp_range($crate::templater::TimestampRange) => TimestampRange,
+                wrap_trailer(jj_lib::description::Trailer) => Trailer,
+                wrap_trailer_list(Vec<jj_lib::description::Trailer>) => TrailerList,
             }
         );
         fn wrap_template(
@@ -199,7 +213,10 @@ pub enum CoreTemplatePropertyKind<'a> {
     Email(Box<dyn TemplateProperty<Output = Email> + 'a>),
     SizeHint(Box<dyn TemplateProperty<Output = SizeHint> + 'a>),
     Timestamp(Box<dyn TemplateProperty<Output = Timestamp> + 'a>),
+    TimestampOpt(Box<dyn TemplateProperty<Output = Option<Timestamp>> + 'a>),
     TimestampRange(Box<dyn TemplateProperty<Output = TimestampRange> + 'a>),
+    Trailer(Box<dyn TemplateProperty<Output = Trailer> + 'a>),
+    TrailerList(Box<dyn TemplateProperty<Output = Vec<Trailer>> + 'a>),
 
     // Both TemplateProperty and Template can represent a value to be evaluated
     // dynamically, which suggests that `Box<dyn Template + 'a>` could be
@@ -228,7 +245,10 @@ impl<'a> IntoTemplateProperty<'a> for CoreTemplatePropertyKind<'a> {
             CoreTemplatePropertyKind::Email(_) => "Email",
             CoreTemplatePropertyKind::SizeHint(_) => "SizeHint",
             CoreTemplatePropertyKind::Timestamp(_) => "Timestamp",
+            CoreTemplatePropertyKind::TimestampOpt(_) => "Option<Timestamp>",
             CoreTemplatePropertyKind::TimestampRange(_) => "TimestampRange",
+            CoreTemplatePropertyKind::Trailer(_) => "Trailer",
+            CoreTemplatePropertyKind::TrailerList(_) => "List<Trailer>",
             CoreTemplatePropertyKind::Template(_) => "Template",
             CoreTemplatePropertyKind::ListTemplate(_) => "ListTemplate",
         }
@@ -254,7 +274,14 @@ impl<'a> IntoTemplateProperty<'a> for CoreTemplatePropertyKind<'a> {
             }
             CoreTemplatePropertyKind::SizeHint(_) => None,
             CoreTemplatePropertyKind::Timestamp(_) => None,
+            CoreTemplatePropertyKind::TimestampOpt(property) => {
+                Some(Box::new(property.map(|opt| opt.is_some())))
+            }
             CoreTemplatePropertyKind::TimestampRange(_) => None,
+            CoreTemplatePropertyKind::Trailer(_) => None,
+            CoreTemplatePropertyKind::TrailerList(property) => {
+                Some(Box::new(property.map(|l| !l.is_empty())))
+            }
             // Template types could also be evaluated to boolean, but it's less likely
             // to apply label() or .map() and use the result as conditional. It's also
             // unclear whether ListTemplate should behave as a "list" or a "template".
@@ -295,7 +322,10 @@ impl<'a> IntoTemplateProperty<'a> for CoreTemplatePropertyKind<'a> {
             CoreTemplatePropertyKind::Email(property) => Some(property.into_template()),
             CoreTemplatePropertyKind::SizeHint(_) => None,
             CoreTemplatePropertyKind::Timestamp(property) => Some(property.into_template()),
+            CoreTemplatePropertyKind::TimestampOpt(property) => Some(property.into_template()),
             CoreTemplatePropertyKind::TimestampRange(property) => Some(property.into_template()),
+            CoreTemplatePropertyKind::Trailer(property) => Some(property.into_template()),
+            CoreTemplatePropertyKind::TrailerList(property) => Some(property.into_template()),
             CoreTemplatePropertyKind::Template(template) => Some(template),
             CoreTemplatePropertyKind::ListTemplate(template) => Some(template.into_template()),
         }
@@ -331,7 +361,10 @@ impl<'a> IntoTemplateProperty<'a> for CoreTemplatePropertyKind<'a> {
             (CoreTemplatePropertyKind::Email(_), _) => None,
             (CoreTemplatePropertyKind::SizeHint(_), _) => None,
             (CoreTemplatePropertyKind::Timestamp(_), _) => None,
+            (CoreTemplatePropertyKind::TimestampOpt(_), _) => None,
             (CoreTemplatePropertyKind::TimestampRange(_), _) => None,
+            (CoreTemplatePropertyKind::Trailer(_), _) => None,
+            (CoreTemplatePropertyKind::TrailerList(_), _) => None,
             (CoreTemplatePropertyKind::Template(_), _) => None,
             (CoreTemplatePropertyKind::ListTemplate(_), _) => None,
         }
@@ -355,7 +388,10 @@ impl<'a> IntoTemplateProperty<'a> for CoreTemplatePropertyKind<'a> {
             (CoreTemplatePropertyKind::Email(_), _) => None,
             (CoreTemplatePropertyKind::SizeHint(_), _) => None,
             (CoreTemplatePropertyKind::Timestamp(_), _) => None,
+            (CoreTemplatePropertyKind::TimestampOpt(_), _) => None,
             (CoreTemplatePropertyKind::TimestampRange(_), _) => None,
+            (CoreTemplatePropertyKind::Trailer(_), _) => None,
+            (CoreTemplatePropertyKind::TrailerList(_), _) => None,
             (CoreTemplatePropertyKind::Template(_), _) => None,
             (CoreTemplatePropertyKind::ListTemplate(_), _) => None,
         }
@@ -405,6 +441,7 @@ pub struct CoreTemplateBuildFnTable<'a, L: TemplateLanguage<'a> + ?Sized> {
     pub size_hint_methods: TemplateBuildMethodFnMap<'a, L, SizeHint>,
     pub timestamp_methods: TemplateBuildMethodFnMap<'a, L, Timestamp>,
     pub timestamp_range_methods: TemplateBuildMethodFnMap<'a, L, TimestampRange>,
+    pub trailer_methods: TemplateBuildMethodFnMap<'a, L, Trailer>,
 }
 
 pub fn merge_fn_map<'s, F>(base: &mut HashMap<&'s str, F>, extension: HashMap<&'s str, F>) {
@@ -429,6 +466,7 @@ impl<'a, L: TemplateLanguage<'a> + ?Sized> CoreTemplateBuildFnTable<'a, L> {
             size_hint_methods: builtin_size_hint_methods(),
             timestamp_methods: builtin_timestamp_methods(),
             timestamp_range_methods: builtin_timestamp_range_methods(),
+            trailer_methods: builtin_trailer_methods(),
         }
     }
 
@@ -444,6 +482,7 @@ impl<'a, L: TemplateLanguage<'a> + ?Sized> CoreTemplateBuildFnTable<'a, L> {
             size_hint_methods: HashMap::new(),
             timestamp_methods: HashMap::new(),
             timestamp_range_methods: HashMap::new(),
+            trailer_methods: HashMap::new(),
         }
     }
 
@@ -459,6 +498,7 @@ impl<'a, L: TemplateLanguage<'a> + ?Sized> CoreTemplateBuildFnTable<'a, L> {
             size_hint_methods,
             timestamp_methods,
             timestamp_range_methods,
+            trailer_methods,
         } = extension;
 
         merge_fn_map(&mut self.functions, functions);
@@ -471,6 +511,7 @@ impl<'a, L: TemplateLanguage<'a> + ?Sized> CoreTemplateBuildFnTable<'a, L> {
         merge_fn_map(&mut self.size_hint_methods, size_hint_methods);
         merge_fn_map(&mut self.timestamp_methods, timestamp_methods);
         merge_fn_map(&mut self.timestamp_range_methods, timestamp_range_methods);
+        merge_fn_map(&mut self.trailer_methods, trailer_methods);
     }
 
     /// Translates the function call node `function` by using this symbol table.
@@ -563,11 +604,41 @@ impl<'a, L: TemplateLanguage<'a> + ?Sized> CoreTemplateBuildFnTable<'a, L> {
                 let build = template_parser::lookup_method(type_name, table, function)?;
                 build(language, diagnostics, build_ctx, property, function)
             }
+            CoreTemplatePropertyKind::TimestampOpt(property) => {
+                let type_name = "Timestamp";
+                let table = &self.timestamp_methods;
+                let build = template_parser::lookup_method(type_name, table, function)?;
+                let inner_property = property.try_unwrap(type_name);
+                build(
+                    language,
+                    diagnostics,
+                    build_ctx,
+                    Box::new(inner_property),
+                    function,
+                )
+            }
             CoreTemplatePropertyKind::TimestampRange(property) => {
                 let table = &self.timestamp_range_methods;
                 let build = template_parser::lookup_method(type_name, table, function)?;
                 build(language, diagnostics, build_ctx, property, function)
             }
+            CoreTemplatePropertyKind::Trailer(property) => {
+                let table = &self.trailer_methods;
+                let build = template_parser::lookup_method(type_name, table, function)?;
+                build(language, diagnostics, build_ctx, property, function)
+            }
+            CoreTemplatePropertyKind::TrailerList(property) => {
+                // TODO: migrate to table?
+                build_formattable_list_method(
+                    language,
+                    diagnostics,
+                    build_ctx,
+                    property,
+                    function,
+                    L::wrap_trailer,
+                    L::wrap_trailer_list,
+                )
+            }
             CoreTemplatePropertyKind::Template(_) => {
                 // TODO: migrate to table?
                 Err(TemplateParseError::no_such_method(type_name, function))
@@ -903,6 +974,30 @@ fn builtin_string_methods<'a, L: TemplateLanguage<'a> + ?Sized>(
             Ok(L::wrap_string_list(out_property))
         },
     );
+    map.insert(
+        "subject",
+        |_language, _diagnostics, _build_ctx, self_property, function| {
+            function.expect_no_arguments()?;
+            let out_property = self_property.map(|s| description::subject(&s));
+            Ok(L::wrap_string(out_property))
+        },
+    );
+    map.insert(
+        "body",
+        |_language, _diagnostics, _build_ctx, self_property, function| {
+            function.expect_no_arguments()?;
+            let out_property = self_property.map(|s| description::body(&s).to_owned());
+            Ok(L::wrap_string(out_property))
+        },
+    );
+    map.insert(
+        "trailers",
+        |_language, _diagnostics, _build_ctx, self_property, function| {
+            function.expect_no_arguments()?;
+            let out_property = self_property.map(|s| description::trailers(&s));
+            Ok(L::wrap_trailer_list(out_property))
+        },
+    );
     map.insert(
         "upper",
         |_language, _diagnostics, _build_ctx, self_property, function| {
@@ -1236,6 +1331,30 @@ fn builtin_timestamp_range_methods<'a, L: TemplateLanguage<'a> + ?Sized>(
     map
 }
 
+fn builtin_trailer_methods<'a, L: TemplateLanguage<'a> + ?Sized>(
+) -> TemplateBuildMethodFnMap<'a, L, Trailer> {
+    // Not using maplit::hashmap!{} or custom declarative macro here because
+    // code completion inside macro is quite restricted.
+    let mut map = TemplateBuildMethodFnMap::<L, Trailer>::new();
+    map.insert(
+        "key",
+        |_language, _diagnostics, _build_ctx, self_property, function| {
+            function.expect_no_arguments()?;
+            let out_property = self_property.map(|trailer| trailer.key);
+            Ok(L::wrap_string(out_property))
+        },
+    );
+    map.insert(
+        "value",
+        |_language, _diagnostics, _build_ctx, self_property, function| {
+            function.expect_no_arguments()?;
+            let out_property = self_property.map(|trailer| trailer.value);
+            Ok(L::wrap_string(out_property))
+        },
+    );
+    map
+}
+
 fn build_list_template_method<'a, L: TemplateLanguage<'a> + ?Sized>(
     language: &L,
     diagnostics: &mut TemplateDiagnostics,
@@ -1555,6 +1674,22 @@ fn builtin_functions<'a, L: TemplateLanguage<'a> + ?Sized>() -> TemplateBuildFun
             Ok(L::wrap_template(template))
         },
     );
+    map.insert(
+        "truncate_middle",
+        |language, diagnostics, build_ctx, function| {
+            let ([width_node, content_node], [ellipsis_node]) =
+                function.expect_named_arguments(&["", "", "ellipsis"])?;
+            let width = expect_usize_expression(language, diagnostics, build_ctx, width_node)?;
+            let content =
+                expect_template_expression(language, diagnostics, build_ctx, content_node)?;
+            let ellipsis = ellipsis_node
+                .map(|node| expect_template_expression(language, diagnostics, build_ctx, node))
+                .transpose()?;
+            let template =
+                new_truncate_template(content, ellipsis, width, text_util::write_truncated_middle);
+            Ok(L::wrap_template(template))
+        },
+    );
     map.insert("label", |language, diagnostics, build_ctx, function| {
         let [label_node, content_node] = function.expect_exact_arguments()?;
         let label_property =
@@ -1594,6 +1729,26 @@ fn builtin_functions<'a, L: TemplateLanguage<'a> + ?Sized>() -> TemplateBuildFun
         let template = ConditionalTemplate::new(condition, true_template, false_template);
         Ok(L::wrap_template(Box::new(template)))
     });
+    map.insert("if_color", |language, diagnostics, build_ctx, function| {
+        let ([true_node], [false_node]) = function.expect_arguments()?;
+        let true_template =
+            expect_template_expression(language, diagnostics, build_ctx, true_node)?;
+        let false_template = false_node
+            .map(|node| expect_template_expression(language, diagnostics, build_ctx, node))
+            .transpose()?;
+        let template = IfColorTemplate {
+            true_template,
+            false_template,
+        };
+        Ok(L::wrap_template(Box::new(template)))
+    });
+    map.insert("hyperlink", |language, diagnostics, build_ctx, function| {
+        let [url_node, content_node] = function.expect_exact_arguments()?;
+        let url = expect_plain_text_expression(language, diagnostics, build_ctx, url_node)?;
+        let content = expect_template_expression(language, diagnostics, build_ctx, content_node)?;
+        let template = HyperlinkTemplate { url, content };
+        Ok(L::wrap_template(Box::new(template)))
+    });
     map.insert("coalesce", |language, diagnostics, build_ctx, function| {
         let contents = function
             .args
@@ -1677,7 +1832,7 @@ where
         let mut fill_char_recorder;
         let recorded_fill_char = if let Some(fill_char) = &fill_char {
             let rewrap = formatter.rewrap_fn();
-            fill_char_recorder = FormatRecorder::new();
+            fill_char_recorder = FormatRecorder::new(formatter.maybe_color());
             fill_char.format(&mut rewrap(&mut fill_char_recorder))?;
             &fill_char_recorder
         } else {
@@ -1706,7 +1861,7 @@ where
         let mut ellipsis_recorder;
         let recorded_ellipsis = if let Some(ellipsis) = &ellipsis {
             let rewrap = formatter.rewrap_fn();
-            ellipsis_recorder = FormatRecorder::new();
+            ellipsis_recorder = FormatRecorder::new(formatter.maybe_color());
             ellipsis.format(&mut rewrap(&mut ellipsis_recorder))?;
             &ellipsis_recorder
         } else {
@@ -2713,6 +2868,41 @@ mod tests {
         insta::assert_snapshot!(env.render_ok(r#""he \n ll \n \" o".escape_json()"#), @r#""he \n ll \n \" o""#);
     }
 
+    #[test]
+    fn test_string_description_method() {
+        let mut env = TestTemplateEnv::new();
+        env.add_keyword("description", || {
+            L::wrap_string(Literal(
+                "subject\n\nbody\n\nSigned-off-by: Foo\nChange-Id: I1234\n".to_owned(),
+            ))
+        });
+
+        insta::assert_snapshot!(env.render_ok(r#""".subject()"#), @"");
+        insta::assert_snapshot!(env.render_ok(r#""foo\nbar\n\nbaz".subject()"#), @"foo bar");
+        insta::assert_snapshot!(env.render_ok(r#""foo\n".body()"#), @"");
+        insta::assert_snapshot!(env.render_ok(r#"description.body()"#), @r"
+        body
+
+        Signed-off-by: Foo
+        Change-Id: I1234
+        ");
+
+        insta::assert_snapshot!(env.render_ok(r#""".trailers()"#), @"");
+        insta::assert_snapshot!(env.render_ok(r#"description.trailers()"#), @r"
+        Signed-off-by: Foo
+        Change-Id: I1234
+        ");
+        insta::assert_snapshot!(env.render_ok(r#"description.trailers().len()"#), @"2");
+        insta::assert_snapshot!(
+            env.render_ok(r#"description.trailers().map(|t| t.key()).join(",")"#), @"Signed-off-by,Change-Id");
+        insta::assert_snapshot!(
+            env.render_ok(
+                r#"description.trailers().filter(|t| t.key() == "Change-Id").map(|t| t.value())"#),
+            @"I1234");
+        insta::assert_snapshot!(
+            env.render_ok(r#"if("subject".trailers(), "true", "false")"#), @"false");
+    }
+
     #[test]
     fn test_config_value_method() {
         let mut env = TestTemplateEnv::new();
@@ -3108,6 +3298,12 @@ mod tests {
         insta::assert_snapshot!(
             env.render_ok(r"truncate_end(2, label('red', 'foobar')) ++ 'baz'"),
             @"[38;5;9mfo[39mbaz");
+        insta::assert_snapshot!(
+            env.render_ok(r"truncate_middle(4, label('red', 'foobar')) ++ 'baz'"),
+            @"[38;5;9mfo[39m[38;5;9mar[39mbaz");
+        insta::assert_snapshot!(
+            env.render_ok(r"truncate_middle(5, 'foobar', ellipsis='…')"),
+            @"fo…ar");
     }
 
     #[test]
@@ -3174,6 +3370,49 @@ mod tests {
             @r"]8;;http://example.com\Example]8;;\");
     }
 
+    #[test]
+    fn test_hyperlink_function() {
+        let mut env = TestTemplateEnv::new();
+        env.add_color("error", crossterm::style::Color::DarkRed);
+
+        insta::assert_snapshot!(
+            env.render_ok(r#"hyperlink("http://example.com", label("error", "Example"))"#),
+            @r"
+        ]8;;http://example.com\[38;5;1mExample[39m]8;;\
+        ");
+        // Control characters are removed from the URL
+        insta::assert_snapshot!(
+            env.render_ok(r#"hyperlink("http://example.com/\e\x07", "Example")"#),
+            @r"
+        ]8;;http://example.com/\Example]8;;\
+        ");
+        // No hyperlink if the URL is empty or the output isn't colorized
+        insta::assert_snapshot!(env.render_ok(r#"hyperlink("", "Example")"#), @"Example");
+        insta::assert_snapshot!(
+            env.render_ok(r#"stringify(hyperlink("http://example.com", "Example"))"#),
+            @"Example");
+        // Hyperlink is preserved when recorded for reformatting
+        insta::assert_snapshot!(
+            env.render_ok(r#"fill(10, hyperlink("http://example.com", "Example"))"#),
+            @r"
+        ]8;;http://example.com\Example]8;;\
+        ");
+    }
+
+    #[test]
+    fn test_if_color_function() {
+        let env = TestTemplateEnv::new();
+
+        insta::assert_snapshot!(env.render_ok(r#"if_color("color", "plain")"#), @"color");
+        insta::assert_snapshot!(
+            env.render_ok(r#"stringify(if_color("color", "plain"))"#),
+            @"plain");
+        insta::assert_snapshot!(env.render_ok(r#"stringify(if_color("color"))"#), @"");
+        insta::assert_snapshot!(
+            env.render_ok(r#"separate(" ", if_color("color"), "text")"#),
+            @"color text");
+    }
+
     #[test]
     fn test_stringify_function() {
         let mut env = TestTemplateEnv::new();
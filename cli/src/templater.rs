@@ -25,6 +25,7 @@ use bstr::BString;
 use jj_lib::backend::Signature;
 use jj_lib::backend::Timestamp;
 use jj_lib::config::ConfigValue;
+use jj_lib::description::Trailer;
 
 use crate::formatter::FormatRecorder;
 use crate::formatter::Formatter;
@@ -188,6 +189,18 @@ impl Template for Vec<String> {
     }
 }
 
+impl Template for Trailer {
+    fn format(&self, formatter: &mut TemplateFormatter) -> io::Result<()> {
+        write!(formatter, "{}: {}", self.key, self.value)
+    }
+}
+
+impl Template for Vec<Trailer> {
+    fn format(&self, formatter: &mut TemplateFormatter) -> io::Result<()> {
+        format_joined(formatter, self, "\n")
+    }
+}
+
 impl Template for bool {
     fn format(&self, formatter: &mut TemplateFormatter) -> io::Result<()> {
         let repr = if *self { "true" } else { "false" };
@@ -290,7 +303,7 @@ where
 {
     fn format(&self, formatter: &mut TemplateFormatter) -> io::Result<()> {
         let rewrap = formatter.rewrap_fn();
-        let mut recorder = FormatRecorder::new();
+        let mut recorder = FormatRecorder::new(formatter.maybe_color());
         self.content.format(&mut rewrap(&mut recorder))?;
         (self.reformat)(formatter, &recorder)
     }
@@ -617,6 +630,56 @@ where
     }
 }
 
+/// Renders `true_template` if the output may be colorized, `false_template`
+/// otherwise.
+pub struct IfColorTemplate<T, U> {
+    pub true_template: T,
+    pub false_template: Option<U>,
+}
+
+impl<T, U> Template for IfColorTemplate<T, U>
+where
+    T: Template,
+    U: Template,
+{
+    fn format(&self, formatter: &mut TemplateFormatter) -> io::Result<()> {
+        if formatter.maybe_color() {
+            self.true_template.format(formatter)?;
+        } else if let Some(false_template) = &self.false_template {
+            false_template.format(formatter)?;
+        }
+        Ok(())
+    }
+}
+
+/// Renders `content` as a terminal hyperlink to `url` if the output may be
+/// colorized and the `url` isn't empty. Otherwise, renders `content` as is.
+pub struct HyperlinkTemplate<P, T> {
+    pub url: P,
+    pub content: T,
+}
+
+impl<P, T> Template for HyperlinkTemplate<P, T>
+where
+    P: TemplateProperty<Output = String>,
+    T: Template,
+{
+    fn format(&self, formatter: &mut TemplateFormatter) -> io::Result<()> {
+        let url = match self.url.extract() {
+            Ok(url) => url,
+            Err(err) => return formatter.handle_error(err),
+        };
+        if url.is_empty() || !formatter.maybe_color() {
+            return self.content.format(formatter);
+        }
+        // Control characters would terminate the OSC 8 escape sequence early.
+        let url: String = url.chars().filter(|c| !c.is_control()).collect();
+        write!(formatter.raw()?, "\x1b]8;;{url}\x1b\\")?;
+        self.content.format(formatter)?;
+        write!(formatter.raw()?, "\x1b]8;;\x1b\\")
+    }
+}
+
 /// Adapter to apply fallible `function` to the `property`.
 ///
 /// This is usually created by `TemplatePropertyExt::and_then()`/`map()`.
@@ -756,6 +819,10 @@ impl<'a> TemplateFormatter<'a> {
         self.formatter.raw()
     }
 
+    pub fn maybe_color(&self) -> bool {
+        self.formatter.maybe_color()
+    }
+
     pub fn labeled<S: AsRef<str>>(
         &mut self,
         label: S,
@@ -884,8 +951,9 @@ fn record_non_empty_fn<T: Template + ?Sized>(
     // 1.85.0.
 ) -> impl Fn(&T) -> Option<io::Result<FormatRecorder>> + use<T> {
     let rewrap = formatter.rewrap_fn();
+    let maybe_color = formatter.maybe_color();
     move |template| {
-        let mut recorder = FormatRecorder::new();
+        let mut recorder = FormatRecorder::new(maybe_color);
         match template.format(&mut rewrap(&mut recorder)) {
             Ok(()) if recorder.data().is_empty() => None, // omit empty content
             Ok(()) => Some(Ok(recorder)),
@@ -307,6 +307,50 @@ pub fn write_truncated_end(
     Ok(truncated_width)
 }
 
+/// Writes text truncated to `max_width` by removing characters in the middle.
+/// Returns width of the truncated text, which may be shorter than `max_width`.
+///
+/// The input `recorded_content` should be a single-line text. If the text is
+/// truncated, the leading part gets the extra column if any.
+pub fn write_truncated_middle(
+    formatter: &mut dyn Formatter,
+    recorded_content: &FormatRecorder,
+    recorded_ellipsis: &FormatRecorder,
+    max_width: usize,
+) -> io::Result<usize> {
+    let data = recorded_content.data();
+    let data_width = String::from_utf8_lossy(data).width();
+    if data_width <= max_width {
+        recorded_content.replay(formatter)?;
+        return Ok(data_width);
+    }
+    let ellipsis_data = recorded_ellipsis.data();
+    let ellipsis_width = String::from_utf8_lossy(ellipsis_data).width();
+
+    let max_text_width = max_width.saturating_sub(ellipsis_width);
+    let (head_end, head_width) = truncate_end_pos_bytes(data, max_text_width - max_text_width / 2);
+    let (tail_start, tail_width) = truncate_start_pos_bytes(data, max_text_width / 2);
+    let tail_start = tail_start + count_start_zero_width_chars_bytes(&data[tail_start..]);
+    // The ellipsis itself may be larger than max_width, so maybe truncate it too.
+    let (ellipsis_end, ellipsis_width) = truncate_end_pos_bytes(ellipsis_data, max_width);
+
+    let mut replay_truncated = |recorded: &FormatRecorder, start: usize, end: usize| {
+        recorded.replay_with(formatter, |formatter, range| {
+            let start = cmp::max(range.start, start);
+            let end = cmp::min(range.end, end);
+            if start < end {
+                formatter.write_all(&recorded.data()[start..end])?;
+            }
+            Ok(())
+        })
+    };
+
+    replay_truncated(recorded_content, 0, head_end)?;
+    replay_truncated(recorded_ellipsis, 0, ellipsis_end)?;
+    replay_truncated(recorded_content, tail_start, data.len())?;
+    Ok(head_width + ellipsis_width + tail_width)
+}
+
 /// Writes text padded to `min_width` by adding leading fill characters.
 ///
 /// The input `recorded_content` should be a single-line text. The
@@ -734,8 +778,8 @@ mod tests {
 
     #[test]
     fn test_write_truncated_labeled() {
-        let ellipsis_recorder = FormatRecorder::new();
-        let mut recorder = FormatRecorder::new();
+        let ellipsis_recorder = FormatRecorder::new(false);
+        let mut recorder = FormatRecorder::new(false);
         for (label, word) in [("red", "foo"), ("cyan", "bar")] {
             recorder.push_label(label).unwrap();
             write!(recorder, "{word}").unwrap();
@@ -809,8 +853,8 @@ mod tests {
 
     #[test]
     fn test_write_truncated_non_ascii_chars() {
-        let ellipsis_recorder = FormatRecorder::new();
-        let mut recorder = FormatRecorder::new();
+        let ellipsis_recorder = FormatRecorder::new(false);
+        let mut recorder = FormatRecorder::new(false);
         write!(recorder, "a\u{300}bc\u{300}一二三").unwrap();
 
         // Truncate start
@@ -892,8 +936,8 @@ mod tests {
 
     #[test]
     fn test_write_truncated_empty_content() {
-        let ellipsis_recorder = FormatRecorder::new();
-        let recorder = FormatRecorder::new();
+        let ellipsis_recorder = FormatRecorder::new(false);
+        let recorder = FormatRecorder::new(false);
 
         // Truncate start
         insta::assert_snapshot!(
@@ -924,10 +968,66 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_write_truncated_middle() {
+        let ellipsis_recorder = FormatRecorder::with_data("..");
+        let mut recorder = FormatRecorder::new(false);
+        for (label, word) in [("red", "foo"), ("cyan", "bar")] {
+            recorder.push_label(label).unwrap();
+            write!(recorder, "{word}").unwrap();
+            recorder.pop_label().unwrap();
+        }
+        insta::assert_snapshot!(
+            format_colored(|formatter| {
+                write_truncated_middle(formatter, &recorder, &ellipsis_recorder, 6).map(|_| ())
+            }),
+            @"[38;5;1mfoo[39m[38;5;6mbar[39m"
+        );
+        insta::assert_snapshot!(
+            format_colored(|formatter| {
+                write_truncated_middle(formatter, &recorder, &ellipsis_recorder, 5).map(|_| ())
+            }),
+            @"[38;5;1mfo[39m..[38;5;6mr[39m"
+        );
+        insta::assert_snapshot!(
+            format_colored(|formatter| {
+                write_truncated_middle(formatter, &recorder, &ellipsis_recorder, 4).map(|_| ())
+            }),
+            @"[38;5;1mf[39m..[38;5;6mr[39m"
+        );
+        insta::assert_snapshot!(
+            format_colored(|formatter| {
+                write_truncated_middle(formatter, &recorder, &ellipsis_recorder, 1).map(|_| ())
+            }),
+            @"."
+        );
+
+        let ellipsis_recorder = FormatRecorder::with_data("…");
+        let mut recorder = FormatRecorder::new(false);
+        write!(recorder, "a\u{300}bc\u{300}一二三").unwrap();
+        let truncated = |max_width| {
+            let mut output = Vec::new();
+            let width = write_truncated_middle(
+                &mut PlainTextFormatter::new(&mut output),
+                &recorder,
+                &ellipsis_recorder,
+                max_width,
+            )
+            .unwrap();
+            (String::from_utf8(output).unwrap(), width)
+        };
+        assert_eq!(truncated(9), ("a\u{300}bc\u{300}一二三".to_owned(), 9));
+        assert_eq!(truncated(8), ("a\u{300}bc\u{300}…三".to_owned(), 6));
+        assert_eq!(truncated(6), ("a\u{300}bc\u{300}…三".to_owned(), 6));
+        assert_eq!(truncated(5), ("a\u{300}b…三".to_owned(), 5));
+        assert_eq!(truncated(3), ("a\u{300}…".to_owned(), 2));
+        assert_eq!(truncated(0), ("".to_owned(), 0));
+    }
+
     #[test]
     fn test_write_truncated_ellipsis_labeled() {
         let ellipsis_recorder = FormatRecorder::with_data("..");
-        let mut recorder = FormatRecorder::new();
+        let mut recorder = FormatRecorder::new(false);
         for (label, word) in [("red", "foo"), ("cyan", "bar")] {
             recorder.push_label(label).unwrap();
             write!(recorder, "{word}").unwrap();
@@ -1014,7 +1114,7 @@ mod tests {
     #[test]
     fn test_write_truncated_ellipsis_non_ascii_chars() {
         let ellipsis_recorder = FormatRecorder::with_data("..");
-        let mut recorder = FormatRecorder::new();
+        let mut recorder = FormatRecorder::new(false);
         write!(recorder, "a\u{300}bc\u{300}一二三").unwrap();
 
         // Truncate start
@@ -1079,7 +1179,7 @@ mod tests {
     #[test]
     fn test_write_truncated_ellipsis_empty_content() {
         let ellipsis_recorder = FormatRecorder::with_data("..");
-        let recorder = FormatRecorder::new();
+        let recorder = FormatRecorder::new(false);
 
         // Truncate start, empty content
         insta::assert_snapshot!(
@@ -1112,7 +1212,7 @@ mod tests {
 
     #[test]
     fn test_write_padded_labeled_content() {
-        let mut recorder = FormatRecorder::new();
+        let mut recorder = FormatRecorder::new(false);
         for (label, word) in [("red", "foo"), ("cyan", "bar")] {
             recorder.push_label(label).unwrap();
             write!(recorder, "{word}").unwrap();
@@ -1170,7 +1270,7 @@ mod tests {
     #[test]
     fn test_write_padded_labeled_fill_char() {
         let recorder = FormatRecorder::with_data("foo");
-        let mut fill = FormatRecorder::new();
+        let mut fill = FormatRecorder::new(false);
         fill.push_label("red").unwrap();
         write!(fill, "=").unwrap();
         fill.pop_label().unwrap();
@@ -1236,7 +1336,7 @@ mod tests {
 
     #[test]
     fn test_write_padded_empty_content() {
-        let recorder = FormatRecorder::new();
+        let recorder = FormatRecorder::new(false);
         let fill = FormatRecorder::with_data("=");
 
         // Pad start
@@ -1404,7 +1504,7 @@ mod tests {
     #[test]
     fn test_write_wrapped() {
         // Split single label chunk
-        let mut recorder = FormatRecorder::new();
+        let mut recorder = FormatRecorder::new(false);
         recorder.push_label("red").unwrap();
         write!(recorder, "foo bar baz\nqux quux\n").unwrap();
         recorder.pop_label().unwrap();
@@ -1419,7 +1519,7 @@ mod tests {
         );
 
         // Multiple label chunks in a line
-        let mut recorder = FormatRecorder::new();
+        let mut recorder = FormatRecorder::new(false);
         for (i, word) in ["foo ", "bar ", "baz\n", "qux ", "quux"].iter().enumerate() {
             recorder.push_label(["red", "cyan"][i & 1]).unwrap();
             write!(recorder, "{word}").unwrap();
@@ -1436,7 +1536,7 @@ mod tests {
         );
 
         // Empty lines should not cause panic
-        let mut recorder = FormatRecorder::new();
+        let mut recorder = FormatRecorder::new(false);
         for (i, word) in ["", "foo", "", "bar baz", ""].iter().enumerate() {
             recorder.push_label(["red", "cyan"][i & 1]).unwrap();
             writeln!(recorder, "{word}").unwrap();
@@ -1454,7 +1554,7 @@ mod tests {
         );
 
         // Split at label boundary
-        let mut recorder = FormatRecorder::new();
+        let mut recorder = FormatRecorder::new(false);
         recorder.push_label("red").unwrap();
         write!(recorder, "foo bar").unwrap();
         recorder.pop_label().unwrap();
@@ -1471,7 +1571,7 @@ mod tests {
         );
 
         // Do not split at label boundary "ba|z" (since it's a single word)
-        let mut recorder = FormatRecorder::new();
+        let mut recorder = FormatRecorder::new(false);
         recorder.push_label("red").unwrap();
         write!(recorder, "foo bar ba").unwrap();
         recorder.pop_label().unwrap();
@@ -1489,7 +1589,7 @@ mod tests {
 
     #[test]
     fn test_write_wrapped_leading_labeled_whitespace() {
-        let mut recorder = FormatRecorder::new();
+        let mut recorder = FormatRecorder::new(false);
         recorder.push_label("red").unwrap();
         write!(recorder, " ").unwrap();
         recorder.pop_label().unwrap();
@@ -1504,7 +1604,7 @@ mod tests {
     fn test_write_wrapped_trailing_labeled_whitespace() {
         // data: "foo" " "
         // line:  ---
-        let mut recorder = FormatRecorder::new();
+        let mut recorder = FormatRecorder::new(false);
         write!(recorder, "foo").unwrap();
         recorder.push_label("red").unwrap();
         write!(recorder, " ").unwrap();
@@ -1516,7 +1616,7 @@ mod tests {
 
         // data: "foo" "\n"
         // line:  ---     -
-        let mut recorder = FormatRecorder::new();
+        let mut recorder = FormatRecorder::new(false);
         write!(recorder, "foo").unwrap();
         recorder.push_label("red").unwrap();
         writeln!(recorder).unwrap();
@@ -1528,7 +1628,7 @@ mod tests {
 
         // data: "foo\n" " "
         // line:  ---    -
-        let mut recorder = FormatRecorder::new();
+        let mut recorder = FormatRecorder::new(false);
         writeln!(recorder, "foo").unwrap();
         recorder.push_label("red").unwrap();
         write!(recorder, " ").unwrap();
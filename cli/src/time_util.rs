@@ -1,3 +1,5 @@
+use std::time::Duration;
+
 use chrono::format::StrftimeItems;
 use chrono::DateTime;
 use chrono::FixedOffset;
@@ -95,3 +97,24 @@ pub fn format_duration(
         .map_err(|_: chrono::OutOfRangeError| TimestampOutOfRange)?;
     Ok(format.convert(duration))
 }
+
+#[derive(Debug, Error)]
+#[error("Invalid duration {0:?}: expected a number followed by s, m, h, or d")]
+pub struct DurationParseError(String);
+
+/// Parses a duration such as `30s`, `10m`, `2h`, or `1d`.
+pub fn parse_duration(text: &str) -> Result<Duration, DurationParseError> {
+    let make_error = || DurationParseError(text.to_owned());
+    let split_at = text.len().saturating_sub(1);
+    let (count, unit) = text.split_at_checked(split_at).ok_or_else(make_error)?;
+    let count: u64 = count.parse().map_err(|_| make_error())?;
+    let unit_secs = match unit {
+        "s" => 1,
+        "m" => 60,
+        "h" => 60 * 60,
+        "d" => 24 * 60 * 60,
+        _ => return Err(make_error()),
+    };
+    let secs = count.checked_mul(unit_secs).ok_or_else(make_error)?;
+    Ok(Duration::from_secs(secs))
+}
@@ -52,10 +52,13 @@ mod test_help_command;
 mod test_immutable_commits;
 mod test_interdiff_command;
 mod test_log_command;
+mod test_meta_command;
 mod test_new_command;
 mod test_next_prev_commands;
 mod test_operations;
 mod test_parallelize_command;
+mod test_patch_command;
+mod test_pick_command;
 mod test_rebase_command;
 mod test_repo_change_report;
 mod test_resolve_command;
@@ -63,6 +66,7 @@ mod test_restore_command;
 mod test_revset_output;
 mod test_root;
 mod test_shell_completion;
+mod test_shelve_command;
 mod test_show_command;
 mod test_sign_unsign_commands;
 mod test_simplify_parents_command;
@@ -72,6 +76,7 @@ mod test_squash_command;
 mod test_status_command;
 mod test_tag_command;
 mod test_templater;
+mod test_topic_command;
 mod test_undo;
 mod test_util_command;
 mod test_working_copy;
@@ -158,6 +158,68 @@ fn test_absorb_simple() {
     ");
 }
 
+#[test]
+fn test_absorb_drop_empty_config() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let repo_path = test_env.env_root().join("repo");
+
+    test_env
+        .run_jj_in(&repo_path, ["describe", "-m1"])
+        .success();
+    std::fs::write(repo_path.join("file1"), "1a\n").unwrap();
+    test_env.run_jj_in(&repo_path, ["new", "-m2"]).success();
+    std::fs::write(repo_path.join("file1"), "1A\n").unwrap();
+    test_env
+        .run_jj_in(&repo_path, ["debug", "snapshot"])
+        .success();
+    let setup_opid = test_env.current_operation_id(&repo_path);
+
+    // The source commit has a description, so it's kept by default
+    let output = test_env.run_jj_in(&repo_path, ["absorb"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Absorbed changes into these revisions:
+      qpvuntsm 13d71d45 1
+    Rebased 1 descendant commits.
+    Working copy now at: kkmpptxz 89a440cb (empty) 2
+    Parent commit      : qpvuntsm 13d71d45 1
+    [EOF]
+    ");
+
+    // With "always", the source commit is abandoned once it becomes empty
+    test_env
+        .run_jj_in(&repo_path, ["op", "restore", &setup_opid])
+        .success();
+    let output = test_env.run_jj_in(&repo_path, ["absorb", "--config=rewrite.drop-empty=always"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Absorbed changes into these revisions:
+      qpvuntsm 7fc40b19 1
+    Working copy now at: vruxwmqv 77d73f6e (empty) (no description set)
+    Parent commit      : qpvuntsm 7fc40b19 1
+    [EOF]
+    ");
+
+    // With "never", an emptied source commit is kept even if it's discardable
+    test_env
+        .run_jj_in(&repo_path, ["op", "restore", &setup_opid])
+        .success();
+    test_env
+        .run_jj_in(&repo_path, ["describe", "-m="])
+        .success();
+    let output = test_env.run_jj_in(&repo_path, ["absorb", "--config=rewrite.drop-empty=never"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Absorbed changes into these revisions:
+      qpvuntsm 88282461 1
+    Rebased 1 descendant commits.
+    Working copy now at: kkmpptxz e4f7d208 (empty) (no description set)
+    Parent commit      : qpvuntsm 88282461 1
+    [EOF]
+    ");
+}
+
 #[test]
 fn test_absorb_replace_single_line_hunk() {
     let test_env = TestEnvironment::default();
@@ -1819,6 +1819,36 @@ fn test_bookmark_list() {
     Hint: Bookmarks marked as deleted will be *deleted permanently* on the remote on the next `jj git push`. Use `jj bookmark forget` to prevent this.
     [EOF]
     ");
+
+    // Sync state of local refs relative to the tracked remote refs
+    let template = r#"
+    concat(
+      "[" ++ name ++ if(remote, "@" ++ remote) ++ "] ",
+      separate(" ",
+        tracked_remote_refs.map(|r| "@" ++ r.remote()),
+        "↑" ++ ahead_count.lower(),
+        "↓" ++ behind_count.lower(),
+      ) ++ "\n",
+    )
+    "#;
+    let output = test_env.run_jj_in(
+        &local_path,
+        ["bookmark", "list", "--all-remotes", "-T", template],
+    );
+    insta::assert_snapshot!(output, @r"
+    [local-only] ↑<Error: Not tracking any remote refs> ↓<Error: Not tracking any remote refs>
+    [remote-delete] @origin ↑0 ↓2
+    [remote-delete@origin] <Error: Not a local ref> ↑<Error: Not a local ref> ↓<Error: Not a local ref>
+    [remote-sync] @origin ↑0 ↓0
+    [remote-sync@origin] <Error: Not a local ref> ↑<Error: Not a local ref> ↓<Error: Not a local ref>
+    [remote-unsync] @origin ↑1 ↓1
+    [remote-unsync@origin] <Error: Not a local ref> ↑<Error: Not a local ref> ↓<Error: Not a local ref>
+    [remote-untrack@origin] <Error: Not a local ref> ↑<Error: Not a local ref> ↓<Error: Not a local ref>
+    [EOF]
+    ------- stderr -------
+    Hint: Bookmarks marked as deleted will be *deleted permanently* on the remote on the next `jj git push`. Use `jj bookmark forget` to prevent this.
+    [EOF]
+    ");
 }
 
 #[test]
@@ -2397,6 +2427,240 @@ fn test_bookmark_list_conflicted() {
     ");
 }
 
+#[test]
+fn test_bookmark_list_json() {
+    let test_env = TestEnvironment::default();
+    test_env.add_config("git.auto-local-bookmark = true");
+
+    // Initialize remote refs
+    test_env.run_jj_in(".", ["git", "init", "remote"]).success();
+    let remote_path = test_env.env_root().join("remote");
+    for bookmark in ["remote-sync", "remote-unsync", "remote-untrack"] {
+        test_env
+            .run_jj_in(&remote_path, ["new", "root()", "-m", bookmark])
+            .success();
+        test_env
+            .run_jj_in(&remote_path, ["bookmark", "create", "-r@", bookmark])
+            .success();
+    }
+    test_env.run_jj_in(&remote_path, ["new"]).success();
+    test_env
+        .run_jj_in(&remote_path, ["git", "export"])
+        .success();
+
+    // Initialize local refs
+    let mut remote_git_path = remote_path;
+    remote_git_path.extend([".jj", "repo", "store", "git"]);
+    test_env
+        .run_jj_in(
+            ".",
+            ["git", "clone", remote_git_path.to_str().unwrap(), "local"],
+        )
+        .success();
+    let local_path = test_env.env_root().join("local");
+    test_env
+        .run_jj_in(&local_path, ["new", "remote-unsync", "-m", "local"])
+        .success();
+    test_env
+        .run_jj_in(&local_path, ["bookmark", "set", "--to=@", "remote-unsync"])
+        .success();
+    test_env
+        .run_jj_in(&local_path, ["bookmark", "delete", "remote-untrack"])
+        .success();
+    test_env
+        .run_jj_in(
+            &local_path,
+            ["bookmark", "untrack", "remote-untrack@origin"],
+        )
+        .success();
+
+    // The remote bookmarks listed by default are nested in the local bookmark
+    let output = test_env.run_jj_in(&local_path, ["bookmark", "list", "--json"]);
+    insta::assert_snapshot!(output, @r#"
+    {"added_targets":["c761c7ea2e2235c50fc0da3cfc2e82b05d1086c0"],"conflict":false,"name":"remote-sync","present":true,"remote":null,"removed_targets":[],"tracked":[]}
+    {"added_targets":["3290862b91571adb47c478fff32f8bb181e7e46f"],"conflict":false,"name":"remote-unsync","present":true,"remote":null,"removed_targets":[],"tracked":[{"added_targets":["38ef8af7134173e4682b5aea2822b14a98549e59"],"conflict":false,"name":"remote-unsync","present":true,"remote":"origin","removed_targets":[],"tracking_ahead_count":0,"tracking_behind_count":1}]}
+    [EOF]
+    "#);
+
+    let output = test_env.run_jj_in(
+        &local_path,
+        [
+            "bookmark",
+            "list",
+            "--json",
+            "--all-remotes",
+            "glob:remote-*sync",
+        ],
+    );
+    insta::assert_snapshot!(output, @r#"
+    {"added_targets":["c761c7ea2e2235c50fc0da3cfc2e82b05d1086c0"],"conflict":false,"name":"remote-sync","present":true,"remote":null,"removed_targets":[],"tracked":[{"added_targets":["c761c7ea2e2235c50fc0da3cfc2e82b05d1086c0"],"conflict":false,"name":"remote-sync","present":true,"remote":"origin","removed_targets":[],"tracking_ahead_count":0,"tracking_behind_count":0}]}
+    {"added_targets":["3290862b91571adb47c478fff32f8bb181e7e46f"],"conflict":false,"name":"remote-unsync","present":true,"remote":null,"removed_targets":[],"tracked":[{"added_targets":["38ef8af7134173e4682b5aea2822b14a98549e59"],"conflict":false,"name":"remote-unsync","present":true,"remote":"origin","removed_targets":[],"tracking_ahead_count":0,"tracking_behind_count":1}]}
+    [EOF]
+    "#);
+
+    // Untracked remote bookmarks are listed separately
+    let output = test_env.run_jj_in(
+        &local_path,
+        [
+            "bookmark",
+            "list",
+            "--json",
+            "--all-remotes",
+            "remote-untrack",
+        ],
+    );
+    insta::assert_snapshot!(output, @r#"
+    {"added_targets":["71a16b0505cdf2d3c3b91975a7c739be1d761db2"],"conflict":false,"name":"remote-untrack","present":true,"remote":"origin","removed_targets":[],"tracked":[]}
+    [EOF]
+    "#);
+
+    let output = test_env.run_jj_in(&local_path, ["bookmark", "list", "--json", "--tree"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    error: the argument '--json' cannot be used with '--tree'
+
+    Usage: jj bookmark list --json [NAMES]...
+
+    For more information, try '--help'.
+    [EOF]
+    [exit status: 2]
+    ");
+}
+
+#[test]
+fn test_bookmark_list_tree() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let repo_path = test_env.env_root().join("repo");
+
+    test_env
+        .run_jj_in(&repo_path, ["describe", "-m=a"])
+        .success();
+    for name in [
+        "feature",
+        "feature-x",
+        "feature/foo",
+        "feature/sub/bar",
+        "feature/sub/baz",
+        "fix/qux",
+        "main",
+    ] {
+        test_env
+            .run_jj_in(&repo_path, ["bookmark", "create", "-r@", name])
+            .success();
+    }
+    let output = test_env.run_jj_in(&repo_path, ["bookmark", "list", "--tree"]);
+    insta::assert_snapshot!(output, @r"
+    feature: qpvuntsm d8d5f980 (empty) a
+    feature-x: qpvuntsm d8d5f980 (empty) a
+    feature/
+      feature/foo: qpvuntsm d8d5f980 (empty) a
+      sub/
+        feature/sub/bar: qpvuntsm d8d5f980 (empty) a
+        feature/sub/baz: qpvuntsm d8d5f980 (empty) a
+    fix/
+      fix/qux: qpvuntsm d8d5f980 (empty) a
+    main: qpvuntsm d8d5f980 (empty) a
+    [EOF]
+    ");
+    let output = test_env.run_jj_in(
+        &repo_path,
+        ["bookmark", "list", "--tree", "-T", r#"name ++ "\n""#],
+    );
+    insta::assert_snapshot!(output, @r"
+    feature
+    feature-x
+    feature/
+      feature/foo
+      sub/
+        feature/sub/bar
+        feature/sub/baz
+    fix/
+      fix/qux
+    main
+    [EOF]
+    ");
+}
+
+#[test]
+fn test_bookmark_list_verbose() {
+    let test_env = TestEnvironment::default();
+    test_env.add_config(r#"template-aliases.'format_bookmark_timestamp(timestamp)' = 'timestamp'"#);
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let repo_path = test_env.env_root().join("repo");
+
+    test_env
+        .run_jj_in(&repo_path, ["describe", "-m=a"])
+        .success();
+    test_env
+        .run_jj_in(&repo_path, ["bookmark", "create", "-r@", "foo", "bar"])
+        .success();
+    test_env.run_jj_in(&repo_path, ["new", "-m=b"]).success();
+    test_env
+        .run_jj_in(&repo_path, ["bookmark", "set", "-r@", "bar"])
+        .success();
+
+    let output = test_env.run_jj_in(&repo_path, ["bookmark", "list", "--verbose"]);
+    insta::assert_snapshot!(output, @r"
+    bar: zsuskuln 22a82dfa (empty) b
+      Created 2001-02-03 04:05:09.000 +07:00
+      Moved 2001-02-03 04:05:11.000 +07:00 by test-username@host.example.com
+    foo: qpvuntsm d8d5f980 (empty) a
+      Created 2001-02-03 04:05:09.000 +07:00
+      Moved 2001-02-03 04:05:09.000 +07:00 by test-username@host.example.com
+    [EOF]
+    ");
+
+    let output = test_env.run_jj_in(
+        &repo_path,
+        [
+            "bookmark",
+            "list",
+            "-T",
+            r#"separate(" ", name, created, moved, moved_by) ++ "\n""#,
+        ],
+    );
+    insta::assert_snapshot!(output, @r"
+    bar 2001-02-03 04:05:09.000 +07:00 2001-02-03 04:05:11.000 +07:00 test-username@host.example.com
+    foo 2001-02-03 04:05:09.000 +07:00 2001-02-03 04:05:09.000 +07:00 test-username@host.example.com
+    [EOF]
+    ");
+
+    // Select bookmarks by the time they were last moved
+    let output = test_env.run_jj_in(
+        &repo_path,
+        [
+            "log",
+            "-r",
+            "bookmarks(moved=after:'2001-02-03 04:05:11+07:00')",
+            "-T",
+            r#"description ++ "\n""#,
+        ],
+    );
+    insta::assert_snapshot!(output, @r"
+    @  b
+    │
+    ~
+    [EOF]
+    ");
+    let output = test_env.run_jj_in(
+        &repo_path,
+        [
+            "log",
+            "-r",
+            "bookmarks(moved=before:'2001-02-03 04:05:11+07:00')",
+            "-T",
+            r#"description ++ "\n""#,
+        ],
+    );
+    insta::assert_snapshot!(output, @r"
+    ○  a
+    │
+    ~
+    [EOF]
+    ");
+}
+
 #[test]
 fn test_bookmark_create_with_default_target_revision() {
     let test_env = TestEnvironment::default();
@@ -180,3 +180,161 @@ fn test_builtin_user_redefines_builtin_immutable_heads() {
     [EOF]
     ");
 }
+
+#[test]
+fn test_builtin_alias_active() {
+    let (test_env, workspace_root) = set_up("main");
+    test_env
+        .run_jj_in(&workspace_root, ["new", "main", "-m=mine"])
+        .success();
+    test_env
+        .run_jj_in(
+            &workspace_root,
+            [
+                "new",
+                "main",
+                "-m=someone else's",
+                "--config=user.email=someone@example.com",
+            ],
+        )
+        .success();
+
+    // Immutable commits and commits by other users are excluded
+    let output = test_env.run_jj_in(
+        &workspace_root,
+        ["log", "-r", "active()", "-T", "description"],
+    );
+    insta::assert_snapshot!(output, @r"
+    ○  mine
+    │
+    ~
+
+    ○  description 2
+    │
+    ~
+    [EOF]
+    ");
+    let output = test_env.run_jj_in(
+        &workspace_root,
+        [
+            "log",
+            "-r",
+            r#"active(author(exact:"someone@example.com"))"#,
+            "-T",
+            "description",
+        ],
+    );
+    insta::assert_snapshot!(output, @r"
+    @  someone else's
+    │
+    ~
+    [EOF]
+    ");
+    let output = test_env.run_jj_in(
+        &workspace_root,
+        [
+            "log",
+            "-r",
+            r#"active(all(), before:"2001-01-01")"#,
+            "-T",
+            "description",
+        ],
+    );
+    insta::assert_snapshot!(output, @"");
+
+    // The default window can be overridden
+    test_env.add_config(r#"revset-aliases."active_window()" = '(before:"2001-01-01")'"#);
+    let output = test_env.run_jj_in(
+        &workspace_root,
+        ["log", "-r", "active()", "-T", "description"],
+    );
+    insta::assert_snapshot!(output, @"");
+}
+
+#[test]
+fn test_builtin_alias_stack_open_wip() {
+    let (test_env, workspace_root) = set_up("main");
+    let template = r#"separate(" ", description.first_line(), author.email()) ++ "\n""#;
+    test_env
+        .run_jj_in(
+            &workspace_root,
+            [
+                "new",
+                "main",
+                "-m=someone else's",
+                "--config=user.email=someone@example.com",
+            ],
+        )
+        .success();
+    test_env
+        .run_jj_in(&workspace_root, ["new", "main", "-m=wipe out"])
+        .success();
+    std::fs::write(workspace_root.join("file1"), "1").unwrap();
+    test_env
+        .run_jj_in(&workspace_root, ["new", "main", "-m=first"])
+        .success();
+    std::fs::write(workspace_root.join("file2"), "2").unwrap();
+    test_env
+        .run_jj_in(&workspace_root, ["new", "-m=WIP: second"])
+        .success();
+    std::fs::write(workspace_root.join("file3"), "3").unwrap();
+    test_env.run_jj_in(&workspace_root, ["new"]).success();
+    std::fs::write(workspace_root.join("file4"), "4").unwrap();
+    test_env.run_jj_in(&workspace_root, ["new"]).success();
+
+    // The stack excludes the immutable "main" and the other stacks
+    let output = test_env.run_jj_in(&workspace_root, ["log", "-r", "stack()", "-T", template]);
+    insta::assert_snapshot!(output, @r"
+    @  test.user@example.com
+    ○  test.user@example.com
+    ○  WIP: second test.user@example.com
+    ○  first test.user@example.com
+    │
+    ~
+    [EOF]
+    ");
+    let output = test_env.run_jj_in(
+        &workspace_root,
+        ["log", "-r", "stack(description(first))", "-T", template],
+    );
+    insta::assert_snapshot!(output, @r"
+    @  test.user@example.com
+    ○  test.user@example.com
+    ○  WIP: second test.user@example.com
+    ○  first test.user@example.com
+    │
+    ~
+    [EOF]
+    ");
+
+    // Stacks by other users are excluded
+    let output = test_env.run_jj_in(&workspace_root, ["log", "-r", "open()", "-T", template]);
+    insta::assert_snapshot!(output, @r"
+    @  test.user@example.com
+    ○  test.user@example.com
+    ○  WIP: second test.user@example.com
+    ○  first test.user@example.com
+    │
+    ~
+
+    ○  wipe out test.user@example.com
+    │
+    ~
+
+    ○  description 2 test.user@example.com
+    │
+    ~
+    [EOF]
+    ");
+
+    // Undescribed commits without changes and subjects which only start with
+    // "wip" as part of a word are excluded
+    let output = test_env.run_jj_in(&workspace_root, ["log", "-r", "wip()", "-T", template]);
+    insta::assert_snapshot!(output, @r"
+    ○  test.user@example.com
+    ○  WIP: second test.user@example.com
+    │
+    ~
+    [EOF]
+    ");
+}
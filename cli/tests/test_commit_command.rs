@@ -36,6 +36,39 @@ fn test_commit_with_description_from_cli() {
     ");
 }
 
+#[test]
+fn test_commit_checks() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    test_env.add_config(
+        r#"
+        commit.checks.level = "error"
+        commit.checks.max-subject-length = 5
+        "#,
+    );
+    let workspace_path = test_env.env_root().join("repo");
+
+    let output = test_env.run_jj_in(&workspace_path, ["commit", "-m=too long"]);
+    insta::assert_snapshot!(output, @r#"
+    ------- stderr -------
+    Error: The description "too long" does not pass the commit checks:
+      The subject line is longer than 5 characters
+    Hint: Use --no-verify to skip the checks.
+    [EOF]
+    [exit status: 1]
+    "#);
+
+    test_env
+        .run_jj_in(&workspace_path, ["commit", "-m=too long", "--no-verify"])
+        .success();
+    insta::assert_snapshot!(get_log_output(&test_env, &workspace_path), @r"
+    @  05dc29c129de
+    ○  f7a6aba965a3 too long
+    ◆  000000000000
+    [EOF]
+    ");
+}
+
 #[test]
 fn test_commit_with_editor() {
     let mut test_env = TestEnvironment::default();
@@ -242,6 +275,84 @@ fn test_commit_interactive_with_paths() {
     ");
 }
 
+#[test]
+fn test_commit_interactive_with_builtin_text_editor() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let workspace_path = test_env.env_root().join("repo");
+    test_env.add_config(r#"ui.diff-editor = ":builtin-text""#);
+
+    std::fs::write(workspace_path.join("file1"), "a\nb\nc\nd\ne\nf\ng\n").unwrap();
+    std::fs::write(workspace_path.join("file2"), "foo\n").unwrap();
+    test_env.run_jj_in(&workspace_path, ["new"]).success();
+    std::fs::write(workspace_path.join("file1"), "A\nb\nc\nd\ne\nf\nG\n").unwrap();
+    std::fs::write(workspace_path.join("file2"), "bar\n").unwrap();
+
+    // Select the first hunk of file1 and skip the rest
+    let output = test_env.run_jj_with(|cmd| {
+        cmd.current_dir(&workspace_path)
+            .args(["commit", "-i", "-m", "first"])
+            .write_stdin("y\nn\n")
+    });
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    @@ file1 (1/2) @@
+    -a
+    +A
+     b
+     c
+     d
+    Select this hunk? [y,n,a,d,q,?] @@ file1 (2/2) @@
+     d
+     e
+     f
+    -g
+    +G
+    Select this hunk? [y,n,a,d,q,?] @@ file2 (1/1) @@
+    -foo
+    +bar
+    Select this hunk? [y,n,a,d,q,?] 
+    Working copy now at: kkmpptxz 7ae70a3d (no description set)
+    Parent commit      : rlvkpnrz 9efbd1fc first
+    [EOF]
+    ");
+    let output = test_env.run_jj_in(&workspace_path, ["diff", "--git", "-r", "@-"]);
+    insta::assert_snapshot!(output, @r"
+    diff --git a/file1 b/file1
+    index f9d9a0195c..665c1cc7bb 100644
+    --- a/file1
+    +++ b/file1
+    @@ -1,4 +1,4 @@
+    -a
+    +A
+     b
+     c
+     d
+    [EOF]
+    ");
+    let output = test_env.run_jj_in(&workspace_path, ["diff", "--git"]);
+    insta::assert_snapshot!(output, @r"
+    diff --git a/file1 b/file1
+    index 665c1cc7bb..1d6182b46c 100644
+    --- a/file1
+    +++ b/file1
+    @@ -4,4 +4,4 @@
+     d
+     e
+     f
+    -g
+    +G
+    diff --git a/file2 b/file2
+    index 257cc5642c..5716ca5987 100644
+    --- a/file2
+    +++ b/file2
+    @@ -1,1 +1,1 @@
+    -foo
+    +bar
+    [EOF]
+    ");
+}
+
 #[test]
 fn test_commit_with_default_description() {
     let mut test_env = TestEnvironment::default();
@@ -475,6 +586,44 @@ fn test_commit_reset_author() {
     ");
 }
 
+#[test]
+fn test_commit_with_dates() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let repo_path = test_env.env_root().join("repo");
+    let get_dates = || {
+        let template = r#"author.timestamp().utc() ++ "\n" ++ committer.timestamp().utc()"#;
+        test_env.run_jj_in(&repo_path, ["log", "-r@-", "-T", template])
+    };
+
+    test_env
+        .run_jj_in(
+            &repo_path,
+            [
+                "commit",
+                "--author-date=2020-01-02T03:04:05+06:00",
+                "--committer-date=2 days ago",
+                "-m1",
+            ],
+        )
+        .success();
+    insta::assert_snapshot!(get_dates(), @r"
+    ○  2020-01-01 21:04:05.000 +00:00
+    │  2001-01-31 21:05:08.000 +00:00
+    ~
+    [EOF]
+    ");
+
+    let output = test_env.run_jj_in(&repo_path, ["commit", "--author-date=not a date", "-m2"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Error: Failed to parse date `not a date`
+    Caused by: expected week day or month name
+    [EOF]
+    [exit status: 1]
+    ");
+}
+
 #[must_use]
 fn get_log_output(test_env: &TestEnvironment, cwd: &Path) -> CommandOutput {
     let template = r#"commit_id.short() ++ " " ++ description"#;
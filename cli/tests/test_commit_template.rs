@@ -907,6 +907,34 @@ fn test_log_customize_short_id() {
     ");
 }
 
+#[test]
+fn test_log_change_url() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let repo_path = test_env.env_root().join("repo");
+    test_env.add_config(
+        r#"template-aliases.'change_url(id)' = '"https://review.example.com/q/" ++ id'"#,
+    );
+
+    // Change ids are linked if the output is colorized
+    let output = test_env.run_jj_in(&repo_path, ["log", "-r@", "--color=always"]);
+    insta::assert_snapshot!(output, @r"
+    [1m[38;5;2m@[0m  [1m]8;;https://review.example.com/q/qpvuntsmwlqtpsluzzsnyyzlmlwvmlnu\[38;5;13mq[38;5;8mpvuntsm[39m]8;;\ [38;5;3mtest.user@example.com[39m [38;5;14m2001-02-03 08:05:07[39m [38;5;12m2[38;5;8m30dd059[39m[0m
+    │  [1m[38;5;10m(empty)[39m [38;5;10m(no description set)[39m[0m
+    ~
+    [EOF]
+    ");
+
+    // No links without colors
+    let output = test_env.run_jj_in(&repo_path, ["log", "-r@"]);
+    insta::assert_snapshot!(output, @r"
+    @  qpvuntsm test.user@example.com 2001-02-03 08:05:07 230dd059
+    │  (empty) (no description set)
+    ~
+    [EOF]
+    ");
+}
+
 #[test]
 fn test_log_immutable() {
     let test_env = TestEnvironment::default();
@@ -981,6 +1009,64 @@ fn test_log_immutable() {
     "#);
 }
 
+#[test]
+fn test_log_fork_point() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let repo_path = test_env.env_root().join("repo");
+    test_env
+        .run_jj_in(&repo_path, ["new", "-mA", "root()"])
+        .success();
+    test_env.run_jj_in(&repo_path, ["new", "-mB"]).success();
+    test_env
+        .run_jj_in(&repo_path, ["bookmark", "create", "-r@", "main"])
+        .success();
+    test_env.run_jj_in(&repo_path, ["new", "-mC"]).success();
+    test_env
+        .run_jj_in(&repo_path, ["new", "-mD", "description(A)"])
+        .success();
+
+    let template = r#"
+    separate(" ",
+      description.first_line(),
+      "fork_point:",
+      self.fork_point("main").map(|c| c.description().first_line()),
+    ) ++ "\n"
+    "#;
+    let output = test_env.run_jj_in(&repo_path, ["log", "-r::", "-T", template]);
+    insta::assert_snapshot!(output, @r"
+    @  D fork_point: A
+    │ ○  C fork_point: B
+    │ ○  B fork_point: B
+    ├─╯
+    ○  A fork_point: A
+    ◆  fork_point:
+    [EOF]
+    ");
+
+    let template = r#"self.fork_point("unknown_fn()")"#;
+    let output = test_env.run_jj_in(&repo_path, ["log", "-r@", "-T", template]);
+    insta::assert_snapshot!(output, @r#"
+    ------- stderr -------
+    Error: Failed to parse template: In revset expression
+    Caused by:
+    1:  --> 1:17
+      |
+    1 | self.fork_point("unknown_fn()")
+      |                 ^------------^
+      |
+      = In revset expression
+    2:  --> 1:1
+      |
+    1 | unknown_fn()
+      | ^--------^
+      |
+      = Function `unknown_fn` doesn't exist
+    [EOF]
+    [exit status: 1]
+    "#);
+}
+
 #[test]
 fn test_log_contained_in() {
     let test_env = TestEnvironment::default();
@@ -1199,6 +1285,63 @@ fn test_short_prefix_in_transaction() {
     ");
 }
 
+#[test]
+fn test_log_touched_paths() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let repo_path = test_env.env_root().join("repo");
+
+    std::fs::create_dir(repo_path.join("dir")).unwrap();
+    std::fs::write(repo_path.join("dir").join("a"), "1").unwrap();
+    std::fs::write(repo_path.join("b"), "1").unwrap();
+    test_env.run_jj_in(&repo_path, ["commit", "-m1"]).success();
+    std::fs::write(repo_path.join("dir").join("c"), "2").unwrap();
+    test_env.run_jj_in(&repo_path, ["commit", "-m2"]).success();
+    std::fs::write(repo_path.join("b"), "3").unwrap();
+    test_env
+        .run_jj_in(&repo_path, ["describe", "-m3"])
+        .success();
+
+    // Annotate the commits matched by the log fileset with the paths they touched
+    let template = r#"description.first_line() ++ ": " ++ self.touched_paths("dir | b") ++ "\n""#;
+    let output = test_env.run_jj_in(&repo_path, ["log", "-T", template, "dir"]);
+    insta::assert_snapshot!(output, @r"
+    ○  2: dir/c
+    ○  1: b dir/a
+    │
+    ~
+    [EOF]
+    ");
+
+    // List methods can be used on the result
+    let template = r#"
+        description.first_line() ++ ":"
+        ++ self.touched_paths("dir").map(|p| " [" ++ p ++ "]").join("") ++ "\n"
+    "#;
+    let output = test_env.run_jj_in(&repo_path, ["log", "--no-graph", "-T", template]);
+    insta::assert_snapshot!(output, @r"
+    3:
+    2: [dir/c]
+    1: [dir/a]
+    :
+    [EOF]
+    ");
+
+    let output = test_env.run_jj_in(&repo_path, ["log", "-T", "self.touched_paths()"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Error: Failed to parse template: Function `touched_paths`: Expected 1 arguments
+    Caused by:  --> 1:20
+      |
+    1 | self.touched_paths()
+      |                    ^
+      |
+      = Function `touched_paths`: Expected 1 arguments
+    [EOF]
+    [exit status: 1]
+    ");
+}
+
 #[test]
 fn test_log_diff_predefined_formats() {
     let test_env = TestEnvironment::default();
@@ -1263,6 +1406,7 @@ fn test_log_diff_predefined_formats() {
     [38;5;2m+[4mb[24m[39m
     [38;5;2m+[4mc[24m[39m
     [1mdiff --git a/rename-source b/rename-target[0m
+    [1msimilarity index 100%[0m
     [1mrename from rename-source[0m
     [1mrename to rename-target[0m
     === stat ===
@@ -1310,6 +1454,7 @@ fn test_log_diff_predefined_formats() {
     [38;5;2m<<log diff git added::+>>[4m<<log diff git added token::b>>[24m<<log diff git added::>>[39m
     [38;5;2m<<log diff git added::+>>[4m<<log diff git added token::c>>[24m[39m
     [1m<<log diff git file_header::diff --git a/rename-source b/rename-target>>[0m
+    [1m<<log diff git file_header::similarity index 100%>>[0m
     [1m<<log diff git file_header::rename from rename-source>>[0m
     [1m<<log diff git file_header::rename to rename-target>>[0m
     <<log::=== stat ===>>
@@ -1354,6 +1499,7 @@ fn test_log_diff_predefined_formats() {
     +b
     +c
     diff --git a/rename-source b/rename-target
+    similarity index 100%
     rename from rename-source
     rename to rename-target
     === stat ===
@@ -1416,6 +1562,7 @@ fn test_log_diff_predefined_formats() {
     +b
     +c
     diff --git a/rename-source b/rename-target
+    similarity index 100%
     rename from rename-source
     rename to rename-target
     === stat ===
@@ -1605,6 +1752,55 @@ fn test_file_list_symlink() {
     ");
 }
 
+#[cfg(unix)]
+#[test]
+fn test_file_list_metadata() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let repo_path = test_env.env_root().join("repo");
+
+    std::fs::create_dir(repo_path.join("dir")).unwrap();
+    std::fs::write(repo_path.join("dir").join("file"), "content\n").unwrap();
+    std::fs::write(repo_path.join("empty"), "").unwrap();
+    std::os::unix::fs::symlink("dir/file", repo_path.join("symlink")).unwrap();
+
+    let template = indoc! {r#"
+        separate(" ",
+          path,
+          "size=" ++ if(size, size, "<none>"),
+          "symlink_target=" ++ symlink_target,
+          "object_id=" ++ object_id.substr(0, 12),
+        ) ++ "\n"
+    "#};
+    let output = test_env.run_jj_in(&repo_path, ["file", "list", "-T", template]);
+    insta::assert_snapshot!(output, @r"
+    dir/file size=8 symlink_target= object_id=d95f3ad14dee
+    empty size=0 symlink_target= object_id=e69de29bb2d1
+    symlink size=<none> symlink_target=dir/file object_id=dea97c3520a7
+    [EOF]
+    ");
+
+    let output = test_env.run_jj_in(&repo_path.join("dir"), ["file", "list", "--json"]);
+    insta::assert_snapshot!(output, @r#"
+    {"path":"dir/file","file_type":"file","executable":false,"size":8,"symlink_target":null,"object_id":"d95f3ad14dee633a758d2e331151e950dd13e4ed"}
+    {"path":"empty","file_type":"file","executable":false,"size":0,"symlink_target":null,"object_id":"e69de29bb2d1d6434b8b29ae775ad8c2e48c5391"}
+    {"path":"symlink","file_type":"symlink","executable":false,"size":null,"symlink_target":"dir/file","object_id":"dea97c3520a755e4db5694d743aa8599511bbe9c"}
+    [EOF]
+    "#);
+
+    let output = test_env.run_jj_in(&repo_path, ["file", "list", "--json", "-T", "path"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    error: the argument '--json' cannot be used with '--template <TEMPLATE>'
+
+    Usage: jj file list --json [FILESETS]...
+
+    For more information, try '--help'.
+    [EOF]
+    [exit status: 2]
+    ");
+}
+
 #[test]
 fn test_repo_path() {
     let test_env = TestEnvironment::default();
@@ -101,7 +101,9 @@ fn test_bookmark_names() {
     --ignore-working-copy	Don't snapshot the working copy, and don't update it
     --ignore-immutable	Allow rewriting immutable commits
     --at-operation	Operation to load the repo at
+    --at-workspace	Workspace whose working-copy commit to operate on
     --debug	Enable debug logging
+    --trace-output	Write a performance trace of the command to this file
     --color	When to colorize output
     --quiet	Silence non-primary command output
     --no-pager	Disable the pager
@@ -678,28 +680,42 @@ fn test_config() {
     let dir = test_env.env_root();
 
     let output = test_env.run_jj_in(dir, ["--", "jj", "config", "get", "c"]);
-    insta::assert_snapshot!(output, @r"
+    insta::assert_snapshot!(output, @r#"
+    commit.checks.level	Whether a description that violates the rules is accepted with a warning or rejected with an error
+    commit.checks.max-subject-length	Maximum number of characters in the first line of the description
+    commit.checks.required-trailers	Trailer keys (like "Signed-off-by") that must be present in the last paragraph of the description
+    commit.checks.subject-pattern	Regular expression that the first line of the description must match
     core.fsmonitor	Whether to use an external filesystem monitor, useful for large repos
     core.watchman.register-snapshot-trigger	Whether to use triggers to monitor for changes in the background.
     [EOF]
-    ");
+    "#);
 
     let output = test_env.run_jj_in(dir, ["--", "jj", "config", "list", "c"]);
-    insta::assert_snapshot!(output, @r"
+    insta::assert_snapshot!(output, @r#"
     colors	Mapping from jj formatter labels to colors
+    commit	Settings for commit descriptions
+    commit.checks	Rules that new descriptions set by `jj describe`, `jj commit`, and `jj split` are checked against
+    commit.checks.level	Whether a description that violates the rules is accepted with a warning or rejected with an error
+    commit.checks.max-subject-length	Maximum number of characters in the first line of the description
+    commit.checks.required-trailers	Trailer keys (like "Signed-off-by") that must be present in the last paragraph of the description
+    commit.checks.subject-pattern	Regular expression that the first line of the description must match
     core
     core.fsmonitor	Whether to use an external filesystem monitor, useful for large repos
     core.watchman
     core.watchman.register-snapshot-trigger	Whether to use triggers to monitor for changes in the background.
     [EOF]
-    ");
+    "#);
 
     let output = test_env.run_jj_in(dir, ["--", "jj", "log", "--config", "c"]);
-    insta::assert_snapshot!(output, @r"
+    insta::assert_snapshot!(output, @r#"
+    commit.checks.level=	Whether a description that violates the rules is accepted with a warning or rejected with an error
+    commit.checks.max-subject-length=	Maximum number of characters in the first line of the description
+    commit.checks.required-trailers=	Trailer keys (like "Signed-off-by") that must be present in the last paragraph of the description
+    commit.checks.subject-pattern=	Regular expression that the first line of the description must match
     core.fsmonitor=	Whether to use an external filesystem monitor, useful for large repos
     core.watchman.register-snapshot-trigger=	Whether to use triggers to monitor for changes in the background.
     [EOF]
-    ");
+    "#);
 
     let output = test_env.run_jj_in(
         dir,
@@ -745,6 +761,9 @@ fn test_template_alias() {
 
     let output = test_env.run_jj_in(dir, ["--", "jj", "log", "-T", ""]);
     insta::assert_snapshot!(output, @r"
+    builtin_bookmark_list
+    builtin_bookmark_list_verbose
+    builtin_file_list_json
     builtin_log_comfortable
     builtin_log_compact
     builtin_log_compact_full_description
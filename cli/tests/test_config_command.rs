@@ -1073,6 +1073,71 @@ fn test_config_get() {
     ");
 }
 
+#[test]
+fn test_config_get_resolve_origin() {
+    let test_env = TestEnvironment::default();
+    test_env.add_config(
+        r#"
+    [table]
+    string = "some value 1"
+    overridden = "foo"
+    "#,
+    );
+    test_env.add_config(
+        r#"
+    [table]
+    overridden = "bar"
+    "#,
+    );
+
+    let output = test_env.run_jj_in(
+        ".",
+        ["config", "get", "--resolve-origin", "table.overridden"],
+    );
+    insta::assert_snapshot!(output, @r#"
+    user $TEST_ENV/config/config0002.toml:4: "foo" (overridden)
+    user $TEST_ENV/config/config0003.toml:3: "bar"
+    effective: "bar"
+    [EOF]
+    "#);
+
+    let output = test_env.run_jj_in(
+        ".",
+        [
+            "config",
+            "get",
+            "--resolve-origin",
+            "table.overridden",
+            "--config=table.overridden=baz",
+        ],
+    );
+    insta::assert_snapshot!(output, @r#"
+    user $TEST_ENV/config/config0002.toml:4: "foo" (overridden)
+    user $TEST_ENV/config/config0003.toml:3: "bar" (overridden)
+    command-arg: "baz"
+    effective: "baz"
+    [EOF]
+    "#);
+
+    // Tables are merged from all layers
+    let output = test_env.run_jj_in(".", ["config", "get", "--resolve-origin", "table"]);
+    insta::assert_snapshot!(output, @r#"
+    user $TEST_ENV/config/config0002.toml:2: { string = "some value 1", overridden = "foo" }
+    user $TEST_ENV/config/config0003.toml:2: { overridden = "bar" }
+    effective: { string = "some value 1", overridden = "bar" }
+    [EOF]
+    "#);
+
+    let output = test_env.run_jj_in(".", ["config", "get", "--resolve-origin", "nonexistent"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Config error: Value not found for nonexistent
+    For help, see https://jj-vcs.github.io/jj/latest/config/ or use `jj help -k config`.
+    [EOF]
+    [exit status: 1]
+    ");
+}
+
 #[test]
 fn test_config_path_syntax() {
     let test_env = TestEnvironment::default();
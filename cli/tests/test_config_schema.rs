@@ -69,3 +69,8 @@ fn test_taplo_check_unix_config() {
 fn test_taplo_check_windows_config() {
     taplo_check_config("src/config/windows.toml");
 }
+
+#[test]
+fn test_taplo_check_macos_config() {
+    taplo_check_config("src/config/macos.toml");
+}
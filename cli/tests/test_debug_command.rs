@@ -238,6 +238,111 @@ fn test_debug_tree() {
     [EOF]
     "#
     );
+
+    // Can show only conflicted entries, with the terms of the merge
+    test_env
+        .run_jj_in(&workspace_path, ["new", "-m=side1"])
+        .success();
+    std::fs::write(subdir.join("file1"), "side 1").unwrap();
+    test_env
+        .run_jj_in(&workspace_path, ["new", "@-", "-m=side2"])
+        .success();
+    std::fs::write(subdir.join("file1"), "side 2").unwrap();
+    test_env
+        .run_jj_in(&workspace_path, ["new", "description(side1)", "@"])
+        .success();
+    let output = test_env.run_jj_in(&workspace_path, ["debug", "tree", "--conflicts"]);
+    assert_snapshot!(output.normalize_backslash(), @r#"
+    dir/subdir/file1: Ok(Conflicted([Some(File { id: FileId("602a08d301f229448d28b6f05720dc1c7e31e8fd"), executable: false }), Some(File { id: FileId("498e9b01d79cb8d31cdf0df1a663cc1fcefd9de3"), executable: false }), Some(File { id: FileId("13b96c08b96e812088e1e5679f151ada6ab9516c"), executable: false })]))
+    [EOF]
+    "#);
+    let output = test_env.run_jj_in(&workspace_path, ["debug", "tree", "--json"]);
+    assert_snapshot!(output.normalize_backslash(), @r#"
+    {"adds":[{"executable":false,"id":"602a08d301f229448d28b6f05720dc1c7e31e8fd","type":"file"},{"executable":false,"id":"13b96c08b96e812088e1e5679f151ada6ab9516c","type":"file"}],"path":"dir/subdir/file1","removes":[{"executable":false,"id":"498e9b01d79cb8d31cdf0df1a663cc1fcefd9de3","type":"file"}],"resolved":false}
+    {"adds":[{"executable":false,"id":"b2496eaffe394cd50a9db4de5787f45f09fd9722","type":"file"}],"path":"dir/subdir/file2","removes":[],"resolved":true}
+    [EOF]
+    "#);
+}
+
+#[test]
+fn test_debug_working_copy_json() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let workspace_path = test_env.env_root().join("repo");
+    std::fs::write(workspace_path.join("file1"), "a\n").unwrap();
+    std::fs::write(workspace_path.join("file2"), "b\n").unwrap();
+    test_env.run_jj_in(&workspace_path, ["status"]).success();
+
+    // The working copy isn't snapshotted
+    std::fs::write(workspace_path.join("file2"), "modified\n").unwrap();
+    let output = test_env.run_jj_in(&workspace_path, ["debug", "working-copy", "--json"]);
+    let mtime_regex = Regex::new(r#""mtime":[0-9]+"#).unwrap();
+    assert_snapshot!(output.normalize_stdout_with(|text| {
+        mtime_regex.replace_all(&text, r#""mtime":[mtime]"#).into_owned()
+    }), @r#"
+    {"files":[{"conflict_marker_len":null,"modified":false,"mtime":[mtime],"path":"file1","size":2,"type":"file"},{"conflict_marker_len":null,"modified":true,"mtime":[mtime],"path":"file2","size":2,"type":"file"}],"operation_id":"9f14c16125752ca8a6f4ac52ce9ab4fe2dcf2e0a4a128c986548a8a77e395f492c5fb7730d82d398e00e932875e9dc052da6595cb55de3acc0ff3d940239fccb","sparse_patterns":[""],"tree_ids":["d7442c38e4a949c6e5d82e669b5bc439624cd990"],"type":"local","workspace":"default"}
+    [EOF]
+    "#);
+}
+
+#[test]
+fn test_debug_watch() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let workspace_path = test_env.env_root().join("repo");
+    let op_id = test_env.current_operation_id(&workspace_path);
+
+    std::fs::write(workspace_path.join("file"), "a\n").unwrap();
+    test_env
+        .run_jj_in(&workspace_path, ["bookmark", "create", "-r@", "foo"])
+        .success();
+    test_env.run_jj_in(&workspace_path, ["new"]).success();
+    test_env
+        .run_jj_in(&workspace_path, ["bookmark", "delete", "foo"])
+        .success();
+
+    // Events for the operations after the given one are printed right away
+    let output = test_env.run_jj_in(
+        &workspace_path,
+        [
+            "debug",
+            "watch",
+            "--from",
+            &op_id,
+            "--limit=8",
+            "--interval=0",
+        ],
+    );
+    assert_snapshot!(output, @r#"
+    {"description":"snapshot working copy","id":"fe6aaf5eb2150611b135f044fb446081f4c73f1570c96f7cd7004f9c8cb0f24c4d5edc92b6b7822fb817e79d3ca56263c8ca90635cfe462ad4c44784dc9bfdb7","type":"operation"}
+    {"commit_id":"3b1673b6370cdf5bf08bc6c5b19d1a66f428fb5a","type":"working-copy","workspace":"default"}
+    {"description":"create bookmark foo pointing to commit 3b1673b6370cdf5bf08bc6c5b19d1a66f428fb5a","id":"411d1a9a80ea38a8489704e735d34bac35acd66afcd2fe5aec84521b492272e015b3bc6c016286f0f01d6d07ea0f6ab7d72e4b8f370e3a45fcd6765bdd39774d","type":"operation"}
+    {"name":"foo","new":["3b1673b6370cdf5bf08bc6c5b19d1a66f428fb5a"],"old":null,"type":"bookmark"}
+    {"description":"new empty commit","id":"f7db2b34108bda09494ace8016bf2a1ebfbc3eb27f6ed4e94d28da628fcd75ec5de54422ec67693534a28bc733ca9de77208cd33d21ef5667f2bb389fdc33bf8","type":"operation"}
+    {"commit_id":"219198de11a6d68ad7b46073dd4cd9c9a3066eab","type":"working-copy","workspace":"default"}
+    {"description":"delete bookmark foo","id":"520ce4acc90d51dd5b2d5a0433c1000339f777b45cb51428d0247acdf1e995a5ef75d3b14778f0e7e37cbfb975d3bdd1dd3b1a822ea85eb7b050ddd35560ce36","type":"operation"}
+    {"name":"foo","new":null,"old":["3b1673b6370cdf5bf08bc6c5b19d1a66f428fb5a"],"type":"bookmark"}
+    [EOF]
+    "#);
+
+    // The limit may stop in the middle of an operation's events
+    let output = test_env.run_jj_in(
+        &workspace_path,
+        [
+            "debug",
+            "watch",
+            "--from",
+            &op_id,
+            "--limit=3",
+            "--interval=0",
+        ],
+    );
+    assert_snapshot!(output, @r#"
+    {"description":"snapshot working copy","id":"fe6aaf5eb2150611b135f044fb446081f4c73f1570c96f7cd7004f9c8cb0f24c4d5edc92b6b7822fb817e79d3ca56263c8ca90635cfe462ad4c44784dc9bfdb7","type":"operation"}
+    {"commit_id":"3b1673b6370cdf5bf08bc6c5b19d1a66f428fb5a","type":"working-copy","workspace":"default"}
+    {"description":"create bookmark foo pointing to commit 3b1673b6370cdf5bf08bc6c5b19d1a66f428fb5a","id":"411d1a9a80ea38a8489704e735d34bac35acd66afcd2fe5aec84521b492272e015b3bc6c016286f0f01d6d07ea0f6ab7d72e4b8f370e3a45fcd6765bdd39774d","type":"operation"}
+    [EOF]
+    "#);
 }
 
 #[test]
@@ -252,7 +357,138 @@ fn test_debug_operation_id() {
     ");
 }
 
+#[test]
+fn test_debug_fsck() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let workspace_path = test_env.env_root().join("repo");
+    test_env
+        .run_jj_in(&workspace_path, ["describe", "-m", "first"])
+        .success();
+    test_env
+        .run_jj_in(&workspace_path, ["new", "-m", "second"])
+        .success();
+
+    let output = test_env.run_jj_in(&workspace_path, ["debug", "fsck"]);
+    assert_snapshot!(output, @r"
+    No problems found.
+    [EOF]
+    ");
+
+    // Remove the Git object of the parent commit
+    let output = test_env.run_jj_in(
+        &workspace_path,
+        ["log", "--no-graph", "-r@-", "-Tcommit_id"],
+    );
+    let commit_id = output.success().stdout.into_raw();
+    let object_path = workspace_path
+        .join(".jj/repo/store/git/objects")
+        .join(&commit_id[..2])
+        .join(&commit_id[2..]);
+    std::fs::remove_file(object_path).unwrap();
+    let output = test_env.run_jj_in(&workspace_path, ["debug", "fsck"]);
+    assert_snapshot!(output, @r"
+    unreadable-commit: Commit fa15625b4a986997697639dfc2844138900c79f2 cannot be read: Object fa15625b4a986997697639dfc2844138900c79f2 of type commit not found
+      repair: Restore an operation from before the commit was lost with `jj op restore`
+    [EOF]
+    ------- stderr -------
+    Error: Found 1 problem
+    [EOF]
+    [exit status: 1]
+    ");
+}
+
+#[test]
+fn test_debug_snapshot_bench() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let workspace_path = test_env.env_root().join("repo");
+    std::fs::write(workspace_path.join("file1"), "a\n").unwrap();
+    std::fs::create_dir(workspace_path.join("dir")).unwrap();
+    std::fs::write(workspace_path.join("dir").join("file2"), "b\n").unwrap();
+    std::fs::write(workspace_path.join(".gitignore"), "ignored\n").unwrap();
+    std::fs::write(workspace_path.join("ignored"), "c\n").unwrap();
+    // Make the files older than the temporary working-copy state so they
+    // aren't considered racily modified by the incremental snapshot
+    let mtime = std::time::SystemTime::now() - std::time::Duration::from_secs(3600);
+    for path in ["file1", "dir/file2", ".gitignore", "ignored"] {
+        let file = std::fs::File::options()
+            .write(true)
+            .open(workspace_path.join(path))
+            .unwrap();
+        file.set_modified(mtime).unwrap();
+    }
+
+    let output = test_env.run_jj_in(
+        &workspace_path,
+        ["debug", "snapshot-bench", "--iterations=2"],
+    );
+    insta::assert_snapshot!(filter_durations(output), @r"
+    Full snapshot:
+      traverse:     [duration]
+      hash files:   [duration] (3 files, summed over threads)
+      update state: [duration]
+      write tree:   [duration]
+      total:        [duration]
+    Incremental snapshot:
+      traverse:     [duration]
+      hash files:   [duration] (0 files, summed over threads)
+      update state: [duration]
+      write tree:   [duration]
+      total:        [duration]
+    [EOF]
+    ");
+}
+
+#[test]
+fn test_debug_diff_bench() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let workspace_path = test_env.env_root().join("repo");
+    std::fs::write(workspace_path.join("file1"), "a\nb\n").unwrap();
+    test_env.run_jj_in(&workspace_path, ["new"]).success();
+    std::fs::write(workspace_path.join("file1"), "a\nc\nd\n").unwrap();
+    std::fs::write(workspace_path.join("file2"), "e\n").unwrap();
+
+    let output = test_env.run_jj_in(&workspace_path, ["debug", "diff-bench"]);
+    insta::assert_snapshot!(filter_durations(output), @r"
+    Revset evaluation: 3 commits
+      time: [duration]
+    Tree diff: 2 paths
+      time: [duration]
+    Content diff: 2 files, 3 insertions(+), 1 deletions(-)
+      time: [duration]
+    [EOF]
+    ");
+
+    let output = test_env.run_jj_in(
+        &workspace_path,
+        [
+            "debug",
+            "diff-bench",
+            "-rall()",
+            "--from=root()",
+            "--to=@-",
+            "--iterations=1",
+        ],
+    );
+    insta::assert_snapshot!(filter_durations(output), @r"
+    Revset evaluation: 3 commits
+      time: [duration]
+    Tree diff: 1 paths
+      time: [duration]
+    Content diff: 1 files, 2 insertions(+), 0 deletions(-)
+      time: [duration]
+    [EOF]
+    ");
+}
+
 fn filter_index_stats(output: CommandOutput) -> CommandOutput {
     let regex = Regex::new(r"    Name: [0-9a-z]+").unwrap();
     output.normalize_stdout_with(|text| regex.replace_all(&text, "    Name: [hash]").into_owned())
 }
+
+fn filter_durations(output: CommandOutput) -> CommandOutput {
+    let regex = Regex::new(r"[0-9]+(\.[0-9]+)?(ns|µs|ms|s)\b").unwrap();
+    output.normalize_stdout_with(|text| regex.replace_all(&text, "[duration]").into_owned())
+}
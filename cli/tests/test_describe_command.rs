@@ -651,6 +651,122 @@ fn test_describe_default_description() {
     "#);
 }
 
+#[test]
+fn test_describe_commit_checks() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    test_env.add_config(
+        r#"
+        [commit.checks]
+        max-subject-length = 10
+        subject-pattern = '^[a-z]+: '
+        required-trailers = ["Signed-off-by"]
+        "#,
+    );
+    let workspace_path = test_env.env_root().join("repo");
+
+    // Violations are reported as warnings by default
+    let output = test_env.run_jj_in(&workspace_path, ["describe", "-m", "a long subject line"]);
+    insta::assert_snapshot!(output, @r#"
+    ------- stderr -------
+    Warning: The description "a long subject line" does not pass the commit checks:
+      The subject line is longer than 10 characters
+      The subject line does not match the pattern "^[a-z]+: "
+      The trailer "Signed-off-by" is missing
+    Working copy now at: qpvuntsm 205f2b30 (empty) a long subject line
+    Parent commit      : zzzzzzzz 00000000 (empty) (no description set)
+    [EOF]
+    "#);
+
+    // A description that passes the checks
+    let output = test_env.run_jj_in(
+        &workspace_path,
+        ["describe", "-m", "cli: foo", "-m", "Signed-off-by: me"],
+    );
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Working copy now at: qpvuntsm 64b13e01 (empty) cli: foo
+    Parent commit      : zzzzzzzz 00000000 (empty) (no description set)
+    [EOF]
+    ");
+
+    // Lines that look like trailers don't count if the paragraph isn't made
+    // of trailers only
+    let output = test_env.run_jj_in(
+        &workspace_path,
+        [
+            "describe",
+            "-m",
+            "cli: foo",
+            "-m",
+            "Signed-off-by: me\nand others",
+        ],
+    );
+    insta::assert_snapshot!(output, @r#"
+    ------- stderr -------
+    Warning: The description "cli: foo" does not pass the commit checks:
+      The trailer "Signed-off-by" is missing
+    Working copy now at: qpvuntsm 6c919ae6 (empty) cli: foo
+    Parent commit      : zzzzzzzz 00000000 (empty) (no description set)
+    [EOF]
+    "#);
+
+    // Unchanged descriptions aren't checked
+    test_env
+        .run_jj_in(&workspace_path, ["describe", "-m", "bad"])
+        .success();
+    let output = test_env.run_jj_in(&workspace_path, ["describe", "-m", "bad"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Nothing changed.
+    [EOF]
+    ");
+
+    // Violations are errors if configured
+    test_env.add_config(r#"commit.checks.level = "error""#);
+    let output = test_env.run_jj_in(&workspace_path, ["describe", "-m", "worse"]);
+    insta::assert_snapshot!(output, @r#"
+    ------- stderr -------
+    Error: The description "worse" does not pass the commit checks:
+      The subject line does not match the pattern "^[a-z]+: "
+      The trailer "Signed-off-by" is missing
+    Hint: Use --no-verify to skip the checks.
+    [EOF]
+    [exit status: 1]
+    "#);
+
+    // The checks can be skipped
+    let output = test_env.run_jj_in(&workspace_path, ["describe", "-m", "worse", "--no-verify"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Working copy now at: qpvuntsm 209968a5 (empty) worse
+    Parent commit      : zzzzzzzz 00000000 (empty) (no description set)
+    [EOF]
+    ");
+
+    // Invalid pattern
+    let output = test_env.run_jj_in(
+        &workspace_path,
+        [
+            "describe",
+            "-m",
+            "foo",
+            "--config=commit.checks.subject-pattern='('",
+        ],
+    );
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Config error: Invalid regex in commit.checks.subject-pattern
+    Caused by: regex parse error:
+        (
+        ^
+    error: unclosed group
+    For help, see https://jj-vcs.github.io/jj/latest/config/ or use `jj help -k config`.
+    [EOF]
+    [exit status: 1]
+    ");
+}
+
 #[test]
 fn test_describe_author() {
     let mut test_env = TestEnvironment::default();
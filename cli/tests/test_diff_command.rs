@@ -117,9 +117,11 @@ fn test_diff_basic() {
      3
     -4
     diff --git a/file1 b/file3
+    similarity index 100%
     rename from file1
     rename to file3
     diff --git a/file2 b/file4
+    similarity index 100%
     copy from file2
     copy to file4
     [EOF]
@@ -137,9 +139,11 @@ fn test_diff_basic() {
     @@ -4,1 +3,0 @@
     -4
     diff --git a/file1 b/file3
+    similarity index 100%
     rename from file1
     rename to file3
     diff --git a/file2 b/file4
+    similarity index 100%
     copy from file2
     copy to file4
     [EOF]
@@ -158,9 +162,11 @@ fn test_diff_basic() {
     <<diff context:: 3>>
     [38;5;1m<<diff removed::->>[4m<<diff removed token::4>>[24m[39m
     [1m<<diff file_header::diff --git a/file1 b/file3>>[0m
+    [1m<<diff file_header::similarity index 100%>>[0m
     [1m<<diff file_header::rename from file1>>[0m
     [1m<<diff file_header::rename to file3>>[0m
     [1m<<diff file_header::diff --git a/file2 b/file4>>[0m
+    [1m<<diff file_header::similarity index 100%>>[0m
     [1m<<diff file_header::copy from file2>>[0m
     [1m<<diff file_header::copy to file4>>[0m
     [EOF]
@@ -182,9 +188,11 @@ fn test_diff_basic() {
      3
     -4
     diff --git a/file1 b/file3
+    similarity index 100%
     rename from file1
     rename to file3
     diff --git a/file2 b/file4
+    similarity index 100%
     copy from file2
     copy to file4
     [EOF]
@@ -207,6 +215,38 @@ fn test_diff_basic() {
     [EOF]
     ");
 
+    // Filter by case-insensitive glob pattern
+    let output = test_env.run_jj_in(&repo_path, ["diff", "-s", "glob-i:FILE[12]"]);
+    insta::assert_snapshot!(output, @r"
+    D file1
+    M file2
+    [EOF]
+    ");
+    let output = test_env.run_jj_in(
+        &repo_path,
+        [
+            "diff",
+            "-s",
+            "glob:FILE[12]",
+            "--config=fileset.case-insensitive-globs=true",
+        ],
+    );
+    insta::assert_snapshot!(output, @r"
+    D file1
+    M file2
+    [EOF]
+    ");
+    let output = test_env.run_jj_in(
+        &repo_path,
+        [
+            "diff",
+            "-s",
+            "glob:FILE[12]",
+            "--config=fileset.case-insensitive-globs=false",
+        ],
+    );
+    insta::assert_snapshot!(output, @"");
+
     // Unmatched paths should generate warnings
     let output = test_env.run_jj_in(
         ".",
@@ -317,11 +357,15 @@ fn test_diff_file_mode() {
     let output = test_env.run_jj_in(&repo_path, ["diff", "-r@-"]);
     insta::assert_snapshot!(output, @r"
     Executable file became non-executable at file1:
+        (executable bit unset)
             1: 2
     Executable file became non-executable at file2:
+        (executable bit unset)
     Non-executable file became executable at file3:
+        (executable bit set)
        1    1: 12
     Non-executable file became executable at file4:
+        (executable bit set)
     [EOF]
     ");
     let output = test_env.run_jj_in(&repo_path, ["diff", "-r@"]);
@@ -504,6 +548,92 @@ fn test_diff_types() {
     }
 }
 
+#[cfg(unix)]
+#[test]
+fn test_diff_type_transition() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let repo_path = test_env.env_root().join("repo");
+
+    std::fs::write(repo_path.join("file"), "foo\nbar\n").unwrap();
+    std::fs::write(repo_path.join("modified"), "foo\n").unwrap();
+    test_env.run_jj_in(&repo_path, ["new"]).success();
+    std::fs::remove_file(repo_path.join("file")).unwrap();
+    std::os::unix::fs::symlink("target", repo_path.join("file")).unwrap();
+    std::fs::write(repo_path.join("modified"), "bar\n").unwrap();
+
+    let output = test_env.run_jj_in(&repo_path, ["diff", "--summary"]);
+    insta::assert_snapshot!(output, @r"
+    T file
+    M modified
+    [EOF]
+    ");
+    let output = test_env.run_jj_in(&repo_path, ["status"]);
+    insta::assert_snapshot!(output, @r"
+    Working copy changes:
+    T file
+    M modified
+    Working copy : rlvkpnrz e9ca0bad (no description set)
+    Parent commit: qpvuntsm fcdcac22 (no description set)
+    [EOF]
+    ");
+    let output = test_env.run_jj_in(&repo_path, ["diff", "--color-words", "file"]);
+    insta::assert_snapshot!(output, @r"
+    Regular file became symlink at file:
+       1     : foo
+       2     : bar
+        (symlink target is target)
+    [EOF]
+    ");
+    let output = test_env.run_jj_in(
+        &repo_path,
+        ["diff", "--color-words", "--from=@", "--to=@-", "file"],
+    );
+    insta::assert_snapshot!(output, @r"
+    Symlink became regular file at file:
+        (symlink target was target)
+            1: foo
+            2: bar
+    [EOF]
+    ");
+}
+
+#[cfg(unix)]
+#[test]
+fn test_diff_symlink_target() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let repo_path = test_env.env_root().join("repo");
+
+    std::os::unix::fs::symlink("target1", repo_path.join("link")).unwrap();
+    std::fs::write(repo_path.join("file"), "foo\n").unwrap();
+    test_env.run_jj_in(&repo_path, ["new"]).success();
+    std::fs::remove_file(repo_path.join("link")).unwrap();
+    std::os::unix::fs::symlink("target2", repo_path.join("link")).unwrap();
+    test_env
+        .run_jj_in(&repo_path, ["file", "chmod", "x", "file"])
+        .success();
+
+    let output = test_env.run_jj_in(&repo_path, ["diff", "--color-words"]);
+    insta::assert_snapshot!(output, @r"
+    Non-executable file became executable at file:
+        (executable bit set)
+    Symlink target changed at link:
+        (symlink target was target1)
+        (symlink target is target2)
+    [EOF]
+    ");
+    let output = test_env.run_jj_in(&repo_path, ["diff", "--color-words", "--color=always"]);
+    insta::assert_snapshot!(output, @r"
+    [38;5;3mNon-executable file became executable at file:[39m
+    [38;5;6m    (executable bit set)[39m
+    [38;5;3mSymlink target changed at link:[39m
+    [38;5;1m    (symlink target was target1)[39m
+    [38;5;2m    (symlink target is target2)[39m
+    [EOF]
+    ");
+}
+
 #[test]
 fn test_diff_name_only() {
     let test_env = TestEnvironment::default();
@@ -536,6 +666,399 @@ fn test_diff_name_only() {
     ");
 }
 
+#[test]
+fn test_diff_name_status() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let repo_path = test_env.env_root().join("repo");
+
+    std::fs::write(repo_path.join("deleted"), "d\n").unwrap();
+    std::fs::write(repo_path.join("modified"), "m\n").unwrap();
+    std::fs::write(repo_path.join("copied"), "1\n2\n3\n4\n").unwrap();
+    std::fs::write(repo_path.join("renamed"), "1\n2\n3\n4\n5\n6\n7\n8\n").unwrap();
+    test_env.run_jj_in(&repo_path, ["new"]).success();
+    std::fs::remove_file(repo_path.join("deleted")).unwrap();
+    std::fs::write(repo_path.join("modified"), "mod\n").unwrap();
+    std::fs::write(repo_path.join("added"), "add\n").unwrap();
+    std::fs::write(repo_path.join("copied"), "1\n2\n").unwrap();
+    std::fs::write(repo_path.join("copy"), "1\n2\n3\n4\n").unwrap();
+    std::fs::remove_file(repo_path.join("renamed")).unwrap();
+    std::fs::create_dir(repo_path.join("sub")).unwrap();
+    std::fs::write(repo_path.join("sub/renamed"), "1\n2\n3\n4\n5\n6\n7\nx\n").unwrap();
+
+    let output = test_env.run_jj_in(&repo_path, ["diff", "--name-status"]);
+    insta::assert_snapshot!(output.normalize_backslash(), @r"
+    A	added
+    M	copied
+    C100	copied	copy
+    D	deleted
+    M	modified
+    R087	renamed	sub/renamed
+    [EOF]
+    ");
+
+    // Can be combined with long formats, but not with other short formats
+    let output = test_env.run_jj_in(&repo_path, ["diff", "--name-status", "--git", "added"]);
+    insta::assert_snapshot!(output, @r"
+    A	added
+    diff --git a/added b/added
+    new file mode 100644
+    index 0000000000..76d4bb83f8
+    --- /dev/null
+    +++ b/added
+    @@ -0,0 +1,1 @@
+    +add
+    [EOF]
+    ");
+
+    let output = test_env.run_jj_in(&repo_path, ["diff", "--name-status", "--summary"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    error: the argument '--name-status' cannot be used with '--summary'
+
+    Usage: jj diff --name-status [FILESETS]...
+
+    For more information, try '--help'.
+    [EOF]
+    [exit status: 2]
+    ");
+}
+
+#[test]
+fn test_diff_json() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let repo_path = test_env.env_root().join("repo");
+
+    std::fs::write(repo_path.join("deleted"), "d\n").unwrap();
+    std::fs::write(repo_path.join("modified"), "1\n2\n3\n").unwrap();
+    std::fs::write(repo_path.join("renamed"), "1\n2\n3\n4\n5\n6\n7\n8\n").unwrap();
+    std::fs::write(repo_path.join("binary"), b"\0a").unwrap();
+    test_env.run_jj_in(&repo_path, ["new"]).success();
+    std::fs::remove_file(repo_path.join("deleted")).unwrap();
+    std::fs::write(repo_path.join("modified"), "1\ntwo\n3").unwrap();
+    std::fs::write(repo_path.join("added"), "add\n").unwrap();
+    std::fs::remove_file(repo_path.join("renamed")).unwrap();
+    std::fs::create_dir(repo_path.join("sub")).unwrap();
+    std::fs::write(repo_path.join("sub/renamed"), "1\n2\n3\n4\n5\n6\n7\nx\n").unwrap();
+    std::fs::write(repo_path.join("binary"), b"\0b").unwrap();
+
+    let output = test_env.run_jj_in(&repo_path, ["diff", "--json", "--context=1"]);
+    insta::assert_snapshot!(output, @r#"
+    {"binary":false,"hunks":[{"lines":[{"content":"add\n","type":"added"}],"source_lines":0,"source_start":0,"target_lines":1,"target_start":1}],"path":"added","source_mode":null,"source_path":"added","status":"added","target_mode":"100644"}
+    {"binary":true,"hunks":[],"path":"binary","source_mode":"100644","source_path":"binary","status":"modified","target_mode":"100644"}
+    {"binary":false,"hunks":[{"lines":[{"content":"d\n","type":"removed"}],"source_lines":1,"source_start":1,"target_lines":0,"target_start":0}],"path":"deleted","source_mode":"100644","source_path":"deleted","status":"removed","target_mode":null}
+    {"binary":false,"hunks":[{"lines":[{"content":"1\n","type":"context"},{"content":"2\n","type":"removed"},{"content":"3\n","type":"removed"},{"content":"two\n","type":"added"},{"content":"3","type":"added"}],"source_lines":3,"source_start":1,"target_lines":3,"target_start":1}],"path":"modified","source_mode":"100644","source_path":"modified","status":"modified","target_mode":"100644"}
+    {"binary":false,"hunks":[{"lines":[{"content":"7\n","type":"context"},{"content":"8\n","type":"removed"},{"content":"x\n","type":"added"}],"source_lines":2,"source_start":7,"target_lines":2,"target_start":7}],"path":"sub/renamed","source_mode":"100644","source_path":"renamed","status":"renamed","target_mode":"100644"}
+    [EOF]
+    "#);
+
+    // The same entries are printed by `jj show` and `jj log -p`
+    let output = test_env.run_jj_in(&repo_path, ["show", "-T''", "--json", "@-"]);
+    insta::assert_snapshot!(output, @r#"
+    {"binary":true,"hunks":[],"path":"binary","source_mode":null,"source_path":"binary","status":"added","target_mode":"100644"}
+    {"binary":false,"hunks":[{"lines":[{"content":"d\n","type":"added"}],"source_lines":0,"source_start":0,"target_lines":1,"target_start":1}],"path":"deleted","source_mode":null,"source_path":"deleted","status":"added","target_mode":"100644"}
+    {"binary":false,"hunks":[{"lines":[{"content":"1\n","type":"added"},{"content":"2\n","type":"added"},{"content":"3\n","type":"added"}],"source_lines":0,"source_start":0,"target_lines":3,"target_start":1}],"path":"modified","source_mode":null,"source_path":"modified","status":"added","target_mode":"100644"}
+    {"binary":false,"hunks":[{"lines":[{"content":"1\n","type":"added"},{"content":"2\n","type":"added"},{"content":"3\n","type":"added"},{"content":"4\n","type":"added"},{"content":"5\n","type":"added"},{"content":"6\n","type":"added"},{"content":"7\n","type":"added"},{"content":"8\n","type":"added"}],"source_lines":0,"source_start":0,"target_lines":8,"target_start":1}],"path":"renamed","source_mode":null,"source_path":"renamed","status":"added","target_mode":"100644"}
+    [EOF]
+    "#);
+    let output = test_env.run_jj_in(
+        &repo_path,
+        ["log", "--no-graph", "-r@", "-T''", "-p", "--json", "added"],
+    );
+    insta::assert_snapshot!(output, @r#"
+    {"binary":false,"hunks":[{"lines":[{"content":"add\n","type":"added"}],"source_lines":0,"source_start":0,"target_lines":1,"target_start":1}],"path":"added","source_mode":null,"source_path":"added","status":"added","target_mode":"100644"}
+    [EOF]
+    "#);
+}
+
+#[test]
+fn test_diff_similarity_threshold() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let repo_path = test_env.env_root().join("repo");
+
+    std::fs::write(repo_path.join("copied"), "1\n2\n3\n4\n").unwrap();
+    std::fs::write(repo_path.join("renamed"), "1\n2\n3\n4\n5\n6\n7\n8\n").unwrap();
+    test_env.run_jj_in(&repo_path, ["new"]).success();
+    std::fs::write(repo_path.join("copied"), "1\n2\n").unwrap();
+    std::fs::write(repo_path.join("copy"), "1\n2\n3\nx\n").unwrap();
+    std::fs::remove_file(repo_path.join("renamed")).unwrap();
+    std::fs::write(repo_path.join("target"), "1\n2\n3\n4\n5\n6\n7\nx\n").unwrap();
+
+    let output = test_env.run_jj_in(&repo_path, ["diff", "--name-status"]);
+    insta::assert_snapshot!(output, @r"
+    M	copied
+    C075	copied	copy
+    R087	renamed	target
+    [EOF]
+    ");
+
+    let output = test_env.run_jj_in(&repo_path, ["diff", "--name-status", "-M90%"]);
+    insta::assert_snapshot!(output, @r"
+    M	copied
+    C075	copied	copy
+    D	renamed
+    A	target
+    [EOF]
+    ");
+
+    // Digits are taken as a fraction as in Git
+    let output = test_env.run_jj_in(&repo_path, ["diff", "--name-status", "-M8", "-C8"]);
+    insta::assert_snapshot!(output, @r"
+    M	copied
+    A	copy
+    R087	renamed	target
+    [EOF]
+    ");
+
+    let output = test_env.run_jj_in(
+        &repo_path,
+        ["diff", "--git", "--find-renames=80%", "--find-copies=80%"],
+    );
+    insta::assert_snapshot!(output, @r"
+    diff --git a/copied b/copied
+    index 94ebaf9001..1191247b6d 100644
+    --- a/copied
+    +++ b/copied
+    @@ -1,4 +1,2 @@
+     1
+     2
+    -3
+    -4
+    diff --git a/copy b/copy
+    new file mode 100644
+    index 0000000000..3098bcb6a1
+    --- /dev/null
+    +++ b/copy
+    @@ -0,0 +1,4 @@
+    +1
+    +2
+    +3
+    +x
+    diff --git a/renamed b/target
+    similarity index 87%
+    rename from renamed
+    rename to target
+    index 535d2b01d3..40aa09d8df 100644
+    --- a/renamed
+    +++ b/target
+    @@ -5,4 +5,4 @@
+     5
+     6
+     7
+    -8
+    +x
+    [EOF]
+    ");
+
+    let output = test_env.run_jj_in(&repo_path, ["diff", "--summary", "--find-copies"]);
+    insta::assert_snapshot!(output, @r"
+    M copied
+    C {copied => copy}
+    R {renamed => target}
+    [EOF]
+    ");
+
+    let output = test_env.run_jj_in(&repo_path, ["diff", "-M101%"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    error: invalid value '101%' for '--find-renames [<N>]': Invalid similarity threshold: 101%
+
+    For more information, try '--help'.
+    [EOF]
+    [exit status: 2]
+    ");
+}
+
+#[test]
+fn test_diff_from_path_to_path() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let repo_path = test_env.env_root().join("repo");
+
+    std::fs::create_dir_all(repo_path.join("vendor/sub")).unwrap();
+    std::fs::write(repo_path.join("vendor/same"), "same\n").unwrap();
+    std::fs::write(repo_path.join("vendor/modified"), "old\n").unwrap();
+    std::fs::write(repo_path.join("vendor/sub/removed"), "removed\n").unwrap();
+    std::fs::create_dir_all(repo_path.join("copy")).unwrap();
+    std::fs::write(repo_path.join("copy/same"), "same\n").unwrap();
+    std::fs::write(repo_path.join("copy/modified"), "new\n").unwrap();
+    std::fs::write(repo_path.join("copy/added"), "added\n").unwrap();
+    std::fs::write(repo_path.join("outside"), "outside\n").unwrap();
+    test_env.run_jj_in(&repo_path, ["new"]).success();
+    std::fs::write(repo_path.join("copy/modified"), "newer\n").unwrap();
+
+    // Compare directories within the same revision
+    let output = test_env.run_jj_in(
+        &repo_path,
+        [
+            "diff",
+            "--summary",
+            "--from-path=vendor",
+            "--to-path=copy",
+            "-r@-",
+        ],
+    );
+    insta::assert_snapshot!(output, @r"
+    A copy/added
+    M copy/modified
+    D copy/sub/removed
+    [EOF]
+    ");
+    let output = test_env.run_jj_in(
+        &repo_path,
+        [
+            "diff",
+            "--git",
+            "--from-path=vendor",
+            "--to-path=copy",
+            "copy/modified",
+        ],
+    );
+    insta::assert_snapshot!(output, @r"
+    diff --git a/copy/modified b/copy/modified
+    index 3367afdbbf..d58ed19c91 100644
+    --- a/copy/modified
+    +++ b/copy/modified
+    @@ -1,1 +1,1 @@
+    -old
+    +newer
+    [EOF]
+    ");
+
+    // Compare directories across revisions
+    let output = test_env.run_jj_in(
+        &repo_path,
+        [
+            "diff",
+            "--summary",
+            "--from=@-",
+            "--from-path=copy",
+            "--to-path=copy",
+        ],
+    );
+    insta::assert_snapshot!(output, @r"
+    M copy/modified
+    [EOF]
+    ");
+
+    let output = test_env.run_jj_in(
+        &repo_path,
+        ["diff", "--from-path=nonexistent", "--to-path=copy"],
+    );
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Error: No such directory in the source revision: nonexistent
+    [EOF]
+    [exit status: 1]
+    ");
+    let output = test_env.run_jj_in(
+        &repo_path,
+        ["diff", "--from-path=vendor", "--to-path=outside"],
+    );
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Error: No such directory in the target revision: outside
+    [EOF]
+    [exit status: 1]
+    ");
+    let output = test_env.run_jj_in(&repo_path, ["diff", "--from-path=vendor"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    error: the following required arguments were not provided:
+      --to-path <PATH>
+
+    Usage: jj diff --from-path <PATH> --to-path <PATH> [FILESETS]...
+
+    For more information, try '--help'.
+    [EOF]
+    [exit status: 2]
+    ");
+}
+
+#[test]
+fn test_diff_path_prefixes_and_relative() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let repo_path = test_env.env_root().join("repo");
+
+    std::fs::create_dir_all(repo_path.join("dir/sub")).unwrap();
+    std::fs::write(repo_path.join("dir/file"), "foo\n").unwrap();
+    std::fs::write(repo_path.join("dir/sub/file"), "bar\n").unwrap();
+    std::fs::write(repo_path.join("outside"), "baz\n").unwrap();
+
+    // Custom prefixes in the Git format
+    let output = test_env.run_jj_in(
+        &repo_path,
+        [
+            "diff",
+            "--git",
+            "--from=root()",
+            "--src-prefix=old/",
+            "--dst-prefix=new/",
+            "outside",
+        ],
+    );
+    insta::assert_snapshot!(output, @r"
+    diff --git old/outside new/outside
+    new file mode 100644
+    index 0000000000..76018072e0
+    --- /dev/null
+    +++ new/outside
+    @@ -0,0 +1,1 @@
+    +baz
+    [EOF]
+    ");
+    let output = test_env.run_jj_in(
+        &repo_path,
+        ["diff", "--git", "--src-prefix=", "--dst-prefix=", "outside"],
+    );
+    insta::assert_snapshot!(output, @r"
+    diff --git outside outside
+    new file mode 100644
+    index 0000000000..76018072e0
+    --- /dev/null
+    +++ outside
+    @@ -0,0 +1,1 @@
+    +baz
+    [EOF]
+    ");
+
+    // Paths relative to a directory
+    let output = test_env.run_jj_in(&repo_path, ["diff", "--summary", "--relative=dir"]);
+    insta::assert_snapshot!(output, @r"
+    A file
+    A sub/file
+    [EOF]
+    ");
+    let output = test_env.run_jj_in(
+        &repo_path.join("dir"),
+        ["diff", "--summary", "--relative=sub"],
+    );
+    insta::assert_snapshot!(output, @r"
+    A file
+    [EOF]
+    ");
+    let output = test_env.run_jj_in(&repo_path, ["diff", "--git", "--relative=dir/sub"]);
+    insta::assert_snapshot!(output, @r"
+    diff --git a/file b/file
+    new file mode 100644
+    index 0000000000..5716ca5987
+    --- /dev/null
+    +++ b/file
+    @@ -0,0 +1,1 @@
+    +bar
+    [EOF]
+    ");
+    let output = test_env.run_jj_in(&repo_path, ["diff", "--stat", "--relative=dir", "dir/sub"]);
+    insta::assert_snapshot!(output, @r"
+    sub/file | 1 +
+    1 file changed, 1 insertion(+), 0 deletions(-)
+    [EOF]
+    ");
+}
+
 #[test]
 fn test_diff_bad_args() {
     let test_env = TestEnvironment::default();
@@ -802,6 +1325,80 @@ fn test_diff_hunks() {
     [38;5;2m<<diff added::+baz >>[4m<<diff added token::quux>>[24m<<diff added:: blah blah>>[39m
     [EOF]
     ");
+
+    let output = test_env.run_jj_in(&repo_path, ["diff", "--word-diff"]);
+    insta::assert_snapshot!(output, @r"
+    diff --git a/file1 b/file1
+    index e69de29bb2..257cc5642c 100644
+    --- a/file1
+    +++ b/file1
+    @@ -0,0 +1,1 @@
+    {+foo+}
+    diff --git a/file2 b/file2
+    index 257cc5642c..e69de29bb2 100644
+    --- a/file2
+    +++ b/file2
+    @@ -1,1 +0,0 @@
+    [-foo-]
+    diff --git a/file3 b/file3
+    index 221a95a095..a543ef3892 100644
+    --- a/file3
+    +++ b/file3
+    @@ -1,2 +1,3 @@
+    foo
+    {+bar+}
+    baz [-qux-]{+quux+} blah blah
+    [EOF]
+    ");
+
+    let output = test_env.run_jj_in(&repo_path, ["diff", "--word-diff", "--color=debug"]);
+    insta::assert_snapshot!(output, @r"
+    [1m<<diff file_header::diff --git a/file1 b/file1>>[0m
+    [1m<<diff file_header::index e69de29bb2..257cc5642c 100644>>[0m
+    [1m<<diff file_header::--- a/file1>>[0m
+    [1m<<diff file_header::+++ b/file1>>[0m
+    [38;5;6m<<diff hunk_header::@@ -0,0 +1,1 @@>>[39m
+    [38;5;2m<<diff added::{+>>[4m<<diff added token::foo>>[24m<<diff added::+}>>[39m<<diff::>>
+    [1m<<diff file_header::diff --git a/file2 b/file2>>[0m
+    [1m<<diff file_header::index 257cc5642c..e69de29bb2 100644>>[0m
+    [1m<<diff file_header::--- a/file2>>[0m
+    [1m<<diff file_header::+++ b/file2>>[0m
+    [38;5;6m<<diff hunk_header::@@ -1,1 +0,0 @@>>[39m
+    [38;5;1m<<diff removed::[->>[4m<<diff removed token::foo>>[24m<<diff removed::-]>>[39m<<diff::>>
+    [1m<<diff file_header::diff --git a/file3 b/file3>>[0m
+    [1m<<diff file_header::index 221a95a095..a543ef3892 100644>>[0m
+    [1m<<diff file_header::--- a/file3>>[0m
+    [1m<<diff file_header::+++ b/file3>>[0m
+    [38;5;6m<<diff hunk_header::@@ -1,2 +1,3 @@>>[39m
+    <<diff context::foo>>
+    [38;5;2m<<diff added::{+>>[4m<<diff added token::bar>>[24m<<diff added::+}>>[39m<<diff::>>
+    <<diff::baz >>[38;5;1m<<diff removed::[->>[4m<<diff removed token::qux>>[24m<<diff removed::-]>>[38;5;2m<<diff added::{+>>[4m<<diff added token::quux>>[24m<<diff added::+}>>[39m<<diff:: blah blah>>
+    [EOF]
+    ");
+
+    let output = test_env.run_jj_in(
+        &repo_path,
+        [
+            "log",
+            "--no-graph",
+            "-r@",
+            "-T''",
+            "-p",
+            "--word-diff",
+            "file3",
+        ],
+    );
+    insta::assert_snapshot!(output, @r"
+    diff --git a/file3 b/file3
+    index 221a95a095..a543ef3892 100644
+    --- a/file3
+    +++ b/file3
+    @@ -1,2 +1,3 @@
+    foo
+    {+bar+}
+    baz [-qux-]{+quux+} blah blah
+    [EOF]
+    ");
 }
 
 #[test]
@@ -1409,6 +2006,27 @@ fn test_diff_missing_newline() {
     [EOF]
     ");
 
+    let output = test_env.run_jj_in(&repo_path, ["diff", "--word-diff"]);
+    insta::assert_snapshot!(output, @r"
+    diff --git a/file1 b/file1
+    index 1910281566..a907ec3f43 100644
+    --- a/file1
+    +++ b/file1
+    @@ -1,1 +1,2 @@
+    foo
+    {+bar+}
+    \ No newline at end of file
+    diff --git a/file2 b/file2
+    index a907ec3f43..1910281566 100644
+    --- a/file2
+    +++ b/file2
+    @@ -1,2 +1,1 @@
+    foo
+    [-bar-]
+    \ No newline at end of file
+    [EOF]
+    ");
+
     let output = test_env.run_jj_in(&repo_path, ["diff", "--stat"]);
     insta::assert_snapshot!(output, @r"
     file1 | 3 ++-
@@ -1729,6 +2347,326 @@ fn test_diff_ignore_whitespace() {
     ");
 }
 
+#[test]
+fn test_diff_ignore_matching_lines() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let repo_path = test_env.env_root().join("repo");
+
+    std::fs::write(
+        repo_path.join("file1"),
+        indoc! {"
+            Generated: 2024-01-01
+            a
+            b
+            c
+            d
+            e
+            f
+            g
+        "},
+    )
+    .unwrap();
+    test_env.run_jj_in(&repo_path, ["new"]).success();
+    std::fs::write(
+        repo_path.join("file1"),
+        indoc! {"
+            Generated: 2025-02-02
+            Built-by: ci
+            a
+            b
+            c
+            d
+            e
+            f
+            G
+        "},
+    )
+    .unwrap();
+
+    // Without the option, all changes are shown
+    let output = test_env.run_jj_in(&repo_path, ["diff", "--stat"]);
+    insta::assert_snapshot!(output, @r"
+    file1 | 5 +++--
+    1 file changed, 3 insertions(+), 2 deletions(-)
+    [EOF]
+    ");
+
+    // Hunks consisting only of ignored changes are omitted
+    let output = test_env.run_jj_in(
+        &repo_path,
+        [
+            "diff",
+            "--git",
+            "--context=2",
+            "--ignore-matching-lines=^(Generated|Built-by):",
+        ],
+    );
+    insta::assert_snapshot!(output, @r"
+    diff --git a/file1 b/file1
+    index 500c2ea06b..57e5092138 100644
+    --- a/file1
+    +++ b/file1
+    @@ -6,3 +7,3 @@
+     e
+     f
+    -g
+    +G
+    [EOF]
+    ");
+    let output = test_env.run_jj_in(
+        &repo_path,
+        [
+            "diff",
+            "--stat",
+            "--ignore-matching-lines=^(Generated|Built-by):",
+        ],
+    );
+    insta::assert_snapshot!(output, @r"
+    file1 | 2 +-
+    1 file changed, 1 insertion(+), 1 deletion(-)
+    [EOF]
+    ");
+    let output = test_env.run_jj_in(
+        &repo_path,
+        ["diff", "--ignore-matching-lines=^(Generated|Built-by):"],
+    );
+    insta::assert_snapshot!(output, @r"
+    Modified regular file file1:
+        ...
+       5    6: d
+       6    7: e
+       7    8: f
+       8    9: gG
+    [EOF]
+    ");
+
+    // The pattern can be set in config
+    let output = test_env.run_jj_in(
+        &repo_path,
+        [
+            "diff",
+            "--git",
+            "--config=diff.ignore-matching-lines='^Generated:'",
+        ],
+    );
+    insta::assert_snapshot!(output, @r"
+    diff --git a/file1 b/file1
+    index 500c2ea06b..57e5092138 100644
+    --- a/file1
+    +++ b/file1
+    @@ -1,8 +1,9 @@
+    -Generated: 2024-01-01
+    +Generated: 2025-02-02
+    +Built-by: ci
+     a
+     b
+     c
+     d
+     e
+     f
+    -g
+    +G
+    [EOF]
+    ");
+
+    // Ignored changes next to other changes are kept in git format so the
+    // patch still applies
+    let output = test_env.run_jj_in(
+        &repo_path,
+        ["diff", "--git", "--ignore-matching-lines=^Built-by:"],
+    );
+    insta::assert_snapshot!(output, @r"
+    diff --git a/file1 b/file1
+    index 500c2ea06b..57e5092138 100644
+    --- a/file1
+    +++ b/file1
+    @@ -1,8 +1,9 @@
+    -Generated: 2024-01-01
+    +Generated: 2025-02-02
+    +Built-by: ci
+     a
+     b
+     c
+     d
+     e
+     f
+    -g
+    +G
+    [EOF]
+    ");
+
+    // Invalid pattern
+    let output = test_env.run_jj_in(&repo_path, ["diff", "--ignore-matching-lines=("]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    error: invalid value '(' for '--ignore-matching-lines <REGEX>': regex parse error:
+        (
+        ^
+    error: unclosed group
+
+    For more information, try '--help'.
+    [EOF]
+    [exit status: 2]
+    ");
+    let output = test_env.run_jj_in(
+        &repo_path,
+        ["diff", "--config=diff.ignore-matching-lines='('"],
+    );
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Config error: Invalid type or value for diff.ignore-matching-lines
+    Caused by: regex parse error:
+        (
+        ^
+    error: unclosed group
+    For help, see https://jj-vcs.github.io/jj/latest/config/ or use `jj help -k config`.
+    [EOF]
+    [exit status: 1]
+    ");
+}
+
+#[test]
+fn test_diff_ignore_matching_lines_whole_file() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let repo_path = test_env.env_root().join("repo");
+
+    std::fs::write(repo_path.join("file1"), "Generated: 2024-01-01\na\n").unwrap();
+    std::fs::write(repo_path.join("file2"), "b\n").unwrap();
+    test_env.run_jj_in(&repo_path, ["new"]).success();
+    std::fs::write(repo_path.join("file1"), "Generated: 2025-02-02\na\n").unwrap();
+    std::fs::write(repo_path.join("file2"), "B\n").unwrap();
+
+    // Files with only ignored changes aren't shown at all
+    let output = test_env.run_jj_in(
+        &repo_path,
+        ["diff", "--git", "--ignore-matching-lines=^Generated:"],
+    );
+    insta::assert_snapshot!(output, @r"
+    diff --git a/file2 b/file2
+    index 6178079822..223b7836fb 100644
+    --- a/file2
+    +++ b/file2
+    @@ -1,1 +1,1 @@
+    -b
+    +B
+    [EOF]
+    ");
+    let output = test_env.run_jj_in(
+        &repo_path,
+        ["diff", "--stat", "--ignore-matching-lines=^Generated:"],
+    );
+    insta::assert_snapshot!(output, @r"
+    file2 | 2 +-
+    1 file changed, 1 insertion(+), 1 deletion(-)
+    [EOF]
+    ");
+    let output = test_env.run_jj_in(
+        &repo_path,
+        [
+            "diff",
+            "--color-words",
+            "--ignore-matching-lines=^Generated:",
+        ],
+    );
+    insta::assert_snapshot!(output, @r"
+    Modified regular file file2:
+       1    1: bB
+    [EOF]
+    ");
+}
+
+#[test]
+fn test_diff_anchored_and_algorithm() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let repo_path = test_env.env_root().join("repo");
+
+    std::fs::write(repo_path.join("file1"), "a\nb\nc\nx\nx\ny\ny\n").unwrap();
+    test_env.run_jj_in(&repo_path, ["new"]).success();
+    std::fs::write(repo_path.join("file1"), "c\na\nb\ny\ny\nx\nx\n").unwrap();
+
+    let output = test_env.run_jj_in(&repo_path, ["diff", "--git"]);
+    insta::assert_snapshot!(output, @r"
+    diff --git a/file1 b/file1
+    index 7e1fdc5592..c58e744abc 100644
+    --- a/file1
+    +++ b/file1
+    @@ -1,7 +1,7 @@
+    +c
+     a
+     b
+    -c
+    -x
+    -x
+     y
+     y
+    +x
+    +x
+    [EOF]
+    ");
+
+    // The anchored line is kept unchanged
+    let output = test_env.run_jj_in(&repo_path, ["diff", "--git", "--anchored=c"]);
+    insta::assert_snapshot!(output, @r"
+    diff --git a/file1 b/file1
+    index 7e1fdc5592..c58e744abc 100644
+    --- a/file1
+    +++ b/file1
+    @@ -1,7 +1,7 @@
+    -a
+    -b
+     c
+    -x
+    -x
+    +a
+    +b
+     y
+     y
+    +x
+    +x
+    [EOF]
+    ");
+
+    // Patience diff doesn't match the non-unique lines
+    let output = test_env.run_jj_in(
+        &repo_path,
+        ["diff", "--git", "--config=diff.algorithm=patience"],
+    );
+    insta::assert_snapshot!(output, @r"
+    diff --git a/file1 b/file1
+    index 7e1fdc5592..c58e744abc 100644
+    --- a/file1
+    +++ b/file1
+    @@ -1,7 +1,7 @@
+    +c
+     a
+     b
+    -c
+    -x
+    -x
+    -y
+    -y
+    +y
+    +y
+    +x
+    +x
+    [EOF]
+    ");
+
+    let output = test_env.run_jj_in(&repo_path, ["diff", "--config=diff.algorithm=myers"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Config error: Invalid type or value for diff.algorithm
+    Caused by: unknown variant `myers`, expected `histogram` or `patience`
+
+    For help, see https://jj-vcs.github.io/jj/latest/config/ or use `jj help -k config`.
+    [EOF]
+    [exit status: 1]
+    ");
+}
+
 #[test]
 fn test_diff_skipped_context() {
     let test_env = TestEnvironment::default();
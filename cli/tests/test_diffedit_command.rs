@@ -438,12 +438,13 @@ fn test_diffedit_external_tool_conflict_marker_style() {
     // File should be conflicted with no changes
     let output = test_env.run_jj_in(&repo_path, ["st"]);
     insta::assert_snapshot!(output, @r"
+    Warning: There are unresolved conflicts at these paths:
+    file    2-sided conflict
     The working copy has no changes.
     Working copy : mzvwutvl fb39e804 (conflict) (empty) (no description set)
     Parent commit: rlvkpnrz 3765cc27 side-a
     Parent commit: zsuskuln 8b3de837 side-b
-    Warning: There are unresolved conflicts at these paths:
-    file    2-sided conflict
+    Hint: Use `jj resolve` to resolve the conflicts with a merge tool, or edit the conflict markers in the files directly.
     [EOF]
     ");
 }
@@ -74,7 +74,7 @@ fn test_duplicate() {
     let output = test_env.run_jj_in(&repo_path, ["undo"]);
     insta::assert_snapshot!(output, @r"
     ------- stderr -------
-    Undid operation: 6eead29c6998 (2001-02-03 08:05:17) duplicate 1 commit(s)
+    Undid operation: 476923d6dec0 (2001-02-03 08:05:17) duplicate 1 commit(s)
     [EOF]
     ");
     let output = test_env.run_jj_in(&repo_path, ["duplicate" /* duplicates `c` */]);
@@ -2580,7 +2580,7 @@ fn test_undo_after_duplicate() {
     let output = test_env.run_jj_in(&repo_path, ["undo"]);
     insta::assert_snapshot!(output, @r"
     ------- stderr -------
-    Undid operation: d64d953f7d2b (2001-02-03 08:05:11) duplicate 1 commit(s)
+    Undid operation: 4ba60d9705dc (2001-02-03 08:05:11) duplicate 1 commit(s)
     [EOF]
     ");
     insta::assert_snapshot!(get_log_output(&test_env, &repo_path), @r"
@@ -365,6 +365,9 @@ fn test_evolog_with_no_template() {
 
     For more information, try '--help'.
     Hint: The following template aliases are defined:
+    - builtin_bookmark_list
+    - builtin_bookmark_list_verbose
+    - builtin_file_list_json
     - builtin_log_comfortable
     - builtin_log_compact
     - builtin_log_compact_full_description
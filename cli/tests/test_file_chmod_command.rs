@@ -111,6 +111,7 @@ fn test_chmod_regular_conflict() {
     insta::assert_snapshot!(output, @r"
     ------- stderr -------
     Warning: No matching entries for paths: nonexistent
+    Made executable: file
     Working copy now at: yostqsxw 2b11d002 conflict | (conflict) conflict
     Parent commit      : royxmykx 427fbd2f x | x
     Parent commit      : zsuskuln 3f83a26d n | n
@@ -210,6 +211,7 @@ fn test_chmod_file_dir_deletion_conflicts() {
     );
     insta::assert_snapshot!(output, @r"
     ------- stderr -------
+    Made executable: file
     Working copy now at: kmkuslsw 139dee15 file_deletion | (conflict) file_deletion
     Parent commit      : zsuskuln c51c9c55 file | file
     Parent commit      : royxmykx 6b18b3c1 deletion | deletion
@@ -241,3 +243,73 @@ fn test_chmod_file_dir_deletion_conflicts() {
     [EOF]
     ");
 }
+
+#[cfg(unix)]
+#[test]
+fn test_chmod_recursive_and_symlinks() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let repo_path = test_env.env_root().join("repo");
+
+    std::fs::create_dir_all(repo_path.join("scripts/sub")).unwrap();
+    std::fs::write(repo_path.join("scripts/a.sh"), "a\n").unwrap();
+    std::fs::write(repo_path.join("scripts/sub/b.sh"), "b\n").unwrap();
+    std::fs::write(repo_path.join("scripts/README"), "readme\n").unwrap();
+    std::os::unix::fs::symlink("a.sh", repo_path.join("scripts/link.sh")).unwrap();
+
+    // Directories are processed recursively, but symlinks are rejected
+    let output = test_env.run_jj_in(&repo_path, ["file", "chmod", "x", "scripts"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Error: Found a symlink at 'scripts/link.sh'.
+    Hint: Use `--skip-symlinks` to leave symlinks unchanged.
+    [EOF]
+    [exit status: 1]
+    ");
+
+    let output = test_env.run_jj_in(
+        &repo_path,
+        ["file", "chmod", "x", "scripts", "--skip-symlinks"],
+    );
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Made executable: scripts/README
+    Made executable: scripts/a.sh
+    Skipped symlink: scripts/link.sh
+    Made executable: scripts/sub/b.sh
+    Working copy now at: qpvuntsm 7d4a2913 (no description set)
+    Parent commit      : zzzzzzzz 00000000 (empty) (no description set)
+    Added 0 files, modified 3 files, removed 0 files
+    [EOF]
+    ");
+
+    // Filesets can select the files
+    let output = test_env.run_jj_in(
+        &repo_path,
+        [
+            "file",
+            "chmod",
+            "n",
+            "glob:scripts/**/*.sh",
+            "--skip-symlinks",
+        ],
+    );
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Made non-executable: scripts/a.sh
+    Skipped symlink: scripts/link.sh
+    Made non-executable: scripts/sub/b.sh
+    Working copy now at: qpvuntsm 12fd291a (no description set)
+    Parent commit      : zzzzzzzz 00000000 (empty) (no description set)
+    Added 0 files, modified 2 files, removed 0 files
+    [EOF]
+    ");
+
+    // Nothing to change
+    let output = test_env.run_jj_in(&repo_path, ["file", "chmod", "n", "scripts/a.sh"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Nothing changed.
+    [EOF]
+    ");
+}
@@ -12,6 +12,7 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::io::Write as _;
 use std::path;
 use std::path::Path;
 use std::path::PathBuf;
@@ -682,6 +683,7 @@ fn test_git_clone_ignore_working_copy(subprocess: bool) {
     The working copy has no changes.
     Working copy : sqpuoqvx 2ca1c979 (empty) (no description set)
     Parent commit: qomsplrm ebeb70d8 main | message
+    Hint: The working copy is stale (not updated since the working-copy commit was rewritten). Use `jj workspace update-stale` to update it.
     [EOF]
     ");
     }
@@ -1000,6 +1002,98 @@ fn test_git_clone_with_depth_subprocess() {
     ");
 }
 
+#[test]
+fn test_git_clone_with_filter() {
+    let test_env = TestEnvironment::default();
+    let clone_path = test_env.env_root().join("clone");
+    let git_repo_path = test_env.env_root().join("source");
+    let git_repo = git::init(git_repo_path);
+    set_up_non_empty_git_repo(&git_repo);
+    // The local transport only serves partial clones if allowed by the config
+    let mut config_file = std::fs::OpenOptions::new()
+        .append(true)
+        .open(git_repo.path().join("config"))
+        .unwrap();
+    config_file
+        .write_all(b"[uploadpack]\n\tallowFilter = true\n")
+        .unwrap();
+
+    let output = test_env.run_jj_in(
+        ".",
+        ["git", "clone", "--filter=blob:none", "source", "clone"],
+    );
+    insta::assert_snapshot!(output, @r#"
+    ------- stderr -------
+    Fetching into new repo in "$TEST_ENV/clone"
+    bookmark: main@origin [new] untracked
+    Setting the revset alias `trunk()` to `main@origin`
+    Working copy now at: sqpuoqvx 2ca1c979 (empty) (no description set)
+    Parent commit      : qomsplrm ebeb70d8 main | message
+    Added 1 files, modified 0 files, removed 0 files
+    [EOF]
+    "#);
+    let output = test_env.run_jj_in(&clone_path, ["file", "show", "file"]);
+    insta::assert_snapshot!(output, @r"
+    content[EOF]
+    ");
+
+    // Files added to the remote are fetched once they're read
+    let main_id = git_repo.find_reference("main").unwrap().id().detach();
+    git::add_commit(
+        &git_repo,
+        "refs/heads/main",
+        "new-file",
+        b"new content",
+        "add new file",
+        &[main_id],
+    );
+    let output = test_env.run_jj_in(&clone_path, ["git", "fetch"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    bookmark: main@origin [updated] tracked
+    [EOF]
+    ");
+    let output = test_env.run_jj_in(&clone_path, ["diff", "--git", "-r", "main"]);
+    insta::assert_snapshot!(output, @r"
+    diff --git a/new-file b/new-file
+    new file mode 100644
+    index 0000000000..47d2739ba2
+    --- /dev/null
+    +++ b/new-file
+    @@ -0,0 +1,1 @@
+    +new content
+    \ No newline at end of file
+    [EOF]
+    ");
+
+    // Files are fetched when they're checked out
+    git::add_commit(
+        &git_repo,
+        "refs/heads/main",
+        "another-file",
+        b"another content",
+        "add another file",
+        &[git_repo.find_reference("main").unwrap().id().detach()],
+    );
+    test_env.run_jj_in(&clone_path, ["git", "fetch"]).success();
+    test_env.run_jj_in(&clone_path, ["new", "main"]).success();
+    assert_eq!(
+        std::fs::read(clone_path.join("another-file")).unwrap(),
+        b"another content"
+    );
+
+    // Omitted trees can't be fetched on demand
+    let output = test_env.run_jj_in(".", ["git", "clone", "--filter=tree:0", "source", "clone2"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    error: invalid value 'tree:0' for '--filter <FILTER>': only `blob:` filters are supported
+
+    For more information, try '--help'.
+    [EOF]
+    [exit status: 2]
+    ");
+}
+
 #[test_case(false; "use git2 for remote calls")]
 #[test_case(true; "spawn a git subprocess for remote calls")]
 fn test_git_clone_invalid_immutable_heads(subprocess: bool) {
@@ -13,6 +13,7 @@
 // limitations under the License.
 
 use std::fmt::Write;
+use std::io::Write as _;
 use std::path::Path;
 
 use testutils::git;
@@ -195,6 +196,7 @@ fn test_git_colocated_unborn_bookmark() {
     The working copy has no changes.
     Working copy : royxmykx 0e146103 (empty) (no description set)
     Parent commit: kkmpptxz e3e01407 (no description set)
+    Hint: Git's HEAD is detached at the parent of the working-copy commit, which has no bookmark. Use `jj bookmark create <name> -r @-` to name it for Git tools.
     [EOF]
     ");
 
@@ -294,6 +296,77 @@ fn test_git_colocated_export_bookmarks_on_snapshot() {
         .to_string(), @"4d2c49a8f8e2f1ba61f48ba79e5f4a5faa6512cf");
 }
 
+#[test]
+fn test_git_colocated_export_reflog() {
+    let test_env = TestEnvironment::default();
+    let workspace_root = test_env.env_root().join("repo");
+    git::init(&workspace_root);
+    test_env
+        .run_jj_in(&workspace_root, ["git", "init", "--git-repo", "."])
+        .success();
+
+    test_env
+        .run_jj_in(&workspace_root, ["bookmark", "create", "-r@", "foo"])
+        .success();
+    test_env.run_jj_in(&workspace_root, ["new"]).success();
+    test_env
+        .run_jj_in(&workspace_root, ["bookmark", "set", "-r@", "foo"])
+        .success();
+
+    // Reflog messages refer to the parent of the operation that moved the ref
+    let read_reflog_messages = |path: &str| {
+        let log = std::fs::read_to_string(workspace_root.join(".git/logs").join(path)).unwrap();
+        log.lines()
+            .map(|line| line.split_once('\t').unwrap().1.to_owned() + "\n")
+            .collect::<String>()
+    };
+    insta::assert_snapshot!(read_reflog_messages("refs/heads/foo"), @r"
+    export from jj (parent operation eac759b9ab75)
+    export from jj (parent operation 7da709c3dcb7)
+    ");
+    insta::assert_snapshot!(read_reflog_messages("HEAD"), @"export from jj (parent operation fb5062747ccc)");
+    let output = test_env.run_jj_in(
+        &workspace_root,
+        [
+            "op",
+            "log",
+            "--no-graph",
+            "-T",
+            r#"id.short() ++ " " ++ description ++ "\n""#,
+        ],
+    );
+    insta::assert_snapshot!(output, @r"
+    333aaa3ce242 point bookmark foo to commit 4db490c88528133d579540b6900b8098f0c17701
+    7da709c3dcb7 new empty commit
+    fb5062747ccc create bookmark foo pointing to commit 230dd059e1b059aefc0da06a2e5a7dbf22362f22
+    eac759b9ab75 add workspace 'default'
+    000000000000 
+    [EOF]
+    ");
+}
+
+#[test]
+fn test_git_colocated_unsupported_ref_storage() {
+    let test_env = TestEnvironment::default();
+    let workspace_root = test_env.env_root().join("repo");
+    git::init(&workspace_root);
+    let mut config_file = std::fs::OpenOptions::new()
+        .append(true)
+        .open(workspace_root.join(".git/config"))
+        .unwrap();
+    writeln!(config_file, "[extensions]\n\trefStorage = reftable").unwrap();
+
+    let output = test_env.run_jj_in(&workspace_root, ["git", "init", "--git-repo", "."]);
+    insta::assert_snapshot!(output, @r#"
+    ------- stderr -------
+    Error: Failed to import refs from underlying Git repo
+    Caused by: Git ref storage format `reftable` is not supported
+    Hint: jj can only access refs stored in the "files" format. Run `git refs migrate --ref-format=files` to convert the repository.
+    [EOF]
+    [exit status: 1]
+    "#);
+}
+
 #[test]
 fn test_git_colocated_rebase_on_import() {
     let test_env = TestEnvironment::default();
@@ -520,18 +593,16 @@ fn test_git_colocated_conflicting_git_refs() {
         .run_jj_in(&workspace_root, ["bookmark", "create", "-r@", "main"])
         .success();
     let output = test_env.run_jj_in(&workspace_root, ["bookmark", "create", "-r@", "main/sub"]);
-    insta::with_settings!({filters => vec![("Failed to set: .*", "Failed to set: ...")]}, {
-        insta::assert_snapshot!(output, @r#"
-        ------- stderr -------
-        Created 1 bookmarks pointing to qpvuntsm 230dd059 main main/sub | (empty) (no description set)
-        Warning: Failed to export some bookmarks:
-          main/sub: Failed to set: ...
-        Hint: Git doesn't allow a branch name that looks like a parent directory of
-        another (e.g. `foo` and `foo/bar`). Try to rename the bookmarks that failed to
-        export or their "parent" bookmarks.
-        [EOF]
-        "#);
-    });
+    insta::assert_snapshot!(output, @r#"
+    ------- stderr -------
+    Created 1 bookmarks pointing to qpvuntsm 230dd059 main main/sub | (empty) (no description set)
+    Warning: Failed to export some bookmarks:
+      main/sub: Name conflicts with Git ref refs/heads/main
+    Hint: Git doesn't allow a branch name that looks like a parent directory of
+    another (e.g. `foo` and `foo/bar`). Try to rename the bookmarks that failed to
+    export or their "parent" bookmarks.
+    [EOF]
+    "#);
 }
 
 #[test]
@@ -693,13 +764,13 @@ fn test_git_colocated_rebase_dirty_working_copy() {
     // diverged. Therefore, the feature bookmark has change-delete conflict.
     let output = test_env.run_jj_in(&repo_path, ["status"]);
     insta::assert_snapshot!(output, @r"
+    Warning: These bookmarks have conflicts:
+      feature
+    Hint: Use `jj bookmark list` to see details. Use `jj bookmark set <name> -r <rev>` to resolve.
     Working copy changes:
     M file
     Working copy : rlvkpnrz 6bad94b1 feature?? | (no description set)
     Parent commit: qpvuntsm 3230d522 (no description set)
-    Warning: These bookmarks have conflicts:
-      feature
-    Hint: Use `jj bookmark list` to see details. Use `jj bookmark set <name> -r <rev>` to resolve.
     [EOF]
     ------- stderr -------
     Warning: Failed to export some bookmarks:
@@ -168,6 +168,145 @@ fn test_git_fetch_single_remote(subprocess: bool) {
     }
 }
 
+#[test_case(false; "use git2 for remote calls")]
+#[test_case(true; "spawn a git subprocess for remote calls")]
+fn test_git_fetch_ref_filter(subprocess: bool) {
+    let test_env = TestEnvironment::default();
+    if !subprocess {
+        test_env.add_config("git.subprocess = false");
+    }
+    test_env.add_config(r#"git.fetch-ref-filter = ["origin", "glob:release/*"]"#);
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let repo_path = test_env.env_root().join("repo");
+    let git_repo = add_git_remote(&test_env, &repo_path, "origin");
+    add_commit_to_branch(&git_repo, "release/1");
+    let feature_id = add_commit_to_branch(&git_repo, "feature");
+
+    // Only the matching remote bookmarks are imported
+    let output = test_env.run_jj_in(&repo_path, ["git", "fetch"]);
+    insta::allow_duplicates! {
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    bookmark: origin@origin    [new] untracked
+    bookmark: release/1@origin [new] untracked
+    [EOF]
+    ");
+    }
+    insta::allow_duplicates! {
+    insta::assert_snapshot!(get_bookmark_output(&test_env, &repo_path), @r"
+    origin@origin: qmyrypzk ab8b299e message
+    release/1@origin: vuyrxrkt f1adfd6f message
+    [EOF]
+    ");
+    }
+
+    // The excluded remote bookmark can be imported on demand
+    let output = test_env.run_jj_in(&repo_path, ["git", "import", "--bookmark", "feature"]);
+    insta::allow_duplicates! {
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    bookmark: feature@origin [new] untracked
+    [EOF]
+    ");
+    }
+
+    // Once imported, the remote bookmark is updated by fetch
+    git::add_commit(
+        &git_repo,
+        "refs/heads/feature",
+        "feature",
+        b"updated",
+        "updated",
+        &[feature_id],
+    );
+    let output = test_env.run_jj_in(&repo_path, ["git", "fetch"]);
+    insta::allow_duplicates! {
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    bookmark: feature@origin [updated] untracked
+    [EOF]
+    ");
+    }
+    insta::allow_duplicates! {
+    insta::assert_snapshot!(get_bookmark_output(&test_env, &repo_path), @r"
+    feature@origin: vwtznwrw 1b0a81e8 updated
+    origin@origin: qmyrypzk ab8b299e message
+    release/1@origin: vuyrxrkt f1adfd6f message
+    [EOF]
+    ");
+    }
+}
+
+#[test_case(false; "use git2 for remote calls")]
+#[test_case(true; "spawn a git subprocess for remote calls")]
+fn test_git_fetch_tags(subprocess: bool) {
+    let test_env = TestEnvironment::default();
+    if !subprocess {
+        test_env.add_config("git.subprocess = false");
+    }
+    let git_repo = init_git_remote(&test_env, "origin");
+    let origin_id = git_repo.find_reference("refs/heads/origin").unwrap().id();
+    let other_id = add_commit_to_branch(&git_repo, "other");
+    let create_tag = |name: &str, id: gix::ObjectId| {
+        git_repo
+            .reference(
+                format!("refs/tags/{name}"),
+                id,
+                gix::refs::transaction::PreviousValue::MustNotExist,
+                "create tag",
+            )
+            .unwrap();
+    };
+    create_tag("reachable", origin_id.detach());
+    create_tag("unreachable", other_id);
+
+    let fetch_tags = |mode: &str| {
+        let repo_path = test_env.env_root().join(mode);
+        test_env.run_jj_in(".", ["git", "init", mode]).success();
+        test_env
+            .run_jj_in(&repo_path, ["git", "remote", "add", "origin", "../origin"])
+            .success();
+        test_env
+            .run_jj_in(
+                &repo_path,
+                [
+                    "git",
+                    "fetch",
+                    "--branch=origin",
+                    &format!("--config=git.fetch-tags={mode}"),
+                ],
+            )
+            .success();
+        test_env.run_jj_in(&repo_path, ["tag", "list"])
+    };
+
+    // Only the tags pointing to the fetched commits are fetched by default.
+    // libgit2 also fetches the other tags.
+    let output = fetch_tags("reachable");
+    if subprocess {
+        insta::assert_snapshot!(output, @r"
+        reachable: qmyrypzk ab8b299e message
+        [EOF]
+        ");
+    } else {
+        insta::assert_snapshot!(output, @r"
+        reachable: qmyrypzk ab8b299e message
+        unreachable: qtzmqslk 8820d88a message
+        [EOF]
+        ");
+    }
+    insta::allow_duplicates! {
+    insta::assert_snapshot!(fetch_tags("none"), @"");
+    }
+    insta::allow_duplicates! {
+    insta::assert_snapshot!(fetch_tags("all"), @r"
+    reachable: qmyrypzk ab8b299e message
+    unreachable: qtzmqslk 8820d88a message
+    [EOF]
+    ");
+    }
+}
+
 #[test_case(false; "use git2 for remote calls")]
 #[test_case(true; "spawn a git subprocess for remote calls")]
 fn test_git_fetch_single_remote_all_remotes_flag(subprocess: bool) {
@@ -1477,7 +1616,7 @@ fn test_git_fetch_undo(subprocess: bool) {
     insta::allow_duplicates! {
     insta::assert_snapshot!(output, @r"
     ------- stderr -------
-    Undid operation: 4bd67fb242bc (2001-02-03 08:05:18) fetch from git remote(s) origin
+    Undid operation: afe6966ecf5c (2001-02-03 08:05:18) fetch from git remote(s) origin
     [EOF]
     ");
     }
@@ -2194,3 +2333,162 @@ fn test_git_fetch_preserve_commits_across_repos(subprocess: bool) {
     ");
     }
 }
+
+#[test]
+fn test_git_unshallow() {
+    let test_env = TestEnvironment::default();
+    let git_repo_path = test_env.env_root().join("source");
+    let git_repo = git::init(git_repo_path);
+    let commit1 = git::add_commit(&git_repo, "refs/heads/main", "file", b"1", "commit1", &[]);
+    let commit2 = git::add_commit(
+        &git_repo,
+        "refs/heads/main",
+        "file",
+        b"2",
+        "commit2",
+        &[commit1.commit_id],
+    );
+    git::add_commit(
+        &git_repo,
+        "refs/heads/main",
+        "file",
+        b"3",
+        "commit3",
+        &[commit2.commit_id],
+    );
+    test_env
+        .run_jj_in(".", ["git", "clone", "--depth=1", "source", "clone"])
+        .success();
+    let repo_path = test_env.env_root().join("clone");
+
+    let output = test_env.run_jj_in(&repo_path, ["git", "shallowinfo"]);
+    insta::assert_snapshot!(output, @r"
+    kykymqtp 89e1c5af main@origin | commit3
+    [EOF]
+    ");
+    insta::assert_snapshot!(get_log_output(&test_env, &repo_path), @r#"
+    @  230dd059e1b0 ""
+    │ ◆  89e1c5af6bbd "commit3" main@origin
+    ├─╯
+    ◆  000000000000 ""
+    [EOF]
+    "#);
+
+    let output = test_env.run_jj_in(&repo_path, ["git", "unshallow"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Reindexed 5 commits.
+    [EOF]
+    ");
+    insta::assert_snapshot!(get_log_output(&test_env, &repo_path), @r#"
+    @  230dd059e1b0 ""
+    │ ◆  89e1c5af6bbd "commit3" main@origin
+    │ ◆  14ed80f622f5 "commit2"
+    │ ◆  c66dc2639847 "commit1"
+    ├─╯
+    ◆  000000000000 ""
+    [EOF]
+    "#);
+
+    let output = test_env.run_jj_in(&repo_path, ["git", "shallowinfo"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    The repository is not shallow.
+    [EOF]
+    ");
+    let output = test_env.run_jj_in(&repo_path, ["git", "unshallow"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    The repository is not shallow.
+    [EOF]
+    ");
+}
+
+#[test]
+fn test_git_fetch_shallow() {
+    let test_env = TestEnvironment::default();
+    let git_repo_path = test_env.env_root().join("source");
+    let git_repo = git::init(git_repo_path);
+    let commit1 = git::add_commit(&git_repo, "refs/heads/main", "file", b"1", "commit1", &[]);
+    let commit2 = git::add_commit(
+        &git_repo,
+        "refs/heads/main",
+        "file",
+        b"2",
+        "commit2",
+        &[commit1.commit_id],
+    );
+    git::add_commit(
+        &git_repo,
+        "refs/heads/main",
+        "file",
+        b"3",
+        "commit3",
+        &[commit2.commit_id],
+    );
+    git_repo
+        .reference(
+            "refs/heads/old",
+            commit1.commit_id,
+            gix::refs::transaction::PreviousValue::MustNotExist,
+            "",
+        )
+        .unwrap();
+    test_env
+        .run_jj_in(".", ["git", "clone", "--deepen-not=old", "source", "clone"])
+        .success();
+    let repo_path = test_env.env_root().join("clone");
+    insta::assert_snapshot!(get_log_output(&test_env, &repo_path), @r#"
+    @  230dd059e1b0 ""
+    │ ◆  c66dc2639847 "commit1" old@origin
+    ├─╯
+    │ ◆  89e1c5af6bbd "commit3" main@origin
+    │ ◆  14ed80f622f5 "commit2"
+    ├─╯
+    ◆  000000000000 ""
+    [EOF]
+    "#);
+
+    // Deepening the history reindexes the commits at the shallow boundary
+    let output = test_env.run_jj_in(&repo_path, ["git", "fetch", "--depth=3"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Nothing changed.
+    Reindexed 5 commits.
+    [EOF]
+    ");
+    insta::assert_snapshot!(get_log_output(&test_env, &repo_path), @r#"
+    @  230dd059e1b0 ""
+    │ ◆  89e1c5af6bbd "commit3" main@origin
+    │ ◆  14ed80f622f5 "commit2"
+    │ ◆  c66dc2639847 "commit1" old@origin
+    ├─╯
+    ◆  000000000000 ""
+    [EOF]
+    "#);
+
+    let output = test_env.run_jj_in(
+        &repo_path,
+        ["git", "fetch", "--depth=1", "--deepen-since=yesterday"],
+    );
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    error: the argument '--depth <DEPTH>' cannot be used with '--deepen-since <DATE>'
+
+    Usage: jj git fetch --depth <DEPTH>
+
+    For more information, try '--help'.
+    [EOF]
+    [exit status: 2]
+    ");
+
+    test_env.add_config("git.subprocess = false");
+    let output = test_env.run_jj_in(&repo_path, ["git", "fetch", "--deepen-not=old"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Error: Shallow fetch by date or excluded refs requires the Git subprocess
+    Hint: Set `git.subprocess = true` to use the Git CLI.
+    [EOF]
+    [exit status: 1]
+    ");
+}
@@ -14,7 +14,11 @@
 
 use std::path::Path;
 use std::path::PathBuf;
+use std::time::Duration;
+use std::time::SystemTime;
+use std::time::UNIX_EPOCH;
 
+use indoc::indoc;
 use test_case::test_case;
 use testutils::git;
 
@@ -235,6 +239,230 @@ fn test_git_push_parent_bookmark(subprocess: bool) {
     }
 }
 
+#[test_case(false; "use git2 for remote calls")]
+#[test_case(true; "spawn a git subprocess for remote calls")]
+fn test_git_push_multiple_remotes(subprocess: bool) {
+    let (test_env, workspace_root) = set_up();
+    if !subprocess {
+        test_env.add_config("git.subprocess = false");
+    }
+    test_env.run_jj_in(".", ["git", "init", "mirror"]).success();
+    let mirror_git_repo_path = test_env
+        .env_root()
+        .join("mirror")
+        .join(".jj")
+        .join("repo")
+        .join("store")
+        .join("git");
+    test_env
+        .run_jj_in(
+            &workspace_root,
+            [
+                "git",
+                "remote",
+                "add",
+                "mirror",
+                mirror_git_repo_path.to_str().unwrap(),
+            ],
+        )
+        .success();
+    test_env
+        .run_jj_in(
+            &workspace_root,
+            ["describe", "bookmark1", "-m", "modified bookmark1 commit"],
+        )
+        .success();
+
+    // The same bookmark is pushed to each remote
+    let output = test_env.run_jj_in(
+        &workspace_root,
+        [
+            "git",
+            "push",
+            "--remote=origin",
+            "--remote=mirror",
+            "--allow-new",
+            "-bbookmark1",
+        ],
+    );
+    insta::allow_duplicates! {
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Changes to push to origin:
+      Move sideways bookmark bookmark1 from d13ecdbda2a2 to 4a7e4246fc4d
+    Changes to push to mirror:
+      Add bookmark bookmark1 to 4a7e4246fc4d
+    [EOF]
+    ");
+    }
+    insta::allow_duplicates! {
+    insta::assert_snapshot!(get_bookmark_output(&test_env, &workspace_root), @r"
+    bookmark1: xtvrqkyv 4a7e4246 (empty) modified bookmark1 commit
+      @mirror: xtvrqkyv 4a7e4246 (empty) modified bookmark1 commit
+      @origin: xtvrqkyv 4a7e4246 (empty) modified bookmark1 commit
+    bookmark2: rlzusymt 8476341e (empty) description 2
+      @origin: rlzusymt 8476341e (empty) description 2
+    [EOF]
+    ");
+    }
+
+    // Remotes that are already up to date are skipped
+    test_env
+        .run_jj_in(
+            &workspace_root,
+            ["describe", "bookmark2", "-m", "modified bookmark2 commit"],
+        )
+        .success();
+    test_env.add_config(r#"git.push = ["origin", "mirror"]"#);
+    let output = test_env.run_jj_in(&workspace_root, ["git", "push", "--tracked", "--dry-run"]);
+    insta::allow_duplicates! {
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Nothing to push to mirror.
+    Changes to push to origin:
+      Move sideways bookmark bookmark2 from 8476341eb395 to 48bea81df013
+    Dry-run requested, not pushing.
+    [EOF]
+    ");
+    }
+
+    // A failing remote doesn't prevent pushing to the others
+    test_env
+        .run_jj_in(
+            &workspace_root,
+            ["git", "remote", "add", "broken", "nonexistent"],
+        )
+        .success();
+    let output = test_env.run_jj_in(
+        &workspace_root,
+        [
+            "git",
+            "push",
+            "--remote=broken",
+            "--remote=origin",
+            "--allow-new",
+            "-bbookmark2",
+        ],
+    );
+    if subprocess {
+        insta::assert_snapshot!(output, @r"
+        ------- stderr -------
+        Changes to push to broken:
+          Add bookmark bookmark2 to 48bea81df013
+        Changes to push to origin:
+          Move sideways bookmark bookmark2 from 8476341eb395 to 48bea81df013
+        Warning: Failed to push to broken: Could not find repository at '$TEST_ENV/local/nonexistent'
+        Error: Failed to push to broken
+        [EOF]
+        [exit status: 1]
+        ");
+    } else {
+        insta::assert_snapshot!(output, @r"
+        ------- stderr -------
+        Changes to push to broken:
+          Add bookmark bookmark2 to 48bea81df013
+        Changes to push to origin:
+          Move sideways bookmark bookmark2 from 8476341eb395 to 48bea81df013
+        Warning: Failed to push to broken: Unexpected git error when pushing
+        Error: Failed to push to broken
+        [EOF]
+        [exit status: 1]
+        ");
+    }
+    insta::allow_duplicates! {
+    insta::assert_snapshot!(get_bookmark_output(&test_env, &workspace_root), @r"
+    bookmark1: xtvrqkyv 4a7e4246 (empty) modified bookmark1 commit
+      @mirror: xtvrqkyv 4a7e4246 (empty) modified bookmark1 commit
+      @origin: xtvrqkyv 4a7e4246 (empty) modified bookmark1 commit
+    bookmark2: rlzusymt 48bea81d (empty) modified bookmark2 commit
+      @origin: rlzusymt 48bea81d (empty) modified bookmark2 commit
+    [EOF]
+    ");
+    }
+}
+
+#[test]
+fn test_git_push_require_fetched() {
+    let (test_env, workspace_root) = set_up();
+    let fetch_times_path = workspace_root
+        .join(".jj")
+        .join("repo")
+        .join("git_fetch_times.json");
+    test_env
+        .run_jj_in(&workspace_root, ["describe", "bookmark1", "-m=modified"])
+        .success();
+
+    // The remote bookmarks were fetched by clone
+    let output = test_env.run_jj_in(
+        &workspace_root,
+        [
+            "git",
+            "push",
+            "--require-fetched",
+            "--dry-run",
+            "-b=bookmark1",
+        ],
+    );
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Changes to push to origin:
+      Move sideways bookmark bookmark1 from d13ecdbda2a2 to 362f96033795
+    Dry-run requested, not pushing.
+    [EOF]
+    ");
+
+    // Fetched too long ago
+    let two_days_ago = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .saturating_sub(Duration::from_secs(2 * 24 * 60 * 60))
+        .as_millis();
+    std::fs::write(
+        &fetch_times_path,
+        format!(r#"{{"remotes":{{"origin":{{"*":{two_days_ago}}}}}}}"#),
+    )
+    .unwrap();
+    let output = test_env.run_jj_in(
+        &workspace_root,
+        ["git", "push", "--require-fetched=1d", "-b=bookmark1"],
+    );
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Error: Refusing to push bookmark bookmark1@origin, which was last fetched 2 days ago
+    Hint: Run `jj git fetch --remote origin` first.
+    [EOF]
+    [exit status: 1]
+    ");
+
+    // Only other bookmarks were fetched
+    std::fs::write(
+        &fetch_times_path,
+        r#"{"remotes":{"origin":{"bookmark2":0}}}"#,
+    )
+    .unwrap();
+    test_env.add_config(r#"git.push-require-fetched = "1d""#);
+    let output = test_env.run_jj_in(&workspace_root, ["git", "push", "-b=bookmark1"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Error: Refusing to push bookmark bookmark1@origin, which was never fetched
+    Hint: Run `jj git fetch --remote origin` first.
+    [EOF]
+    [exit status: 1]
+    ");
+
+    // Fetching the bookmark allows pushing it
+    test_env
+        .run_jj_in(&workspace_root, ["git", "fetch", "-b=bookmark1"])
+        .success();
+    let output = test_env.run_jj_in(&workspace_root, ["git", "push", "-b=bookmark1"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Changes to push to origin:
+      Move sideways bookmark bookmark1 from d13ecdbda2a2 to 362f96033795
+    [EOF]
+    ");
+}
+
 #[test_case(false; "use git2 for remote calls")]
 #[test_case(true; "spawn a git subprocess for remote calls")]
 fn test_git_push_no_matching_bookmark(subprocess: bool) {
@@ -1019,6 +1247,7 @@ fn test_git_push_changes(subprocess: bool) {
     M file
     Working copy : yostqsxw 38cb417c bar
     Parent commit: yqosqzyt a050abf4 push-yostqsxwqrlt* push-yqosqzytrlsw | foo
+    Hint: These bookmarks have changes not pushed to their tracked remotes: push-yostqsxwqrlt. Use `jj git push` to push them.
     [EOF]
     ");
     }
@@ -1297,6 +1526,97 @@ fn test_git_push_existing_long_bookmark(subprocess: bool) {
     }
 }
 
+#[test]
+fn test_git_push_renamed_bookmark() {
+    let (test_env, workspace_root) = set_up();
+    test_env
+        .run_jj_in(&workspace_root, ["new", "bookmark1", "-m=foo"])
+        .success();
+    test_env
+        .run_jj_in(&workspace_root, ["bookmark", "create", "-r@", "my-feature"])
+        .success();
+
+    // Push local bookmark to remote bookmark of different name
+    let output = test_env.run_jj_in(
+        &workspace_root,
+        ["git", "push", "--allow-new", "-b=my-feature:feature"],
+    );
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Changes to push to origin:
+      Add bookmark my-feature:feature to 0c142ff3a34f
+    Warning: The working-copy commit in workspace 'default' became immutable, so a new commit has been created on top of it.
+    Working copy now at: znkkpsqq 0a4f4c42 (empty) (no description set)
+    Parent commit      : vruxwmqv 0c142ff3 feature@origin my-feature | (empty) foo
+    [EOF]
+    ");
+    // The remote bookmark isn't tracked by the local bookmark
+    insta::assert_snapshot!(get_bookmark_output(&test_env, &workspace_root), @r"
+    bookmark1: xtvrqkyv d13ecdbd (empty) description 1
+      @origin: xtvrqkyv d13ecdbd (empty) description 1
+    bookmark2: rlzusymt 8476341e (empty) description 2
+      @origin: rlzusymt 8476341e (empty) description 2
+    feature@origin: vruxwmqv 0c142ff3 (empty) foo
+    my-feature: vruxwmqv 0c142ff3 (empty) foo
+    [EOF]
+    ");
+
+    // Push again with the mapping configured
+    test_env
+        .run_jj_in(&workspace_root, ["new", "my-feature", "-m=bar"])
+        .success();
+    test_env
+        .run_jj_in(&workspace_root, ["bookmark", "set", "-r@", "my-feature"])
+        .success();
+    test_env.add_config(r#"git.push-remote-names.my-feature = "feature""#);
+    let output = test_env.run_jj_in(&workspace_root, ["git", "push", "-b=my-feature"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Changes to push to origin:
+      Move forward bookmark my-feature:feature from 0c142ff3a34f to 27f1334fa0c0
+    Warning: The working-copy commit in workspace 'default' became immutable, so a new commit has been created on top of it.
+    Working copy now at: lylxulpl 2f777d0e (empty) (no description set)
+    Parent commit      : kmkuslsw 27f1334f feature@origin my-feature | (empty) bar
+    [EOF]
+    ");
+    let output = test_env.run_jj_in(&workspace_root, ["git", "push", "-b=my-feature"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Bookmark feature@origin already matches my-feature
+    Nothing changed.
+    [EOF]
+    ");
+
+    // Cannot push two bookmarks to the same remote bookmark
+    let output = test_env.run_jj_in(
+        &workspace_root,
+        [
+            "git",
+            "push",
+            "--allow-new",
+            "-b=my-feature:shared",
+            "-b=bookmark1:shared",
+        ],
+    );
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Error: Cannot push multiple bookmarks to shared@origin
+    [EOF]
+    [exit status: 1]
+    ");
+
+    // Invalid mapping
+    let output = test_env.run_jj_in(&workspace_root, ["git", "push", "-b=my-feature:"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    error: invalid value 'my-feature:' for '--bookmark <BOOKMARK>': Invalid bookmark mapping `my-feature:`
+
+    For more information, try '--help'.
+    [EOF]
+    [exit status: 2]
+    ");
+}
+
 #[test_case(false; "use git2 for remote calls")]
 #[test_case(true; "spawn a git subprocess for remote calls")]
 fn test_git_push_unsnapshotted_change(subprocess: bool) {
@@ -1401,6 +1721,261 @@ fn test_git_push_no_description(subprocess: bool) {
         .success();
 }
 
+#[test]
+fn test_git_push_commit_checks() {
+    let (test_env, workspace_root) = set_up();
+    test_env.add_config(
+        r#"
+        [commit.checks]
+        max-subject-length = 10
+        level = "error"
+        "#,
+    );
+    test_env
+        .run_jj_in(
+            &workspace_root,
+            [
+                "describe",
+                "--no-verify",
+                "-m=a description that is too long",
+            ],
+        )
+        .success();
+    test_env
+        .run_jj_in(
+            &workspace_root,
+            ["bookmark", "create", "-r@", "my-bookmark"],
+        )
+        .success();
+    let output = test_env.run_jj_in(
+        &workspace_root,
+        ["git", "push", "--allow-new", "--bookmark", "my-bookmark"],
+    );
+    insta::assert_snapshot!(output, @r#"
+    ------- stderr -------
+    Error: The description "a description that is too long" does not pass the commit checks:
+      The subject line is longer than 10 characters
+    Hint: Use --no-verify to skip the checks.
+    [EOF]
+    [exit status: 1]
+    "#);
+
+    let output = test_env.run_jj_in(
+        &workspace_root,
+        [
+            "git",
+            "push",
+            "--allow-new",
+            "--bookmark",
+            "my-bookmark",
+            "--no-verify",
+        ],
+    );
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Changes to push to origin:
+      Add bookmark my-bookmark to 4d2917693da4
+    [EOF]
+    ");
+    let output = test_env.run_jj_in(
+        &workspace_root,
+        [
+            "op",
+            "log",
+            "-n1",
+            "--no-graph",
+            "-T",
+            r#"tags.contains("no-verify: true") ++ "\n""#,
+        ],
+    );
+    insta::assert_snapshot!(output, @r"
+    true
+    [EOF]
+    ");
+}
+
+#[cfg(unix)]
+#[test]
+fn test_git_push_rejected_by_remote_hook() {
+    use std::os::unix::fs::PermissionsExt as _;
+
+    let (test_env, workspace_root) = set_up();
+    let hooks_dir = test_env.env_root().join("origin/.jj/repo/store/git/hooks");
+    std::fs::create_dir_all(&hooks_dir).unwrap();
+    let hook_path = hooks_dir.join("pre-receive");
+    std::fs::write(
+        &hook_path,
+        "#!/bin/sh\necho 'Pushes to this repo are not allowed' >&2\nexit 1\n",
+    )
+    .unwrap();
+    std::fs::set_permissions(&hook_path, std::fs::Permissions::from_mode(0o755)).unwrap();
+
+    test_env
+        .run_jj_in(&workspace_root, ["describe", "-m=foo"])
+        .success();
+    test_env
+        .run_jj_in(
+            &workspace_root,
+            ["bookmark", "create", "-r@", "my-bookmark"],
+        )
+        .success();
+    let output = test_env.run_jj_in(
+        &workspace_root,
+        ["git", "push", "--allow-new", "--bookmark", "my-bookmark"],
+    );
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Changes to push to origin:
+      Add bookmark my-bookmark to 7283b790a895
+    remote: Pushes to this repo are not allowed        
+    Error: The remote refused to update some refs:
+      refs/heads/my-bookmark: pre-receive hook declined
+    Hint: The output from the remote above, if any, may contain more details.
+    [EOF]
+    [exit status: 1]
+    ");
+}
+
+#[cfg(unix)]
+#[test]
+fn test_git_push_split_into_chunks() {
+    use std::os::unix::fs::PermissionsExt as _;
+
+    let (test_env, workspace_root) = set_up();
+    test_env.add_config("git.push-max-pack-size = 150");
+    test_env
+        .run_jj_in(&workspace_root, ["new", "bookmark1", "-m=a"])
+        .success();
+    std::fs::write(workspace_root.join("a"), "a".repeat(100)).unwrap();
+    test_env
+        .run_jj_in(&workspace_root, ["new", "-m=b"])
+        .success();
+    std::fs::write(workspace_root.join("b"), "b".repeat(100)).unwrap();
+    test_env
+        .run_jj_in(&workspace_root, ["new", "-m=c"])
+        .success();
+    std::fs::write(workspace_root.join("c"), "c".repeat(100)).unwrap();
+    test_env
+        .run_jj_in(&workspace_root, ["bookmark", "move", "bookmark1", "--to=@"])
+        .success();
+
+    // Make the remote reject the second push
+    let hooks_dir = test_env.env_root().join("origin/.jj/repo/store/git/hooks");
+    std::fs::create_dir_all(&hooks_dir).unwrap();
+    let hook_path = hooks_dir.join("pre-receive");
+    std::fs::write(
+        &hook_path,
+        indoc! {"
+            #!/bin/sh
+            count=$(($(cat push-count 2>/dev/null || echo 0) + 1))
+            echo $count >push-count
+            test $count -ne 2
+        "},
+    )
+    .unwrap();
+    std::fs::set_permissions(&hook_path, std::fs::Permissions::from_mode(0o755)).unwrap();
+    let output = test_env.run_jj_in(&workspace_root, ["git", "push", "-b=bookmark1"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Changes to push to origin:
+      Move forward bookmark bookmark1 from d13ecdbda2a2 to 179f2156a944
+    Pushing chunk 1 of 3
+    Pushing chunk 2 of 3
+    Error: Failed to push chunk 2 of 3
+    Caused by: Remote refused the update of some refs
+    Hint: The chunks pushed so far have been recorded. Run `jj git push` again to push the remaining chunks.
+    [EOF]
+    [exit status: 1]
+    ");
+    insta::assert_snapshot!(get_bookmark_output(&test_env, &workspace_root), @r"
+    bookmark1: znkkpsqq 179f2156 c
+      @origin (behind by 2 commits): vruxwmqv 85778b13 a
+    bookmark2: rlzusymt 8476341e (empty) description 2
+      @origin: rlzusymt 8476341e (empty) description 2
+    [EOF]
+    ");
+
+    // Pushing again resumes from the chunk that failed
+    std::fs::remove_file(&hook_path).unwrap();
+    let output = test_env.run_jj_in(&workspace_root, ["git", "push", "-b=bookmark1"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Changes to push to origin:
+      Move forward bookmark bookmark1 from 85778b131bef to 179f2156a944
+    Pushing chunk 1 of 2
+    Pushing chunk 2 of 2
+    [EOF]
+    ");
+    insta::assert_snapshot!(get_bookmark_output(&test_env, &workspace_root), @r"
+    bookmark1: znkkpsqq 179f2156 c
+      @origin: znkkpsqq 179f2156 c
+    bookmark2: rlzusymt 8476341e (empty) description 2
+      @origin: rlzusymt 8476341e (empty) description 2
+    [EOF]
+    ");
+}
+
+#[cfg(unix)]
+#[test]
+fn test_git_push_split_into_chunks_with_merge() {
+    use std::os::unix::fs::PermissionsExt as _;
+
+    let (test_env, workspace_root) = set_up();
+    test_env.add_config("git.push-max-pack-size = 150");
+    test_env
+        .run_jj_in(&workspace_root, ["new", "bookmark1", "-m=x"])
+        .success();
+    std::fs::write(workspace_root.join("x"), "x".repeat(100)).unwrap();
+    test_env
+        .run_jj_in(&workspace_root, ["new", "-m=y"])
+        .success();
+    std::fs::write(workspace_root.join("y"), "y".repeat(100)).unwrap();
+    test_env
+        .run_jj_in(&workspace_root, ["new", "bookmark1", "-m=a"])
+        .success();
+    std::fs::write(workspace_root.join("a"), "a".repeat(100)).unwrap();
+    test_env
+        .run_jj_in(&workspace_root, ["new", "@", "description(y)", "-m=merge"])
+        .success();
+    test_env
+        .run_jj_in(&workspace_root, ["bookmark", "move", "bookmark1", "--to=@"])
+        .success();
+
+    // Make the remote reject updates which aren't fast-forwards
+    let hooks_dir = test_env.env_root().join("origin/.jj/repo/store/git/hooks");
+    std::fs::create_dir_all(&hooks_dir).unwrap();
+    let hook_path = hooks_dir.join("pre-receive");
+    std::fs::write(
+        &hook_path,
+        indoc! {"
+            #!/bin/sh
+            while read old new ref; do
+                git merge-base --is-ancestor $old $new || exit 1
+            done
+        "},
+    )
+    .unwrap();
+    std::fs::set_permissions(&hook_path, std::fs::Permissions::from_mode(0o755)).unwrap();
+    // The merged commits are pushed along with the merge, not by moving the
+    // bookmark to them
+    let output = test_env.run_jj_in(&workspace_root, ["git", "push", "-b=bookmark1"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Changes to push to origin:
+      Move forward bookmark bookmark1 from d13ecdbda2a2 to 9f644cf04f59
+    Pushing chunk 1 of 2
+    Pushing chunk 2 of 2
+    [EOF]
+    ");
+    insta::assert_snapshot!(get_bookmark_output(&test_env, &workspace_root), @r"
+    bookmark1: kpqxywon 9f644cf0 (empty) merge
+      @origin: kpqxywon 9f644cf0 (empty) merge
+    bookmark2: rlzusymt 8476341e (empty) description 2
+      @origin: rlzusymt 8476341e (empty) description 2
+    [EOF]
+    ");
+}
+
 #[test_case(false; "use git2 for remote calls")]
 #[test_case(true; "spawn a git subprocess for remote calls")]
 fn test_git_push_no_description_in_immutable(subprocess: bool) {
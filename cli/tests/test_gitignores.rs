@@ -104,6 +104,81 @@ fn test_gitignores_relative_excludes_file_path() {
     ");
 }
 
+#[test]
+fn test_ignore_sources() {
+    let test_env = TestEnvironment::default();
+    test_env
+        .run_jj_in(".", ["git", "init", "--colocate", "repo"])
+        .success();
+    let workspace_root = test_env.env_root().join("repo");
+
+    // Each source overrides the ones with lower precedence
+    std::fs::write(
+        test_env.env_root().join("global-ignores"),
+        "file1\nfile2\nfile3\nfile4\nfile5\n",
+    )
+    .unwrap();
+    test_env.add_config(r#"snapshot.excludes-file = "../global-ignores""#);
+    std::fs::write(
+        workspace_root.join(".git").join("info").join("exclude"),
+        "!file2\n",
+    )
+    .unwrap();
+    std::fs::write(workspace_root.join(".jj").join("exclude"), "!file3\n").unwrap();
+    std::fs::write(workspace_root.join(".gitignore"), "!file4\n!file5\n").unwrap();
+    std::fs::create_dir(workspace_root.join("dir")).unwrap();
+    std::fs::write(workspace_root.join("dir").join(".ignore"), "/file5\n").unwrap();
+    for name in ["file1", "file2", "file3", "file4", "file5"] {
+        std::fs::write(workspace_root.join(name), "").unwrap();
+        std::fs::write(workspace_root.join("dir").join(name), "").unwrap();
+    }
+
+    let output = test_env.run_jj_in(&workspace_root, ["file", "list"]);
+    insta::assert_snapshot!(output.normalize_backslash(), @r"
+    .gitignore
+    dir/.ignore
+    dir/file2
+    dir/file3
+    dir/file4
+    file2
+    file3
+    file4
+    file5
+    [EOF]
+    ");
+
+    let output = test_env.run_jj_in(
+        &workspace_root,
+        [
+            "debug",
+            "ignores",
+            "file1",
+            "file2",
+            "file3",
+            "dir/file4",
+            "dir/file5",
+            "dir",
+        ],
+    );
+    insta::assert_snapshot!(output.normalize_backslash(), @r#"
+    file1: ignored: pattern "file1" in $TEST_ENV/repo/../global-ignores
+    file2: not ignored: pattern "!file2" in $TEST_ENV/repo/.git/info/exclude
+    file3: not ignored: pattern "!file3" in $TEST_ENV/repo/.jj/exclude
+    dir/file4: not ignored: pattern "!file4" in $TEST_ENV/repo/.gitignore
+    dir/file5: ignored: pattern "/file5" in $TEST_ENV/repo/dir/.ignore
+    dir: not ignored
+    [EOF]
+    "#);
+
+    // Contents of an ignored directory are ignored
+    std::fs::write(workspace_root.join(".ignore"), "dir/\n").unwrap();
+    let output = test_env.run_jj_in(&workspace_root, ["debug", "ignores", "dir/file4"]);
+    insta::assert_snapshot!(output.normalize_backslash(), @r#"
+    dir/file4: ignored because dir is ignored: pattern "dir/" in $TEST_ENV/repo/.ignore
+    [EOF]
+    "#);
+}
+
 #[test]
 fn test_gitignores_ignored_file_in_target_commit() {
     let test_env = TestEnvironment::default();
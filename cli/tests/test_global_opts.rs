@@ -12,6 +12,7 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::collections::HashSet;
 use std::ffi::OsString;
 
 use indoc::indoc;
@@ -1062,7 +1063,9 @@ fn test_help() {
           --ignore-working-copy          Don't snapshot the working copy, and don't update it
           --ignore-immutable             Allow rewriting immutable commits
           --at-operation <AT_OPERATION>  Operation to load the repo at [aliases: at-op]
+          --at-workspace <WORKSPACE>     Workspace whose working-copy commit to operate on
           --debug                        Enable debug logging
+          --trace-output <FILE>          Write a performance trace of the command to this file
           --color <WHEN>                 When to colorize output [possible values: always, never, debug,
                                          auto]
           --quiet                        Silence non-primary command output
@@ -1093,3 +1096,34 @@ fn test_debug_logging_enabled() {
     // Luckily, insta will print this in colour when reviewing.
     insta::assert_snapshot!(log_line, @"[32m INFO[0m [2mjj_cli::cli_util[0m[2m:[0m debug logging enabled");
 }
+
+#[test]
+fn test_trace_output() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let repo_path = test_env.env_root().join("repo");
+
+    let trace_path = test_env.env_root().join("trace.json");
+    let trace_arg = format!("--trace-output={}", trace_path.display());
+    test_env
+        .run_jj_in(&repo_path, ["log", &trace_arg])
+        .success();
+    let trace: serde_json::Value =
+        serde_json::from_slice(&std::fs::read(&trace_path).unwrap()).unwrap();
+    let span_names: HashSet<_> = trace
+        .as_array()
+        .unwrap()
+        .iter()
+        .filter_map(|event| event["name"].as_str())
+        .collect();
+    assert!(span_names.contains("snapshot"), "{span_names:?}");
+    assert!(span_names.contains("evaluate"), "{span_names:?}");
+
+    let output = test_env.run_jj_in(&repo_path, ["log", "--trace-output=nonexistent/trace.json"]);
+    insta::assert_snapshot!(output.strip_stderr_last_line(), @r"
+    ------- stderr -------
+    Error: Failed to create trace file nonexistent/trace.json
+    [EOF]
+    [exit status: 1]
+    ");
+}
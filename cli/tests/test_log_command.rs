@@ -45,6 +45,9 @@ fn test_log_with_no_template() {
 
     For more information, try '--help'.
     Hint: The following template aliases are defined:
+    - builtin_bookmark_list
+    - builtin_bookmark_list_verbose
+    - builtin_file_list_json
     - builtin_log_comfortable
     - builtin_log_compact
     - builtin_log_compact_full_description
@@ -1081,6 +1084,94 @@ fn test_log_limit() {
     ");
 }
 
+#[test]
+fn test_log_follow_renames() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let repo_path = test_env.env_root().join("repo");
+
+    std::fs::write(repo_path.join("file1"), "foo\n").unwrap();
+    test_env
+        .run_jj_in(&repo_path, ["commit", "-m", "add file1"])
+        .success();
+    std::fs::write(repo_path.join("file1"), "foo\nbar\n").unwrap();
+    test_env
+        .run_jj_in(&repo_path, ["commit", "-m", "modify file1"])
+        .success();
+    std::fs::rename(repo_path.join("file1"), repo_path.join("file2")).unwrap();
+    std::fs::write(repo_path.join("other"), "other\n").unwrap();
+    test_env
+        .run_jj_in(&repo_path, ["commit", "-m", "rename file1 to file2"])
+        .success();
+    std::fs::write(repo_path.join("file1"), "unrelated\n").unwrap();
+    std::fs::write(repo_path.join("file2"), "foo\nbar\nbaz\n").unwrap();
+    test_env
+        .run_jj_in(&repo_path, ["commit", "-m", "modify file2"])
+        .success();
+
+    // Without --follow, the history stops at the rename
+    let output = test_env.run_jj_in(
+        &repo_path,
+        ["log", "-T", "description", "--summary", "file2"],
+    );
+    insta::assert_snapshot!(output, @r"
+    ○  modify file2
+    │  M file2
+    ○  rename file1 to file2
+    │  R {file1 => file2}
+    ~
+    [EOF]
+    ");
+
+    // The unrelated new file1 and the other file in the rename commit aren't
+    // shown
+    let output = test_env.run_jj_in(
+        &repo_path,
+        ["log", "-T", "description", "--git", "--follow", "file2"],
+    );
+    insta::assert_snapshot!(output, @r"
+    ○  modify file2
+    │  diff --git a/file2 b/file2
+    │  index 3bd1f0e297..86e041dad6 100644
+    │  --- a/file2
+    │  +++ b/file2
+    │  @@ -1,2 +1,3 @@
+    │   foo
+    │   bar
+    │  +baz
+    ○  rename file1 to file2
+    │  diff --git a/file1 b/file2
+    │  similarity index 100%
+    │  rename from file1
+    │  rename to file2
+    ○  modify file1
+    │  diff --git a/file1 b/file1
+    │  index 257cc5642c..3bd1f0e297 100644
+    │  --- a/file1
+    │  +++ b/file1
+    │  @@ -1,1 +1,2 @@
+    │   foo
+    │  +bar
+    ○  add file1
+    │  diff --git a/file1 b/file1
+    ~  new file mode 100644
+       index 0000000000..257cc5642c
+       --- /dev/null
+       +++ b/file1
+       @@ -0,0 +1,1 @@
+       +foo
+    [EOF]
+    ");
+
+    let output = test_env.run_jj_in(&repo_path, ["log", "--follow", "file1", "file2"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Error: --follow requires exactly one file path
+    [EOF]
+    [exit status: 1]
+    ");
+}
+
 #[test]
 fn test_log_warn_path_might_be_revset() {
     let test_env = TestEnvironment::default();
@@ -1587,6 +1678,90 @@ fn test_log_diff_stat_width() {
     ");
 }
 
+#[test]
+fn test_log_graph_format() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let repo_path = test_env.env_root().join("repo");
+
+    test_env
+        .run_jj_in(&repo_path, ["describe", "-m", "initial"])
+        .success();
+    test_env
+        .run_jj_in(&repo_path, ["new", "-m", "main \"1\""])
+        .success();
+    test_env
+        .run_jj_in(&repo_path, ["new", "-m", "main 2"])
+        .success();
+    test_env
+        .run_jj_in(&repo_path, ["new", "@--", "-m", "side"])
+        .success();
+    test_env
+        .run_jj_in(
+            &repo_path,
+            ["new", "-m", "merge", r#"description("main 2")"#, "@"],
+        )
+        .success();
+    let template = r#"commit_id.short() ++ "\n" ++ description"#;
+
+    // Elided and missing edges are included
+    let output = test_env.run_jj_in(
+        &repo_path,
+        [
+            "log",
+            "--graph-format=dot",
+            "-T",
+            template,
+            "-r",
+            "@ | @- | description(initial)",
+        ],
+    );
+    insta::assert_snapshot!(output, @r#"
+    digraph {
+      "ebb742b888d682f312af58d693bf787d9c04288e" [label="ebb742b888d6\lmerge\l"];
+      "ebb742b888d682f312af58d693bf787d9c04288e" -> "0fd192b93caa3a4f15037d20f18500e518cd2647";
+      "ebb742b888d682f312af58d693bf787d9c04288e" -> "5dab80827d1312681a3f0c060b3189c154ff61d4";
+      "5dab80827d1312681a3f0c060b3189c154ff61d4" [label="5dab80827d13\lside\l"];
+      "5dab80827d1312681a3f0c060b3189c154ff61d4" -> "68e1101283b0a6c4694f92fab85bcd90f0c83652";
+      "0fd192b93caa3a4f15037d20f18500e518cd2647" [label="0fd192b93caa\lmain 2\l"];
+      "0fd192b93caa3a4f15037d20f18500e518cd2647" -> "68e1101283b0a6c4694f92fab85bcd90f0c83652" [style=dashed];
+      "68e1101283b0a6c4694f92fab85bcd90f0c83652" [label="68e1101283b0\linitial\l"];
+      "0000000000000000000000000000000000000000" [label="~", shape=plaintext];
+      "68e1101283b0a6c4694f92fab85bcd90f0c83652" -> "0000000000000000000000000000000000000000" [style=dotted];
+    }
+    [EOF]
+    "#);
+
+    let output = test_env.run_jj_in(
+        &repo_path,
+        [
+            "log",
+            "--graph-format=json",
+            "-T",
+            "description",
+            "-r",
+            "@ | description(initial)",
+        ],
+    );
+    insta::assert_snapshot!(output, @r#"
+    {"change_id":"royxmykxtrkrqppotnrvutxlvrvqyxmy","commit_id":"ebb742b888d682f312af58d693bf787d9c04288e","edges":[{"target":"68e1101283b0a6c4694f92fab85bcd90f0c83652","type":"elided"}],"label":"merge"}
+    {"change_id":"qpvuntsmwlqtpsluzzsnyyzlmlwvmlnu","commit_id":"68e1101283b0a6c4694f92fab85bcd90f0c83652","edges":[{"target":"0000000000000000000000000000000000000000","type":"missing"}],"label":"initial"}
+    [EOF]
+    "#);
+
+    let output = test_env.run_jj_in(&repo_path, ["log", "--graph-format=json", "--no-graph"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    error: the argument '--graph-format <FORMAT>' cannot be used with '--no-graph'
+
+    Usage: jj log --graph-format <FORMAT> [FILESETS]...
+
+    For more information, try '--help'.
+    [EOF]
+    [exit status: 2]
+    ");
+}
+
 #[test]
 fn test_elided() {
     // Test that elided commits are shown as synthetic nodes.
@@ -1830,3 +2005,37 @@ fn test_log_full_description_template() {
     [EOF]
     ");
 }
+
+#[test]
+fn test_log_format_description_first_line() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let repo_path = test_env.env_root().join("repo");
+
+    test_env
+        .run_jj_in(
+            &repo_path,
+            ["describe", "-m", "一二三四五 is a long description\n\nbody"],
+        )
+        .success();
+    test_env.add_config(
+        r#"template-aliases.'format_description_first_line(description)' = 'truncate_middle(16, description.first_line(), "…")'"#,
+    );
+
+    let output = test_env.run_jj_in(&repo_path, ["log"]);
+    insta::assert_snapshot!(output, @r"
+    @  qpvuntsm test.user@example.com 2001-02-03 08:05:08 49a7a95b
+    │  (empty) 一二三四…ription
+    ◆  zzzzzzzz root() 00000000
+    [EOF]
+    ");
+    let output = test_env.run_jj_in(&repo_path, ["op", "log", "-n1"]);
+    insta::assert_snapshot!(output, @r"
+    @  246a10a0772e test-username@host.example.com 2001-02-03 04:05:08.000 +07:00 - 2001-02-03 04:05:08.000 +07:00
+    │  describe…2362f22
+    │  args: jj describe -m '一二三四五 is a long description
+    │
+    │  body'
+    [EOF]
+    ");
+}
@@ -0,0 +1,124 @@
+// Copyright 2025 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::common::TestEnvironment;
+
+#[test]
+fn test_meta_set_get_unset() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let repo_path = test_env.env_root().join("repo");
+
+    test_env
+        .run_jj_in(&repo_path, ["describe", "-mcommit1"])
+        .success();
+    test_env
+        .run_jj_in(&repo_path, ["new", "-mcommit2"])
+        .success();
+
+    let output = test_env.run_jj_in(
+        &repo_path,
+        ["meta", "set", "-r@-", "review-url", "https://example.com/1"],
+    );
+    insta::assert_snapshot!(output, @"");
+    let output = test_env.run_jj_in(&repo_path, ["meta", "set", "-r@-", "ci", "passed"]);
+    insta::assert_snapshot!(output, @"");
+    let output = test_env.run_jj_in(&repo_path, ["meta", "set", "-r@-", "ci", "passed"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Nothing changed.
+    [EOF]
+    ");
+    let output = test_env.run_jj_in(&repo_path, ["meta", "set", "bad key", "value"]);
+    insta::assert_snapshot!(output, @r#"
+    ------- stderr -------
+    Error: Invalid metadata key: "bad key"
+    [EOF]
+    [exit status: 1]
+    "#);
+
+    let output = test_env.run_jj_in(&repo_path, ["meta", "get", "-r@-", "review-url"]);
+    insta::assert_snapshot!(output, @r"
+    https://example.com/1
+    [EOF]
+    ");
+    let output = test_env.run_jj_in(&repo_path, ["meta", "get", "review-url"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Error: No metadata review-url on change kkmpptxzrspx
+    [EOF]
+    [exit status: 1]
+    ");
+    let output = test_env.run_jj_in(&repo_path, ["meta", "list", "-r@-"]);
+    insta::assert_snapshot!(output, @r"
+    ci: passed
+    review-url: https://example.com/1
+    [EOF]
+    ");
+    let output = test_env.run_jj_in(&repo_path, ["meta", "list"]);
+    insta::assert_snapshot!(output, @"");
+
+    // Metadata follows the change when it's rewritten
+    test_env
+        .run_jj_in(&repo_path, ["describe", "-r@-", "-mcommit1 updated"])
+        .success();
+    let template = r#"description.first_line() ++ " " ++ self.metadata("review-url") ++ "\n""#;
+    let output = test_env.run_jj_in(&repo_path, ["log", "--no-graph", "-T", template]);
+    insta::assert_snapshot!(output, @r"
+    commit2 
+    commit1 updated https://example.com/1
+     
+    [EOF]
+    ");
+
+    let output = test_env.run_jj_in(&repo_path, ["meta", "unset", "-r@-", "review-url"]);
+    insta::assert_snapshot!(output, @"");
+    let output = test_env.run_jj_in(&repo_path, ["meta", "unset", "-r@-", "review-url"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Nothing changed.
+    [EOF]
+    ");
+    let output = test_env.run_jj_in(&repo_path, ["meta", "list", "-r@-"]);
+    insta::assert_snapshot!(output, @r"
+    ci: passed
+    [EOF]
+    ");
+
+    let output = test_env.run_jj_in(
+        &repo_path,
+        [
+            "op",
+            "log",
+            "-n3",
+            "--no-graph",
+            "-T",
+            "description ++ \"\\n\"",
+        ],
+    );
+    insta::assert_snapshot!(output, @r"
+    unset metadata review-url on change qpvuntsmwlqt
+    describe commit caf975d0989a4e84d6d2fd1047ea03663244969c
+    set metadata ci on change qpvuntsmwlqt
+    [EOF]
+    ");
+
+    // Undo restores the metadata
+    test_env.run_jj_in(&repo_path, ["undo"]).success();
+    let output = test_env.run_jj_in(&repo_path, ["meta", "get", "-r@-", "review-url"]);
+    insta::assert_snapshot!(output, @r"
+    https://example.com/1
+    [EOF]
+    ");
+}
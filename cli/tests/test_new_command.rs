@@ -78,6 +78,33 @@ fn test_new() {
     ");
 }
 
+#[test]
+fn test_new_with_author_and_dates() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let repo_path = test_env.env_root().join("repo");
+
+    test_env
+        .run_jj_in(
+            &repo_path,
+            [
+                "new",
+                "--author=Ove Ridder <ove.ridder@example.com>",
+                "--author-date=2020-01-02T03:04:05+06:00",
+                "--committer-date=2020-01-03 10:00:00Z",
+            ],
+        )
+        .success();
+    let template = r#"author ++ " " ++ author.timestamp() ++ "\n" ++ committer ++ " " ++ committer.timestamp()"#;
+    let output = test_env.run_jj_in(&repo_path, ["log", "-r@", "-T", template]);
+    insta::assert_snapshot!(output, @r"
+    @  Ove Ridder <ove.ridder@example.com> 2020-01-02 03:04:05.000 +06:00
+    │  Test User <test.user@example.com> 2020-01-03 10:00:00.000 +00:00
+    ~
+    [EOF]
+    ");
+}
+
 #[test]
 fn test_new_merge() {
     let test_env = TestEnvironment::default();
@@ -541,6 +568,115 @@ fn test_new_insert_before_root() {
     ");
 }
 
+#[test]
+fn test_new_insert_before_edge() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let repo_path = test_env.env_root().join("repo");
+    setup_before_insertion(&test_env, &repo_path);
+    insta::assert_snapshot!(get_short_log_output(&test_env, &repo_path), @r"
+    @    F
+    ├─╮
+    │ ○  E
+    ○ │  D
+    ├─╯
+    │ ○  C
+    │ ○  B
+    │ ○  A
+    ├─╯
+    ◆  root
+    [EOF]
+    ");
+
+    // Insert only on the edge from E to the merge commit F
+    let output = test_env.run_jj_in(
+        &repo_path,
+        ["new", "-m", "G", "--insert-before", "F", "--edge", "E"],
+    );
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Rebased 1 descendant commits
+    Working copy now at: kxryzmor eaa3a472 (empty) G
+    Parent commit      : znkkpsqq 41a89ffc E | (empty) E
+    [EOF]
+    ");
+    insta::assert_snapshot!(get_short_log_output(&test_env, &repo_path), @r"
+    ○    F
+    ├─╮
+    │ @  G
+    │ ○  E
+    ○ │  D
+    ├─╯
+    │ ○  C
+    │ ○  B
+    │ ○  A
+    ├─╯
+    ◆  root
+    [EOF]
+    ");
+
+    // The position of the replaced parent is preserved
+    test_env.run_jj_in(&repo_path, ["undo"]).success();
+    test_env
+        .run_jj_in(
+            &repo_path,
+            ["new", "-m", "G", "--insert-before", "F", "--edge", "D"],
+        )
+        .success();
+    let template = "parents.map(|c| c.description().first_line())";
+    let output = test_env.run_jj_in(&repo_path, ["log", "--no-graph", "-rF", "-T", template]);
+    insta::assert_snapshot!(output, @"G E[EOF]");
+
+    // --edge must select a parent of the --insert-before commits
+    test_env.run_jj_in(&repo_path, ["undo"]).success();
+    let output = test_env.run_jj_in(
+        &repo_path,
+        ["new", "-m", "G", "--insert-before", "F", "--edge", "A"],
+    );
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Error: Commit 5ef24e4bf2be is not a parent of any of the --insert-before commits
+    [EOF]
+    [exit status: 1]
+    ");
+
+    // Each --insert-before commit must have a selected parent
+    let output = test_env.run_jj_in(
+        &repo_path,
+        [
+            "new",
+            "-m",
+            "G",
+            "--insert-before",
+            "F",
+            "--insert-before",
+            "C",
+            "--edge",
+            "E",
+        ],
+    );
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Error: None of the parents of commit 83376b270925 were selected by --edge
+    [EOF]
+    [exit status: 1]
+    ");
+
+    // --edge requires --insert-before
+    let output = test_env.run_jj_in(&repo_path, ["new", "--edge", "E"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    error: the following required arguments were not provided:
+      --insert-before <REVSETS>
+
+    Usage: jj new --insert-before <REVSETS> --edge <REVSETS> [REVSETS]...
+
+    For more information, try '--help'.
+    [EOF]
+    [exit status: 2]
+    ");
+}
+
 #[test]
 fn test_new_insert_after_before() {
     let test_env = TestEnvironment::default();
@@ -162,6 +162,9 @@ fn test_op_log_with_no_template() {
 
     For more information, try '--help'.
     Hint: The following template aliases are defined:
+    - builtin_bookmark_list
+    - builtin_bookmark_list_verbose
+    - builtin_file_list_json
     - builtin_log_comfortable
     - builtin_log_compact
     - builtin_log_compact_full_description
@@ -196,6 +199,102 @@ fn test_op_log_limit() {
     ");
 }
 
+#[test]
+fn test_op_log_affects() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let repo_path = test_env.env_root().join("repo");
+    test_env
+        .run_jj_in(&repo_path, ["describe", "-m", "first"])
+        .success();
+    test_env
+        .run_jj_in(&repo_path, ["bookmark", "create", "-r@", "main"])
+        .success();
+    test_env
+        .run_jj_in(&repo_path, ["new", "-m", "second"])
+        .success();
+    test_env
+        .run_jj_in(&repo_path, ["bookmark", "create", "-r@", "other"])
+        .success();
+    test_env
+        .run_jj_in(&repo_path, ["bookmark", "move", "main", "--to=@"])
+        .success();
+    test_env
+        .run_jj_in(&repo_path, ["abandon", "description(first)"])
+        .success();
+
+    let output = test_env.run_jj_in(
+        &repo_path,
+        ["op", "log", "-Tdescription", "--affects-bookmark=main"],
+    );
+    insta::assert_snapshot!(output, @r"
+    @  abandon commit fa15625b4a986997697639dfc2844138900c79f2
+    ○  point bookmark main to commit 3dc07d5eb676271b2a5661b1403558758076137d
+    ○  create bookmark main pointing to commit fa15625b4a986997697639dfc2844138900c79f2
+    [EOF]
+    ");
+
+    // Glob pattern, no graph, and limit applied after filtering
+    let output = test_env.run_jj_in(
+        &repo_path,
+        [
+            "op",
+            "log",
+            r#"-Tdescription ++ "\n""#,
+            "--affects-bookmark=glob:*",
+            "--no-graph",
+            "--limit=2",
+        ],
+    );
+    insta::assert_snapshot!(output, @r"
+    abandon commit fa15625b4a986997697639dfc2844138900c79f2
+    point bookmark main to commit 3dc07d5eb676271b2a5661b1403558758076137d
+    [EOF]
+    ");
+
+    // The commit is resolved at the --at-op operation
+    let output = test_env.run_jj_in(
+        &repo_path,
+        [
+            "op",
+            "log",
+            "--at-op=@-",
+            "-Tdescription",
+            "--affects-commit=description(first)",
+        ],
+    );
+    insta::assert_snapshot!(output, @r"
+    @  point bookmark main to commit 3dc07d5eb676271b2a5661b1403558758076137d
+    ○  new empty commit
+    ○  create bookmark main pointing to commit fa15625b4a986997697639dfc2844138900c79f2
+    ○  describe commit 230dd059e1b059aefc0da06a2e5a7dbf22362f22
+    [EOF]
+    ");
+    let output = test_env.run_jj_in(
+        &repo_path,
+        ["op", "log", "-Tdescription", "--affects-commit=root()"],
+    );
+    insta::assert_snapshot!(output, @"");
+
+    // Both filters match operations affecting either
+    let output = test_env.run_jj_in(
+        &repo_path,
+        [
+            "op",
+            "log",
+            "-Tdescription",
+            "--affects-bookmark=other",
+            "--affects-commit=@",
+            "--reversed",
+        ],
+    );
+    insta::assert_snapshot!(output, @r"
+    ○  create bookmark other pointing to commit 3dc07d5eb676271b2a5661b1403558758076137d
+    @  abandon commit fa15625b4a986997697639dfc2844138900c79f2
+    [EOF]
+    ");
+}
+
 #[test]
 fn test_op_log_no_graph() {
     let test_env = TestEnvironment::default();
@@ -1122,7 +1221,7 @@ fn test_op_diff() {
     // Overview of op log.
     let output = test_env.run_jj_in(&repo_path, ["op", "log"]);
     insta::assert_snapshot!(output, @r"
-    @  364d0a677b0c test-username@host.example.com 2001-02-03 04:05:07.000 +07:00 - 2001-02-03 04:05:07.000 +07:00
+    @  c977f8183d3f test-username@host.example.com 2001-02-03 04:05:07.000 +07:00 - 2001-02-03 04:05:07.000 +07:00
     │  check out git remote's default branch
     │  args: jj git clone git-repo repo
     ○  369ee2939177 test-username@host.example.com 2001-02-03 04:05:07.000 +07:00 - 2001-02-03 04:05:07.000 +07:00
@@ -1146,8 +1245,8 @@ fn test_op_diff() {
     ");
     let output = test_env.run_jj_in(&repo_path, ["op", "diff", "--from", "@", "--to", "@"]);
     insta::assert_snapshot!(output, @r"
-    From operation: 364d0a677b0c (2001-02-03 08:05:07) check out git remote's default branch
-      To operation: 364d0a677b0c (2001-02-03 08:05:07) check out git remote's default branch
+    From operation: c977f8183d3f (2001-02-03 08:05:07) check out git remote's default branch
+      To operation: c977f8183d3f (2001-02-03 08:05:07) check out git remote's default branch
     [EOF]
     ");
 
@@ -1157,7 +1256,7 @@ fn test_op_diff() {
     let output = test_env.run_jj_in(&repo_path, ["op", "diff", "--from", "@-", "--to", "@"]);
     insta::assert_snapshot!(output, @r"
     From operation: 369ee2939177 (2001-02-03 08:05:07) fetch from git remote into empty repo
-      To operation: 364d0a677b0c (2001-02-03 08:05:07) check out git remote's default branch
+      To operation: c977f8183d3f (2001-02-03 08:05:07) check out git remote's default branch
 
     Changed commits:
     ○  + sqpuoqvx c7b48fea (empty) (no description set)
@@ -1181,7 +1280,7 @@ fn test_op_diff() {
     let output = test_env.run_jj_in(&repo_path, ["op", "diff", "--from", "0000000"]);
     insta::assert_snapshot!(output, @r"
     From operation: 000000000000 root()
-      To operation: 364d0a677b0c (2001-02-03 08:05:07) check out git remote's default branch
+      To operation: c977f8183d3f (2001-02-03 08:05:07) check out git remote's default branch
 
     Changed commits:
     ○  + sqpuoqvx c7b48fea (empty) (no description set)
@@ -1210,7 +1309,7 @@ fn test_op_diff() {
     // Diff from latest operation to root operation
     let output = test_env.run_jj_in(&repo_path, ["op", "diff", "--to", "0000000"]);
     insta::assert_snapshot!(output, @r"
-    From operation: 364d0a677b0c (2001-02-03 08:05:07) check out git remote's default branch
+    From operation: c977f8183d3f (2001-02-03 08:05:07) check out git remote's default branch
       To operation: 000000000000 root()
 
     Changed commits:
@@ -1266,13 +1365,13 @@ fn test_op_diff() {
     ");
     let output = test_env.run_jj_in(&repo_path, ["op", "log"]);
     insta::assert_snapshot!(output, @r"
-    @    7f6887c5ae04 test-username@host.example.com 2001-02-03 04:05:16.000 +07:00 - 2001-02-03 04:05:16.000 +07:00
+    @    63973954f78f test-username@host.example.com 2001-02-03 04:05:16.000 +07:00 - 2001-02-03 04:05:16.000 +07:00
     ├─╮  reconcile divergent operations
     │ │  args: jj log
-    ○ │  364d0a677b0c test-username@host.example.com 2001-02-03 04:05:07.000 +07:00 - 2001-02-03 04:05:07.000 +07:00
+    ○ │  c977f8183d3f test-username@host.example.com 2001-02-03 04:05:07.000 +07:00 - 2001-02-03 04:05:07.000 +07:00
     │ │  check out git remote's default branch
     │ │  args: jj git clone git-repo repo
-    │ ○  ee092a3adf88 test-username@host.example.com 2001-02-03 04:05:15.000 +07:00 - 2001-02-03 04:05:15.000 +07:00
+    │ ○  f027312dda4a test-username@host.example.com 2001-02-03 04:05:15.000 +07:00 - 2001-02-03 04:05:15.000 +07:00
     ├─╯  point bookmark bookmark-1 to commit 4ff6253913375c6ebdddd8423c11df3b3f17e331
     │    args: jj bookmark set bookmark-1 -r bookmark-2@origin --at-op @-
     ○  369ee2939177 test-username@host.example.com 2001-02-03 04:05:07.000 +07:00 - 2001-02-03 04:05:07.000 +07:00
@@ -1294,8 +1393,8 @@ fn test_op_diff() {
         ["op", "diff", "--from", first_parent_id, "--to", op_id],
     );
     insta::assert_snapshot!(output, @r"
-    From operation: 364d0a677b0c (2001-02-03 08:05:07) check out git remote's default branch
-      To operation: 7f6887c5ae04 (2001-02-03 08:05:16) reconcile divergent operations
+    From operation: c977f8183d3f (2001-02-03 08:05:07) check out git remote's default branch
+      To operation: 63973954f78f (2001-02-03 08:05:16) reconcile divergent operations
 
     Changed local bookmarks:
     bookmark-1:
@@ -1312,8 +1411,8 @@ fn test_op_diff() {
         ["op", "diff", "--from", second_parent_id, "--to", op_id],
     );
     insta::assert_snapshot!(output, @r"
-    From operation: ee092a3adf88 (2001-02-03 08:05:15) point bookmark bookmark-1 to commit 4ff6253913375c6ebdddd8423c11df3b3f17e331
-      To operation: 7f6887c5ae04 (2001-02-03 08:05:16) reconcile divergent operations
+    From operation: f027312dda4a (2001-02-03 08:05:15) point bookmark bookmark-1 to commit 4ff6253913375c6ebdddd8423c11df3b3f17e331
+      To operation: 63973954f78f (2001-02-03 08:05:16) reconcile divergent operations
 
     Changed commits:
     ○  + sqpuoqvx c7b48fea (empty) (no description set)
@@ -1345,8 +1444,8 @@ fn test_op_diff() {
     ");
     let output = test_env.run_jj_in(&repo_path, ["op", "diff"]);
     insta::assert_snapshot!(output, @r"
-    From operation: 7f6887c5ae04 (2001-02-03 08:05:16) reconcile divergent operations
-      To operation: 0fda9dbbc995 (2001-02-03 08:05:20) fetch from git remote(s) origin
+    From operation: 63973954f78f (2001-02-03 08:05:16) reconcile divergent operations
+      To operation: 3a4e21412667 (2001-02-03 08:05:20) fetch from git remote(s) origin
 
     Changed commits:
     ○  + kulxwnxm e1a239a5 bookmark-2@origin | Commit 5
@@ -1391,8 +1490,8 @@ fn test_op_diff() {
     ");
     let output = test_env.run_jj_in(&repo_path, ["op", "diff"]);
     insta::assert_snapshot!(output, @r"
-    From operation: 0fda9dbbc995 (2001-02-03 08:05:20) fetch from git remote(s) origin
-      To operation: 505a09f5c0f0 (2001-02-03 08:05:22) create bookmark bookmark-2 pointing to commit e1a239a57eb15cefc5910198befbbbe2b43c47af
+    From operation: 3a4e21412667 (2001-02-03 08:05:20) fetch from git remote(s) origin
+      To operation: 0be111cf616b (2001-02-03 08:05:22) create bookmark bookmark-2 pointing to commit e1a239a57eb15cefc5910198befbbbe2b43c47af
 
     Changed local bookmarks:
     bookmark-2:
@@ -1410,8 +1509,8 @@ fn test_op_diff() {
     ");
     let output = test_env.run_jj_in(&repo_path, ["op", "diff"]);
     insta::assert_snapshot!(output, @r"
-    From operation: 505a09f5c0f0 (2001-02-03 08:05:22) create bookmark bookmark-2 pointing to commit e1a239a57eb15cefc5910198befbbbe2b43c47af
-      To operation: e7d3f25689e1 (2001-02-03 08:05:24) track remote bookmark bookmark-2@origin
+    From operation: 0be111cf616b (2001-02-03 08:05:22) create bookmark bookmark-2 pointing to commit e1a239a57eb15cefc5910198befbbbe2b43c47af
+      To operation: 47716ab4067a (2001-02-03 08:05:24) track remote bookmark bookmark-2@origin
 
     Changed remote bookmarks:
     bookmark-2@origin:
@@ -1431,8 +1530,8 @@ fn test_op_diff() {
     ");
     let output = test_env.run_jj_in(&repo_path, ["op", "diff"]);
     insta::assert_snapshot!(output, @r"
-    From operation: 505a09f5c0f0 (2001-02-03 08:05:22) create bookmark bookmark-2 pointing to commit e1a239a57eb15cefc5910198befbbbe2b43c47af
-      To operation: e7d3f25689e1 (2001-02-03 08:05:24) track remote bookmark bookmark-2@origin
+    From operation: 0be111cf616b (2001-02-03 08:05:22) create bookmark bookmark-2 pointing to commit e1a239a57eb15cefc5910198befbbbe2b43c47af
+      To operation: 47716ab4067a (2001-02-03 08:05:24) track remote bookmark bookmark-2@origin
 
     Changed remote bookmarks:
     bookmark-2@origin:
@@ -1452,8 +1551,8 @@ fn test_op_diff() {
     ");
     let output = test_env.run_jj_in(&repo_path, ["op", "diff"]);
     insta::assert_snapshot!(output, @r"
-    From operation: e7d3f25689e1 (2001-02-03 08:05:24) track remote bookmark bookmark-2@origin
-      To operation: b94b5ef70c8a (2001-02-03 08:05:28) new empty commit
+    From operation: 47716ab4067a (2001-02-03 08:05:24) track remote bookmark bookmark-2@origin
+      To operation: ce71635dd94a (2001-02-03 08:05:28) new empty commit
 
     Changed commits:
     ○  + wvuyspvk fefb1e17 (empty) new commit
@@ -1470,8 +1569,8 @@ fn test_op_diff() {
     ");
     let output = test_env.run_jj_in(&repo_path, ["op", "diff"]);
     insta::assert_snapshot!(output, @r"
-    From operation: b94b5ef70c8a (2001-02-03 08:05:28) new empty commit
-      To operation: 26918495eee5 (2001-02-03 08:05:30) point bookmark bookmark-1 to commit fefb1e17c85328767a596c6dc3d9d604c024a02c
+    From operation: ce71635dd94a (2001-02-03 08:05:28) new empty commit
+      To operation: a9cbe1ac6733 (2001-02-03 08:05:30) point bookmark bookmark-1 to commit fefb1e17c85328767a596c6dc3d9d604c024a02c
 
     Changed local bookmarks:
     bookmark-1:
@@ -1490,8 +1589,8 @@ fn test_op_diff() {
     ");
     let output = test_env.run_jj_in(&repo_path, ["op", "diff"]);
     insta::assert_snapshot!(output, @r"
-    From operation: 26918495eee5 (2001-02-03 08:05:30) point bookmark bookmark-1 to commit fefb1e17c85328767a596c6dc3d9d604c024a02c
-      To operation: 9969a6088fd3 (2001-02-03 08:05:32) delete bookmark bookmark-2
+    From operation: a9cbe1ac6733 (2001-02-03 08:05:30) point bookmark bookmark-1 to commit fefb1e17c85328767a596c6dc3d9d604c024a02c
+      To operation: 6f0e258bda63 (2001-02-03 08:05:32) delete bookmark bookmark-2
 
     Changed local bookmarks:
     bookmark-2:
@@ -1514,8 +1613,8 @@ fn test_op_diff() {
     ");
     let output = test_env.run_jj_in(&repo_path, ["op", "diff"]);
     insta::assert_snapshot!(output, @r"
-    From operation: 9969a6088fd3 (2001-02-03 08:05:32) delete bookmark bookmark-2
-      To operation: ff305cfe0aca (2001-02-03 08:05:34) push all tracked bookmarks to git remote origin
+    From operation: 6f0e258bda63 (2001-02-03 08:05:32) delete bookmark bookmark-2
+      To operation: 69d2cbb10e1f (2001-02-03 08:05:34) push all tracked bookmarks to git remote origin
 
     Changed commits:
     ○  + oupztwtk fe3ad088 (empty) (no description set)
@@ -1795,7 +1894,7 @@ fn test_op_diff_word_wrap() {
     // ui.log-word-wrap option works, and diff stat respects content width
     insta::assert_snapshot!(render(&["op", "diff", "--from=@---", "--stat"], 40, true), @r"
     From operation: eac759b9ab75 (2001-02-03 08:05:07) add workspace 'default'
-      To operation: e1a943dc0fe5 (2001-02-03 08:05:08) snapshot working copy
+      To operation: 11c904470005 (2001-02-03 08:05:08) snapshot working copy
 
     Changed commits:
     ○  + sqpuoqvx 7581c520 (no description
@@ -1845,7 +1944,7 @@ fn test_op_diff_word_wrap() {
     insta::assert_snapshot!(
         render(&["op", "diff", "--from=@---", "--config", config], 10, true), @r"
     From operation: eac759b9ab75 (2001-02-03 08:05:07) add workspace 'default'
-      To operation: e1a943dc0fe5 (2001-02-03 08:05:08) snapshot working copy
+      To operation: 11c904470005 (2001-02-03 08:05:08) snapshot working copy
 
     Changed
     commits:
@@ -1917,7 +2016,7 @@ fn test_op_show() {
     // Overview of op log.
     let output = test_env.run_jj_in(&repo_path, ["op", "log"]);
     insta::assert_snapshot!(output, @r"
-    @  364d0a677b0c test-username@host.example.com 2001-02-03 04:05:07.000 +07:00 - 2001-02-03 04:05:07.000 +07:00
+    @  c977f8183d3f test-username@host.example.com 2001-02-03 04:05:07.000 +07:00 - 2001-02-03 04:05:07.000 +07:00
     │  check out git remote's default branch
     │  args: jj git clone git-repo repo
     ○  369ee2939177 test-username@host.example.com 2001-02-03 04:05:07.000 +07:00 - 2001-02-03 04:05:07.000 +07:00
@@ -1939,7 +2038,7 @@ fn test_op_show() {
     // Showing the latest operation.
     let output = test_env.run_jj_in(&repo_path, ["op", "show", "@"]);
     insta::assert_snapshot!(output, @r"
-    364d0a677b0c test-username@host.example.com 2001-02-03 04:05:07.000 +07:00 - 2001-02-03 04:05:07.000 +07:00
+    c977f8183d3f test-username@host.example.com 2001-02-03 04:05:07.000 +07:00 - 2001-02-03 04:05:07.000 +07:00
     check out git remote's default branch
     args: jj git clone git-repo repo
 
@@ -2017,7 +2116,7 @@ fn test_op_show() {
     // Showing a merge operation is empty.
     let output = test_env.run_jj_in(&repo_path, ["op", "show"]);
     insta::assert_snapshot!(output, @r"
-    774687cc6e4e test-username@host.example.com 2001-02-03 04:05:14.000 +07:00 - 2001-02-03 04:05:14.000 +07:00
+    7cfe3eadf9ec test-username@host.example.com 2001-02-03 04:05:14.000 +07:00 - 2001-02-03 04:05:14.000 +07:00
     reconcile divergent operations
     args: jj log
     [EOF]
@@ -2036,7 +2135,7 @@ fn test_op_show() {
     ");
     let output = test_env.run_jj_in(&repo_path, ["op", "show"]);
     insta::assert_snapshot!(output, @r"
-    fed3d4f59819 test-username@host.example.com 2001-02-03 04:05:16.000 +07:00 - 2001-02-03 04:05:16.000 +07:00
+    12130e651a03 test-username@host.example.com 2001-02-03 04:05:16.000 +07:00 - 2001-02-03 04:05:16.000 +07:00
     fetch from git remote(s) origin
     args: jj git fetch
 
@@ -2083,7 +2182,7 @@ fn test_op_show() {
     ");
     let output = test_env.run_jj_in(&repo_path, ["op", "show"]);
     insta::assert_snapshot!(output, @r"
-    77ed6ae71f3f test-username@host.example.com 2001-02-03 04:05:18.000 +07:00 - 2001-02-03 04:05:18.000 +07:00
+    f5ed74c1112b test-username@host.example.com 2001-02-03 04:05:18.000 +07:00 - 2001-02-03 04:05:18.000 +07:00
     create bookmark bookmark-2 pointing to commit e1a239a57eb15cefc5910198befbbbe2b43c47af
     args: jj bookmark create bookmark-2 -r bookmark-2@origin
 
@@ -2103,7 +2202,7 @@ fn test_op_show() {
     ");
     let output = test_env.run_jj_in(&repo_path, ["op", "show"]);
     insta::assert_snapshot!(output, @r"
-    f4e770fd7370 test-username@host.example.com 2001-02-03 04:05:20.000 +07:00 - 2001-02-03 04:05:20.000 +07:00
+    a05618027da0 test-username@host.example.com 2001-02-03 04:05:20.000 +07:00 - 2001-02-03 04:05:20.000 +07:00
     track remote bookmark bookmark-2@origin
     args: jj bookmark track bookmark-2@origin
 
@@ -2124,7 +2223,7 @@ fn test_op_show() {
     ");
     let output = test_env.run_jj_in(&repo_path, ["op", "show"]);
     insta::assert_snapshot!(output, @r"
-    f4e770fd7370 test-username@host.example.com 2001-02-03 04:05:20.000 +07:00 - 2001-02-03 04:05:20.000 +07:00
+    a05618027da0 test-username@host.example.com 2001-02-03 04:05:20.000 +07:00 - 2001-02-03 04:05:20.000 +07:00
     track remote bookmark bookmark-2@origin
     args: jj bookmark track bookmark-2@origin
 
@@ -2146,7 +2245,7 @@ fn test_op_show() {
     ");
     let output = test_env.run_jj_in(&repo_path, ["op", "show"]);
     insta::assert_snapshot!(output, @r"
-    e54bb5012457 test-username@host.example.com 2001-02-03 04:05:24.000 +07:00 - 2001-02-03 04:05:24.000 +07:00
+    799811c94653 test-username@host.example.com 2001-02-03 04:05:24.000 +07:00 - 2001-02-03 04:05:24.000 +07:00
     new empty commit
     args: jj new bookmark-1@origin -m 'new commit'
 
@@ -2165,7 +2264,7 @@ fn test_op_show() {
     ");
     let output = test_env.run_jj_in(&repo_path, ["op", "show"]);
     insta::assert_snapshot!(output, @r"
-    55527022d8b9 test-username@host.example.com 2001-02-03 04:05:26.000 +07:00 - 2001-02-03 04:05:26.000 +07:00
+    35749e6c826e test-username@host.example.com 2001-02-03 04:05:26.000 +07:00 - 2001-02-03 04:05:26.000 +07:00
     point bookmark bookmark-1 to commit 560df364f0a09fe29f6a4fca8bd07c4464c7feee
     args: jj bookmark set bookmark-1 -r @
 
@@ -2186,7 +2285,7 @@ fn test_op_show() {
     ");
     let output = test_env.run_jj_in(&repo_path, ["op", "show"]);
     insta::assert_snapshot!(output, @r"
-    a6838035030b test-username@host.example.com 2001-02-03 04:05:28.000 +07:00 - 2001-02-03 04:05:28.000 +07:00
+    a7cc1f98e4cf test-username@host.example.com 2001-02-03 04:05:28.000 +07:00 - 2001-02-03 04:05:28.000 +07:00
     delete bookmark bookmark-2
     args: jj bookmark delete bookmark-2
 
@@ -2211,7 +2310,7 @@ fn test_op_show() {
     ");
     let output = test_env.run_jj_in(&repo_path, ["op", "show"]);
     insta::assert_snapshot!(output, @r"
-    2a7821341a99 test-username@host.example.com 2001-02-03 04:05:30.000 +07:00 - 2001-02-03 04:05:30.000 +07:00
+    5b3d8d535e14 test-username@host.example.com 2001-02-03 04:05:30.000 +07:00 - 2001-02-03 04:05:30.000 +07:00
     push all tracked bookmarks to git remote origin
     args: jj git push --tracked
 
@@ -2482,3 +2581,259 @@ fn get_log_output(test_env: &TestEnvironment, repo_path: &Path, op_id: &str) ->
         ["log", "-T", "commit_id", "--at-op", op_id, "-r", "all()"],
     )
 }
+
+#[test]
+fn test_op_describe() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let repo_path = test_env.env_root().join("repo");
+    test_env
+        .run_jj_in(&repo_path, ["describe", "-m", "description 0"])
+        .success();
+
+    let output = test_env.run_jj_in(&repo_path, ["op", "describe", "@-", "-m", "initial state"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Described operation eac759b9ab75
+    [EOF]
+    ");
+    let output = test_env.run_jj_in(&repo_path, ["op", "log"]);
+    insta::assert_snapshot!(output, @r"
+    @  6409788c858d test-username@host.example.com 2001-02-03 04:05:09.000 +07:00 - 2001-02-03 04:05:09.000 +07:00
+    │  describe operation eac759b9ab75
+    │  args: jj op describe @- -m 'initial state'
+    ○  d009cfc04993 test-username@host.example.com 2001-02-03 04:05:08.000 +07:00 - 2001-02-03 04:05:08.000 +07:00
+    │  describe commit 230dd059e1b059aefc0da06a2e5a7dbf22362f22
+    │  args: jj describe -m 'description 0'
+    ○  eac759b9ab75 test-username@host.example.com 2001-02-03 04:05:07.000 +07:00 - 2001-02-03 04:05:07.000 +07:00
+    │  add workspace 'default'
+    │  note: initial state
+    ○  000000000000 root()
+    [EOF]
+    ");
+    let output = test_env.run_jj_in(&repo_path, ["op", "log", "-T", "builtin_op_log_oneline"]);
+    insta::assert_snapshot!(output, @r"
+    @  6409788c858d test-username@host.example.com 2001-02-03 04:05:09.000 +07:00 - 2001-02-03 04:05:09.000 +07:00 describe operation eac759b9ab75 args: jj op describe @- -m 'initial state'
+    ○  d009cfc04993 test-username@host.example.com 2001-02-03 04:05:08.000 +07:00 - 2001-02-03 04:05:08.000 +07:00 describe commit 230dd059e1b059aefc0da06a2e5a7dbf22362f22 args: jj describe -m 'description 0'
+    ○  eac759b9ab75 test-username@host.example.com 2001-02-03 04:05:07.000 +07:00 - 2001-02-03 04:05:07.000 +07:00 add workspace 'default' (note: initial state)
+    ○  000000000000 root()
+    [EOF]
+    ");
+
+    // The working copy isn't stale
+    let output = test_env.run_jj_in(&repo_path, ["status"]);
+    insta::assert_snapshot!(output, @r"
+    The working copy has no changes.
+    Working copy : qpvuntsm 19611c99 (empty) description 0
+    Parent commit: zzzzzzzz 00000000 (empty) (no description set)
+    [EOF]
+    ");
+
+    // Describing the operation again replaces the note
+    test_env
+        .run_jj_in(
+            &repo_path,
+            ["op", "describe", "@--", "-m", "before big rebase"],
+        )
+        .success();
+    let output = test_env.run_jj_in(
+        &repo_path,
+        [
+            "op",
+            "log",
+            "--no-graph",
+            "-T",
+            r#"separate(" ", id.short(), annotation) ++ "\n""#,
+        ],
+    );
+    insta::assert_snapshot!(output, @r"
+    1d5285cee1bd
+    6409788c858d
+    d009cfc04993
+    eac759b9ab75 before big rebase
+    000000000000
+    [EOF]
+    ");
+
+    // An empty message removes the note
+    let output = test_env.run_jj_in(&repo_path, ["op", "describe", "@---", "-m", ""]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Removed the note from operation eac759b9ab75
+    [EOF]
+    ");
+    let output = test_env.run_jj_in(
+        &repo_path,
+        [
+            "op",
+            "log",
+            "--no-graph",
+            "-T",
+            r#"separate(" ", id.short(), annotation) ++ "\n""#,
+        ],
+    );
+    insta::assert_snapshot!(output, @r"
+    dc049e32422f
+    1d5285cee1bd
+    6409788c858d
+    d009cfc04993
+    eac759b9ab75
+    000000000000
+    [EOF]
+    ");
+}
+
+#[test]
+fn test_op_recover_from_backup() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let repo_path = test_env.env_root().join("repo");
+    test_env.add_config(
+        r#"
+        backup.enabled = true
+        backup.min-commits = 2
+        backup.keep = 1
+        "#,
+    );
+    let log_template = r#"separate(" ", commit_id.short(), description) ++ "\n""#;
+    let get_log = || test_env.run_jj_in(&repo_path, ["log", "-T", log_template]);
+
+    let output = test_env.run_jj_in(&repo_path, ["op", "recover", "--from-backup"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Error: There are no backups
+    [EOF]
+    [exit status: 1]
+    ");
+
+    for name in ["a", "b", "c"] {
+        test_env
+            .run_jj_in(&repo_path, ["new", "-m", name])
+            .success();
+    }
+
+    // Abandoning a single commit isn't backed up
+    let output = test_env.run_jj_in(&repo_path, ["abandon", "description(c)"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Abandoned commit mzvwutvl d75038ed (empty) c
+    Working copy now at: royxmykx 0a7c04b4 (empty) (no description set)
+    Parent commit      : zsuskuln adb72c7f (empty) b
+    [EOF]
+    ");
+
+    // Abandoning more commits is backed up
+    let output = test_env.run_jj_in(&repo_path, ["abandon", "description(a)::"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Abandoned the following commits:
+      royxmykx 0a7c04b4 (empty) (no description set)
+      zsuskuln adb72c7f (empty) b
+      kkmpptxz 7796c4df (empty) a
+    Created backup 1 of the 3 hidden commits
+    Hint: Run `jj op recover --from-backup=1` to recover them.
+    Working copy now at: yqosqzyt 096dc80d (empty) (no description set)
+    Parent commit      : qpvuntsm 230dd059 (empty) (no description set)
+    [EOF]
+    ");
+    insta::assert_snapshot!(get_log(), @r"
+    @  096dc80da670
+    ○  230dd059e1b0
+    ◆  000000000000
+    [EOF]
+    ");
+
+    // Recover the latest backup
+    let output = test_env.run_jj_in(&repo_path, ["op", "recover", "--from-backup"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Recovered 3 commits from backup 1
+    [EOF]
+    ");
+    insta::assert_snapshot!(get_log(), @r"
+    @  096dc80da670
+    │ ○  0a7c04b4bf2a
+    │ ○  adb72c7f4556 b
+    │ │
+    │ ○  7796c4df8c2f a
+    ├─╯
+    ○  230dd059e1b0
+    ◆  000000000000
+    [EOF]
+    ");
+
+    // Restoring an operation which hides commits is backed up. The oldest
+    // backup is pruned.
+    let output = test_env.run_jj_in(&repo_path, ["op", "restore", "@-"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Restored to operation: e27a5026a5be (2001-02-03 08:05:13) abandon commit 0a7c04b4bf2af9e5ab3e449e0cf84df6f8acba88 and 2 more
+    Created backup 2 of the 3 hidden commits
+    Hint: Run `jj op recover --from-backup=2` to recover them.
+    [EOF]
+    ");
+    let output = test_env.run_jj_in(&repo_path, ["op", "recover", "--from-backup=1"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Error: No backup numbered 1
+    [EOF]
+    [exit status: 1]
+    ");
+    let output = test_env.run_jj_in(&repo_path, ["op", "recover", "--from-backup=2"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Recovered 3 commits from backup 2
+    [EOF]
+    ");
+    insta::assert_snapshot!(get_log(), @r"
+    @  096dc80da670
+    │ ○  0a7c04b4bf2a
+    │ ○  adb72c7f4556 b
+    │ │
+    │ ○  7796c4df8c2f a
+    ├─╯
+    ○  230dd059e1b0
+    ◆  000000000000
+    [EOF]
+    ");
+}
+
+#[test]
+fn test_op_recover_from_backup_after_gc() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let repo_path = test_env.env_root().join("repo");
+    test_env.add_config("backup.enabled = true");
+    let log_template = r#"separate(" ", commit_id.short(), description) ++ "\n""#;
+    let get_log = || test_env.run_jj_in(&repo_path, ["log", "-T", log_template]);
+
+    test_env
+        .run_jj_in(&repo_path, ["new", "-m", "abandoned"])
+        .success();
+    test_env.run_jj_in(&repo_path, ["abandon"]).success();
+    test_env
+        .run_jj_in(&repo_path, ["op", "abandon", "..@-"])
+        .success();
+    let output = test_env.run_jj_in(&repo_path, ["util", "gc", "--expire=now"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Created backup 1 of the commits to be garbage-collected
+    Hint: Run `jj op recover --from-backup=1` to recover them.
+    [EOF]
+    ");
+
+    let output = test_env.run_jj_in(&repo_path, ["op", "recover", "--from-backup"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Recovered 1 commits from backup 1
+    [EOF]
+    ");
+    insta::assert_snapshot!(get_log(), @r"
+    @  4db490c88528
+    │ ○  afe7eceff005 abandoned
+    ├─╯
+    ○  230dd059e1b0
+    ◆  000000000000
+    [EOF]
+    ");
+}
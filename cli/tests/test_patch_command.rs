@@ -0,0 +1,259 @@
+// Copyright 2025 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::path::Path;
+
+use indoc::indoc;
+
+use crate::common::CommandOutput;
+use crate::common::TestEnvironment;
+
+const TWO_PATCHES: &str = indoc! {r#"
+    From 1111111111111111111111111111111111111111 Mon Sep 17 00:00:00 2001
+    From: Patch Author <patch.author@example.com>
+    Date: Tue, 4 Mar 2025 10:20:30 +0100
+    Subject: [PATCH 1/2] Edit file and add another
+
+    Body of the first patch.
+    ---
+    diff --git a/file b/file
+    index 1111111..2222222 100644
+    --- a/file
+    +++ b/file
+    @@ -1,3 +1,3 @@
+     a
+    -b
+    +B
+     c
+    diff --git a/new b/new
+    new file mode 100644
+    index 0000000..3333333
+    --- /dev/null
+    +++ b/new
+    @@ -0,0 +1 @@
+    +new
+    -- 
+    2.47.0
+
+    From 2222222222222222222222222222222222222222 Mon Sep 17 00:00:00 2001
+    From: Other Author <other@example.com>
+    Subject: [PATCH 2/2] Rename file
+
+    diff --git a/file b/renamed
+    similarity index 100%
+    rename from file
+    rename to renamed
+    -- 
+    2.47.0
+"#};
+
+#[test]
+fn test_patch_import() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let repo_path = test_env.env_root().join("repo");
+    std::fs::write(repo_path.join("file"), "a\nb\nc\n").unwrap();
+    test_env
+        .run_jj_in(&repo_path, ["commit", "-m", "base"])
+        .success();
+    std::fs::write(test_env.env_root().join("series.mbox"), TWO_PATCHES).unwrap();
+
+    let output = test_env.run_jj_in(
+        &repo_path,
+        ["patch", "import", "../series.mbox", "-o", "@-"],
+    );
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Imported 2 commits:
+      kkmpptxz 33522067 Edit file and add another
+      pmmvwywv 249297b1 Rename file
+    [EOF]
+    ");
+    insta::assert_snapshot!(get_log_output(&test_env, &repo_path), @r"
+    @  rlvkpnrzqnoo Test User 2001-02-03 04:05:08.000 +07:00
+    │ ○  pmmvwywvzvvn Other Author 2001-02-03 04:05:09.000 +07:00 Rename file
+    │ ○  kkmpptxzrspx Patch Author 2025-03-04 10:20:30.000 +01:00 Edit file and add another
+    ├─╯
+    ○  qpvuntsmwlqt Test User 2001-02-03 04:05:08.000 +07:00 base
+    ◆  zzzzzzzzzzzz 1970-01-01 00:00:00.000 +00:00
+    [EOF]
+    ");
+
+    let output = test_env.run_jj_in(
+        &repo_path,
+        ["file", "show", "-r", "description(Rename)", "renamed"],
+    );
+    insta::assert_snapshot!(output, @r"
+    a
+    B
+    c
+    [EOF]
+    ");
+    let output = test_env.run_jj_in(
+        &repo_path,
+        ["diff", "--summary", "-r", "description(Rename)"],
+    );
+    insta::assert_snapshot!(output, @r"
+    R {file => renamed}
+    [EOF]
+    ");
+    let output = test_env.run_jj_in(
+        &repo_path,
+        [
+            "log",
+            "--no-graph",
+            "-r",
+            "description(Edit)",
+            "-T",
+            "description",
+        ],
+    );
+    insta::assert_snapshot!(output, @r"
+    Edit file and add another
+
+    Body of the first patch.
+    [EOF]
+    ");
+}
+
+#[test]
+fn test_patch_import_on_conflict() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let repo_path = test_env.env_root().join("repo");
+    std::fs::write(repo_path.join("file"), "a\nx\nc\n").unwrap();
+    test_env
+        .run_jj_in(&repo_path, ["commit", "-m", "base"])
+        .success();
+    std::fs::write(test_env.env_root().join("series.mbox"), TWO_PATCHES).unwrap();
+
+    // The first patch doesn't apply, so nothing is imported by default
+    let output = test_env.run_jj_in(
+        &repo_path,
+        ["patch", "import", "../series.mbox", "-o", "@-"],
+    );
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Error: Failed to apply patch 1 (Edit file and add another)
+    Caused by: file: Hunk #1 does not apply
+    [EOF]
+    [exit status: 1]
+    ");
+
+    // With --on-conflict=skip, the next patch is applied on top of the base
+    let output = test_env.run_jj_in(
+        &repo_path,
+        [
+            "patch",
+            "import",
+            "../series.mbox",
+            "-o",
+            "@-",
+            "--on-conflict=skip",
+        ],
+    );
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Warning: Skipped patch 1 (Edit file and add another): file: Hunk #1 does not apply
+    Imported 1 commits:
+      zsuskuln b2a24cfc Rename file
+    [EOF]
+    ");
+    insta::assert_snapshot!(get_log_output(&test_env, &repo_path), @r"
+    @  rlvkpnrzqnoo Test User 2001-02-03 04:05:08.000 +07:00
+    │ ○  zsuskulnrvyr Other Author 2001-02-03 04:05:10.000 +07:00 Rename file
+    ├─╯
+    ○  qpvuntsmwlqt Test User 2001-02-03 04:05:08.000 +07:00 base
+    ◆  zzzzzzzzzzzz 1970-01-01 00:00:00.000 +00:00
+    [EOF]
+    ");
+}
+
+#[test]
+fn test_patch_import_keep_change_ids() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let repo_path = test_env.env_root().join("repo");
+    let patch = indoc! {r#"
+        From 1111111111111111111111111111111111111111 Mon Sep 17 00:00:00 2001
+        From: Patch Author <patch.author@example.com>
+        Subject: [PATCH] Add file
+
+        Description body.
+
+        Reviewed-by: Someone <someone@example.com>
+        Change-Id: kkmpptxzrspxrzommnulwmwkkqwworpl
+        ---
+        diff --git a/file b/file
+        new file mode 100644
+        --- /dev/null
+        +++ b/file
+        @@ -0,0 +1 @@
+        +content
+    "#};
+    std::fs::write(test_env.env_root().join("change.mbox"), patch).unwrap();
+
+    let output = test_env.run_jj_in(
+        &repo_path,
+        ["patch", "import", "../change.mbox", "--keep-change-ids"],
+    );
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Imported 1 commits:
+      kkmpptxz 8ea37554 Add file
+    [EOF]
+    ");
+    let output = test_env.run_jj_in(
+        &repo_path,
+        [
+            "log",
+            "--no-graph",
+            "-r",
+            "kkmpptxzrspxrzommnulwmwkkqwworpl",
+            "-T",
+            "description",
+        ],
+    );
+    insta::assert_snapshot!(output, @r"
+    Add file
+
+    Description body.
+
+    Reviewed-by: Someone <someone@example.com>
+    [EOF]
+    ");
+}
+
+#[test]
+fn test_patch_import_invalid() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let repo_path = test_env.env_root().join("repo");
+    std::fs::write(test_env.env_root().join("bad.mbox"), "not a patch\n").unwrap();
+
+    let output = test_env.run_jj_in(&repo_path, ["patch", "import", "../bad.mbox"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Error: Failed to parse patch file ../bad.mbox
+    Caused by: Invalid patch at line 1: Missing From header
+    [EOF]
+    [exit status: 1]
+    ");
+}
+
+#[must_use]
+fn get_log_output(test_env: &TestEnvironment, repo_path: &Path) -> CommandOutput {
+    let template = r#"separate(" ", change_id.short(), author.name(), author.timestamp(), description.first_line())"#;
+    test_env.run_jj_in(repo_path, ["log", "-T", template])
+}
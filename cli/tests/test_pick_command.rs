@@ -0,0 +1,190 @@
+// Copyright 2025 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::common::create_commit_with_files;
+use crate::common::TestEnvironment;
+
+#[test]
+fn test_pick() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let repo_path = test_env.env_root().join("repo");
+
+    create_commit_with_files(
+        &test_env,
+        &repo_path,
+        "a",
+        &[],
+        &[("file1", "1\n2\n3\n"), ("file2", "a\n")],
+    );
+    create_commit_with_files(
+        &test_env,
+        &repo_path,
+        "b",
+        &["a"],
+        &[("file1", "one\n2\n3\n"), ("file2", "b\n"), ("file3", "b\n")],
+    );
+    create_commit_with_files(
+        &test_env,
+        &repo_path,
+        "c",
+        &["a"],
+        &[("file1", "1\n2\nthree\n"), ("file2", "c\n")],
+    );
+
+    // Changes to the selected paths are merged into the working copy
+    let output = test_env.run_jj_in(&repo_path, ["pick", "-r=b", "file1", "file3"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Created royxmykx b5c5c947 c | c
+    Working copy now at: royxmykx b5c5c947 c | c
+    Parent commit      : rlvkpnrz 4afdba5c a | a
+    Added 1 files, modified 1 files, removed 0 files
+    [EOF]
+    ");
+    let output = test_env.run_jj_in(&repo_path, ["diff", "--git"]);
+    insta::assert_snapshot!(output, @r"
+    diff --git a/file1 b/file1
+    index 01e79c32a8..f04eb265eb 100644
+    --- a/file1
+    +++ b/file1
+    @@ -1,3 +1,3 @@
+    -1
+    +one
+     2
+    -3
+    +three
+    diff --git a/file2 b/file2
+    index 7898192261..f2ad6c76f0 100644
+    --- a/file2
+    +++ b/file2
+    @@ -1,1 +1,1 @@
+    -a
+    +c
+    diff --git a/file3 b/file3
+    new file mode 100644
+    index 0000000000..6178079822
+    --- /dev/null
+    +++ b/file3
+    @@ -0,0 +1,1 @@
+    +b
+    [EOF]
+    ");
+
+    // Nothing to apply
+    let output = test_env.run_jj_in(&repo_path, ["pick", "-r=b", "file1"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Nothing changed.
+    [EOF]
+    ");
+
+    // Conflicting changes
+    let output = test_env.run_jj_in(&repo_path, ["pick", "-r=b", "file2"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Created royxmykx 2aa761db c | (conflict) c
+    Working copy now at: royxmykx 2aa761db c | (conflict) c
+    Parent commit      : rlvkpnrz 4afdba5c a | a
+    Added 0 files, modified 1 files, removed 0 files
+    Warning: There are unresolved conflicts at these paths:
+    file2    2-sided conflict
+    New conflicts appeared in these commits:
+      royxmykx 2aa761db c | (conflict) c
+    Hint: To resolve the conflicts, start by updating to it:
+      jj new royxmykx
+    Then use `jj resolve`, or edit the conflict markers in the file directly.
+    Once the conflicts are resolved, you may want to inspect the result with `jj diff`.
+    Then run `jj squash` to move the resolution into the conflicted commit.
+    [EOF]
+    ");
+
+    // Can apply into another revision
+    test_env.run_jj_in(&repo_path, ["undo"]).success();
+    let output = test_env.run_jj_in(&repo_path, ["pick", "-r=c", "--into=b", "file1"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Created zsuskuln f69b4ba0 b | b
+    [EOF]
+    ");
+    let output = test_env.run_jj_in(&repo_path, ["file", "show", "-r=b", "file1"]);
+    insta::assert_snapshot!(output, @r"
+    one
+    2
+    three
+    [EOF]
+    ");
+
+    // Immutable destination
+    let output = test_env.run_jj_in(&repo_path, ["pick", "-r=b", "--into=root()"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Error: The root commit 000000000000 is immutable
+    [EOF]
+    [exit status: 1]
+    ");
+}
+
+#[test]
+fn test_pick_interactive() {
+    let mut test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let repo_path = test_env.env_root().join("repo");
+
+    create_commit_with_files(&test_env, &repo_path, "a", &[], &[("file1", "a1\n")]);
+    create_commit_with_files(
+        &test_env,
+        &repo_path,
+        "b",
+        &["a"],
+        &[("file1", "b1\n"), ("file2", "b2\n")],
+    );
+    create_commit_with_files(&test_env, &repo_path, "c", &["a"], &[]);
+
+    let diff_editor = test_env.set_up_fake_diff_editor();
+    let diff_script = [
+        "files-before file1",
+        "files-after JJ-INSTRUCTIONS file1 file2",
+        "reset file1",
+        "dump JJ-INSTRUCTIONS instrs",
+    ]
+    .join("\0");
+    std::fs::write(diff_editor, diff_script).unwrap();
+
+    // Apply file2 only
+    let output = test_env.run_jj_in(&repo_path, ["pick", "-i", "-r=b"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Created royxmykx ee98d1a6 c | c
+    Working copy now at: royxmykx ee98d1a6 c | c
+    Parent commit      : rlvkpnrz 79c1b823 a | a
+    Added 1 files, modified 0 files, removed 0 files
+    [EOF]
+    ");
+    insta::assert_snapshot!(
+        std::fs::read_to_string(test_env.env_root().join("instrs")).unwrap(), @r"
+    You are picking changes from: zsuskuln c3eecc61 b | b
+    into commit: royxmykx 3c23c478 c | (empty) c
+
+    The left side of the diff shows the contents of the parent(s) of the
+    source commit. The right side initially shows the contents of the source
+    commit. Adjust the right side until the diff shows the changes you want
+    to apply to the destination commit.
+    ");
+    let output = test_env.run_jj_in(&repo_path, ["diff", "-s"]);
+    insta::assert_snapshot!(output, @r"
+    A file2
+    [EOF]
+    ");
+}
@@ -711,7 +711,7 @@ fn test_rebase_revision_onto_descendant() {
     let output = test_env.run_jj_in(&repo_path, ["op", "restore", &setup_opid]);
     insta::assert_snapshot!(output, @r"
     ------- stderr -------
-    Restored to operation: 60e340690be6 (2001-02-03 08:05:15) create bookmark merge pointing to commit b05964d109522cd06e48f1a2661e1a0f58be0984
+    Restored to operation: 03439053d3c7 (2001-02-03 08:05:15) create bookmark merge pointing to commit b05964d109522cd06e48f1a2661e1a0f58be0984
     Working copy now at: vruxwmqv b05964d1 merge | merge
     Parent commit      : royxmykx cea87a87 b | b
     Parent commit      : zsuskuln 2c5b7858 a | a
@@ -2970,6 +2970,69 @@ fn test_rebase_skip_emptied() {
     ");
 }
 
+#[test]
+fn test_rebase_drop_empty_config() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let repo_path = test_env.env_root().join("repo");
+
+    create_commit(&test_env, &repo_path, "a", &[]);
+    create_commit(&test_env, &repo_path, "b", &["a"]);
+    test_env
+        .run_jj_in(&repo_path, ["new", "a", "-m", "will become empty"])
+        .success();
+    test_env
+        .run_jj_in(&repo_path, ["restore", "--from=b"])
+        .success();
+    test_env
+        .run_jj_in(&repo_path, ["new", "-m", "already empty"])
+        .success();
+    let setup_opid = test_env.current_operation_id(&repo_path);
+
+    // The newly emptied commit is abandoned by default with "always"
+    let output = test_env.run_jj_in(
+        &repo_path,
+        ["rebase", "-d=b", "--config=rewrite.drop-empty=always"],
+    );
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Rebased 1 commits onto destination
+    Abandoned 1 newly emptied commits
+    Working copy now at: vruxwmqv a695e246 (empty) already empty
+    Parent commit      : zsuskuln 1394f625 b | b
+    [EOF]
+    ");
+    insta::assert_snapshot!(test_env.run_jj_in(&repo_path, ["log", "-T", "description"]), @r"
+    @  already empty
+    ○  b
+    ○  a
+    ◆
+    [EOF]
+    ");
+
+    // "never" and "auto" keep the commit unless --skip-emptied is given
+    for policy in ["never", "auto"] {
+        test_env
+            .run_jj_in(&repo_path, ["op", "restore", &setup_opid])
+            .success();
+        let config = format!("--config=rewrite.drop-empty={policy}");
+        test_env
+            .run_jj_in(&repo_path, ["rebase", "-d=b", &config])
+            .success();
+        let output = test_env.run_jj_in(&repo_path, ["log", "-T", "description"]);
+        insta::allow_duplicates! {
+            insta::assert_snapshot!(output, @r"
+            @  already empty
+            ○  will become empty
+            ○  b
+            ○  a
+            ◆
+            [EOF]
+            ");
+        }
+    }
+}
+
 #[test]
 fn test_rebase_skip_emptied_descendants() {
     let test_env = TestEnvironment::default();
@@ -3158,6 +3221,43 @@ fn test_rebase_skip_if_on_destination() {
     ");
 }
 
+#[test]
+fn test_rebase_keep_timestamps() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let repo_path = test_env.env_root().join("repo");
+
+    create_commit(&test_env, &repo_path, "a", &[]);
+    create_commit(&test_env, &repo_path, "b", &[]);
+    create_commit(&test_env, &repo_path, "c", &[]);
+    let template = r#"description.first_line() ++ " " ++ committer.timestamp() ++ "\n""#;
+    let output = test_env.run_jj_in(&repo_path, ["log", "--no-graph", "-T", template]);
+    insta::assert_snapshot!(output, @r"
+    c 2001-02-03 04:05:13.000 +07:00
+    b 2001-02-03 04:05:11.000 +07:00
+    a 2001-02-03 04:05:09.000 +07:00
+     1970-01-01 00:00:00.000 +00:00
+    [EOF]
+    ");
+
+    // The rebased commit keeps its committer timestamp
+    test_env
+        .run_jj_in(&repo_path, ["rebase", "-r=b", "-d=a", "--keep-timestamps"])
+        .success();
+    // ...unless the flag isn't given
+    test_env
+        .run_jj_in(&repo_path, ["rebase", "-r=c", "-d=a"])
+        .success();
+    let output = test_env.run_jj_in(&repo_path, ["log", "--no-graph", "-T", template]);
+    insta::assert_snapshot!(output, @r"
+    c 2001-02-03 04:05:16.000 +07:00
+    b 2001-02-03 04:05:11.000 +07:00
+    a 2001-02-03 04:05:09.000 +07:00
+     1970-01-01 00:00:00.000 +00:00
+    [EOF]
+    ");
+}
+
 #[must_use]
 fn get_log_output(test_env: &TestEnvironment, repo_path: &Path) -> CommandOutput {
     let template = "bookmarks ++ surround(': ', '', parents.map(|c| c.bookmarks()))";
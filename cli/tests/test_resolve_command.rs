@@ -14,9 +14,12 @@
 
 use std::path::Path;
 
+use indoc::formatdoc;
 use indoc::indoc;
 
 use crate::common::create_commit_with_files;
+use crate::common::fake_editor_path;
+use crate::common::to_toml_value;
 use crate::common::CommandOutput;
 use crate::common::TestEnvironment;
 
@@ -73,6 +76,7 @@ fn test_resolution() {
     insta::assert_snapshot!(output, @r"
     ------- stderr -------
     Resolving conflicts in: file
+    Resolved conflicts in 1 of 1 files
     Working copy now at: vruxwmqv e069f073 conflict | conflict
     Parent commit      : zsuskuln aa493daf a | a
     Parent commit      : royxmykx db6a4daf b | b
@@ -118,6 +122,7 @@ fn test_resolution() {
     insta::assert_snapshot!(output, @r"
     ------- stderr -------
     Resolving conflicts in: file
+    Resolved conflicts in 1 of 1 files
     Working copy now at: vruxwmqv 1a70c7c6 conflict | conflict
     Parent commit      : zsuskuln aa493daf a | a
     Parent commit      : royxmykx db6a4daf b | b
@@ -224,6 +229,7 @@ fn test_resolution() {
     insta::assert_snapshot!(output, @r"
     ------- stderr -------
     Resolving conflicts in: file
+    Resolved conflicts in 0 of 1 files
     Working copy now at: vruxwmqv 608a2310 conflict | (conflict) conflict
     Parent commit      : zsuskuln aa493daf a | a
     Parent commit      : royxmykx db6a4daf b | b
@@ -273,8 +279,8 @@ fn test_resolution() {
     ");
 
     // Check that if merge tool leaves conflict markers in output file but
-    // `merge-tool-edits-conflict-markers=false` or is not specified,
-    // `jj` considers the conflict resolved.
+    // `merge-tool-edits-conflict-markers=false` or is not specified, `jj`
+    // refuses to record the output as a resolution.
     test_env.run_jj_in(&repo_path, ["undo"]).success();
     insta::assert_snapshot!(test_env.run_jj_in(&repo_path, ["diff", "--git"]), @"");
     std::fs::write(
@@ -296,51 +302,24 @@ fn test_resolution() {
     )
     .unwrap();
     let output = test_env.run_jj_in(&repo_path, ["resolve"]);
-    insta::assert_snapshot!(output, @r"
+    insta::assert_snapshot!(output, @r#"
     ------- stderr -------
     Resolving conflicts in: file
-    Working copy now at: vruxwmqv 3166dfd2 conflict | conflict
-    Parent commit      : zsuskuln aa493daf a | a
-    Parent commit      : royxmykx db6a4daf b | b
-    Added 0 files, modified 1 files, removed 0 files
+    Error: Failed to resolve conflicts
+    Caused by: The output file for "file" contains conflict markers, but the merge tool isn't configured to leave conflicts unresolved
     [EOF]
-    ");
+    [exit status: 1]
+    "#);
     insta::assert_snapshot!(
         std::fs::read_to_string(test_env.env_root().join("editor3")).unwrap(), @"");
-    // Note the "Resolved" below
-    insta::assert_snapshot!(test_env.run_jj_in(&repo_path, ["diff", "--git"]), @r"
-    diff --git a/file b/file
-    index 0000000000..0610716cc1 100644
-    --- a/file
-    +++ b/file
-    @@ -1,7 +1,7 @@
-    -<<<<<<< Conflict 1 of 1
-    -%%%%%%% Changes from base to side #1
-    --base
-    -+a
-    -+++++++ Contents of side #2
-    -b
-    ->>>>>>> Conflict 1 of 1 ends
-    +<<<<<<<
-    +%%%%%%%
-    +-some
-    ++fake
-    ++++++++
-    +conflict
-    +>>>>>>>
-    [EOF]
-    ");
+    insta::assert_snapshot!(test_env.run_jj_in(&repo_path, ["diff", "--git"]), @"");
     insta::assert_snapshot!(test_env.run_jj_in(&repo_path, ["resolve", "--list"]), @r"
-    ------- stderr -------
-    Error: No conflicts found at this revision
+    file    2-sided conflict
     [EOF]
-    [exit status: 2]
     ");
 
     // Check that merge tool can override conflict marker style setting, and that
     // the merge tool can output Git-style conflict markers
-    test_env.run_jj_in(&repo_path, ["undo"]).success();
-    insta::assert_snapshot!(test_env.run_jj_in(&repo_path, ["diff", "--git"]), @"");
     std::fs::write(
         &editor_script,
         [
@@ -370,14 +349,15 @@ fn test_resolution() {
     insta::assert_snapshot!(output, @r"
     ------- stderr -------
     Resolving conflicts in: file
-    Working copy now at: vruxwmqv 8e03fefa conflict | (conflict) conflict
+    Resolved conflicts in 0 of 1 files
+    Working copy now at: vruxwmqv 9cc8c7a1 conflict | (conflict) conflict
     Parent commit      : zsuskuln aa493daf a | a
     Parent commit      : royxmykx db6a4daf b | b
     Added 0 files, modified 1 files, removed 0 files
     Warning: There are unresolved conflicts at these paths:
     file    2-sided conflict
     New conflicts appeared in these commits:
-      vruxwmqv 8e03fefa conflict | (conflict) conflict
+      vruxwmqv 9cc8c7a1 conflict | (conflict) conflict
     Hint: To resolve the conflicts, start by updating to it:
       jj new vruxwmqv
     Then use `jj resolve`, or edit the conflict markers in the file directly.
@@ -451,14 +431,15 @@ fn test_resolution() {
     insta::assert_snapshot!(output, @r"
     ------- stderr -------
     Resolving conflicts in: file
-    Working copy now at: vruxwmqv a786ac2f conflict | (conflict) conflict
+    Resolved conflicts in 0 of 1 files
+    Working copy now at: vruxwmqv eaa43df5 conflict | (conflict) conflict
     Parent commit      : zsuskuln aa493daf a | a
     Parent commit      : royxmykx db6a4daf b | b
     Added 0 files, modified 1 files, removed 0 files
     Warning: There are unresolved conflicts at these paths:
     file    2-sided conflict
     New conflicts appeared in these commits:
-      vruxwmqv a786ac2f conflict | (conflict) conflict
+      vruxwmqv eaa43df5 conflict | (conflict) conflict
     Hint: To resolve the conflicts, start by updating to it:
       jj new vruxwmqv
     Then use `jj resolve`, or edit the conflict markers in the file directly.
@@ -602,6 +583,89 @@ fn test_normal_conflict_input_files() {
     check_resolve_produces_input_file(&mut test_env, &repo_path, "file", "right", "b\n");
 }
 
+#[test]
+fn test_premerged_conflict_input_files() {
+    let mut test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let repo_path = test_env.env_root().join("repo");
+    test_env.add_config("merge-tools.fake-editor.premerge = true");
+
+    // The file contains text that looks like conflict markers, so the
+    // materialized conflict is nested inside it
+    let base = "a\n<<<<<<<\nb\n=======\nc\n>>>>>>>\nd\n";
+    let side_a = "a2\n<<<<<<<\nb\n=======\nc\n>>>>>>>\nd-a\n";
+    let side_b = "a\n<<<<<<<\nb\n=======\nc2\n>>>>>>>\nd-b\n";
+    create_commit_with_files(&test_env, &repo_path, "base", &[], &[("file", base)]);
+    create_commit_with_files(&test_env, &repo_path, "a", &["base"], &[("file", side_a)]);
+    create_commit_with_files(&test_env, &repo_path, "b", &["base"], &[("file", side_b)]);
+    create_commit_with_files(&test_env, &repo_path, "conflict", &["a", "b"], &[]);
+    insta::assert_snapshot!(test_env.run_jj_in(&repo_path, ["resolve", "--list"]), @r"
+    file    2-sided conflict
+    [EOF]
+    ");
+    insta::assert_snapshot!(
+    std::fs::read_to_string(repo_path.join("file")).unwrap()
+        , @r"
+    a2
+    <<<<<<<
+    b
+    =======
+    c2
+    >>>>>>>
+    <<<<<<<<<<< Conflict 1 of 1
+    %%%%%%%%%%% Changes from base to side #1
+    -d
+    +d-a
+    +++++++++++ Contents of side #2
+    d-b
+    >>>>>>>>>>> Conflict 1 of 1 ends
+    ");
+
+    // Only the conflicted region differs between the input files
+    check_resolve_produces_input_file(
+        &mut test_env,
+        &repo_path,
+        "file",
+        "base",
+        "a2\n<<<<<<<\nb\n=======\nc2\n>>>>>>>\nd\n",
+    );
+    check_resolve_produces_input_file(
+        &mut test_env,
+        &repo_path,
+        "file",
+        "left",
+        "a2\n<<<<<<<\nb\n=======\nc2\n>>>>>>>\nd-a\n",
+    );
+    check_resolve_produces_input_file(
+        &mut test_env,
+        &repo_path,
+        "file",
+        "right",
+        "a2\n<<<<<<<\nb\n=======\nc2\n>>>>>>>\nd-b\n",
+    );
+
+    // The merge tool's output is used as the full file
+    let editor_script = test_env.set_up_fake_editor();
+    std::fs::write(
+        editor_script,
+        "write\na2\n<<<<<<<\nb\n=======\nc2\n>>>>>>>\nd-a-b\n",
+    )
+    .unwrap();
+    insta::assert_snapshot!(
+        test_env.run_jj_in(&repo_path, ["resolve", "--quiet"]), @"");
+    insta::assert_snapshot!(
+        test_env.run_jj_in(&repo_path, ["file", "show", "file"]), @r"
+    a2
+    <<<<<<<
+    b
+    =======
+    c2
+    >>>>>>>
+    d-a-b
+    [EOF]
+    ");
+}
+
 #[test]
 fn test_baseless_conflict_input_files() {
     let mut test_env = TestEnvironment::default();
@@ -775,6 +839,7 @@ fn test_simplify_conflict_sides() {
     insta::assert_snapshot!(output, @r"
     ------- stderr -------
     Resolving conflicts in: fileB
+    Resolved conflicts in 0 of 1 files
     Working copy now at: nkmrtpmo 69cc0c2d conflict | (conflict) conflict
     Parent commit      : kmkuslsw 4601566f conflictA | (conflict) (empty) conflictA
     Parent commit      : lylxulpl 6f8d8381 conflictB | (conflict) (empty) conflictB
@@ -1027,6 +1092,7 @@ fn test_resolve_conflicts_with_executable() {
     insta::assert_snapshot!(output, @r"
     ------- stderr -------
     Resolving conflicts in: file1
+    Resolved conflicts in 1 of 1 files
     Working copy now at: znkkpsqq eb159d56 conflict | (conflict) conflict
     Parent commit      : mzvwutvl 08932848 a | a
     Parent commit      : yqosqzyt b69b3de6 b | b
@@ -1071,6 +1137,7 @@ fn test_resolve_conflicts_with_executable() {
     insta::assert_snapshot!(output, @r"
     ------- stderr -------
     Resolving conflicts in: file2
+    Resolved conflicts in 1 of 1 files
     Working copy now at: znkkpsqq 4dccbb3c conflict | (conflict) conflict
     Parent commit      : mzvwutvl 08932848 a | a
     Parent commit      : yqosqzyt b69b3de6 b | b
@@ -1183,6 +1250,7 @@ fn test_resolve_long_conflict_markers() {
     insta::assert_snapshot!(output, @r"
     ------- stderr -------
     Resolving conflicts in: file
+    Resolved conflicts in 0 of 1 files
     Working copy now at: vruxwmqv 2b985546 conflict | (conflict) conflict
     Parent commit      : zsuskuln 64177fd4 a | a
     Parent commit      : royxmykx db442c1e b | b
@@ -1256,6 +1324,7 @@ fn test_resolve_long_conflict_markers() {
     insta::assert_snapshot!(output, @r"
     ------- stderr -------
     Resolving conflicts in: file
+    Resolved conflicts in 0 of 1 files
     Working copy now at: vruxwmqv fac9406d conflict | (conflict) conflict
     Parent commit      : zsuskuln 64177fd4 a | a
     Parent commit      : royxmykx db442c1e b | b
@@ -1335,6 +1404,7 @@ fn test_resolve_long_conflict_markers() {
     insta::assert_snapshot!(output, @r"
     ------- stderr -------
     Resolving conflicts in: file
+    Resolved conflicts in 0 of 1 files
     Working copy now at: vruxwmqv 1b29631a conflict | (conflict) conflict
     Parent commit      : zsuskuln 64177fd4 a | a
     Parent commit      : royxmykx db442c1e b | b
@@ -1474,6 +1544,7 @@ fn test_multiple_conflicts() {
     insta::assert_snapshot!(output, @r"
     ------- stderr -------
     Resolving conflicts in: another_file
+    Resolved conflicts in 1 of 1 files
     Working copy now at: vruxwmqv 309e981c conflict | (conflict) conflict
     Parent commit      : zsuskuln de7553ef a | a
     Parent commit      : royxmykx f68bc2f0 b | b
@@ -1576,6 +1647,135 @@ fn test_multiple_conflicts() {
     ");
 }
 
+#[test]
+fn test_resolve_with_routes() {
+    let mut test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let repo_path = test_env.env_root().join("repo");
+
+    create_commit_with_files(
+        &test_env,
+        &repo_path,
+        "base",
+        &[],
+        &[("file1", "base 1\n"), ("file2.txt", "base 2\n")],
+    );
+    create_commit_with_files(
+        &test_env,
+        &repo_path,
+        "a",
+        &["base"],
+        &[("file1", "a 1\n"), ("file2.txt", "a 2\n")],
+    );
+    create_commit_with_files(
+        &test_env,
+        &repo_path,
+        "b",
+        &["base"],
+        &[("file1", "b 1\n"), ("file2.txt", "b 2\n")],
+    );
+    create_commit_with_files(&test_env, &repo_path, "conflict", &["a", "b"], &[]);
+
+    // The "*.txt" files are routed to a tool which starts with the conflict
+    // markers in the output file, and the other files go to the default tool.
+    let editor_script = test_env.set_up_fake_editor();
+    let editor_path = to_toml_value(fake_editor_path());
+    test_env.add_config(formatdoc! {r#"
+        merge-tools.routed.program = {editor_path}
+        merge-tools.routed.merge-args = ["$output"]
+        merge-tools.routed.merge-tool-edits-conflict-markers = true
+        merge-tools.routes = [{{ pattern = "*.txt", tool = "routed" }}]
+    "#});
+    std::fs::write(
+        &editor_script,
+        [
+            "dump editor1",
+            "write\nresolution 1\n",
+            "next invocation\n",
+            "dump editor2",
+            "write\nresolution 2\n",
+        ]
+        .join("\0"),
+    )
+    .unwrap();
+    let output = test_env.run_jj_in(&repo_path, ["resolve"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Resolving conflicts in: file1
+    Resolving conflicts in: file2.txt
+    Resolved conflicts in 2 of 2 files
+    Working copy now at: vruxwmqv 7313af44 conflict | conflict
+    Parent commit      : zsuskuln 620999d7 a | a
+    Parent commit      : royxmykx 55b19217 b | b
+    Added 0 files, modified 2 files, removed 0 files
+    [EOF]
+    ");
+    insta::assert_snapshot!(
+        std::fs::read_to_string(test_env.env_root().join("editor1")).unwrap(), @"");
+    insta::assert_snapshot!(
+        std::fs::read_to_string(test_env.env_root().join("editor2")).unwrap(), @r"
+        <<<<<<< Conflict 1 of 1
+        %%%%%%% Changes from base to side #1
+        -base 2
+        +a 2
+        +++++++ Contents of side #2
+        b 2
+        >>>>>>> Conflict 1 of 1 ends
+        ");
+    insta::assert_snapshot!(test_env.run_jj_in(&repo_path, ["resolve", "--list"]), @r"
+    ------- stderr -------
+    Error: No conflicts found at this revision
+    [EOF]
+    [exit status: 2]
+    ");
+
+    // An explicitly specified tool is used for all files
+    test_env.run_jj_in(&repo_path, ["undo"]).success();
+    std::fs::write(
+        &editor_script,
+        [
+            "dump editor3",
+            "write\nresolution 1\n",
+            "next invocation\n",
+            "dump editor4",
+            "write\nresolution 2\n",
+        ]
+        .join("\0"),
+    )
+    .unwrap();
+    let output = test_env.run_jj_in(&repo_path, ["resolve", "--tool=fake-editor"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Resolving conflicts in: file1
+    Resolving conflicts in: file2.txt
+    Resolved conflicts in 2 of 2 files
+    Working copy now at: vruxwmqv fbe639b3 conflict | conflict
+    Parent commit      : zsuskuln 620999d7 a | a
+    Parent commit      : royxmykx 55b19217 b | b
+    Added 0 files, modified 2 files, removed 0 files
+    [EOF]
+    ");
+    insta::assert_snapshot!(
+        std::fs::read_to_string(test_env.env_root().join("editor4")).unwrap(), @"");
+
+    // Routes to tools which can't merge are rejected
+    test_env.run_jj_in(&repo_path, ["undo"]).success();
+    let output = test_env.run_jj_in(
+        &repo_path,
+        [
+            "resolve",
+            r#"--config=merge-tools.routes=[{pattern="*.txt", tool=":builtin-text"}]"#,
+        ],
+    );
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Error: The tool `:builtin-text` cannot be used as a merge tool with `jj resolve`
+    Hint: To use `:builtin-text` as a merge tool, the config `merge-tools.:builtin-text.merge-args` must be defined (see docs for details)
+    [EOF]
+    [exit status: 1]
+    ");
+}
+
 #[test]
 fn test_multiple_conflicts_with_error() {
     let mut test_env = TestEnvironment::default();
@@ -1649,6 +1849,7 @@ fn test_multiple_conflicts_with_error() {
     ------- stderr -------
     Resolving conflicts in: file1
     Resolving conflicts in: file2
+    Resolved conflicts in 1 of 2 files
     Working copy now at: vruxwmqv d2f3f858 conflict | (conflict) conflict
     Parent commit      : zsuskuln 9db7fdfb a | a
     Parent commit      : royxmykx d67e26e4 b | b
@@ -1701,6 +1902,7 @@ fn test_multiple_conflicts_with_error() {
     ------- stderr -------
     Resolving conflicts in: file1
     Resolving conflicts in: file2
+    Resolved conflicts in 1 of 2 files
     Working copy now at: vruxwmqv 0a54e8ed conflict | (conflict) conflict
     Parent commit      : zsuskuln 9db7fdfb a | a
     Parent commit      : royxmykx d67e26e4 b | b
@@ -0,0 +1,189 @@
+// Copyright 2025 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::common::TestEnvironment;
+
+#[test]
+fn test_shelve_unshelve() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let repo_path = test_env.env_root().join("repo");
+
+    std::fs::write(repo_path.join("file1"), "a\n").unwrap();
+    std::fs::write(repo_path.join("file2"), "a\n").unwrap();
+    test_env.run_jj_in(&repo_path, ["new"]).success();
+    std::fs::write(repo_path.join("file1"), "b\n").unwrap();
+    std::fs::write(repo_path.join("file2"), "b\n").unwrap();
+
+    // Shelve some of the changes
+    let output = test_env.run_jj_in(&repo_path, ["shelve", "file1", "-m", "wip"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Shelved changes as 1
+    Working copy now at: rlvkpnrz 5b2ba145 (no description set)
+    Parent commit      : qpvuntsm fc687cb8 (no description set)
+    Added 0 files, modified 1 files, removed 0 files
+    [EOF]
+    ");
+    let output = test_env.run_jj_in(&repo_path, ["diff", "--git"]);
+    insta::assert_snapshot!(output, @r"
+    diff --git a/file2 b/file2
+    index 7898192261..6178079822 100644
+    --- a/file2
+    +++ b/file2
+    @@ -1,1 +1,1 @@
+    -a
+    +b
+    [EOF]
+    ");
+
+    // Shelve the rest under a name
+    let output = test_env.run_jj_in(&repo_path, ["shelve", "--name", "other"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Shelved changes as other
+    Working copy now at: rlvkpnrz 535879e4 (empty) (no description set)
+    Parent commit      : qpvuntsm fc687cb8 (no description set)
+    Added 0 files, modified 1 files, removed 0 files
+    [EOF]
+    ");
+    let output = test_env.run_jj_in(&repo_path, ["diff", "--git"]);
+    insta::assert_snapshot!(output, @"");
+
+    // Nothing left to shelve
+    let output = test_env.run_jj_in(&repo_path, ["shelve"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    No changes to shelve.
+    [EOF]
+    ");
+
+    // The shelved commits are hidden
+    let output = test_env.run_jj_in(&repo_path, ["log", "-r", "all()", "-T", "description"]);
+    insta::assert_snapshot!(output, @r"
+    @
+    ○
+    ◆
+    [EOF]
+    ");
+
+    let output = test_env.run_jj_in(&repo_path, ["shelve", "--list"]);
+    insta::assert_snapshot!(output, @r"
+    1: kkmpptxz hidden 5d1986ce wip
+    other: mzvwutvl hidden 87f86ae2 (no description set)
+    [EOF]
+    ");
+
+    let output = test_env.run_jj_in(&repo_path, ["shelve", "--name", "other"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Error: Shelf other already exists
+    [EOF]
+    [exit status: 1]
+    ");
+
+    // Apply the changes again
+    let output = test_env.run_jj_in(&repo_path, ["unshelve", "1"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Applied shelf 1
+    Working copy now at: rlvkpnrz d7483714 (no description set)
+    Parent commit      : qpvuntsm fc687cb8 (no description set)
+    Added 0 files, modified 1 files, removed 0 files
+    [EOF]
+    ");
+    let output = test_env.run_jj_in(&repo_path, ["diff", "--git"]);
+    insta::assert_snapshot!(output, @r"
+    diff --git a/file1 b/file1
+    index 7898192261..6178079822 100644
+    --- a/file1
+    +++ b/file1
+    @@ -1,1 +1,1 @@
+    -a
+    +b
+    [EOF]
+    ");
+    let output = test_env.run_jj_in(&repo_path, ["shelve", "--list"]);
+    insta::assert_snapshot!(output, @r"
+    other: mzvwutvl hidden 87f86ae2 (no description set)
+    [EOF]
+    ");
+
+    let output = test_env.run_jj_in(&repo_path, ["unshelve", "1"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Error: No such shelf: 1
+    Hint: Use `jj shelve --list` to see the available shelves.
+    [EOF]
+    [exit status: 1]
+    ");
+
+    // Undo restores the shelf
+    test_env.run_jj_in(&repo_path, ["undo"]).success();
+    let output = test_env.run_jj_in(&repo_path, ["shelve", "--list"]);
+    insta::assert_snapshot!(output, @r"
+    1: kkmpptxz hidden 5d1986ce wip
+    other: mzvwutvl hidden 87f86ae2 (no description set)
+    [EOF]
+    ");
+}
+
+#[test]
+fn test_unshelve_conflict() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let repo_path = test_env.env_root().join("repo");
+
+    std::fs::write(repo_path.join("file"), "a\n").unwrap();
+    test_env.run_jj_in(&repo_path, ["new"]).success();
+    std::fs::write(repo_path.join("file"), "b\n").unwrap();
+    test_env.run_jj_in(&repo_path, ["shelve"]).success();
+
+    // The shelved changes are merged into the working copy
+    std::fs::write(repo_path.join("file"), "c\n").unwrap();
+    let output = test_env.run_jj_in(&repo_path, ["unshelve", "1", "--keep"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Applied shelf 1
+    Working copy now at: rlvkpnrz e570bf42 (conflict) (no description set)
+    Parent commit      : qpvuntsm 6b1027d2 (no description set)
+    Added 0 files, modified 1 files, removed 0 files
+    Warning: There are unresolved conflicts at these paths:
+    file    2-sided conflict
+    New conflicts appeared in these commits:
+      rlvkpnrz e570bf42 (conflict) (no description set)
+    Hint: To resolve the conflicts, start by updating to it:
+      jj new rlvkpnrz
+    Then use `jj resolve`, or edit the conflict markers in the file directly.
+    Once the conflicts are resolved, you may want to inspect the result with `jj diff`.
+    Then run `jj squash` to move the resolution into the conflicted commit.
+    [EOF]
+    ");
+    let output = test_env.run_jj_in(&repo_path, ["file", "show", "file"]);
+    insta::assert_snapshot!(output, @r"
+    <<<<<<< Conflict 1 of 1
+    %%%%%%% Changes from base to side #1
+    -a
+    +c
+    +++++++ Contents of side #2
+    b
+    >>>>>>> Conflict 1 of 1 ends
+    [EOF]
+    ");
+    let output = test_env.run_jj_in(&repo_path, ["shelve", "--list"]);
+    insta::assert_snapshot!(output, @r"
+    1: kkmpptxz hidden 53530168 (no description set)
+    [EOF]
+    ");
+}
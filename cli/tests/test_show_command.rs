@@ -146,6 +146,7 @@ fn test_show_basic() {
     +bar
     +baz quux
     diff --git a/file1 b/file3
+    similarity index 100%
     rename from file1
     rename to file3
     [EOF]
@@ -169,6 +170,7 @@ fn test_show_basic() {
     +bar
     +baz quux
     diff --git a/file1 b/file3
+    similarity index 100%
     rename from file1
     rename to file3
     [EOF]
@@ -193,6 +195,7 @@ fn test_show_basic() {
     [38;5;2m<<diff added::+>>[4m<<diff added token::bar>>[24m[39m
     [38;5;2m<<diff added::+baz >>[4m<<diff added token::quux>>[24m<<diff added::>>[39m
     [1m<<diff file_header::diff --git a/file1 b/file3>>[0m
+    [1m<<diff file_header::similarity index 100%>>[0m
     [1m<<diff file_header::rename from file1>>[0m
     [1m<<diff file_header::rename to file3>>[0m
     [EOF]
@@ -219,6 +222,7 @@ fn test_show_basic() {
     +bar
     +baz quux
     diff --git a/file1 b/file3
+    similarity index 100%
     rename from file1
     rename to file3
     [EOF]
@@ -240,6 +244,164 @@ fn test_show_basic() {
     ");
 }
 
+#[test]
+fn test_show_diff_merges() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let repo_path = test_env.env_root().join("repo");
+
+    std::fs::write(repo_path.join("file"), "a\nb\nc\nd\ne\nf\ng\nh\n").unwrap();
+    test_env
+        .run_jj_in(&repo_path, ["describe", "-m=base"])
+        .success();
+    test_env.run_jj_in(&repo_path, ["new", "-m=left"]).success();
+    std::fs::write(repo_path.join("file"), "a\nB\nc\nd\ne\nf\ng\nh\n").unwrap();
+    std::fs::write(repo_path.join("left"), "left\n").unwrap();
+    test_env
+        .run_jj_in(&repo_path, ["new", "description(base)", "-m=right"])
+        .success();
+    std::fs::write(repo_path.join("file"), "a\nb\nc\nd\ne\nf\nG\nh\n").unwrap();
+    test_env
+        .run_jj_in(
+            &repo_path,
+            ["new", "description(left)", "description(right)", "-m=merge"],
+        )
+        .success();
+    // Change a line that wasn't changed by either parent
+    std::fs::write(repo_path.join("file"), "a\nB\nc\nD\ne\nf\nG\nh\n").unwrap();
+
+    // By default, the merge is compared to the merged parents
+    let output = test_env.run_jj_in(&repo_path, ["show", "-T=''", "--git"]);
+    insta::assert_snapshot!(output, @r"
+    diff --git a/file b/file
+    index e00739d5ed..8e67d34d75 100644
+    --- a/file
+    +++ b/file
+    @@ -1,7 +1,7 @@
+     a
+     B
+     c
+    -d
+    +D
+     e
+     f
+     G
+    [EOF]
+    ");
+
+    let output = test_env.run_jj_in(
+        &repo_path,
+        ["show", "-T=''", "--summary", "--diff-merges=first-parent"],
+    );
+    insta::assert_snapshot!(output, @r"
+    M file
+    [EOF]
+    ");
+
+    let output = test_env.run_jj_in(
+        &repo_path,
+        ["show", "-T=''", "--summary", "--diff-merges=all"],
+    );
+    insta::assert_snapshot!(output, @r"
+    Changes from parent fed3f3848f67:
+    M file
+    Changes from parent 60e926508830:
+    M file
+    A left
+    [EOF]
+    ");
+
+    let output = test_env.run_jj_in(
+        &repo_path,
+        [
+            "show",
+            "-T=''",
+            "--summary",
+            "--git",
+            "--diff-merges=combined",
+        ],
+    );
+    insta::assert_snapshot!(output, @r"
+    M file
+    diff --cc file
+    index 007f726334,bcbd9165a5..8e67d34d75
+    --- a/file
+    +++ b/file
+    @@@ -1,8 -1,8 +1,8 @@@
+      a
+     -b
+     +B
+      c
+    --d
+    ++D
+      e
+      f
+    - g
+    + G
+      h
+    [EOF]
+    ");
+
+    // Hunks that match one of the parents are omitted
+    let output = test_env.run_jj_in(
+        &repo_path,
+        [
+            "show",
+            "-T=''",
+            "--git",
+            "--context=0",
+            "--diff-merges=combined",
+        ],
+    );
+    insta::assert_snapshot!(output, @r"
+    diff --cc file
+    index 007f726334,bcbd9165a5..8e67d34d75
+    --- a/file
+    +++ b/file
+    @@@ -4,1 -4,1 +4,1 @@@
+    --d
+    ++D
+    [EOF]
+    ");
+
+    // The option only affects merge commits
+    let output = test_env.run_jj_in(
+        &repo_path,
+        [
+            "log",
+            "-T=description",
+            "--no-graph",
+            "-p",
+            "--summary",
+            "--diff-merges=combined",
+            "-r=description(right)|@",
+        ],
+    );
+    insta::assert_snapshot!(output, @r"
+    merge
+    M file
+    Modified regular file file:
+       1    1: a
+       2    2: B
+       3    3: c
+       4    4: dD
+       5    5: e
+       6    6: f
+       7    7: G
+       8    8: h
+    right
+    M file
+    Modified regular file file:
+        ...
+       4    4: d
+       5    5: e
+       6    6: f
+       7    7: gG
+       8    8: h
+    [EOF]
+    ");
+}
+
 #[test]
 fn test_show_with_template() {
     let test_env = TestEnvironment::default();
@@ -270,6 +432,9 @@ fn test_show_with_no_template() {
 
     For more information, try '--help'.
     Hint: The following template aliases are defined:
+    - builtin_bookmark_list
+    - builtin_bookmark_list_verbose
+    - builtin_file_list_json
     - builtin_log_comfortable
     - builtin_log_compact
     - builtin_log_compact_full_description
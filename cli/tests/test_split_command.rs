@@ -769,7 +769,8 @@ fn test_split_with_multiple_workspaces_same_working_copy() {
         .run_jj_in(&secondary_path, ["edit", "-r", "description(first-commit)"])
         .success();
     // Check the working-copy commit in each workspace in the log output. The "@"
-    // node in the graph indicates the current workspace's working-copy commit.
+    // node in the graph indicates the current workspace's working-copy commit,
+    // and the "◎" nodes indicate the other workspaces' working-copy commits.
     insta::assert_snapshot!(get_workspace_log_output(&test_env, &main_path), @r"
     @  qpvuntsmwlqt default@ second@ first-commit
     ◆  zzzzzzzzzzzz
@@ -832,10 +833,11 @@ fn test_split_with_multiple_workspaces_different_working_copy() {
         )
         .success();
     // Check the working-copy commit in each workspace in the log output. The "@"
-    // node in the graph indicates the current workspace's working-copy commit.
+    // node in the graph indicates the current workspace's working-copy commit,
+    // and the "◎" nodes indicate the other workspaces' working-copy commits.
     insta::assert_snapshot!(get_workspace_log_output(&test_env, &main_path), @r"
     @  qpvuntsmwlqt default@ first-commit
-    │ ○  pmmvwywvzvvn second@
+    │ ◎  pmmvwywvzvvn second@
     ├─╯
     ◆  zzzzzzzzzzzz
     [EOF]
@@ -852,7 +854,7 @@ fn test_split_with_multiple_workspaces_different_working_copy() {
     insta::assert_snapshot!(get_workspace_log_output(&test_env, &main_path), @r"
     @  mzvwutvlkqwt default@ second-commit
     ○  qpvuntsmwlqt first-commit
-    │ ○  pmmvwywvzvvn second@
+    │ ◎  pmmvwywvzvvn second@
     ├─╯
     ◆  zzzzzzzzzzzz
     [EOF]
@@ -872,7 +874,7 @@ fn test_split_with_multiple_workspaces_different_working_copy() {
     @  vruxwmqvtpmx default@ second-commit
     │ ○  qpvuntsmwlqt first-commit
     ├─╯
-    │ ○  pmmvwywvzvvn second@
+    │ ◎  pmmvwywvzvvn second@
     ├─╯
     ◆  zzzzzzzzzzzz
     [EOF]
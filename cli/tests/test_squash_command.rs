@@ -380,6 +380,39 @@ fn test_squash_keep_emptied() {
     ");
 }
 
+#[test]
+fn test_squash_drop_empty_config() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let repo_path = test_env.env_root().join("repo");
+
+    test_env
+        .run_jj_in(&repo_path, ["bookmark", "create", "-r@", "a"])
+        .success();
+    std::fs::write(repo_path.join("file1"), "a\n").unwrap();
+    test_env.run_jj_in(&repo_path, ["new"]).success();
+    test_env
+        .run_jj_in(&repo_path, ["bookmark", "create", "-r@", "b"])
+        .success();
+    std::fs::write(repo_path.join("file1"), "b\n").unwrap();
+
+    // With "never", the source commit is kept as if --keep-emptied were given
+    let output = test_env.run_jj_in(&repo_path, ["squash", "--config=rewrite.drop-empty=never"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Rebased 1 descendant commits
+    Working copy now at: kkmpptxz 61028fb9 b | (empty) (no description set)
+    Parent commit      : qpvuntsm a9df1b3f a | (no description set)
+    [EOF]
+    ");
+    insta::assert_snapshot!(get_log_output(&test_env, &repo_path), @r"
+    @  61028fb91a89 b (empty)
+    ○  a9df1b3fed99 a
+    ◆  000000000000 (empty)
+    [EOF]
+    ");
+}
+
 #[test]
 fn test_squash_from_to() {
     let test_env = TestEnvironment::default();
@@ -1360,6 +1393,86 @@ fn test_squash_empty() {
     ");
 }
 
+#[test]
+fn test_squash_combine_descriptions_setting() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let repo_path = test_env.env_root().join("repo");
+
+    std::fs::write(repo_path.join("file1"), "a\n").unwrap();
+    test_env
+        .run_jj_in(&repo_path, ["commit", "-m", "destination"])
+        .success();
+    std::fs::write(repo_path.join("file1"), "b\n").unwrap();
+    test_env
+        .run_jj_in(&repo_path, ["describe", "-m", "source"])
+        .success();
+
+    // Descriptions are concatenated without opening an editor
+    test_env
+        .run_jj_in(
+            &repo_path,
+            [
+                "squash",
+                "--config=squash.combine-descriptions=concatenate",
+                r#"--config=squash.description-separator="\n---\n""#,
+            ],
+        )
+        .success();
+    insta::assert_snapshot!(get_description(&test_env, &repo_path, "@-"), @r"
+    destination
+
+    ---
+    source
+    [EOF]
+    ");
+
+    // The destination's description is kept
+    test_env.run_jj_in(&repo_path, ["undo"]).success();
+    test_env
+        .run_jj_in(
+            &repo_path,
+            ["squash", "--config=squash.combine-descriptions=destination"],
+        )
+        .success();
+    insta::assert_snapshot!(get_description(&test_env, &repo_path, "@-"), @r"
+    destination
+    [EOF]
+    ");
+
+    // The source's description is used if the destination has none
+    test_env.run_jj_in(&repo_path, ["undo"]).success();
+    test_env
+        .run_jj_in(&repo_path, ["describe", "-r@-", "-m", ""])
+        .success();
+    test_env
+        .run_jj_in(
+            &repo_path,
+            ["squash", "--config=squash.combine-descriptions=destination"],
+        )
+        .success();
+    insta::assert_snapshot!(get_description(&test_env, &repo_path, "@-"), @r"
+    source
+    [EOF]
+    ");
+
+    // Invalid mode
+    test_env.run_jj_in(&repo_path, ["undo"]).success();
+    let output = test_env.run_jj_in(
+        &repo_path,
+        ["squash", "--config=squash.combine-descriptions=bad"],
+    );
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Config error: Invalid type or value for squash.combine-descriptions
+    Caused by: unknown variant `bad`, expected one of `edit`, `concatenate`, `destination`
+
+    For help, see https://jj-vcs.github.io/jj/latest/config/ or use `jj help -k config`.
+    [EOF]
+    [exit status: 1]
+    ");
+}
+
 #[test]
 fn test_squash_use_destination_message() {
     let test_env = TestEnvironment::default();
@@ -1442,6 +1555,36 @@ fn test_squash_use_destination_message_and_message_mutual_exclusion() {
     ");
 }
 
+#[test]
+fn test_squash_keep_timestamps() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let repo_path = test_env.env_root().join("repo");
+    std::fs::write(repo_path.join("file1"), "a\n").unwrap();
+    test_env.run_jj_in(&repo_path, ["commit", "-m=a"]).success();
+    std::fs::write(repo_path.join("file1"), "b\n").unwrap();
+    let template = r#"description.first_line() ++ " " ++ committer.timestamp() ++ "\n""#;
+    let output = test_env.run_jj_in(&repo_path, ["log", "--no-graph", "-T", template]);
+    insta::assert_snapshot!(output, @r"
+     2001-02-03 04:05:09.000 +07:00
+    a 2001-02-03 04:05:08.000 +07:00
+     1970-01-01 00:00:00.000 +00:00
+    [EOF]
+    ");
+
+    // The destination keeps its committer timestamp
+    test_env
+        .run_jj_in(&repo_path, ["squash", "--keep-timestamps"])
+        .success();
+    let output = test_env.run_jj_in(&repo_path, ["log", "--no-graph", "-T", template]);
+    insta::assert_snapshot!(output, @r"
+     2001-02-03 04:05:10.000 +07:00
+    a 2001-02-03 04:05:08.000 +07:00
+     1970-01-01 00:00:00.000 +00:00
+    [EOF]
+    ");
+}
+
 #[must_use]
 fn get_description(test_env: &TestEnvironment, repo_path: &Path, rev: &str) -> CommandOutput {
     test_env.run_jj_in(
@@ -199,9 +199,6 @@ fn test_status_display_relevant_working_commit_conflict_hints() {
 
     let output = test_env.run_jj_in(&repo_path, ["status"]);
     insta::assert_snapshot!(output, @r"
-    The working copy has no changes.
-    Working copy : yqosqzyt dcb25635 (conflict) (empty) boom-cont-2
-    Parent commit: royxmykx 664a4c6c (conflict) (empty) boom-cont
     Warning: There are unresolved conflicts at these paths:
     conflicted.txt    2-sided conflict
     Hint: To resolve the conflicts, start by updating to the first one:
@@ -209,14 +206,14 @@ fn test_status_display_relevant_working_commit_conflict_hints() {
     Then use `jj resolve`, or edit the conflict markers in the file directly.
     Once the conflicts are resolved, you may want to inspect the result with `jj diff`.
     Then run `jj squash` to move the resolution into the conflicted commit.
+    The working copy has no changes.
+    Working copy : yqosqzyt dcb25635 (conflict) (empty) boom-cont-2
+    Parent commit: royxmykx 664a4c6c (conflict) (empty) boom-cont
     [EOF]
     ");
 
     let output = test_env.run_jj_in(&repo_path, ["status", "--color=always"]);
     insta::assert_snapshot!(output, @r"
-    The working copy has no changes.
-    Working copy : [1m[38;5;13my[38;5;8mqosqzyt[39m [38;5;12md[38;5;8mcb25635[39m [38;5;9m(conflict)[39m [38;5;10m(empty)[39m boom-cont-2[0m
-    Parent commit: [1m[38;5;5mr[0m[38;5;8moyxmykx[39m [1m[38;5;4m6[0m[38;5;8m64a4c6c[39m [38;5;1m(conflict)[39m [38;5;2m(empty)[39m boom-cont
     [1m[38;5;3mWarning: [39mThere are unresolved conflicts at these paths:[0m
     conflicted.txt    [38;5;3m2-sided conflict[39m
     [1m[38;5;6mHint: [0m[39mTo resolve the conflicts, start by updating to the first one:[39m
@@ -224,6 +221,9 @@ fn test_status_display_relevant_working_commit_conflict_hints() {
     [39mThen use `jj resolve`, or edit the conflict markers in the file directly.[39m
     [39mOnce the conflicts are resolved, you may want to inspect the result with `jj diff`.[39m
     [39mThen run `jj squash` to move the resolution into the conflicted commit.[39m
+    The working copy has no changes.
+    Working copy : [1m[38;5;13my[38;5;8mqosqzyt[39m [38;5;12md[38;5;8mcb25635[39m [38;5;9m(conflict)[39m [38;5;10m(empty)[39m boom-cont-2[0m
+    Parent commit: [1m[38;5;5mr[0m[38;5;8moyxmykx[39m [1m[38;5;4m6[0m[38;5;8m64a4c6c[39m [38;5;1m(conflict)[39m [38;5;2m(empty)[39m boom-cont
     [EOF]
     ");
 
@@ -232,11 +232,11 @@ fn test_status_display_relevant_working_commit_conflict_hints() {
         ["status", "--config=hints.resolving-conflicts=false"],
     );
     insta::assert_snapshot!(output, @r"
+    Warning: There are unresolved conflicts at these paths:
+    conflicted.txt    2-sided conflict
     The working copy has no changes.
     Working copy : yqosqzyt dcb25635 (conflict) (empty) boom-cont-2
     Parent commit: royxmykx 664a4c6c (conflict) (empty) boom-cont
-    Warning: There are unresolved conflicts at these paths:
-    conflicted.txt    2-sided conflict
     [EOF]
     ");
 
@@ -395,10 +395,6 @@ fn test_status_simplify_conflict_sides() {
 
     insta::assert_snapshot!(test_env.run_jj_in(&repo_path, ["status"]),
     @r"
-    The working copy has no changes.
-    Working copy : nkmrtpmo 83c4b9e7 conflict | (conflict) (empty) conflict
-    Parent commit: kmkuslsw 4601566f conflictA | (conflict) (empty) conflictA
-    Parent commit: lylxulpl 6f8d8381 conflictB | (conflict) (empty) conflictB
     Warning: There are unresolved conflicts at these paths:
     fileA    2-sided conflict
     fileB    2-sided conflict
@@ -408,6 +404,10 @@ fn test_status_simplify_conflict_sides() {
     Then use `jj resolve`, or edit the conflict markers in the file directly.
     Once the conflicts are resolved, you may want to inspect the result with `jj diff`.
     Then run `jj squash` to move the resolution into the conflicted commit.
+    The working copy has no changes.
+    Working copy : nkmrtpmo 83c4b9e7 conflict | (conflict) (empty) conflict
+    Parent commit: kmkuslsw 4601566f conflictA | (conflict) (empty) conflictA
+    Parent commit: lylxulpl 6f8d8381 conflictB | (conflict) (empty) conflictB
     [EOF]
     ");
 }
@@ -515,3 +515,303 @@ fn test_status_untracked_files() {
     [EOF]
     ");
 }
+
+#[test]
+fn test_status_defer_snapshot() {
+    let test_env = TestEnvironment::default();
+    test_env.add_config("snapshot.defer-in-status = true");
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let repo_path = test_env.env_root().join("repo");
+
+    std::fs::write(repo_path.join("file1"), "1").unwrap();
+    std::fs::write(repo_path.join("file2"), "2").unwrap();
+    test_env.run_jj_in(&repo_path, ["new"]).success();
+
+    // Unchanged working copy
+    let output = test_env.run_jj_in(&repo_path, ["status"]);
+    insta::assert_snapshot!(output, @r"
+    The working copy has no changes.
+    Working copy : rlvkpnrz 0cb36939 (empty) (no description set)
+    Parent commit: qpvuntsm af3c0426 (no description set)
+    [EOF]
+    ");
+
+    std::fs::write(repo_path.join("file1"), "modified").unwrap();
+    std::fs::remove_file(repo_path.join("file2")).unwrap();
+    std::fs::write(repo_path.join("file3"), "3").unwrap();
+    let output = test_env.run_jj_in(&repo_path, ["status"]);
+    insta::assert_snapshot!(output, @r"
+    Working copy changes (not snapshotted yet):
+    M file1
+    D file2
+    A file3
+    Working copy : rlvkpnrz 0cb36939 (empty) (no description set)
+    Parent commit: qpvuntsm af3c0426 (no description set)
+    [EOF]
+    ");
+    let output = test_env.run_jj_in(&repo_path, ["status", "file1", "file3"]);
+    insta::assert_snapshot!(output, @r"
+    Working copy changes (not snapshotted yet):
+    M file1
+    A file3
+    Working copy : rlvkpnrz 0cb36939 (empty) (no description set)
+    Parent commit: qpvuntsm af3c0426 (no description set)
+    [EOF]
+    ");
+
+    // No operation was recorded
+    let output = test_env.run_jj_in(
+        &repo_path,
+        [
+            "op",
+            "log",
+            "--ignore-working-copy",
+            "--no-graph",
+            "-T=description.first_line() ++ \"\\n\"",
+        ],
+    );
+    insta::assert_snapshot!(output, @r"
+    new empty commit
+    snapshot working copy
+    add workspace 'default'
+
+    [EOF]
+    ");
+
+    // The next command snapshots the changes
+    let output = test_env.run_jj_in(&repo_path, ["diff", "--summary"]);
+    insta::assert_snapshot!(output, @r"
+    M file1
+    D file2
+    A file3
+    [EOF]
+    ");
+    let output = test_env.run_jj_in(&repo_path, ["status"]);
+    insta::assert_snapshot!(output, @r"
+    Working copy changes:
+    M file1
+    D file2
+    A file3
+    Working copy : rlvkpnrz db41412f (no description set)
+    Parent commit: qpvuntsm af3c0426 (no description set)
+    [EOF]
+    ");
+}
+
+#[test]
+fn test_status_divergent_hint() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let repo_path = test_env.env_root().join("repo");
+
+    test_env
+        .run_jj_in(&repo_path, ["describe", "-m", "description 1"])
+        .success();
+    test_env
+        .run_jj_in(
+            &repo_path,
+            ["describe", "-m", "description 2", "--at-operation", "@-"],
+        )
+        .success();
+
+    let output = test_env.run_jj_in(&repo_path, ["status"]);
+    insta::assert_snapshot!(output, @r"
+    The working copy has no changes.
+    Working copy : qpvuntsm?? d13ecdbd (empty) description 1
+    Parent commit: zzzzzzzz 00000000 (empty) (no description set)
+    Hint: The working-copy change is divergent (2 visible commits share its change ID). Use `jj log` to inspect them and `jj abandon` to drop the unwanted ones.
+    [EOF]
+    ------- stderr -------
+    Concurrent modification detected, resolving automatically.
+    [EOF]
+    ");
+
+    // The hint can be disabled
+    let output = test_env.run_jj_in(
+        &repo_path,
+        ["status", "--config=hints.status-divergent-change=false"],
+    );
+    insta::assert_snapshot!(output, @r"
+    The working copy has no changes.
+    Working copy : qpvuntsm?? d13ecdbd (empty) description 1
+    Parent commit: zzzzzzzz 00000000 (empty) (no description set)
+    [EOF]
+    ");
+}
+
+#[test]
+fn test_status_unpushed_bookmarks_hint() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "origin"]).success();
+    let origin_path = test_env.env_root().join("origin");
+    let origin_git_repo_path = origin_path
+        .join(".jj")
+        .join("repo")
+        .join("store")
+        .join("git");
+    test_env
+        .run_jj_in(&origin_path, ["describe", "-m=description 1"])
+        .success();
+    test_env
+        .run_jj_in(&origin_path, ["bookmark", "create", "-r@", "bookmark1"])
+        .success();
+    test_env
+        .run_jj_in(&origin_path, ["git", "export"])
+        .success();
+    test_env
+        .run_jj_in(
+            ".",
+            [
+                "git",
+                "clone",
+                "--config=git.auto-local-bookmark=true",
+                origin_git_repo_path.to_str().unwrap(),
+                "local",
+            ],
+        )
+        .success();
+    let repo_path = test_env.env_root().join("local");
+
+    // Bookmark is in sync with the remote
+    test_env
+        .run_jj_in(&repo_path, ["new", "bookmark1"])
+        .success();
+    let output = test_env.run_jj_in(&repo_path, ["status"]);
+    insta::assert_snapshot!(output, @r"
+    The working copy has no changes.
+    Working copy : royxmykx 88806b26 (empty) (no description set)
+    Parent commit: xtvrqkyv d13ecdbd bookmark1 | (empty) description 1
+    [EOF]
+    ");
+
+    // Move the bookmark locally
+    test_env
+        .run_jj_in(&repo_path, ["describe", "-m=description 2"])
+        .success();
+    test_env
+        .run_jj_in(&repo_path, ["bookmark", "set", "-r@", "bookmark1"])
+        .success();
+    let output = test_env.run_jj_in(&repo_path, ["status"]);
+    insta::assert_snapshot!(output, @r"
+    The working copy has no changes.
+    Working copy : royxmykx ca4329a6 bookmark1* | (empty) description 2
+    Parent commit: xtvrqkyv d13ecdbd bookmark1@origin | (empty) description 1
+    Hint: These bookmarks have changes not pushed to their tracked remotes: bookmark1. Use `jj git push` to push them.
+    [EOF]
+    ");
+}
+
+#[test]
+fn test_status_conflicts_hint() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let repo_path = test_env.env_root().join("repo");
+
+    std::fs::write(repo_path.join("file"), "a\n").unwrap();
+    test_env
+        .run_jj_in(&repo_path, ["describe", "-m=a"])
+        .success();
+    test_env
+        .run_jj_in(&repo_path, ["new", "root()", "-m=b"])
+        .success();
+    std::fs::write(repo_path.join("file"), "b\n").unwrap();
+    test_env
+        .run_jj_in(&repo_path, ["new", "description(a)", "description(b)"])
+        .success();
+
+    // Only the working-copy commit has conflicts
+    let output = test_env.run_jj_in(&repo_path, ["status"]);
+    insta::assert_snapshot!(output, @r"
+    Warning: There are unresolved conflicts at these paths:
+    file    2-sided conflict
+    The working copy has no changes.
+    Working copy : zsuskuln daa35a84 (conflict) (empty) (no description set)
+    Parent commit: qpvuntsm b5eb8ca5 a
+    Parent commit: kkmpptxz 28610a3c b
+    Hint: Use `jj resolve` to resolve the conflicts with a merge tool, or edit the conflict markers in the files directly.
+    [EOF]
+    ");
+
+    // The hint can be disabled
+    let output = test_env.run_jj_in(
+        &repo_path,
+        ["status", "--config=hints.status-conflicts=false"],
+    );
+    insta::assert_snapshot!(output, @r"
+    Warning: There are unresolved conflicts at these paths:
+    file    2-sided conflict
+    The working copy has no changes.
+    Working copy : zsuskuln daa35a84 (conflict) (empty) (no description set)
+    Parent commit: qpvuntsm b5eb8ca5 a
+    Parent commit: kkmpptxz 28610a3c b
+    [EOF]
+    ");
+}
+
+#[test]
+fn test_status_stale_working_copy_hint() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let repo_path = test_env.env_root().join("repo");
+
+    std::fs::write(repo_path.join("file"), "contents\n").unwrap();
+    test_env.run_jj_in(&repo_path, ["status"]).success();
+    // Rewrite the working-copy commit without updating the working copy
+    test_env
+        .run_jj_in(&repo_path, ["restore", "--ignore-working-copy"])
+        .success();
+
+    let output = test_env.run_jj_in(&repo_path, ["status", "--ignore-working-copy"]);
+    insta::assert_snapshot!(output, @r"
+    The working copy has no changes.
+    Working copy : qpvuntsm a5a4bc5d (empty) (no description set)
+    Parent commit: zzzzzzzz 00000000 (empty) (no description set)
+    Hint: The working copy is stale (not updated since the working-copy commit was rewritten). Use `jj workspace update-stale` to update it.
+    [EOF]
+    ");
+
+    test_env
+        .run_jj_in(&repo_path, ["workspace", "update-stale"])
+        .success();
+    let output = test_env.run_jj_in(&repo_path, ["status", "--ignore-working-copy"]);
+    insta::assert_snapshot!(output, @r"
+    The working copy has no changes.
+    Working copy : qpvuntsm a5a4bc5d (empty) (no description set)
+    Parent commit: zzzzzzzz 00000000 (empty) (no description set)
+    [EOF]
+    ");
+}
+
+#[test]
+fn test_status_detached_head_hint() {
+    let test_env = TestEnvironment::default();
+    test_env
+        .run_jj_in(".", ["git", "init", "--colocate", "repo"])
+        .success();
+    let repo_path = test_env.env_root().join("repo");
+
+    std::fs::write(repo_path.join("file"), "contents\n").unwrap();
+    test_env
+        .run_jj_in(&repo_path, ["commit", "-m=first"])
+        .success();
+    let output = test_env.run_jj_in(&repo_path, ["status"]);
+    insta::assert_snapshot!(output, @r"
+    The working copy has no changes.
+    Working copy : rlvkpnrz 3970c4ab (empty) (no description set)
+    Parent commit: qpvuntsm c37998f9 first
+    Hint: Git's HEAD is detached at the parent of the working-copy commit, which has no bookmark. Use `jj bookmark create <name> -r @-` to name it for Git tools.
+    [EOF]
+    ");
+
+    // No hint once the parent has a bookmark
+    test_env
+        .run_jj_in(&repo_path, ["bookmark", "create", "-r@-", "feature"])
+        .success();
+    let output = test_env.run_jj_in(&repo_path, ["status"]);
+    insta::assert_snapshot!(output, @r"
+    The working copy has no changes.
+    Working copy : rlvkpnrz 3970c4ab (empty) (no description set)
+    Parent commit: qpvuntsm c37998f9 feature | first
+    [EOF]
+    ");
+}
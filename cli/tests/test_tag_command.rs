@@ -142,4 +142,12 @@ fn test_tag_list() {
     added_targets: commit2
     [EOF]
     ");
+
+    insta::assert_snapshot!(
+        test_env.run_jj_in(&repo_path, ["tag", "list", "--json", "glob:*tag"]),
+        @r#"
+    {"added_targets":["3db783e0fc377b1a54ff5f775794e13e76c25da2","68d950cee071c8ba75711a6eef4bc4744d810bf5"],"conflict":true,"name":"conflicted_tag","present":true,"remote":null,"removed_targets":["caf975d0989a4e84d6d2fd1047ea03663244969c"],"tracked":[]}
+    {"added_targets":["caf975d0989a4e84d6d2fd1047ea03663244969c"],"conflict":false,"name":"test_tag","present":true,"remote":null,"removed_targets":[],"tracked":[]}
+    [EOF]
+    "#);
 }
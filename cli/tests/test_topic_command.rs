@@ -0,0 +1,179 @@
+// Copyright 2025 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::common::TestEnvironment;
+
+#[test]
+fn test_topic_set_unset() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let repo_path = test_env.env_root().join("repo");
+
+    test_env
+        .run_jj_in(&repo_path, ["describe", "-mcommit1"])
+        .success();
+    test_env
+        .run_jj_in(&repo_path, ["new", "-mcommit2"])
+        .success();
+    test_env
+        .run_jj_in(&repo_path, ["new", "-mcommit3"])
+        .success();
+
+    let output = test_env.run_jj_in(&repo_path, ["topic", "set", "feature", "-r@--|@-"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Added 2 changes to topic feature
+    [EOF]
+    ");
+    let output = test_env.run_jj_in(&repo_path, ["topic", "set", "other"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Added 1 changes to topic other
+    [EOF]
+    ");
+    let output = test_env.run_jj_in(&repo_path, ["topic", "set", "other"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Nothing changed.
+    [EOF]
+    ");
+    let output = test_env.run_jj_in(&repo_path, ["topic", "set", "bad name"]);
+    insta::assert_snapshot!(output, @r#"
+    ------- stderr -------
+    Error: Invalid topic name: "bad name"
+    [EOF]
+    [exit status: 1]
+    "#);
+
+    insta::assert_snapshot!(get_log_output(&test_env, &repo_path), @r"
+    @  zsuskulnrvyr commit3 other
+    ○  kkmpptxzrspx commit2 feature
+    ○  qpvuntsmwlqt commit1 feature
+    ◆  zzzzzzzzzzzz
+    [EOF]
+    ");
+    let output = test_env.run_jj_in(&repo_path, ["log", "-r", "topic(feature)"]);
+    insta::assert_snapshot!(output, @r"
+    ○  kkmpptxz test.user@example.com 2001-02-03 08:05:09 feature d0a19ea4
+    │  (empty) commit2
+    ○  qpvuntsm test.user@example.com 2001-02-03 08:05:08 feature caf975d0
+    │  (empty) commit1
+    ~
+    [EOF]
+    ");
+
+    // Topics follow rewritten changes
+    test_env
+        .run_jj_in(&repo_path, ["describe", "-r@--", "-mcommit1 rewritten"])
+        .success();
+    insta::assert_snapshot!(get_log_output(&test_env, &repo_path), @r"
+    @  zsuskulnrvyr commit3 other
+    ○  kkmpptxzrspx commit2 feature
+    ○  qpvuntsmwlqt commit1 rewritten feature
+    ◆  zzzzzzzzzzzz
+    [EOF]
+    ");
+
+    let output = test_env.run_jj_in(&repo_path, ["topic", "unset", "feature", "-r@--"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Removed 1 changes from topic feature
+    [EOF]
+    ");
+    let output = test_env.run_jj_in(&repo_path, ["topic", "unset", "feature", "-r@--"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Nothing changed.
+    [EOF]
+    ");
+    insta::assert_snapshot!(get_log_output(&test_env, &repo_path), @r"
+    @  zsuskulnrvyr commit3 other
+    ○  kkmpptxzrspx commit2 feature
+    ○  qpvuntsmwlqt commit1 rewritten
+    ◆  zzzzzzzzzzzz
+    [EOF]
+    ");
+}
+
+#[test]
+fn test_topic_list_delete() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let repo_path = test_env.env_root().join("repo");
+
+    test_env
+        .run_jj_in(&repo_path, ["describe", "-mcommit1"])
+        .success();
+    test_env
+        .run_jj_in(&repo_path, ["topic", "set", "topic-a"])
+        .success();
+    test_env
+        .run_jj_in(&repo_path, ["new", "-mcommit2"])
+        .success();
+    test_env
+        .run_jj_in(&repo_path, ["topic", "set", "topic-a"])
+        .success();
+    test_env
+        .run_jj_in(&repo_path, ["topic", "set", "topic-b"])
+        .success();
+
+    let output = test_env.run_jj_in(&repo_path, ["topic", "list"]);
+    insta::assert_snapshot!(output, @r"
+    topic-a:
+      zsuskuln e74ba982 (empty) commit2
+      qpvuntsm caf975d0 (empty) commit1
+    topic-b:
+      zsuskuln e74ba982 (empty) commit2
+    [EOF]
+    ");
+    let output = test_env.run_jj_in(&repo_path, ["topic", "list", "glob:*-b"]);
+    insta::assert_snapshot!(output, @r"
+    topic-b:
+      zsuskuln e74ba982 (empty) commit2
+    [EOF]
+    ");
+
+    // Topics of abandoned changes are hidden
+    test_env.run_jj_in(&repo_path, ["abandon", "@-"]).success();
+    let output = test_env.run_jj_in(&repo_path, ["topic", "list"]);
+    insta::assert_snapshot!(output, @r"
+    topic-a:
+      zsuskuln 81307c4f (empty) commit2
+    topic-b:
+      zsuskuln 81307c4f (empty) commit2
+    [EOF]
+    ");
+
+    let output = test_env.run_jj_in(
+        &repo_path,
+        ["topic", "delete", "glob:topic-*", "nonexistent"],
+    );
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Warning: No matching topics for pattern: nonexistent
+    Deleted 2 topics.
+    [EOF]
+    ");
+    let output = test_env.run_jj_in(&repo_path, ["topic", "list"]);
+    insta::assert_snapshot!(output, @"");
+}
+
+#[must_use]
+fn get_log_output(
+    test_env: &TestEnvironment,
+    cwd: &std::path::Path,
+) -> crate::common::CommandOutput {
+    let template = r#"separate(" ", change_id.short(), description.first_line(), topics)"#;
+    test_env.run_jj_in(cwd, ["log", "-T", template])
+}
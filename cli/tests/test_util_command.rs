@@ -13,6 +13,7 @@
 // limitations under the License.
 
 use insta::assert_snapshot;
+use testutils::git;
 
 use crate::common::TestEnvironment;
 
@@ -106,6 +107,148 @@ fn test_gc_operation_log() {
     "#);
 }
 
+#[test]
+fn test_maintenance_run() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let repo_path = test_env.env_root().join("repo");
+    let times_path = repo_path.join(".jj/repo/maintenance.json");
+
+    let output = test_env.run_jj_in(&repo_path, ["util", "maintenance", "run"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Finished maintenance task gc
+    Finished maintenance task index
+    [EOF]
+    ");
+    let times = std::fs::read_to_string(&times_path).unwrap();
+    assert!(times.contains(r#""gc":"#));
+    assert!(times.contains(r#""index":"#));
+    assert!(!times.contains(r#""prefetch":"#));
+
+    // Disabled tasks can be run explicitly.
+    let output = test_env.run_jj_in(
+        &repo_path,
+        ["util", "maintenance", "run", "--task=prefetch"],
+    );
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Finished maintenance task prefetch
+    [EOF]
+    ");
+    let times = std::fs::read_to_string(&times_path).unwrap();
+    assert!(times.contains(r#""prefetch":"#));
+
+    // Tasks that aren't due are skipped.
+    let output = test_env.run_jj_in(&repo_path, ["util", "maintenance", "run", "--auto"]);
+    insta::assert_snapshot!(output, @"");
+    assert_eq!(std::fs::read_to_string(&times_path).unwrap(), times);
+
+    let output = test_env.run_jj_in(&repo_path, ["util", "maintenance", "run", "--at-op=@-"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Error: Cannot run maintenance from a non-head operation
+    [EOF]
+    [exit status: 1]
+    ");
+}
+
+#[test]
+fn test_maintenance_prefetch() {
+    let test_env = TestEnvironment::default();
+    let git_repo_path = test_env.env_root().join("git-repo");
+    let git_repo = git::init(&git_repo_path);
+    let commit = git::add_commit(
+        &git_repo,
+        "refs/heads/main",
+        "file",
+        b"content",
+        "message",
+        &[],
+    );
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let repo_path = test_env.env_root().join("repo");
+    test_env
+        .run_jj_in(
+            &repo_path,
+            ["git", "remote", "add", "origin", "../git-repo"],
+        )
+        .success();
+
+    test_env
+        .run_jj_in(
+            &repo_path,
+            ["util", "maintenance", "run", "--task=prefetch"],
+        )
+        .success();
+    let jj_git_repo = git::open(repo_path.join(".jj/repo/store/git"));
+    let prefetched = jj_git_repo
+        .find_reference("refs/prefetch/remotes/origin/main")
+        .unwrap();
+    assert_eq!(prefetched.id().detach(), commit.commit_id);
+
+    // The remote-tracking bookmarks aren't updated.
+    let output = test_env.run_jj_in(&repo_path, ["bookmark", "list", "--all-remotes"]);
+    insta::assert_snapshot!(output, @"");
+}
+
+#[test]
+fn test_maintenance_auto() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let repo_path = test_env.env_root().join("repo");
+    let times_path = repo_path.join(".jj/repo/maintenance.json");
+
+    // Maintenance isn't run automatically by default.
+    test_env.run_jj_in(&repo_path, ["log"]).success();
+    assert!(!times_path.exists());
+
+    let output = test_env.run_jj_in(&repo_path, ["util", "maintenance", "register"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Maintenance tasks will run automatically in this repository.
+    [EOF]
+    ");
+    let config = std::fs::read_to_string(repo_path.join(".jj/repo/config.toml")).unwrap();
+    insta::assert_snapshot!(config, @r"
+    [maintenance]
+    auto = true
+    ");
+    // The next command runs the due tasks.
+    test_env.run_jj_in(&repo_path, ["log"]).success();
+    let times = std::fs::read_to_string(&times_path).unwrap();
+    assert!(times.contains(r#""gc":"#));
+    assert!(times.contains(r#""index":"#));
+
+    // Tasks aren't run again until the interval has passed.
+    test_env.run_jj_in(&repo_path, ["log"]).success();
+    assert_eq!(std::fs::read_to_string(&times_path).unwrap(), times);
+
+    // Tasks are run after other commands once they're due.
+    std::thread::sleep(std::time::Duration::from_millis(10));
+    test_env
+        .run_jj_in(
+            &repo_path,
+            ["log", "--config=maintenance.gc.interval-hours=0"],
+        )
+        .success();
+    let new_times = std::fs::read_to_string(&times_path).unwrap();
+    assert_ne!(new_times, times);
+
+    // Maintenance isn't run when loading an old operation.
+    test_env
+        .run_jj_in(
+            &repo_path,
+            [
+                "log",
+                "--at-op=@-",
+                "--config=maintenance.gc.interval-hours=0",
+            ],
+        )
+        .success();
+    assert_eq!(std::fs::read_to_string(&times_path).unwrap(), new_times);
+}
+
 #[test]
 fn test_shell_completions() {
     #[track_caller]
@@ -128,6 +271,38 @@ fn test_shell_completions() {
     test("zsh");
 }
 
+#[test]
+fn test_shell_completions_dynamic() {
+    #[track_caller]
+    fn test(shell: &str) {
+        let test_env = TestEnvironment::default();
+        let output = test_env
+            .run_jj_in(".", ["util", "completion", "--dynamic", shell])
+            .success();
+        // The script should call back into jj with the COMPLETE variable set
+        assert!(
+            output
+                .stdout
+                .raw()
+                .contains(&format!("COMPLETE=\"{shell}\"")),
+            "{output}"
+        );
+        assert!(output.stderr.is_empty(), "{output}");
+    }
+
+    test("bash");
+    test("zsh");
+
+    let test_env = TestEnvironment::default();
+    let output = test_env.run_jj_in(".", ["util", "completion", "--dynamic", "nushell"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Error: Dynamic completion isn't supported for Nushell
+    [EOF]
+    [exit status: 1]
+    ");
+}
+
 #[test]
 fn test_util_exec() {
     let test_env = TestEnvironment::default();
@@ -158,3 +333,140 @@ fn test_util_exec_fail() {
     [exit status: 1]
     ");
 }
+
+#[cfg(unix)]
+#[test]
+fn test_util_exec_repo_env() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let repo_path = test_env.env_root().join("repo");
+    let print_env = [
+        "util",
+        "exec",
+        "--",
+        "sh",
+        "-c",
+        r#"printf '[%s]\n' "$JJ_WORKSPACE_ROOT" "$JJ_WORKSPACE_NAME" "$JJ_OP_ID" \
+            "$JJ_WORKING_COPY_COMMIT_ID""#,
+    ];
+
+    // The working copy is snapshotted before running the command
+    std::fs::write(repo_path.join("file"), "a").unwrap();
+    let output = test_env.run_jj_in(&repo_path, print_env);
+    insta::assert_snapshot!(output, @r"
+    [$TEST_ENV/repo]
+    [default]
+    [0753671bf32ee2770edf6ad482d055ba26e7e7fa9d06c5ab5c18c8c8aa022d2cfd5dd6e18d15856fad0db9d3e6de40c408c0ce8843bbeebba145524249554517]
+    [f3220d78b25353bd8fd65988018004b962d0894a]
+    [EOF]
+    ");
+    let output = test_env.run_jj_in(&repo_path, ["log", "--no-graph", "-r@", "-T", "commit_id"]);
+    insta::assert_snapshot!(output, @r"
+    f3220d78b25353bd8fd65988018004b962d0894a[EOF]
+    ");
+
+    // Without snapshotting, the previous working-copy commit is reported
+    std::fs::write(repo_path.join("file"), "b").unwrap();
+    let output = test_env.run_jj_in(
+        &repo_path,
+        [&["util", "exec", "--no-snapshot"], &print_env[2..]].concat(),
+    );
+    insta::assert_snapshot!(output, @r"
+    [$TEST_ENV/repo]
+    [default]
+    [0753671bf32ee2770edf6ad482d055ba26e7e7fa9d06c5ab5c18c8c8aa022d2cfd5dd6e18d15856fad0db9d3e6de40c408c0ce8843bbeebba145524249554517]
+    [f3220d78b25353bd8fd65988018004b962d0894a]
+    [EOF]
+    ");
+
+    // No repo variables are set outside a repo
+    let output = test_env.run_jj_in(".", print_env);
+    insta::assert_snapshot!(output, @r"
+    []
+    []
+    []
+    []
+    [EOF]
+    ");
+}
+
+#[test]
+fn test_util_upgrade_repo() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let repo_path = test_env.env_root().join("repo");
+    let store_repo_path = repo_path.join(".jj").join("repo");
+
+    // Simulate a repo created before format versions were recorded
+    for dir in ["store", "op_store", "op_heads", "index", "submodule_store"] {
+        std::fs::remove_file(store_repo_path.join(dir).join("format_version")).unwrap();
+    }
+
+    // Unversioned stores are used as is, and loading the repo doesn't write
+    // format versions
+    test_env.run_jj_in(&repo_path, ["log"]).success();
+    assert!(!store_repo_path
+        .join("index")
+        .join("format_version")
+        .exists());
+
+    // The default backup directory must not exist
+    std::fs::create_dir(store_repo_path.join("upgrade-backup")).unwrap();
+    let output = test_env.run_jj_in(&repo_path, ["util", "upgrade-repo"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Error: Backup directory $TEST_ENV/repo/.jj/repo/upgrade-backup already exists
+    Hint: Remove it, or specify another directory with `--backup-dir`.
+    [EOF]
+    [exit status: 1]
+    ");
+    std::fs::remove_dir(store_repo_path.join("upgrade-backup")).unwrap();
+
+    let output = test_env.run_jj_in(&repo_path, ["util", "upgrade-repo"]);
+    insta::assert_snapshot!(output, @r#"
+    ------- stderr -------
+    Upgraded commit store from format version 0 to 1
+    Upgraded operation store from format version 0 to 1
+    Upgraded operation heads store from format version 0 to 1
+    Upgraded index store from format version 0 to 1
+    Upgraded submodule store from format version 0 to 1
+    Backed up the previous stores to ".jj/repo/upgrade-backup"
+    [EOF]
+    "#);
+    assert!(store_repo_path
+        .join("upgrade-backup")
+        .join("store")
+        .join("type")
+        .is_file());
+    assert!(store_repo_path
+        .join("upgrade-backup")
+        .join("op_store")
+        .join("operations")
+        .is_dir());
+    test_env.run_jj_in(&repo_path, ["log"]).success();
+
+    let output = test_env.run_jj_in(&repo_path, ["util", "upgrade-repo"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    The repository is already in the current format.
+    [EOF]
+    ");
+
+    // Repo written by a newer version of jj
+    std::fs::write(store_repo_path.join("index").join("format_version"), "100").unwrap();
+    let output = test_env.run_jj_in(&repo_path, ["log"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Error: The index store has format version 100, but this version of jj only supports up to version 1
+    Hint: The repository was written by a newer version of jj. Upgrade jj to use it.
+    [EOF]
+    [exit status: 1]
+    ");
+    let output = test_env.run_jj_in(&repo_path, ["util", "upgrade-repo"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Error: The index store has format version 100, but this version of jj only supports up to version 1
+    [EOF]
+    [exit status: 1]
+    ");
+}
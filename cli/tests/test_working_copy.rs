@@ -320,6 +320,56 @@ fn test_snapshot_invalid_ignore_pattern() {
     ");
 }
 
+#[test]
+fn test_snapshot_nested_repository() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let repo_path = test_env.env_root().join("repo");
+    test_env
+        .run_jj_in(&repo_path, ["git", "init", "nested"])
+        .success();
+    std::fs::write(repo_path.join("nested").join("file"), "contents").unwrap();
+    std::fs::write(repo_path.join("file"), "contents").unwrap();
+
+    // Files in the nested repository are ignored by default
+    let output = test_env.run_jj_in(&repo_path, ["file", "list"]);
+    insta::assert_snapshot!(output, @r"
+    file
+    [EOF]
+    ");
+
+    let output = test_env.run_jj_in(
+        &repo_path,
+        [
+            "file",
+            "list",
+            "--config=snapshot.nested-repositories=error",
+        ],
+    );
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Error: Failed to snapshot the working copy
+    Caused by: Working copy contains a nested repository at $TEST_ENV/repo/nested
+    Hint: Add the directory to .gitignore, or set `snapshot.nested-repositories` to `ignore` or `submodule`
+    [EOF]
+    [exit status: 1]
+    ");
+
+    let output = test_env.run_jj_in(
+        &repo_path,
+        ["file", "list", "--config=snapshot.nested-repositories=bad"],
+    );
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Config error: Invalid type or value for snapshot.nested-repositories
+    Caused by: unknown variant `bad`, expected one of `ignore`, `error`, `submodule`
+
+    For help, see https://jj-vcs.github.io/jj/latest/config/ or use `jj help -k config`.
+    [EOF]
+    [exit status: 1]
+    ");
+}
+
 #[test]
 fn test_conflict_marker_length_stored_in_working_copy() {
     let test_env = TestEnvironment::default();
@@ -439,13 +489,14 @@ fn test_conflict_marker_length_stored_in_working_copy() {
     // The file should still be conflicted, and the new content should be saved
     let output = test_env.run_jj_in(&repo_path, ["st"]);
     insta::assert_snapshot!(output, @r"
+    Warning: There are unresolved conflicts at these paths:
+    file    2-sided conflict
     Working copy changes:
     M file
     Working copy : mzvwutvl 3a981880 (conflict) (no description set)
     Parent commit: rlvkpnrz ce613b49 side-a
     Parent commit: zsuskuln 7b2b03ab side-b
-    Warning: There are unresolved conflicts at these paths:
-    file    2-sided conflict
+    Hint: Use `jj resolve` to resolve the conflicts with a merge tool, or edit the conflict markers in the files directly.
     [EOF]
     ");
     insta::assert_snapshot!(test_env.run_jj_in(&repo_path, ["diff", "--git"]), @r"
@@ -52,10 +52,11 @@ fn test_workspaces_add_second_workspace() {
     "#);
 
     // Can see the working-copy commit in each workspace in the log output. The "@"
-    // node in the graph indicates the current workspace's working-copy commit.
+    // node in the graph indicates the current workspace's working-copy commit,
+    // and the "◎" nodes indicate the other workspaces' working-copy commits.
     insta::assert_snapshot!(get_log_output(&test_env, &main_path), @r"
     @  8183d0fcaa4c default@
-    │ ○  5ed2222c28e2 second@
+    │ ◎  5ed2222c28e2 second@
     ├─╯
     ○  751b12b7b981
     ◆  000000000000
@@ -63,7 +64,7 @@ fn test_workspaces_add_second_workspace() {
     ");
     insta::assert_snapshot!(get_log_output(&test_env, &secondary_path), @r"
     @  5ed2222c28e2 second@
-    │ ○  8183d0fcaa4c default@
+    │ ◎  8183d0fcaa4c default@
     ├─╯
     ○  751b12b7b981
     ◆  000000000000
@@ -148,8 +149,215 @@ fn test_workspaces_sparse_patterns() {
     insta::assert_snapshot!(output, @"");
 }
 
+/// Test sparse profiles defined in the config
+#[test]
+fn test_workspaces_sparse_profiles() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "main"]).success();
+    test_env.add_config(
+        r#"
+        [sparse.profiles]
+        backend = ["libs/common", "crates/backend", "libs/common"]
+        bad = ["../outside"]
+        "#,
+    );
+    let main_path = test_env.env_root().join("main");
+
+    test_env
+        .run_jj_in(
+            &main_path,
+            ["workspace", "add", "--sparse-profile=backend", "../ws1"],
+        )
+        .success();
+    let output = test_env.run_jj_in(test_env.env_root().join("ws1"), ["sparse", "list"]);
+    insta::assert_snapshot!(output.normalize_backslash(), @r"
+    crates/backend
+    libs/common
+    [EOF]
+    ");
+
+    // The default profile is used unless another option is given
+    test_env.add_config(r#"sparse.default-profile = "backend""#);
+    test_env
+        .run_jj_in(&main_path, ["workspace", "add", "../ws2"])
+        .success();
+    let output = test_env.run_jj_in(test_env.env_root().join("ws2"), ["sparse", "list"]);
+    insta::assert_snapshot!(output.normalize_backslash(), @r"
+    crates/backend
+    libs/common
+    [EOF]
+    ");
+    test_env
+        .run_jj_in(
+            &main_path,
+            ["workspace", "add", "--sparse-patterns=copy", "../ws3"],
+        )
+        .success();
+    let output = test_env.run_jj_in(test_env.env_root().join("ws3"), ["sparse", "list"]);
+    insta::assert_snapshot!(output, @r"
+    .
+    [EOF]
+    ");
+
+    // Unknown and invalid profiles are rejected before creating the workspace
+    let output = test_env.run_jj_in(
+        &main_path,
+        ["workspace", "add", "--sparse-profile=unknown", "../ws4"],
+    );
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Error: No such sparse profile: unknown
+    [EOF]
+    [exit status: 1]
+    ");
+    let output = test_env.run_jj_in(
+        &main_path,
+        ["workspace", "add", "--sparse-profile=bad", "../ws4"],
+    );
+    insta::assert_snapshot!(output, @r#"
+    ------- stderr -------
+    Config error: Invalid path in sparse profile bad
+    Caused by: Invalid component ".." in repo-relative path "../outside"
+    For help, see https://jj-vcs.github.io/jj/latest/config/ or use `jj help -k config`.
+    [EOF]
+    [exit status: 1]
+    "#);
+    assert!(!test_env.env_root().join("ws4").exists());
+
+    let output = test_env.run_jj_in(
+        &main_path,
+        [
+            "workspace",
+            "add",
+            "--sparse-profile=backend",
+            "--sparse-patterns=full",
+            "../ws4",
+        ],
+    );
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    error: the argument '--sparse-profile <NAME>' cannot be used with '--sparse-patterns <SPARSE_PATTERNS>'
+
+    Usage: jj workspace add --sparse-profile <NAME> <DESTINATION>
+
+    For more information, try '--help'.
+    [EOF]
+    [exit status: 2]
+    ");
+}
+
 /// Test adding a second workspace while the current workspace is editing a
 /// merge
+/// Test revsets and filesets referring to another workspace
+#[test]
+fn test_workspaces_visible_in_workspace() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "main"]).success();
+    let main_path = test_env.env_root().join("main");
+    let secondary_path = test_env.env_root().join("secondary");
+
+    std::fs::create_dir(main_path.join("foo")).unwrap();
+    std::fs::create_dir(main_path.join("bar")).unwrap();
+    std::fs::write(main_path.join("foo").join("file"), "").unwrap();
+    test_env
+        .run_jj_in(&main_path, ["commit", "-m=add foo"])
+        .success();
+    std::fs::write(main_path.join("bar").join("file"), "").unwrap();
+    test_env
+        .run_jj_in(&main_path, ["commit", "-m=add bar"])
+        .success();
+    test_env
+        .run_jj_in(
+            &main_path,
+            ["workspace", "add", "--name", "second", "../secondary"],
+        )
+        .success();
+    test_env
+        .run_jj_in(&secondary_path, ["sparse", "set", "--clear", "--add=bar"])
+        .success();
+    std::fs::write(main_path.join("bar").join("file"), "modified").unwrap();
+    test_env
+        .run_jj_in(&main_path, ["commit", "-m=modify bar"])
+        .success();
+
+    let template = r#"description.first_line() ++ "\n""#;
+    let output = test_env.run_jj_in(
+        &main_path,
+        [
+            "log",
+            "--no-graph",
+            "-T",
+            template,
+            "-r",
+            "visible_in_workspace(second)",
+        ],
+    );
+    insta::assert_snapshot!(output, @r"
+    add bar
+    add foo
+
+    [EOF]
+    ");
+    let output = test_env.run_jj_in(
+        &main_path,
+        [
+            "log",
+            "--no-graph",
+            "-T",
+            template,
+            "-r",
+            "files(workspace_sparse(second))",
+        ],
+    );
+    insta::assert_snapshot!(output, @r"
+    modify bar
+    add bar
+    [EOF]
+    ");
+    let output = test_env.run_jj_in(&main_path, ["file", "list", "workspace_sparse(second)"]);
+    insta::assert_snapshot!(output, @r"
+    bar/file
+    [EOF]
+    ");
+
+    // Workspaces without recorded patterns check out all files
+    let output = test_env.run_jj_in(&main_path, ["file", "list", "workspace_sparse(unknown)"]);
+    insta::assert_snapshot!(output, @r"
+    bar/file
+    foo/file
+    [EOF]
+    ------- stderr -------
+    Warning: In fileset expression
+     --> 1:18
+      |
+    1 | workspace_sparse(unknown)
+      |                  ^-----^
+      |
+      = No sparse patterns recorded for workspace `unknown`, assuming it checks out all files
+    [EOF]
+    ");
+
+    // Forgetting the workspace removes its patterns
+    test_env
+        .run_jj_in(&main_path, ["workspace", "forget", "second"])
+        .success();
+    let output = test_env.run_jj_in(&main_path, ["file", "list", "workspace_sparse(second)"]);
+    insta::assert_snapshot!(output, @r"
+    bar/file
+    foo/file
+    [EOF]
+    ------- stderr -------
+    Warning: In fileset expression
+     --> 1:18
+      |
+    1 | workspace_sparse(second)
+      |                  ^----^
+      |
+      = No sparse patterns recorded for workspace `second`, assuming it checks out all files
+    [EOF]
+    ");
+}
+
 #[test]
 fn test_workspaces_add_second_workspace_on_merge() {
     let test_env = TestEnvironment::default();
@@ -183,7 +391,7 @@ fn test_workspaces_add_second_workspace_on_merge() {
     insta::assert_snapshot!(get_log_output(&test_env, &main_path), @r"
     @    35e47bff781e default@
     ├─╮
-    │ │ ○  7013a493bd09 second@
+    │ │ ◎  7013a493bd09 second@
     ╭─┬─╯
     │ ○  444b77e99d43
     ○ │  1694f2ddf8ec
@@ -335,11 +543,12 @@ fn test_workspaces_add_workspace_at_revision() {
     "#);
 
     // Can see the working-copy commit in each workspace in the log output. The "@"
-    // node in the graph indicates the current workspace's working-copy commit.
+    // node in the graph indicates the current workspace's working-copy commit,
+    // and the "◎" nodes indicate the other workspaces' working-copy commits.
     insta::assert_snapshot!(get_log_output(&test_env, &main_path), @r"
     @  dadeedb493e8 default@
     ○  c420244c6398
-    │ ○  e374e74aa0c8 second@
+    │ ◎  e374e74aa0c8 second@
     ├─╯
     ○  f6097c2f7cac
     ◆  000000000000
@@ -347,7 +556,7 @@ fn test_workspaces_add_workspace_at_revision() {
     ");
     insta::assert_snapshot!(get_log_output(&test_env, &secondary_path), @r"
     @  e374e74aa0c8 second@
-    │ ○  dadeedb493e8 default@
+    │ ◎  dadeedb493e8 default@
     │ ○  c420244c6398
     ├─╯
     ○  f6097c2f7cac
@@ -425,7 +634,7 @@ fn test_workspaces_add_workspace_multiple_revisions() {
 
     insta::assert_snapshot!(get_log_output(&test_env, &main_path), @r"
     @  5b36783cd11c default@
-    │ ○      f4fa64f40944 merge@
+    │ ◎      f4fa64f40944 merge@
     │ ├─┬─╮
     │ │ │ ○  f6097c2f7cac
     ├─────╯
@@ -438,6 +647,56 @@ fn test_workspaces_add_workspace_multiple_revisions() {
     ");
 }
 
+#[test]
+fn test_workspaces_add_workspace_default_revision_config() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "main"]).success();
+    let main_path = test_env.env_root().join("main");
+
+    std::fs::write(main_path.join("file-1"), "contents").unwrap();
+    test_env
+        .run_jj_in(&main_path, ["commit", "-m", "first"])
+        .success();
+    test_env
+        .run_jj_in(&main_path, ["new", "-r", "root()"])
+        .success();
+    std::fs::write(main_path.join("file-2"), "contents").unwrap();
+    test_env
+        .run_jj_in(&main_path, ["commit", "-m", "second"])
+        .success();
+    test_env
+        .run_jj_in(&main_path, ["new", "-r", "root()"])
+        .success();
+
+    // The configured revset is used if no revision is specified
+    test_env
+        .add_config(r#"revsets.workspace-add = "all:description(first) | description(second)""#);
+    let output = test_env.run_jj_in(&main_path, ["workspace", "add", "../merged"]);
+    insta::assert_snapshot!(output.normalize_backslash(), @r#"
+    ------- stderr -------
+    Created workspace in "../merged"
+    Working copy now at: nppvrztz c5bb4952 (empty) (no description set)
+    Parent commit      : kkmpptxz 544cd61f second
+    Parent commit      : qpvuntsm f6097c2f first
+    Added 2 files, modified 0 files, removed 0 files
+    [EOF]
+    "#);
+
+    // An explicit revision overrides the config
+    let output = test_env.run_jj_in(
+        &main_path,
+        ["workspace", "add", "../secondary", "-r=description(first)"],
+    );
+    insta::assert_snapshot!(output.normalize_backslash(), @r#"
+    ------- stderr -------
+    Created workspace in "../secondary"
+    Working copy now at: spxsnpux a1a6fd36 (empty) (no description set)
+    Parent commit      : qpvuntsm f6097c2f first
+    Added 1 files, modified 0 files, removed 0 files
+    [EOF]
+    "#);
+}
+
 #[test]
 fn test_workspaces_add_workspace_from_subdir() {
     let test_env = TestEnvironment::default();
@@ -504,7 +763,7 @@ fn test_workspaces_add_workspace_in_current_workspace() {
     // Workspace created despite warning
     let output = test_env.run_jj_in(&main_path, ["workspace", "list"]);
     insta::assert_snapshot!(output, @r"
-    default: rlvkpnrz 46d9ba8b (no description set)
+    default: rlvkpnrz 8183d0fc (empty) (no description set)
     secondary: pmmvwywv 0a77a39d (empty) (no description set)
     [EOF]
     ");
@@ -523,19 +782,17 @@ fn test_workspaces_add_workspace_in_current_workspace() {
     // Both workspaces created
     let output = test_env.run_jj_in(&main_path, ["workspace", "list"]);
     insta::assert_snapshot!(output, @r"
-    default: rlvkpnrz 477c647f (no description set)
+    default: rlvkpnrz 8183d0fc (empty) (no description set)
     secondary: pmmvwywv 0a77a39d (empty) (no description set)
     third: zxsnswpr 64746d4b (empty) (no description set)
     [EOF]
     ");
 
-    // Can see files from the other workspaces in main workspace, since they are
-    // child directories and will therefore be snapshotted
+    // Files from the other workspaces aren't snapshotted in the main workspace,
+    // since the workspace directories are nested repositories
     let output = test_env.run_jj_in(&main_path, ["file", "list"]);
     insta::assert_snapshot!(output.normalize_backslash(), @r"
     file
-    secondary/file
-    third/file
     [EOF]
     ");
 }
@@ -558,7 +815,7 @@ fn test_workspaces_conflicting_edits() {
 
     insta::assert_snapshot!(get_log_output(&test_env, &main_path), @r"
     @  06b57f44a3ca default@
-    │ ○  3224de8ae048 secondary@
+    │ ◎  3224de8ae048 secondary@
     ├─╯
     ○  506f4ec3c2c6
     ◆  000000000000
@@ -582,7 +839,7 @@ fn test_workspaces_conflicting_edits() {
     // The secondary workspace's working-copy commit was updated
     insta::assert_snapshot!(get_log_output(&test_env, &main_path), @r"
     @  a58c9a9b19ce default@
-    │ ○  e82cd4ee8faa secondary@
+    │ ◎  e82cd4ee8faa secondary@
     ├─╯
     ○  d41244767d45
     ◆  000000000000
@@ -625,7 +882,7 @@ fn test_workspaces_conflicting_edits() {
     @  e82cd4ee8faa secondary@ (divergent)
     │ ×  30816012e0da (divergent)
     ├─╯
-    │ ○  a58c9a9b19ce default@
+    │ ◎  a58c9a9b19ce default@
     ├─╯
     ○  d41244767d45
     ◆  000000000000
@@ -636,7 +893,7 @@ fn test_workspaces_conflicting_edits() {
     @  e82cd4ee8faa secondary@ (divergent)
     │ ×  30816012e0da (divergent)
     ├─╯
-    │ ○  a58c9a9b19ce default@
+    │ ◎  a58c9a9b19ce default@
     ├─╯
     ○  d41244767d45
     ◆  000000000000
@@ -661,7 +918,7 @@ fn test_workspaces_updated_by_other() {
 
     insta::assert_snapshot!(get_log_output(&test_env, &main_path), @r"
     @  06b57f44a3ca default@
-    │ ○  3224de8ae048 secondary@
+    │ ◎  3224de8ae048 secondary@
     ├─╯
     ○  506f4ec3c2c6
     ◆  000000000000
@@ -682,7 +939,7 @@ fn test_workspaces_updated_by_other() {
     // The secondary workspace's working-copy commit was updated.
     insta::assert_snapshot!(get_log_output(&test_env, &main_path), @r"
     @  a58c9a9b19ce default@
-    │ ○  e82cd4ee8faa secondary@
+    │ ◎  e82cd4ee8faa secondary@
     ├─╯
     ○  d41244767d45
     ◆  000000000000
@@ -710,7 +967,7 @@ fn test_workspaces_updated_by_other() {
     insta::assert_snapshot!(get_log_output(&test_env, &secondary_path),
     @r"
     @  e82cd4ee8faa secondary@
-    │ ○  a58c9a9b19ce default@
+    │ ◎  a58c9a9b19ce default@
     ├─╯
     ○  d41244767d45
     ◆  000000000000
@@ -737,7 +994,7 @@ fn test_workspaces_updated_by_other_automatic() {
 
     insta::assert_snapshot!(get_log_output(&test_env, &main_path), @r"
     @  06b57f44a3ca default@
-    │ ○  3224de8ae048 secondary@
+    │ ◎  3224de8ae048 secondary@
     ├─╯
     ○  506f4ec3c2c6
     ◆  000000000000
@@ -758,7 +1015,7 @@ fn test_workspaces_updated_by_other_automatic() {
     // The secondary workspace's working-copy commit was updated.
     insta::assert_snapshot!(get_log_output(&test_env, &main_path), @r"
     @  a58c9a9b19ce default@
-    │ ○  e82cd4ee8faa secondary@
+    │ ◎  e82cd4ee8faa secondary@
     ├─╯
     ○  d41244767d45
     ◆  000000000000
@@ -782,7 +1039,7 @@ fn test_workspaces_updated_by_other_automatic() {
     insta::assert_snapshot!(get_log_output(&test_env, &secondary_path),
     @r"
     @  e82cd4ee8faa secondary@
-    │ ○  a58c9a9b19ce default@
+    │ ◎  a58c9a9b19ce default@
     ├─╯
     ○  d41244767d45
     ◆  000000000000
@@ -869,7 +1126,7 @@ fn test_workspaces_current_op_discarded_by_other(automatic: bool) {
     insta::allow_duplicates! {
         insta::assert_snapshot!(get_log_output(&test_env, &main_path), @r"
         @  2d02e07ed190 default@
-        │ ○  3df3bf89ddf1 secondary@
+        │ ◎  3df3bf89ddf1 secondary@
         ├─╯
         ○  e734830954d8
         ◆  000000000000
@@ -918,7 +1175,7 @@ fn test_workspaces_current_op_discarded_by_other(automatic: bool) {
     insta::allow_duplicates! {
         insta::assert_snapshot!(get_log_output(&test_env, &main_path), @r"
         @  2d02e07ed190 default@
-        │ ○  0b5181407d03 secondary@
+        │ ◎  0b5181407d03 secondary@
         │ ○  3df3bf89ddf1
         ├─╯
         ○  e734830954d8
@@ -967,6 +1224,85 @@ fn test_workspaces_current_op_discarded_by_other(automatic: bool) {
     }
 }
 
+/// Test operating on another workspace's working-copy commit
+#[test]
+fn test_workspaces_at_workspace() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "main"]).success();
+    let main_path = test_env.env_root().join("main");
+    let secondary_path = test_env.env_root().join("secondary");
+
+    std::fs::write(main_path.join("file"), "contents\n").unwrap();
+    test_env
+        .run_jj_in(&main_path, ["commit", "-m", "initial"])
+        .success();
+    test_env
+        .run_jj_in(&main_path, ["workspace", "add", "../secondary"])
+        .success();
+
+    // The current workspace isn't snapshotted
+    std::fs::write(main_path.join("file"), "modified in main\n").unwrap();
+    let output = test_env.run_jj_in(&main_path, ["--at-workspace", "secondary", "status"]);
+    insta::assert_snapshot!(output, @r"
+    The working copy has no changes.
+    Working copy : pmmvwywv e5e7c456 (empty) (no description set)
+    Parent commit: qpvuntsm 2ff7ae85 initial
+    [EOF]
+    ");
+    let output = test_env.run_jj_in(
+        &main_path,
+        [
+            "--at-workspace",
+            "secondary",
+            "describe",
+            "-m",
+            "described from main",
+        ],
+    );
+    insta::assert_snapshot!(output, @"");
+    let output = test_env.run_jj_in(&main_path, ["--at-workspace", "secondary", "new"]);
+    insta::assert_snapshot!(output, @"");
+    insta::assert_snapshot!(get_log_output(&test_env, &main_path), @r"
+    @  819f35501572 default@
+    │ ◎  12e0de42aa8c secondary@
+    │ ○  d25486490390
+    ├─╯
+    ○  2ff7ae858a3a
+    ◆  000000000000
+    [EOF]
+    ");
+
+    // The other workspace picks up the new working-copy commit
+    let output = test_env.run_jj_in(&secondary_path, ["status"]);
+    insta::assert_snapshot!(output, @r"
+    The working copy has no changes.
+    Working copy : royxmykx 12e0de42 (empty) (no description set)
+    Parent commit: pmmvwywv d2548649 (empty) described from main
+    [EOF]
+    ");
+
+    // Commands that need to update the working copy are rejected
+    let output = test_env.run_jj_in(
+        &main_path,
+        ["--at-workspace", "secondary", "workspace", "update-stale"],
+    );
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Error: This command must be able to update the working copy.
+    Hint: Don't use --at-workspace.
+    [EOF]
+    [exit status: 1]
+    ");
+
+    let output = test_env.run_jj_in(&main_path, ["--at-workspace", "unknown", "log"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Error: No such workspace: unknown
+    [EOF]
+    [exit status: 1]
+    ");
+}
+
 #[test]
 fn test_workspaces_update_stale_noop() {
     let test_env = TestEnvironment::default();
@@ -1030,7 +1366,7 @@ fn test_workspaces_update_stale_snapshot() {
 
     insta::assert_snapshot!(get_log_output(&test_env, &secondary_path), @r"
     @  e672fd8fefac secondary@
-    │ ○  ea37b073f5ab default@
+    │ ◎  ea37b073f5ab default@
     │ ○  b13c81dedc64
     ├─╯
     ○  e6e9989f1179
@@ -1039,6 +1375,119 @@ fn test_workspaces_update_stale_snapshot() {
     ");
 }
 
+/// Test rebuilding a corrupted working copy state
+#[test]
+fn test_workspaces_repair() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "main"]).success();
+    let main_path = test_env.env_root().join("main");
+
+    std::fs::write(main_path.join("file1"), "contents\n").unwrap();
+    std::fs::write(main_path.join("file2"), "contents\n").unwrap();
+    test_env.run_jj_in(&main_path, ["new"]).success();
+
+    // Modify files while the state file is corrupted
+    std::fs::write(main_path.join(".jj/working_copy/tree_state"), "garbage").unwrap();
+    std::fs::write(main_path.join("file1"), "modified\n").unwrap();
+    std::fs::remove_file(main_path.join("file2")).unwrap();
+    let output = test_env.run_jj_in(&main_path, ["status"]);
+    insta::assert_snapshot!(output.normalize_backslash(), @r"
+    ------- stderr -------
+    Internal error: Failed to access working copy state
+    Caused by:
+    1: Failed to read working copy state
+    2: Decoding tree state from $TEST_ENV/main/.jj/working_copy/tree_state
+    3: failed to decode Protobuf message: invalid wire type value: 7
+    Hint: The working copy state file may be corrupted. Run `jj workspace repair` to rebuild it from the working-copy commit.
+    [EOF]
+    [exit status: 255]
+    ");
+
+    // The sparse patterns can't be read from the corrupted state file
+    let output = test_env.run_jj_in(&main_path, ["workspace", "repair"]);
+    insta::assert_snapshot!(output.normalize_backslash(), @r"
+    ------- stderr -------
+    Error: Failed to read the sparse patterns
+    Caused by:
+    1: Decoding tree state from $TEST_ENV/main/.jj/working_copy/tree_state
+    2: failed to decode Protobuf message: invalid wire type value: 7
+    Hint: Specify the sparse patterns of the workspace with `--sparse-pattern`, or use `--sparse-pattern .` to include all files.
+    [EOF]
+    [exit status: 1]
+    ");
+
+    let output = test_env.run_jj_in(&main_path, ["workspace", "repair", "--sparse-pattern=."]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Rebuilt working copy state from commit rlvkpnrz 8eb6584b (empty) (no description set)
+    [EOF]
+    ");
+
+    let output = test_env.run_jj_in(&main_path, ["status"]);
+    insta::assert_snapshot!(output, @r"
+    Working copy changes:
+    M file1
+    D file2
+    Working copy : rlvkpnrz 61969351 (no description set)
+    Parent commit: qpvuntsm 52423de0 (no description set)
+    [EOF]
+    ");
+}
+
+#[test]
+fn test_workspaces_repair_sparse() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "main"]).success();
+    let main_path = test_env.env_root().join("main");
+
+    std::fs::write(main_path.join("file1"), "contents\n").unwrap();
+    std::fs::write(main_path.join("file2"), "contents\n").unwrap();
+    test_env.run_jj_in(&main_path, ["new"]).success();
+    test_env
+        .run_jj_in(&main_path, ["sparse", "set", "--clear", "--add=file1"])
+        .success();
+    assert!(!main_path.join("file2").exists());
+
+    // The sparse patterns are kept if they can be read
+    std::fs::write(main_path.join("file1"), "modified\n").unwrap();
+    let output = test_env.run_jj_in(&main_path, ["workspace", "repair"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Rebuilt working copy state from commit rlvkpnrz 8eb6584b (empty) (no description set)
+    [EOF]
+    ");
+    let output = test_env.run_jj_in(&main_path, ["sparse", "list"]);
+    insta::assert_snapshot!(output, @r"
+    file1
+    [EOF]
+    ");
+
+    // Files outside of the sparse patterns aren't recorded as deleted
+    std::fs::write(main_path.join(".jj/working_copy/tree_state"), "garbage").unwrap();
+    let output = test_env.run_jj_in(
+        &main_path,
+        ["workspace", "repair", "--sparse-pattern=file1"],
+    );
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Rebuilt working copy state from commit rlvkpnrz 6d7b88c3 (no description set)
+    [EOF]
+    ");
+    let output = test_env.run_jj_in(&main_path, ["sparse", "list"]);
+    insta::assert_snapshot!(output, @r"
+    file1
+    [EOF]
+    ");
+    let output = test_env.run_jj_in(&main_path, ["status"]);
+    insta::assert_snapshot!(output, @r"
+    Working copy changes:
+    M file1
+    Working copy : rlvkpnrz 6d7b88c3 (no description set)
+    Parent commit: qpvuntsm 52423de0 (no description set)
+    [EOF]
+    ");
+}
+
 /// Test forgetting workspaces
 #[test]
 fn test_workspaces_forget() {
@@ -1205,7 +1654,7 @@ fn test_workspaces_forget_abandon_commits() {
     ");
     insta::assert_snapshot!(get_log_output(&test_env, &main_path), @r"
     @  4e8f9d2be039 default@
-    │ ○  57d63245a308 fourth@ second@ third@
+    │ ◎  57d63245a308 fourth@ second@ third@
     ├─╯
     ◆  000000000000
     [EOF]
@@ -1216,7 +1665,7 @@ fn test_workspaces_forget_abandon_commits() {
         .run_jj_in(&main_path, ["workspace", "forget", "default"])
         .success();
     insta::assert_snapshot!(get_log_output(&test_env, &main_path), @r"
-    ○  57d63245a308 fourth@ second@ third@
+    ◎  57d63245a308 fourth@ second@ third@
     │ ○  4e8f9d2be039
     ├─╯
     ◆  000000000000
@@ -1229,7 +1678,7 @@ fn test_workspaces_forget_abandon_commits() {
         .run_jj_in(&main_path, ["workspace", "forget", "second"])
         .success();
     insta::assert_snapshot!(get_log_output(&test_env, &main_path), @r"
-    ○  57d63245a308 fourth@ third@
+    ◎  57d63245a308 fourth@ third@
     │ ○  4e8f9d2be039
     ├─╯
     ◆  000000000000
@@ -1460,18 +1909,111 @@ fn test_workspaces_rename_workspace() {
     // Can see the working-copy commit in each workspace in the log output.
     insta::assert_snapshot!(get_log_output(&test_env, &main_path), @r"
     @  230dd059e1b0 default@
-    │ ○  57d63245a308 third@
+    │ ◎  57d63245a308 third@
     ├─╯
     ◆  000000000000
     [EOF]
     ");
     insta::assert_snapshot!(get_log_output(&test_env, &secondary_path), @r"
     @  57d63245a308 third@
-    │ ○  230dd059e1b0 default@
+    │ ◎  230dd059e1b0 default@
+    ├─╯
+    ◆  000000000000
+    [EOF]
+    ");
+}
+
+/// Test restoring the working-copy commit of a single workspace
+#[test]
+fn test_workspaces_op_restore_workspace() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "main"]).success();
+    let main_path = test_env.env_root().join("main");
+    let secondary_path = test_env.env_root().join("secondary");
+
+    test_env
+        .run_jj_in(&main_path, ["workspace", "add", "../secondary"])
+        .success();
+    let op_id = test_env.current_operation_id(&main_path);
+    test_env
+        .run_jj_in(&main_path, ["new", "-m", "main new"])
+        .success();
+    test_env
+        .run_jj_in(&secondary_path, ["new", "-m", "secondary new"])
+        .success();
+    test_env
+        .run_jj_in(&main_path, ["bookmark", "create", "-r@", "foo"])
+        .success();
+    insta::assert_snapshot!(get_log_output(&test_env, &main_path), @r"
+    @  757a3ed0a609 default@
+    ○  230dd059e1b0
+    │ ◎  77c9f245d3a8 secondary@
+    │ ○  57d63245a308
+    ├─╯
+    ◆  000000000000
+    [EOF]
+    ");
+
+    // Only the working-copy commit of the secondary workspace is restored
+    let output = test_env.run_jj_in(
+        &main_path,
+        [
+            "op",
+            "restore",
+            "--what=working-copies",
+            "--workspace=secondary",
+            &op_id,
+        ],
+    );
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Restored to operation: 6bd0572f5d64 (2001-02-03 08:05:08) create initial working-copy commit in workspace secondary
+    [EOF]
+    ");
+    insta::assert_snapshot!(get_log_output(&test_env, &main_path), @r"
+    @  757a3ed0a609 default@
+    ○  230dd059e1b0
+    │ ○  77c9f245d3a8
+    │ ◎  57d63245a308 secondary@
+    ├─╯
+    ◆  000000000000
+    [EOF]
+    ");
+    let output = test_env.run_jj_in(&main_path, ["bookmark", "list"]);
+    insta::assert_snapshot!(output, @r"
+    foo: zsuskuln 757a3ed0 (empty) main new
+    [EOF]
+    ");
+
+    // Local bookmarks can be restored on their own
+    let output = test_env.run_jj_in(
+        &main_path,
+        ["op", "restore", "--what=local-bookmarks", &op_id],
+    );
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Restored to operation: 6bd0572f5d64 (2001-02-03 08:05:08) create initial working-copy commit in workspace secondary
+    [EOF]
+    ");
+    let output = test_env.run_jj_in(&main_path, ["bookmark", "list"]);
+    insta::assert_snapshot!(output, @"");
+    insta::assert_snapshot!(get_log_output(&test_env, &main_path), @r"
+    @  757a3ed0a609 default@
+    ○  230dd059e1b0
+    │ ○  77c9f245d3a8
+    │ ◎  57d63245a308 secondary@
     ├─╯
     ◆  000000000000
     [EOF]
     ");
+
+    let output = test_env.run_jj_in(&main_path, ["op", "restore", "--workspace=unknown", &op_id]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Error: No such workspace: unknown
+    [EOF]
+    [exit status: 1]
+    ");
 }
 
 #[must_use]
@@ -1485,3 +2027,104 @@ fn get_log_output(test_env: &TestEnvironment, cwd: &Path) -> CommandOutput {
     "#;
     test_env.run_jj_in(cwd, ["log", "-T", template, "-r", "all()"])
 }
+
+#[test]
+fn test_workspaces_undo_files() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let repo_path = test_env.env_root().join("repo");
+
+    let output = test_env.run_jj_in(&repo_path, ["workspace", "undo-files"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Error: The undo stash is empty
+    [EOF]
+    [exit status: 1]
+    ");
+
+    std::fs::write(repo_path.join("file"), "original\n").unwrap();
+    test_env.run_jj_in(&repo_path, ["new"]).success();
+
+    // Simulate checkouts which replaced modified files
+    let stash_path = repo_path.join(".jj/working_copy/undo-stash");
+    std::fs::create_dir_all(stash_path.join("0978307200000/dir")).unwrap();
+    std::fs::write(stash_path.join("0978307200000/file"), "older\n").unwrap();
+    std::fs::write(stash_path.join("0978307200000/dir/other"), "older\n").unwrap();
+    std::fs::create_dir_all(stash_path.join("0978307260000")).unwrap();
+    std::fs::write(stash_path.join("0978307260000/file"), "newer\n").unwrap();
+
+    let output = test_env.run_jj_in(&repo_path, ["workspace", "undo-files", "--list"]);
+    insta::assert_snapshot!(output, @r"
+    0978307260000: 2001-01-01 00:01:00.000 +00:00
+      file
+    0978307200000: 2001-01-01 00:00:00.000 +00:00
+      dir/other
+      file
+    [EOF]
+    ");
+
+    let output = test_env.run_jj_in(&repo_path, ["workspace", "undo-files"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Restored 1 files from undo stash entry 0978307260000:
+      file
+    [EOF]
+    ");
+    let output = test_env.run_jj_in(&repo_path, ["diff", "--git"]);
+    insta::assert_snapshot!(output, @r"
+    diff --git a/file b/file
+    index 4b48deed3a..d58ed19c91 100644
+    --- a/file
+    +++ b/file
+    @@ -1,1 +1,1 @@
+    -original
+    +newer
+    [EOF]
+    ");
+
+    let output = test_env.run_jj_in(&repo_path, ["workspace", "undo-files", "0978307200000"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Restored 2 files from undo stash entry 0978307200000:
+      dir/other
+      file
+    [EOF]
+    ");
+    let output = test_env.run_jj_in(&repo_path, ["diff", "--summary"]);
+    insta::assert_snapshot!(output, @r"
+    C {file => dir/other}
+    M file
+    [EOF]
+    ");
+
+    let output = test_env.run_jj_in(&repo_path, ["workspace", "undo-files", "nonexistent"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Error: No such undo stash entry: nonexistent
+    [EOF]
+    [exit status: 1]
+    ");
+}
+
+#[test]
+fn test_workspaces_undo_stash_days_too_large() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let repo_path = test_env.env_root().join("repo");
+
+    let output = test_env.run_jj_in(
+        &repo_path,
+        [
+            "status",
+            "--config=working-copy.undo-stash-days=9223372036854775807",
+        ],
+    );
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Config error: Invalid type or value for working-copy.undo-stash-days
+    Caused by: Number of days is too large
+    For help, see https://jj-vcs.github.io/jj/latest/config/ or use `jj help -k config`.
+    [EOF]
+    [exit status: 1]
+    ");
+}
@@ -0,0 +1,46 @@
+// Copyright 2025 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Updates the description of a revision in a transaction, using the
+//! `jj_lib::api` module.
+//!
+//! Usage: `cargo run -p jj-lib --example api-describe -- WORKSPACE REVSET MESSAGE`
+
+use std::error::Error;
+use std::path::PathBuf;
+
+use jj_lib::api;
+
+fn main() -> Result<(), Box<dyn Error>> {
+    let [workspace_root, revset, message] = std::env::args()
+        .skip(1)
+        .collect::<Vec<_>>()
+        .try_into()
+        .map_err(|_| "usage: api-describe WORKSPACE REVSET MESSAGE")?;
+
+    let settings = api::default_settings()?;
+    let mut repo = api::Repository::open(&settings, &PathBuf::from(workspace_root))?;
+    let [commit] = repo
+        .log(&revset)?
+        .collect::<Result<Vec<_>, _>>()?
+        .try_into()
+        .map_err(|_| "the revset must resolve to exactly one revision")?;
+
+    let mut tx = repo.start_transaction();
+    let new_commit = tx.describe(&commit, &format!("{message}\n"))?;
+    // Descendants of the rewritten commit are rebased onto the new commit.
+    repo.commit_transaction(tx, &format!("describe commit {}", commit.id()))?;
+    println!("Rewrote {} as {}", commit.id(), new_commit.id());
+    Ok(())
+}
@@ -0,0 +1,52 @@
+// Copyright 2025 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Prints the commits in a revset along with the paths they changed, using
+//! only the `jj_lib::api` module.
+//!
+//! Usage: `cargo run -p jj-lib --example api-log -- [WORKSPACE] [REVSET]`
+
+use std::error::Error;
+use std::path::PathBuf;
+
+use jj_lib::api;
+use jj_lib::api::ChangeKind;
+use jj_lib::api::ObjectId as _;
+
+fn main() -> Result<(), Box<dyn Error>> {
+    let mut args = std::env::args().skip(1);
+    let workspace_root = PathBuf::from(args.next().unwrap_or_else(|| ".".to_owned()));
+    let revset = args.next().unwrap_or_else(|| "::@".to_owned());
+
+    let settings = api::default_settings()?;
+    let repo = api::Repository::open(&settings, &workspace_root)?;
+    for commit in repo.log(&revset)? {
+        let commit = commit?;
+        let subject = commit.description().lines().next().unwrap_or("");
+        println!("{} {subject}", &commit.id().hex()[..12]);
+        // Diff against the first parent. The root commit has no parents.
+        let Some(parent) = commit.parents().next().transpose()? else {
+            continue;
+        };
+        for entry in repo.diff(&parent, &commit)? {
+            let status = match entry.kind {
+                ChangeKind::Added => 'A',
+                ChangeKind::Removed => 'D',
+                _ => 'M',
+            };
+            println!("  {status} {}", entry.path.as_internal_file_string());
+        }
+    }
+    Ok(())
+}
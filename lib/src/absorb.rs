@@ -48,6 +48,7 @@ use crate::repo_path::RepoPath;
 use crate::repo_path::RepoPathBuf;
 use crate::revset::ResolvedRevsetExpression;
 use crate::revset::RevsetEvaluationError;
+use crate::rewrite::DropEmptyPolicy;
 
 /// The source commit to absorb into its ancestry.
 #[derive(Clone, Debug)]
@@ -283,11 +284,14 @@ pub struct AbsorbStats {
 }
 
 /// Merges selected trees into the specified commits. Abandons the source commit
-/// if it becomes discardable.
+/// if it becomes empty, as specified by `drop_empty`. By default
+/// ([`DropEmptyPolicy::Auto`]), the source commit is abandoned only if it
+/// becomes discardable.
 pub fn absorb_hunks(
     repo: &mut MutableRepo,
     source: &AbsorbSource,
     mut selected_trees: HashMap<CommitId, MergedTreeBuilder>,
+    drop_empty: DropEmptyPolicy,
 ) -> BackendResult<AbsorbStats> {
     let store = repo.store().clone();
     let mut rewritten_source = None;
@@ -299,7 +303,14 @@ pub fn absorb_hunks(
         // Remove selected hunks from the source commit by reparent()
         if rewriter.old_commit().id() == source.commit.id() {
             let commit_builder = rewriter.reparent();
-            if commit_builder.is_discardable()? {
+            let should_abandon = match drop_empty {
+                DropEmptyPolicy::Never => false,
+                DropEmptyPolicy::Auto => commit_builder.is_discardable()?,
+                DropEmptyPolicy::Always => {
+                    commit_builder.parents().len() == 1 && commit_builder.is_empty()?
+                }
+            };
+            if should_abandon {
                 commit_builder.abandon();
             } else {
                 rewritten_source = Some(commit_builder.write()?);
@@ -0,0 +1,406 @@
+// Copyright 2025 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Curated entry points for third-party tools built on `jj_lib`.
+//!
+//! Most modules of this crate are shared with the `jj` CLI and change as
+//! needed. This module covers the common tasks of tools that inspect or
+//! modify a repository: opening a workspace, evaluating revsets, iterating
+//! over commits, diffing them, and committing transactions.
+//!
+//! Items in this module follow stricter compatibility rules than the rest of
+//! the crate. Breaking changes are only made in releases that bump the minor
+//! version, are listed in the changelog, and are preceded by a deprecation in
+//! an earlier release where possible. The repository, commits, settings, and
+//! transactions are wrapped in types of this module, so they don't change
+//! along with the internal types. Only plain value types like [`CommitId`]
+//! and [`RepoPath`] are re-exported.
+//!
+//! ```no_run
+//! use jj_lib::api;
+//!
+//! let settings = api::default_settings()?;
+//! let repo = api::Repository::open(&settings, ".".as_ref())?;
+//! for commit in repo.log("::@")? {
+//!     let commit = commit?;
+//!     println!("{} {}", commit.id(), commit.description());
+//! }
+//! # Ok::<_, api::Error>(())
+//! ```
+
+use std::path::Path;
+use std::sync::Arc;
+
+use futures::StreamExt as _;
+use itertools::Itertools as _;
+use pollster::FutureExt as _;
+use thiserror::Error;
+
+pub use crate::backend::ChangeId;
+pub use crate::backend::CommitId;
+use crate::commit;
+use crate::config::ConfigLayer;
+use crate::config::ConfigSource;
+use crate::config::StackedConfig;
+use crate::matchers::EverythingMatcher;
+pub use crate::object_id::ObjectId;
+use crate::repo::ReadonlyRepo;
+use crate::repo::Repo as _;
+use crate::repo::StoreFactories;
+pub use crate::repo_path::RepoPath;
+pub use crate::repo_path::RepoPathBuf;
+use crate::repo_path::RepoPathUiConverter;
+use crate::revset;
+use crate::revset::DefaultSymbolResolver;
+use crate::revset::RevsetAliasesMap;
+use crate::revset::RevsetDiagnostics;
+use crate::revset::RevsetExtensions;
+use crate::revset::RevsetIteratorExt as _;
+use crate::revset::RevsetParseContext;
+use crate::revset::RevsetWorkspaceContext;
+use crate::rewrite::merge_commit_trees;
+use crate::settings::UserSettings;
+use crate::transaction;
+use crate::workspace::default_working_copy_factories;
+use crate::workspace::Workspace;
+
+/// Error returned by the functions in this module.
+///
+/// The underlying error can be obtained from [`std::error::Error::source()`],
+/// but its type isn't covered by the compatibility rules of this module.
+#[derive(Debug, Error)]
+#[error("{message}")]
+pub struct Error {
+    message: String,
+    #[source]
+    source: Box<dyn std::error::Error + Send + Sync>,
+}
+
+impl Error {
+    fn new(message: &str, source: impl Into<Box<dyn std::error::Error + Send + Sync>>) -> Self {
+        Error {
+            message: message.to_owned(),
+            source: source.into(),
+        }
+    }
+}
+
+/// Settings used to open repositories, such as the user name and email
+/// recorded in new commits.
+#[derive(Clone, Debug)]
+pub struct Settings {
+    settings: UserSettings,
+}
+
+impl Settings {
+    /// Returns settings built from the default configuration, overridden by
+    /// the configuration in TOML `text`.
+    ///
+    /// The user's configuration files aren't read. Tools that want to honor
+    /// them can pass their contents.
+    pub fn from_toml(text: &str) -> Result<Self, Error> {
+        let mut config = StackedConfig::with_defaults();
+        let layer = ConfigLayer::parse(ConfigSource::User, text)
+            .map_err(|err| Error::new("Failed to parse configuration", err))?;
+        config.add_layer(layer);
+        Self::from_config(config)
+    }
+
+    fn from_config(config: StackedConfig) -> Result<Self, Error> {
+        let settings = UserSettings::from_config(config)
+            .map_err(|err| Error::new("Failed to load settings", err))?;
+        Ok(Settings { settings })
+    }
+}
+
+/// Returns settings built from the default configuration only.
+///
+/// Use [`Settings::from_toml()`] to add configuration, such as the user name
+/// and email.
+pub fn default_settings() -> Result<Settings, Error> {
+    Settings::from_config(StackedConfig::with_defaults())
+}
+
+/// A commit in a [`Repository`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Commit {
+    commit: commit::Commit,
+}
+
+impl Commit {
+    /// The id of the commit.
+    pub fn id(&self) -> &CommitId {
+        self.commit.id()
+    }
+
+    /// The change id, which is kept when the commit is rewritten.
+    pub fn change_id(&self) -> &ChangeId {
+        self.commit.change_id()
+    }
+
+    /// The ids of the parent commits. Only the root commit has no parents.
+    pub fn parent_ids(&self) -> &[CommitId] {
+        self.commit.parent_ids()
+    }
+
+    /// Reads the parent commits.
+    pub fn parents(&self) -> impl Iterator<Item = Result<Commit, Error>> + use<'_> {
+        self.commit.parents().map(|parent| {
+            parent
+                .map(|commit| Commit { commit })
+                .map_err(|err| Error::new("Failed to read commit", err))
+        })
+    }
+
+    /// The full commit description.
+    pub fn description(&self) -> &str {
+        self.commit.description()
+    }
+
+    /// The name of the author.
+    pub fn author_name(&self) -> &str {
+        &self.commit.author().name
+    }
+
+    /// The email address of the author.
+    pub fn author_email(&self) -> &str {
+        &self.commit.author().email
+    }
+}
+
+/// How a path changed between two commits.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum ChangeKind {
+    /// The path didn't exist in the old commit.
+    Added,
+    /// The path doesn't exist in the new commit.
+    Removed,
+    /// The path exists in both commits with different contents or type.
+    Modified,
+}
+
+/// A path that differs between two commits.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[non_exhaustive]
+pub struct DiffEntry {
+    /// The changed path.
+    pub path: RepoPathBuf,
+    /// How the path changed.
+    pub kind: ChangeKind,
+}
+
+/// A workspace and the repository it belongs to, loaded at a specific
+/// operation.
+///
+/// The working copy isn't snapshotted when the repository is opened, so
+/// changes made to the files since the last `jj` command aren't visible.
+pub struct Repository {
+    workspace: Workspace,
+    repo: Arc<ReadonlyRepo>,
+}
+
+impl Repository {
+    /// Opens the workspace at `workspace_root`, and loads its repository at
+    /// the latest operation.
+    pub fn open(settings: &Settings, workspace_root: &Path) -> Result<Self, Error> {
+        let workspace = Workspace::load(
+            &settings.settings,
+            workspace_root,
+            &StoreFactories::default(),
+            &default_working_copy_factories(),
+        )
+        .map_err(|err| Error::new("Failed to load workspace", err))?;
+        let repo = workspace
+            .repo_loader()
+            .load_at_head()
+            .map_err(|err| Error::new("Failed to load repository", err))?;
+        Ok(Repository { workspace, repo })
+    }
+
+    /// The root directory of the workspace.
+    pub fn workspace_root(&self) -> &Path {
+        self.workspace.workspace_root()
+    }
+
+    /// The description of the operation the repository is loaded at.
+    pub fn operation_description(&self) -> &str {
+        &self.repo.operation().metadata().description
+    }
+
+    /// Reads the commit with the given `id`.
+    pub fn get_commit(&self, id: &CommitId) -> Result<Commit, Error> {
+        let commit = self
+            .repo
+            .store()
+            .get_commit(id)
+            .map_err(|err| Error::new("Failed to read commit", err))?;
+        Ok(Commit { commit })
+    }
+
+    /// Evaluates the `revset` expression and returns the matching commit ids
+    /// in the same order as `jj log` (children before parents).
+    ///
+    /// Only the built-in revset functions are available. Aliases defined in
+    /// the user's configuration (such as `trunk()`) aren't.
+    pub fn evaluate_revset(&self, revset: &str) -> Result<Vec<CommitId>, Error> {
+        let revset_error = |err| Error::new("Failed to evaluate revset", err);
+        let aliases_map = RevsetAliasesMap::new();
+        let extensions = RevsetExtensions::new();
+        let path_converter = RepoPathUiConverter::Fs {
+            cwd: self.workspace_root().to_owned(),
+            base: self.workspace_root().to_owned(),
+        };
+        let context = RevsetParseContext::new(
+            &aliases_map,
+            self.repo.settings().user_email(),
+            chrono::Local::now().into(),
+            &extensions,
+            Some(RevsetWorkspaceContext {
+                path_converter: &path_converter,
+                workspace_id: self.workspace.workspace_id(),
+                workspace_sparse_patterns: None,
+            }),
+        );
+        let expression = revset::parse(&mut RevsetDiagnostics::new(), revset, &context)
+            .map_err(|err| Error::new("Failed to parse revset", err))?;
+        let symbol_resolver =
+            DefaultSymbolResolver::new(self.repo.as_ref(), context.symbol_resolvers());
+        let resolved = expression
+            .resolve_user_expression(self.repo.as_ref(), &symbol_resolver)
+            .map_err(|err| Error::new("Failed to resolve revset", err))?;
+        let revset = resolved
+            .evaluate(self.repo.as_ref())
+            .map_err(revset_error)?;
+        revset.iter().try_collect().map_err(revset_error)
+    }
+
+    /// Evaluates the `revset` expression and iterates over the matching
+    /// commits in the same order as `jj log`.
+    pub fn log(
+        &self,
+        revset: &str,
+    ) -> Result<impl Iterator<Item = Result<Commit, Error>> + use<'_>, Error> {
+        let commit_ids = self.evaluate_revset(revset)?;
+        let commits = commit_ids
+            .into_iter()
+            .map(Ok)
+            .commits(self.repo.store())
+            .map(|commit| {
+                commit
+                    .map(|commit| Commit { commit })
+                    .map_err(|err| Error::new("Failed to read commit", err))
+            });
+        Ok(commits)
+    }
+
+    /// Lists the paths that differ between the trees of `from` and `to`.
+    pub fn diff(&self, from: &Commit, to: &Commit) -> Result<Vec<DiffEntry>, Error> {
+        let diff_error = |err| Error::new("Failed to diff commits", err);
+        let from_tree = from.commit.tree().map_err(diff_error)?;
+        let to_tree = to.commit.tree().map_err(diff_error)?;
+        from_tree
+            .diff_stream(&to_tree, &EverythingMatcher)
+            .map(|entry| {
+                let (before, after) = entry.values.map_err(diff_error)?;
+                let kind = if before.is_absent() {
+                    ChangeKind::Added
+                } else if after.is_absent() {
+                    ChangeKind::Removed
+                } else {
+                    ChangeKind::Modified
+                };
+                Ok(DiffEntry {
+                    path: entry.path,
+                    kind,
+                })
+            })
+            .collect::<Vec<_>>()
+            .block_on()
+            .into_iter()
+            .collect()
+    }
+
+    /// Starts a transaction on top of the loaded repository. Use
+    /// [`Repository::commit_transaction()`] to record the changes.
+    pub fn start_transaction(&self) -> Transaction {
+        Transaction {
+            tx: self.repo.start_transaction(),
+        }
+    }
+
+    /// Commits the transaction as a new operation described by `description`,
+    /// and reloads the repository at that operation.
+    ///
+    /// Descendants of rewritten and abandoned commits are rebased. The files
+    /// in the working copy aren't updated. If the transaction rewrote the
+    /// working-copy commit, the next `jj` command will update them.
+    pub fn commit_transaction(&mut self, tx: Transaction, description: &str) -> Result<(), Error> {
+        let mut tx = tx.tx;
+        tx.repo_mut()
+            .rebase_descendants()
+            .map_err(|err| Error::new("Failed to rebase descendants", err))?;
+        self.repo = tx
+            .commit(description)
+            .map_err(|err| Error::new("Failed to commit transaction", err))?;
+        Ok(())
+    }
+}
+
+/// Changes to a [`Repository`], which are recorded by
+/// [`Repository::commit_transaction()`].
+pub struct Transaction {
+    tx: transaction::Transaction,
+}
+
+impl Transaction {
+    /// Creates an empty commit on top of the `parents`, with the merged
+    /// contents of the parents.
+    pub fn new_commit(&mut self, parents: &[Commit], description: &str) -> Result<Commit, Error> {
+        let commit_error = |err| Error::new("Failed to create commit", err);
+        let parents = parents
+            .iter()
+            .map(|parent| parent.commit.clone())
+            .collect_vec();
+        let tree = merge_commit_trees(self.tx.repo(), &parents).map_err(commit_error)?;
+        let commit = self
+            .tx
+            .repo_mut()
+            .new_commit(
+                parents.iter().map(|parent| parent.id().clone()).collect(),
+                tree.id(),
+            )
+            .set_description(description)
+            .write()
+            .map_err(commit_error)?;
+        Ok(Commit { commit })
+    }
+
+    /// Rewrites the `commit` with a new `description`.
+    pub fn describe(&mut self, commit: &Commit, description: &str) -> Result<Commit, Error> {
+        let commit = self
+            .tx
+            .repo_mut()
+            .rewrite_commit(&commit.commit)
+            .set_description(description)
+            .write()
+            .map_err(|err| Error::new("Failed to rewrite commit", err))?;
+        Ok(Commit { commit })
+    }
+
+    /// Abandons the `commit`. Its descendants are rebased onto its parents.
+    pub fn abandon(&mut self, commit: &Commit) {
+        self.tx.repo_mut().record_abandoned_commit(&commit.commit);
+    }
+}
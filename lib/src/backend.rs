@@ -18,6 +18,7 @@ use std::any::Any;
 use std::collections::BTreeMap;
 use std::fmt::Debug;
 use std::io::Read;
+use std::time::Duration;
 use std::time::SystemTime;
 
 use async_trait::async_trait;
@@ -260,6 +261,27 @@ pub enum BackendError {
     /// the particular backend.
     #[error("{0}")]
     Unsupported(String),
+    /// The backend couldn't handle the request for now, e.g. because the
+    /// server is unreachable. The same request may succeed later.
+    #[error("Backend is temporarily unavailable")]
+    Unavailable(#[source] Box<dyn std::error::Error + Send + Sync>),
+    /// The request didn't complete in time.
+    #[error("Backend request timed out after {elapsed:.1?}")]
+    Timeout {
+        elapsed: Duration,
+        source: Box<dyn std::error::Error + Send + Sync>,
+    },
+}
+
+impl BackendError {
+    /// Whether the same request may succeed if retried later, as opposed to
+    /// errors which will keep failing.
+    pub fn is_retriable(&self) -> bool {
+        matches!(
+            self,
+            BackendError::Unavailable(_) | BackendError::Timeout { .. }
+        )
+    }
 }
 
 pub type BackendResult<T> = Result<T, BackendError>;
@@ -274,6 +296,17 @@ pub enum TreeValue {
 }
 
 impl TreeValue {
+    /// Returns the name of the kind of this value, such as `"file"`.
+    pub fn kind_name(&self) -> &'static str {
+        match self {
+            TreeValue::File { .. } => "file",
+            TreeValue::Symlink(_) => "symlink",
+            TreeValue::Tree(_) => "tree",
+            TreeValue::GitSubmodule(_) => "git-submodule",
+            TreeValue::Conflict(_) => "conflict",
+        }
+    }
+
     pub fn hex(&self) -> String {
         match self {
             TreeValue::File { id, .. } => id.hex(),
@@ -423,6 +456,42 @@ pub trait Backend: Send + Sync + Debug {
 
     async fn read_file(&self, path: &RepoPath, id: &FileId) -> BackendResult<Box<dyn Read>>;
 
+    /// Returns whether the file object exists in the backend.
+    ///
+    /// The default implementation tries to read the file. Backends should
+    /// override it if they can check the existence without reading the
+    /// content.
+    async fn contains_file(&self, path: &RepoPath, id: &FileId) -> BackendResult<bool> {
+        match self.read_file(path, id).await {
+            Ok(_) => Ok(true),
+            Err(BackendError::ObjectNotFound { .. }) => Ok(false),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Returns the size of the file content in bytes.
+    ///
+    /// The default implementation reads the whole content. Backends should
+    /// override it if they can look up the size without reading the content.
+    async fn file_size(&self, path: &RepoPath, id: &FileId) -> BackendResult<u64> {
+        let mut reader = self.read_file(path, id).await?;
+        std::io::copy(&mut reader, &mut std::io::sink()).map_err(|err| BackendError::ReadObject {
+            object_type: id.object_type(),
+            hash: id.hex(),
+            source: Box::new(err),
+        })
+    }
+
+    /// Makes the files available for reading at once, e.g. by fetching them
+    /// from a remote in a single request.
+    ///
+    /// This is called before many files are read, such as on checkout. The
+    /// default implementation does nothing, which is appropriate for backends
+    /// that read each file equally fast.
+    async fn prefetch_files(&self, _ids: &[FileId]) -> BackendResult<()> {
+        Ok(())
+    }
+
     async fn write_file(
         &self,
         path: &RepoPath,
@@ -489,4 +558,23 @@ pub trait Backend: Send + Sync + Debug {
     /// objects created after `keep_newer` will be preserved. This mitigates a
     /// risk of deleting new commits created concurrently by another process.
     fn gc(&self, index: &dyn Index, keep_newer: SystemTime) -> BackendResult<()>;
+
+    /// Starts garbage collection as part of the repo-level GC protocol.
+    ///
+    /// Objects which are unreachable from the `index` and older than
+    /// `keep_newer` are recorded as deletion candidates, but aren't deleted
+    /// until [`Backend::finish_gc()`] is called. The default implementation
+    /// records nothing.
+    fn prepare_gc(&self, _index: &dyn Index, _keep_newer: SystemTime) -> BackendResult<()> {
+        Ok(())
+    }
+
+    /// Finishes garbage collection started by [`Backend::prepare_gc()`].
+    ///
+    /// The `index` should be loaded from the op heads again, so that the
+    /// deletion candidates made reachable by concurrent operations are kept.
+    /// The default implementation performs [`Backend::gc()`] with the `index`.
+    fn finish_gc(&self, index: &dyn Index, keep_newer: SystemTime) -> BackendResult<()> {
+        self.gc(index, keep_newer)
+    }
 }
@@ -0,0 +1,672 @@
+// Copyright 2025 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Retries and circuit breaking for backend requests.
+//!
+//! Local backends never report [retriable](BackendError::is_retriable) errors,
+//! so the policy only comes into play with backends that talk to a server.
+
+use std::any::Any;
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use std::future::Future;
+use std::io::Read;
+use std::pin::pin;
+use std::pin::Pin;
+use std::sync::atomic::AtomicU32;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::sync::Condvar;
+use std::sync::Mutex;
+use std::sync::Weak;
+use std::task::Context;
+use std::task::Poll;
+use std::task::Waker;
+use std::thread;
+use std::time::Duration;
+use std::time::Instant;
+use std::time::SystemTime;
+
+use async_trait::async_trait;
+use futures::future::select;
+use futures::future::Either;
+use futures::stream::BoxStream;
+use once_cell::sync::Lazy;
+use pollster::FutureExt as _;
+
+use crate::backend::Backend;
+use crate::backend::BackendError;
+use crate::backend::BackendResult;
+use crate::backend::ChangeId;
+use crate::backend::Commit;
+use crate::backend::CommitId;
+use crate::backend::Conflict;
+use crate::backend::ConflictId;
+use crate::backend::CopyRecord;
+use crate::backend::FileId;
+use crate::backend::SigningFn;
+use crate::backend::SymlinkId;
+use crate::backend::Tree;
+use crate::backend::TreeId;
+use crate::index::Index;
+use crate::repo_path::RepoPath;
+use crate::repo_path::RepoPathBuf;
+
+/// How requests that fail with retriable errors are handled.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct BackendPolicy {
+    /// How many times a request is retried after the first attempt.
+    pub max_retries: u32,
+    /// Delay before the first retry. The delay doubles with each retry.
+    pub initial_backoff: Duration,
+    /// Upper bound of the delay between retries.
+    pub max_backoff: Duration,
+    /// Time after which a single attempt fails with
+    /// [`BackendError::Timeout`]. A timed-out attempt is retried like other
+    /// retriable errors.
+    pub timeout: Option<Duration>,
+    /// Time after which a request is no longer retried, counted from the
+    /// first attempt. This doesn't limit how long a single attempt may take.
+    pub retry_deadline: Option<Duration>,
+    /// Number of consecutive failed requests after which further requests
+    /// fail without reaching the backend. Zero disables circuit breaking.
+    pub circuit_breaker_threshold: u32,
+    /// How long requests are rejected once the circuit breaker has tripped.
+    pub circuit_breaker_cooldown: Duration,
+}
+
+impl Default for BackendPolicy {
+    /// Policy that makes each request exactly once.
+    fn default() -> Self {
+        BackendPolicy {
+            max_retries: 0,
+            initial_backoff: Duration::ZERO,
+            max_backoff: Duration::ZERO,
+            timeout: None,
+            retry_deadline: None,
+            circuit_breaker_threshold: 0,
+            circuit_breaker_cooldown: Duration::ZERO,
+        }
+    }
+}
+
+impl BackendPolicy {
+    /// Delay before the `retry`-th retry, counted from zero.
+    fn backoff(&self, retry: u32) -> Duration {
+        self.initial_backoff
+            .saturating_mul(2_u32.saturating_pow(retry))
+            .min(self.max_backoff)
+    }
+}
+
+/// Backend that applies a [`BackendPolicy`] to the requests sent to the
+/// wrapped backend.
+///
+/// Requests are retried with exponential backoff while they fail with
+/// retriable errors. The request future is pending while waiting for the next
+/// retry, so it doesn't block the executor.
+/// `write_file()` is never retried since its content can only be read once,
+/// and neither is `write_commit()` with a signing function, which may prompt
+/// the user.
+#[derive(Debug)]
+pub struct PolicyBackend {
+    inner: Box<dyn Backend>,
+    policy: BackendPolicy,
+    consecutive_failures: AtomicU32,
+    // Only locked once the circuit breaker threshold has been reached.
+    open_until: Mutex<Option<Instant>>,
+}
+
+impl PolicyBackend {
+    /// Wraps the `inner` backend.
+    pub fn new(inner: Box<dyn Backend>, policy: BackendPolicy) -> Self {
+        PolicyBackend {
+            inner,
+            policy,
+            consecutive_failures: AtomicU32::new(0),
+            open_until: Mutex::new(None),
+        }
+    }
+
+    /// Wraps the `inner` backend unless the `policy` is the default, which
+    /// would pass the requests through unchanged.
+    pub fn wrap(inner: Box<dyn Backend>, policy: &BackendPolicy) -> Box<dyn Backend> {
+        if *policy == BackendPolicy::default() {
+            inner
+        } else {
+            Box::new(PolicyBackend::new(inner, policy.clone()))
+        }
+    }
+
+    /// Returns the wrapped backend.
+    pub fn inner(&self) -> &dyn Backend {
+        self.inner.as_ref()
+    }
+
+    /// Returns the policy applied to the requests.
+    pub fn policy(&self) -> &BackendPolicy {
+        &self.policy
+    }
+
+    fn check_circuit(&self) -> BackendResult<()> {
+        let threshold = self.policy.circuit_breaker_threshold;
+        let failures = self.consecutive_failures.load(Ordering::Relaxed);
+        if threshold == 0 || failures < threshold {
+            return Ok(());
+        }
+        match *self.open_until.lock().unwrap() {
+            Some(open_until) if Instant::now() < open_until => Err(BackendError::Unavailable(
+                format!("Giving up after {failures} consecutive failed requests").into(),
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    fn record_result<T>(&self, result: &BackendResult<T>) {
+        match result {
+            Err(err) if err.is_retriable() => {
+                let failures = self
+                    .consecutive_failures
+                    .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |n| {
+                        Some(n.saturating_add(1))
+                    })
+                    .unwrap()
+                    .saturating_add(1);
+                let threshold = self.policy.circuit_breaker_threshold;
+                if threshold > 0 && failures >= threshold {
+                    tracing::warn!(failures, "backend circuit breaker tripped");
+                    *self.open_until.lock().unwrap() =
+                        Some(Instant::now() + self.policy.circuit_breaker_cooldown);
+                }
+            }
+            // Permanent errors say nothing about the health of the backend.
+            Err(_) => {}
+            Ok(_) => {
+                if self.consecutive_failures.load(Ordering::Relaxed) != 0 {
+                    self.consecutive_failures.store(0, Ordering::Relaxed);
+                }
+            }
+        }
+    }
+
+    /// Makes a single attempt, failing with [`BackendError::Timeout`] if it
+    /// doesn't complete in time. Requests which block the thread can't be
+    /// interrupted.
+    async fn attempt<T>(
+        &self,
+        request: impl Future<Output = BackendResult<T>>,
+    ) -> BackendResult<T> {
+        let Some(timeout) = self.policy.timeout else {
+            return request.await;
+        };
+        let start = Instant::now();
+        match select(pin!(request), Delay::new(timeout)).await {
+            Either::Left((result, _)) => result,
+            Either::Right(((), _)) => Err(BackendError::Timeout {
+                elapsed: start.elapsed(),
+                source: "No response from the backend".into(),
+            }),
+        }
+    }
+
+    async fn call<T, F, Fut>(&self, mut request: F) -> BackendResult<T>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = BackendResult<T>>,
+    {
+        self.check_circuit()?;
+        let start = Instant::now();
+        let mut retry = 0;
+        let result = loop {
+            let delay = match self.attempt(request()).await {
+                Err(err) if err.is_retriable() && retry < self.policy.max_retries => {
+                    let delay = self.policy.backoff(retry);
+                    if let Some(deadline) = self.policy.retry_deadline {
+                        if start.elapsed() + delay >= deadline {
+                            break Err(err);
+                        }
+                    }
+                    tracing::debug!(?err, ?delay, retry, "retrying backend request");
+                    delay
+                }
+                result => break result,
+            };
+            Delay::new(delay).await;
+            retry += 1;
+        };
+        self.record_result(&result);
+        result
+    }
+
+    /// Makes a request which can't be retried.
+    async fn call_once<T>(
+        &self,
+        request: impl Future<Output = BackendResult<T>>,
+    ) -> BackendResult<T> {
+        self.check_circuit()?;
+        let result = self.attempt(request).await;
+        self.record_result(&result);
+        result
+    }
+
+    fn call_sync<T>(&self, mut request: impl FnMut() -> BackendResult<T>) -> BackendResult<T> {
+        self.call(|| std::future::ready(request())).block_on()
+    }
+}
+
+/// Future which completes once the given duration has passed.
+///
+/// The backend isn't tied to a particular async runtime, so all delays are
+/// handled by a timer thread which wakes up the task when done.
+struct Delay {
+    state: Arc<Mutex<DelayState>>,
+}
+
+#[derive(Debug, Default)]
+struct DelayState {
+    done: bool,
+    waker: Option<Waker>,
+}
+
+impl Delay {
+    fn new(duration: Duration) -> Self {
+        let state = Arc::new(Mutex::new(DelayState::default()));
+        TIMER.add(Instant::now() + duration, Arc::downgrade(&state));
+        Delay { state }
+    }
+}
+
+impl Future for Delay {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let mut state = self.state.lock().unwrap();
+        if state.done {
+            Poll::Ready(())
+        } else {
+            state.waker = Some(cx.waker().clone());
+            Poll::Pending
+        }
+    }
+}
+
+/// Timer thread shared by all [`Delay`]s. The thread is started on first use.
+static TIMER: Lazy<Timer> = Lazy::new(|| {
+    thread::Builder::new()
+        .name("jj-backend-timer".to_owned())
+        .spawn(|| TIMER.run())
+        .expect("failed to spawn backend timer thread");
+    Timer::default()
+});
+
+#[derive(Default)]
+struct Timer {
+    queue: Mutex<BinaryHeap<Reverse<TimerEntry>>>,
+    condvar: Condvar,
+}
+
+struct TimerEntry {
+    deadline: Instant,
+    // Dropped delays don't need to be woken up.
+    state: Weak<Mutex<DelayState>>,
+}
+
+impl PartialEq for TimerEntry {
+    fn eq(&self, other: &Self) -> bool {
+        self.deadline == other.deadline
+    }
+}
+
+impl Eq for TimerEntry {}
+
+impl PartialOrd for TimerEntry {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for TimerEntry {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.deadline.cmp(&other.deadline)
+    }
+}
+
+impl Timer {
+    fn add(&self, deadline: Instant, state: Weak<Mutex<DelayState>>) {
+        let mut queue = self.queue.lock().unwrap();
+        queue.push(Reverse(TimerEntry { deadline, state }));
+        self.condvar.notify_one();
+    }
+
+    fn run(&self) -> ! {
+        let mut queue = self.queue.lock().unwrap();
+        loop {
+            let now = Instant::now();
+            while queue
+                .peek()
+                .is_some_and(|Reverse(entry)| entry.deadline <= now)
+            {
+                let Reverse(entry) = queue.pop().unwrap();
+                if let Some(state) = entry.state.upgrade() {
+                    let mut state = state.lock().unwrap();
+                    state.done = true;
+                    if let Some(waker) = state.waker.take() {
+                        waker.wake();
+                    }
+                }
+            }
+            let next_deadline = queue.peek().map(|Reverse(entry)| entry.deadline);
+            queue = match next_deadline {
+                Some(deadline) => {
+                    let timeout = deadline.saturating_duration_since(now);
+                    self.condvar.wait_timeout(queue, timeout).unwrap().0
+                }
+                None => self.condvar.wait(queue).unwrap(),
+            };
+        }
+    }
+}
+
+#[async_trait]
+impl Backend for PolicyBackend {
+    fn as_any(&self) -> &dyn Any {
+        // Callers downcast to the concrete backend type.
+        self.inner.as_any()
+    }
+
+    fn name(&self) -> &str {
+        self.inner.name()
+    }
+
+    fn commit_id_length(&self) -> usize {
+        self.inner.commit_id_length()
+    }
+
+    fn change_id_length(&self) -> usize {
+        self.inner.change_id_length()
+    }
+
+    fn root_commit_id(&self) -> &CommitId {
+        self.inner.root_commit_id()
+    }
+
+    fn root_change_id(&self) -> &ChangeId {
+        self.inner.root_change_id()
+    }
+
+    fn empty_tree_id(&self) -> &TreeId {
+        self.inner.empty_tree_id()
+    }
+
+    fn concurrency(&self) -> usize {
+        self.inner.concurrency()
+    }
+
+    async fn read_file(&self, path: &RepoPath, id: &FileId) -> BackendResult<Box<dyn Read>> {
+        self.call(|| self.inner.read_file(path, id)).await
+    }
+
+    async fn contains_file(&self, path: &RepoPath, id: &FileId) -> BackendResult<bool> {
+        self.call(|| self.inner.contains_file(path, id)).await
+    }
+
+    async fn file_size(&self, path: &RepoPath, id: &FileId) -> BackendResult<u64> {
+        self.call(|| self.inner.file_size(path, id)).await
+    }
+
+    async fn prefetch_files(&self, ids: &[FileId]) -> BackendResult<()> {
+        self.call(|| self.inner.prefetch_files(ids)).await
+    }
+
+    async fn write_file(
+        &self,
+        path: &RepoPath,
+        contents: &mut (dyn Read + Send),
+    ) -> BackendResult<FileId> {
+        self.call_once(self.inner.write_file(path, contents)).await
+    }
+
+    async fn read_symlink(&self, path: &RepoPath, id: &SymlinkId) -> BackendResult<String> {
+        self.call(|| self.inner.read_symlink(path, id)).await
+    }
+
+    async fn write_symlink(&self, path: &RepoPath, target: &str) -> BackendResult<SymlinkId> {
+        self.call(|| self.inner.write_symlink(path, target)).await
+    }
+
+    async fn read_tree(&self, path: &RepoPath, id: &TreeId) -> BackendResult<Tree> {
+        self.call(|| self.inner.read_tree(path, id)).await
+    }
+
+    async fn write_tree(&self, path: &RepoPath, contents: &Tree) -> BackendResult<TreeId> {
+        self.call(|| self.inner.write_tree(path, contents)).await
+    }
+
+    fn read_conflict(&self, path: &RepoPath, id: &ConflictId) -> BackendResult<Conflict> {
+        self.call_sync(|| self.inner.read_conflict(path, id))
+    }
+
+    fn write_conflict(&self, path: &RepoPath, contents: &Conflict) -> BackendResult<ConflictId> {
+        self.call_sync(|| self.inner.write_conflict(path, contents))
+    }
+
+    async fn read_commit(&self, id: &CommitId) -> BackendResult<Commit> {
+        self.call(|| self.inner.read_commit(id)).await
+    }
+
+    async fn write_commit(
+        &self,
+        contents: Commit,
+        sign_with: Option<&mut SigningFn>,
+    ) -> BackendResult<(CommitId, Commit)> {
+        match sign_with {
+            Some(sign_with) => {
+                self.call_once(self.inner.write_commit(contents, Some(sign_with)))
+                    .await
+            }
+            None => {
+                self.call(|| self.inner.write_commit(contents.clone(), None))
+                    .await
+            }
+        }
+    }
+
+    fn get_copy_records(
+        &self,
+        paths: Option<&[RepoPathBuf]>,
+        root: &CommitId,
+        head: &CommitId,
+    ) -> BackendResult<BoxStream<BackendResult<CopyRecord>>> {
+        self.call_sync(|| self.inner.get_copy_records(paths, root, head))
+    }
+
+    fn gc(&self, index: &dyn Index, keep_newer: SystemTime) -> BackendResult<()> {
+        self.inner.gc(index, keep_newer)
+    }
+
+    fn prepare_gc(&self, index: &dyn Index, keep_newer: SystemTime) -> BackendResult<()> {
+        self.inner.prepare_gc(index, keep_newer)
+    }
+
+    fn finish_gc(&self, index: &dyn Index, keep_newer: SystemTime) -> BackendResult<()> {
+        self.inner.finish_gc(index, keep_newer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+
+    use assert_matches::assert_matches;
+
+    use super::*;
+    use crate::backend::make_root_commit;
+    use crate::local_backend::LocalBackend;
+
+    fn unavailable() -> BackendError {
+        BackendError::Unavailable("connection refused".into())
+    }
+
+    fn new_backend(policy: BackendPolicy) -> (tempfile::TempDir, PolicyBackend) {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let inner = LocalBackend::init(temp_dir.path());
+        (temp_dir, PolicyBackend::new(Box::new(inner), policy))
+    }
+
+    #[test]
+    fn test_backoff() {
+        let policy = BackendPolicy {
+            initial_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_millis(500),
+            ..Default::default()
+        };
+        assert_eq!(policy.backoff(0), Duration::from_millis(100));
+        assert_eq!(policy.backoff(1), Duration::from_millis(200));
+        assert_eq!(policy.backoff(2), Duration::from_millis(400));
+        assert_eq!(policy.backoff(3), Duration::from_millis(500));
+        assert_eq!(policy.backoff(100), Duration::from_millis(500));
+    }
+
+    #[test]
+    fn test_retry() {
+        let (_temp_dir, backend) = new_backend(BackendPolicy {
+            max_retries: 2,
+            ..Default::default()
+        });
+        let root = make_root_commit(
+            backend.root_change_id().clone(),
+            backend.empty_tree_id().clone(),
+        );
+
+        // Retriable errors are retried until the request succeeds
+        let attempts = Cell::new(0);
+        let result = backend.call_sync(|| {
+            attempts.set(attempts.get() + 1);
+            if attempts.get() < 3 {
+                Err(unavailable())
+            } else {
+                Ok(root.clone())
+            }
+        });
+        assert_matches!(result, Ok(_));
+        assert_eq!(attempts.get(), 3);
+
+        // ...or the retries run out
+        attempts.set(0);
+        let result = backend.call_sync(|| -> BackendResult<()> {
+            attempts.set(attempts.get() + 1);
+            Err(unavailable())
+        });
+        assert_matches!(result, Err(BackendError::Unavailable(_)));
+        assert_eq!(attempts.get(), 3);
+
+        // Permanent errors are returned immediately
+        attempts.set(0);
+        let result = backend.call_sync(|| -> BackendResult<()> {
+            attempts.set(attempts.get() + 1);
+            Err(BackendError::Unsupported("nope".to_owned()))
+        });
+        assert_matches!(result, Err(BackendError::Unsupported(_)));
+        assert_eq!(attempts.get(), 1);
+    }
+
+    #[test]
+    fn test_timeout() {
+        let (_temp_dir, backend) = new_backend(BackendPolicy {
+            max_retries: 1,
+            timeout: Some(Duration::from_millis(10)),
+            ..Default::default()
+        });
+
+        // Hung requests time out, and are retried like other retriable errors
+        let attempts = Cell::new(0);
+        let result = backend
+            .call(|| {
+                attempts.set(attempts.get() + 1);
+                std::future::pending::<BackendResult<()>>()
+            })
+            .block_on();
+        assert_matches!(
+            result,
+            Err(BackendError::Timeout { elapsed, .. }) if elapsed >= Duration::from_millis(10)
+        );
+        assert_eq!(attempts.get(), 2);
+
+        // Requests which complete in time are unaffected
+        assert_matches!(
+            backend.read_commit(backend.root_commit_id()).block_on(),
+            Ok(_)
+        );
+    }
+
+    #[test]
+    fn test_retry_deadline() {
+        let (_temp_dir, backend) = new_backend(BackendPolicy {
+            max_retries: 10,
+            initial_backoff: Duration::from_millis(10),
+            max_backoff: Duration::from_millis(10),
+            retry_deadline: Some(Duration::from_millis(25)),
+            ..Default::default()
+        });
+        let attempts = Cell::new(0);
+        let result = backend.call_sync(|| -> BackendResult<()> {
+            attempts.set(attempts.get() + 1);
+            Err(unavailable())
+        });
+        assert_matches!(result, Err(BackendError::Unavailable(_)));
+        assert!(attempts.get() < 10);
+    }
+
+    #[test]
+    fn test_circuit_breaker() {
+        let (_temp_dir, backend) = new_backend(BackendPolicy {
+            circuit_breaker_threshold: 2,
+            circuit_breaker_cooldown: Duration::from_secs(3600),
+            ..Default::default()
+        });
+        let attempts = Cell::new(0);
+        let fail = || -> BackendResult<()> {
+            attempts.set(attempts.get() + 1);
+            Err(unavailable())
+        };
+
+        // Permanent errors don't trip the circuit breaker
+        for _ in 0..3 {
+            let result = backend.call_sync(|| -> BackendResult<()> {
+                Err(BackendError::Unsupported("nope".to_owned()))
+            });
+            assert_matches!(result, Err(BackendError::Unsupported(_)));
+        }
+        assert_matches!(backend.call_sync(|| Ok(())), Ok(()));
+
+        // A success resets the count of failures
+        assert_matches!(backend.call_sync(fail), Err(_));
+        assert_matches!(backend.call_sync(|| Ok(())), Ok(()));
+        assert_matches!(backend.call_sync(fail), Err(_));
+        assert_eq!(attempts.get(), 2);
+
+        // Once tripped, requests fail without reaching the backend
+        assert_matches!(backend.call_sync(fail), Err(_));
+        assert_eq!(attempts.get(), 3);
+        assert_matches!(
+            backend.call_sync(|| Ok(())),
+            Err(BackendError::Unavailable(_))
+        );
+        assert_matches!(
+            backend.read_commit(backend.root_commit_id()).block_on(),
+            Err(BackendError::Unavailable(_))
+        );
+        assert_eq!(attempts.get(), 3);
+    }
+}
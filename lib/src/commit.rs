@@ -30,6 +30,8 @@ use crate::backend::ChangeId;
 use crate::backend::CommitId;
 use crate::backend::MergedTreeId;
 use crate::backend::Signature;
+use crate::description;
+use crate::description::Trailer;
 use crate::merged_tree::MergedTree;
 use crate::repo::Repo;
 use crate::rewrite::merge_commit_trees;
@@ -149,6 +151,22 @@ impl Commit {
         &self.data.description
     }
 
+    /// Returns the first paragraph of the description joined into a single
+    /// line.
+    pub fn description_subject(&self) -> String {
+        description::subject(&self.data.description)
+    }
+
+    /// Returns the description without the subject paragraph.
+    pub fn description_body(&self) -> &str {
+        description::body(&self.data.description)
+    }
+
+    /// Parses the trailers at the end of the description.
+    pub fn description_trailers(&self) -> Vec<Trailer> {
+        description::trailers(&self.data.description)
+    }
+
     pub fn author(&self) -> &Signature {
         &self.data.author
     }
@@ -24,6 +24,7 @@ use crate::backend::ChangeId;
 use crate::backend::CommitId;
 use crate::backend::MergedTreeId;
 use crate::backend::Signature;
+use crate::backend::Timestamp;
 use crate::commit::is_backend_commit_empty;
 use crate::commit::Commit;
 use crate::repo::MutableRepo;
@@ -111,6 +112,11 @@ impl CommitBuilder<'_> {
         self
     }
 
+    pub fn set_author_timestamp(mut self, timestamp: Timestamp) -> Self {
+        self.inner.set_author_timestamp(timestamp);
+        self
+    }
+
     pub fn committer(&self) -> &Signature {
         self.inner.committer()
     }
@@ -120,6 +126,11 @@ impl CommitBuilder<'_> {
         self
     }
 
+    pub fn set_committer_timestamp(mut self, timestamp: Timestamp) -> Self {
+        self.inner.set_committer_timestamp(timestamp);
+        self
+    }
+
     /// [`Commit::is_discardable()`] for the new commit.
     pub fn is_discardable(&self) -> BackendResult<bool> {
         self.inner.is_discardable(self.mut_repo)
@@ -206,7 +217,11 @@ impl DetachedCommitBuilder {
         let store = repo.store().clone();
         let mut commit = predecessor.store_commit().clone();
         commit.predecessors = vec![predecessor.id().clone()];
+        let committer_timestamp = predecessor.committer().timestamp;
         commit.committer = settings.signature();
+        if !settings.update_committer_timestamp() {
+            commit.committer.timestamp = committer_timestamp;
+        }
         // If the user had not configured a name and email before but now they have,
         // update the author fields with the new information.
         if commit.author.name.is_empty()
@@ -222,8 +237,10 @@ impl DetachedCommitBuilder {
 
         // Reset author timestamp on discardable commits if the author is the
         // committer. While it's unlikely we'll have somebody else's commit
-        // with no description in our repo, we'd like to be extra safe.
-        if commit.author.name == commit.committer.name
+        // with no description in our repo, we'd like to be extra safe. The
+        // timestamps are left alone if they are to be preserved on rewrite.
+        if settings.update_committer_timestamp()
+            && commit.author.name == commit.committer.name
             && commit.author.email == commit.committer.email
             && predecessor.is_discardable(repo).unwrap_or_default()
         {
@@ -313,6 +330,11 @@ impl DetachedCommitBuilder {
         self
     }
 
+    pub fn set_author_timestamp(&mut self, timestamp: Timestamp) -> &mut Self {
+        self.commit.author.timestamp = timestamp;
+        self
+    }
+
     pub fn committer(&self) -> &Signature {
         &self.commit.committer
     }
@@ -322,6 +344,11 @@ impl DetachedCommitBuilder {
         self
     }
 
+    pub fn set_committer_timestamp(&mut self, timestamp: Timestamp) -> &mut Self {
+        self.commit.committer.timestamp = timestamp;
+        self
+    }
+
     /// [`Commit::is_discardable()`] for the new commit.
     pub fn is_discardable(&self, repo: &dyn Repo) -> BackendResult<bool> {
         Ok(self.description().is_empty() && self.is_empty(repo)?)
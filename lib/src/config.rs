@@ -67,6 +67,18 @@ pub enum ConfigLoadError {
         /// Source file path.
         source_path: Option<PathBuf>,
     },
+    /// `include` directive doesn't contain a list of file paths.
+    #[error("Invalid `include` directive: expected a string or an array of strings")]
+    InvalidInclude {
+        /// Source file path.
+        source_path: PathBuf,
+    },
+    /// Config files include each other.
+    #[error("Config file {} includes itself", path.display())]
+    IncludeCycle {
+        /// Path to the file which is included recursively.
+        path: PathBuf,
+    },
 }
 
 /// Error that can occur when saving config variables to file.
@@ -344,6 +356,58 @@ impl ConfigLayer {
         })
     }
 
+    /// Loads TOML file from the specified `path` and the files it includes.
+    ///
+    /// The top-level `include` key may specify a path or an array of paths,
+    /// which are resolved relative to the including file. Included layers are
+    /// returned before the including layer so the latter takes precedence.
+    /// The `include` key itself is removed from the loaded data.
+    pub fn load_from_file_with_includes(
+        source: ConfigSource,
+        path: PathBuf,
+    ) -> Result<Vec<Self>, ConfigLoadError> {
+        let mut layers = vec![];
+        Self::load_with_includes_rec(source, path, &mut vec![], &mut layers)?;
+        Ok(layers)
+    }
+
+    fn load_with_includes_rec(
+        source: ConfigSource,
+        path: PathBuf,
+        stack: &mut Vec<PathBuf>,
+        layers: &mut Vec<Self>,
+    ) -> Result<(), ConfigLoadError> {
+        // Compare canonical paths so that e.g. "a/../b.toml" and "b.toml" are
+        // recognized as the same file.
+        let canonical_path = fs::canonicalize(&path).unwrap_or_else(|_| path.clone());
+        if stack.contains(&canonical_path) {
+            return Err(ConfigLoadError::IncludeCycle { path });
+        }
+        let mut layer = Self::load_from_file(source, path.clone())?;
+        let included_paths = match layer.data.remove("include") {
+            None => vec![],
+            Some(item) => {
+                let to_path = |value: &ConfigValue| value.as_str().map(PathBuf::from);
+                let paths = match item.as_value() {
+                    Some(ConfigValue::Array(array)) => array.iter().map(to_path).collect(),
+                    Some(value) => to_path(value).map(|path| vec![path]),
+                    None => None,
+                };
+                paths.ok_or_else(|| ConfigLoadError::InvalidInclude {
+                    source_path: path.clone(),
+                })?
+            }
+        };
+        stack.push(canonical_path);
+        let base_dir = path.parent().unwrap_or(Path::new(""));
+        for included_path in included_paths {
+            Self::load_with_includes_rec(source, base_dir.join(included_path), stack, layers)?;
+        }
+        stack.pop();
+        layers.push(layer);
+        Ok(())
+    }
+
     fn load_from_dir(source: ConfigSource, path: &Path) -> Result<Vec<Self>, ConfigLoadError> {
         // TODO: Walk the directory recursively?
         let mut file_paths: Vec<_> = path
@@ -358,10 +422,11 @@ impl ConfigLayer {
             .context(path)
             .map_err(ConfigLoadError::Read)?;
         file_paths.sort_unstable();
-        file_paths
-            .into_iter()
-            .map(|path| Self::load_from_file(source, path))
-            .try_collect()
+        let mut layers = vec![];
+        for path in file_paths {
+            layers.extend(Self::load_from_file_with_includes(source, path)?);
+        }
+        Ok(layers)
     }
 
     /// Returns true if the table has no configuration variables.
@@ -397,6 +462,28 @@ impl ConfigLayer {
         look_up_item(self.data.as_item(), name.into_name_path().borrow())
     }
 
+    /// Looks up the line number (starting from 1) where the `name` path is
+    /// defined in the source file. Returns `None` if this layer wasn't loaded
+    /// from a file, or if the item couldn't be located in the file.
+    ///
+    /// Since the loaded data doesn't preserve the source locations, the file
+    /// is read and parsed again.
+    pub fn look_up_line(&self, name: impl ToConfigNamePath) -> Option<usize> {
+        let path = self.path.as_ref()?;
+        let text = fs::read_to_string(path).ok()?;
+        let doc = ImDocument::parse(text.as_str()).ok()?;
+        let name = name.into_name_path();
+        let mut cur_item = doc.as_item();
+        let mut span = None;
+        for key in name.borrow().components() {
+            let (found_key, item) = cur_item.as_table_like()?.get_key_value(key)?;
+            span = found_key.span().or_else(|| item.span()).or(span);
+            cur_item = item;
+        }
+        let offset = span?.start;
+        Some(text[..offset].matches('\n').count() + 1)
+    }
+
     /// Sets `new_value` to the `name` path. Returns old value if any.
     ///
     /// This function errors out if attempted to overwrite a non-table middle
@@ -601,6 +688,17 @@ impl ConfigFile {
     }
 }
 
+/// Item of configuration variable defined in a layer.
+#[derive(Clone, Debug)]
+pub struct ConfigOrigin<'a> {
+    /// Layer where the item is defined.
+    pub layer: &'a ConfigLayer,
+    /// Item defined in the layer.
+    pub item: &'a ConfigItem,
+    /// True if the item is shadowed by upper layers.
+    pub is_overridden: bool,
+}
+
 /// Stack of configuration layers which can be merged as needed.
 ///
 /// A [`StackedConfig`] is something like a read-only `overlayfs`. Tables and
@@ -634,15 +732,16 @@ impl StackedConfig {
         }
     }
 
-    /// Loads config file from the specified `path`, inserts it at the position
-    /// specified by `source`. The file should exist.
+    /// Loads config file from the specified `path` and the files it includes,
+    /// inserts them at the position specified by `source`. The file should
+    /// exist.
     pub fn load_file(
         &mut self,
         source: ConfigSource,
         path: impl Into<PathBuf>,
     ) -> Result<(), ConfigLoadError> {
-        let layer = ConfigLayer::load_from_file(source, path.into())?;
-        self.add_layer(layer);
+        let layers = ConfigLayer::load_from_file_with_includes(source, path.into())?;
+        self.extend_layers(layers);
         Ok(())
     }
 
@@ -724,6 +823,36 @@ impl StackedConfig {
         &self.layers[self.layer_range(source)]
     }
 
+    /// Looks up items of the `name` path defined in each layer, sorted by
+    /// precedence. Items shadowed by upper layers are marked as overridden.
+    pub fn look_up_origins(&self, name: impl ToConfigNamePath) -> Vec<ConfigOrigin<'_>> {
+        let name = name.into_name_path();
+        let name = name.borrow();
+        let mut origins = Vec::new();
+        let mut is_overridden = false;
+        for layer in self.layers.iter().rev() {
+            let item = match layer.look_up_item(name) {
+                Ok(Some(item)) => item,
+                Ok(None) => continue, // parent is a table, but no value found
+                Err(_) => {
+                    // parent is not a table, shadows lower layers
+                    is_overridden = true;
+                    continue;
+                }
+            };
+            origins.push(ConfigOrigin {
+                layer,
+                item,
+                is_overridden,
+            });
+            if !item.is_table_like() {
+                is_overridden = true;
+            }
+        }
+        origins.reverse();
+        origins
+    }
+
     /// Looks up value of the specified type `T` from all layers, merges sub
     /// fields as needed.
     pub fn get<'de, T: Deserialize<'de>>(
@@ -887,6 +1016,7 @@ mod tests {
     use pretty_assertions::assert_eq;
 
     use super::*;
+    use crate::tests::new_temp_dir;
 
     #[test]
     fn test_config_layer_set_value() {
@@ -1121,6 +1251,78 @@ mod tests {
         ConfigLayer::parse(ConfigSource::User, text).unwrap()
     }
 
+    #[test]
+    fn test_stacked_config_load_file_with_includes() {
+        let temp_dir = new_temp_dir();
+        let root_path = temp_dir.path().join("config.toml");
+        fs::create_dir(temp_dir.path().join("sub")).unwrap();
+        fs::write(
+            &root_path,
+            indoc! {"
+                include = ['sub/a.toml', 'b.toml']
+                a = 'root'
+            "},
+        )
+        .unwrap();
+        fs::write(
+            temp_dir.path().join("sub/a.toml"),
+            indoc! {"
+                include = '../b.toml'
+                a = 'a'
+                b = 'a'
+            "},
+        )
+        .unwrap();
+        fs::write(temp_dir.path().join("b.toml"), "b = 'b'\nc = 'b'\n").unwrap();
+
+        let mut config = StackedConfig::empty();
+        config.load_file(ConfigSource::User, &root_path).unwrap();
+        // Included files are loaded first, so the including file wins
+        let paths = config
+            .layers()
+            .iter()
+            .map(|layer| {
+                layer
+                    .path
+                    .as_ref()
+                    .unwrap()
+                    .strip_prefix(temp_dir.path())
+                    .unwrap()
+            })
+            .collect_vec();
+        assert_eq!(
+            paths,
+            [
+                Path::new("sub/../b.toml"),
+                Path::new("sub/a.toml"),
+                Path::new("b.toml"),
+                Path::new("config.toml"),
+            ]
+        );
+        assert_eq!(config.get::<String>("a").unwrap(), "root");
+        assert_eq!(config.get::<String>("b").unwrap(), "b");
+        assert_matches!(
+            config.get::<String>("include"),
+            Err(ConfigGetError::NotFound { .. })
+        );
+
+        // Cycle
+        fs::write(temp_dir.path().join("b.toml"), "include = 'config.toml'\n").unwrap();
+        let mut config = StackedConfig::empty();
+        assert_matches!(
+            config.load_file(ConfigSource::User, &root_path),
+            Err(ConfigLoadError::IncludeCycle { path }) if path.ends_with("config.toml")
+        );
+
+        // Invalid type
+        fs::write(temp_dir.path().join("b.toml"), "include = 1\n").unwrap();
+        let mut config = StackedConfig::empty();
+        assert_matches!(
+            config.load_file(ConfigSource::User, &root_path),
+            Err(ConfigLoadError::InvalidInclude { source_path }) if source_path.ends_with("b.toml")
+        );
+    }
+
     #[test]
     fn test_stacked_config_get_simple_value() {
         let mut config = StackedConfig::empty();
@@ -1327,4 +1529,91 @@ mod tests {
         insta::assert_snapshot!(config.get_table("a.a").unwrap(), @"b = 'a.a.b #2'");
         assert_eq!(config.table_keys("a.a").collect_vec(), vec!["b"]);
     }
+
+    #[test]
+    fn test_stacked_config_look_up_origins() {
+        let mut config = StackedConfig::empty();
+        config.add_layer(new_user_layer(indoc! {"
+            a.a = 'a.a #0'
+            a.b = 'a.b #0'
+        "}));
+        config.add_layer(new_user_layer(indoc! {"
+            a.a = 'a.a #1'
+        "}));
+        config.add_layer(ConfigLayer::parse(ConfigSource::CommandArg, "a.b = 'a.b #2'").unwrap());
+        let origins_of = |config: &StackedConfig, name: &'static str| {
+            config
+                .look_up_origins(name)
+                .iter()
+                .map(|origin| {
+                    let value = origin.item.as_value().map(|v| v.to_string());
+                    (origin.layer.source, value, origin.is_overridden)
+                })
+                .collect_vec()
+        };
+        assert_eq!(
+            origins_of(&config, "a.a"),
+            vec![
+                (ConfigSource::User, Some(" 'a.a #0'".to_owned()), true),
+                (ConfigSource::User, Some(" 'a.a #1'".to_owned()), false),
+            ]
+        );
+        assert_eq!(
+            origins_of(&config, "a.b"),
+            vec![
+                (ConfigSource::User, Some(" 'a.b #0'".to_owned()), true),
+                (
+                    ConfigSource::CommandArg,
+                    Some(" 'a.b #2'".to_owned()),
+                    false
+                ),
+            ]
+        );
+        // Tables are merged, not overridden
+        assert_eq!(
+            origins_of(&config, "a"),
+            vec![
+                (ConfigSource::User, None, false),
+                (ConfigSource::User, None, false),
+                (ConfigSource::CommandArg, None, false),
+            ]
+        );
+        assert_eq!(origins_of(&config, "a.missing"), vec![]);
+
+        // a.a is shadowed by a value of parent
+        config.add_layer(ConfigLayer::parse(ConfigSource::CommandArg, "a = 'a #3'").unwrap());
+        assert_eq!(
+            origins_of(&config, "a.a"),
+            vec![
+                (ConfigSource::User, Some(" 'a.a #0'".to_owned()), true),
+                (ConfigSource::User, Some(" 'a.a #1'".to_owned()), true),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_config_layer_look_up_line() {
+        let temp_dir = new_temp_dir();
+        let path = temp_dir.path().join("config.toml");
+        fs::write(
+            &path,
+            indoc! {"
+                # comment
+                a.b = 'a.b'
+
+                [c]
+                d = 'c.d'
+                e = { f = 'c.e.f' }
+            "},
+        )
+        .unwrap();
+        let layer = ConfigLayer::load_from_file(ConfigSource::User, path).unwrap();
+        assert_eq!(layer.look_up_line("a.b"), Some(2));
+        assert_eq!(layer.look_up_line("c"), Some(4));
+        assert_eq!(layer.look_up_line("c.d"), Some(5));
+        assert_eq!(layer.look_up_line("c.e.f"), Some(6));
+        assert_eq!(layer.look_up_line("c.missing"), None);
+        // No source file
+        assert_eq!(new_user_layer("a = 1").look_up_line("a"), None);
+    }
 }
@@ -763,6 +763,7 @@ where
     }
 }
 
+#[tracing::instrument(skip_all)]
 pub fn evaluate<I: AsCompositeIndex + Clone>(
     expression: &ResolvedExpression,
     store: &Arc<Store>,
@@ -972,6 +973,24 @@ impl EvaluationContext<'_> {
                 positions.reverse();
                 Ok(Box::new(EagerRevset { positions }))
             }
+            ResolvedExpression::CommonAncestors(expression1, expression2) => {
+                let positions1: Vec<_> = self
+                    .evaluate(expression1)?
+                    .positions()
+                    .attach(index)
+                    .try_collect()?;
+                let positions2: Vec<_> = self
+                    .evaluate(expression2)?
+                    .positions()
+                    .attach(index)
+                    .try_collect()?;
+                let mut positions = index
+                    .common_ancestors_pos(&positions1, &positions2)
+                    .into_iter()
+                    .collect_vec();
+                positions.reverse();
+                Ok(Box::new(EagerRevset { positions }))
+            }
             ResolvedExpression::Latest { candidates, count } => {
                 let candidate_set = self.evaluate(candidates)?;
                 Ok(Box::new(self.take_latest_revset(&*candidate_set, *count)?))
@@ -1017,6 +1036,21 @@ impl EvaluationContext<'_> {
             ResolvedPredicateExpression::Filter(predicate) => {
                 Ok(build_predicate_fn(self.store.clone(), predicate))
             }
+            ResolvedPredicateExpression::TreeEquals(expression) => {
+                let index = self.index;
+                let tree_ids: Vec<_> = self
+                    .evaluate(expression)?
+                    .positions()
+                    .attach(index)
+                    .map(|pos| {
+                        let entry = index.entry_by_pos(pos?);
+                        let commit = self.store.get_commit(&entry.commit_id())?;
+                        Ok::<_, RevsetEvaluationError>(commit.tree_id().clone())
+                    })
+                    .try_collect()?;
+                let predicate = RevsetFilterPredicate::Tree(tree_ids);
+                Ok(build_predicate_fn(self.store.clone(), &predicate))
+            }
             ResolvedPredicateExpression::Set(expression) => {
                 Ok(self.evaluate(expression)?.into_predicate())
             }
@@ -1249,6 +1283,15 @@ fn build_predicate_fn(
             let commit = store.get_commit(&entry.commit_id())?;
             Ok(commit.has_conflict()?)
         }),
+        RevsetFilterPredicate::Tree(tree_ids) => {
+            let tree_ids: Rc<HashSet<_>> =
+                Rc::new(tree_ids.iter().map(|id| id.to_merge()).collect());
+            box_pure_predicate_fn(move |index, pos| {
+                let entry = index.entry_by_pos(pos);
+                let commit = store.get_commit(&entry.commit_id())?;
+                Ok(tree_ids.contains(&commit.tree_id().to_merge()))
+            })
+        }
         RevsetFilterPredicate::Extension(ext) => {
             let ext = ext.clone();
             box_pure_predicate_fn(move |index, pos| {
@@ -120,6 +120,13 @@ impl DefaultIndexStore {
         // Remove index segments to save disk space. If raced, new segment file
         // will be created by the other process.
         file_util::remove_dir_contents(&self.segments_dir())?;
+        Ok(())
+    }
+
+    /// Migrates the index directory written by jj <= 0.14 to the current
+    /// layout. Called when upgrading the unversioned index format.
+    pub(crate) fn upgrade_legacy_layout(&self) -> Result<(), PathError> {
+        self.ensure_base_dirs()?;
         // jj <= 0.14 created segment files in the top directory
         for entry in self.dir.read_dir().context(&self.dir)? {
             let entry = entry.context(&self.dir)?;
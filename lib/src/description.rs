@@ -0,0 +1,171 @@
+// Copyright 2025 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Parsing of commit descriptions into subject, body, and trailers.
+//!
+//! The rules loosely follow Git's: the subject is the first paragraph, the
+//! body is everything after it, and the trailers are parsed from the last
+//! paragraph of the body if it consists only of `Key: value` lines.
+
+use itertools::Itertools as _;
+
+/// A `Key: value` line at the end of a commit description.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Trailer {
+    /// Trailer key, e.g. `Signed-off-by`.
+    pub key: String,
+    /// Trailer value with continuation lines joined by a space.
+    pub value: String,
+}
+
+/// Returns the first paragraph of the `description` joined into a single
+/// line.
+pub fn subject(description: &str) -> String {
+    let (paragraph, _) = split_first_paragraph(description);
+    paragraph.lines().map(str::trim).join(" ")
+}
+
+/// Returns the text after the first paragraph of the `description`, with
+/// leading blank lines removed. The trailers are included.
+pub fn body(description: &str) -> &str {
+    let (_, rest) = split_first_paragraph(description);
+    skip_blank_lines(rest)
+}
+
+/// Parses the trailers from the last paragraph of the `description` body.
+///
+/// Returns an empty list if the body is empty or if any line of its last
+/// paragraph is neither a trailer nor a continuation of one.
+pub fn trailers(description: &str) -> Vec<Trailer> {
+    let lines = body(description).lines().collect_vec();
+    let is_blank = |line: &&str| line.trim().is_empty();
+    let end = lines
+        .iter()
+        .rposition(|line| !is_blank(line))
+        .map_or(0, |i| i + 1);
+    let start = lines[..end].iter().rposition(is_blank).map_or(0, |i| i + 1);
+    let mut trailers: Vec<Trailer> = vec![];
+    for line in &lines[start..end] {
+        if line.starts_with([' ', '\t']) {
+            let Some(last) = trailers.last_mut() else {
+                return vec![];
+            };
+            last.value.push(' ');
+            last.value.push_str(line.trim());
+        } else if let Some(trailer) = parse_trailer_line(line) {
+            trailers.push(trailer);
+        } else {
+            return vec![];
+        }
+    }
+    trailers
+}
+
+fn parse_trailer_line(line: &str) -> Option<Trailer> {
+    let (key, value) = line.split_once(':')?;
+    let is_valid_key =
+        !key.is_empty() && key.chars().all(|c| c.is_ascii_alphanumeric() || c == '-');
+    is_valid_key.then(|| Trailer {
+        key: key.to_owned(),
+        value: value.trim().to_owned(),
+    })
+}
+
+/// Splits `text` into the first paragraph and the rest. Leading blank lines
+/// are skipped.
+fn split_first_paragraph(text: &str) -> (&str, &str) {
+    let text = skip_blank_lines(text);
+    let len = text
+        .split_inclusive('\n')
+        .take_while(|line| !line.trim().is_empty())
+        .map(str::len)
+        .sum();
+    text.split_at(len)
+}
+
+fn skip_blank_lines(text: &str) -> &str {
+    let len = text
+        .split_inclusive('\n')
+        .take_while(|line| line.trim().is_empty())
+        .map(str::len)
+        .sum();
+    &text[len..]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn trailer(key: &str, value: &str) -> Trailer {
+        Trailer {
+            key: key.to_owned(),
+            value: value.to_owned(),
+        }
+    }
+
+    #[test]
+    fn test_subject_and_body() {
+        assert_eq!(subject(""), "");
+        assert_eq!(body(""), "");
+
+        assert_eq!(subject("foo\n"), "foo");
+        assert_eq!(body("foo\n"), "");
+
+        let description = "\nfoo\n  bar\n\n\nbody 1\n\nbody 2\n";
+        assert_eq!(subject(description), "foo bar");
+        assert_eq!(body(description), "body 1\n\nbody 2\n");
+
+        // Blank lines may contain whitespace
+        assert_eq!(subject("foo\n \nbar"), "foo");
+        assert_eq!(body("foo\n \nbar"), "bar");
+    }
+
+    #[test]
+    fn test_trailers() {
+        assert_eq!(trailers(""), vec![]);
+
+        // Subject isn't parsed as trailers
+        assert_eq!(trailers("Key: value\n"), vec![]);
+
+        let description = indoc::indoc! {"
+            subject
+
+            body: not a trailer
+
+            Signed-off-by: Foo <foo@example.com>
+            Change-Id: I1234
+              continued
+            Fixes:#123
+        "};
+        assert_eq!(
+            trailers(description),
+            vec![
+                trailer("Signed-off-by", "Foo <foo@example.com>"),
+                trailer("Change-Id", "I1234 continued"),
+                trailer("Fixes", "#123"),
+            ]
+        );
+
+        // Last paragraph containing a non-trailer line
+        assert_eq!(trailers("subject\n\nKey: value\nnot a trailer\n"), vec![]);
+        assert_eq!(trailers("subject\n\nBad key: value\n"), vec![]);
+        // Continuation line can't start a paragraph
+        assert_eq!(trailers("subject\n\n  Key: value\n"), vec![]);
+        // Trailing blank lines are ignored
+        assert_eq!(
+            trailers("subject\n\nKey: value\n\n\n"),
+            vec![trailer("Key", "value")]
+        );
+    }
+}
@@ -24,6 +24,7 @@ use std::ops::Range;
 use std::slice;
 
 use bstr::BStr;
+use bstr::BString;
 use hashbrown::HashTable;
 use itertools::Itertools;
 use smallvec::smallvec;
@@ -183,6 +184,29 @@ impl CompareBytes for CompareBytesIgnoreWhitespaceAmount {
     }
 }
 
+/// Strategy to find unchanged tokens between inputs.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, serde::Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum DiffAlgorithm {
+    /// Matches the least frequent tokens shared by the inputs, and recurses
+    /// into the regions between them.
+    #[default]
+    Histogram,
+    /// Matches only tokens that occur exactly once in each input.
+    Patience,
+}
+
+/// Options to control how unchanged tokens are found.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct DiffOptions {
+    /// Strategy to find unchanged tokens.
+    pub algorithm: DiffAlgorithm,
+    /// Tokens starting with any of these prefixes are matched before the
+    /// others, so they will be kept unchanged if they occur exactly once in
+    /// each input.
+    pub anchors: Vec<BString>,
+}
+
 // Not implementing Eq because the text should be compared by WordComparator.
 #[derive(Clone, Copy, Debug)]
 struct HashedWord<'input> {
@@ -424,6 +448,7 @@ fn collect_unchanged_words<C: CompareBytes, S: BuildHasher>(
     left: &LocalDiffSource,
     right: &LocalDiffSource,
     comp: &WordComparator<C, S>,
+    algorithm: DiffAlgorithm,
 ) {
     if left.ranges.is_empty() || right.ranges.is_empty() {
         return;
@@ -431,7 +456,7 @@ fn collect_unchanged_words<C: CompareBytes, S: BuildHasher>(
 
     // Prioritize LCS-based algorithm than leading/trailing matches
     let old_len = found_positions.len();
-    collect_unchanged_words_lcs(found_positions, left, right, comp);
+    collect_unchanged_words_lcs(found_positions, left, right, comp, algorithm);
     if found_positions.len() != old_len {
         return;
     }
@@ -464,11 +489,56 @@ fn collect_unchanged_words<C: CompareBytes, S: BuildHasher>(
     ));
 }
 
+/// Finds unchanged word positions like [`collect_unchanged_words()`], but
+/// matches the words starting with any of the `options.anchors` first.
+fn collect_unchanged_words_anchored<C: CompareBytes, S: BuildHasher>(
+    found_positions: &mut Vec<(WordPosition, WordPosition)>,
+    left: &LocalDiffSource,
+    right: &LocalDiffSource,
+    comp: &WordComparator<C, S>,
+    options: &DiffOptions,
+) {
+    if options.anchors.is_empty() {
+        collect_unchanged_words(found_positions, left, right, comp, options.algorithm);
+        return;
+    }
+    let is_anchor = |text: &BStr| {
+        options
+            .anchors
+            .iter()
+            .any(|prefix| text.starts_with(prefix.as_slice()))
+    };
+    // Only words that occur exactly once in both inputs can be anchors.
+    let left_histogram = Histogram::calculate(left, comp, 1);
+    let right_histogram = Histogram::calculate(right, comp, 1);
+    let anchor_positions = left_histogram
+        .word_to_positions
+        .iter()
+        .filter(|(word, _)| is_anchor(word.text))
+        .filter_map(|(word, left_positions)| {
+            let right_positions = right_histogram.positions_by_word(*word, comp)?;
+            match (&left_positions[..], right_positions) {
+                (&[left_pos], &[right_pos]) => Some((left_pos, right_pos)),
+                _ => None,
+            }
+        })
+        .collect_vec();
+    collect_unchanged_words_with_lcs_of(
+        found_positions,
+        left,
+        right,
+        comp,
+        options.algorithm,
+        anchor_positions,
+    );
+}
+
 fn collect_unchanged_words_lcs<C: CompareBytes, S: BuildHasher>(
     found_positions: &mut Vec<(WordPosition, WordPosition)>,
     left: &LocalDiffSource,
     right: &LocalDiffSource,
     comp: &WordComparator<C, S>,
+    algorithm: DiffAlgorithm,
 ) {
     let max_occurrences = 100;
     let left_histogram = Histogram::calculate(left, comp, max_occurrences);
@@ -478,32 +548,61 @@ fn collect_unchanged_words_lcs<C: CompareBytes, S: BuildHasher>(
         return;
     }
     let right_histogram = Histogram::calculate(right, comp, max_occurrences);
+    // Patience diff only considers words which are unique in both inputs.
+    let max_count = match algorithm {
+        DiffAlgorithm::Histogram => usize::MAX,
+        DiffAlgorithm::Patience => 1,
+    };
     // Look for words with few occurrences in `left` (could equally well have picked
     // `right`?). If any of them also occur in `right`, then we add the words to
     // the LCS.
     let Some(uncommon_shared_word_positions) =
-        left_count_to_entries.values().find_map(|left_entries| {
-            let mut both_positions = left_entries
-                .iter()
-                .filter_map(|&(word, left_positions)| {
-                    let right_positions = right_histogram.positions_by_word(*word, comp)?;
-                    (left_positions.len() == right_positions.len())
-                        .then_some((left_positions, right_positions))
-                })
-                .peekable();
-            both_positions.peek().is_some().then_some(both_positions)
-        })
+        left_count_to_entries
+            .range(..=max_count)
+            .find_map(|(_, left_entries)| {
+                let mut both_positions = left_entries
+                    .iter()
+                    .filter_map(|&(word, left_positions)| {
+                        let right_positions = right_histogram.positions_by_word(*word, comp)?;
+                        (left_positions.len() == right_positions.len())
+                            .then_some((left_positions, right_positions))
+                    })
+                    .peekable();
+                both_positions.peek().is_some().then_some(both_positions)
+            })
     else {
         return;
     };
 
+    let shared_word_positions = uncommon_shared_word_positions
+        .flat_map(|(lefts, rights)| iter::zip(lefts, rights))
+        .map(|(&left_pos, &right_pos)| (left_pos, right_pos));
+    collect_unchanged_words_with_lcs_of(
+        found_positions,
+        left,
+        right,
+        comp,
+        algorithm,
+        shared_word_positions,
+    );
+}
+
+/// Adds the LCS of the given pairs of matching word positions, recursing into
+/// the modified areas between them.
+fn collect_unchanged_words_with_lcs_of<C: CompareBytes, S: BuildHasher>(
+    found_positions: &mut Vec<(WordPosition, WordPosition)>,
+    left: &LocalDiffSource,
+    right: &LocalDiffSource,
+    comp: &WordComparator<C, S>,
+    algorithm: DiffAlgorithm,
+    shared_word_positions: impl IntoIterator<Item = (LocalWordPosition, LocalWordPosition)>,
+) {
     // [(index into ranges, serial to identify {word, occurrence #})]
-    let (mut left_positions, mut right_positions): (Vec<_>, Vec<_>) =
-        uncommon_shared_word_positions
-            .flat_map(|(lefts, rights)| iter::zip(lefts, rights))
-            .enumerate()
-            .map(|(serial, (&left_pos, &right_pos))| ((left_pos, serial), (right_pos, serial)))
-            .unzip();
+    let (mut left_positions, mut right_positions): (Vec<_>, Vec<_>) = shared_word_positions
+        .into_iter()
+        .enumerate()
+        .map(|(serial, (left_pos, right_pos))| ((left_pos, serial), (right_pos, serial)))
+        .unzip();
     left_positions.sort_unstable_by_key(|&(pos, _serial)| pos);
     right_positions.sort_unstable_by_key(|&(pos, _serial)| pos);
     let left_index_by_right_index: Vec<usize> = {
@@ -531,6 +630,7 @@ fn collect_unchanged_words_lcs<C: CompareBytes, S: BuildHasher>(
             &left.narrowed(previous_left_position..left_position),
             &right.narrowed(previous_right_position..right_position),
             comp,
+            algorithm,
         );
         found_positions.push((
             left.map_to_global(left_position),
@@ -545,6 +645,7 @@ fn collect_unchanged_words_lcs<C: CompareBytes, S: BuildHasher>(
         &left.narrowed(previous_left_position..LocalWordPosition(left.ranges.len())),
         &right.narrowed(previous_right_position..LocalWordPosition(right.ranges.len())),
         comp,
+        algorithm,
     );
 }
 
@@ -613,6 +714,17 @@ impl<'input> Diff<'input> {
         inputs: impl IntoIterator<Item = &'input T>,
         tokenizer: impl Fn(&[u8]) -> Vec<Range<usize>>,
         compare: impl CompareBytes,
+    ) -> Self {
+        Self::for_tokenizer_with_options(inputs, tokenizer, compare, &DiffOptions::default())
+    }
+
+    /// Like [`Diff::for_tokenizer()`], but finds unchanged tokens as
+    /// configured by the `options`.
+    pub fn for_tokenizer_with_options<T: AsRef<[u8]> + ?Sized + 'input>(
+        inputs: impl IntoIterator<Item = &'input T>,
+        tokenizer: impl Fn(&[u8]) -> Vec<Range<usize>>,
+        compare: impl CompareBytes,
+        options: &DiffOptions,
     ) -> Self {
         let mut inputs = inputs.into_iter().map(BStr::new);
         let base_input = inputs.next().expect("inputs must not be empty");
@@ -640,6 +752,7 @@ impl<'input> Diff<'input> {
             &base_token_ranges,
             &other_token_ranges,
             compare,
+            options,
         )
     }
 
@@ -649,6 +762,7 @@ impl<'input> Diff<'input> {
         base_token_ranges: &[Range<usize>],
         other_token_ranges: &[Vec<Range<usize>>],
         compare: impl CompareBytes,
+        options: &DiffOptions,
     ) -> Self {
         assert_eq!(other_inputs.len(), other_token_ranges.len());
         let comp = WordComparator::new(compare);
@@ -676,11 +790,12 @@ impl<'input> Diff<'input> {
                     others: smallvec![0..0; other_inputs.len()],
                 });
                 let mut first_positions = Vec::new();
-                collect_unchanged_words(
+                collect_unchanged_words_anchored(
                     &mut first_positions,
                     &base_source.local(),
                     &first_other_source.local(),
                     &comp,
+                    options,
                 );
                 if tail_other_sources.is_empty() {
                     unchanged_regions.extend(first_positions.iter().map(
@@ -702,11 +817,12 @@ impl<'input> Diff<'input> {
                         first_positions,
                         |current_positions, other_source| {
                             let mut new_positions = Vec::new();
-                            collect_unchanged_words(
+                            collect_unchanged_words_anchored(
                                 &mut new_positions,
                                 &base_source.local(),
                                 &other_source.local(),
                                 &comp,
+                                options,
                             );
                             intersect_unchanged_words(current_positions, &new_positions)
                         },
@@ -1201,7 +1317,13 @@ mod tests {
         let left = DiffSource::new(left_text, left_ranges, &comp);
         let right = DiffSource::new(right_text, right_ranges, &comp);
         let mut positions = Vec::new();
-        collect_unchanged_words(&mut positions, &left.local(), &right.local(), &comp);
+        collect_unchanged_words(
+            &mut positions,
+            &left.local(),
+            &right.local(),
+            &comp,
+            DiffAlgorithm::Histogram,
+        );
         positions
             .into_iter()
             .map(|(left_pos, right_pos)| (left.range_at(left_pos), right.range_at(right_pos)))
@@ -1487,6 +1609,83 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_diff_patience_algorithm() {
+        let diff_with = |algorithm, inputs: [&'static str; 2]| {
+            let options = DiffOptions {
+                algorithm,
+                ..DiffOptions::default()
+            };
+            let diff = Diff::for_tokenizer_with_options(
+                inputs,
+                find_line_ranges,
+                CompareBytesExactly,
+                &options,
+            );
+            diff.hunks().collect_vec()
+        };
+
+        // Histogram diff matches the least frequent lines even if they aren't
+        // unique.
+        assert_eq!(
+            diff_with(DiffAlgorithm::Histogram, ["a\na\nb\nb\n", "b\nb\na\na\n"]),
+            vec![
+                DiffHunk::different(["a\na\n", ""]),
+                DiffHunk::matching(["b\nb\n"].repeat(2)),
+                DiffHunk::different(["", "a\na\n"]),
+            ]
+        );
+        // Patience diff only matches unique lines.
+        assert_eq!(
+            diff_with(DiffAlgorithm::Patience, ["a\na\nb\nb\n", "b\nb\na\na\n"]),
+            vec![DiffHunk::different(["a\na\nb\nb\n", "b\nb\na\na\n"])]
+        );
+    }
+
+    #[test]
+    fn test_diff_anchored() {
+        let diff_anchored = |anchors: &[&str], inputs: [&'static str; 2]| {
+            let options = DiffOptions {
+                anchors: anchors.iter().map(|&s| s.into()).collect(),
+                ..DiffOptions::default()
+            };
+            let diff = Diff::for_tokenizer_with_options(
+                inputs,
+                find_line_ranges,
+                CompareBytesExactly,
+                &options,
+            );
+            diff.hunks().collect_vec()
+        };
+
+        assert_eq!(
+            diff_anchored(&[], ["a\nb\nc\n", "c\na\nb\n"]),
+            vec![
+                DiffHunk::different(["", "c\n"]),
+                DiffHunk::matching(["a\nb\n"].repeat(2)),
+                DiffHunk::different(["c\n", ""]),
+            ]
+        );
+        // The anchored line is kept unchanged
+        assert_eq!(
+            diff_anchored(&["c"], ["a\nb\nc\n", "c\na\nb\n"]),
+            vec![
+                DiffHunk::different(["a\nb\n", ""]),
+                DiffHunk::matching(["c\n"].repeat(2)),
+                DiffHunk::different(["", "a\nb\n"]),
+            ]
+        );
+        // Non-unique lines can't be anchors
+        assert_eq!(
+            diff_anchored(&["c"], ["a\nb\nc\nc\n", "c\nc\na\nb\n"]),
+            vec![
+                DiffHunk::different(["", "c\nc\n"]),
+                DiffHunk::matching(["a\nb\n"].repeat(2)),
+                DiffHunk::different(["c\nc\n", ""]),
+            ]
+        );
+    }
+
     #[test]
     fn test_diff_ignore_all_whitespace() {
         fn diff(inputs: [&str; 2]) -> Vec<DiffHunk<'_>> {
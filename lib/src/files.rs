@@ -15,6 +15,7 @@
 #![allow(missing_docs)]
 
 use std::borrow::Borrow;
+use std::borrow::Cow;
 use std::collections::VecDeque;
 use std::iter;
 use std::mem;
@@ -202,6 +203,102 @@ pub fn merge<T: AsRef<[u8]>>(slices: &Merge<T>) -> MergeResult {
     merge_hunks(&Diff::by_line(diff_inputs), num_diffs)
 }
 
+/// Differences in file contents to ignore when merging, configured by the
+/// `merge.normalize-*` settings.
+///
+/// If the contents still conflict, the normalized contents are compared. When
+/// they can be trivially resolved, the original bytes of the resolved side are
+/// used instead of recording a conflict.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct MergeNormalization {
+    /// Treat CRLF and LF line endings as equal.
+    pub eol: bool,
+    /// Ignore whitespace at the end of lines.
+    pub trailing_whitespace: bool,
+}
+
+impl MergeNormalization {
+    /// Returns true if any normalization is enabled.
+    pub fn is_enabled(&self) -> bool {
+        self.eol || self.trailing_whitespace
+    }
+
+    /// Normalizes the line endings and trailing whitespace of the `content`.
+    pub fn normalize<'a>(&self, content: &'a [u8]) -> Cow<'a, [u8]> {
+        if !self.is_enabled() {
+            return Cow::Borrowed(content);
+        }
+        let mut normalized = Vec::with_capacity(content.len());
+        for line in content.split_inclusive(|b| *b == b'\n') {
+            let (mut text, newline) = match line.strip_suffix(b"\n") {
+                Some(text) => (text, &b"\n"[..]),
+                None => (line, &b""[..]),
+            };
+            if self.eol && !newline.is_empty() {
+                text = text.strip_suffix(b"\r").unwrap_or(text);
+            }
+            if self.trailing_whitespace {
+                text = text.trim_ascii_end();
+            }
+            normalized.extend_from_slice(text);
+            normalized.extend_from_slice(newline);
+        }
+        if normalized == content {
+            Cow::Borrowed(content)
+        } else {
+            Cow::Owned(normalized)
+        }
+    }
+}
+
+/// Conflict resolved by [`resolve_normalized()`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct NormalizedResolution<'a> {
+    /// The original content of the resolved side.
+    pub content: &'a [u8],
+    /// The normalizations which changed any of the sides, and so were needed
+    /// to resolve the conflict.
+    pub applied: MergeNormalization,
+}
+
+/// Resolves the conflicting `contents` which are equal after normalization.
+///
+/// Returns the original content of the first side which has the resolved
+/// normalized content, or `None` if the normalized contents still conflict.
+pub fn resolve_normalized<T: AsRef<[u8]>>(
+    contents: &Merge<T>,
+    normalization: MergeNormalization,
+) -> Option<NormalizedResolution<'_>> {
+    if !normalization.is_enabled() {
+        return None;
+    }
+    let normalized = contents.map(|content| normalization.normalize(content.as_ref()));
+    let resolved = normalized.resolve_trivial()?;
+    let (content, _) = iter::zip(contents.adds(), normalized.adds())
+        .find(|(_, normalized)| *normalized == resolved)?;
+    let changes_any = |normalization: MergeNormalization| {
+        contents
+            .iter()
+            .any(|content| matches!(normalization.normalize(content.as_ref()), Cow::Owned(_)))
+    };
+    let applied = MergeNormalization {
+        eol: normalization.eol
+            && changes_any(MergeNormalization {
+                eol: true,
+                trailing_whitespace: false,
+            }),
+        trailing_whitespace: normalization.trailing_whitespace
+            && changes_any(MergeNormalization {
+                eol: false,
+                trailing_whitespace: true,
+            }),
+    };
+    Some(NormalizedResolution {
+        content: content.as_ref(),
+        applied,
+    })
+}
+
 fn merge_hunks(diff: &Diff, num_diffs: usize) -> MergeResult {
     let mut resolved_hunk = BString::new(vec![]);
     let mut merge_hunks: Vec<Merge<BString>> = vec![];
@@ -524,4 +621,72 @@ b {
             ))
         );
     }
+
+    #[test]
+    fn test_merge_normalization_normalize() {
+        let eol = MergeNormalization {
+            eol: true,
+            trailing_whitespace: false,
+        };
+        let whitespace = MergeNormalization {
+            eol: false,
+            trailing_whitespace: true,
+        };
+        assert_eq!(eol.normalize(b"a \r\nb\r\n\r"), &b"a \nb\n\r"[..]);
+        assert_eq!(whitespace.normalize(b"a \r\nb\t\nc "), &b"a\nb\nc"[..]);
+        assert!(matches!(eol.normalize(b"a\nb"), Cow::Borrowed(_)));
+        assert!(matches!(
+            MergeNormalization::default().normalize(b"a\r\n"),
+            Cow::Borrowed(_)
+        ));
+    }
+
+    #[test]
+    fn test_resolve_normalized() {
+        let eol = MergeNormalization {
+            eol: true,
+            trailing_whitespace: false,
+        };
+        let both = MergeNormalization {
+            eol: true,
+            trailing_whitespace: true,
+        };
+        let resolve = |removes: &[&[u8]], adds: &[&[u8]], normalization| {
+            resolve_normalized(&Merge::from_removes_adds(removes, adds), normalization)
+                .map(|resolution| (resolution.content.to_vec(), resolution.applied))
+        };
+
+        // The side which changed the normalized content wins
+        assert_eq!(
+            resolve(&[b"a\nb\n"], &[b"a\r\nb\r\n", b"a\nB\n"], eol),
+            Some((b"a\nB\n".to_vec(), eol))
+        );
+        // All sides are equal after normalization
+        assert_eq!(
+            resolve(&[b"a\n"], &[b"a\r\n", b"a\n"], eol),
+            Some((b"a\r\n".to_vec(), eol))
+        );
+        // Only the normalizations which changed the contents are recorded
+        assert_eq!(
+            resolve(&[b"a\n"], &[b"a \n", b"a\n"], both),
+            Some((
+                b"a \n".to_vec(),
+                MergeNormalization {
+                    eol: false,
+                    trailing_whitespace: true,
+                }
+            ))
+        );
+        // Still conflicting after normalization
+        assert_eq!(resolve(&[b"a\n"], &[b"b\r\n", b"c\n"], eol), None);
+        // Normalization is disabled
+        assert_eq!(
+            resolve(
+                &[b"a\n"],
+                &[b"a\r\n", b"a\n"],
+                MergeNormalization::default()
+            ),
+            None
+        );
+    }
 }
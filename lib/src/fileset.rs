@@ -43,6 +43,7 @@ use crate::matchers::Matcher;
 use crate::matchers::NothingMatcher;
 use crate::matchers::PrefixMatcher;
 use crate::matchers::UnionMatcher;
+use crate::op_store::WorkspaceId;
 use crate::repo_path::RelativePathParseError;
 use crate::repo_path::RepoPath;
 use crate::repo_path::RepoPathBuf;
@@ -80,6 +81,11 @@ pub enum FilePattern {
         /// Glob pattern relative to `dir`.
         pattern: glob::Pattern,
     },
+    /// Matches file (or exact) path with case-insensitive glob pattern.
+    ///
+    /// Unlike `FileGlob`, the pattern is relative to the workspace root so
+    /// that the directory part is also matched case-insensitively.
+    FileGlobI(glob::Pattern),
     // TODO: add more patterns:
     // - FilesInPath: files in directory, non-recursively?
     // - NameGlob or SuffixGlob: file name with glob?
@@ -106,13 +112,17 @@ impl FilePattern {
         //   * path: literal path (default) (default anchor: prefix)
         //   * glob: glob pattern (default anchor: file)
         //   * regex?
+        // * case sensitivity
+        //   * -i: case-insensitive (glob only)
         match kind {
             "cwd" => Self::cwd_prefix_path(path_converter, input),
             "cwd-file" | "file" => Self::cwd_file_path(path_converter, input),
             "cwd-glob" | "glob" => Self::cwd_file_glob(path_converter, input),
+            "cwd-glob-i" | "glob-i" => Self::cwd_file_glob_i(path_converter, input),
             "root" => Self::root_prefix_path(input),
             "root-file" => Self::root_file_path(input),
             "root-glob" => Self::root_file_glob(input),
+            "root-glob-i" => Self::root_file_glob_i(input),
             _ => Err(FilePatternParseError::InvalidKind(kind.to_owned())),
         }
     }
@@ -145,6 +155,16 @@ impl FilePattern {
         Self::file_glob_at(dir, pattern)
     }
 
+    /// Pattern that matches cwd-relative file path glob case-insensitively.
+    pub fn cwd_file_glob_i(
+        path_converter: &RepoPathUiConverter,
+        input: impl AsRef<str>,
+    ) -> Result<Self, FilePatternParseError> {
+        let (dir, pattern) = split_glob_path(input.as_ref());
+        let dir = path_converter.parse_file_path(dir)?;
+        Self::file_glob_i_at(dir, pattern)
+    }
+
     /// Pattern that matches workspace-relative file (or exact) path.
     pub fn root_file_path(input: impl AsRef<str>) -> Result<Self, FilePatternParseError> {
         // TODO: Let caller pass in converter for root-relative paths too
@@ -165,6 +185,14 @@ impl FilePattern {
         Self::file_glob_at(dir, pattern)
     }
 
+    /// Pattern that matches workspace-relative file path glob
+    /// case-insensitively.
+    pub fn root_file_glob_i(input: impl AsRef<str>) -> Result<Self, FilePatternParseError> {
+        let (dir, pattern) = split_glob_path(input.as_ref());
+        let dir = RepoPathBuf::from_relative_path(dir)?;
+        Self::file_glob_i_at(dir, pattern)
+    }
+
     fn file_glob_at(dir: RepoPathBuf, input: &str) -> Result<Self, FilePatternParseError> {
         if input.is_empty() {
             return Ok(FilePattern::FilePath(dir));
@@ -175,6 +203,42 @@ impl FilePattern {
         Ok(FilePattern::FileGlob { dir, pattern })
     }
 
+    fn file_glob_i_at(dir: RepoPathBuf, input: &str) -> Result<Self, FilePatternParseError> {
+        // Normalize separator to '/', reject ".." which will never match
+        let normalized = RepoPathBuf::from_relative_path(input)?;
+        Ok(Self::glob_i_from_parts(
+            &dir,
+            normalized.as_internal_file_string(),
+        )?)
+    }
+
+    /// Builds case-insensitive glob from literal `dir` and `pattern` relative
+    /// to it.
+    fn glob_i_from_parts(dir: &RepoPath, pattern: &str) -> Result<Self, glob::PatternError> {
+        let escaped_dir = glob::Pattern::escape(dir.as_internal_file_string());
+        let pattern = match (escaped_dir.is_empty(), pattern.is_empty()) {
+            (true, true) => return Ok(FilePattern::FilePath(dir.to_owned())),
+            (false, true) => escaped_dir,
+            (true, false) => pattern.to_owned(),
+            (false, false) => format!("{escaped_dir}/{pattern}"),
+        };
+        Ok(FilePattern::FileGlobI(glob::Pattern::new(&pattern)?))
+    }
+
+    /// Converts glob pattern to case-insensitive one. Other patterns are
+    /// returned unchanged.
+    pub fn to_case_insensitive(&self) -> Self {
+        match self {
+            FilePattern::FileGlob { dir, pattern } => {
+                Self::glob_i_from_parts(dir, pattern.as_str())
+                    .expect("escaped dir joined with valid pattern should be valid")
+            }
+            FilePattern::FilePath(_) | FilePattern::PrefixPath(_) | FilePattern::FileGlobI(_) => {
+                self.clone()
+            }
+        }
+    }
+
     /// Returns path if this pattern represents a literal path in a workspace.
     /// Returns `None` if this is a glob pattern for example.
     pub fn as_path(&self) -> Option<&RepoPath> {
@@ -182,6 +246,7 @@ impl FilePattern {
             FilePattern::FilePath(path) => Some(path),
             FilePattern::PrefixPath(path) => Some(path),
             FilePattern::FileGlob { .. } => None,
+            FilePattern::FileGlobI(_) => None,
         }
     }
 }
@@ -303,6 +368,31 @@ impl FilesetExpression {
         })
     }
 
+    /// Makes all glob patterns in this expression case-insensitive.
+    pub fn to_case_insensitive_globs(&self) -> Self {
+        match self {
+            FilesetExpression::None => FilesetExpression::None,
+            FilesetExpression::All => FilesetExpression::All,
+            FilesetExpression::Pattern(pattern) => {
+                FilesetExpression::Pattern(pattern.to_case_insensitive())
+            }
+            FilesetExpression::UnionAll(exprs) => FilesetExpression::UnionAll(
+                exprs
+                    .iter()
+                    .map(|expr| expr.to_case_insensitive_globs())
+                    .collect(),
+            ),
+            FilesetExpression::Intersection(expr1, expr2) => FilesetExpression::Intersection(
+                Box::new(expr1.to_case_insensitive_globs()),
+                Box::new(expr2.to_case_insensitive_globs()),
+            ),
+            FilesetExpression::Difference(expr1, expr2) => FilesetExpression::Difference(
+                Box::new(expr1.to_case_insensitive_globs()),
+                Box::new(expr2.to_case_insensitive_globs()),
+            ),
+        }
+    }
+
     /// Transforms the expression tree to `Matcher` object.
     pub fn to_matcher(&self) -> Box<dyn Matcher> {
         build_union_matcher(self.as_union_all())
@@ -317,6 +407,7 @@ fn build_union_matcher(expressions: &[FilesetExpression]) -> Box<dyn Matcher> {
     let mut file_paths = Vec::new();
     let mut prefix_paths = Vec::new();
     let mut file_globs = Vec::new();
+    let mut file_globs_i = Vec::new();
     let mut matchers: Vec<Option<Box<dyn Matcher>>> = Vec::new();
     for expr in expressions {
         let matcher: Box<dyn Matcher> = match expr {
@@ -330,6 +421,9 @@ fn build_union_matcher(expressions: &[FilesetExpression]) -> Box<dyn Matcher> {
                     FilePattern::FileGlob { dir, pattern } => {
                         file_globs.push((dir, pattern.clone()));
                     }
+                    FilePattern::FileGlobI(pattern) => {
+                        file_globs_i.push((RepoPath::root(), pattern.clone()));
+                    }
                 }
                 continue;
             }
@@ -358,6 +452,11 @@ fn build_union_matcher(expressions: &[FilesetExpression]) -> Box<dyn Matcher> {
     if !file_globs.is_empty() {
         matchers.push(Some(Box::new(FileGlobsMatcher::new(file_globs))));
     }
+    if !file_globs_i.is_empty() {
+        matchers.push(Some(Box::new(FileGlobsMatcher::new_case_insensitive(
+            file_globs_i,
+        ))));
+    }
     union_all_matchers(&mut matchers)
 }
 
@@ -379,9 +478,29 @@ fn union_all_matchers(matchers: &mut [Option<Box<dyn Matcher>>]) -> Box<dyn Matc
     }
 }
 
+/// Context needed to resolve fileset expressions.
+#[derive(Clone, Copy, Debug)]
+pub struct FilesetParseContext<'a> {
+    /// Converts paths specified by the user to repo paths.
+    pub path_converter: &'a RepoPathUiConverter,
+    /// Sparse patterns of the workspaces, which are referenced by the
+    /// `workspace_sparse()` function. The function isn't available if unset.
+    pub workspace_sparse_patterns: Option<&'a HashMap<WorkspaceId, Vec<RepoPathBuf>>>,
+}
+
+impl<'a> FilesetParseContext<'a> {
+    /// Creates context which doesn't know about any workspaces.
+    pub fn new(path_converter: &'a RepoPathUiConverter) -> Self {
+        FilesetParseContext {
+            path_converter,
+            workspace_sparse_patterns: None,
+        }
+    }
+}
+
 type FilesetFunction = fn(
     &mut FilesetDiagnostics,
-    &RepoPathUiConverter,
+    &FilesetParseContext,
     &FunctionCallNode,
 ) -> FilesetParseResult<FilesetExpression>;
 
@@ -389,24 +508,62 @@ static BUILTIN_FUNCTION_MAP: Lazy<HashMap<&'static str, FilesetFunction>> = Lazy
     // Not using maplit::hashmap!{} or custom declarative macro here because
     // code completion inside macro is quite restricted.
     let mut map: HashMap<&'static str, FilesetFunction> = HashMap::new();
-    map.insert("none", |_diagnostics, _path_converter, function| {
+    map.insert("none", |_diagnostics, _context, function| {
         function.expect_no_arguments()?;
         Ok(FilesetExpression::none())
     });
-    map.insert("all", |_diagnostics, _path_converter, function| {
+    map.insert("all", |_diagnostics, _context, function| {
         function.expect_no_arguments()?;
         Ok(FilesetExpression::all())
     });
+    map.insert("workspace_sparse", |diagnostics, context, function| {
+        let [arg] = function.expect_exact_arguments()?;
+        let name = match &arg.kind {
+            ExpressionKind::Identifier(name) => *name,
+            ExpressionKind::String(name) => name.as_str(),
+            _ => {
+                return Err(FilesetParseError::expression(
+                    "Expected workspace name",
+                    arg.span,
+                ))
+            }
+        };
+        let Some(sparse_patterns) = context.workspace_sparse_patterns else {
+            return Err(FilesetParseError::expression(
+                "Workspaces aren't available in this context",
+                function.name_span,
+            ));
+        };
+        let patterns = match sparse_patterns.get(&WorkspaceId::new(name.to_string())) {
+            Some(patterns) => patterns.clone(),
+            None => {
+                diagnostics.add_warning(FilesetParseError::expression(
+                    format!(
+                        "No sparse patterns recorded for workspace `{name}`, assuming it checks \
+                         out all files"
+                    ),
+                    arg.span,
+                ));
+                vec![RepoPathBuf::root()]
+            }
+        };
+        Ok(FilesetExpression::union_all(
+            patterns
+                .into_iter()
+                .map(FilesetExpression::prefix_path)
+                .collect(),
+        ))
+    });
     map
 });
 
 fn resolve_function(
     diagnostics: &mut FilesetDiagnostics,
-    path_converter: &RepoPathUiConverter,
+    context: &FilesetParseContext,
     function: &FunctionCallNode,
 ) -> FilesetParseResult<FilesetExpression> {
     if let Some(func) = BUILTIN_FUNCTION_MAP.get(function.name) {
-        func(diagnostics, path_converter, function)
+        func(diagnostics, context, function)
     } else {
         Err(FilesetParseError::new(
             FilesetParseErrorKind::NoSuchFunction {
@@ -420,9 +577,10 @@ fn resolve_function(
 
 fn resolve_expression(
     diagnostics: &mut FilesetDiagnostics,
-    path_converter: &RepoPathUiConverter,
+    context: &FilesetParseContext,
     node: &ExpressionNode,
 ) -> FilesetParseResult<FilesetExpression> {
+    let path_converter = context.path_converter;
     let wrap_pattern_error =
         |err| FilesetParseError::expression("Invalid file pattern", node.span).with_source(err);
     match &node.kind {
@@ -442,14 +600,14 @@ fn resolve_expression(
             Ok(FilesetExpression::pattern(pattern))
         }
         ExpressionKind::Unary(op, arg_node) => {
-            let arg = resolve_expression(diagnostics, path_converter, arg_node)?;
+            let arg = resolve_expression(diagnostics, context, arg_node)?;
             match op {
                 UnaryOp::Negate => Ok(FilesetExpression::all().difference(arg)),
             }
         }
         ExpressionKind::Binary(op, lhs_node, rhs_node) => {
-            let lhs = resolve_expression(diagnostics, path_converter, lhs_node)?;
-            let rhs = resolve_expression(diagnostics, path_converter, rhs_node)?;
+            let lhs = resolve_expression(diagnostics, context, lhs_node)?;
+            let rhs = resolve_expression(diagnostics, context, rhs_node)?;
             match op {
                 BinaryOp::Intersection => Ok(lhs.intersection(rhs)),
                 BinaryOp::Difference => Ok(lhs.difference(rhs)),
@@ -458,13 +616,11 @@ fn resolve_expression(
         ExpressionKind::UnionAll(nodes) => {
             let expressions = nodes
                 .iter()
-                .map(|node| resolve_expression(diagnostics, path_converter, node))
+                .map(|node| resolve_expression(diagnostics, context, node))
                 .try_collect()?;
             Ok(FilesetExpression::union_all(expressions))
         }
-        ExpressionKind::FunctionCall(function) => {
-            resolve_function(diagnostics, path_converter, function)
-        }
+        ExpressionKind::FunctionCall(function) => resolve_function(diagnostics, context, function),
     }
 }
 
@@ -472,11 +628,11 @@ fn resolve_expression(
 pub fn parse(
     diagnostics: &mut FilesetDiagnostics,
     text: &str,
-    path_converter: &RepoPathUiConverter,
+    context: &FilesetParseContext,
 ) -> FilesetParseResult<FilesetExpression> {
     let node = fileset_parser::parse_program(text)?;
     // TODO: add basic tree substitution pass to eliminate redundant expressions
-    resolve_expression(diagnostics, path_converter, &node)
+    resolve_expression(diagnostics, context, &node)
 }
 
 /// Parses text into `FilesetExpression` with bare string fallback.
@@ -486,11 +642,11 @@ pub fn parse(
 pub fn parse_maybe_bare(
     diagnostics: &mut FilesetDiagnostics,
     text: &str,
-    path_converter: &RepoPathUiConverter,
+    context: &FilesetParseContext,
 ) -> FilesetParseResult<FilesetExpression> {
     let node = fileset_parser::parse_program_or_bare_string(text)?;
     // TODO: add basic tree substitution pass to eliminate redundant expressions
-    resolve_expression(diagnostics, path_converter, &node)
+    resolve_expression(diagnostics, context, &node)
 }
 
 #[cfg(test)]
@@ -529,7 +685,8 @@ mod tests {
             cwd: PathBuf::from("/ws/cur"),
             base: PathBuf::from("/ws"),
         };
-        let parse = |text| parse_maybe_bare(&mut FilesetDiagnostics::new(), text, &path_converter);
+        let context = FilesetParseContext::new(&path_converter);
+        let parse = |text| parse_maybe_bare(&mut FilesetDiagnostics::new(), text, &context);
 
         // cwd-relative patterns
         insta::assert_debug_snapshot!(
@@ -574,7 +731,8 @@ mod tests {
             cwd: PathBuf::from("/ws/cur*"),
             base: PathBuf::from("/ws"),
         };
-        let parse = |text| parse_maybe_bare(&mut FilesetDiagnostics::new(), text, &path_converter);
+        let context = FilesetParseContext::new(&path_converter);
+        let parse = |text| parse_maybe_bare(&mut FilesetDiagnostics::new(), text, &context);
 
         // cwd-relative, without meta characters
         insta::assert_debug_snapshot!(
@@ -747,7 +905,8 @@ mod tests {
             cwd: PathBuf::from("/ws/cur"),
             base: PathBuf::from("/ws"),
         };
-        let parse = |text| parse_maybe_bare(&mut FilesetDiagnostics::new(), text, &path_converter);
+        let context = FilesetParseContext::new(&path_converter);
+        let parse = |text| parse_maybe_bare(&mut FilesetDiagnostics::new(), text, &context);
 
         insta::assert_debug_snapshot!(parse("all()").unwrap(), @"All");
         insta::assert_debug_snapshot!(parse("none()").unwrap(), @"None");
@@ -767,6 +926,144 @@ mod tests {
         "#);
     }
 
+    #[test]
+    fn test_parse_workspace_sparse_function() {
+        let settings = insta_settings();
+        let _guard = settings.bind_to_scope();
+        let path_converter = RepoPathUiConverter::Fs {
+            cwd: PathBuf::from("/ws/cur"),
+            base: PathBuf::from("/ws"),
+        };
+        let workspace_sparse_patterns = HashMap::from([(
+            WorkspaceId::new("second".to_owned()),
+            vec![repo_path_buf("foo"), repo_path_buf("bar/baz")],
+        )]);
+        let context = FilesetParseContext {
+            path_converter: &path_converter,
+            workspace_sparse_patterns: Some(&workspace_sparse_patterns),
+        };
+        let parse = |text| parse_maybe_bare(&mut FilesetDiagnostics::new(), text, &context);
+
+        insta::assert_debug_snapshot!(parse("workspace_sparse(second)").unwrap(), @r#"
+        UnionAll(
+            [
+                Pattern(PrefixPath("foo")),
+                Pattern(PrefixPath("bar/baz")),
+            ],
+        )
+        "#);
+        insta::assert_debug_snapshot!(parse("workspace_sparse('unknown')").unwrap(), @r#"Pattern(PrefixPath(""))"#);
+        insta::assert_debug_snapshot!(parse("workspace_sparse(all())").unwrap_err().kind(), @r#"Expression("Expected workspace name")"#);
+
+        let context = FilesetParseContext::new(&path_converter);
+        insta::assert_debug_snapshot!(
+            parse_maybe_bare(&mut FilesetDiagnostics::new(), "workspace_sparse(second)", &context)
+                .unwrap_err()
+                .kind(),
+            @r#"
+            Expression("Workspaces aren't available in this context")
+            "#);
+    }
+
+    #[test]
+    fn test_parse_glob_i_pattern() {
+        let settings = insta_settings();
+        let _guard = settings.bind_to_scope();
+        let path_converter = RepoPathUiConverter::Fs {
+            cwd: PathBuf::from("/ws/cur"),
+            base: PathBuf::from("/ws"),
+        };
+        let context = FilesetParseContext::new(&path_converter);
+        let parse = |text| parse_maybe_bare(&mut FilesetDiagnostics::new(), text, &context);
+
+        // The directory part is also matched case-insensitively
+        insta::assert_debug_snapshot!(
+            parse(r#"glob-i:"foo""#).unwrap(), @r#"
+        Pattern(
+            FileGlobI(
+                Pattern {
+                    original: "cur/foo",
+                    tokens: _,
+                    is_recursive: false,
+                },
+            ),
+        )
+        "#);
+        insta::assert_debug_snapshot!(
+            parse(r#"cwd-glob-i:"*.MD""#).unwrap(), @r#"
+        Pattern(
+            FileGlobI(
+                Pattern {
+                    original: "cur/*.MD",
+                    tokens: _,
+                    is_recursive: false,
+                },
+            ),
+        )
+        "#);
+        insta::assert_debug_snapshot!(
+            parse(r#"glob-i:"../*.MD""#).unwrap(), @r#"
+        Pattern(
+            FileGlobI(
+                Pattern {
+                    original: "*.MD",
+                    tokens: _,
+                    is_recursive: false,
+                },
+            ),
+        )
+        "#);
+        insta::assert_debug_snapshot!(
+            parse(r#"glob-i:"..""#).unwrap(), @r#"
+        Pattern(FilePath(""))
+        "#);
+        insta::assert_debug_snapshot!(
+            parse(r#"root-glob-i:"Docs/**/*.MD""#).unwrap(), @r#"
+        Pattern(
+            FileGlobI(
+                Pattern {
+                    original: "Docs/**/*.MD",
+                    tokens: _,
+                    is_recursive: true,
+                },
+            ),
+        )
+        "#);
+
+        // Conversion of case-sensitive globs
+        insta::assert_debug_snapshot!(
+            parse(r#"glob:"foo/*.md" | ~root-glob:"*.rs" | "bar""#)
+                .unwrap()
+                .to_case_insensitive_globs(), @r#"
+        UnionAll(
+            [
+                Pattern(
+                    FileGlobI(
+                        Pattern {
+                            original: "cur/foo/*.md",
+                            tokens: _,
+                            is_recursive: false,
+                        },
+                    ),
+                ),
+                Difference(
+                    All,
+                    Pattern(
+                        FileGlobI(
+                            Pattern {
+                                original: "*.rs",
+                                tokens: _,
+                                is_recursive: false,
+                            },
+                        ),
+                    ),
+                ),
+                Pattern(PrefixPath("cur/bar")),
+            ],
+        )
+        "#);
+    }
+
     #[test]
     fn test_parse_compound_expression() {
         let settings = insta_settings();
@@ -775,7 +1072,8 @@ mod tests {
             cwd: PathBuf::from("/ws/cur"),
             base: PathBuf::from("/ws"),
         };
-        let parse = |text| parse_maybe_bare(&mut FilesetDiagnostics::new(), text, &path_converter);
+        let context = FilesetParseContext::new(&path_converter);
+        let parse = |text| parse_maybe_bare(&mut FilesetDiagnostics::new(), text, &context);
 
         insta::assert_debug_snapshot!(parse("~x").unwrap(), @r#"
         Difference(
@@ -885,6 +1183,7 @@ mod tests {
                     is_recursive: false,
                 },
             ] {},
+            case_sensitive: true,
         }
         "#);
 
@@ -909,6 +1208,7 @@ mod tests {
                     ] {},
                 },
             },
+            case_sensitive: true,
         }
         "#);
     }
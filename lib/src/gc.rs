@@ -0,0 +1,87 @@
+// Copyright 2025 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Repo-level garbage collection.
+//!
+//! Garbage collection can run concurrently with other processes writing to
+//! the repo. To not delete objects that become reachable in the meantime, the
+//! backend first records the deletion candidates determined by the current op
+//! heads. The op heads are then loaded again, and only the candidates that are
+//! still unreachable from them are deleted.
+
+use std::path::Path;
+use std::slice;
+use std::time::SystemTime;
+
+use thiserror::Error;
+
+use crate::backend::BackendError;
+use crate::lock::FileLock;
+use crate::lock::FileLockError;
+use crate::op_store::OpStoreError;
+use crate::repo::Repo as _;
+use crate::repo::RepoLoader;
+use crate::repo::RepoLoaderError;
+
+/// Name of the lock file held while collecting garbage.
+pub const GC_LOCK_FILE_NAME: &str = "gc_lock";
+
+/// Error that may occur during garbage collection.
+#[derive(Debug, Error)]
+pub enum GcError {
+    /// Failed to acquire the GC lock.
+    #[error("Failed to lock the repository for garbage collection")]
+    Lock(#[source] FileLockError),
+    /// Failed to load the repo at the op heads.
+    #[error(transparent)]
+    RepoLoader(#[from] RepoLoaderError),
+    /// Failed to collect garbage in the operation store.
+    #[error(transparent)]
+    OpStore(#[from] OpStoreError),
+    /// Failed to collect garbage in the commit backend.
+    #[error(transparent)]
+    Backend(#[from] BackendError),
+}
+
+/// Garbage-collects the operations and commits of the repo at `repo_path`.
+///
+/// Operations and objects which are unreachable from the op heads and older
+/// than `keep_newer` are deleted. Only one process can collect garbage at a
+/// time. Other processes waiting for the GC lock will run after the current
+/// one has finished.
+pub fn gc_repo(
+    repo_path: &Path,
+    repo_loader: &RepoLoader,
+    keep_newer: SystemTime,
+) -> Result<(), GcError> {
+    let _lock = FileLock::lock(repo_path.join(GC_LOCK_FILE_NAME)).map_err(GcError::Lock)?;
+    let repo = repo_loader.load_at_head()?;
+    repo.store().prepare_gc(repo.index(), keep_newer)?;
+    repo.op_store()
+        .gc(slice::from_ref(repo.op_id()), keep_newer)?;
+    // Concurrent operations may have made some of the deletion candidates
+    // reachable again.
+    let fresh_repo = repo_loader.load_at_head()?;
+    if fresh_repo.op_id() != repo.op_id() {
+        tracing::info!(
+            old_op_id = ?repo.op_id(),
+            new_op_id = ?fresh_repo.op_id(),
+            "op heads changed during gc"
+        );
+    }
+    fresh_repo
+        .store()
+        .finish_gc(fresh_repo.index(), keep_newer)?;
+    Ok(())
+}
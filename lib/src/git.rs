@@ -14,34 +14,71 @@
 
 #![allow(missing_docs)]
 
+use std::borrow::Borrow;
 use std::borrow::Cow;
+use std::cell::RefCell;
+use std::collections::hash_map;
 use std::collections::BTreeMap;
+use std::collections::BTreeSet;
 use std::collections::HashMap;
 use std::collections::HashSet;
 use std::default::Default;
 use std::fmt;
+use std::fs;
 use std::fs::File;
+use std::io;
 use std::io::Read;
+use std::io::Write as _;
 use std::num::NonZeroU32;
+use std::ops::Bound;
+use std::path::Path;
 use std::path::PathBuf;
+use std::rc::Rc;
+use std::slice;
 use std::str;
+use std::sync::Arc;
 
+use blake2::Blake2b512;
+use blake2::Digest as _;
 use bstr::BStr;
 use bstr::BString;
+use futures::StreamExt as _;
 use itertools::Itertools;
+use pollster::FutureExt as _;
 use tempfile::NamedTempFile;
 use thiserror::Error;
 
+use crate::backend;
 use crate::backend::BackendError;
 use crate::backend::BackendResult;
+use crate::backend::ChangeId;
 use crate::backend::CommitId;
+use crate::backend::MergedTreeId;
+use crate::backend::MillisSinceEpoch;
+use crate::backend::Signature;
+use crate::backend::Timestamp;
+use crate::backend::TreeId;
 use crate::backend::TreeValue;
 use crate::commit::Commit;
+use crate::default_index::AsCompositeIndex as _;
+use crate::default_index::DefaultIndexStore;
+use crate::diff::Diff;
+use crate::diff::DiffHunkKind;
+use crate::file_util::IoResultExt as _;
+use crate::file_util::PathError;
 use crate::git_backend::GitBackend;
+#[cfg(feature = "git-native-http")]
+use crate::git_http;
+use crate::git_subprocess::GitCredential;
+use crate::git_subprocess::GitCredentialAction;
+use crate::git_subprocess::GitPushOutput;
 use crate::git_subprocess::GitSubprocessContext;
 use crate::git_subprocess::GitSubprocessError;
 use crate::index::Index;
+use crate::matchers::EverythingMatcher;
+use crate::merge::MergedTreeValue;
 use crate::merged_tree::MergedTree;
+use crate::merged_tree::TreeDiffEntry;
 use crate::object_id::ObjectId;
 use crate::op_store::RefTarget;
 use crate::op_store::RefTargetOptionExt;
@@ -52,9 +89,14 @@ use crate::refs::BookmarkPushUpdate;
 use crate::refs::RemoteRefSymbol;
 use crate::refs::RemoteRefSymbolBuf;
 use crate::repo::MutableRepo;
+use crate::repo::ReadonlyRepo;
 use crate::repo::Repo;
+use crate::repo::RepoLoaderError;
 use crate::repo_path::RepoPath;
+use crate::revset::ResolvedRevsetExpression;
 use crate::revset::RevsetExpression;
+use crate::settings::GitFetchTags;
+use crate::settings::GitHttpSettings;
 use crate::settings::GitSettings;
 use crate::store::Store;
 use crate::str_util::StringPattern;
@@ -227,6 +269,25 @@ fn to_git_ref_name(parsed_ref: &RefName) -> Option<String> {
 #[error("The repo is not backed by a Git repo")]
 pub struct UnexpectedGitBackendError;
 
+/// Git repository uses a ref storage format other than loose and packed refs.
+///
+/// Refs in reftable storage can't be read or updated through `gix` yet, so
+/// importing or exporting them would silently see stale or no refs.
+#[derive(Debug, Error)]
+#[error("Git ref storage format `{0}` is not supported")]
+pub struct UnsupportedRefStorageError(pub String);
+
+/// Checks that refs in `git_repo` are stored in the "files" format.
+fn check_ref_storage(git_repo: &gix::Repository) -> Result<(), UnsupportedRefStorageError> {
+    let config = git_repo.config_snapshot();
+    match config.string("extensions.refStorage") {
+        Some(format) if !format.eq_ignore_ascii_case(b"files") => {
+            Err(UnsupportedRefStorageError(format.to_string()))
+        }
+        _ => Ok(()),
+    }
+}
+
 /// Returns the underlying `GitBackend` implementation.
 pub fn get_git_backend(store: &Store) -> Result<&GitBackend, UnexpectedGitBackendError> {
     store
@@ -311,6 +372,8 @@ pub enum GitImportError {
     InternalGitError(#[source] Box<dyn std::error::Error + Send + Sync>),
     #[error(transparent)]
     UnexpectedBackend(#[from] UnexpectedGitBackendError),
+    #[error(transparent)]
+    UnsupportedRefStorage(#[from] UnsupportedRefStorageError),
 }
 
 impl GitImportError {
@@ -368,12 +431,21 @@ pub fn import_some_refs(
     let store = mut_repo.store();
     let git_backend = get_git_backend(store)?;
     let git_repo = git_backend.git_repo();
+    check_ref_storage(&git_repo)?;
 
     let RefsToImport {
         changed_git_refs,
         changed_remote_refs,
         failed_ref_names,
-    } = diff_refs_to_import(mut_repo.view(), &git_repo, git_ref_filter)?;
+    } = diff_refs_to_import(
+        mut_repo.view(),
+        &git_repo,
+        git_ref_filter,
+        git_settings.fetch_ref_filter.as_deref(),
+    )?;
+    let externally_kept_ids = git_backend
+        .externally_kept_commit_ids()
+        .map_err(GitImportError::InternalBackend)?;
 
     // Bulk-import all reachable Git commits to the backend to reduce overhead
     // of table merging and ref updates.
@@ -386,7 +458,32 @@ pub fn import_some_refs(
         .iter()
         .flat_map(|(_, new_target)| new_target.added_ids())
         .filter(|&id| !index.has_id(id));
-    let heads_imported = git_backend.import_head_commits(missing_head_ids).is_ok();
+    let heads_imported = if git_settings.detect_external_rewrites {
+        // Only local branches are rewritten by the user. Commits rewritten in
+        // remotes are imported as new changes.
+        let old_head_ids = changed_remote_refs
+            .iter()
+            .filter(|(ref_name, _)| matches!(ref_name, RefName::LocalBranch(_)))
+            .flat_map(|(_, (old_remote_ref, _))| old_remote_ref.target.added_ids())
+            .cloned()
+            .collect_vec();
+        let new_head_ids = changed_remote_refs
+            .iter()
+            .filter(|(ref_name, _)| matches!(ref_name, RefName::LocalBranch(_)))
+            .flat_map(|(_, (_, new_target))| new_target.added_ids())
+            .cloned()
+            .collect_vec();
+        let mut candidates =
+            find_external_rewrite_candidates(mut_repo, git_backend, old_head_ids, &new_head_ids)
+                .map_err(GitImportError::InternalBackend)?;
+        git_backend
+            .import_head_commits_with_rewrites(missing_head_ids, |id, commit| {
+                candidates.take_matching(id, commit)
+            })
+            .is_ok()
+    } else {
+        git_backend.import_head_commits(missing_head_ids).is_ok()
+    };
 
     // Import new remote heads
     let mut head_commits = Vec::new();
@@ -457,8 +554,13 @@ pub fn import_some_refs(
     }
 
     let abandoned_commits = if git_settings.abandon_unreachable_commits {
-        abandon_unreachable_commits(mut_repo, &changed_remote_refs)
-            .map_err(GitImportError::InternalBackend)?
+        abandon_unreachable_commits(
+            mut_repo,
+            git_settings,
+            &changed_remote_refs,
+            externally_kept_ids,
+        )
+        .map_err(GitImportError::InternalBackend)?
     } else {
         vec![]
     };
@@ -471,10 +573,16 @@ pub fn import_some_refs(
 }
 
 /// Finds commits that used to be reachable in git that no longer are reachable.
-/// Those commits will be recorded as abandoned in the `MutableRepo`.
+/// Those commits will be recorded as abandoned in the `MutableRepo`, or as
+/// rewritten if external rewrites are detected.
+///
+/// Ancestors of `externally_kept_ids`, the targets of `refs/jj/keep/<name>/`
+/// refs created by external tools, are never abandoned.
 fn abandon_unreachable_commits(
     mut_repo: &mut MutableRepo,
+    git_settings: &GitSettings,
     changed_remote_refs: &BTreeMap<RefName, (RemoteRef, RefTarget)>,
+    externally_kept_ids: Vec<CommitId>,
 ) -> BackendResult<Vec<CommitId>> {
     let hidable_git_heads = changed_remote_refs
         .values()
@@ -490,6 +598,12 @@ fn abandon_unreachable_commits(
         RevsetExpression::commits(remotely_pinned_commit_ids(mut_repo.view()))
             // Hidden remote branches should not contribute to pinning
             .intersection(&RevsetExpression::visible_heads().ancestors()),
+        RevsetExpression::commits(
+            externally_kept_ids
+                .into_iter()
+                .filter(|id| mut_repo.index().has_id(id))
+                .collect(),
+        ),
         RevsetExpression::root(),
     ]);
     let abandoned_expression = pinned_expression
@@ -504,16 +618,287 @@ fn abandon_unreachable_commits(
         .map_err(|err| err.expect_backend_error())?;
     for id in &abandoned_commit_ids {
         let commit = mut_repo.store().get_commit(id)?;
-        mut_repo.record_abandoned_commit(&commit);
+        let successor_id = if git_settings.detect_external_rewrites {
+            find_external_rewrite_successor(mut_repo, &commit)?
+        } else {
+            None
+        };
+        if let Some(successor_id) = successor_id {
+            mut_repo.set_rewritten_commit(id.clone(), successor_id);
+        } else {
+            mut_repo.record_abandoned_commit(&commit);
+        }
     }
     Ok(abandoned_commit_ids)
 }
 
+/// Local commits that may have been rewritten by external Git commands.
+///
+/// Git commands like `git commit --amend` and `git rebase` preserve the author
+/// of the rewritten commits, so a new commit is matched with the old commit
+/// that has the same author name, email, and timestamp. If the author doesn't
+/// match exactly one old commit, the new commit is matched by the author name
+/// and email and the patch id, which stays the same when a commit is rebased.
+/// Commits without changes have no patch id.
+///
+/// Old commits that are still reachable from the new heads weren't rewritten
+/// but copied, e.g. by `git cherry-pick`, so they aren't candidates.
+#[derive(Debug, Default)]
+struct ExternalRewriteCandidates {
+    by_author: HashMap<AuthorKey, Option<(ChangeId, CommitId)>>,
+    by_patch_id: HashMap<(String, String, PatchId), Option<(ChangeId, CommitId)>>,
+    new_patch_ids: HashMap<CommitId, PatchId>,
+    matched_ids: HashSet<CommitId>,
+}
+
+/// Author name, email, timestamp, and time zone offset.
+type AuthorKey = (String, String, i64, i32);
+
+/// Hash of the lines changed by a commit, see [`patch_id()`].
+type PatchId = Vec<u8>;
+
+impl ExternalRewriteCandidates {
+    fn author_key(author: &Signature) -> AuthorKey {
+        (
+            author.name.clone(),
+            author.email.clone(),
+            author.timestamp.timestamp.0,
+            author.timestamp.tz_offset,
+        )
+    }
+
+    /// Returns the change id and commit id of the old commit that `commit` was
+    /// rewritten from. Each old commit is matched at most once.
+    fn take_matching(
+        &mut self,
+        id: &CommitId,
+        commit: &backend::Commit,
+    ) -> Option<(ChangeId, CommitId)> {
+        let by_author = self
+            .by_author
+            .get(&Self::author_key(&commit.author))
+            .cloned()
+            .flatten();
+        let (change_id, old_id) = by_author.or_else(|| {
+            let patch_id = self.new_patch_ids.get(id)?.clone();
+            let key = (
+                commit.author.name.clone(),
+                commit.author.email.clone(),
+                patch_id,
+            );
+            self.by_patch_id.get(&key).cloned().flatten()
+        })?;
+        self.matched_ids
+            .insert(old_id.clone())
+            .then_some((change_id, old_id))
+    }
+}
+
+/// Collects the visible local commits that were reachable from the
+/// `old_head_ids` but haven't been pushed to any remote, and aren't reachable
+/// from the `new_head_ids`.
+fn find_external_rewrite_candidates(
+    repo: &dyn Repo,
+    git_backend: &GitBackend,
+    old_head_ids: Vec<CommitId>,
+    new_head_ids: &[CommitId],
+) -> BackendResult<ExternalRewriteCandidates> {
+    let mut candidates = ExternalRewriteCandidates::default();
+    if old_head_ids.is_empty() {
+        return Ok(candidates);
+    }
+    // Find the commits to be imported, and the already imported commits they
+    // descend from.
+    let mut new_commits = HashMap::new();
+    let mut reachable_ids = vec![];
+    let mut pending_ids = new_head_ids.to_vec();
+    while let Some(id) = pending_ids.pop() {
+        if repo.index().has_id(&id) {
+            reachable_ids.push(id);
+        } else if let hash_map::Entry::Vacant(entry) = new_commits.entry(id) {
+            let (tree_id, parent_ids) = match git_backend.read_git_tree_and_parents(entry.key()) {
+                Ok(tree_and_parents) => tree_and_parents,
+                // Parents of shallow commits are missing.
+                Err(BackendError::ObjectNotFound { .. }) => continue,
+                Err(err) => return Err(err),
+            };
+            pending_ids.extend(parent_ids.iter().cloned());
+            entry.insert((tree_id, parent_ids.into_iter().next()));
+        }
+    }
+
+    let pushed_ids = repo
+        .view()
+        .all_remote_bookmarks()
+        .filter(|(symbol, _)| symbol.remote != REMOTE_NAME_FOR_LOCAL_GIT_REPO)
+        .flat_map(|(_, remote_ref)| remote_ref.target.added_ids())
+        .cloned()
+        .collect_vec();
+    let expression = RevsetExpression::commits(pushed_ids)
+        .union(&RevsetExpression::root())
+        .union(&RevsetExpression::commits(reachable_ids))
+        .range(&RevsetExpression::commits(old_head_ids))
+        .intersection(&RevsetExpression::visible_heads().ancestors());
+    let commit_ids: Vec<_> = expression
+        .evaluate(repo)
+        .map_err(|err| err.expect_backend_error())?
+        .iter()
+        .try_collect()
+        .map_err(|err| err.expect_backend_error())?;
+    if commit_ids.is_empty() {
+        return Ok(candidates);
+    }
+    let git_tree_id = |id: &CommitId| -> BackendResult<TreeId> {
+        match new_commits.get(id) {
+            Some((tree_id, _)) => Ok(tree_id.clone()),
+            None => Ok(git_backend.read_git_tree_and_parents(id)?.0),
+        }
+    };
+    let git_patch_id = |id: &CommitId| -> BackendResult<Option<PatchId>> {
+        let (tree_id, parent_ids) = git_backend.read_git_tree_and_parents(id)?;
+        let parent_tree_id = match parent_ids.first() {
+            Some(parent_id) => git_tree_id(parent_id)?,
+            None => repo.store().empty_tree_id().clone(),
+        };
+        patch_id(repo.store(), &parent_tree_id, &tree_id)
+    };
+    for id in commit_ids {
+        let commit = repo.store().get_commit(&id)?;
+        let key = ExternalRewriteCandidates::author_key(commit.author());
+        let patch_id = git_patch_id(&id)?;
+        let candidate = Some((commit.change_id().clone(), id));
+        candidates
+            .by_author
+            .entry(key)
+            .and_modify(|entry| *entry = None) // ambiguous
+            .or_insert_with(|| candidate.clone());
+        if let Some(patch_id) = patch_id {
+            let author = commit.author();
+            candidates
+                .by_patch_id
+                .entry((author.name.clone(), author.email.clone(), patch_id))
+                .and_modify(|entry| *entry = None)
+                .or_insert(candidate);
+        }
+    }
+    for (id, (tree_id, parent_id)) in &new_commits {
+        let parent_tree_id = match parent_id {
+            Some(parent_id) => git_tree_id(parent_id)?,
+            None => repo.store().empty_tree_id().clone(),
+        };
+        if let Some(patch_id) = patch_id(repo.store(), &parent_tree_id, tree_id)? {
+            candidates.new_patch_ids.insert(id.clone(), patch_id);
+        }
+    }
+    Ok(candidates)
+}
+
+/// Computes a hash of the lines changed between the trees, similar to
+/// `git patch-id`. Unchanged lines and line numbers aren't included, so the
+/// hash stays the same when a commit is rebased without conflicts. Returns
+/// `None` if the trees are the same.
+fn patch_id(
+    store: &Arc<Store>,
+    from_id: &TreeId,
+    to_id: &TreeId,
+) -> BackendResult<Option<PatchId>> {
+    if from_id == to_id {
+        return Ok(None);
+    }
+    let from_tree = store.get_root_tree(&MergedTreeId::Legacy(from_id.clone()))?;
+    let to_tree = store.get_root_tree(&MergedTreeId::Legacy(to_id.clone()))?;
+    let read_contents = |path: &RepoPath, value: &MergedTreeValue| -> BackendResult<Vec<u8>> {
+        match value.as_resolved() {
+            Some(None) => Ok(vec![]),
+            Some(Some(TreeValue::File { id, .. })) => {
+                let mut contents = vec![];
+                store
+                    .read_file(path, id)?
+                    .read_to_end(&mut contents)
+                    .map_err(|err| BackendError::ReadFile {
+                        path: path.to_owned(),
+                        id: id.clone(),
+                        source: err.into(),
+                    })?;
+                Ok(contents)
+            }
+            _ => Ok(format!("{value:?}").into_bytes()),
+        }
+    };
+    let mut hasher = Blake2b512::new();
+    let mut diff_stream = from_tree.diff_stream(&to_tree, &EverythingMatcher);
+    async {
+        while let Some(TreeDiffEntry { path, values }) = diff_stream.next().await {
+            let (before, after) = values?;
+            let before = read_contents(&path, &before)?;
+            let after = read_contents(&path, &after)?;
+            hasher.update(path.as_internal_file_string());
+            hasher.update([0]);
+            for hunk in Diff::by_line([&before, &after]).hunks() {
+                if hunk.kind == DiffHunkKind::Different {
+                    for side in &hunk.contents {
+                        hasher.update(side);
+                        hasher.update([0]);
+                    }
+                }
+            }
+        }
+        Ok::<_, BackendError>(())
+    }
+    .block_on()?;
+    Ok(Some(hasher.finalize().to_vec()))
+}
+
+/// Returns the visible commit that was imported as a rewrite of the `commit`.
+fn find_external_rewrite_successor(
+    repo: &dyn Repo,
+    commit: &Commit,
+) -> BackendResult<Option<CommitId>> {
+    let Some(ids) = repo.resolve_change_id(commit.change_id()) else {
+        return Ok(None);
+    };
+    let mut successor_ids = vec![];
+    for id in ids.iter().filter(|&id| id != commit.id()) {
+        let successor = repo.store().get_commit(id)?;
+        if successor.predecessor_ids() == [commit.id().clone()] {
+            successor_ids.push(id.clone());
+        }
+    }
+    Ok(successor_ids.into_iter().exactly_one().ok())
+}
+
 /// Calculates diff of git refs to be imported.
+/// Imports the remote bookmarks matching the given patterns, including the
+/// ones excluded by the `git.fetch-ref-filter` setting.
+///
+/// Once imported, the remote bookmarks are kept up to date by the subsequent
+/// imports.
+pub fn import_remote_bookmarks(
+    mut_repo: &mut MutableRepo,
+    git_settings: &GitSettings,
+    remote_pattern: &StringPattern,
+    bookmark_patterns: &[StringPattern],
+) -> Result<GitImportStats, GitImportError> {
+    let git_settings = GitSettings {
+        fetch_ref_filter: None,
+        ..git_settings.clone()
+    };
+    import_some_refs(mut_repo, &git_settings, |ref_name| match ref_name {
+        RefName::RemoteBranch(symbol) => {
+            remote_pattern.matches(&symbol.remote)
+                && bookmark_patterns
+                    .iter()
+                    .any(|pattern| pattern.matches(&symbol.name))
+        }
+        RefName::LocalBranch(_) | RefName::Tag(_) => false,
+    })
+}
+
 fn diff_refs_to_import(
     view: &View,
     git_repo: &gix::Repository,
     git_ref_filter: impl Fn(&RefName) -> bool,
+    remote_bookmark_filter: Option<&[StringPattern]>,
 ) -> Result<RefsToImport, GitImportError> {
     let mut known_git_refs: HashMap<&str, &RefTarget> = view
         .git_refs()
@@ -578,6 +963,16 @@ fn diff_refs_to_import(
         if !git_ref_filter(&ref_name) {
             continue;
         }
+        // Remote bookmarks excluded by the filter aren't imported unless they
+        // have already been imported.
+        if let (RefName::RemoteBranch(symbol), Some(patterns)) = (&ref_name, remote_bookmark_filter)
+        {
+            if !known_remote_refs.contains_key(&ref_name)
+                && !patterns.iter().any(|pattern| pattern.matches(&symbol.name))
+            {
+                continue;
+            }
+        }
         let old_git_target = known_git_refs.get(full_name).copied().flatten();
         let Some(id) = resolve_git_ref_to_commit_id(&git_ref, old_git_target) else {
             // Skip (or remove existing) invalid refs.
@@ -668,14 +1063,21 @@ fn remotely_pinned_commit_ids(view: &View) -> Vec<CommitId> {
 /// Imports HEAD from the underlying Git repo.
 ///
 /// Unlike `import_refs()`, the old HEAD branch is not abandoned because HEAD
-/// move doesn't always mean the old HEAD branch has been rewritten.
+/// move doesn't always mean the old HEAD branch has been rewritten. However, if
+/// `git_settings.detect_external_rewrites` is enabled, commits detected as
+/// rewritten by Git are recorded as such. The caller should rebase descendants
+/// afterwards.
 ///
 /// Unlike `reset_head()`, this function doesn't move the working-copy commit to
 /// the child of the new HEAD revision.
-pub fn import_head(mut_repo: &mut MutableRepo) -> Result<(), GitImportError> {
+pub fn import_head(
+    mut_repo: &mut MutableRepo,
+    git_settings: &GitSettings,
+) -> Result<(), GitImportError> {
     let store = mut_repo.store();
     let git_backend = get_git_backend(store)?;
     let git_repo = git_backend.git_repo();
+    check_ref_storage(&git_repo)?;
 
     let old_git_head = mut_repo.view().git_head();
     let new_git_head_id = if let Ok(oid) = git_repo.head_id() {
@@ -688,14 +1090,30 @@ pub fn import_head(mut_repo: &mut MutableRepo) -> Result<(), GitImportError> {
     }
 
     // Import new head
+    let mut rewrites = vec![];
     if let Some(head_id) = &new_git_head_id {
         let index = mut_repo.index();
         if !index.has_id(head_id) {
-            git_backend.import_head_commits([head_id]).map_err(|err| {
-                GitImportError::MissingHeadTarget {
-                    id: head_id.clone(),
-                    err,
-                }
+            let result = if git_settings.detect_external_rewrites {
+                let old_head_ids = old_git_head.added_ids().cloned().collect_vec();
+                let mut candidates = find_external_rewrite_candidates(
+                    mut_repo,
+                    git_backend,
+                    old_head_ids,
+                    slice::from_ref(head_id),
+                )
+                .map_err(GitImportError::InternalBackend)?;
+                git_backend.import_head_commits_with_rewrites([head_id], |id, commit| {
+                    let (change_id, old_id) = candidates.take_matching(id, commit)?;
+                    rewrites.push((old_id.clone(), id.clone()));
+                    Some((change_id, old_id))
+                })
+            } else {
+                git_backend.import_head_commits([head_id])
+            };
+            result.map_err(|err| GitImportError::MissingHeadTarget {
+                id: head_id.clone(),
+                err,
             })?;
         }
         // It's unlikely the imported commits were missing, but I/O-related
@@ -705,6 +1123,11 @@ pub fn import_head(mut_repo: &mut MutableRepo) -> Result<(), GitImportError> {
             .and_then(|commit| mut_repo.add_head(&commit))
             .map_err(GitImportError::InternalBackend)?;
     }
+    // Unlike the old HEAD branch, commits rewritten by Git are known to be
+    // obsolete.
+    for (old_id, new_id) in rewrites {
+        mut_repo.set_rewritten_commit(old_id, new_id);
+    }
 
     mut_repo.set_git_head_target(RefTarget::resolved(new_git_head_id));
     Ok(())
@@ -717,6 +1140,8 @@ pub enum GitExportError {
     #[error(transparent)]
     UnexpectedBackend(#[from] UnexpectedGitBackendError),
     #[error(transparent)]
+    UnsupportedRefStorage(#[from] UnsupportedRefStorageError),
+    #[error(transparent)]
     Backend(#[from] BackendError),
 }
 
@@ -761,6 +1186,10 @@ pub enum FailedRefExportReason {
     /// Failed to set the ref in the Git repo
     #[error("Failed to set")]
     FailedToSet(#[source] Box<gix::reference::edit::Error>),
+    /// The name looks like a parent directory of another ref, or the other way
+    /// around, which Git can't store.
+    #[error("Name conflicts with Git ref {0}")]
+    ConflictingName(String),
 }
 
 #[derive(Debug)]
@@ -790,6 +1219,8 @@ pub fn export_some_refs(
     git_ref_filter: impl Fn(&RefName) -> bool,
 ) -> Result<Vec<FailedRefExport>, GitExportError> {
     let git_repo = get_git_repo(mut_repo.store())?;
+    check_ref_storage(&git_repo)?;
+    let reflog_message = export_reflog_message(mut_repo);
 
     let RefsToExport {
         branches_to_update,
@@ -833,6 +1264,7 @@ pub fn export_some_refs(
                     &git_repo,
                     gix::refs::transaction::PreviousValue::MustExistAndMatch(old_target),
                     current_oid,
+                    &reflog_message,
                 )?;
             }
         }
@@ -854,7 +1286,9 @@ pub fn export_some_refs(
             failed_branches.insert(parsed_ref_name, FailedRefExportReason::InvalidGitName);
             continue;
         };
-        if let Err(reason) = update_git_ref(&git_repo, &git_ref_name, old_oid, new_oid) {
+        if let Err(reason) =
+            update_git_ref(&git_repo, &git_ref_name, old_oid, new_oid, &reflog_message)
+        {
             failed_branches.insert(parsed_ref_name, reason);
         } else {
             let new_target = RefTarget::normal(CommitId::from_bytes(new_oid.as_bytes()));
@@ -982,6 +1416,34 @@ fn diff_refs_to_export(
         }
     }
 
+    // Git refs may be stored as files, so "foo" and "foo/bar" can't coexist.
+    // Reject new refs conflicting with the others before updating anything.
+    let mut git_ref_names: BTreeSet<&str> =
+        view.git_refs().keys().map(|name| name.as_str()).collect();
+    let deleted_git_ref_names = branches_to_delete.keys().filter_map(to_git_ref_name);
+    for name in deleted_git_ref_names {
+        git_ref_names.remove(name.as_str());
+    }
+    let mut new_git_ref_names = BTreeSet::new();
+    branches_to_update.retain(|ref_name, (old_oid, _)| {
+        if old_oid.is_some() {
+            return true;
+        }
+        let Some(git_ref_name) = to_git_ref_name(ref_name) else {
+            return true;
+        };
+        let conflicting_name = find_conflicting_git_ref_name(&git_ref_names, &git_ref_name)
+            .or_else(|| find_conflicting_git_ref_name(&new_git_ref_names, &git_ref_name));
+        if let Some(conflicting_name) = conflicting_name {
+            let reason = FailedRefExportReason::ConflictingName(conflicting_name.to_owned());
+            failed_branches.insert(ref_name.clone(), reason);
+            false
+        } else {
+            new_git_ref_names.insert(git_ref_name);
+            true
+        }
+    });
+
     RefsToExport {
         branches_to_update,
         branches_to_delete,
@@ -989,6 +1451,26 @@ fn diff_refs_to_export(
     }
 }
 
+/// Finds a ref name which is a parent "directory" of `name`, or the other way
+/// around.
+fn find_conflicting_git_ref_name<'a>(
+    names: &'a BTreeSet<impl Borrow<str> + Ord>,
+    name: &str,
+) -> Option<&'a str> {
+    let parent_name = name
+        .match_indices('/')
+        .find_map(|(pos, _)| names.get(&name[..pos]));
+    if let Some(parent_name) = parent_name {
+        return Some(parent_name.borrow());
+    }
+    let prefix = format!("{name}/");
+    names
+        .range::<str, _>((Bound::Included(prefix.as_str()), Bound::Unbounded))
+        .next()
+        .map(|child_name| child_name.borrow())
+        .filter(|child_name| child_name.starts_with(&prefix))
+}
+
 fn delete_git_ref(
     git_repo: &gix::Repository,
     git_ref_name: &str,
@@ -1010,11 +1492,22 @@ fn delete_git_ref(
     Ok(())
 }
 
+/// Builds the reflog message for refs updated by jj.
+///
+/// The new operation doesn't exist yet while the transaction is in progress,
+/// so the message refers to the operation the changes are based on. This
+/// appears as the parent in `jj op log`.
+fn export_reflog_message(mut_repo: &MutableRepo) -> String {
+    let op_id = mut_repo.base_repo().op_id().hex();
+    format!("export from jj (parent operation {})", &op_id[..12])
+}
+
 fn update_git_ref(
     git_repo: &gix::Repository,
     git_ref_name: &str,
     old_oid: Option<gix::ObjectId>,
     new_oid: gix::ObjectId,
+    reflog_message: &str,
 ) -> Result<(), FailedRefExportReason> {
     match old_oid {
         None => {
@@ -1031,7 +1524,7 @@ fn update_git_ref(
                         git_ref_name,
                         new_oid,
                         gix::refs::transaction::PreviousValue::MustNotExist,
-                        "export from jj",
+                        reflog_message,
                     )
                     .map_err(|err| FailedRefExportReason::FailedToSet(err.into()))?;
             }
@@ -1042,7 +1535,7 @@ fn update_git_ref(
                 git_ref_name,
                 new_oid,
                 gix::refs::transaction::PreviousValue::MustExistAndMatch(old_oid.into()),
-                "export from jj",
+                reflog_message,
             ) {
                 // The reference was probably updated in git
                 if let Ok(git_repo_ref) = git_repo.find_reference(git_ref_name) {
@@ -1069,6 +1562,7 @@ fn update_git_head(
     git_repo: &gix::Repository,
     expected_ref: gix::refs::transaction::PreviousValue,
     new_oid: Option<gix::ObjectId>,
+    reflog_message: &str,
 ) -> Result<(), GitExportError> {
     let mut ref_edits = Vec::new();
     let new_target = if let Some(oid) = new_oid {
@@ -1091,7 +1585,7 @@ fn update_git_head(
     ref_edits.push(gix::refs::transaction::RefEdit {
         change: gix::refs::transaction::Change::Update {
             log: gix::refs::transaction::LogChange {
-                message: "export from jj".into(),
+                message: reflog_message.into(),
                 ..Default::default()
             },
             expected: expected_ref,
@@ -1110,6 +1604,7 @@ fn update_git_head(
 /// the Git index.
 pub fn reset_head(mut_repo: &mut MutableRepo, wc_commit: &Commit) -> Result<(), GitExportError> {
     let git_repo = get_git_repo(mut_repo.store())?;
+    check_ref_storage(&git_repo)?;
 
     let first_parent_id = &wc_commit.parent_ids()[0];
     let new_head_target = if first_parent_id != mut_repo.store().root_commit_id() {
@@ -1140,7 +1635,8 @@ pub fn reset_head(mut_repo: &mut MutableRepo, wc_commit: &Commit) -> Result<(),
         let new_oid = new_head_target
             .as_normal()
             .map(|id| gix::ObjectId::from_bytes_or_panic(id.as_bytes()));
-        update_git_head(&git_repo, expected_ref, new_oid)?;
+        let reflog_message = export_reflog_message(mut_repo);
+        update_git_head(&git_repo, expected_ref, new_oid, &reflog_message)?;
         mut_repo.set_git_head_target(new_head_target);
     }
 
@@ -1767,6 +2263,53 @@ pub fn set_remote_url(
     Ok(())
 }
 
+/// Configures the remote as the promisor remote of a partial clone.
+///
+/// Subsequent fetches from the remote omit the objects excluded by the
+/// `filter` (e.g. `blob:none`), which are then fetched from the remote on
+/// demand. The repository must be reloaded for the change to take effect.
+pub fn set_remote_partial_clone_filter(
+    store: &Store,
+    remote_name: &str,
+    filter: &str,
+) -> Result<(), GitRemoteManagementError> {
+    let git_repo = get_git_repo(store)?;
+
+    validate_remote_name(remote_name)?;
+
+    if git_repo
+        .try_find_remote_without_url_rewrite(remote_name)
+        .is_none()
+    {
+        return Err(GitRemoteManagementError::NoSuchRemote(
+            remote_name.to_owned(),
+        ));
+    }
+
+    let mut config = git_repo.config_snapshot().clone();
+    let meta = config.meta().clone();
+    let values: [(&str, Option<&str>, &str, &str); 4] = [
+        ("core", None, "repositoryFormatVersion", "1"),
+        ("extensions", None, "partialClone", remote_name),
+        ("remote", Some(remote_name), "promisor", "true"),
+        ("remote", Some(remote_name), "partialCloneFilter", filter),
+    ];
+    for (section_name, subsection_name, key, value) in values {
+        config
+            .set_raw_value_filter_by(
+                section_name,
+                subsection_name.map(|name| name.into()),
+                key,
+                value,
+                |section_meta| *section_meta == meta,
+            )
+            .map_err(GitRemoteManagementError::from_git)?;
+    }
+    save_git_config(&config).map_err(GitRemoteManagementError::GitConfigSaveError)?;
+
+    Ok(())
+}
+
 fn rename_remote_refs(mut_repo: &mut MutableRepo, old_remote_name: &str, new_remote_name: &str) {
     mut_repo.rename_remote(old_remote_name, new_remote_name);
     let prefix = format!("refs/remotes/{old_remote_name}/");
@@ -1808,6 +2351,30 @@ pub enum GitFetchError {
     InternalGitError(#[from] git2::Error),
     #[error(transparent)]
     Subprocess(#[from] GitSubprocessError),
+    #[error(transparent)]
+    NativeHttp(#[from] GitHttpError),
+    #[error("Shallow fetch depth can't be combined with a date or excluded refs")]
+    ConflictingShallowOptions,
+    #[error("Shallow fetch by date or excluded refs requires the Git subprocess")]
+    UnsupportedShallowOptions,
+}
+
+/// Error originating from the native HTTP transport, which is used if
+/// `git.native-http` is enabled.
+#[derive(Error, Debug)]
+pub enum GitHttpError {
+    #[error("Failed to connect to the remote")]
+    Connect(#[source] Box<dyn std::error::Error + Send + Sync>),
+    #[error("Failed to fetch from the remote")]
+    Fetch(#[source] Box<dyn std::error::Error + Send + Sync>),
+    #[error("Failed to push to the remote")]
+    Push(#[source] Box<dyn std::error::Error + Send + Sync>),
+    #[error("Unexpected response from the remote: {0}")]
+    Protocol(String),
+    #[error("Remote {0} doesn't have an HTTP(S) URL")]
+    NotHttpRemote(String),
+    #[error("{0} is not supported by the native HTTP transport")]
+    UnsupportedSetting(&'static str),
 }
 
 // TODO: If Git2 implementation is removed, this can be replaced with
@@ -1820,29 +2387,183 @@ pub enum GitFetchPrepareError {
     UnexpectedBackend(#[from] UnexpectedGitBackendError),
 }
 
-fn git2_fetch_options(
-    mut callbacks: RemoteCallbacks<'_>,
-    depth: Option<NonZeroU32>,
-) -> git2::FetchOptions<'_> {
+fn git2_proxy_options(http_settings: &GitHttpSettings) -> git2::ProxyOptions<'_> {
     let mut proxy_options = git2::ProxyOptions::new();
-    proxy_options.auto();
+    if let Some(proxy) = &http_settings.proxy {
+        proxy_options.url(proxy);
+    } else {
+        proxy_options.auto();
+    }
+    proxy_options
+}
 
+fn git2_fetch_options<'a, 'cb: 'a>(
+    mut callbacks: RemoteCallbacks<'cb>,
+    shallow: &GitShallowOptions,
+    http_settings: &'a GitHttpSettings,
+    credential_helper: &'a GitCredentialHelper,
+) -> git2::FetchOptions<'a> {
     let mut fetch_options = git2::FetchOptions::new();
-    fetch_options.proxy_options(proxy_options);
+    fetch_options.proxy_options(git2_proxy_options(http_settings));
     // git2 doesn't provide API to set "no-progress" protocol option. If
     // sideband callback were enabled, remote progress messages would be written
     // no matter if the process was attached to a tty or not.
     if callbacks.progress.is_none() {
         callbacks.sideband_progress = None;
     }
-    fetch_options.remote_callbacks(callbacks.into_git());
-    if let Some(depth) = depth {
+    fetch_options.remote_callbacks(callbacks.into_git(credential_helper));
+    if let Some(depth) = shallow.depth {
         fetch_options.depth(depth.get().try_into().unwrap_or(i32::MAX));
     }
 
     fetch_options
 }
 
+/// Times at which remote bookmarks were last fetched.
+///
+/// The times are recorded per fetched bookmark pattern, so bookmarks that
+/// didn't exist in the remote are also known to be up to date. This is stored
+/// in the repo directory instead of the operation log since it describes the
+/// state of the remotes, which isn't affected by undoing operations.
+#[derive(Clone, Debug, Default, serde::Deserialize, serde::Serialize)]
+pub struct GitFetchTimes {
+    /// Map of remote name to glob pattern to milliseconds since epoch.
+    remotes: BTreeMap<String, BTreeMap<String, i64>>,
+}
+
+impl GitFetchTimes {
+    const FILE_NAME: &str = "git_fetch_times.json";
+
+    /// Loads the fetch times stored in the `repo_path` directory.
+    pub fn load(repo_path: &Path) -> Result<Self, PathError> {
+        let path = repo_path.join(Self::FILE_NAME);
+        match fs::read(&path) {
+            Ok(data) => serde_json::from_slice(&data)
+                .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+                .context(&path),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(err) => Err(err).context(&path),
+        }
+    }
+
+    /// Saves the fetch times to the `repo_path` directory.
+    pub fn save(&self, repo_path: &Path) -> Result<(), PathError> {
+        let path = repo_path.join(Self::FILE_NAME);
+        let data = serde_json::to_vec(self).expect("fetch times should be serializable");
+        let mut temp_file = NamedTempFile::new_in(repo_path).context(repo_path)?;
+        temp_file.write_all(&data).context(temp_file.path())?;
+        temp_file
+            .persist(&path)
+            .map_err(|err| err.error)
+            .context(&path)?;
+        Ok(())
+    }
+
+    /// Records that the bookmarks matching the `patterns` were fetched from
+    /// the `remote` at `time`.
+    ///
+    /// Patterns that can't be expressed as globs aren't recorded.
+    pub fn record(&mut self, remote: &str, patterns: &[StringPattern], time: MillisSinceEpoch) {
+        let times = self.remotes.entry(remote.to_owned()).or_default();
+        for glob in patterns.iter().filter_map(|pattern| pattern.to_glob()) {
+            times.insert(glob.into_owned(), time.0);
+        }
+    }
+
+    /// Returns the last time the bookmark was fetched from the remote.
+    pub fn last_fetched(&self, symbol: RemoteRefSymbol<'_>) -> Option<MillisSinceEpoch> {
+        let times = self.remotes.get(symbol.remote)?;
+        times
+            .iter()
+            .filter(|(glob, _)| {
+                StringPattern::glob(glob).is_ok_and(|pattern| pattern.matches(symbol.name))
+            })
+            .map(|(_, &time)| MillisSinceEpoch(time))
+            .max()
+    }
+}
+
+/// Downloads the branches of the remote in the background.
+///
+/// The fetched branches are stored under `refs/prefetch/remotes/<remote>/`,
+/// so neither the remote-tracking branches in the Git repo nor the view are
+/// updated. A subsequent `git fetch` will only have to download the objects
+/// created after the prefetch.
+pub fn prefetch(
+    store: &Store,
+    git_settings: &GitSettings,
+    remote_name: &str,
+) -> Result<(), GitPrefetchError> {
+    let git_backend = get_git_backend(store)?;
+    if git_backend
+        .git_repo()
+        .try_find_remote(remote_name)
+        .is_none()
+    {
+        return Err(GitFetchError::NoSuchRemote(remote_name.to_owned()).into());
+    }
+    let git_ctx = GitSubprocessContext::from_git_backend(git_backend, git_settings);
+    let refspecs = [RefSpec::forced(
+        "refs/heads/*",
+        format!("refs/prefetch/remotes/{remote_name}/*"),
+    )];
+    git_ctx
+        .spawn_fetch(
+            remote_name,
+            &refspecs,
+            &mut RemoteCallbacks::default(),
+            &GitShallowOptions::default(),
+            git_settings.fetch_tags,
+        )
+        .map_err(GitFetchError::from)?;
+    Ok(())
+}
+
+#[derive(Debug, Error)]
+pub enum GitPrefetchError {
+    #[error(transparent)]
+    UnexpectedBackend(#[from] UnexpectedGitBackendError),
+    #[error(transparent)]
+    Fetch(#[from] GitFetchError),
+}
+
+/// Limits the history downloaded by [`GitFetch::fetch()`].
+///
+/// The default options don't change the shallow boundary of the repository.
+/// As with `git fetch`, `depth` can't be combined with the other options.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct GitShallowOptions {
+    /// Number of commits to fetch from the tip of each branch.
+    pub depth: Option<NonZeroU32>,
+    /// Only fetch commits newer than this time.
+    pub deepen_since: Option<Timestamp>,
+    /// Don't fetch commits reachable from these remote branches or tags.
+    pub deepen_not: Vec<String>,
+}
+
+impl GitShallowOptions {
+    /// Options to fetch the given number of commits from each branch.
+    pub fn with_depth(depth: NonZeroU32) -> Self {
+        GitShallowOptions {
+            depth: Some(depth),
+            ..Default::default()
+        }
+    }
+
+    /// Whether the options need the `deepen-since` or `deepen-not`
+    /// capability of the remote.
+    pub(crate) fn has_deepen_filters(&self) -> bool {
+        self.deepen_since.is_some() || !self.deepen_not.is_empty()
+    }
+
+    fn validate(&self) -> Result<(), GitFetchError> {
+        if self.depth.is_some() && self.has_deepen_filters() {
+            return Err(GitFetchError::ConflictingShallowOptions);
+        }
+        Ok(())
+    }
+}
+
 struct FetchedBranches {
     remote: String,
     branches: Vec<StringPattern>,
@@ -1875,17 +2596,27 @@ impl<'a> GitFetch<'a> {
     ///
     /// Keeps track of the {branch_names, remote_name} pair the refs can be
     /// subsequently imported into the `jj` repo by calling `import_refs()`.
+    ///
+    /// The history is truncated as specified by `shallow`. If the repository
+    /// becomes less shallow, [`deepen_shallow()`] should be called after the
+    /// changes are committed.
     #[tracing::instrument(skip(self, callbacks))]
     pub fn fetch(
         &mut self,
         remote_name: &str,
         branch_names: &[StringPattern],
         callbacks: RemoteCallbacks<'_>,
-        depth: Option<NonZeroU32>,
+        shallow: &GitShallowOptions,
     ) -> Result<(), GitFetchError> {
         validate_remote_name(remote_name)?;
-        self.fetch_impl
-            .fetch(remote_name, branch_names, callbacks, depth)?;
+        shallow.validate()?;
+        self.fetch_impl.fetch(
+            remote_name,
+            branch_names,
+            callbacks,
+            shallow,
+            self.git_settings.fetch_tags,
+        )?;
         self.fetched.push(FetchedBranches {
             remote: remote_name.to_string(),
             branches: branch_names.to_vec(),
@@ -1893,6 +2624,29 @@ impl<'a> GitFetch<'a> {
         Ok(())
     }
 
+    /// Fetches the history missing from a shallow repository.
+    ///
+    /// All branches of the remote are fetched with unlimited depth. The
+    /// fetched refs can be imported by calling `import_refs()` as with
+    /// `fetch()`. Since the previously shallow commits get their parents back,
+    /// the commit index has to be rebuilt by [`deepen_shallow()`] once the
+    /// changes are committed.
+    #[tracing::instrument(skip(self, callbacks))]
+    pub fn unshallow(
+        &mut self,
+        remote_name: &str,
+        callbacks: RemoteCallbacks<'_>,
+    ) -> Result<(), GitFetchError> {
+        // Both Git and libgit2 interpret this depth as "unlimited".
+        const INFINITE_DEPTH: NonZeroU32 = NonZeroU32::new(i32::MAX as u32).unwrap();
+        self.fetch(
+            remote_name,
+            &[StringPattern::everything()],
+            callbacks,
+            &GitShallowOptions::with_depth(INFINITE_DEPTH),
+        )
+    }
+
     /// Queries remote for the default branch name.
     #[tracing::instrument(skip(self, callbacks))]
     pub fn get_default_branch(
@@ -1905,7 +2659,7 @@ impl<'a> GitFetch<'a> {
 
     /// Import the previously fetched remote-tracking branches into the jj repo
     /// and update jj's local branches. We also import local tags since remote
-    /// tags should have been merged by Git.
+    /// tags should have been merged by Git, unless tags aren't fetched at all.
     ///
     /// Clears all yet-to-be-imported {branch_names, remote_name} pairs after
     /// the import. If `fetch()` has not been called since the last time
@@ -1919,7 +2673,7 @@ impl<'a> GitFetch<'a> {
                 self.git_settings,
                 |ref_name| match ref_name {
                     RefName::LocalBranch(_) => false,
-                    RefName::Tag(_) => true,
+                    RefName::Tag(_) => self.git_settings.fetch_tags != GitFetchTags::None,
                     RefName::RemoteBranch(symbol) => self.fetched.iter().any(|fetched| {
                         if fetched.remote != symbol.remote {
                             return false;
@@ -1939,6 +2693,76 @@ impl<'a> GitFetch<'a> {
     }
 }
 
+#[derive(Debug, Error)]
+pub enum GitDeepenShallowError {
+    #[error("Cannot reindex indexes of type '{0}'")]
+    UnsupportedIndex(String),
+    #[error("Failed to rebuild the commit index")]
+    Index(#[source] Box<dyn std::error::Error + Send + Sync>),
+    #[error(transparent)]
+    Backend(#[from] BackendError),
+    #[error(transparent)]
+    RepoLoader(#[from] RepoLoaderError),
+    #[error(transparent)]
+    UnexpectedBackend(#[from] UnexpectedGitBackendError),
+}
+
+/// Describes the changes made by [`deepen_shallow()`].
+#[derive(Clone, Debug, Default)]
+pub struct GitDeepenShallowStats {
+    /// Commits which were at the shallow boundary, and have their parents
+    /// now.
+    pub deepened_commits: Vec<CommitId>,
+    /// Number of commits in the rebuilt index, if it was rebuilt.
+    pub num_indexed_commits: Option<u32>,
+}
+
+/// Brings the repo up to date after the history of a shallow Git repository
+/// has been deepened, e.g. by [`GitFetch::unshallow()`].
+///
+/// The commits at the former shallow boundary were indexed with the root
+/// commit as their only parent, and their newly fetched ancestors may not be
+/// indexed at all. If there are any such commits, the index is rebuilt at the
+/// operation of `repo`, and the repo is reloaded with the rebuilt index.
+pub fn deepen_shallow(
+    repo: &Arc<ReadonlyRepo>,
+) -> Result<(Arc<ReadonlyRepo>, GitDeepenShallowStats), GitDeepenShallowError> {
+    let store = repo.store();
+    let git_backend = get_git_backend(store)?;
+    let root_children: Vec<CommitId> = RevsetExpression::root()
+        .children()
+        .evaluate(repo.as_ref())
+        .map_err(|err| err.expect_backend_error())?
+        .iter()
+        .try_collect()
+        .map_err(|err| err.expect_backend_error())?;
+    let deepened_commits = git_backend.deepened_commit_ids(&root_children)?;
+    if deepened_commits.is_empty() {
+        return Ok((repo.clone(), GitDeepenShallowStats::default()));
+    }
+    tracing::info!(?deepened_commits, "rebuilding index of deepened repo");
+
+    // The cached commits may still have the root commit as their parent.
+    store.clear_commit_cache();
+    let index_store = repo.loader().index_store();
+    let default_index_store = index_store
+        .as_any()
+        .downcast_ref::<DefaultIndexStore>()
+        .ok_or_else(|| GitDeepenShallowError::UnsupportedIndex(index_store.name().to_owned()))?;
+    default_index_store
+        .reinit()
+        .map_err(|err| GitDeepenShallowError::Index(err.into()))?;
+    let index = default_index_store
+        .build_index_at_operation(repo.operation(), store)
+        .map_err(|err| GitDeepenShallowError::Index(err.into()))?;
+    let repo = repo.reload_at(repo.operation())?;
+    let stats = GitDeepenShallowStats {
+        deepened_commits,
+        num_indexed_commits: Some(index.as_composite().stats().num_commits),
+    };
+    Ok((repo, stats))
+}
+
 fn expand_fetch_refspecs(
     remote_name: &str,
     branch_names: &[StringPattern],
@@ -1967,25 +2791,45 @@ fn expand_fetch_refspecs(
 enum GitFetchImpl<'a> {
     Git2 {
         git_repo: git2::Repository,
+        git_ctx: GitSubprocessContext<'a>,
     },
     Subprocess {
         git_repo: Box<gix::Repository>,
         git_ctx: GitSubprocessContext<'a>,
     },
+    /// Fetches from HTTP(S) remotes natively, and from other remotes with the
+    /// `fallback` implementation.
+    #[cfg(feature = "git-native-http")]
+    NativeHttp {
+        git_repo: Box<gix::Repository>,
+        http_settings: &'a GitHttpSettings,
+        fallback: Box<Self>,
+    },
 }
 
 impl<'a> GitFetchImpl<'a> {
     fn new(store: &Store, git_settings: &'a GitSettings) -> Result<Self, GitFetchPrepareError> {
         let git_backend = get_git_backend(store)?;
-        if git_settings.subprocess {
+        let git_ctx = GitSubprocessContext::from_git_backend(git_backend, git_settings);
+        // Neither libgit2 nor gix can fetch into a partial clone without
+        // breaking it, so the Git subprocess is always used.
+        let is_partial_clone = git_backend.promisor_remote_name().is_some();
+        let fetch_impl = if git_settings.subprocess || is_partial_clone {
             let git_repo = Box::new(git_backend.git_repo());
-            let git_ctx =
-                GitSubprocessContext::from_git_backend(git_backend, &git_settings.executable_path);
-            Ok(GitFetchImpl::Subprocess { git_repo, git_ctx })
+            GitFetchImpl::Subprocess { git_repo, git_ctx }
         } else {
             let git_repo = git_backend.open_git_repo()?;
-            Ok(GitFetchImpl::Git2 { git_repo })
+            GitFetchImpl::Git2 { git_repo, git_ctx }
+        };
+        #[cfg(feature = "git-native-http")]
+        if git_settings.native_http && !is_partial_clone {
+            return Ok(GitFetchImpl::NativeHttp {
+                git_repo: Box::new(git_backend.git_repo()),
+                http_settings: &git_settings.http,
+                fallback: Box::new(fetch_impl),
+            });
         }
+        Ok(fetch_impl)
     }
 
     fn fetch(
@@ -1993,20 +2837,60 @@ impl<'a> GitFetchImpl<'a> {
         remote_name: &str,
         branch_names: &[StringPattern],
         callbacks: RemoteCallbacks<'_>,
-        depth: Option<NonZeroU32>,
+        shallow: &GitShallowOptions,
+        fetch_tags: GitFetchTags,
     ) -> Result<(), GitFetchError> {
         match self {
-            GitFetchImpl::Git2 { git_repo } => {
-                git2_fetch(git_repo, remote_name, branch_names, callbacks, depth)
-            }
+            GitFetchImpl::Git2 { git_repo, git_ctx } => git2_fetch(
+                git_repo,
+                git_ctx,
+                remote_name,
+                branch_names,
+                callbacks,
+                shallow,
+                fetch_tags,
+            ),
             GitFetchImpl::Subprocess { git_repo, git_ctx } => subprocess_fetch(
                 git_repo,
                 git_ctx,
                 remote_name,
                 branch_names,
                 callbacks,
-                depth,
+                shallow,
+                fetch_tags,
             ),
+            #[cfg(feature = "git-native-http")]
+            GitFetchImpl::NativeHttp {
+                git_repo,
+                http_settings,
+                fallback,
+            } => {
+                if !git_http::is_http_remote(git_repo, remote_name, gix::remote::Direction::Fetch) {
+                    return fallback.fetch(
+                        remote_name,
+                        branch_names,
+                        callbacks,
+                        shallow,
+                        fetch_tags,
+                    );
+                }
+                let refspecs = expand_fetch_refspecs(remote_name, branch_names)?;
+                if refspecs.is_empty() {
+                    // Don't fall back to the base refspecs.
+                    return Ok(());
+                }
+                git_http::fetch(
+                    git_repo,
+                    http_settings,
+                    remote_name,
+                    &refspecs,
+                    branch_names,
+                    callbacks,
+                    shallow,
+                    fetch_tags,
+                )?;
+                Ok(())
+            }
         }
     }
 
@@ -2016,23 +2900,47 @@ impl<'a> GitFetchImpl<'a> {
         callbacks: RemoteCallbacks<'_>,
     ) -> Result<Option<String>, GitFetchError> {
         match self {
-            GitFetchImpl::Git2 { git_repo } => {
-                git2_get_default_branch(git_repo, remote_name, callbacks)
+            GitFetchImpl::Git2 { git_repo, git_ctx } => {
+                git2_get_default_branch(git_repo, git_ctx, remote_name, callbacks)
             }
             GitFetchImpl::Subprocess { git_repo, git_ctx } => {
                 subprocess_get_default_branch(git_repo, git_ctx, remote_name, callbacks)
             }
+            #[cfg(feature = "git-native-http")]
+            GitFetchImpl::NativeHttp {
+                git_repo,
+                http_settings,
+                fallback,
+            } => {
+                if git_http::is_http_remote(git_repo, remote_name, gix::remote::Direction::Fetch) {
+                    Ok(git_http::get_default_branch(
+                        git_repo,
+                        http_settings,
+                        remote_name,
+                        callbacks,
+                    )?)
+                } else {
+                    fallback.get_default_branch(remote_name, callbacks)
+                }
+            }
         }
     }
 }
 
+#[tracing::instrument(skip_all, fields(remote = remote_name))]
 fn git2_fetch(
     git_repo: &git2::Repository,
+    git_ctx: &GitSubprocessContext,
     remote_name: &str,
     branch_names: &[StringPattern],
     callbacks: RemoteCallbacks<'_>,
-    depth: Option<NonZeroU32>,
+    shallow: &GitShallowOptions,
+    fetch_tags: GitFetchTags,
 ) -> Result<(), GitFetchError> {
+    // libgit2 only supports limiting the depth.
+    if shallow.has_deepen_filters() {
+        return Err(GitFetchError::UnsupportedShallowOptions);
+    }
     let mut remote = git_repo.find_remote(remote_name).map_err(|err| {
         if is_remote_not_found_err(&err) {
             GitFetchError::NoSuchRemote(remote_name.to_string())
@@ -2053,16 +2961,26 @@ fn git2_fetch(
     }
 
     tracing::debug!("remote.download");
-    remote.download(&refspecs, Some(&mut git2_fetch_options(callbacks, depth)))?;
+    let autotag = match fetch_tags {
+        GitFetchTags::None => git2::AutotagOption::None,
+        GitFetchTags::Reachable => git2::AutotagOption::Auto,
+        GitFetchTags::All => git2::AutotagOption::All,
+    };
+    let credential_helper = GitCredentialHelper::new(git_ctx);
+    let mut fetch_options = git2_fetch_options(
+        callbacks,
+        shallow,
+        git_ctx.http_settings(),
+        &credential_helper,
+    );
+    fetch_options.download_tags(autotag);
+    let download_result = remote.download(&refspecs, Some(&mut fetch_options));
+    credential_helper.finish(&download_result);
+    download_result?;
     tracing::debug!("remote.prune");
     remote.prune(None)?;
     tracing::debug!("remote.update_tips");
-    remote.update_tips(
-        None,
-        git2::RemoteUpdateFlags::empty(),
-        git2::AutotagOption::Unspecified,
-        None,
-    )?;
+    remote.update_tips(None, git2::RemoteUpdateFlags::empty(), autotag, None)?;
     tracing::debug!("remote.disconnect");
     remote.disconnect()?;
     Ok(())
@@ -2070,6 +2988,7 @@ fn git2_fetch(
 
 fn git2_get_default_branch(
     git_repo: &git2::Repository,
+    git_ctx: &GitSubprocessContext,
     remote_name: &str,
     callbacks: RemoteCallbacks<'_>,
 ) -> Result<Option<String>, GitFetchError> {
@@ -2082,15 +3001,14 @@ fn git2_get_default_branch(
     })?;
     // Unlike .download(), connect_auth() returns RAII object.
     tracing::debug!("remote.connect");
-    let connection = {
-        let mut proxy_options = git2::ProxyOptions::new();
-        proxy_options.auto();
-        remote.connect_auth(
-            git2::Direction::Fetch,
-            Some(callbacks.into_git()),
-            Some(proxy_options),
-        )?
-    };
+    let credential_helper = GitCredentialHelper::new(git_ctx);
+    let connect_result = remote.connect_auth(
+        git2::Direction::Fetch,
+        Some(callbacks.into_git(&credential_helper)),
+        Some(git2_proxy_options(git_ctx.http_settings())),
+    );
+    credential_helper.finish(&connect_result);
+    let connection = connect_result?;
     let mut default_branch = None;
     tracing::debug!("remote.default_branch");
     if let Ok(default_ref_buf) = connection.default_branch() {
@@ -2112,7 +3030,8 @@ fn subprocess_fetch(
     remote_name: &str,
     branch_names: &[StringPattern],
     mut callbacks: RemoteCallbacks<'_>,
-    depth: Option<NonZeroU32>,
+    shallow: &GitShallowOptions,
+    fetch_tags: GitFetchTags,
 ) -> Result<(), GitFetchError> {
     // check the remote exists
     if git_repo.try_find_remote(remote_name).is_none() {
@@ -2134,9 +3053,13 @@ fn subprocess_fetch(
     //
     // even more unfortunately, git errors out one refspec at a time,
     // meaning that the below cycle runs in O(#failed refspecs)
-    while let Some(failing_refspec) =
-        git_ctx.spawn_fetch(remote_name, &remaining_refspecs, &mut callbacks, depth)?
-    {
+    while let Some(failing_refspec) = git_ctx.spawn_fetch(
+        remote_name,
+        &remaining_refspecs,
+        &mut callbacks,
+        shallow,
+        fetch_tags,
+    )? {
         remaining_refspecs.retain(|r| r.source.as_ref() != Some(&failing_refspec));
 
         if let Some(branch_name) = failing_refspec.strip_prefix("refs/heads/") {
@@ -2174,6 +3097,18 @@ pub enum GitPushError {
     RefInUnexpectedLocation(Vec<String>),
     #[error("Remote rejected the update of some refs (do you have permission to push to {0:?}?)")]
     RefUpdateRejected(Vec<String>),
+    /// The remote refused to update some refs, e.g. because a pre-receive
+    /// hook declined them. Each ref comes with the reason given by the remote.
+    #[error("Remote refused the update of some refs")]
+    RemoteRejected(Vec<(String, Option<String>)>),
+    /// A push that was split into chunks failed after some of the chunks had
+    /// been pushed. The remote-tracking bookmarks record how far the push got.
+    #[error("Failed to push chunk {} of {total_chunks}", pushed_chunks + 1)]
+    PartiallyPushed {
+        pushed_chunks: usize,
+        total_chunks: usize,
+        source: Box<GitPushError>,
+    },
     // TODO: I'm sure there are other errors possible, such as transport-level errors,
     // and errors caused by the remote rejecting the push.
     #[error("Unexpected git error when pushing")]
@@ -2181,12 +3116,31 @@ pub enum GitPushError {
     #[error(transparent)]
     Subprocess(#[from] GitSubprocessError),
     #[error(transparent)]
+    NativeHttp(#[from] GitHttpError),
+    #[error(transparent)]
+    Backend(#[from] BackendError),
+    #[error(transparent)]
     UnexpectedBackend(#[from] UnexpectedGitBackendError),
 }
 
 #[derive(Clone, Debug)]
 pub struct GitBranchPushTargets {
     pub branch_updates: Vec<(String, BookmarkPushUpdate)>,
+    /// Remote branch names to push the local branches to, keyed by the local
+    /// branch name. Branches not in the map are pushed to the remote branch of
+    /// the same name.
+    ///
+    /// The `old_target` of the update should be sourced from the renamed
+    /// remote-tracking branch. A remote branch pushed under a different name
+    /// doesn't become tracking.
+    pub remote_names: HashMap<String, String>,
+}
+
+impl GitBranchPushTargets {
+    /// Returns the remote branch name to push the local branch `name` to.
+    pub fn remote_name<'a>(&'a self, name: &'a str) -> &'a str {
+        self.remote_names.get(name).map_or(name, String::as_str)
+    }
 }
 
 pub struct GitRefUpdate {
@@ -2200,12 +3154,18 @@ pub struct GitRefUpdate {
 }
 
 /// Pushes the specified branches and updates the repo view accordingly.
+///
+/// If `git.push-max-pack-size` is set and the commits to push are estimated to
+/// exceed it, the push is split into several pushes, each moving the remote
+/// branches forward to an ancestor of their final targets. If one of those
+/// pushes fails, the remote-tracking branches are updated to the positions
+/// pushed so far, so pushing again resumes from there.
 pub fn push_branches(
     mut_repo: &mut MutableRepo,
     git_settings: &GitSettings,
     remote: &str,
     targets: &GitBranchPushTargets,
-    callbacks: RemoteCallbacks<'_>,
+    mut callbacks: RemoteCallbacks<'_>,
 ) -> Result<(), GitPushError> {
     validate_remote_name(remote)?;
 
@@ -2213,30 +3173,228 @@ pub fn push_branches(
         .branch_updates
         .iter()
         .map(|(name, update)| GitRefUpdate {
-            qualified_name: format!("refs/heads/{name}"),
+            qualified_name: format!("refs/heads/{}", targets.remote_name(name)),
             expected_current_target: update.old_target.clone(),
             new_target: update.new_target.clone(),
         })
         .collect_vec();
-    push_updates(mut_repo, git_settings, remote, &ref_updates, callbacks)?;
+    let chunks = match git_settings.push_max_pack_size {
+        Some(max_size) => split_push_into_chunks(mut_repo, remote, ref_updates, max_size)?,
+        None => vec![ref_updates],
+    };
+    for (index, chunk) in chunks.iter().enumerate() {
+        if chunks.len() > 1 {
+            if let Some(cb) = &mut callbacks.push_chunk {
+                cb(index + 1, chunks.len());
+            }
+        }
+        let result = push_updates(mut_repo, git_settings, remote, chunk, callbacks.reborrow());
+        if let Err(err) = result {
+            if index == 0 {
+                return Err(err);
+            }
+            // Record the intermediate positions the remote branches were
+            // moved to by the chunks pushed so far. Later chunks override
+            // earlier ones.
+            let pushed_targets: HashMap<&str, &Option<CommitId>> = chunks[..index]
+                .iter()
+                .flatten()
+                .map(|update| (update.qualified_name.as_str(), &update.new_target))
+                .collect();
+            for (name, _) in &targets.branch_updates {
+                let qualified_name = format!("refs/heads/{}", targets.remote_name(name));
+                if let Some(new_target) = pushed_targets.get(qualified_name.as_str()) {
+                    record_pushed_branch(mut_repo, remote, targets, name, new_target);
+                }
+            }
+            return Err(GitPushError::PartiallyPushed {
+                pushed_chunks: index,
+                total_chunks: chunks.len(),
+                source: Box::new(err),
+            });
+        }
+    }
 
-    // TODO: add support for partially pushed refs? we could update the view
-    // excluding rejected refs, but the transaction would be aborted anyway
-    // if we returned an Err.
     for (name, update) in &targets.branch_updates {
-        let remote_symbol = RemoteRefSymbol { name, remote };
-        let git_ref_name = format!("refs/remotes/{remote}/{name}");
-        let new_remote_ref = RemoteRef {
-            target: RefTarget::resolved(update.new_target.clone()),
-            state: RemoteRefState::Tracking,
-        };
-        mut_repo.set_git_ref_target(&git_ref_name, new_remote_ref.target.clone());
-        mut_repo.set_remote_bookmark(remote_symbol, new_remote_ref);
+        record_pushed_branch(mut_repo, remote, targets, name, &update.new_target);
     }
 
     Ok(())
 }
 
+/// Updates the remote-tracking branch of the local branch `name` after it was
+/// pushed to `new_target`.
+fn record_pushed_branch(
+    mut_repo: &mut MutableRepo,
+    remote: &str,
+    targets: &GitBranchPushTargets,
+    name: &str,
+    new_target: &Option<CommitId>,
+) {
+    let remote_name = targets.remote_name(name);
+    let remote_symbol = RemoteRefSymbol {
+        name: remote_name,
+        remote,
+    };
+    let git_ref_name = format!("refs/remotes/{remote}/{remote_name}");
+    let state = if remote_name == name {
+        RemoteRefState::Tracking
+    } else {
+        mut_repo.get_remote_bookmark(remote_symbol).state
+    };
+    let new_remote_ref = RemoteRef {
+        target: RefTarget::resolved(new_target.clone()),
+        state,
+    };
+    mut_repo.set_git_ref_target(&git_ref_name, new_remote_ref.target.clone());
+    mut_repo.set_remote_bookmark(remote_symbol, new_remote_ref);
+}
+
+/// Splits the `updates` into a sequence of pushes whose estimated pack sizes
+/// don't exceed `max_size` bytes.
+///
+/// Each branch is moved along the first-parent chain of its new target, so
+/// that every push is a fast-forward of the previous one. Whenever the next
+/// commit of the chain, along with the commits merged in by its other parents,
+/// would make the current chunk too large, the chunk is closed by moving the
+/// branch to the last commit of the chain included so far. Deleted branches are
+/// pushed with the last chunk. A single step larger than `max_size` still gets
+/// pushed as its own chunk.
+fn split_push_into_chunks(
+    repo: &dyn Repo,
+    remote: &str,
+    updates: Vec<GitRefUpdate>,
+    max_size: u64,
+) -> Result<Vec<Vec<GitRefUpdate>>, GitPushError> {
+    // Commits reachable from these are assumed to be present on the remote.
+    let mut known_ids = repo
+        .view()
+        .remote_bookmarks(remote)
+        .flat_map(|(_, remote_ref)| remote_ref.target.added_ids())
+        .chain(
+            updates
+                .iter()
+                .filter_map(|update| update.expected_current_target.as_ref()),
+        )
+        .cloned()
+        .collect_vec();
+
+    let mut chunks = vec![];
+    let mut chunk = vec![];
+    let mut chunk_size = 0;
+    let mut deletions = vec![];
+    for update in updates {
+        let Some(new_target) = update.new_target.clone() else {
+            deletions.push(update);
+            continue;
+        };
+        let known_expression =
+            RevsetExpression::commits(known_ids.clone()).union(&RevsetExpression::root());
+        let chain = first_parent_chain(repo, &known_expression, &new_target)?;
+        let mut expected_current_target = update.expected_current_target;
+        // The last commit of the chain included in the current chunk
+        let mut last_id: Option<CommitId> = None;
+        for commit in chain {
+            let mut size = estimate_commit_pack_size(repo, commit.id())?;
+            if let [first_parent_id, other_parent_ids @ ..] = commit.parent_ids() {
+                if !other_parent_ids.is_empty() {
+                    let merged_ids: Vec<CommitId> = known_expression
+                        .union(&RevsetExpression::commit(first_parent_id.clone()))
+                        .range(&RevsetExpression::commits(other_parent_ids.to_vec()))
+                        .evaluate(repo)
+                        .map_err(|err| err.expect_backend_error())?
+                        .iter()
+                        .try_collect()
+                        .map_err(|err| err.expect_backend_error())?;
+                    for id in &merged_ids {
+                        size += estimate_commit_pack_size(repo, id)?;
+                    }
+                }
+            }
+            // The branch can only be moved forward from its position on the
+            // remote.
+            let can_cut = match (&last_id, &expected_current_target) {
+                (Some(last_id), Some(current_id)) => repo.index().is_ancestor(current_id, last_id),
+                _ => true,
+            };
+            if can_cut && chunk_size > 0 && chunk_size + size > max_size {
+                if let Some(last_id) = last_id.take() {
+                    chunk.push(GitRefUpdate {
+                        qualified_name: update.qualified_name.clone(),
+                        expected_current_target: expected_current_target.replace(last_id.clone()),
+                        new_target: Some(last_id),
+                    });
+                }
+                chunks.push(std::mem::take(&mut chunk));
+                chunk_size = 0;
+            }
+            chunk_size += size;
+            last_id = Some(commit.id().clone());
+        }
+        if expected_current_target.as_ref() != Some(&new_target) {
+            chunk.push(GitRefUpdate {
+                qualified_name: update.qualified_name,
+                expected_current_target,
+                new_target: Some(new_target.clone()),
+            });
+        }
+        known_ids.push(new_target);
+    }
+    chunk.extend(deletions);
+    if !chunk.is_empty() || chunks.is_empty() {
+        chunks.push(chunk);
+    }
+    Ok(chunks)
+}
+
+/// Returns the commits on the first-parent chain of `head_id` which aren't
+/// ancestors of the `known_expression`, ancestors first.
+fn first_parent_chain(
+    repo: &dyn Repo,
+    known_expression: &Rc<ResolvedRevsetExpression>,
+    head_id: &CommitId,
+) -> Result<Vec<Commit>, GitPushError> {
+    let known_revset = known_expression
+        .ancestors()
+        .evaluate(repo)
+        .map_err(|err| err.expect_backend_error())?;
+    let is_known = known_revset.containing_fn();
+    let mut chain = vec![];
+    let mut id = head_id.clone();
+    while !is_known(&id).map_err(|err| err.expect_backend_error())? {
+        let commit = repo.store().get_commit(&id)?;
+        let Some(parent_id) = commit.parent_ids().first() else {
+            break;
+        };
+        id = parent_id.clone();
+        chain.push(commit);
+    }
+    chain.reverse();
+    Ok(chain)
+}
+
+/// Estimates the number of bytes pushing the commit would add to a pack, which
+/// is the total size of the files it adds or modifies compared to its parents.
+fn estimate_commit_pack_size(repo: &dyn Repo, id: &CommitId) -> Result<u64, GitPushError> {
+    let commit = repo.store().get_commit(id)?;
+    let parent_tree = commit.parent_tree(repo)?;
+    let tree = commit.tree()?;
+    async {
+        let mut size = 0;
+        let mut diff_stream = parent_tree.diff_stream(&tree, &EverythingMatcher);
+        while let Some(TreeDiffEntry { path, values }) = diff_stream.next().await {
+            let (_, after) = values?;
+            for value in after.iter().flatten() {
+                if let TreeValue::File { id, .. } = value {
+                    size += repo.store().file_size_async(&path, id).await?;
+                }
+            }
+        }
+        Ok(size)
+    }
+    .block_on()
+}
+
 /// Pushes the specified Git refs without updating the repo view.
 pub fn push_updates(
     repo: &dyn Repo,
@@ -2268,10 +3426,23 @@ pub fn push_updates(
     // requires adjusting some tests.
 
     let git_backend = get_git_backend(repo.store())?;
+    #[cfg(feature = "git-native-http")]
+    if git_settings.native_http {
+        let git_repo = git_backend.git_repo();
+        if git_http::is_http_remote(&git_repo, remote_name, gix::remote::Direction::Push) {
+            return native_http_push_refs(
+                repo,
+                &git_repo,
+                &git_settings.http,
+                remote_name,
+                updates,
+                callbacks,
+            );
+        }
+    }
+    let git_ctx = GitSubprocessContext::from_git_backend(git_backend, git_settings);
     if git_settings.subprocess {
         let git_repo = git_backend.git_repo();
-        let git_ctx =
-            GitSubprocessContext::from_git_backend(git_backend, &git_settings.executable_path);
         subprocess_push_refs(
             &git_repo,
             &git_ctx,
@@ -2286,6 +3457,7 @@ pub fn push_updates(
         git2_push_refs(
             repo,
             &git_repo,
+            &git_ctx,
             remote_name,
             &qualified_remote_refs_expected_locations,
             &refspecs,
@@ -2294,9 +3466,11 @@ pub fn push_updates(
     }
 }
 
+#[tracing::instrument(skip_all, fields(remote = remote_name))]
 fn git2_push_refs(
     repo: &dyn Repo,
     git_repo: &git2::Repository,
+    git_ctx: &GitSubprocessContext,
     remote_name: &str,
     qualified_remote_refs_expected_locations: &HashMap<&str, Option<&CommitId>>,
     refspecs: &[String],
@@ -2314,12 +3488,11 @@ fn git2_push_refs(
         .copied()
         .collect();
     let mut failed_push_negotiations = vec![];
+    let credential_helper = GitCredentialHelper::new(git_ctx);
     let push_result = {
         let mut push_options = git2::PushOptions::new();
-        let mut proxy_options = git2::ProxyOptions::new();
-        proxy_options.auto();
-        push_options.proxy_options(proxy_options);
-        let mut callbacks = callbacks.into_git();
+        push_options.proxy_options(git2_proxy_options(git_ctx.http_settings()));
+        let mut callbacks = callbacks.into_git(&credential_helper);
         callbacks.push_negotiation(|updates| {
             for update in updates {
                 let dst_refname = update
@@ -2389,6 +3562,7 @@ fn git2_push_refs(
         push_options.remote_callbacks(callbacks);
         remote.push(refspecs, Some(&mut push_options))
     };
+    credential_helper.finish(&push_result);
     if !failed_push_negotiations.is_empty() {
         // If the push negotiation returned an error, `remote.push` would not
         // have pushed anything and would have returned an error, as expected.
@@ -2433,7 +3607,7 @@ fn subprocess_push_refs(
         return Err(GitPushError::NoSuchRemote(remote_name.to_owned()));
     }
 
-    let mut remaining_remote_refs: HashSet<_> = qualified_remote_refs_expected_locations
+    let remaining_remote_refs: HashSet<_> = qualified_remote_refs_expected_locations
         .keys()
         .copied()
         .collect();
@@ -2443,19 +3617,31 @@ fn subprocess_push_refs(
         .map(|full_refspec| RefToPush::new(full_refspec, qualified_remote_refs_expected_locations))
         .collect();
 
-    let (failed_ref_matches, successful_pushes) =
-        git_ctx.spawn_push(remote_name, &refs_to_push, &mut callbacks)?;
+    let output = git_ctx.spawn_push(remote_name, &refs_to_push, &mut callbacks)?;
+    check_push_output(remaining_remote_refs, output)
+}
 
-    for remote_ref in successful_pushes {
+/// Turns the refs the remote didn't accept into an error.
+///
+/// `remaining_remote_refs` are the refs that were to be pushed.
+fn check_push_output(
+    mut remaining_remote_refs: HashSet<&str>,
+    output: GitPushOutput,
+) -> Result<(), GitPushError> {
+    for remote_ref in &output.pushed {
         remaining_remote_refs.remove(remote_ref.as_str());
     }
 
-    if !failed_ref_matches.is_empty() {
-        let mut refs_in_unexpected_locations = failed_ref_matches;
+    if !output.rejected.is_empty() {
+        let mut refs_in_unexpected_locations = output.rejected;
         refs_in_unexpected_locations.sort();
         Err(GitPushError::RefInUnexpectedLocation(
             refs_in_unexpected_locations,
         ))
+    } else if !output.remote_rejected.is_empty() {
+        let mut remote_rejected = output.remote_rejected;
+        remote_rejected.sort();
+        Err(GitPushError::RemoteRejected(remote_rejected))
     } else if remaining_remote_refs.is_empty() {
         Ok(())
     } else {
@@ -2472,6 +3658,79 @@ fn subprocess_push_refs(
     }
 }
 
+#[cfg(feature = "git-native-http")]
+#[tracing::instrument(skip_all, fields(remote = remote_name))]
+fn native_http_push_refs(
+    repo: &dyn Repo,
+    git_repo: &gix::Repository,
+    http_settings: &GitHttpSettings,
+    remote_name: &str,
+    updates: &[GitRefUpdate],
+    callbacks: RemoteCallbacks<'_>,
+) -> Result<(), GitPushError> {
+    let push = git_http::GitHttpPush::connect(git_repo, http_settings, remote_name, callbacks)?;
+    let mut remaining_remote_refs = HashSet::new();
+    let mut ref_updates = vec![];
+    let mut failed_push_negotiations = vec![];
+    for update in updates {
+        let qualified_name = update.qualified_name.as_str();
+        let actual_remote_location = push.remote_ref(qualified_name);
+        let expected_remote_location = update.expected_current_target.as_ref();
+        let local_location = update.new_target.as_ref();
+        if allow_push(
+            repo.index(),
+            actual_remote_location,
+            expected_remote_location,
+            local_location,
+        )
+        .is_err()
+        {
+            tracing::info!(
+                "Cannot push {qualified_name} to {local_location:?}; it is at unexpectedly at \
+                 {actual_remote_location:?} on the server as opposed to the expected \
+                 {expected_remote_location:?}",
+            );
+            failed_push_negotiations.push(qualified_name.to_owned());
+        } else if actual_remote_location != local_location {
+            remaining_remote_refs.insert(qualified_name);
+            ref_updates.push(git_http::GitHttpRefUpdate {
+                qualified_name: qualified_name.to_owned(),
+                old_target: actual_remote_location.cloned(),
+                new_target: local_location.cloned(),
+            });
+        }
+    }
+    if !failed_push_negotiations.is_empty() {
+        failed_push_negotiations.sort();
+        return Err(GitPushError::RefInUnexpectedLocation(
+            failed_push_negotiations,
+        ));
+    }
+
+    // Commits reachable from the remote refs are assumed to be present on the
+    // remote.
+    let known_ids = push
+        .remote_ref_targets()
+        .filter(|id| repo.index().has_id(id))
+        .cloned()
+        .collect_vec();
+    let new_ids = ref_updates
+        .iter()
+        .filter_map(|update| update.new_target.clone())
+        .collect_vec();
+    let commit_ids: Vec<CommitId> = RevsetExpression::commits(known_ids)
+        .union(&RevsetExpression::root())
+        .range(&RevsetExpression::commits(new_ids))
+        .evaluate(repo)
+        .map_err(|err| err.expect_backend_error())?
+        .iter()
+        .try_collect()
+        .map_err(|err| err.expect_backend_error())?;
+    let output = push.send(&ref_updates, &commit_ids)?;
+    git_http::update_remote_tracking_branches(git_repo, remote_name, &ref_updates, &output)?;
+    check_push_output(remaining_remote_refs, output)
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 enum PushAllowReason {
     NormalMatch,
@@ -2539,10 +3798,32 @@ pub struct RemoteCallbacks<'a> {
     pub get_ssh_keys: Option<&'a mut dyn FnMut(&str) -> Vec<PathBuf>>,
     pub get_password: Option<&'a mut dyn FnMut(&str, &str) -> Option<String>>,
     pub get_username_password: Option<&'a mut dyn FnMut(&str) -> Option<(String, String)>>,
+    /// Called before each push of a push that was split into chunks, with the
+    /// 1-based index of the chunk and the total number of chunks.
+    pub push_chunk: Option<&'a mut dyn FnMut(usize, usize)>,
 }
 
 impl<'a> RemoteCallbacks<'a> {
-    fn into_git(mut self) -> git2::RemoteCallbacks<'a> {
+    /// Borrows the callbacks so they can be passed to more than one remote
+    /// operation.
+    fn reborrow(&mut self) -> RemoteCallbacks<'_> {
+        RemoteCallbacks {
+            progress: self.progress.as_deref_mut().map(|cb| cb as _),
+            sideband_progress: self.sideband_progress.as_deref_mut().map(|cb| cb as _),
+            get_ssh_keys: self.get_ssh_keys.as_deref_mut().map(|cb| cb as _),
+            get_password: self.get_password.as_deref_mut().map(|cb| cb as _),
+            get_username_password: self.get_username_password.as_deref_mut().map(|cb| cb as _),
+            push_chunk: self.push_chunk.as_deref_mut().map(|cb| cb as _),
+        }
+    }
+
+    fn into_git<'h>(
+        mut self,
+        credential_helper: &'h GitCredentialHelper,
+    ) -> git2::RemoteCallbacks<'h>
+    where
+        'a: 'h,
+    {
         let mut callbacks = git2::RemoteCallbacks::new();
         if let Some(progress_cb) = self.progress {
             callbacks.transfer_progress(move |progress| {
@@ -2564,18 +3845,24 @@ impl<'a> RemoteCallbacks<'a> {
         // TODO: We should expose the callbacks to the caller instead -- the library
         // crate shouldn't read environment variables.
         let mut tried_ssh_agent = false;
+        let mut tried_credential_helper = false;
         let mut ssh_key_paths_to_try: Option<Vec<PathBuf>> = None;
         callbacks.credentials(move |url, username_from_url, allowed_types| {
             let span = tracing::debug_span!("RemoteCallbacks.credentials");
             let _ = span.enter();
 
-            let git_config = git2::Config::open_default();
-            let credential_helper = git_config
-                .and_then(|conf| git2::Cred::credential_helper(&conf, url, username_from_url));
-            if let Ok(creds) = credential_helper {
-                tracing::info!("using credential_helper");
-                return Ok(creds);
-            } else if let Some(username) = username_from_url {
+            // Ask the credential helpers once. If libgit2 asks again, the
+            // credential was rejected, and we fall back to prompting.
+            if allowed_types.contains(git2::CredentialType::USER_PASS_PLAINTEXT)
+                && !tried_credential_helper
+            {
+                tried_credential_helper = true;
+                if let Some((username, pw)) = credential_helper.fill(url, username_from_url) {
+                    tracing::info!(username, "using credential helper");
+                    return git2::Cred::userpass_plaintext(&username, &pw);
+                }
+            }
+            if let Some(username) = username_from_url {
                 if allowed_types.contains(git2::CredentialType::SSH_KEY) {
                     // Try to get the SSH key from the agent once. We don't even check if
                     // $SSH_AUTH_SOCK is set because Windows uses another mechanism.
@@ -2613,6 +3900,7 @@ impl<'a> RemoteCallbacks<'a> {
                                 username,
                                 "using userpass_plaintext with username from url"
                             );
+                            credential_helper.supply(url, username, &pw);
                             return git2::Cred::userpass_plaintext(username, &pw).map_err(|err| {
                                 tracing::error!(err = %err);
                                 err
@@ -2624,6 +3912,7 @@ impl<'a> RemoteCallbacks<'a> {
                 if let Some(ref mut cb) = self.get_username_password {
                     if let Some((username, pw)) = cb(url) {
                         tracing::info!(username, "using userpass_plaintext");
+                        credential_helper.supply(url, &username, &pw);
                         return git2::Cred::userpass_plaintext(&username, &pw).map_err(|err| {
                             tracing::error!(err = %err);
                             err
@@ -2638,6 +3927,75 @@ impl<'a> RemoteCallbacks<'a> {
     }
 }
 
+/// Exchanges HTTP(S) credentials used by libgit2 with the Git credential
+/// helpers through `git credential`.
+struct GitCredentialHelper<'a> {
+    git_ctx: &'a GitSubprocessContext<'a>,
+    /// The last credential handed to libgit2.
+    supplied: RefCell<Option<GitCredential>>,
+}
+
+impl<'a> GitCredentialHelper<'a> {
+    fn new(git_ctx: &'a GitSubprocessContext<'a>) -> Self {
+        GitCredentialHelper {
+            git_ctx,
+            supplied: RefCell::new(None),
+        }
+    }
+
+    /// Looks up the username and password for the `url`.
+    fn fill(&self, url: &str, username: Option<&str>) -> Option<(String, String)> {
+        let request = GitCredential::from_url(url, username);
+        let credential = self
+            .git_ctx
+            .spawn_credential(GitCredentialAction::Fill, &request)
+            .inspect_err(|err| tracing::info!(?err, "git credential fill failed"))
+            .ok()?;
+        let username = credential.get("username")?.to_owned();
+        let password = credential.get("password")?.to_owned();
+        self.set_supplied(credential);
+        Some((username, password))
+    }
+
+    /// Records a credential obtained elsewhere, so it can be stored by the
+    /// helpers if it gets accepted.
+    fn supply(&self, url: &str, username: &str, password: &str) {
+        let mut credential = GitCredential::from_url(url, Some(username));
+        credential.set("password", password);
+        self.set_supplied(credential);
+    }
+
+    fn set_supplied(&self, credential: GitCredential) {
+        // libgit2 only asks for another credential if the previous one was
+        // rejected.
+        if let Some(previous) = self.supplied.replace(Some(credential)) {
+            self.report(GitCredentialAction::Reject, &previous);
+        }
+    }
+
+    /// Tells the helpers whether the last supplied credential worked.
+    fn finish<T>(&self, result: &Result<T, git2::Error>) {
+        let Some(credential) = self.supplied.take() else {
+            return;
+        };
+        match result {
+            Ok(_) => self.report(GitCredentialAction::Approve, &credential),
+            Err(err) if err.code() == git2::ErrorCode::Auth => {
+                self.report(GitCredentialAction::Reject, &credential);
+            }
+            // The credential may still be valid if the operation failed for
+            // other reasons.
+            Err(_) => {}
+        }
+    }
+
+    fn report(&self, action: GitCredentialAction, credential: &GitCredential) {
+        if let Err(err) = self.git_ctx.spawn_credential(action, credential) {
+            tracing::warn!(?action, ?err, "git credential failed");
+        }
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct Progress {
     /// `Some` iff data transfer is currently in progress
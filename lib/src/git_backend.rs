@@ -15,6 +15,8 @@
 #![allow(missing_docs)]
 
 use std::any::Any;
+use std::collections::BTreeMap;
+use std::collections::HashMap;
 use std::collections::HashSet;
 use std::fmt::Debug;
 use std::fmt::Error;
@@ -23,6 +25,7 @@ use std::fs;
 use std::io;
 use std::io::Cursor;
 use std::io::Read;
+use std::io::Write as _;
 use std::path::Path;
 use std::path::PathBuf;
 use std::process::Command;
@@ -35,7 +38,9 @@ use std::time::SystemTime;
 
 use async_trait::async_trait;
 use futures::stream::BoxStream;
+use gix::bstr::BStr;
 use gix::bstr::BString;
+use gix::bstr::ByteSlice as _;
 use gix::objs::CommitRef;
 use gix::objs::CommitRefIter;
 use gix::objs::WriteTo;
@@ -43,6 +48,7 @@ use itertools::Itertools;
 use pollster::FutureExt;
 use prost::Message;
 use smallvec::SmallVec;
+use tempfile::NamedTempFile;
 use thiserror::Error;
 
 use crate::backend::make_root_commit;
@@ -69,8 +75,11 @@ use crate::backend::Timestamp;
 use crate::backend::Tree;
 use crate::backend::TreeId;
 use crate::backend::TreeValue;
+use crate::config::ConfigGetError;
 use crate::file_util::IoResultExt as _;
 use crate::file_util::PathError;
+use crate::git_subprocess::GitSubprocessContext;
+use crate::git_subprocess::GitSubprocessError;
 use crate::index::Index;
 use crate::lock::FileLock;
 use crate::merge::Merge;
@@ -79,6 +88,7 @@ use crate::object_id::ObjectId;
 use crate::repo_path::RepoPath;
 use crate::repo_path::RepoPathBuf;
 use crate::repo_path::RepoPathComponentBuf;
+use crate::settings::GitSettings;
 use crate::settings::UserSettings;
 use crate::stacked_table::MutableTable;
 use crate::stacked_table::ReadonlyTable;
@@ -89,7 +99,16 @@ use crate::stacked_table::TableStoreError;
 const HASH_LENGTH: usize = 20;
 const CHANGE_ID_LENGTH: usize = 16;
 /// Ref namespace used only for preventing GC.
+///
+/// Refs directly in this namespace are managed by jj. Refs in subdirectories
+/// (such as `refs/jj/keep/ci/build-123`) are created by external tools, and
+/// are never deleted by jj.
 const NO_GC_REF_NAMESPACE: &str = "refs/jj/keep/";
+/// Ref namespace of the backups of commits hidden by destructive operations.
+///
+/// Each backup is stored as `refs/jj/backup/<number>/<commit id>`. Backups are
+/// numbered in the order they were created.
+const BACKUP_REF_NAMESPACE: &str = "refs/jj/backup/";
 const CONFLICT_SUFFIX: &str = ".jjconflict";
 
 const JJ_TREES_COMMIT_HEADER: &[u8] = b"jj:trees";
@@ -101,6 +120,8 @@ pub enum GitBackendInitError {
     #[error("Failed to open git repository")]
     OpenRepository(#[source] gix::open::Error),
     #[error(transparent)]
+    Config(ConfigGetError),
+    #[error(transparent)]
     Path(PathError),
 }
 
@@ -115,6 +136,8 @@ pub enum GitBackendLoadError {
     #[error("Failed to open git repository")]
     OpenRepository(#[source] gix::open::Error),
     #[error(transparent)]
+    Config(ConfigGetError),
+    #[error(transparent)]
     Path(PathError),
 }
 
@@ -159,6 +182,53 @@ pub struct GitBackend {
     empty_tree_id: TreeId,
     extra_metadata_store: TableStore,
     cached_extra_metadata: Mutex<Option<Arc<ReadonlyTable>>>,
+    /// File listing the no-gc refs to be deleted by the ongoing GC.
+    gc_pending_path: PathBuf,
+    /// Replacement objects read from `refs/replace/*` if enabled by the
+    /// `git.use-replace-refs` setting.
+    ///
+    /// The replacements only affect how commits are read from now on. Index
+    /// entries and extra metadata recorded while the setting had a different
+    /// value (or the replace refs pointed elsewhere) are kept as is.
+    replacements: HashMap<gix::ObjectId, gix::ObjectId>,
+    /// Remote to fetch missing objects from if the repo is a partial clone.
+    promisor_remote: Option<PromisorRemote>,
+}
+
+/// Backup of the heads of commits hidden by a destructive operation.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CommitBackup {
+    /// Sequence number identifying the backup.
+    pub number: u64,
+    /// Heads of the backed up commits.
+    pub heads: Vec<CommitId>,
+}
+
+/// Remote which provides the objects omitted from a partial clone.
+///
+/// Git records the name of the remote in the `extensions.partialClone` config.
+/// Since gix can't fetch missing objects on demand, they are fetched by the
+/// Git subprocess.
+#[derive(Debug)]
+struct PromisorRemote {
+    name: String,
+    git_settings: GitSettings,
+}
+
+impl PromisorRemote {
+    fn from_repo(
+        repo: &gix::ThreadSafeRepository,
+        settings: &UserSettings,
+    ) -> Result<Option<Self>, ConfigGetError> {
+        let repo = repo.to_thread_local();
+        let Some(name) = repo.config_snapshot().string("extensions.partialClone") else {
+            return Ok(None);
+        };
+        Ok(Some(PromisorRemote {
+            name: name.to_string(),
+            git_settings: GitSettings::from_settings(settings)?,
+        }))
+    }
 }
 
 impl GitBackend {
@@ -166,8 +236,20 @@ impl GitBackend {
         "git"
     }
 
-    fn new(base_repo: gix::ThreadSafeRepository, extra_metadata_store: TableStore) -> Self {
-        let repo = Mutex::new(base_repo.to_thread_local());
+    fn new(
+        base_repo: gix::ThreadSafeRepository,
+        store_path: &Path,
+        extra_metadata_store: TableStore,
+        use_replace_refs: bool,
+        promisor_remote: Option<PromisorRemote>,
+    ) -> Self {
+        let repo = base_repo.to_thread_local();
+        let replacements = if use_replace_refs {
+            read_replace_refs(&repo)
+        } else {
+            HashMap::new()
+        };
+        let repo = Mutex::new(repo);
         let root_commit_id = CommitId::from_bytes(&[0; HASH_LENGTH]);
         let root_change_id = ChangeId::from_bytes(&[0; CHANGE_ID_LENGTH]);
         let empty_tree_id = TreeId::from_hex("4b825dc642cb6eb9a060e54bf8d69288fbee4904");
@@ -179,6 +261,9 @@ impl GitBackend {
             empty_tree_id,
             extra_metadata_store,
             cached_extra_metadata: Mutex::new(None),
+            gc_pending_path: store_path.join("gc_pending"),
+            replacements,
+            promisor_remote,
         }
     }
 
@@ -194,7 +279,7 @@ impl GitBackend {
             gix_open_opts_from_settings(settings),
         )
         .map_err(GitBackendInitError::InitRepository)?;
-        Self::init_with_repo(store_path, git_repo_path, git_repo)
+        Self::init_with_repo(settings, store_path, git_repo_path, git_repo)
     }
 
     /// Initializes backend by creating a new Git repo at the specified
@@ -218,7 +303,7 @@ impl GitBackend {
         )
         .map_err(GitBackendInitError::InitRepository)?;
         let git_repo_path = workspace_root.join(".git");
-        Self::init_with_repo(store_path, &git_repo_path, git_repo)
+        Self::init_with_repo(settings, store_path, &git_repo_path, git_repo)
     }
 
     /// Initializes backend with an existing Git repo at the specified path.
@@ -238,10 +323,11 @@ impl GitBackend {
             gix_open_opts_from_settings(settings),
         )
         .map_err(GitBackendInitError::OpenRepository)?;
-        Self::init_with_repo(store_path, git_repo_path, git_repo)
+        Self::init_with_repo(settings, store_path, git_repo_path, git_repo)
     }
 
     fn init_with_repo(
+        settings: &UserSettings,
         store_path: &Path,
         git_repo_path: &Path,
         git_repo: gix::ThreadSafeRepository,
@@ -271,7 +357,18 @@ impl GitBackend {
                 .map_err(GitBackendInitError::Path)?;
         };
         let extra_metadata_store = TableStore::init(extra_path, HASH_LENGTH);
-        Ok(GitBackend::new(git_repo, extra_metadata_store))
+        let use_replace_refs = settings
+            .get_bool("git.use-replace-refs")
+            .map_err(GitBackendInitError::Config)?;
+        let promisor_remote =
+            PromisorRemote::from_repo(&git_repo, settings).map_err(GitBackendInitError::Config)?;
+        Ok(GitBackend::new(
+            git_repo,
+            store_path,
+            extra_metadata_store,
+            use_replace_refs,
+            promisor_remote,
+        ))
     }
 
     pub fn load(
@@ -294,13 +391,34 @@ impl GitBackend {
         )
         .map_err(GitBackendLoadError::OpenRepository)?;
         let extra_metadata_store = TableStore::load(store_path.join("extra"), HASH_LENGTH);
-        Ok(GitBackend::new(repo, extra_metadata_store))
+        let use_replace_refs = settings
+            .get_bool("git.use-replace-refs")
+            .map_err(GitBackendLoadError::Config)?;
+        let promisor_remote =
+            PromisorRemote::from_repo(&repo, settings).map_err(GitBackendLoadError::Config)?;
+        Ok(GitBackend::new(
+            repo,
+            store_path,
+            extra_metadata_store,
+            use_replace_refs,
+            promisor_remote,
+        ))
     }
 
     fn lock_git_repo(&self) -> MutexGuard<'_, gix::Repository> {
         self.repo.lock().unwrap()
     }
 
+    /// Returns the heads of the commits to be kept by GC.
+    fn heads_for_gc(&self, index: &dyn Index) -> BackendResult<HashSet<CommitId>> {
+        let heads = index
+            .all_heads_for_gc()
+            .map_err(|err| BackendError::Other(err.into()))?
+            .filter(|id| *id != self.root_commit_id)
+            .collect();
+        Ok(heads)
+    }
+
     /// Returns new thread-local instance to access to the underlying Git repo.
     pub fn git_repo(&self) -> gix::Repository {
         self.base_repo.to_thread_local()
@@ -316,6 +434,224 @@ impl GitBackend {
         self.base_repo.path()
     }
 
+    /// Name of the remote which the objects missing from a partial clone are
+    /// fetched from.
+    pub fn promisor_remote_name(&self) -> Option<&str> {
+        self.promisor_remote
+            .as_ref()
+            .map(|remote| remote.name.as_str())
+    }
+
+    /// Fetches the object from the promisor remote if it's missing from a
+    /// partial clone.
+    fn fetch_if_missing(&self, git_id: gix::ObjectId, id: &impl ObjectId) -> BackendResult<()> {
+        if self.promisor_remote.is_none() || self.lock_git_repo().has_object(git_id) {
+            return Ok(());
+        }
+        self.fetch_objects(&[git_id])
+            .map_err(|err| to_read_object_err(err, id))
+    }
+
+    fn fetch_objects(&self, ids: &[gix::ObjectId]) -> Result<(), GitSubprocessError> {
+        let Some(remote) = &self.promisor_remote else {
+            return Ok(());
+        };
+        tracing::debug!(
+            remote = remote.name,
+            count = ids.len(),
+            "fetching missing objects"
+        );
+        GitSubprocessContext::new(self.git_repo_path(), &remote.git_settings)
+            .spawn_fetch_objects(&remote.name, ids)
+    }
+
+    /// Returns the ids of the commits at the boundary of a shallow
+    /// repository. The list is empty if the repository isn't shallow.
+    pub fn shallow_commit_ids(&self) -> BackendResult<Vec<CommitId>> {
+        let shallow_commits = self
+            .lock_git_repo()
+            .shallow_commits()
+            .map_err(|err| BackendError::Other(err.into()))?;
+        Ok(shallow_commits
+            .iter()
+            .flat_map(|ids| ids.iter())
+            .map(|id| CommitId::from_bytes(id.as_bytes()))
+            .collect())
+    }
+
+    /// Returns the `commit_ids` which have parents in the Git repository but
+    /// aren't at its shallow boundary.
+    ///
+    /// These commits used to be at the shallow boundary if they're recorded
+    /// with the root commit as their only parent, e.g. in the commit index.
+    pub fn deepened_commit_ids<'a>(
+        &self,
+        commit_ids: impl IntoIterator<Item = &'a CommitId>,
+    ) -> BackendResult<Vec<CommitId>> {
+        let shallow_commit_ids: HashSet<_> = self.shallow_commit_ids()?.into_iter().collect();
+        let locked_repo = self.lock_git_repo();
+        let mut deepened_ids = vec![];
+        for id in commit_ids {
+            if *id == self.root_commit_id || shallow_commit_ids.contains(id) {
+                continue;
+            }
+            let git_commit_id = validate_git_object_id(id)?;
+            let commit = locked_repo
+                .find_object(git_commit_id)
+                .map_err(|err| map_not_found_err(err, id))?
+                .try_into_commit()
+                .map_err(|err| to_read_object_err(err, id))?;
+            if commit.parent_ids().next().is_some() {
+                deepened_ids.push(id.clone());
+            }
+        }
+        Ok(deepened_ids)
+    }
+
+    /// Returns the Git tree and parents of the commit, which doesn't have to
+    /// be imported.
+    pub fn read_git_tree_and_parents(
+        &self,
+        id: &CommitId,
+    ) -> BackendResult<(TreeId, Vec<CommitId>)> {
+        let locked_repo = self.lock_git_repo();
+        let commit = find_commit_object(&locked_repo, &self.replacements, id)?
+            .try_into_commit()
+            .map_err(|err| to_read_object_err(err, id))?;
+        let tree_id = commit
+            .tree_id()
+            .map_err(|err| to_read_object_err(err, id))?;
+        let parent_ids = commit
+            .parent_ids()
+            .map(|oid| CommitId::from_bytes(oid.as_bytes()))
+            .collect();
+        Ok((TreeId::from_bytes(tree_id.as_bytes()), parent_ids))
+    }
+
+    /// Returns the ids of the commits pointed to by the no-gc refs created by
+    /// external tools in `refs/jj/keep/<name>/`.
+    pub fn externally_kept_commit_ids(&self) -> BackendResult<Vec<CommitId>> {
+        let git_repo = self.lock_git_repo();
+        let git_references = git_repo
+            .references()
+            .map_err(|err| BackendError::Other(err.into()))?;
+        let no_gc_refs_iter = git_references
+            .prefixed(NO_GC_REF_NAMESPACE)
+            .map_err(|err| BackendError::Other(err.into()))?;
+        let mut commit_ids = vec![];
+        for git_ref in no_gc_refs_iter {
+            let mut git_ref = git_ref.map_err(BackendError::Other)?;
+            if !is_external_no_gc_ref(git_ref.name().as_bstr()) {
+                continue;
+            }
+            // Refs to non-commit objects are ignored.
+            if let Ok(commit) = git_ref.peel_to_commit() {
+                commit_ids.push(CommitId::from_bytes(commit.id().as_bytes()));
+            }
+        }
+        Ok(commit_ids)
+    }
+
+    /// Returns the ids of the heads which would no longer be protected from
+    /// GC if it ran now.
+    pub fn gc_candidate_commit_ids(
+        &self,
+        index: &dyn Index,
+        keep_newer: SystemTime,
+    ) -> BackendResult<Vec<CommitId>> {
+        let git_repo = self.lock_git_repo();
+        let heads = self.heads_for_gc(index)?;
+        let refs_to_delete = find_no_gc_refs_to_delete(&git_repo, &heads, keep_newer)?;
+        let ids = refs_to_delete
+            .iter()
+            .map(|git_ref| {
+                let oid = git_ref.target.try_id().expect("no-gc ref should be direct");
+                CommitId::from_bytes(oid.as_bytes())
+            })
+            .filter(|id| !heads.contains(id))
+            .unique()
+            .collect();
+        Ok(ids)
+    }
+
+    /// Returns the backups of hidden commits, oldest first.
+    pub fn backups(&self) -> BackendResult<Vec<CommitBackup>> {
+        let git_repo = self.lock_git_repo();
+        let git_references = git_repo
+            .references()
+            .map_err(|err| BackendError::Other(err.into()))?;
+        let backup_refs_iter = git_references
+            .prefixed(BACKUP_REF_NAMESPACE)
+            .map_err(|err| BackendError::Other(err.into()))?;
+        let mut backups: BTreeMap<u64, Vec<CommitId>> = BTreeMap::new();
+        for git_ref in backup_refs_iter {
+            let git_ref = git_ref.map_err(BackendError::Other)?;
+            let name = &git_ref.name().as_bstr()[BACKUP_REF_NAMESPACE.len()..];
+            // Refs not created by jj are ignored.
+            let Some(number) = name
+                .split_once_str("/")
+                .and_then(|(number, _)| number.to_str().ok()?.parse().ok())
+            else {
+                continue;
+            };
+            if let Some(oid) = git_ref.target().try_id() {
+                let id = CommitId::from_bytes(oid.as_bytes());
+                backups.entry(number).or_default().push(id);
+            }
+        }
+        let backups = backups
+            .into_iter()
+            .map(|(number, heads)| CommitBackup { number, heads })
+            .collect();
+        Ok(backups)
+    }
+
+    /// Creates a backup of the `heads` and their ancestors, which protects
+    /// them from GC.
+    pub fn write_backup(&self, heads: &[CommitId], message: &str) -> BackendResult<CommitBackup> {
+        let number = self.backups()?.last().map_or(1, |backup| backup.number + 1);
+        let ref_edits = heads.iter().map(|id| gix::refs::transaction::RefEdit {
+            change: gix::refs::transaction::Change::Update {
+                log: gix::refs::transaction::LogChange {
+                    message: message.into(),
+                    ..Default::default()
+                },
+                expected: gix::refs::transaction::PreviousValue::MustNotExist,
+                new: gix::refs::Target::Object(gix::ObjectId::from_bytes_or_panic(id.as_bytes())),
+            },
+            name: format!("{BACKUP_REF_NAMESPACE}{number}/{id}")
+                .try_into()
+                .unwrap(),
+            deref: false,
+        });
+        self.lock_git_repo()
+            .edit_references(ref_edits)
+            .map_err(|err| BackendError::Other(err.into()))?;
+        Ok(CommitBackup {
+            number,
+            heads: heads.to_vec(),
+        })
+    }
+
+    /// Deletes the backup, which allows the commits to be garbage-collected.
+    pub fn remove_backup(&self, backup: &CommitBackup) -> BackendResult<()> {
+        let git_repo = self.lock_git_repo();
+        let mut ref_edits = vec![];
+        for id in &backup.heads {
+            let name = format!("{BACKUP_REF_NAMESPACE}{}/{id}", backup.number);
+            if let Some(git_ref) = git_repo
+                .try_find_reference(&name)
+                .map_err(|err| BackendError::Other(err.into()))?
+            {
+                ref_edits.push(to_ref_deletion(git_ref.detach()));
+            }
+        }
+        git_repo
+            .edit_references(ref_edits)
+            .map_err(|err| BackendError::Other(err.into()))?;
+        Ok(())
+    }
+
     /// Path to the working directory if the repository isn't bare.
     pub fn git_workdir(&self) -> Option<&Path> {
         self.base_repo.work_dir()
@@ -336,6 +672,15 @@ impl GitBackend {
         }
     }
 
+    /// Returns true if the extra metadata table has an entry for the commit.
+    ///
+    /// Unlike reading the commit, this doesn't import the missing entry, so it
+    /// can be used to check the consistency of the table.
+    pub fn has_extra_metadata(&self, id: &CommitId) -> BackendResult<bool> {
+        let table = self.cached_extra_metadata_table()?;
+        Ok(table.get_value(id.as_bytes()).is_some())
+    }
+
     fn read_extra_metadata_table_locked(&self) -> BackendResult<(Arc<ReadonlyTable>, FileLock)> {
         let table = self
             .extra_metadata_store
@@ -368,13 +713,30 @@ impl GitBackend {
         &self,
         head_ids: impl IntoIterator<Item = &'a CommitId>,
     ) -> BackendResult<()> {
-        self.import_head_commits_with_tree_conflicts(head_ids, true)
+        self.import_head_commits_with_tree_conflicts(head_ids, true, &mut |_, _| None)
+    }
+
+    /// Imports the given commits and ancestors like
+    /// [`GitBackend::import_head_commits()`], recording commits that were
+    /// rewritten outside of jj.
+    ///
+    /// `rewritten_from` is called with the id and contents of each commit that
+    /// hasn't been imported yet. If it returns the change id and commit id of the commit the new
+    /// commit was rewritten from, the new commit inherits the change id and
+    /// records the old commit as its predecessor.
+    pub fn import_head_commits_with_rewrites<'a>(
+        &self,
+        head_ids: impl IntoIterator<Item = &'a CommitId>,
+        mut rewritten_from: impl FnMut(&CommitId, &Commit) -> Option<(ChangeId, CommitId)>,
+    ) -> BackendResult<()> {
+        self.import_head_commits_with_tree_conflicts(head_ids, true, &mut rewritten_from)
     }
 
     fn import_head_commits_with_tree_conflicts<'a>(
         &self,
         head_ids: impl IntoIterator<Item = &'a CommitId>,
         uses_tree_conflict_format: bool,
+        rewritten_from: &mut RewrittenFromFn,
     ) -> BackendResult<()> {
         let head_ids: HashSet<&CommitId> = head_ids
             .into_iter()
@@ -401,16 +763,19 @@ impl GitBackend {
         let mut mut_table = table.start_mutation();
         import_extra_metadata_entries_from_heads(
             &locked_repo,
+            &self.replacements,
             &mut mut_table,
             &table_lock,
             &head_ids,
             uses_tree_conflict_format,
+            rewritten_from,
         )?;
         self.save_extra_metadata_table(mut_table, &table_lock)
     }
 
     fn read_file_sync(&self, id: &FileId) -> BackendResult<Box<dyn Read>> {
         let git_blob_id = validate_git_object_id(id)?;
+        self.fetch_if_missing(git_blob_id, id)?;
         let locked_repo = self.lock_git_repo();
         let mut blob = locked_repo
             .find_object(git_blob_id)
@@ -463,6 +828,9 @@ impl GitBackend {
     }
 }
 
+/// Returns the change id and predecessor of a commit imported from Git.
+type RewrittenFromFn<'a> = dyn FnMut(&CommitId, &Commit) -> Option<(ChangeId, CommitId)> + 'a;
+
 /// Canonicalizes the given `path` except for the last `".git"` component.
 ///
 /// The last path component matters when opening a Git repo without `core.bare`
@@ -498,6 +866,38 @@ fn gix_open_opts_from_settings(settings: &UserSettings) -> gix::open::Options {
         .lossy_config(false)
 }
 
+/// Collects `refs/replace/*` refs as a map from the replaced object id to the
+/// replacement object id. Malformed refs are ignored as Git does.
+fn read_replace_refs(git_repo: &gix::Repository) -> HashMap<gix::ObjectId, gix::ObjectId> {
+    let Ok(platform) = git_repo.references() else {
+        return HashMap::new();
+    };
+    let Ok(refs) = platform.prefixed("refs/replace/") else {
+        return HashMap::new();
+    };
+    refs.filter_map(Result::ok)
+        .filter_map(|git_ref| {
+            let name = git_ref.name().as_bstr().strip_prefix(b"refs/replace/")?;
+            let source = gix::ObjectId::from_hex(name).ok()?;
+            let target = git_ref.target().try_id()?.to_owned();
+            Some((source, target))
+        })
+        .collect()
+}
+
+/// Looks up the commit object, or its replacement object if any.
+fn find_commit_object<'repo>(
+    git_repo: &'repo gix::Repository,
+    replacements: &HashMap<gix::ObjectId, gix::ObjectId>,
+    id: &CommitId,
+) -> BackendResult<gix::Object<'repo>> {
+    let git_commit_id = validate_git_object_id(id)?;
+    let git_object_id = replacements.get(&git_commit_id).unwrap_or(&git_commit_id);
+    git_repo
+        .find_object(*git_object_id)
+        .map_err(|err| map_not_found_err(err, id))
+}
+
 /// Reads the `jj:trees` header from the commit.
 fn root_tree_from_header(git_commit: &CommitRef) -> Result<Option<MergedTreeId>, ()> {
     for (key, value) in &git_commit.extra_headers {
@@ -545,6 +945,7 @@ fn commit_from_git_without_root_parent(
     // shallow commits don't have parents their parents actually fetched, so we
     // discard them here
     // TODO: This causes issues when a shallow repository is deepened/unshallowed
+    // unless the index is rebuilt afterwards
     let parents = if is_shallow {
         vec![]
     } else {
@@ -565,12 +966,14 @@ fn commit_from_git_without_root_parent(
             MergedTreeId::Legacy(tree_id)
         }
     });
-    // Use lossy conversion as commit message with "mojibake" is still better than
-    // nothing.
-    // TODO: what should we do with commit.encoding?
-    let description = String::from_utf8_lossy(commit.message).into_owned();
-    let author = signature_from_git(commit.author());
-    let committer = signature_from_git(commit.committer());
+    // Commits written with i18n.commitEncoding declare the encoding of the
+    // message and signatures. Unknown encodings are treated as UTF-8.
+    let encoding = commit
+        .encoding
+        .and_then(|label| encoding_rs::Encoding::for_label(label));
+    let description = decode_text(commit.message, encoding);
+    let author = signature_from_git(commit.author(), encoding);
+    let committer = signature_from_git(commit.committer(), encoding);
 
     // If the commit is signed, extract both the signature and the signed data
     // (which is the commit buffer with the gpgsig header omitted).
@@ -607,16 +1010,28 @@ fn commit_from_git_without_root_parent(
 
 const EMPTY_STRING_PLACEHOLDER: &str = "JJ_EMPTY_STRING";
 
-fn signature_from_git(signature: gix::actor::SignatureRef) -> Signature {
+/// Decodes commit text in the given encoding, or in UTF-8 if unspecified.
+///
+/// Uses lossy conversion as text with "mojibake" is still better than nothing.
+fn decode_text(bytes: &[u8], encoding: Option<&'static encoding_rs::Encoding>) -> String {
+    let encoding = encoding.unwrap_or(encoding_rs::UTF_8);
+    let (text, _) = encoding.decode_without_bom_handling(bytes);
+    text.into_owned()
+}
+
+fn signature_from_git(
+    signature: gix::actor::SignatureRef,
+    encoding: Option<&'static encoding_rs::Encoding>,
+) -> Signature {
     let name = signature.name;
     let name = if name != EMPTY_STRING_PLACEHOLDER {
-        String::from_utf8_lossy(name).into_owned()
+        decode_text(name, encoding)
     } else {
         "".to_string()
     };
     let email = signature.email;
     let email = if email != EMPTY_STRING_PLACEHOLDER {
-        String::from_utf8_lossy(email).into_owned()
+        decode_text(email, encoding)
     } else {
         "".to_string()
     };
@@ -724,6 +1139,12 @@ fn to_no_gc_ref_update(id: &CommitId) -> gix::refs::transaction::RefEdit {
     }
 }
 
+/// Returns true if the no-gc ref of the given full `name` was created by an
+/// external tool.
+fn is_external_no_gc_ref(name: &BStr) -> bool {
+    name[NO_GC_REF_NAMESPACE.len()..].contains(&b'/')
+}
+
 fn to_ref_deletion(git_ref: gix::refs::Reference) -> gix::refs::transaction::RefEdit {
     let expected = gix::refs::transaction::PreviousValue::ExistingMustMatch(git_ref.target);
     gix::refs::transaction::RefEdit {
@@ -736,15 +1157,14 @@ fn to_ref_deletion(git_ref: gix::refs::Reference) -> gix::refs::transaction::Ref
     }
 }
 
-/// Recreates `refs/jj/keep` refs for the `new_heads`, and removes the other
-/// unreachable and non-head refs.
-fn recreate_no_gc_refs(
+/// Returns the `refs/jj/keep` refs which are neither for the `new_heads` nor
+/// newer than `keep_newer`.
+fn find_no_gc_refs_to_delete(
     git_repo: &gix::Repository,
-    new_heads: impl IntoIterator<Item = CommitId>,
+    new_heads: &HashSet<CommitId>,
     keep_newer: SystemTime,
-) -> BackendResult<()> {
+) -> BackendResult<Vec<gix::refs::Reference>> {
     // Calculate diff between existing no-gc refs and new heads.
-    let new_heads: HashSet<CommitId> = new_heads.into_iter().collect();
     let mut no_gc_refs_to_keep_count: usize = 0;
     let mut no_gc_refs_to_delete: Vec<gix::refs::Reference> = Vec::new();
     let git_references = git_repo
@@ -755,6 +1175,10 @@ fn recreate_no_gc_refs(
         .map_err(|err| BackendError::Other(err.into()))?;
     for git_ref in no_gc_refs_iter {
         let git_ref = git_ref.map_err(BackendError::Other)?.detach();
+        if is_external_no_gc_ref(git_ref.name.as_bstr()) {
+            tracing::trace!(?git_ref, "not deleting external");
+            continue;
+        }
         let oid = git_ref.target.try_id().ok_or_else(|| {
             let name = git_ref.name.as_bstr();
             BackendError::Other(format!("Symbolic no-gc ref found: {name}").into())
@@ -793,10 +1217,81 @@ fn recreate_no_gc_refs(
         no_gc_refs_to_delete_count = no_gc_refs_to_delete.len(),
         "collected reachable refs"
     );
+    Ok(no_gc_refs_to_delete)
+}
 
+/// Writes the `refs/jj/keep` refs to be deleted to the deferred deletion list
+/// at `path`.
+fn write_gc_pending_refs(path: &Path, refs: &[gix::refs::Reference]) -> BackendResult<()> {
+    let mut content = String::new();
+    for git_ref in refs {
+        let oid = git_ref.target.try_id().expect("no-gc ref should be direct");
+        content.push_str(&format!("{oid} {}\n", git_ref.name.as_bstr()));
+    }
+    let dir = path.parent().unwrap();
+    let mut temp_file = NamedTempFile::new_in(dir)
+        .context(dir)
+        .map_err(|err| BackendError::Other(err.into()))?;
+    temp_file
+        .write_all(content.as_bytes())
+        .context(temp_file.path())
+        .map_err(|err| BackendError::Other(err.into()))?;
+    temp_file
+        .persist(path)
+        .map_err(|err| BackendError::Other(err.into()))?;
+    Ok(())
+}
+
+/// Reads the deferred deletion list at `path`. Returns `None` if there's no
+/// GC in progress.
+///
+/// Refs which have been deleted or moved since the list was written are
+/// omitted.
+fn read_gc_pending_refs(
+    git_repo: &gix::Repository,
+    path: &Path,
+) -> BackendResult<Option<Vec<gix::refs::Reference>>> {
+    let content = match fs::read_to_string(path).context(path) {
+        Ok(content) => content,
+        Err(err) if err.error.kind() == io::ErrorKind::NotFound => return Ok(None),
+        Err(err) => return Err(BackendError::Other(err.into())),
+    };
+    let mut refs = Vec::new();
+    for line in content.lines() {
+        let invalid_line =
+            || BackendError::Other(format!("Invalid GC pending line: {line}").into());
+        let (hex, name) = line.split_once(' ').ok_or_else(invalid_line)?;
+        let oid = gix::ObjectId::from_hex(hex.as_bytes()).map_err(|_| invalid_line())?;
+        let Some(git_ref) = git_repo
+            .try_find_reference(name)
+            .map_err(|err| BackendError::Other(err.into()))?
+        else {
+            continue;
+        };
+        let git_ref = git_ref.detach();
+        if git_ref.target.try_id() == Some(oid.as_ref()) {
+            refs.push(git_ref);
+        }
+    }
+    Ok(Some(refs))
+}
+
+/// Recreates `refs/jj/keep` refs for the `new_heads`, and removes the
+/// `refs_to_delete` which aren't for the `new_heads`.
+fn recreate_no_gc_refs(
+    git_repo: &gix::Repository,
+    new_heads: &HashSet<CommitId>,
+    refs_to_delete: Vec<gix::refs::Reference>,
+) -> BackendResult<()> {
+    let refs_to_delete = refs_to_delete.into_iter().filter(|git_ref| {
+        let oid = git_ref.target.try_id().expect("no-gc ref should be direct");
+        let id = CommitId::from_bytes(oid.as_bytes());
+        let name_good = git_ref.name.as_bstr()[NO_GC_REF_NAMESPACE.len()..] == id.hex();
+        !(new_heads.contains(&id) && name_good)
+    });
     // It's slow to delete packed refs one by one, so update refs all at once.
     let ref_edits = itertools::chain(
-        no_gc_refs_to_delete.into_iter().map(to_ref_deletion),
+        refs_to_delete.map(to_ref_deletion),
         new_heads.iter().map(to_no_gc_ref_update),
     );
     git_repo
@@ -866,10 +1361,12 @@ fn to_invalid_utf8_err(source: str::Utf8Error, id: &impl ObjectId) -> BackendErr
 
 fn import_extra_metadata_entries_from_heads(
     git_repo: &gix::Repository,
+    replacements: &HashMap<gix::ObjectId, gix::ObjectId>,
     mut_table: &mut MutableTable,
     _table_lock: &FileLock,
     head_ids: &HashSet<&CommitId>,
     uses_tree_conflict_format: bool,
+    rewritten_from: &mut RewrittenFromFn,
 ) -> BackendResult<()> {
     let shallow_commits = git_repo
         .shallow_commits()
@@ -881,21 +1378,24 @@ fn import_extra_metadata_entries_from_heads(
         .map(|&id| id.clone())
         .collect_vec();
     while let Some(id) = work_ids.pop() {
-        let git_object = git_repo
-            .find_object(validate_git_object_id(&id)?)
-            .map_err(|err| map_not_found_err(err, &id))?;
+        let git_commit_id = validate_git_object_id(&id)?;
+        let git_object = find_commit_object(git_repo, replacements, &id)?;
         let is_shallow = shallow_commits
             .as_ref()
-            .is_some_and(|shallow| shallow.contains(&git_object.id));
+            .is_some_and(|shallow| shallow.contains(&git_commit_id));
         // TODO(#1624): Should we read the root tree here and check if it has a
         // `.jjconflict-...` entries? That could happen if the user used `git` to e.g.
         // change the description of a commit with tree-level conflicts.
-        let commit = commit_from_git_without_root_parent(
+        let mut commit = commit_from_git_without_root_parent(
             &id,
             &git_object,
             uses_tree_conflict_format,
             is_shallow,
         )?;
+        if let Some((change_id, predecessor_id)) = rewritten_from(&id, &commit) {
+            commit.change_id = change_id;
+            commit.predecessors = vec![predecessor_id];
+        }
         mut_table.add_entry(id.to_bytes(), serialize_extras(&commit));
         work_ids.extend(
             commit
@@ -953,6 +1453,46 @@ impl Backend for GitBackend {
         self.read_file_sync(id)
     }
 
+    async fn contains_file(&self, _path: &RepoPath, id: &FileId) -> BackendResult<bool> {
+        let git_blob_id = validate_git_object_id(id)?;
+        Ok(self.lock_git_repo().has_object(git_blob_id))
+    }
+
+    async fn file_size(&self, _path: &RepoPath, id: &FileId) -> BackendResult<u64> {
+        let git_blob_id = validate_git_object_id(id)?;
+        self.fetch_if_missing(git_blob_id, id)?;
+        let header = self
+            .lock_git_repo()
+            .find_header(git_blob_id)
+            .map_err(|err| map_not_found_err(err, id))?;
+        if header.kind() != gix::object::Kind::Blob {
+            return Err(to_read_object_err(
+                format!("Expected blob, got {}", header.kind()),
+                id,
+            ));
+        }
+        Ok(header.size())
+    }
+
+    /// Fetches the files which were omitted from a partial clone at once.
+    /// Missing files would otherwise be fetched one by one when they're read.
+    async fn prefetch_files(&self, ids: &[FileId]) -> BackendResult<()> {
+        if self.promisor_remote.is_none() {
+            return Ok(());
+        }
+        let git_ids: Vec<_> = ids.iter().map(validate_git_object_id).try_collect()?;
+        let missing_ids = {
+            let locked_repo = self.lock_git_repo();
+            git_ids
+                .into_iter()
+                .filter(|id| !locked_repo.has_object(id))
+                .unique()
+                .collect_vec()
+        };
+        self.fetch_objects(&missing_ids)
+            .map_err(|err| BackendError::Other(err.into()))
+    }
+
     async fn write_file(
         &self,
         _path: &RepoPath,
@@ -972,6 +1512,7 @@ impl Backend for GitBackend {
 
     async fn read_symlink(&self, _path: &RepoPath, id: &SymlinkId) -> BackendResult<String> {
         let git_blob_id = validate_git_object_id(id)?;
+        self.fetch_if_missing(git_blob_id, id)?;
         let locked_repo = self.lock_git_repo();
         let mut blob = locked_repo
             .find_object(git_blob_id)
@@ -1161,14 +1702,12 @@ impl Backend for GitBackend {
 
         let mut commit = {
             let locked_repo = self.lock_git_repo();
-            let git_object = locked_repo
-                .find_object(git_commit_id)
-                .map_err(|err| map_not_found_err(err, id))?;
+            let git_object = find_commit_object(&locked_repo, &self.replacements, id)?;
             let is_shallow = locked_repo
                 .shallow_commits()
                 .ok()
                 .flatten()
-                .is_some_and(|shallow| shallow.contains(&git_object.id));
+                .is_some_and(|shallow| shallow.contains(&git_commit_id));
             commit_from_git_without_root_parent(id, &git_object, false, is_shallow)?
         };
         if commit.parents.is_empty() {
@@ -1385,12 +1924,34 @@ impl Backend for GitBackend {
 
     #[tracing::instrument(skip(self, index))]
     fn gc(&self, index: &dyn Index, keep_newer: SystemTime) -> BackendResult<()> {
+        self.prepare_gc(index, keep_newer)?;
+        self.finish_gc(index, keep_newer)
+    }
+
+    #[tracing::instrument(skip(self, index))]
+    fn prepare_gc(&self, index: &dyn Index, keep_newer: SystemTime) -> BackendResult<()> {
         let git_repo = self.lock_git_repo();
-        let new_heads = index
-            .all_heads_for_gc()
-            .map_err(|err| BackendError::Other(err.into()))?
-            .filter(|id| *id != self.root_commit_id);
-        recreate_no_gc_refs(&git_repo, new_heads, keep_newer)?;
+        let heads = self.heads_for_gc(index)?;
+        let refs_to_delete = find_no_gc_refs_to_delete(&git_repo, &heads, keep_newer)?;
+        write_gc_pending_refs(&self.gc_pending_path, &refs_to_delete)
+    }
+
+    #[tracing::instrument(skip(self, index))]
+    fn finish_gc(&self, index: &dyn Index, keep_newer: SystemTime) -> BackendResult<()> {
+        let git_repo = self.lock_git_repo();
+        let heads = self.heads_for_gc(index)?;
+        // The deletion candidates were determined by an older index, so
+        // the ones reachable from the fresh heads are kept.
+        let refs_to_delete = match read_gc_pending_refs(&git_repo, &self.gc_pending_path)? {
+            Some(refs) => refs,
+            None => find_no_gc_refs_to_delete(&git_repo, &heads, keep_newer)?,
+        };
+        recreate_no_gc_refs(&git_repo, &heads, refs_to_delete)?;
+        match fs::remove_file(&self.gc_pending_path).context(&self.gc_pending_path) {
+            Ok(()) => {}
+            Err(err) if err.error.kind() == io::ErrorKind::NotFound => {}
+            Err(err) => return Err(BackendError::Other(err.into())),
+        }
         // TODO: remove unreachable entries from extras table if segment file
         // mtime <= keep_newer? (it won't be consistent with no-gc refs
         // preserved by the keep_newer timestamp though)
@@ -1534,12 +2095,16 @@ fn bytes_vec_from_json(value: &serde_json::Value) -> Vec<u8> {
 
 #[cfg(test)]
 mod tests {
+    use std::slice;
+
     use assert_matches::assert_matches;
     use hex::ToHex;
     use pollster::FutureExt;
     use test_case::test_case;
 
     use super::*;
+    use crate::config::ConfigLayer;
+    use crate::config::ConfigSource;
     use crate::config::StackedConfig;
     use crate::content_hash::blake2b_hash;
     use crate::tests::new_temp_dir;
@@ -1657,7 +2222,11 @@ mod tests {
 
         // Import the head commit and its ancestors
         backend
-            .import_head_commits_with_tree_conflicts([&commit_id2], uses_tree_conflict_format)
+            .import_head_commits_with_tree_conflicts(
+                [&commit_id2],
+                uses_tree_conflict_format,
+                &mut |_, _| None,
+            )
             .unwrap();
         // Ref should be created only for the head commit
         let git_refs = backend
@@ -1798,6 +2367,160 @@ mod tests {
         );
     }
 
+    #[test]
+    fn read_git_commit_with_replace_refs() {
+        let temp_dir = new_temp_dir();
+        let git_repo_path = temp_dir.path().join("git");
+        let git_repo = git_init(&git_repo_path);
+
+        let signature = gix::actor::Signature {
+            name: GIT_USER.into(),
+            email: GIT_EMAIL.into(),
+            time: gix::date::Time::now_utc(),
+        };
+        let empty_tree_id =
+            gix::ObjectId::from_hex(b"4b825dc642cb6eb9a060e54bf8d69288fbee4904").unwrap();
+        let write_commit = |ref_name: &str, message: &str, parents: &[gix::ObjectId]| {
+            git_repo
+                .commit_as(
+                    &signature,
+                    &signature,
+                    ref_name,
+                    message,
+                    empty_tree_id,
+                    parents.iter().copied(),
+                )
+                .unwrap()
+                .detach()
+        };
+        let git_commit_id1 = write_commit("refs/heads/main", "commit1", &[]);
+        let git_commit_id2 = write_commit("refs/heads/main", "commit2", &[git_commit_id1]);
+        let git_commit_id3 = write_commit("refs/heads/main", "commit3", &[git_commit_id2]);
+        // Graft commit2 onto the root
+        let git_graft_id = write_commit("refs/heads/graft", "commit2 grafted", &[]);
+        git_repo
+            .reference(
+                format!("refs/replace/{git_commit_id2}"),
+                git_graft_id,
+                gix::refs::transaction::PreviousValue::MustNotExist,
+                "replace",
+            )
+            .unwrap();
+        let commit_id1 = CommitId::from_bytes(git_commit_id1.as_bytes());
+        let commit_id2 = CommitId::from_bytes(git_commit_id2.as_bytes());
+        let commit_id3 = CommitId::from_bytes(git_commit_id3.as_bytes());
+
+        // Replace refs are ignored by default
+        let store_path = temp_dir.path().join("store1");
+        fs::create_dir(&store_path).unwrap();
+        let settings = user_settings();
+        let backend = GitBackend::init_external(&settings, &store_path, git_repo.path()).unwrap();
+        let commit = backend.read_commit(&commit_id2).block_on().unwrap();
+        assert_eq!(commit.description, "commit2");
+        assert_eq!(commit.parents, vec![commit_id1.clone()]);
+
+        let store_path = temp_dir.path().join("store2");
+        fs::create_dir(&store_path).unwrap();
+        let mut config = StackedConfig::with_defaults();
+        config.add_layer(
+            ConfigLayer::parse(ConfigSource::User, "git.use-replace-refs = true").unwrap(),
+        );
+        let settings = UserSettings::from_config(config).unwrap();
+        let backend = GitBackend::init_external(&settings, &store_path, git_repo.path()).unwrap();
+        backend.import_head_commits([&commit_id3]).unwrap();
+        // The replaced commit is presented with its original id
+        let commit = backend.read_commit(&commit_id2).block_on().unwrap();
+        assert_eq!(commit.description, "commit2 grafted");
+        assert_eq!(commit.parents, vec![backend.root_commit_id().clone()]);
+        let commit = backend.read_commit(&commit_id3).block_on().unwrap();
+        assert_eq!(commit.parents, vec![commit_id2.clone()]);
+        // The original parent of the replaced commit isn't imported
+        let table = backend.cached_extra_metadata_table().unwrap();
+        assert!(table.get_value(commit_id2.as_bytes()).is_some());
+        assert!(table.get_value(commit_id1.as_bytes()).is_none());
+    }
+
+    #[test]
+    fn read_git_commit_after_toggling_replace_refs() {
+        let temp_dir = new_temp_dir();
+        let git_repo_path = temp_dir.path().join("git");
+        let git_repo = git_init(&git_repo_path);
+
+        let signature = gix::actor::Signature {
+            name: GIT_USER.into(),
+            email: GIT_EMAIL.into(),
+            time: gix::date::Time::now_utc(),
+        };
+        let empty_tree_id =
+            gix::ObjectId::from_hex(b"4b825dc642cb6eb9a060e54bf8d69288fbee4904").unwrap();
+        let write_commit = |ref_name: &str, message: &str, parents: &[gix::ObjectId]| {
+            git_repo
+                .commit_as(
+                    &signature,
+                    &signature,
+                    ref_name,
+                    message,
+                    empty_tree_id,
+                    parents.iter().copied(),
+                )
+                .unwrap()
+                .detach()
+        };
+        let git_commit_id1 = write_commit("refs/heads/main", "commit1", &[]);
+        let git_commit_id2 = write_commit("refs/heads/main", "commit2", &[git_commit_id1]);
+        let git_graft_id = write_commit("refs/heads/graft", "commit2 grafted", &[]);
+        git_repo
+            .reference(
+                format!("refs/replace/{git_commit_id2}"),
+                git_graft_id,
+                gix::refs::transaction::PreviousValue::MustNotExist,
+                "replace",
+            )
+            .unwrap();
+        let commit_id1 = CommitId::from_bytes(git_commit_id1.as_bytes());
+        let commit_id2 = CommitId::from_bytes(git_commit_id2.as_bytes());
+
+        let settings_with_replace_refs = |enabled: bool| {
+            let mut config = StackedConfig::with_defaults();
+            config.add_layer(
+                ConfigLayer::parse(
+                    ConfigSource::User,
+                    &format!("git.use-replace-refs = {enabled}"),
+                )
+                .unwrap(),
+            );
+            UserSettings::from_config(config).unwrap()
+        };
+
+        // Import the history with replace refs disabled
+        let store_path = temp_dir.path().join("store");
+        fs::create_dir(&store_path).unwrap();
+        let settings = settings_with_replace_refs(false);
+        let backend = GitBackend::init_external(&settings, &store_path, git_repo.path()).unwrap();
+        backend.import_head_commits([&commit_id2]).unwrap();
+        let commit = backend.read_commit(&commit_id2).block_on().unwrap();
+        assert_eq!(commit.parents, vec![commit_id1.clone()]);
+        drop(backend);
+
+        // Once enabled, the replacement is read from the same store
+        let settings = settings_with_replace_refs(true);
+        let backend = GitBackend::load(&settings, &store_path).unwrap();
+        let commit = backend.read_commit(&commit_id2).block_on().unwrap();
+        assert_eq!(commit.description, "commit2 grafted");
+        assert_eq!(commit.parents, vec![backend.root_commit_id().clone()]);
+        // The extra metadata imported before isn't rewritten
+        let table = backend.cached_extra_metadata_table().unwrap();
+        assert!(table.get_value(commit_id1.as_bytes()).is_some());
+        drop(backend);
+
+        // Disabling it again restores the original commit
+        let settings = settings_with_replace_refs(false);
+        let backend = GitBackend::load(&settings, &store_path).unwrap();
+        let commit = backend.read_commit(&commit_id2).block_on().unwrap();
+        assert_eq!(commit.description, "commit2");
+        assert_eq!(commit.parents, vec![commit_id1.clone()]);
+    }
+
     #[test]
     fn read_signed_git_commit() {
         let settings = user_settings();
@@ -1851,6 +2574,53 @@ mod tests {
         assert_eq!(std::str::from_utf8(&sig.data).unwrap(), commit_str);
     }
 
+    #[test]
+    fn read_git_commit_with_encoding() {
+        let settings = user_settings();
+        let temp_dir = new_temp_dir();
+        let store_path = temp_dir.path();
+        let git_repo_path = temp_dir.path().join("git");
+        let git_repo = git_init(git_repo_path);
+
+        let signature = gix::actor::Signature {
+            name: b"Ren\xe9"[..].into(),
+            email: GIT_EMAIL.into(),
+            time: gix::date::Time::now_utc(),
+        };
+        let empty_tree_id =
+            gix::ObjectId::from_hex(b"4b825dc642cb6eb9a060e54bf8d69288fbee4904").unwrap();
+        let mut commit = gix::objs::Commit {
+            tree: empty_tree_id,
+            parents: smallvec::SmallVec::new(),
+            author: signature.clone(),
+            committer: signature,
+            encoding: Some("ISO-8859-1".into()),
+            message: b"caf\xe9\n"[..].into(),
+            extra_headers: Vec::new(),
+        };
+        let latin1_commit_id = git_repo.write_object(&commit).unwrap();
+        // Unknown encoding is treated as UTF-8
+        commit.encoding = Some("no-such-encoding".into());
+        let unknown_commit_id = git_repo.write_object(&commit).unwrap();
+
+        let backend = GitBackend::init_external(&settings, store_path, git_repo.path()).unwrap();
+
+        let commit = backend
+            .read_commit(&CommitId::from_bytes(latin1_commit_id.as_bytes()))
+            .block_on()
+            .unwrap();
+        assert_eq!(commit.description, "caf\u{e9}\n");
+        assert_eq!(commit.author.name, "Ren\u{e9}");
+        assert_eq!(commit.committer.name, "Ren\u{e9}");
+
+        let commit = backend
+            .read_commit(&CommitId::from_bytes(unknown_commit_id.as_bytes()))
+            .block_on()
+            .unwrap();
+        assert_eq!(commit.description, "caf\u{fffd}\n");
+        assert_eq!(commit.author.name, "Ren\u{fffd}");
+    }
+
     #[test]
     fn read_empty_string_placeholder() {
         let git_signature1 = gix::actor::SignatureRef {
@@ -1858,7 +2628,7 @@ mod tests {
             email: "git.author@example.com".into(),
             time: gix::date::Time::new(1000, 60 * 60),
         };
-        let signature1 = signature_from_git(git_signature1);
+        let signature1 = signature_from_git(git_signature1, None);
         assert!(signature1.name.is_empty());
         assert_eq!(signature1.email, "git.author@example.com");
         let git_signature2 = gix::actor::SignatureRef {
@@ -1866,7 +2636,7 @@ mod tests {
             email: EMPTY_STRING_PLACEHOLDER.into(),
             time: gix::date::Time::new(2000, -480 * 60),
         };
-        let signature2 = signature_from_git(git_signature2);
+        let signature2 = signature_from_git(git_signature2, None);
         assert_eq!(signature2.name, "git committer");
         assert!(signature2.email.is_empty());
     }
@@ -2125,6 +2895,71 @@ mod tests {
         assert!(git_ref_ids.iter().any(|id| *id == git_id(&commit_id)));
     }
 
+    #[test]
+    fn write_and_remove_backups() {
+        let settings = user_settings();
+        let temp_dir = new_temp_dir();
+        let backend = GitBackend::init_internal(&settings, temp_dir.path()).unwrap();
+        let signature = Signature {
+            name: "Someone".to_string(),
+            email: "someone@example.com".to_string(),
+            timestamp: Timestamp {
+                timestamp: MillisSinceEpoch(0),
+                tz_offset: 0,
+            },
+        };
+        let write_commit = |description: &str| {
+            let commit = Commit {
+                parents: vec![backend.root_commit_id().clone()],
+                predecessors: vec![],
+                root_tree: MergedTreeId::Legacy(backend.empty_tree_id().clone()),
+                change_id: ChangeId::new(vec![]),
+                description: description.to_string(),
+                author: signature.clone(),
+                committer: signature.clone(),
+                secure_sig: None,
+            };
+            backend.write_commit(commit, None).block_on().unwrap().0
+        };
+        let commit_id1 = write_commit("1");
+        let commit_id2 = write_commit("2");
+        assert_eq!(backend.backups().unwrap(), vec![]);
+
+        let backup1 = backend
+            .write_backup(slice::from_ref(&commit_id1), "test")
+            .unwrap();
+        let backup2 = backend
+            .write_backup(&[commit_id1.clone(), commit_id2.clone()], "test")
+            .unwrap();
+        assert_eq!(backup1.number, 1);
+        assert_eq!(backup2.number, 2);
+        let git_repo = backend.git_repo();
+        let backup_ref_name = format!("refs/jj/backup/2/{commit_id2}");
+        assert_eq!(
+            git_repo.find_reference(&backup_ref_name).unwrap().id(),
+            git_id(&commit_id2)
+        );
+
+        let backups = backend.backups().unwrap();
+        assert_eq!(backups.len(), 2);
+        assert_eq!(backups[0], backup1);
+        assert_eq!(
+            backups[1].heads.iter().sorted().collect_vec(),
+            [&commit_id1, &commit_id2]
+                .into_iter()
+                .sorted()
+                .collect_vec()
+        );
+
+        // Numbers of removed backups are reused only if they were the latest
+        backend.remove_backup(&backup1).unwrap();
+        assert_eq!(backend.backups().unwrap().len(), 1);
+        let backup3 = backend
+            .write_backup(slice::from_ref(&commit_id2), "test")
+            .unwrap();
+        assert_eq!(backup3.number, 3);
+    }
+
     #[test]
     fn import_head_commits_duplicates() {
         let settings = user_settings();
@@ -2205,6 +3040,34 @@ mod tests {
         assert_eq!(actual_commit2, commit2);
     }
 
+    #[test]
+    fn file_size_and_existence() {
+        let settings = user_settings();
+        let temp_dir = new_temp_dir();
+        let backend = GitBackend::init_internal(&settings, temp_dir.path()).unwrap();
+        let path = RepoPath::from_internal_string("file");
+        let file_id = backend
+            .write_file(path, &mut &b"some content"[..])
+            .block_on()
+            .unwrap();
+        assert!(backend.contains_file(path, &file_id).block_on().unwrap());
+        assert_eq!(backend.file_size(path, &file_id).block_on().unwrap(), 12);
+
+        let missing_id = FileId::from_hex("0123456789012345678901234567890123456789");
+        assert!(!backend.contains_file(path, &missing_id).block_on().unwrap());
+        assert_matches!(
+            backend.file_size(path, &missing_id).block_on(),
+            Err(BackendError::ObjectNotFound { .. })
+        );
+
+        // A tree isn't a file
+        let tree_id = FileId::new(backend.empty_tree_id().to_bytes());
+        assert_matches!(
+            backend.file_size(path, &tree_id).block_on(),
+            Err(BackendError::ReadObject { .. })
+        );
+    }
+
     #[test]
     fn write_signed_commit() {
         let settings = user_settings();
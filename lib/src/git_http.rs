@@ -0,0 +1,721 @@
+// Copyright 2025 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Native smart-HTTP transport used for Git remotes when `git.native-http` is
+//! enabled. Fetching is implemented by gitoxide, and pushing speaks the
+//! `git-receive-pack` protocol on top of gitoxide's HTTP client.
+
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::io::Write as _;
+use std::sync::atomic::AtomicBool;
+
+use bstr::BString;
+use bstr::ByteSlice as _;
+use gix::progress::Discard;
+use gix::protocol::transport::client::Capabilities;
+use gix::protocol::transport::client::MessageKind;
+use gix::protocol::transport::client::Transport;
+use gix::protocol::transport::client::TransportWithoutIO as _;
+use gix::protocol::transport::client::WriteMode;
+use gix::protocol::transport::Protocol;
+use gix::protocol::transport::Service;
+use gix::remote::fetch::Shallow;
+use gix::remote::Direction;
+use gix_pack::data::output;
+use itertools::Itertools as _;
+
+use crate::backend::CommitId;
+use crate::git::GitHttpError;
+use crate::git::GitShallowOptions;
+use crate::git::RefSpec;
+use crate::git::RemoteCallbacks;
+use crate::git_subprocess::GitPushOutput;
+use crate::object_id::ObjectId as _;
+use crate::settings::GitFetchTags;
+use crate::settings::GitHttpSettings;
+use crate::str_util::StringPattern;
+
+type BoxError = Box<dyn std::error::Error + Send + Sync>;
+
+impl GitHttpError {
+    fn connect(err: impl Into<BoxError>) -> Self {
+        GitHttpError::Connect(err.into())
+    }
+
+    fn fetch(err: impl Into<BoxError>) -> Self {
+        GitHttpError::Fetch(err.into())
+    }
+
+    fn push(err: impl Into<BoxError>) -> Self {
+        GitHttpError::Push(err.into())
+    }
+}
+
+/// Returns true if the remote's URL for the given direction is an HTTP(S) URL.
+pub(crate) fn is_http_remote(
+    git_repo: &gix::Repository,
+    remote_name: &str,
+    direction: Direction,
+) -> bool {
+    let Some(Ok(remote)) = git_repo.try_find_remote(remote_name) else {
+        return false;
+    };
+    remote.url(direction).is_some_and(is_http_url)
+}
+
+fn is_http_url(url: &gix::Url) -> bool {
+    matches!(url.scheme, gix::url::Scheme::Http | gix::url::Scheme::Https)
+}
+
+/// Returns the remote's URL for the given direction, which must be an HTTP(S)
+/// URL.
+fn http_url(
+    remote: &gix::Remote<'_>,
+    remote_name: &str,
+    direction: Direction,
+) -> Result<gix::Url, GitHttpError> {
+    remote
+        .url(direction)
+        .filter(|url| is_http_url(url))
+        .cloned()
+        .ok_or_else(|| GitHttpError::NotHttpRemote(remote_name.to_owned()))
+}
+
+/// Returns a copy of the repository with the jj HTTP settings applied to its
+/// configuration, which is where gitoxide reads the transport options from.
+///
+/// The TLS settings aren't supported. They are rejected rather than ignored
+/// since the connection would otherwise be made with different certificates
+/// than the user asked for.
+fn configure_repo(
+    git_repo: &gix::Repository,
+    http_settings: &GitHttpSettings,
+) -> Result<gix::Repository, GitHttpError> {
+    let unsupported_settings = [
+        ("git.http.ssl-ca-info", http_settings.ssl_ca_info.is_some()),
+        ("git.http.ssl-cert", http_settings.ssl_cert.is_some()),
+        ("git.http.ssl-key", http_settings.ssl_key.is_some()),
+    ];
+    if let Some((name, _)) = unsupported_settings.iter().find(|(_, is_set)| *is_set) {
+        return Err(GitHttpError::UnsupportedSetting(name));
+    }
+    let mut git_repo = git_repo.clone();
+    if let Some(proxy) = &http_settings.proxy {
+        let mut config = git_repo.config_snapshot_mut();
+        config
+            .set_raw_value(&gix::config::tree::Http::PROXY, proxy.as_str())
+            .map_err(GitHttpError::connect)?;
+        config.commit().map_err(GitHttpError::connect)?;
+    }
+    Ok(git_repo)
+}
+
+/// Returns a function that obtains credentials from the configured Git
+/// credential helpers, and asks the user through the `callbacks` if none of
+/// the helpers provided any.
+// The error type is dictated by gitoxide.
+#[allow(clippy::result_large_err)]
+fn credentials_fn<'a>(
+    git_repo: &gix::Repository,
+    url: gix::Url,
+    mut callbacks: RemoteCallbacks<'a>,
+) -> Result<
+    impl FnMut(gix::credentials::helper::Action) -> gix::credentials::protocol::Result + 'a,
+    GitHttpError,
+> {
+    let (mut cascade, _, mut prompt_options) = git_repo
+        .config_snapshot()
+        .credential_helpers(url)
+        .map_err(GitHttpError::connect)?;
+    // Prompting is left to the callbacks.
+    prompt_options.mode = gix::prompt::Mode::Disable;
+    Ok(move |action: gix::credentials::helper::Action| {
+        let context = match &action {
+            gix::credentials::helper::Action::Get(context) => Some(context.clone()),
+            _ => None,
+        };
+        let result = cascade.invoke(action, prompt_options.clone());
+        let (Some(mut context), Err(_) | Ok(None)) = (context, &result) else {
+            return result;
+        };
+        let get_username_password = callbacks.get_username_password.as_mut();
+        let url = context.to_url().unwrap_or_default();
+        match get_username_password.and_then(|cb| cb(&url.to_str_lossy())) {
+            Some((username, password)) => {
+                context.username = Some(username.clone());
+                context.password = Some(password.clone());
+                Ok(Some(gix::credentials::protocol::Outcome {
+                    identity: gix::sec::identity::Account { username, password },
+                    next: context.into(),
+                }))
+            }
+            None => result,
+        }
+    })
+}
+
+fn to_fetch_tags(fetch_tags: GitFetchTags) -> gix::remote::fetch::Tags {
+    match fetch_tags {
+        GitFetchTags::None => gix::remote::fetch::Tags::None,
+        GitFetchTags::Reachable => gix::remote::fetch::Tags::Included,
+        GitFetchTags::All => gix::remote::fetch::Tags::All,
+    }
+}
+
+/// Fetches the `refspecs` from the remote and prunes the remote-tracking
+/// branches matching `branch_names` which no longer exist on the remote.
+#[expect(clippy::too_many_arguments)]
+#[tracing::instrument(skip_all, fields(remote = remote_name))]
+pub(crate) fn fetch(
+    git_repo: &gix::Repository,
+    http_settings: &GitHttpSettings,
+    remote_name: &str,
+    refspecs: &[RefSpec],
+    branch_names: &[StringPattern],
+    callbacks: RemoteCallbacks<'_>,
+    shallow: &GitShallowOptions,
+    fetch_tags: GitFetchTags,
+) -> Result<(), GitHttpError> {
+    let git_repo = configure_repo(git_repo, http_settings)?;
+    let mut remote = git_repo
+        .find_remote(remote_name)
+        .map_err(GitHttpError::connect)?
+        .with_fetch_tags(to_fetch_tags(fetch_tags));
+    remote
+        .replace_refspecs(
+            refspecs
+                .iter()
+                .map(|refspec| BString::from(refspec.to_git_format())),
+            Direction::Fetch,
+        )
+        .map_err(GitHttpError::fetch)?;
+    let url = http_url(&remote, remote_name, Direction::Fetch)?;
+    let mut connection = remote
+        .connect(Direction::Fetch)
+        .map_err(GitHttpError::connect)?;
+    connection.set_credentials(credentials_fn(&git_repo, url, callbacks)?);
+    tracing::debug!("prepare_fetch");
+    let mut prepare = connection
+        .prepare_fetch(Discard, Default::default())
+        .map_err(GitHttpError::fetch)?;
+    if let Some(shallow) = to_gix_shallow(shallow)? {
+        prepare = prepare.with_shallow(shallow);
+    }
+    let remote_branches: HashSet<String> = prepare
+        .ref_map()
+        .remote_refs
+        .iter()
+        .filter_map(|remote_ref| {
+            let (name, _, _) = remote_ref.unpack();
+            let name = name.to_str().ok()?;
+            Some(name.strip_prefix("refs/heads/")?.to_owned())
+        })
+        .collect();
+    // Fetching fails if none of the refspecs match, but there's nothing to
+    // fetch in that case anyway.
+    if !prepare.ref_map().mappings.is_empty() {
+        tracing::debug!("receive");
+        prepare
+            .receive(Discard, &AtomicBool::new(false))
+            .map_err(GitHttpError::fetch)?;
+    }
+    prune_remote_branches(&git_repo, remote_name, branch_names, &remote_branches)
+}
+
+fn to_gix_shallow(shallow: &GitShallowOptions) -> Result<Option<Shallow>, GitHttpError> {
+    let cutoff = shallow.deepen_since.as_ref().map(|since| {
+        gix::date::Time::new(since.timestamp.0.div_euclid(1000), since.tz_offset * 60)
+    });
+    if let Some(depth) = shallow.depth {
+        Ok(Some(Shallow::DepthAtRemote(depth)))
+    } else if !shallow.deepen_not.is_empty() {
+        let remote_refs = shallow
+            .deepen_not
+            .iter()
+            .map(|name| gix::refs::PartialName::try_from(name.as_str()))
+            .collect::<Result<_, _>>()
+            .map_err(GitHttpError::fetch)?;
+        Ok(Some(Shallow::Exclude {
+            remote_refs,
+            since_cutoff: cutoff,
+        }))
+    } else {
+        Ok(cutoff.map(|cutoff| Shallow::Since { cutoff }))
+    }
+}
+
+/// Deletes the remote-tracking branches matching `branch_names` which aren't
+/// in `remote_branches`.
+fn prune_remote_branches(
+    git_repo: &gix::Repository,
+    remote_name: &str,
+    branch_names: &[StringPattern],
+    remote_branches: &HashSet<String>,
+) -> Result<(), GitHttpError> {
+    let prefix = format!("refs/remotes/{remote_name}/");
+    let references = git_repo.references().map_err(GitHttpError::fetch)?;
+    let edits: Vec<_> = references
+        .prefixed(prefix.as_str())
+        .map_err(GitHttpError::fetch)?
+        .filter_map(Result::ok)
+        .filter(|reference| {
+            let full_name = reference.name().as_bstr();
+            let Some(branch) = full_name
+                .to_str()
+                .ok()
+                .and_then(|name| name.strip_prefix(&prefix))
+            else {
+                return false;
+            };
+            branch != "HEAD"
+                && !remote_branches.contains(branch)
+                && branch_names.iter().any(|pattern| pattern.matches(branch))
+        })
+        .map(|reference| delete_ref_edit(reference.name().to_owned()))
+        .collect();
+    if !edits.is_empty() {
+        tracing::debug!(?edits, "pruning remote-tracking branches");
+        git_repo
+            .edit_references(edits)
+            .map_err(GitHttpError::fetch)?;
+    }
+    Ok(())
+}
+
+fn delete_ref_edit(name: gix::refs::FullName) -> gix::refs::transaction::RefEdit {
+    gix::refs::transaction::RefEdit {
+        change: gix::refs::transaction::Change::Delete {
+            expected: gix::refs::transaction::PreviousValue::Any,
+            log: gix::refs::transaction::RefLog::AndReference,
+        },
+        name,
+        deref: false,
+    }
+}
+
+/// Queries the remote for the branch its `HEAD` points to.
+#[tracing::instrument(skip_all, fields(remote = remote_name))]
+pub(crate) fn get_default_branch(
+    git_repo: &gix::Repository,
+    http_settings: &GitHttpSettings,
+    remote_name: &str,
+    callbacks: RemoteCallbacks<'_>,
+) -> Result<Option<String>, GitHttpError> {
+    let git_repo = configure_repo(git_repo, http_settings)?;
+    let mut remote = git_repo
+        .find_remote(remote_name)
+        .map_err(GitHttpError::connect)?;
+    remote
+        .replace_refspecs(
+            [format!("+refs/heads/*:refs/remotes/{remote_name}/*").as_str()],
+            Direction::Fetch,
+        )
+        .map_err(GitHttpError::fetch)?;
+    let url = http_url(&remote, remote_name, Direction::Fetch)?;
+    let mut connection = remote
+        .connect(Direction::Fetch)
+        .map_err(GitHttpError::connect)?;
+    connection.set_credentials(credentials_fn(&git_repo, url, callbacks)?);
+    let (ref_map, _) = connection
+        .ref_map(
+            Discard,
+            gix::remote::ref_map::Options {
+                prefix_from_spec_as_filter_on_remote: false,
+                ..Default::default()
+            },
+        )
+        .map_err(GitHttpError::fetch)?;
+    let default_branch = ref_map.remote_refs.iter().find_map(|remote_ref| {
+        let gix::protocol::handshake::Ref::Symbolic {
+            full_ref_name,
+            target,
+            ..
+        } = remote_ref
+        else {
+            return None;
+        };
+        if full_ref_name != "HEAD" {
+            return None;
+        }
+        let branch = target.to_str().ok()?.strip_prefix("refs/heads/")?;
+        Some(branch.to_owned())
+    });
+    tracing::debug!(default_branch = default_branch);
+    Ok(default_branch)
+}
+
+/// Update of a ref on the remote, sent as a `git-receive-pack` command.
+#[derive(Clone, Debug)]
+pub(crate) struct GitHttpRefUpdate {
+    pub qualified_name: String,
+    pub old_target: Option<CommitId>,
+    pub new_target: Option<CommitId>,
+}
+
+/// Connection to the `git-receive-pack` service of a remote.
+pub(crate) struct GitHttpPush {
+    git_repo: gix::Repository,
+    transport: Box<dyn Transport + Send>,
+    capabilities: Capabilities,
+    remote_refs: HashMap<String, CommitId>,
+}
+
+impl GitHttpPush {
+    /// Connects to the remote and reads the refs it advertises.
+    #[tracing::instrument(skip_all, fields(remote = remote_name))]
+    pub(crate) fn connect(
+        git_repo: &gix::Repository,
+        http_settings: &GitHttpSettings,
+        remote_name: &str,
+        callbacks: RemoteCallbacks<'_>,
+    ) -> Result<Self, GitHttpError> {
+        let git_repo = configure_repo(git_repo, http_settings)?;
+        let remote = git_repo
+            .find_remote(remote_name)
+            .map_err(GitHttpError::connect)?;
+        let url = http_url(&remote, remote_name, Direction::Push)?;
+        // Pushing isn't supported by protocol v2.
+        let mut transport: Box<dyn Transport + Send> = Box::new(
+            gix::protocol::transport::client::http::connect(url.clone(), Protocol::V1, false),
+        );
+        if let Some(options) = git_repo
+            .transport_options(url.to_bstring().as_bstr(), Some(remote_name.into()))
+            .map_err(GitHttpError::connect)?
+        {
+            transport
+                .configure(&*options)
+                .map_err(GitHttpError::Connect)?;
+        }
+        let authenticate = credentials_fn(&git_repo, url, callbacks)?;
+        tracing::debug!("handshake");
+        let outcome = gix::protocol::handshake(
+            &mut transport,
+            Service::ReceivePack,
+            authenticate,
+            vec![],
+            &mut Discard,
+        )
+        .map_err(GitHttpError::connect)?;
+        let remote_refs = outcome
+            .refs
+            .unwrap_or_default()
+            .iter()
+            .filter_map(|remote_ref| {
+                let (name, Some(id), _) = remote_ref.unpack() else {
+                    return None;
+                };
+                Some((
+                    name.to_str().ok()?.to_owned(),
+                    CommitId::from_bytes(id.as_bytes()),
+                ))
+            })
+            .collect();
+        Ok(GitHttpPush {
+            git_repo,
+            transport,
+            capabilities: outcome.capabilities,
+            remote_refs,
+        })
+    }
+
+    /// Returns the position of the ref on the remote.
+    pub(crate) fn remote_ref(&self, qualified_name: &str) -> Option<&CommitId> {
+        self.remote_refs.get(qualified_name)
+    }
+
+    /// Returns the commits the remote refs point to.
+    pub(crate) fn remote_ref_targets(&self) -> impl Iterator<Item = &CommitId> {
+        self.remote_refs.values()
+    }
+
+    /// Sends the `updates` along with a pack containing the `commits` and the
+    /// objects they add compared to their parents.
+    #[tracing::instrument(skip_all)]
+    pub(crate) fn send(
+        mut self,
+        updates: &[GitHttpRefUpdate],
+        commits: &[CommitId],
+    ) -> Result<GitPushOutput, GitHttpError> {
+        if updates.is_empty() {
+            return Ok(GitPushOutput::default());
+        }
+        let pack = if updates.iter().all(|update| update.new_target.is_none()) {
+            // No pack is sent if all commands are deletions.
+            None
+        } else {
+            Some(self.write_pack(commits)?)
+        };
+
+        let report_status = self.capabilities.contains("report-status");
+        let mut capabilities = vec!["agent=jj"];
+        if report_status {
+            capabilities.push("report-status");
+        }
+        if updates.iter().any(|update| update.new_target.is_none()) {
+            if !self.capabilities.contains("delete-refs") {
+                return Err(GitHttpError::Protocol(
+                    "remote doesn't support deleting refs".to_owned(),
+                ));
+            }
+            capabilities.push("delete-refs");
+        }
+
+        let mut writer = self
+            .transport
+            .request(WriteMode::Binary, MessageKind::Flush, false)
+            .map_err(GitHttpError::push)?;
+        let null_id = "0".repeat(40);
+        for (i, update) in updates.iter().enumerate() {
+            let old = update
+                .old_target
+                .as_ref()
+                .map_or(null_id.clone(), |id| id.hex());
+            let new = update
+                .new_target
+                .as_ref()
+                .map_or(null_id.clone(), |id| id.hex());
+            let mut line = format!("{old} {new} {}", update.qualified_name);
+            if i == 0 {
+                line.push('\0');
+                line.push_str(&capabilities.join(" "));
+            }
+            line.push('\n');
+            writer
+                .write_all(line.as_bytes())
+                .map_err(GitHttpError::push)?;
+        }
+        writer
+            .write_message(MessageKind::Flush)
+            .map_err(GitHttpError::push)?;
+        // The pack is written as is, not as packet lines.
+        let (mut pack_writer, mut reader) = writer.into_parts();
+        if let Some(pack) = &pack {
+            pack_writer.write_all(pack).map_err(GitHttpError::push)?;
+        }
+        pack_writer.flush().map_err(GitHttpError::push)?;
+        drop(pack_writer);
+
+        let mut output = GitPushOutput::default();
+        if !report_status {
+            output.pushed = updates
+                .iter()
+                .map(|update| update.qualified_name.clone())
+                .collect();
+            return Ok(output);
+        }
+        let mut line = String::new();
+        loop {
+            line.clear();
+            let len = reader.readline_str(&mut line).map_err(GitHttpError::push)?;
+            if len == 0 {
+                break;
+            }
+            let line = line.trim_end_matches('\n');
+            if let Some(status) = line.strip_prefix("unpack ") {
+                if status != "ok" {
+                    return Err(GitHttpError::Protocol(format!(
+                        "remote failed to unpack the pack: {status}"
+                    )));
+                }
+            } else if let Some(name) = line.strip_prefix("ok ") {
+                output.pushed.push(name.to_owned());
+            } else if let Some(rest) = line.strip_prefix("ng ") {
+                let (name, reason) = match rest.split_once(' ') {
+                    Some((name, reason)) => (name, Some(reason.to_owned())),
+                    None => (rest, None),
+                };
+                output.remote_rejected.push((name.to_owned(), reason));
+            } else {
+                return Err(GitHttpError::Protocol(format!(
+                    "unexpected status line: {line:?}"
+                )));
+            }
+        }
+        Ok(output)
+    }
+
+    fn write_pack(&self, commits: &[CommitId]) -> Result<Vec<u8>, GitHttpError> {
+        let object_ids = self.objects_to_send(commits)?;
+        // Git doesn't necessarily store the empty tree, but it has to be sent
+        // if any of the commits add it.
+        let empty_tree_id = gix::ObjectId::empty_tree(self.git_repo.object_hash());
+        if object_ids.contains(&empty_tree_id) {
+            self.git_repo
+                .write_object(gix::objs::Tree::empty())
+                .map_err(GitHttpError::push)?;
+        }
+        let mut db = self.git_repo.objects.clone().into_inner();
+        db.prevent_pack_unload();
+        let should_interrupt = AtomicBool::new(false);
+        let input = object_ids.into_iter().map(Ok).collect_vec();
+        let (counts, _) = output::count::objects(
+            db.clone(),
+            Box::new(input.into_iter()),
+            &Discard,
+            &should_interrupt,
+            output::count::objects::Options {
+                input_object_expansion: output::count::objects::ObjectExpansion::AsIs,
+                ..Default::default()
+            },
+        )
+        .map_err(GitHttpError::push)?;
+        let num_entries = counts.len().try_into().map_err(GitHttpError::push)?;
+        let entries = gix::features::parallel::InOrderIter::from(output::entry::iter_from_counts(
+            counts,
+            db,
+            Box::new(Discard),
+            Default::default(),
+        ));
+        let mut pack = vec![];
+        let bytes = output::bytes::FromEntriesIter::new(
+            entries,
+            &mut pack,
+            num_entries,
+            gix_pack::data::Version::V2,
+            self.git_repo.object_hash(),
+        );
+        for written in bytes {
+            written.map_err(GitHttpError::push)?;
+        }
+        tracing::debug!(objects = num_entries, bytes = pack.len(), "wrote pack");
+        Ok(pack)
+    }
+
+    /// Returns the `commits` and the trees and blobs they add. An object is
+    /// added by a commit if it isn't at the same path in any of the parents,
+    /// which are either sent too or already on the remote.
+    fn objects_to_send(&self, commits: &[CommitId]) -> Result<Vec<gix::ObjectId>, GitHttpError> {
+        let mut object_ids = vec![];
+        let mut seen_ids = HashSet::new();
+        for id in commits {
+            let commit = self
+                .git_repo
+                .find_commit(gix::ObjectId::from_bytes_or_panic(id.as_bytes()))
+                .map_err(GitHttpError::push)?;
+            let tree_id = commit.tree_id().map_err(GitHttpError::push)?.detach();
+            let parent_tree_ids: Vec<_> = commit
+                .parent_ids()
+                .map(|parent_id| {
+                    let parent = parent_id.object()?.try_into_commit()?;
+                    Ok::<_, BoxError>(parent.tree_id()?.detach())
+                })
+                .try_collect()
+                .map_err(GitHttpError::Push)?;
+            object_ids.push(commit.id);
+            self.collect_tree_additions(tree_id, &parent_tree_ids, &mut seen_ids, &mut object_ids)?;
+        }
+        Ok(object_ids)
+    }
+
+    fn collect_tree_additions(
+        &self,
+        tree_id: gix::ObjectId,
+        parent_tree_ids: &[gix::ObjectId],
+        seen_ids: &mut HashSet<gix::ObjectId>,
+        object_ids: &mut Vec<gix::ObjectId>,
+    ) -> Result<(), GitHttpError> {
+        if parent_tree_ids.contains(&tree_id) || !seen_ids.insert(tree_id) {
+            return Ok(());
+        }
+        object_ids.push(tree_id);
+        let tree = self.read_tree(tree_id)?;
+        let parent_trees: Vec<_> = parent_tree_ids
+            .iter()
+            .map(|id| self.read_tree(*id))
+            .try_collect()?;
+        let parent_entries: Vec<HashMap<_, _>> = parent_trees
+            .iter()
+            .map(|parent_tree| {
+                parent_tree
+                    .entries
+                    .iter()
+                    .map(|entry| (&entry.filename, entry))
+                    .collect()
+            })
+            .collect();
+        for entry in &tree.entries {
+            // Submodule commits aren't part of the repository.
+            if entry.mode.is_commit() {
+                continue;
+            }
+            let parent_entries = parent_entries
+                .iter()
+                .filter_map(|entries| entries.get(&entry.filename));
+            if entry.mode.is_tree() {
+                let parent_subtree_ids = parent_entries
+                    .filter(|parent_entry| parent_entry.mode.is_tree())
+                    .map(|parent_entry| parent_entry.oid)
+                    .collect_vec();
+                self.collect_tree_additions(entry.oid, &parent_subtree_ids, seen_ids, object_ids)?;
+            } else if !parent_entries
+                .into_iter()
+                .any(|parent_entry| parent_entry.oid == entry.oid)
+                && seen_ids.insert(entry.oid)
+            {
+                object_ids.push(entry.oid);
+            }
+        }
+        Ok(())
+    }
+
+    fn read_tree(&self, id: gix::ObjectId) -> Result<gix::objs::Tree, GitHttpError> {
+        let tree = self.git_repo.find_tree(id).map_err(GitHttpError::push)?;
+        Ok(tree.decode().map_err(GitHttpError::push)?.into())
+    }
+}
+
+/// Updates the remote-tracking branches of the pushed branches as `git push`
+/// does.
+pub(crate) fn update_remote_tracking_branches(
+    git_repo: &gix::Repository,
+    remote_name: &str,
+    updates: &[GitHttpRefUpdate],
+    output: &GitPushOutput,
+) -> Result<(), GitHttpError> {
+    let edits: Vec<_> = updates
+        .iter()
+        .filter(|update| output.pushed.contains(&update.qualified_name))
+        .filter_map(|update| {
+            let branch = update.qualified_name.strip_prefix("refs/heads/")?;
+            let name = format!("refs/remotes/{remote_name}/{branch}")
+                .try_into()
+                .ok()?;
+            let edit = match &update.new_target {
+                Some(id) => gix::refs::transaction::RefEdit {
+                    change: gix::refs::transaction::Change::Update {
+                        log: gix::refs::transaction::LogChange {
+                            message: "update by push".into(),
+                            ..Default::default()
+                        },
+                        expected: gix::refs::transaction::PreviousValue::Any,
+                        new: gix::refs::Target::Object(gix::ObjectId::from_bytes_or_panic(
+                            id.as_bytes(),
+                        )),
+                    },
+                    name,
+                    deref: false,
+                },
+                None => delete_ref_edit(name),
+            };
+            Some(edit)
+        })
+        .collect();
+    if !edits.is_empty() {
+        git_repo
+            .edit_references(edits)
+            .map_err(GitHttpError::push)?;
+    }
+    Ok(())
+}
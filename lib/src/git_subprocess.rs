@@ -12,10 +12,11 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::ffi::OsString;
 use std::io;
 use std::io::BufReader;
 use std::io::Read;
-use std::num::NonZeroU32;
+use std::io::Write as _;
 use std::path::Path;
 use std::path::PathBuf;
 use std::process::Child;
@@ -27,11 +28,15 @@ use std::thread;
 use bstr::ByteSlice;
 use thiserror::Error;
 
+use crate::git::GitShallowOptions;
 use crate::git::Progress;
 use crate::git::RefSpec;
 use crate::git::RefToPush;
 use crate::git::RemoteCallbacks;
 use crate::git_backend::GitBackend;
+use crate::settings::GitFetchTags;
+use crate::settings::GitHttpSettings;
+use crate::settings::GitSettings;
 
 /// Error originating by a Git subprocess
 #[derive(Error, Debug)]
@@ -60,21 +65,27 @@ pub enum GitSubprocessError {
 pub(crate) struct GitSubprocessContext<'a> {
     git_dir: PathBuf,
     git_executable_path: &'a Path,
+    http_settings: &'a GitHttpSettings,
 }
 
 impl<'a> GitSubprocessContext<'a> {
-    pub(crate) fn new(git_dir: impl Into<PathBuf>, git_executable_path: &'a Path) -> Self {
+    pub(crate) fn new(git_dir: impl Into<PathBuf>, git_settings: &'a GitSettings) -> Self {
         GitSubprocessContext {
             git_dir: git_dir.into(),
-            git_executable_path,
+            git_executable_path: &git_settings.executable_path,
+            http_settings: &git_settings.http,
         }
     }
 
     pub(crate) fn from_git_backend(
         git_backend: &GitBackend,
-        git_executable_path: &'a Path,
+        git_settings: &'a GitSettings,
     ) -> Self {
-        Self::new(git_backend.git_repo_path(), git_executable_path)
+        Self::new(git_backend.git_repo_path(), git_settings)
+    }
+
+    pub(crate) fn http_settings(&self) -> &'a GitHttpSettings {
+        self.http_settings
     }
 
     /// Create the Git command
@@ -92,6 +103,9 @@ impl<'a> GitSubprocessContext<'a> {
             .env("LC_ALL", "C")
             .stdin(Stdio::null())
             .stderr(Stdio::piped());
+        for arg in http_config_args(self.http_settings) {
+            git_cmd.arg("-c").arg(arg);
+        }
 
         git_cmd
     }
@@ -118,12 +132,14 @@ impl<'a> GitSubprocessContext<'a> {
     ///
     /// This returns a fully qualified ref that wasn't fetched successfully
     /// Note that git only returns one failed ref at a time
+    #[tracing::instrument(skip_all, fields(remote = remote_name))]
     pub(crate) fn spawn_fetch(
         &self,
         remote_name: &str,
         refspecs: &[RefSpec],
         callbacks: &mut RemoteCallbacks<'_>,
-        depth: Option<NonZeroU32>,
+        shallow: &GitShallowOptions,
+        fetch_tags: GitFetchTags,
     ) -> Result<Option<String>, GitSubprocessError> {
         if refspecs.is_empty() {
             return Ok(None);
@@ -136,9 +152,25 @@ impl<'a> GitSubprocessContext<'a> {
         if callbacks.progress.is_some() {
             command.arg("--progress");
         }
-        if let Some(d) = depth {
+        if let Some(d) = shallow.depth {
             command.arg(format!("--depth={d}"));
         }
+        if let Some(since) = &shallow.deepen_since {
+            let seconds = since.timestamp.0.div_euclid(1000);
+            command.arg(format!("--shallow-since=@{seconds}"));
+        }
+        for name in &shallow.deepen_not {
+            command.arg(format!("--shallow-exclude={name}"));
+        }
+        match fetch_tags {
+            GitFetchTags::None => {
+                command.arg("--no-tags");
+            }
+            GitFetchTags::Reachable => {}
+            GitFetchTags::All => {
+                command.arg("--tags");
+            }
+        }
         command.arg("--").arg(remote_name);
         command.args(refspecs.iter().map(|x| x.to_git_format()));
 
@@ -148,6 +180,7 @@ impl<'a> GitSubprocessContext<'a> {
     }
 
     /// Prune particular branches
+    #[tracing::instrument(skip_all)]
     pub(crate) fn spawn_branch_prune(
         &self,
         branches_to_prune: &[String],
@@ -174,6 +207,7 @@ impl<'a> GitSubprocessContext<'a> {
     ///
     /// dumps a lot of information about the remote, with a line such as:
     /// `  HEAD branch: <default_branch>`
+    #[tracing::instrument(skip_all, fields(remote = remote_name))]
     pub(crate) fn spawn_remote_show(
         &self,
         remote_name: &str,
@@ -194,15 +228,15 @@ impl<'a> GitSubprocessContext<'a> {
     /// All pushes are forced, using --force-with-lease to perform a test&set
     /// operation on the remote repository
     ///
-    /// Return tuple with
-    ///     1. refs that failed to push
-    ///     2. refs that succeeded to push
+    /// Returns the refs that were pushed, and the refs that were rejected
+    /// locally or by the remote.
+    #[tracing::instrument(skip_all, fields(remote = remote_name))]
     pub(crate) fn spawn_push(
         &self,
         remote_name: &str,
         references: &[RefToPush],
         callbacks: &mut RemoteCallbacks<'_>,
-    ) -> Result<(Vec<String>, Vec<String>), GitSubprocessError> {
+    ) -> Result<GitPushOutput, GitSubprocessError> {
         let mut command = self.create_command();
         command.stdout(Stdio::piped());
         // Currently jj does not support commit hooks, so we prevent git from running
@@ -232,6 +266,171 @@ impl<'a> GitSubprocessContext<'a> {
 
         parse_git_push_output(output)
     }
+
+    /// Fetches the objects omitted from a partial clone
+    ///
+    /// This is how Git itself fetches missing objects from a promisor remote.
+    /// Only the requested objects are downloaded, not their dependencies.
+    #[tracing::instrument(skip_all, fields(remote = remote_name))]
+    pub(crate) fn spawn_fetch_objects(
+        &self,
+        remote_name: &str,
+        ids: &[gix::ObjectId],
+    ) -> Result<(), GitSubprocessError> {
+        if ids.is_empty() {
+            return Ok(());
+        }
+        let mut command = self.create_command();
+        command.stdin(Stdio::piped()).stdout(Stdio::null());
+        command.args(["-c", "fetch.negotiationAlgorithm=noop"]);
+        command.args([
+            "fetch",
+            "--no-tags",
+            "--no-write-fetch-head",
+            "--recurse-submodules=no",
+            "--filter=blob:none",
+            "--stdin",
+            "--",
+            remote_name,
+        ]);
+        let mut child = self.spawn_cmd(command)?;
+        let ids_text: String = ids.iter().map(|id| format!("{id}\n")).collect();
+        // If git exited early, the error will be reported by the exit status.
+        _ = child.stdin.take().unwrap().write_all(ids_text.as_bytes());
+        let output = wait_with_output(child)?;
+        if !output.status.success() {
+            return Err(external_git_error(&output.stderr));
+        }
+        Ok(())
+    }
+
+    /// Runs `git credential <action>`, which talks to the configured
+    /// credential helpers.
+    ///
+    /// Terminal prompts are disabled, so `fill` fails if no helper could
+    /// provide the credential.
+    pub(crate) fn spawn_credential(
+        &self,
+        action: GitCredentialAction,
+        credential: &GitCredential,
+    ) -> Result<GitCredential, GitSubprocessError> {
+        let mut command = self.create_command();
+        command
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .env("GIT_TERMINAL_PROMPT", "0");
+        command.args(["credential", action.as_str()]);
+        let mut child = self.spawn_cmd(command)?;
+        // If git exited early, the error will be reported by the exit status.
+        _ = child
+            .stdin
+            .take()
+            .unwrap()
+            .write_all(&credential.to_bytes());
+        let output = wait_with_output(child)?;
+        if !output.status.success() {
+            return Err(external_git_error(&output.stderr));
+        }
+        Ok(GitCredential::parse(&output.stdout))
+    }
+}
+
+/// Action of the `git credential` command.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(crate) enum GitCredentialAction {
+    /// Looks up the credential.
+    Fill,
+    /// Tells the helpers that the credential was accepted.
+    Approve,
+    /// Tells the helpers that the credential was rejected.
+    Reject,
+}
+
+impl GitCredentialAction {
+    fn as_str(self) -> &'static str {
+        match self {
+            GitCredentialAction::Fill => "fill",
+            GitCredentialAction::Approve => "approve",
+            GitCredentialAction::Reject => "reject",
+        }
+    }
+}
+
+/// Attributes exchanged with `git credential`, in the order they were given.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub(crate) struct GitCredential {
+    attrs: Vec<(String, String)>,
+}
+
+impl GitCredential {
+    pub(crate) fn from_url(url: &str, username: Option<&str>) -> Self {
+        let mut credential = GitCredential::default();
+        credential.set("url", url);
+        if let Some(username) = username {
+            credential.set("username", username);
+        }
+        credential
+    }
+
+    pub(crate) fn get(&self, key: &str) -> Option<&str> {
+        self.attrs
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v.as_str())
+    }
+
+    pub(crate) fn set(&mut self, key: &str, value: &str) {
+        if let Some((_, v)) = self.attrs.iter_mut().find(|(k, _)| k == key) {
+            *v = value.to_owned();
+        } else {
+            self.attrs.push((key.to_owned(), value.to_owned()));
+        }
+    }
+
+    fn parse(data: &[u8]) -> Self {
+        let attrs = data
+            .lines()
+            .filter_map(|line| {
+                let (key, value) = line.to_str().ok()?.split_once('=')?;
+                Some((key.to_owned(), value.to_owned()))
+            })
+            .collect();
+        GitCredential { attrs }
+    }
+
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut data = vec![];
+        for (key, value) in &self.attrs {
+            // Newlines and NULs would break the line-based protocol.
+            if value.contains(['\n', '\0']) {
+                continue;
+            }
+            data.extend_from_slice(format!("{key}={value}\n").as_bytes());
+        }
+        data.push(b'\n');
+        data
+    }
+}
+
+/// Translates the jj HTTP settings into `name=value` Git config overrides.
+fn http_config_args(settings: &GitHttpSettings) -> Vec<OsString> {
+    let mut args = vec![];
+    if let Some(proxy) = &settings.proxy {
+        args.push(format!("http.proxy={proxy}").into());
+    }
+    let paths = [
+        ("http.sslCAInfo", &settings.ssl_ca_info),
+        ("http.sslCert", &settings.ssl_cert),
+        ("http.sslKey", &settings.ssl_key),
+    ];
+    for (name, path) in paths {
+        if let Some(path) = path {
+            let mut arg = OsString::from(format!("{name}="));
+            arg.push(path);
+            args.push(arg);
+        }
+    }
+    args
 }
 
 /// Generate a GitSubprocessError::ExternalGitError if the stderr output was not
@@ -370,6 +569,18 @@ fn parse_git_remote_show_default_branch(
         .map(|b| b.map(|x| x.to_string()))
 }
 
+/// Outcome of `git push` per ref. Refs are named as on the remote.
+#[derive(Debug, Default)]
+pub(crate) struct GitPushOutput {
+    /// Refs that failed to push, e.g. because the lease didn't match.
+    pub rejected: Vec<String>,
+    /// Refs that the remote refused to update, e.g. in a pre-receive hook,
+    /// along with the reason reported by the remote.
+    pub remote_rejected: Vec<(String, Option<String>)>,
+    /// Refs that were pushed successfully.
+    pub pushed: Vec<String>,
+}
+
 // git-push porcelain has the following format (per line)
 // `<flag>\t<from>:<to>\t<summary>\t(<reason>)`
 //
@@ -387,7 +598,10 @@ fn parse_git_remote_show_default_branch(
 // at times the summary is omitted
 //
 // <reason> is a human-readable explanation
-fn parse_ref_pushes(stdout: &[u8]) -> Result<(Vec<String>, Vec<String>), GitSubprocessError> {
+//
+// If the remote refused the update (e.g. a hook declined it), the summary is
+// `[remote rejected]`.
+fn parse_ref_pushes(stdout: &[u8]) -> Result<GitPushOutput, GitSubprocessError> {
     if !stdout.starts_with(b"To ") {
         return Err(GitSubprocessError::External(format!(
             "Git push output unfamiliar:\n{}",
@@ -395,8 +609,7 @@ fn parse_ref_pushes(stdout: &[u8]) -> Result<(Vec<String>, Vec<String>), GitSubp
         )));
     }
 
-    let mut pushed_refs = Vec::new();
-    let mut rejected_refs = Vec::new();
+    let mut output = GitPushOutput::default();
     for (idx, line) in stdout
         .lines()
         .skip(1)
@@ -416,7 +629,7 @@ fn parse_ref_pushes(stdout: &[u8]) -> Result<(Vec<String>, Vec<String>), GitSubp
         let flag = it.next().ok_or_else(create_error)?;
         let reference = it.next().ok_or_else(create_error)?;
         // we capture the remaining elements to ensure the line is well formed
-        let _summary_or_comment = it.next().ok_or_else(create_error)?;
+        let summary_or_comment = it.next().ok_or_else(create_error)?;
         let _comment_opt = it.next();
         if it.next().is_some() {
             return Err(create_error());
@@ -450,11 +663,20 @@ fn parse_ref_pushes(stdout: &[u8]) -> Result<(Vec<String>, Vec<String>), GitSubp
             //  * for a successfully pushed new ref
             //  =  for a ref that was up to date and did not need pushing.
             b"+" | b"-" | b"*" | b"=" | b" " => {
-                pushed_refs.push(reference);
+                output.pushed.push(reference);
             }
             // ! for a ref that was rejected or failed to push; and
             b"!" => {
-                rejected_refs.push(reference);
+                if let Some(reason) = summary_or_comment.strip_prefix(b"[remote rejected]") {
+                    let reason = reason
+                        .trim()
+                        .strip_prefix(b"(")
+                        .and_then(|reason| reason.strip_suffix(b")"))
+                        .map(|reason| reason.to_str_lossy().into_owned());
+                    output.remote_rejected.push((reference, reason));
+                } else {
+                    output.rejected.push(reference);
+                }
             }
             unknown => {
                 return Err(GitSubprocessError::External(format!(
@@ -467,13 +689,10 @@ fn parse_ref_pushes(stdout: &[u8]) -> Result<(Vec<String>, Vec<String>), GitSubp
         }
     }
 
-    Ok((rejected_refs, pushed_refs))
+    Ok(output)
 }
 
-// on Ok, return a tuple with
-//  1. list of failed references from test and set
-//  2. list of successful references pushed
-fn parse_git_push_output(output: Output) -> Result<(Vec<String>, Vec<String>), GitSubprocessError> {
+fn parse_git_push_output(output: Output) -> Result<GitPushOutput, GitSubprocessError> {
     if output.status.success() {
         let ref_pushes = parse_ref_pushes(&output.stdout)?;
         return Ok(ref_pushes);
@@ -706,6 +925,8 @@ and the repository exists. "###;
  \tdeadbeef:refs/heads/bookmark4\tdeadbeef\t[new branch]
 =\tdeadbeef:refs/heads/bookmark5\tdeadbeef\t[new branch]
 !\tdeadbeef:refs/heads/bookmark6\tdeadbeef\t[new branch]
+!\trefs/heads/bookmark7:refs/heads/bookmark7\t[remote rejected] (pre-receive hook declined)
+!\trefs/heads/bookmark8:refs/heads/bookmark8\t[remote rejected]
 Done";
     const SAMPLE_OK_STDERR: &[u8] = b"";
 
@@ -778,10 +999,20 @@ Done";
         assert!(parse_ref_pushes(SAMPLE_NO_SUCH_REMOTE_ERROR).is_err());
         assert!(parse_ref_pushes(SAMPLE_NO_REMOTE_REF_ERROR).is_err());
         assert!(parse_ref_pushes(SAMPLE_NO_REMOTE_TRACKING_BRANCH_ERROR).is_err());
-        let (failed, success) = parse_ref_pushes(SAMPLE_PUSH_REFS_PORCELAIN_OUTPUT).unwrap();
-        assert_eq!(failed, vec!["refs/heads/bookmark6".to_string()]);
+        let output = parse_ref_pushes(SAMPLE_PUSH_REFS_PORCELAIN_OUTPUT).unwrap();
+        assert_eq!(output.rejected, vec!["refs/heads/bookmark6".to_string()]);
         assert_eq!(
-            success,
+            output.remote_rejected,
+            vec![
+                (
+                    "refs/heads/bookmark7".to_string(),
+                    Some("pre-receive hook declined".to_string())
+                ),
+                ("refs/heads/bookmark8".to_string(), None),
+            ]
+        );
+        assert_eq!(
+            output.pushed,
             vec![
                 "refs/heads/bookmark1".to_string(),
                 "refs/heads/bookmark2".to_string(),
@@ -859,4 +1090,93 @@ Done";
         );
         assert_eq!(read_progress_line(b"fatal: this is a git error\n"), None);
     }
+
+    #[test]
+    fn test_http_config_args() {
+        assert!(http_config_args(&GitHttpSettings::default()).is_empty());
+        let settings = GitHttpSettings {
+            proxy: Some("http://proxy.example.com:3128".to_owned()),
+            ssl_ca_info: Some(PathBuf::from("/etc/ca.pem")),
+            ssl_cert: None,
+            ssl_key: Some(PathBuf::from("/etc/client.key")),
+        };
+        assert_eq!(
+            http_config_args(&settings),
+            [
+                "http.proxy=http://proxy.example.com:3128",
+                "http.sslCAInfo=/etc/ca.pem",
+                "http.sslKey=/etc/client.key",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_git_credential_roundtrip() {
+        let mut credential = GitCredential::from_url("https://example.com/repo", Some("alice"));
+        credential.set("password", "secret");
+        credential.set("username", "bob");
+        assert_eq!(credential.get("username"), Some("bob"));
+        assert_eq!(credential.get("password"), Some("secret"));
+        assert_eq!(
+            credential.to_bytes(),
+            b"url=https://example.com/repo\nusername=bob\npassword=secret\n\n"
+        );
+        assert_eq!(GitCredential::parse(&credential.to_bytes()), credential);
+
+        let parsed = GitCredential::parse(b"protocol=https\nhost=example.com\ninvalid\n");
+        assert_eq!(parsed.get("protocol"), Some("https"));
+        assert_eq!(parsed.get("host"), Some("example.com"));
+        assert_eq!(parsed.get("invalid"), None);
+    }
+
+    #[test]
+    fn test_spawn_credential() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let git_dir = temp_dir.path().join("repo.git");
+        let status = Command::new("git")
+            .args(["init", "--bare", "--quiet"])
+            .arg(&git_dir)
+            .status()
+            .unwrap();
+        assert!(status.success());
+        let log_path = temp_dir.path().join("log");
+        let helper = format!(
+            "!f() {{ echo \"$1\" >>'{log}'; test \"$1\" = get && echo username=alice && echo \
+             password=secret; true; }}; f",
+            log = log_path.display()
+        );
+        // The empty value resets the helpers configured globally.
+        for value in ["", &helper] {
+            let status = Command::new("git")
+                .arg("--git-dir")
+                .arg(&git_dir)
+                .args(["config", "--add", "credential.helper", value])
+                .status()
+                .unwrap();
+            assert!(status.success());
+        }
+
+        let git_settings = GitSettings::default();
+        let git_ctx = GitSubprocessContext::new(&git_dir, &git_settings);
+        let filled = git_ctx
+            .spawn_credential(
+                GitCredentialAction::Fill,
+                &GitCredential::from_url("https://example.com/repo", None),
+            )
+            .unwrap();
+        assert_eq!(filled.get("protocol"), Some("https"));
+        assert_eq!(filled.get("host"), Some("example.com"));
+        assert_eq!(filled.get("username"), Some("alice"));
+        assert_eq!(filled.get("password"), Some("secret"));
+        git_ctx
+            .spawn_credential(GitCredentialAction::Approve, &filled)
+            .unwrap();
+        git_ctx
+            .spawn_credential(GitCredentialAction::Reject, &filled)
+            .unwrap();
+        assert_eq!(
+            std::fs::read_to_string(&log_path).unwrap(),
+            "get\nstore\nerase\n"
+        );
+    }
 }
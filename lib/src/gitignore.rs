@@ -42,17 +42,36 @@ pub enum GitIgnoreError {
     },
 }
 
+/// Names of the per-directory ignore files, in increasing order of precedence.
+///
+/// Patterns in `.ignore` override the ones in `.gitignore` in the same
+/// directory. Files in subdirectories override the ones in parent directories.
+pub const IGNORE_FILE_NAMES: [&str; 2] = [".gitignore", ".ignore"];
+
 /// Models the effective contents of multiple .gitignore files.
 #[derive(Debug)]
 pub struct GitIgnoreFile {
     parent: Option<Arc<GitIgnoreFile>>,
+    path: PathBuf,
     matcher: gitignore::Gitignore,
 }
 
+/// The ignore pattern that decided whether a path is ignored.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct IgnoreMatch {
+    /// File the pattern was read from.
+    pub ignore_file: PathBuf,
+    /// The pattern as written in the file.
+    pub pattern: String,
+    /// Whether the path is ignored, i.e. the pattern isn't negated with `!`.
+    pub is_ignored: bool,
+}
+
 impl GitIgnoreFile {
     pub fn empty() -> Arc<GitIgnoreFile> {
         Arc::new(GitIgnoreFile {
             parent: None,
+            path: PathBuf::new(),
             matcher: gitignore::Gitignore::empty(),
         })
     }
@@ -95,7 +114,11 @@ impl GitIgnoreFile {
         } else {
             Some(self.clone())
         };
-        Ok(Arc::new(GitIgnoreFile { parent, matcher }))
+        Ok(Arc::new(GitIgnoreFile {
+            parent,
+            path: ignore_path.to_path_buf(),
+            matcher,
+        }))
     }
 
     /// Concatenates new `.gitignore` file at the `prefix` directory.
@@ -118,19 +141,47 @@ impl GitIgnoreFile {
         }
     }
 
-    fn matches_helper(&self, path: &str, is_dir: bool) -> bool {
-        iter::successors(Some(self), |file| file.parent.as_deref())
-            .find_map(|file| {
-                // TODO: the documentation warns that
-                // `matched_path_or_any_parents` is slower than `matched`;
-                // ideally, we would switch to that.
-                match file.matcher.matched_path_or_any_parents(path, is_dir) {
-                    ignore::Match::None => None,
-                    ignore::Match::Ignore(_) => Some(true),
-                    ignore::Match::Whitelist(_) => Some(false),
-                }
+    /// Concatenates the per-directory ignore files in `dir` at the `prefix`
+    /// directory.
+    ///
+    /// The files are chained in the order of [`IGNORE_FILE_NAMES`]. The
+    /// `prefix` should be a slash-separated path relative to the workspace
+    /// root.
+    pub fn chain_with_dir(
+        self: &Arc<GitIgnoreFile>,
+        prefix: &str,
+        dir: &Path,
+    ) -> Result<Arc<GitIgnoreFile>, GitIgnoreError> {
+        IGNORE_FILE_NAMES
+            .iter()
+            .try_fold(self.clone(), |ignores, name| {
+                ignores.chain_with_file(prefix, dir.join(name))
             })
-            .unwrap_or_default()
+    }
+
+    fn matched_glob(
+        &self,
+        path: &str,
+        is_dir: bool,
+    ) -> Option<(&GitIgnoreFile, &gitignore::Glob, bool)> {
+        iter::successors(Some(self), |file| file.parent.as_deref()).find_map(|file| {
+            // TODO: the documentation warns that
+            // `matched_path_or_any_parents` is slower than `matched`;
+            // ideally, we would switch to that.
+            match file.matcher.matched_path_or_any_parents(path, is_dir) {
+                ignore::Match::None => None,
+                ignore::Match::Ignore(glob) => Some((file, glob, true)),
+                ignore::Match::Whitelist(glob) => Some((file, glob, false)),
+            }
+        })
+    }
+
+    fn split_dir_suffix(path: &str) -> (&str, bool) {
+        //If path ends with slash, consider it as a directory.
+        match path.strip_suffix('/') {
+            Some(path) => (path, true),
+            None => (path, false),
+        }
     }
 
     /// Returns whether specified path (not just file!) should be ignored. This
@@ -141,12 +192,22 @@ impl GitIgnoreFile {
     /// files within a ignored directory should be ignored unconditionally.
     /// The code in this file does not take that into account.
     pub fn matches(&self, path: &str) -> bool {
-        //If path ends with slash, consider it as a directory.
-        let (path, is_dir) = match path.strip_suffix('/') {
-            Some(path) => (path, true),
-            None => (path, false),
-        };
-        self.matches_helper(path, is_dir)
+        let (path, is_dir) = Self::split_dir_suffix(path);
+        self.matched_glob(path, is_dir)
+            .is_some_and(|(_, _, is_ignored)| is_ignored)
+    }
+
+    /// Returns the last applicable pattern for the specified path, which
+    /// decides whether the path is ignored. See [`GitIgnoreFile::matches()`]
+    /// for the caveats.
+    pub fn explain(&self, path: &str) -> Option<IgnoreMatch> {
+        let (path, is_dir) = Self::split_dir_suffix(path);
+        let (file, glob, is_ignored) = self.matched_glob(path, is_dir)?;
+        Some(IgnoreMatch {
+            ignore_file: file.path.clone(),
+            pattern: glob.original().to_owned(),
+            is_ignored,
+        })
     }
 }
 
@@ -453,4 +514,39 @@ mod tests {
             .unwrap();
         assert!(ignore.matches("foo/bar.ext"));
     }
+
+    #[test]
+    fn test_gitignore_explain() {
+        let ignore = GitIgnoreFile::empty()
+            .chain("", Path::new(".gitignore"), b"*.o\ndir/\n")
+            .unwrap()
+            .chain("", Path::new(".ignore"), b"!keep.o\n")
+            .unwrap();
+        assert_eq!(ignore.explain("foo.c"), None);
+        assert_eq!(
+            ignore.explain("foo.o"),
+            Some(IgnoreMatch {
+                ignore_file: PathBuf::from(".gitignore"),
+                pattern: "*.o".to_owned(),
+                is_ignored: true,
+            })
+        );
+        assert_eq!(
+            ignore.explain("keep.o"),
+            Some(IgnoreMatch {
+                ignore_file: PathBuf::from(".ignore"),
+                pattern: "!keep.o".to_owned(),
+                is_ignored: false,
+            })
+        );
+        assert_eq!(ignore.explain("dir"), None);
+        assert_eq!(
+            ignore.explain("dir/"),
+            Some(IgnoreMatch {
+                ignore_file: PathBuf::from(".gitignore"),
+                pattern: "dir/".to_owned(),
+                is_ignored: true,
+            })
+        );
+    }
 }
@@ -30,7 +30,9 @@ pub mod content_hash;
 
 pub mod absorb;
 pub mod annotate;
+pub mod api;
 pub mod backend;
+pub mod backend_policy;
 pub mod commit;
 pub mod commit_builder;
 pub mod config;
@@ -40,6 +42,7 @@ pub mod copies;
 pub mod dag_walk;
 pub mod default_index;
 pub mod default_submodule_store;
+pub mod description;
 pub mod diff;
 pub mod dsl_util;
 pub mod extensions_map;
@@ -49,6 +52,7 @@ pub mod fileset;
 mod fileset_parser;
 pub mod fmt_util;
 pub mod fsmonitor;
+pub mod gc;
 #[cfg(feature = "git")]
 pub mod git;
 #[cfg(not(feature = "git"))]
@@ -65,6 +69,8 @@ pub mod git {
 }
 #[cfg(feature = "git")]
 pub mod git_backend;
+#[cfg(feature = "git-native-http")]
+mod git_http;
 #[cfg(feature = "git")]
 mod git_subprocess;
 pub mod gitignore;
@@ -84,10 +90,12 @@ pub mod op_heads_store;
 pub mod op_store;
 pub mod op_walk;
 pub mod operation;
+pub mod patch;
 #[expect(missing_docs)]
 pub mod protos;
 pub mod refs;
 pub mod repo;
+pub mod repo_format;
 pub mod repo_path;
 pub mod revset;
 mod revset_parser;
@@ -109,6 +117,7 @@ pub mod time_util;
 pub mod transaction;
 pub mod tree;
 pub mod tree_builder;
+pub mod undo_stash;
 pub mod union_find;
 pub mod view;
 pub mod working_copy;
@@ -190,6 +190,16 @@ impl Backend for LocalBackend {
         Ok(Box::new(file))
     }
 
+    async fn contains_file(&self, _path: &RepoPath, id: &FileId) -> BackendResult<bool> {
+        Ok(self.file_path(id).is_file())
+    }
+
+    async fn file_size(&self, _path: &RepoPath, id: &FileId) -> BackendResult<u64> {
+        let path = self.file_path(id);
+        let metadata = fs::metadata(path).map_err(|err| map_not_found_err(err, id))?;
+        Ok(metadata.len())
+    }
+
     async fn write_file(
         &self,
         _path: &RepoPath,
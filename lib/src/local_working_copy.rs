@@ -17,6 +17,8 @@
 
 use std::any::Any;
 use std::cmp::Ordering;
+use std::collections::BTreeMap;
+use std::collections::BTreeSet;
 use std::collections::HashSet;
 use std::error::Error;
 use std::fs;
@@ -35,10 +37,17 @@ use std::os::unix::fs::PermissionsExt;
 use std::path::Path;
 use std::path::PathBuf;
 use std::slice;
+use std::sync::atomic;
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::AtomicUsize;
 use std::sync::mpsc::channel;
+use std::sync::mpsc::Receiver;
 use std::sync::mpsc::Sender;
 use std::sync::Arc;
 use std::sync::OnceLock;
+use std::time::Duration;
+use std::time::Instant;
+use std::time::SystemTime;
 use std::time::UNIX_EPOCH;
 
 use either::Either;
@@ -58,6 +67,7 @@ use tracing::trace_span;
 
 use crate::backend::BackendError;
 use crate::backend::BackendResult;
+use crate::backend::CommitId;
 use crate::backend::FileId;
 use crate::backend::MergedTreeId;
 use crate::backend::MillisSinceEpoch;
@@ -80,6 +90,7 @@ use crate::fsmonitor::FsmonitorSettings;
 #[cfg(feature = "watchman")]
 use crate::fsmonitor::WatchmanConfig;
 use crate::gitignore::GitIgnoreFile;
+use crate::local_backend::LocalBackend;
 use crate::lock::FileLock;
 use crate::matchers::DifferenceMatcher;
 use crate::matchers::EverythingMatcher;
@@ -101,15 +112,19 @@ use crate::repo_path::RepoPathBuf;
 use crate::repo_path::RepoPathComponent;
 use crate::store::Store;
 use crate::tree::Tree;
+use crate::undo_stash::UndoStash;
+use crate::undo_stash::UndoStashEntryWriter;
 use crate::working_copy::CheckoutError;
 use crate::working_copy::CheckoutOptions;
 use crate::working_copy::CheckoutStats;
 use crate::working_copy::LockedWorkingCopy;
+use crate::working_copy::NestedRepositoryPolicy;
 use crate::working_copy::ResetError;
 use crate::working_copy::SnapshotError;
 use crate::working_copy::SnapshotOptions;
 use crate::working_copy::SnapshotProgress;
 use crate::working_copy::SnapshotStats;
+use crate::working_copy::SnapshotTimings;
 use crate::working_copy::UntrackedReason;
 use crate::working_copy::WorkingCopy;
 use crate::working_copy::WorkingCopyFactory;
@@ -577,6 +592,10 @@ fn create_parent_dirs(
     Ok(Some(file_path))
 }
 
+fn undo_stash_at(state_path: &Path) -> UndoStash {
+    UndoStash::new(state_path.join("undo-stash"))
+}
+
 /// Removes existing file named `disk_path` if any. Returns `Ok(true)` if the
 /// file was there and got removed, meaning that new file can be safely created.
 ///
@@ -672,6 +691,37 @@ fn reject_reserved_existing_path(disk_path: &Path) -> Result<(), CheckoutError>
     Ok(())
 }
 
+/// Returns the commit checked out in the Git repository at `disk_dir`, or
+/// `None` if there's no Git repository or its `HEAD` is unborn.
+#[cfg(feature = "git")]
+fn read_nested_git_head(disk_dir: &Path) -> Result<Option<CommitId>, SnapshotError> {
+    let to_snapshot_err = |err: Box<dyn std::error::Error + Send + Sync>| SnapshotError::Other {
+        message: format!(
+            "Failed to read HEAD of nested Git repository {}",
+            disk_dir.display()
+        ),
+        err,
+    };
+    if !disk_dir.join(".git").exists() {
+        return Ok(None);
+    }
+    let repo = gix::open_opts(disk_dir, gix::open::Options::isolated())
+        .map_err(|err| to_snapshot_err(err.into()))?;
+    let head_id = repo.head_id().ok();
+    Ok(head_id.map(|id| CommitId::from_bytes(id.as_bytes())))
+}
+
+#[cfg(not(feature = "git"))]
+fn read_nested_git_head(disk_dir: &Path) -> Result<Option<CommitId>, SnapshotError> {
+    Err(SnapshotError::Other {
+        message: format!(
+            "Cannot record nested Git repository {} as submodule",
+            disk_dir.display()
+        ),
+        err: "Git support is disabled".into(),
+    })
+}
+
 fn mtime_from_metadata(metadata: &Metadata) -> MillisSinceEpoch {
     let time = metadata
         .modified()
@@ -808,6 +858,30 @@ impl TreeState {
         Ok(wc)
     }
 
+    /// Reads the sparse patterns from the tree state file in `state_path`.
+    fn read_sparse_patterns(state_path: &Path) -> Result<Vec<RepoPathBuf>, TreeStateError> {
+        let tree_state_path = state_path.join("tree_state");
+        let buf = match fs::read(&tree_state_path) {
+            Err(ref err) if err.kind() == io::ErrorKind::NotFound => {
+                return Ok(vec![RepoPathBuf::root()]);
+            }
+            Err(err) => {
+                return Err(TreeStateError::ReadTreeState {
+                    path: tree_state_path,
+                    source: err,
+                });
+            }
+            Ok(buf) => buf,
+        };
+        let proto = crate::protos::working_copy::TreeState::decode(&*buf).map_err(|err| {
+            TreeStateError::DecodeTreeState {
+                path: tree_state_path,
+                source: err,
+            }
+        })?;
+        Ok(sparse_patterns_from_proto(proto.sparse_patterns.as_ref()))
+    }
+
     fn update_own_mtime(&mut self) {
         if let Ok(metadata) = self.state_path.join("tree_state").symlink_metadata() {
             self.own_mtime = mtime_from_metadata(&metadata);
@@ -948,23 +1022,14 @@ impl TreeState {
         &mut self,
         options: &SnapshotOptions,
     ) -> Result<(bool, SnapshotStats), SnapshotError> {
-        let &SnapshotOptions {
-            ref base_ignores,
-            ref fsmonitor_settings,
-            progress,
-            start_tracking_matcher,
-            max_new_file_size,
-            conflict_marker_style,
-        } = options;
-
         let sparse_matcher = self.sparse_matcher();
 
-        let fsmonitor_clock_needs_save = *fsmonitor_settings != FsmonitorSettings::None;
+        let fsmonitor_clock_needs_save = options.fsmonitor_settings != FsmonitorSettings::None;
         let mut is_dirty = fsmonitor_clock_needs_save;
         let FsmonitorMatcher {
             matcher: fsmonitor_matcher,
             watchman_clock,
-        } = self.make_fsmonitor_matcher(fsmonitor_settings)?;
+        } = self.make_fsmonitor_matcher(&options.fsmonitor_settings)?;
         let fsmonitor_matcher = match fsmonitor_matcher.as_ref() {
             None => &EverythingMatcher,
             Some(fsmonitor_matcher) => fsmonitor_matcher.as_ref(),
@@ -977,45 +1042,17 @@ impl TreeState {
             return Ok((is_dirty, SnapshotStats::default()));
         }
 
-        let (tree_entries_tx, tree_entries_rx) = channel();
-        let (file_states_tx, file_states_rx) = channel();
-        let (untracked_paths_tx, untracked_paths_rx) = channel();
-        let (deleted_files_tx, deleted_files_rx) = channel();
-
-        trace_span!("traverse filesystem").in_scope(|| -> Result<(), SnapshotError> {
-            let snapshotter = FileSnapshotter {
-                tree_state: self,
-                current_tree: &self.current_tree()?,
-                matcher: &matcher,
-                start_tracking_matcher,
-                // Move tx sides so they'll be dropped at the end of the scope.
-                tree_entries_tx,
-                file_states_tx,
-                untracked_paths_tx,
-                deleted_files_tx,
-                error: OnceLock::new(),
-                progress,
-                max_new_file_size,
-                conflict_marker_style,
-            };
-            let directory_to_visit = DirectoryToVisit {
-                dir: RepoPathBuf::root(),
-                disk_dir: self.working_copy_path.clone(),
-                git_ignore: base_ignores.clone(),
-                file_states: self.file_states.all(),
-            };
-            // Here we use scope as a queue of per-directory jobs.
-            rayon::scope(|scope| {
-                snapshotter.spawn_ok(scope, |scope| {
-                    snapshotter.visit_directory(directory_to_visit, scope)
-                });
-            });
-            snapshotter.into_result()
-        })?;
-
-        let stats = SnapshotStats {
-            untracked_paths: untracked_paths_rx.into_iter().collect(),
-        };
+        let mut timings = SnapshotTimings::default();
+        let SnapshotReceivers {
+            tree_entries_rx,
+            file_states_rx,
+            untracked_paths_rx,
+            deleted_files_rx,
+            changed_paths_rx: _,
+        } = self.traverse_working_copy(options, &matcher, false, &mut timings)?;
+
+        let untracked_paths = untracked_paths_rx.into_iter().collect();
+        let update_state_start = Instant::now();
         let mut tree_builder = MergedTreeBuilder::new(self.tree_id.clone());
         trace_span!("process tree entries").in_scope(|| {
             for (path, tree_values) in &tree_entries_rx {
@@ -1039,11 +1076,18 @@ impl TreeState {
             self.file_states
                 .merge_in(changed_file_states, &deleted_files);
         });
+        timings.update_state = update_state_start.elapsed();
+        let write_tree_start = Instant::now();
         trace_span!("write tree").in_scope(|| {
             let new_tree_id = tree_builder.write_tree(&self.store).unwrap();
             is_dirty |= new_tree_id != self.tree_id;
             self.tree_id = new_tree_id;
         });
+        timings.write_tree = write_tree_start.elapsed();
+        let stats = SnapshotStats {
+            untracked_paths,
+            timings,
+        };
         if cfg!(debug_assertions) {
             let tree = self.current_tree().unwrap();
             let tree_paths: HashSet<_> = tree
@@ -1065,6 +1109,114 @@ impl TreeState {
         Ok((is_dirty, stats))
     }
 
+    /// Detects changes in the working copy without writing file contents or
+    /// trees to the store. The tree state isn't updated, so the detected
+    /// changes will be recorded by the next snapshot.
+    #[instrument(skip_all)]
+    pub fn detect_changes(
+        &self,
+        options: &SnapshotOptions,
+    ) -> Result<DetectedChanges, SnapshotError> {
+        let sparse_matcher = self.sparse_matcher();
+        let FsmonitorMatcher {
+            matcher: fsmonitor_matcher,
+            watchman_clock: _,
+        } = self.make_fsmonitor_matcher(&options.fsmonitor_settings)?;
+        let fsmonitor_matcher = match fsmonitor_matcher.as_ref() {
+            None => &EverythingMatcher,
+            Some(fsmonitor_matcher) => fsmonitor_matcher.as_ref(),
+        };
+        let matcher = IntersectionMatcher::new(sparse_matcher.as_ref(), fsmonitor_matcher);
+        if matcher.visit(RepoPath::root()).is_nothing() {
+            return Ok(DetectedChanges::default());
+        }
+
+        let receivers =
+            self.traverse_working_copy(options, &matcher, true, &mut SnapshotTimings::default())?;
+        Ok(DetectedChanges {
+            changed_paths: receivers.changed_paths_rx.into_iter().collect(),
+            deleted_paths: receivers.deleted_files_rx.into_iter().collect(),
+            untracked_paths: receivers.untracked_paths_rx.into_iter().collect(),
+        })
+    }
+
+    /// Walks the working copy and sends the changes found to the returned
+    /// receivers. If `detect_only` is true, changed files are compared with
+    /// the current tree instead of being written to the store.
+    fn traverse_working_copy(
+        &self,
+        options: &SnapshotOptions,
+        matcher: &dyn Matcher,
+        detect_only: bool,
+        timings: &mut SnapshotTimings,
+    ) -> Result<SnapshotReceivers, SnapshotError> {
+        let &SnapshotOptions {
+            ref base_ignores,
+            fsmonitor_settings: _,
+            progress,
+            start_tracking_matcher,
+            max_new_file_size,
+            conflict_marker_style,
+            nested_repository_policy,
+        } = options;
+
+        let (tree_entries_tx, tree_entries_rx) = channel();
+        let (file_states_tx, file_states_rx) = channel();
+        let (untracked_paths_tx, untracked_paths_rx) = channel();
+        let (deleted_files_tx, deleted_files_rx) = channel();
+        let (changed_paths_tx, changed_paths_rx) = channel();
+
+        let traverse_start = Instant::now();
+        trace_span!("traverse filesystem").in_scope(|| -> Result<(), SnapshotError> {
+            let snapshotter = FileSnapshotter {
+                tree_state: self,
+                current_tree: &self.current_tree()?,
+                matcher,
+                start_tracking_matcher,
+                detect_only,
+                // Move tx sides so they'll be dropped at the end of the scope.
+                tree_entries_tx,
+                file_states_tx,
+                untracked_paths_tx,
+                deleted_files_tx,
+                changed_paths_tx,
+                error: OnceLock::new(),
+                hash_nanos: AtomicU64::new(0),
+                hashed_file_count: AtomicUsize::new(0),
+                progress,
+                max_new_file_size,
+                conflict_marker_style,
+                nested_repository_policy,
+            };
+            let directory_to_visit = DirectoryToVisit {
+                dir: RepoPathBuf::root(),
+                disk_dir: self.working_copy_path.clone(),
+                git_ignore: base_ignores.clone(),
+                file_states: self.file_states.all(),
+            };
+            // Here we use scope as a queue of per-directory jobs.
+            rayon::scope(|scope| {
+                snapshotter.spawn_ok(scope, |scope| {
+                    snapshotter.visit_directory(directory_to_visit, scope)
+                });
+            });
+            timings.hash_files =
+                Duration::from_nanos(snapshotter.hash_nanos.load(atomic::Ordering::Relaxed));
+            timings.hashed_file_count = snapshotter
+                .hashed_file_count
+                .load(atomic::Ordering::Relaxed);
+            snapshotter.into_result()
+        })?;
+        timings.traverse = traverse_start.elapsed();
+        Ok(SnapshotReceivers {
+            tree_entries_rx,
+            file_states_rx,
+            untracked_paths_rx,
+            deleted_files_rx,
+            changed_paths_rx,
+        })
+    }
+
     #[instrument(skip_all)]
     fn make_fsmonitor_matcher(
         &self,
@@ -1111,6 +1263,27 @@ impl TreeState {
     }
 }
 
+/// Paths changed in the working copy since the last snapshot, detected without
+/// writing anything to the store.
+#[derive(Clone, Debug, Default)]
+pub struct DetectedChanges {
+    /// Added or modified files.
+    pub changed_paths: BTreeSet<RepoPathBuf>,
+    /// Deleted files.
+    pub deleted_paths: BTreeSet<RepoPathBuf>,
+    /// New files which wouldn't be tracked by snapshot.
+    pub untracked_paths: BTreeMap<RepoPathBuf, UntrackedReason>,
+}
+
+/// Receiving sides of the channels `FileSnapshotter` sends changes to.
+struct SnapshotReceivers {
+    tree_entries_rx: Receiver<(RepoPathBuf, MergedTreeValue)>,
+    file_states_rx: Receiver<(RepoPathBuf, FileState)>,
+    untracked_paths_rx: Receiver<(RepoPathBuf, UntrackedReason)>,
+    deleted_files_rx: Receiver<RepoPathBuf>,
+    changed_paths_rx: Receiver<RepoPathBuf>,
+}
+
 struct DirectoryToVisit<'a> {
     dir: RepoPathBuf,
     disk_dir: PathBuf,
@@ -1136,14 +1309,20 @@ struct FileSnapshotter<'a> {
     current_tree: &'a MergedTree,
     matcher: &'a dyn Matcher,
     start_tracking_matcher: &'a dyn Matcher,
+    /// Compare changed files with the current tree instead of writing them.
+    detect_only: bool,
     tree_entries_tx: Sender<(RepoPathBuf, MergedTreeValue)>,
     file_states_tx: Sender<(RepoPathBuf, FileState)>,
     untracked_paths_tx: Sender<(RepoPathBuf, UntrackedReason)>,
     deleted_files_tx: Sender<RepoPathBuf>,
+    changed_paths_tx: Sender<RepoPathBuf>,
     error: OnceLock<SnapshotError>,
+    hash_nanos: AtomicU64,
+    hashed_file_count: AtomicUsize,
     progress: Option<&'a SnapshotProgress<'a>>,
     max_new_file_size: u64,
     conflict_marker_style: ConflictMarkerStyle,
+    nested_repository_policy: NestedRepositoryPolicy,
 }
 
 impl FileSnapshotter<'_> {
@@ -1184,8 +1363,7 @@ impl FileSnapshotter<'_> {
             file_states,
         } = directory_to_visit;
 
-        let git_ignore = git_ignore
-            .chain_with_file(&dir.to_internal_dir_string(), disk_dir.join(".gitignore"))?;
+        let git_ignore = git_ignore.chain_with_dir(&dir.to_internal_dir_string(), &disk_dir)?;
         let dir_entries: Vec<_> = disk_dir
             .read_dir()
             .and_then(|entries| entries.try_collect())
@@ -1193,6 +1371,15 @@ impl FileSnapshotter<'_> {
                 message: format!("Failed to read directory {}", disk_dir.display()),
                 err: err.into(),
             })?;
+        if !dir.is_root()
+            && dir_entries.iter().any(|entry| {
+                RESERVED_DIR_NAMES
+                    .iter()
+                    .any(|name| entry.file_name() == *name)
+            })
+        {
+            return self.visit_nested_repository(&dir, &disk_dir, file_states);
+        }
         let (dirs, files) = dir_entries
             .into_par_iter()
             // Don't split into too many small jobs. For a small directory,
@@ -1235,6 +1422,14 @@ impl FileSnapshotter<'_> {
         let maybe_current_file_state = file_states.get_at(dir, name);
         if let Some(file_state) = &maybe_current_file_state {
             if file_state.file_type == FileType::GitSubmodule {
+                if file_type.is_dir()
+                    && self.nested_repository_policy == NestedRepositoryPolicy::Submodule
+                    && self.matcher.matches(&path)
+                {
+                    if let Some(commit_id) = read_nested_git_head(&entry.path())? {
+                        self.process_submodule(path, commit_id, Some(file_state))?;
+                    }
+                }
                 return Ok(None);
             }
         }
@@ -1311,6 +1506,68 @@ impl FileSnapshotter<'_> {
         }
     }
 
+    /// Applies the [`NestedRepositoryPolicy`] to the directory `dir`, which
+    /// contains another repository.
+    fn visit_nested_repository(
+        &self,
+        dir: &RepoPath,
+        disk_dir: &Path,
+        file_states: FileStates<'_>,
+    ) -> Result<(), SnapshotError> {
+        match self.nested_repository_policy {
+            NestedRepositoryPolicy::Ignore => self.visit_tracked_files(file_states),
+            NestedRepositoryPolicy::Error => Err(SnapshotError::NestedRepository {
+                path: disk_dir.to_owned(),
+            }),
+            NestedRepositoryPolicy::Submodule => {
+                let Some(commit_id) = read_nested_git_head(disk_dir)? else {
+                    return self.visit_tracked_files(file_states);
+                };
+                // The submodule replaces any files tracked in the directory.
+                // (file_states may contain <dir> file on file->dir transition,
+                // which is overwritten by the submodule.)
+                for (tracked_path, _) in file_states {
+                    if tracked_path != dir && self.matcher.matches(tracked_path) {
+                        self.deleted_files_tx.send(tracked_path.to_owned()).ok();
+                    }
+                }
+                let current_file_state = file_states.get(dir);
+                self.process_submodule(dir.to_owned(), commit_id, current_file_state.as_ref())
+            }
+        }
+    }
+
+    fn process_submodule(
+        &self,
+        path: RepoPathBuf,
+        commit_id: CommitId,
+        maybe_current_file_state: Option<&FileState>,
+    ) -> Result<(), SnapshotError> {
+        if self.tree_state.store.backend_impl().is::<LocalBackend>() {
+            return Err(SnapshotError::Other {
+                message: format!("Cannot record submodule {}", path.as_internal_file_string()),
+                err: "The local backend doesn't support submodules".into(),
+            });
+        }
+        let new_tree_value = Merge::normal(TreeValue::GitSubmodule(commit_id));
+        if self.detect_only {
+            if self.current_tree.path_value(&path)? != new_tree_value {
+                self.changed_paths_tx.send(path).ok();
+            }
+            return Ok(());
+        }
+        if self.current_tree.path_value(&path)? != new_tree_value {
+            self.tree_entries_tx
+                .send((path.clone(), new_tree_value))
+                .ok();
+        }
+        let new_file_state = FileState::for_gitsubmodule();
+        if Some(&new_file_state) != maybe_current_file_state {
+            self.file_states_tx.send((path, new_file_state)).ok();
+        }
+        Ok(())
+    }
+
     /// Visits only paths we're already tracking.
     fn visit_tracked_files(&self, file_states: FileStates<'_>) -> Result<(), SnapshotError> {
         for (tracked_path, current_file_state) in file_states {
@@ -1352,6 +1609,14 @@ impl FileSnapshotter<'_> {
         maybe_current_file_state: Option<&FileState>,
         mut new_file_state: FileState,
     ) -> Result<(), SnapshotError> {
+        if self.detect_only {
+            if !self.is_clean(maybe_current_file_state, &new_file_state)
+                && self.is_file_changed(&path, disk_path, &new_file_state)?
+            {
+                self.changed_paths_tx.send(path).ok();
+            }
+            return Ok(());
+        }
         let update = self.get_updated_tree_value(
             &path,
             disk_path,
@@ -1414,33 +1679,12 @@ impl FileSnapshotter<'_> {
         maybe_current_file_state: Option<&FileState>,
         new_file_state: &FileState,
     ) -> Result<Option<MergedTreeValue>, SnapshotError> {
-        let clean = match maybe_current_file_state {
-            None => {
-                // untracked
-                false
-            }
-            Some(current_file_state) => {
-                // If the file's mtime was set at the same time as this state file's own mtime,
-                // then we don't know if the file was modified before or after this state file.
-                new_file_state.is_clean(current_file_state)
-                    && current_file_state.mtime < self.tree_state.own_mtime
-            }
-        };
-        if clean {
+        if self.is_clean(maybe_current_file_state, new_file_state) {
             Ok(None)
         } else {
             let current_tree_values = self.current_tree.path_value(repo_path)?;
-            let new_file_type = if !self.tree_state.symlink_support {
-                let mut new_file_type = new_file_state.file_type.clone();
-                if matches!(new_file_type, FileType::Normal { .. })
-                    && matches!(current_tree_values.as_normal(), Some(TreeValue::Symlink(_)))
-                {
-                    new_file_type = FileType::Symlink;
-                }
-                new_file_type
-            } else {
-                new_file_state.file_type.clone()
-            };
+            let new_file_type = self.effective_file_type(new_file_state, &current_tree_values);
+            let hash_start = Instant::now();
             let new_tree_values = match new_file_type {
                 FileType::Normal { executable } => self
                     .write_path_to_store(
@@ -1459,6 +1703,11 @@ impl FileSnapshotter<'_> {
                 }
                 FileType::GitSubmodule => panic!("git submodule cannot be written to store"),
             };
+            let hash_nanos = u64::try_from(hash_start.elapsed().as_nanos()).unwrap_or(u64::MAX);
+            self.hash_nanos
+                .fetch_add(hash_nanos, atomic::Ordering::Relaxed);
+            self.hashed_file_count
+                .fetch_add(1, atomic::Ordering::Relaxed);
             if new_tree_values != current_tree_values {
                 Ok(Some(new_tree_values))
             } else {
@@ -1467,6 +1716,99 @@ impl FileSnapshotter<'_> {
         }
     }
 
+    /// Returns true if the file doesn't appear to have been modified since the
+    /// last snapshot.
+    fn is_clean(
+        &self,
+        maybe_current_file_state: Option<&FileState>,
+        new_file_state: &FileState,
+    ) -> bool {
+        match maybe_current_file_state {
+            None => {
+                // untracked
+                false
+            }
+            Some(current_file_state) => {
+                // If the file's mtime was set at the same time as this state file's own mtime,
+                // then we don't know if the file was modified before or after this state file.
+                new_file_state.is_clean(current_file_state)
+                    && current_file_state.mtime < self.tree_state.own_mtime
+            }
+        }
+    }
+
+    /// Returns the type the file should be recorded as. A symlink checked out
+    /// as a regular file stays a symlink if symlinks aren't supported.
+    fn effective_file_type(
+        &self,
+        new_file_state: &FileState,
+        current_tree_values: &MergedTreeValue,
+    ) -> FileType {
+        let mut new_file_type = new_file_state.file_type.clone();
+        if !self.tree_state.symlink_support
+            && matches!(new_file_type, FileType::Normal { .. })
+            && matches!(current_tree_values.as_normal(), Some(TreeValue::Symlink(_)))
+        {
+            new_file_type = FileType::Symlink;
+        }
+        new_file_type
+    }
+
+    /// Compares the file on disk with the current tree without writing it to
+    /// the store. Conflicted files are reported as changed.
+    fn is_file_changed(
+        &self,
+        repo_path: &RepoPath,
+        disk_path: &Path,
+        new_file_state: &FileState,
+    ) -> Result<bool, SnapshotError> {
+        let current_tree_values = self.current_tree.path_value(repo_path)?;
+        let new_file_type = self.effective_file_type(new_file_state, &current_tree_values);
+        let hash_start = Instant::now();
+        let changed = match (current_tree_values.as_normal(), new_file_type) {
+            (
+                Some(TreeValue::File { id, executable }),
+                FileType::Normal {
+                    executable: new_executable,
+                },
+            ) => {
+                // On Windows, the executable bit is preserved from the current tree.
+                #[cfg(windows)]
+                let new_executable = {
+                    let () = new_executable; // use the variable
+                    *executable
+                };
+                let mut old_content = vec![];
+                self.store()
+                    .read_file(repo_path, id)?
+                    .read_to_end(&mut old_content)
+                    .map_err(|err| SnapshotError::Other {
+                        message: format!(
+                            "Failed to read file {}",
+                            repo_path.as_internal_file_string()
+                        ),
+                        err: err.into(),
+                    })?;
+                let new_content = fs::read(disk_path).map_err(|err| SnapshotError::Other {
+                    message: format!("Failed to read file {}", disk_path.display()),
+                    err: err.into(),
+                })?;
+                *executable != new_executable || old_content != new_content
+            }
+            (Some(TreeValue::Symlink(id)), FileType::Symlink) => {
+                let old_target = self.store().read_symlink(repo_path, id)?;
+                old_target != self.read_symlink_target(disk_path)?
+            }
+            _ => true,
+        };
+        let hash_nanos = u64::try_from(hash_start.elapsed().as_nanos()).unwrap_or(u64::MAX);
+        self.hash_nanos
+            .fetch_add(hash_nanos, atomic::Ordering::Relaxed);
+        self.hashed_file_count
+            .fetch_add(1, atomic::Ordering::Relaxed);
+        Ok(changed)
+    }
+
     fn store(&self) -> &Store {
         &self.tree_state.store
     }
@@ -1560,6 +1902,13 @@ impl FileSnapshotter<'_> {
         path: &RepoPath,
         disk_path: &Path,
     ) -> Result<SymlinkId, SnapshotError> {
+        let target = self.read_symlink_target(disk_path)?;
+        Ok(self.store().write_symlink(path, &target).await?)
+    }
+
+    /// Reads the symlink target, or the file content if symlinks aren't
+    /// supported.
+    fn read_symlink_target(&self, disk_path: &Path) -> Result<String, SnapshotError> {
         if self.tree_state.symlink_support {
             let target = disk_path.read_link().map_err(|err| SnapshotError::Other {
                 message: format!("Failed to read symlink {}", disk_path.display()),
@@ -1571,17 +1920,15 @@ impl FileSnapshotter<'_> {
                     .ok_or_else(|| SnapshotError::InvalidUtf8SymlinkTarget {
                         path: disk_path.to_path_buf(),
                     })?;
-            Ok(self.store().write_symlink(path, str_target).await?)
+            Ok(str_target.to_owned())
         } else {
             let target = fs::read(disk_path).map_err(|err| SnapshotError::Other {
                 message: format!("Failed to read file {}", disk_path.display()),
                 err: err.into(),
             })?;
-            let string_target =
-                String::from_utf8(target).map_err(|_| SnapshotError::InvalidUtf8SymlinkTarget {
-                    path: disk_path.to_path_buf(),
-                })?;
-            Ok(self.store().write_symlink(path, &string_target).await?)
+            String::from_utf8(target).map_err(|_| SnapshotError::InvalidUtf8SymlinkTarget {
+                path: disk_path.to_path_buf(),
+            })
         }
     }
 }
@@ -1677,6 +2024,7 @@ impl TreeState {
         Ok(())
     }
 
+    #[instrument(skip_all)]
     pub fn check_out(
         &mut self,
         new_tree: &MergedTree,
@@ -1689,17 +2037,13 @@ impl TreeState {
             other => CheckoutError::InternalBackendError(other),
         })?;
         let stats = self
-            .update(
-                &old_tree,
-                new_tree,
-                self.sparse_matcher().as_ref(),
-                options.conflict_marker_style,
-            )
+            .update(&old_tree, new_tree, self.sparse_matcher().as_ref(), options)
             .block_on()?;
         self.tree_id = new_tree.id();
         Ok(stats)
     }
 
+    #[instrument(skip_all)]
     pub fn set_sparse_patterns(
         &mut self,
         sparse_patterns: Vec<RepoPathBuf>,
@@ -1717,20 +2061,10 @@ impl TreeState {
         let removed_matcher = DifferenceMatcher::new(&old_matcher, &new_matcher);
         let empty_tree = MergedTree::resolved(Tree::empty(self.store.clone(), RepoPathBuf::root()));
         let added_stats = self
-            .update(
-                &empty_tree,
-                &tree,
-                &added_matcher,
-                options.conflict_marker_style,
-            )
+            .update(&empty_tree, &tree, &added_matcher, options)
             .block_on()?;
         let removed_stats = self
-            .update(
-                &tree,
-                &empty_tree,
-                &removed_matcher,
-                options.conflict_marker_style,
-            )
+            .update(&tree, &empty_tree, &removed_matcher, options)
             .block_on()?;
         self.sparse_patterns = sparse_patterns;
         assert_eq!(added_stats.updated_files, 0);
@@ -1746,13 +2080,39 @@ impl TreeState {
         })
     }
 
+    /// Lets the backend fetch the files to be checked out at once.
+    async fn prefetch_files(
+        &self,
+        old_tree: &MergedTree,
+        new_tree: &MergedTree,
+        matcher: &dyn Matcher,
+    ) -> Result<(), CheckoutError> {
+        let mut file_ids = vec![];
+        let mut diff_stream = old_tree.diff_stream(new_tree, matcher);
+        while let Some(TreeDiffEntry { values, .. }) = diff_stream.next().await {
+            let (_before, after) = values?;
+            for value in after.into_iter().flatten() {
+                if let TreeValue::File { id, .. } = value {
+                    file_ids.push(id);
+                }
+            }
+        }
+        self.store.prefetch_files(&file_ids).await?;
+        Ok(())
+    }
+
     async fn update(
         &mut self,
         old_tree: &MergedTree,
         new_tree: &MergedTree,
         matcher: &dyn Matcher,
-        conflict_marker_style: ConflictMarkerStyle,
+        options: &CheckoutOptions,
     ) -> Result<CheckoutStats, CheckoutError> {
+        let conflict_marker_style = options.conflict_marker_style;
+        let now = SystemTime::now();
+        let mut undo_stash_entry = options
+            .undo_stash_retention
+            .map(|_| self.undo_stash().start_entry(now));
         // TODO: maybe it's better not include the skipped counts in the "intended"
         // counts
         let mut stats = CheckoutStats {
@@ -1761,6 +2121,7 @@ impl TreeState {
             removed_files: 0,
             skipped_files: 0,
         };
+        self.prefetch_files(old_tree, new_tree, matcher).await?;
         let mut changed_file_states = Vec::new();
         let mut deleted_files = HashSet::new();
         let mut diff_stream = old_tree
@@ -1795,7 +2156,7 @@ impl TreeState {
             if matches!(before.as_normal(), Some(TreeValue::GitSubmodule(_)))
                 && matches!(after, MaterializedTreeValue::GitSubmodule(_))
             {
-                eprintln!("ignoring git submodule at {path:?}");
+                tracing::debug!(?path, "ignoring git submodule");
                 // Not updating the file state as if there were no diffs. Leave
                 // the state type as FileType::GitSubmodule if it was before.
                 continue;
@@ -1808,6 +2169,11 @@ impl TreeState {
                 stats.skipped_files += 1;
                 continue;
             };
+            if let Some(entry) = &mut undo_stash_entry {
+                if before.is_present() {
+                    self.stash_modified_file(entry, &path, &disk_path)?;
+                }
+            }
             // If the path was present, check reserved path first and delete it.
             let present_file_deleted = before.is_present() && remove_old_file(&disk_path)?;
             // If not, create temporary file to test the path validity.
@@ -1843,7 +2209,7 @@ impl TreeState {
                     }
                 }
                 MaterializedTreeValue::GitSubmodule(_) => {
-                    eprintln!("ignoring git submodule at {path:?}");
+                    tracing::debug!(?path, "ignoring git submodule");
                     FileState::for_gitsubmodule()
                 }
                 MaterializedTreeValue::Tree(_) => {
@@ -1883,9 +2249,52 @@ impl TreeState {
         }
         self.file_states
             .merge_in(changed_file_states, &deleted_files);
+        if let Some(retention) = options.undo_stash_retention {
+            self.undo_stash()
+                .prune(retention, now)
+                .map_err(|err| CheckoutError::Other {
+                    message: "Failed to prune undo stash".to_owned(),
+                    err: err.into(),
+                })?;
+        }
         Ok(stats)
     }
 
+    /// Stash of locally-modified files replaced by checkout.
+    pub fn undo_stash(&self) -> UndoStash {
+        undo_stash_at(&self.state_path)
+    }
+
+    /// Copies the file at `disk_path` to the undo stash if it has been
+    /// modified since the last snapshot.
+    fn stash_modified_file(
+        &self,
+        entry: &mut UndoStashEntryWriter,
+        path: &RepoPath,
+        disk_path: &Path,
+    ) -> Result<(), CheckoutError> {
+        let Ok(metadata) = disk_path.symlink_metadata() else {
+            return Ok(()); // Nothing to lose
+        };
+        if !metadata.is_file() {
+            return Ok(());
+        }
+        // Unlike snapshot, racy-clean files aren't inspected further.
+        let current_file_state = file_state(&metadata);
+        let old_file_state = self.file_states.all().get(path);
+        if let (Some(current), Some(old)) = (current_file_state, old_file_state) {
+            if current.is_clean(&old) {
+                return Ok(());
+            }
+        }
+        entry
+            .add_file(path, disk_path)
+            .map_err(|err| CheckoutError::Other {
+                message: format!("Failed to stash file {}", disk_path.display()),
+                err: err.into(),
+            })
+    }
+
     pub async fn reset(&mut self, new_tree: &MergedTree) -> Result<(), ResetError> {
         let old_tree = self.current_tree().map_err(|err| match err {
             err @ BackendError::ObjectNotFound { .. } => ResetError::SourceNotFound {
@@ -1914,7 +2323,7 @@ impl TreeState {
                             panic!("unexpected conflict entry in diff at {path:?}");
                         }
                         TreeValue::GitSubmodule(_id) => {
-                            eprintln!("ignoring git submodule at {path:?}");
+                            tracing::debug!(?path, "ignoring git submodule");
                             FileType::GitSubmodule
                         }
                         TreeValue::Tree(_id) => {
@@ -2086,6 +2495,62 @@ impl LocalWorkingCopy {
         &self.state_path
     }
 
+    /// Stash of locally-modified files replaced by checkout.
+    pub fn undo_stash(&self) -> UndoStash {
+        undo_stash_at(&self.state_path)
+    }
+
+    /// Reads the sparse patterns recorded in the tree state file, without
+    /// loading the file states.
+    pub fn read_sparse_patterns(&self) -> Result<Vec<RepoPathBuf>, WorkingCopyStateError> {
+        TreeState::read_sparse_patterns(&self.state_path).map_err(|err| WorkingCopyStateError {
+            message: "Failed to read the sparse patterns".to_owned(),
+            err: err.into(),
+        })
+    }
+
+    /// Replaces the tree state with one that has the given commit's tree
+    /// checked out within the `sparse_patterns`, without reading the existing
+    /// file states.
+    ///
+    /// This can be used to recover from a corrupted state file. Since the
+    /// previous file states are lost, all files will be rescanned by the next
+    /// snapshot. The `sparse_patterns` should match the files present on
+    /// disk, or else the files outside of them will be snapshotted as deleted.
+    pub fn repair_tree_state(
+        &self,
+        commit: &Commit,
+        sparse_patterns: Vec<RepoPathBuf>,
+    ) -> Result<(), WorkingCopyStateError> {
+        let lock_path = self.state_path.join("working_copy.lock");
+        let _lock = FileLock::lock(lock_path).map_err(|err| WorkingCopyStateError {
+            message: "Failed to lock working copy".to_owned(),
+            err: err.into(),
+        })?;
+        let mut tree_state = TreeState::empty(
+            self.store.clone(),
+            self.working_copy_path.clone(),
+            self.state_path.clone(),
+        );
+        tree_state.sparse_patterns = sparse_patterns;
+        let new_tree = commit.tree().map_err(|err| WorkingCopyStateError {
+            message: "Failed to read the working-copy commit's tree".to_owned(),
+            err: err.into(),
+        })?;
+        tree_state
+            .recover(&new_tree)
+            .block_on()
+            .map_err(|err| WorkingCopyStateError {
+                message: "Failed to rebuild working copy state".to_owned(),
+                err: err.into(),
+            })?;
+        tree_state.save().map_err(|err| WorkingCopyStateError {
+            message: "Failed to write working copy state".to_owned(),
+            err: err.into(),
+        })?;
+        Ok(())
+    }
+
     fn write_proto(&self, proto: crate::protos::working_copy::Checkout) {
         let mut temp_file = NamedTempFile::new_in(&self.state_path).unwrap();
         temp_file
@@ -2144,6 +2609,57 @@ impl LocalWorkingCopy {
         Ok(self.tree_state()?.file_states())
     }
 
+    /// Checks whether the tracked file at `path` appears to have been modified
+    /// since the last snapshot. Returns `None` if the file isn't tracked.
+    ///
+    /// Unlike snapshotting, this only compares the file type, mtime, and size
+    /// with the recorded state, so a file touched without changing its
+    /// contents is reported as modified.
+    pub fn is_file_modified(&self, path: &RepoPath) -> Result<Option<bool>, WorkingCopyStateError> {
+        let Some(old_file_state) = self.file_states()?.get(path) else {
+            return Ok(None);
+        };
+        if old_file_state.file_type == FileType::GitSubmodule {
+            // Submodules aren't snapshotted.
+            return Ok(Some(false));
+        }
+        let disk_path =
+            path.to_fs_path(&self.working_copy_path)
+                .map_err(|err| WorkingCopyStateError {
+                    message: "Invalid file path".to_string(),
+                    err: err.into(),
+                })?;
+        let new_file_state = match disk_path.symlink_metadata() {
+            Ok(metadata) => file_state(&metadata),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => None,
+            Err(err) => {
+                return Err(WorkingCopyStateError {
+                    message: format!("Failed to stat file {}", disk_path.display()),
+                    err: err.into(),
+                });
+            }
+        };
+        Ok(Some(new_file_state.is_none_or(|new_file_state| {
+            !new_file_state.is_clean(&old_file_state)
+        })))
+    }
+
+    /// Detects changes in the working copy since the last snapshot without
+    /// writing anything to the store or updating the working-copy state.
+    ///
+    /// Unlike [`Self::is_file_modified()`], the contents of the files which
+    /// appear to have been modified are compared with the current tree.
+    pub fn detect_changes(
+        &self,
+        options: &SnapshotOptions,
+    ) -> Result<DetectedChanges, SnapshotError> {
+        let tree_state = self.tree_state().map_err(|err| SnapshotError::Other {
+            message: "Failed to read the working copy state".to_string(),
+            err: err.into(),
+        })?;
+        tree_state.detect_changes(options)
+    }
+
     #[instrument(skip_all)]
     fn save(&mut self) {
         self.write_proto(crate::protos::working_copy::Checkout {
@@ -85,4 +85,26 @@ mod tests {
         let value = u32::from_le_bytes(data.try_into().unwrap());
         assert_eq!(value, num_threads as u32);
     }
+
+    #[test_case(FileLock::lock, FileLock::try_lock)]
+    #[cfg_attr(
+        unix,
+        test_case(fallback::FileLock::lock, fallback::FileLock::try_lock)
+    )]
+    fn try_lock_held<T>(
+        lock_fn: fn(PathBuf) -> Result<T, FileLockError>,
+        try_lock_fn: fn(PathBuf) -> Result<Option<T>, FileLockError>,
+    ) {
+        let temp_dir = new_temp_dir();
+        let lock_path = temp_dir.path().join("test.lock");
+        {
+            let _lock = lock_fn(lock_path.clone()).unwrap();
+            assert!(try_lock_fn(lock_path.clone()).unwrap().is_none());
+        }
+        let lock = try_lock_fn(lock_path.clone()).unwrap();
+        assert!(lock.is_some());
+        assert!(lock_path.exists());
+        drop(lock);
+        assert!(!lock_path.exists());
+    }
 }
@@ -61,6 +61,42 @@ impl FileLock {
             return Ok(Self { path, file });
         }
     }
+
+    /// Acquires the lock if it isn't held by another process.
+    ///
+    /// Returns `None` instead of waiting if the lock is already held.
+    pub fn try_lock(path: PathBuf) -> Result<Option<FileLock>, FileLockError> {
+        loop {
+            let file = File::create(&path).map_err(|err| FileLockError {
+                message: "Failed to open lock file",
+                path: path.clone(),
+                err,
+            })?;
+            match rustix::fs::flock(&file, FlockOperation::NonBlockingLockExclusive) {
+                Ok(()) => {}
+                Err(rustix::io::Errno::WOULDBLOCK) => return Ok(None),
+                Err(errno) => {
+                    return Err(FileLockError {
+                        message: "Failed to lock lock file",
+                        path,
+                        err: errno.into(),
+                    })
+                }
+            }
+
+            let stat = rustix::fs::fstat(&file).map_err(|errno| FileLockError {
+                message: "failed to stat lock file",
+                path: path.clone(),
+                err: errno.into(),
+            })?;
+            if stat.st_nlink == 0 {
+                // Lockfile was deleted by the previous holder; see `lock()`.
+                continue;
+            }
+
+            return Ok(Some(Self { path, file }));
+        }
+    }
 }
 
 impl Drop for FileLock {
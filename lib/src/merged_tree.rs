@@ -38,6 +38,7 @@ use itertools::Itertools;
 use pollster::FutureExt;
 
 use crate::backend;
+use crate::backend::BackendError;
 use crate::backend::BackendResult;
 use crate::backend::MergedTreeId;
 use crate::backend::TreeId;
@@ -161,6 +162,23 @@ impl MergedTree {
     /// Tries to resolve any conflicts, resolving any conflicts that can be
     /// automatically resolved and leaving the rest unresolved.
     pub fn resolve(&self) -> BackendResult<MergedTree> {
+        // Resolved root trees are cached by the store since the same conflicts
+        // are often resolved repeatedly.
+        let cache_key = (self.dir().is_root() && !self.trees.is_resolved())
+            .then(|| self.trees.map(|tree| tree.id().clone()));
+        let store = self.store();
+        if let Some(ids) = cache_key
+            .as_ref()
+            .and_then(|ids| store.get_resolved_tree_merge(ids))
+        {
+            // The resolved trees may have been garbage-collected since they
+            // were saved, in which case they're merged again.
+            match ids.try_map(|id| store.get_tree(RepoPathBuf::root(), id)) {
+                Ok(trees) => return Ok(MergedTree { trees }),
+                Err(BackendError::ObjectNotFound { .. }) => {}
+                Err(err) => return Err(err),
+            }
+        }
         let merged = merge_trees(&self.trees)?;
         // If the result can be resolved, then `merge_trees()` above would have returned
         // a resolved merge. However, that function will always preserve the arity of
@@ -174,6 +192,9 @@ impl MergedTree {
             let re_merged = merge_trees(&simplified).unwrap();
             debug_assert_eq!(re_merged, simplified);
         }
+        if let Some(ids) = cache_key {
+            store.set_resolved_tree_merge(ids, simplified.map(|tree| tree.id().clone()));
+        }
         Ok(MergedTree { trees: simplified })
     }
 
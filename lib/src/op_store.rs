@@ -16,6 +16,7 @@
 
 use std::any::Any;
 use std::collections::BTreeMap;
+use std::collections::BTreeSet;
 use std::collections::HashMap;
 use std::collections::HashSet;
 use std::fmt::Debug;
@@ -28,10 +29,12 @@ use itertools::Itertools as _;
 use once_cell::sync::Lazy;
 use thiserror::Error;
 
+use crate::backend::ChangeId;
 use crate::backend::CommitId;
 use crate::backend::MillisSinceEpoch;
 use crate::backend::Timestamp;
 use crate::content_hash::ContentHash;
+use crate::content_hash::DigestUpdate;
 use crate::merge::Merge;
 use crate::object_id::id_type;
 use crate::object_id::HexPrefix;
@@ -264,7 +267,7 @@ pub struct BookmarkTarget<'a> {
 
 /// Represents the way the repo looks at a given time, just like how a Tree
 /// object represents how the file system looks at a given time.
-#[derive(ContentHash, PartialEq, Eq, Clone, Debug)]
+#[derive(PartialEq, Eq, Clone, Debug)]
 pub struct View {
     /// All head commits
     pub head_ids: HashSet<CommitId>,
@@ -280,6 +283,71 @@ pub struct View {
     // (.jj/working_copy/) has the source of truth about which commit *is* checked out (to be
     // precise: the commit to which we most recently completed an update to).
     pub wc_commit_ids: HashMap<WorkspaceId, CommitId>,
+    /// Topic labels attached to changes. Unlike bookmarks, topics refer to
+    /// change ids, so they follow the changes across rewrites.
+    pub topics: BTreeMap<String, BTreeSet<ChangeId>>,
+    /// When local bookmarks were created and last moved. Bookmarks created
+    /// before this was recorded have no entry.
+    pub bookmark_metadata: BTreeMap<String, BookmarkMetadata>,
+    /// Shelved working-copy changes. Each shelf is a hidden commit on top of
+    /// the parents of the working-copy commit it was taken from.
+    pub shelves: BTreeMap<String, CommitId>,
+    /// Key-value metadata attached to changes, such as review URLs. Keyed by
+    /// change id so that it survives rewrites.
+    pub change_metadata: BTreeMap<ChangeId, BTreeMap<String, String>>,
+}
+
+// Implemented manually because fields added to the view later are only hashed
+// if they aren't empty. Views not using them keep their content hash, and
+// therefore their view id.
+impl ContentHash for View {
+    fn hash(&self, state: &mut impl DigestUpdate) {
+        let View {
+            head_ids,
+            local_bookmarks,
+            tags,
+            remote_views,
+            git_refs,
+            git_head,
+            wc_commit_ids,
+            topics,
+            bookmark_metadata,
+            shelves,
+            change_metadata,
+        } = self;
+        head_ids.hash(state);
+        local_bookmarks.hash(state);
+        tags.hash(state);
+        remote_views.hash(state);
+        git_refs.hash(state);
+        git_head.hash(state);
+        wc_commit_ids.hash(state);
+        hash_if_not_empty(topics, state);
+        hash_if_not_empty(bookmark_metadata, state);
+        hash_if_not_empty(shelves, state);
+        hash_if_not_empty(change_metadata, state);
+    }
+}
+
+fn hash_if_not_empty<K: ContentHash, V: ContentHash>(
+    map: &BTreeMap<K, V>,
+    state: &mut impl DigestUpdate,
+) {
+    if !map.is_empty() {
+        map.hash(state);
+    }
+}
+
+/// Records when a local bookmark was created and last moved.
+#[derive(ContentHash, PartialEq, Eq, Clone, Debug)]
+pub struct BookmarkMetadata {
+    /// Start time of the operation that created the bookmark, or `None` if
+    /// the bookmark was created before this was recorded.
+    pub created: Option<Timestamp>,
+    /// Start time of the operation that last moved the bookmark.
+    pub moved: Timestamp,
+    /// `user@host` of the operation that last moved the bookmark.
+    pub moved_by: String,
 }
 
 impl View {
@@ -296,6 +364,10 @@ impl View {
             git_refs: BTreeMap::new(),
             git_head: RefTarget::absent(),
             wc_commit_ids: HashMap::new(),
+            topics: BTreeMap::new(),
+            bookmark_metadata: BTreeMap::new(),
+            shelves: BTreeMap::new(),
+            change_metadata: BTreeMap::new(),
         }
     }
 
@@ -309,6 +381,10 @@ impl View {
             git_refs: BTreeMap::new(),
             git_head: RefTarget::absent(),
             wc_commit_ids: HashMap::new(),
+            topics: BTreeMap::new(),
+            bookmark_metadata: BTreeMap::new(),
+            shelves: BTreeMap::new(),
+            change_metadata: BTreeMap::new(),
         }
     }
 }
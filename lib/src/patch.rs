@@ -0,0 +1,1032 @@
+// Copyright 2025 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Parsing and applying patches in the email format produced by
+//! `git format-patch`.
+//!
+//! An input may contain several emails in mbox format, each starting with a
+//! `From <hash> <date>` line. Only textual diffs are supported.
+
+use bstr::BString;
+use bstr::ByteSlice as _;
+use thiserror::Error;
+
+use crate::backend::Timestamp;
+
+/// A commit parsed from an email.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PatchEmail {
+    /// Author name from the `From` header.
+    pub author_name: String,
+    /// Author email address from the `From` header.
+    pub author_email: String,
+    /// Author date from the `Date` header, if present.
+    pub author_date: Option<Timestamp>,
+    /// The subject without the `[PATCH ...]` prefix, followed by the body of
+    /// the email up to the `---` separator.
+    pub description: String,
+    /// Changes to the files, in the order they appear in the email.
+    pub files: Vec<FilePatch>,
+}
+
+/// Type of a file in a patch, as given by its Git file mode.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum PatchFileMode {
+    /// Mode `100644`.
+    Regular,
+    /// Mode `100755`.
+    Executable,
+    /// Mode `120000`. The contents are the symlink target.
+    Symlink,
+}
+
+/// Changes to a single file.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct FilePatch {
+    /// Path before the change, or `None` if the file is added.
+    pub old_path: Option<String>,
+    /// Path after the change, or `None` if the file is deleted.
+    pub new_path: Option<String>,
+    /// Whether the new path is a copy of the old path, which should be kept.
+    pub is_copy: bool,
+    /// Mode after the change, or `None` if unchanged.
+    pub new_mode: Option<PatchFileMode>,
+    /// Changes to the contents, in order.
+    pub hunks: Vec<PatchHunk>,
+}
+
+/// A contiguous region of changed lines.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PatchHunk {
+    /// 1-based line number of the first line of the old side. For hunks
+    /// without old lines, the line after which the new lines are inserted.
+    pub old_start: usize,
+    /// Lines of the hunk, including their line terminators.
+    pub lines: Vec<PatchLine>,
+}
+
+/// A line of a hunk.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum PatchLine {
+    /// Line present on both sides.
+    Context(BString),
+    /// Line present on the old side only.
+    Removed(BString),
+    /// Line present on the new side only.
+    Added(BString),
+}
+
+/// Error that may occur while parsing patches.
+#[derive(Debug, Error)]
+#[error("Invalid patch at line {line}: {message}")]
+pub struct PatchParseError {
+    /// 1-based line number in the input.
+    pub line: usize,
+    /// Description of the problem.
+    pub message: String,
+}
+
+/// Error that may occur while applying a patch to a file.
+#[derive(Debug, Error)]
+#[error("Hunk #{hunk_number} does not apply")]
+pub struct PatchApplyError {
+    /// 1-based index of the hunk that couldn't be applied.
+    pub hunk_number: usize,
+}
+
+/// Parses the emails in `data`.
+///
+/// Emails are separated by mbox `From ` lines. If there are none, `data` is
+/// parsed as a single email.
+pub fn parse_mbox(data: &[u8]) -> Result<Vec<PatchEmail>, PatchParseError> {
+    let lines = data.lines_with_terminator().collect::<Vec<_>>();
+    let mut starts = lines
+        .iter()
+        .enumerate()
+        .filter(|&(i, line)| line.starts_with(b"From ") && (i == 0 || is_blank(lines[i - 1])))
+        .map(|(i, _)| i)
+        .collect::<Vec<_>>();
+    let skip_separator = !starts.is_empty();
+    if starts.is_empty() && !lines.is_empty() {
+        starts.push(0);
+    }
+    let mut emails = vec![];
+    for (index, &start) in starts.iter().enumerate() {
+        let end = starts.get(index + 1).copied().unwrap_or(lines.len());
+        let start = if skip_separator { start + 1 } else { start };
+        let parser = LineParser {
+            lines: &lines[..end],
+            pos: start,
+        };
+        emails.push(parser.parse_email()?);
+    }
+    if emails.is_empty() {
+        return Err(PatchParseError {
+            line: 1,
+            message: "No patches found".to_owned(),
+        });
+    }
+    Ok(emails)
+}
+
+/// Applies the `hunks` to the `old` contents.
+///
+/// Each hunk must match the old contents exactly, but it may be found at a
+/// different line than the one recorded in the hunk.
+pub fn apply_hunks(old: &[u8], hunks: &[PatchHunk]) -> Result<BString, PatchApplyError> {
+    let old_lines = old.lines_with_terminator().collect::<Vec<_>>();
+    let mut new = BString::default();
+    let mut pos = 0;
+    let mut offset: isize = 0;
+    for (index, hunk) in hunks.iter().enumerate() {
+        let preimage = hunk
+            .lines
+            .iter()
+            .filter_map(|line| match line {
+                PatchLine::Context(text) | PatchLine::Removed(text) => Some(text.as_slice()),
+                PatchLine::Added(_) => None,
+            })
+            .collect::<Vec<_>>();
+        let recorded = if preimage.is_empty() {
+            hunk.old_start
+        } else {
+            hunk.old_start.saturating_sub(1)
+        };
+        let expected = recorded.saturating_add_signed(offset);
+        let found = find_lines(&old_lines, &preimage, pos, expected).ok_or(PatchApplyError {
+            hunk_number: index + 1,
+        })?;
+        offset = found as isize - recorded as isize;
+        for line in &old_lines[pos..found] {
+            new.extend_from_slice(line);
+        }
+        for line in &hunk.lines {
+            match line {
+                PatchLine::Context(text) | PatchLine::Added(text) => new.extend_from_slice(text),
+                PatchLine::Removed(_) => {}
+            }
+        }
+        pos = found + preimage.len();
+    }
+    for line in &old_lines[pos..] {
+        new.extend_from_slice(line);
+    }
+    Ok(new)
+}
+
+/// Finds `needle` in `haystack[min_pos..]`, preferring the position closest
+/// to `expected`.
+fn find_lines(
+    haystack: &[&[u8]],
+    needle: &[&[u8]],
+    min_pos: usize,
+    expected: usize,
+) -> Option<usize> {
+    let max_pos = haystack.len().checked_sub(needle.len())?;
+    if min_pos > max_pos {
+        return None;
+    }
+    let matches_at = |pos: usize| haystack[pos..pos + needle.len()] == *needle;
+    let expected = expected.clamp(min_pos, max_pos);
+    if needle.is_empty() {
+        return Some(expected);
+    }
+    (0..=max_pos - min_pos)
+        .flat_map(|distance| {
+            [
+                expected.checked_add(distance),
+                expected.checked_sub(distance),
+            ]
+        })
+        .flatten()
+        .filter(|&pos| (min_pos..=max_pos).contains(&pos))
+        .find(|&pos| matches_at(pos))
+}
+
+fn is_blank(line: &[u8]) -> bool {
+    line.trim().is_empty()
+}
+
+/// Removes the line terminator, including a carriage return.
+fn trim_newline(line: &[u8]) -> &[u8] {
+    let line = line.strip_suffix(b"\n").unwrap_or(line);
+    line.strip_suffix(b"\r").unwrap_or(line)
+}
+
+struct LineParser<'a> {
+    lines: &'a [&'a [u8]],
+    pos: usize,
+}
+
+impl LineParser<'_> {
+    fn peek(&self) -> Option<&[u8]> {
+        self.lines.get(self.pos).copied()
+    }
+
+    fn error(&self, message: impl Into<String>) -> PatchParseError {
+        PatchParseError {
+            line: self.pos + 1,
+            message: message.into(),
+        }
+    }
+
+    fn parse_email(mut self) -> Result<PatchEmail, PatchParseError> {
+        let headers_line = self.pos;
+        let mut headers = self.parse_headers()?;
+        if let Some(encoding) = header_value(&headers, "Content-Transfer-Encoding") {
+            if !["7bit", "8bit", "binary"].contains(&encoding.to_ascii_lowercase().as_str()) {
+                return Err(PatchParseError {
+                    line: headers_line + 1,
+                    message: format!("Unsupported Content-Transfer-Encoding: {encoding}"),
+                });
+            }
+        }
+        // Headers at the start of the body override the email headers.
+        let saved_pos = self.pos;
+        let recognized = ["From", "Date", "Subject"];
+        match self.parse_headers() {
+            Ok(body_headers)
+                if !body_headers.is_empty()
+                    && body_headers.iter().all(|(name, _)| {
+                        recognized.iter().any(|r| r.eq_ignore_ascii_case(name))
+                    }) =>
+            {
+                headers.extend(body_headers);
+            }
+            _ => self.pos = saved_pos,
+        }
+
+        let error_at_headers = |message: &str| PatchParseError {
+            line: headers_line + 1,
+            message: message.to_owned(),
+        };
+        let from = header_value(&headers, "From")
+            .ok_or_else(|| error_at_headers("Missing From header"))?;
+        let (author_name, author_email) = parse_address(&from);
+        let author_date = match header_value(&headers, "Date") {
+            Some(date) => {
+                let datetime = chrono::DateTime::parse_from_rfc2822(&date)
+                    .map_err(|err| error_at_headers(&format!("Invalid Date header: {err}")))?;
+                Some(Timestamp::from_datetime(datetime))
+            }
+            None => None,
+        };
+        let subject = header_value(&headers, "Subject")
+            .ok_or_else(|| error_at_headers("Missing Subject header"))?;
+
+        let mut body = String::new();
+        while let Some(line) = self.peek() {
+            if trim_newline(line) == b"---" || line.starts_with(b"diff --git ") {
+                break;
+            }
+            body.push_str(&String::from_utf8_lossy(trim_newline(line)));
+            body.push('\n');
+            self.pos += 1;
+        }
+        let mut description = strip_subject_prefix(&subject).to_owned();
+        description.push('\n');
+        let body = body.trim_matches('\n');
+        if !body.is_empty() {
+            description.push('\n');
+            description.push_str(body);
+            description.push('\n');
+        }
+
+        // Skip the diffstat.
+        while self
+            .peek()
+            .is_some_and(|line| !line.starts_with(b"diff --git "))
+        {
+            self.pos += 1;
+        }
+        let mut files = vec![];
+        while let Some(line) = self.peek() {
+            if line.starts_with(b"diff --git ") {
+                files.push(self.parse_file_patch()?);
+            } else if trim_newline(line) == b"-- " {
+                // Signature follows.
+                break;
+            } else if is_blank(line) {
+                self.pos += 1;
+            } else {
+                return Err(self.error("Unexpected line between file diffs"));
+            }
+        }
+        Ok(PatchEmail {
+            author_name,
+            author_email,
+            author_date,
+            description,
+            files,
+        })
+    }
+
+    /// Parses headers up to and including the blank line that ends them.
+    /// Returns no headers if the current line isn't a header.
+    fn parse_headers(&mut self) -> Result<Vec<(String, String)>, PatchParseError> {
+        let mut headers: Vec<(String, String)> = vec![];
+        while let Some(line) = self.peek() {
+            let line = trim_newline(line);
+            if line.is_empty() {
+                self.pos += 1;
+                break;
+            }
+            if line.starts_with(b" ") || line.starts_with(b"\t") {
+                let Some((_, value)) = headers.last_mut() else {
+                    return Err(self.error("Header continuation without a header"));
+                };
+                value.push(' ');
+                value.push_str(String::from_utf8_lossy(line.trim()).as_ref());
+            } else {
+                let Some((name, value)) = line.split_once_str(":") else {
+                    if headers.is_empty() {
+                        break;
+                    }
+                    return Err(self.error("Invalid header"));
+                };
+                let name = String::from_utf8_lossy(name);
+                if name.is_empty() || name.contains(char::is_whitespace) {
+                    if headers.is_empty() {
+                        break;
+                    }
+                    return Err(self.error("Invalid header"));
+                }
+                headers.push((
+                    name.into_owned(),
+                    String::from_utf8_lossy(value.trim()).into_owned(),
+                ));
+            }
+            self.pos += 1;
+        }
+        Ok(headers)
+    }
+
+    fn parse_file_patch(&mut self) -> Result<FilePatch, PatchParseError> {
+        let header = trim_newline(self.peek().unwrap());
+        let header_paths = header.strip_prefix(b"diff --git ").unwrap();
+        let (mut old_path, mut new_path) = match split_header_paths(header_paths) {
+            Some((old, new)) => (Some(old), Some(new)),
+            None => (None, None),
+        };
+        self.pos += 1;
+        let mut is_copy = false;
+        let mut new_mode = None;
+        while let Some(line) = self.peek() {
+            let line = trim_newline(line);
+            if line.starts_with(b"diff --git ")
+                || line.starts_with(b"--- ")
+                || line.starts_with(b"@@ ")
+                || line == b"-- "
+                || is_blank(line)
+            {
+                break;
+            }
+            let text = String::from_utf8_lossy(line);
+            if let Some(mode) = text.strip_prefix("new file mode ") {
+                old_path = None;
+                new_mode = Some(self.parse_mode(mode)?);
+            } else if let Some(mode) = text.strip_prefix("new mode ") {
+                new_mode = Some(self.parse_mode(mode)?);
+            } else if text.starts_with("deleted file mode ") {
+                new_path = None;
+            } else if let Some(path) = text.strip_prefix("rename from ") {
+                old_path = Some(self.parse_path(path)?);
+            } else if let Some(path) = text.strip_prefix("rename to ") {
+                new_path = Some(self.parse_path(path)?);
+            } else if let Some(path) = text.strip_prefix("copy from ") {
+                old_path = Some(self.parse_path(path)?);
+                is_copy = true;
+            } else if let Some(path) = text.strip_prefix("copy to ") {
+                new_path = Some(self.parse_path(path)?);
+                is_copy = true;
+            } else if text.starts_with("Binary files ") || text == "GIT binary patch" {
+                return Err(self.error("Binary patches are not supported"));
+            } else if !(text.starts_with("old mode ")
+                || text.starts_with("index ")
+                || text.starts_with("similarity index ")
+                || text.starts_with("dissimilarity index "))
+            {
+                return Err(self.error(format!("Unknown extended header: {text}")));
+            }
+            self.pos += 1;
+        }
+        if let Some(line) = self.peek().filter(|line| line.starts_with(b"--- ")) {
+            old_path = self.parse_diff_path(&line[4..], "a/")?;
+            self.pos += 1;
+            let Some(line) = self.peek().filter(|line| line.starts_with(b"+++ ")) else {
+                return Err(self.error("Expected +++ line"));
+            };
+            new_path = self.parse_diff_path(&line[4..], "b/")?;
+            self.pos += 1;
+        }
+        if old_path.is_none() && new_path.is_none() {
+            return Err(self.error("Could not determine the path of the file"));
+        }
+        let mut hunks = vec![];
+        while self.peek().is_some_and(|line| line.starts_with(b"@@ ")) {
+            hunks.push(self.parse_hunk()?);
+        }
+        Ok(FilePatch {
+            old_path,
+            new_path,
+            is_copy,
+            new_mode,
+            hunks,
+        })
+    }
+
+    fn parse_mode(&self, mode: &str) -> Result<PatchFileMode, PatchParseError> {
+        match mode.trim() {
+            "100644" => Ok(PatchFileMode::Regular),
+            "100755" => Ok(PatchFileMode::Executable),
+            "120000" => Ok(PatchFileMode::Symlink),
+            mode => Err(self.error(format!("Unsupported file mode: {mode}"))),
+        }
+    }
+
+    fn parse_path(&self, path: &str) -> Result<String, PatchParseError> {
+        unquote_path(path.as_bytes()).ok_or_else(|| self.error("Invalid quoted path"))
+    }
+
+    /// Parses the path of a `---` or `+++` line. Returns `None` for
+    /// `/dev/null`.
+    fn parse_diff_path(
+        &self,
+        text: &[u8],
+        prefix: &str,
+    ) -> Result<Option<String>, PatchParseError> {
+        let text = trim_newline(text);
+        // A timestamp may follow the path after a tab.
+        let text = if text.starts_with(b"\"") {
+            text
+        } else {
+            text.split_str("\t").next().unwrap()
+        };
+        let path = unquote_path(text).ok_or_else(|| self.error("Invalid quoted path"))?;
+        if path == "/dev/null" {
+            return Ok(None);
+        }
+        match path.strip_prefix(prefix) {
+            Some(path) => Ok(Some(path.to_owned())),
+            None => Err(self.error(format!("Expected path starting with {prefix}"))),
+        }
+    }
+
+    fn parse_hunk(&mut self) -> Result<PatchHunk, PatchParseError> {
+        let header = String::from_utf8_lossy(trim_newline(self.peek().unwrap())).into_owned();
+        let (old_start, mut old_count, mut new_count) =
+            parse_hunk_header(&header).ok_or_else(|| self.error("Invalid hunk header"))?;
+        self.pos += 1;
+        let mut lines: Vec<PatchLine> = vec![];
+        while old_count > 0 || new_count > 0 {
+            let Some(line) = self.peek() else {
+                return Err(self.error("Unexpected end of hunk"));
+            };
+            let (kind, text) = match line.split_first() {
+                // Some mailers strip the trailing space of empty context lines.
+                Some((b'\n', _)) => (b' ', line),
+                Some((&kind, text)) => (kind, text),
+                None => return Err(self.error("Unexpected end of hunk")),
+            };
+            let text = BString::from(text);
+            match kind {
+                b' ' if old_count > 0 && new_count > 0 => {
+                    old_count -= 1;
+                    new_count -= 1;
+                    lines.push(PatchLine::Context(text));
+                }
+                b'-' if old_count > 0 => {
+                    old_count -= 1;
+                    lines.push(PatchLine::Removed(text));
+                }
+                b'+' if new_count > 0 => {
+                    new_count -= 1;
+                    lines.push(PatchLine::Added(text));
+                }
+                b'\\' if !lines.is_empty() => {}
+                _ => return Err(self.error("Invalid line in hunk")),
+            }
+            self.pos += 1;
+            self.parse_no_newline_marker(&mut lines);
+        }
+        Ok(PatchHunk { old_start, lines })
+    }
+
+    /// Strips the line terminator from the last line if it's followed by a
+    /// `\ No newline at end of file` marker.
+    fn parse_no_newline_marker(&mut self, lines: &mut [PatchLine]) {
+        if !self.peek().is_some_and(|line| line.starts_with(b"\\")) {
+            return;
+        }
+        if let Some(PatchLine::Context(text) | PatchLine::Removed(text) | PatchLine::Added(text)) =
+            lines.last_mut()
+        {
+            let len = trim_newline(text).len();
+            text.truncate(len);
+        }
+        self.pos += 1;
+    }
+}
+
+fn header_value(headers: &[(String, String)], name: &str) -> Option<String> {
+    headers
+        .iter()
+        .rev()
+        .find(|(key, _)| key.eq_ignore_ascii_case(name))
+        .map(|(_, value)| decode_encoded_words(value))
+}
+
+/// Splits `Name <email>` into its parts.
+fn parse_address(text: &str) -> (String, String) {
+    match text.rsplit_once('<') {
+        Some((name, rest)) => {
+            let email = rest.split('>').next().unwrap_or_default();
+            let name = name.trim().trim_matches('"');
+            (name.to_owned(), email.trim().to_owned())
+        }
+        None => (String::new(), text.trim().to_owned()),
+    }
+}
+
+/// Removes leading `[PATCH ...]`-style tags from the `subject`.
+fn strip_subject_prefix(subject: &str) -> &str {
+    let mut subject = subject.trim();
+    while subject.starts_with('[') {
+        let Some(end) = subject.find(']') else {
+            break;
+        };
+        subject = subject[end + 1..].trim_start();
+    }
+    subject
+}
+
+/// Decodes RFC 2047 encoded words such as `=?UTF-8?q?caf=C3=A9?=`.
+fn decode_encoded_words(text: &str) -> String {
+    let mut decoded = String::new();
+    let mut previous_was_encoded = false;
+    let mut pending_whitespace = "";
+    let mut rest = text;
+    while !rest.is_empty() {
+        let word_len = rest.find(char::is_whitespace).unwrap_or(rest.len());
+        let (word, after) = rest.split_at(word_len);
+        let trimmed = after.trim_start();
+        let whitespace = &after[..after.len() - trimmed.len()];
+        match decode_encoded_word(word) {
+            Some(word) => {
+                // Whitespace between adjacent encoded words is ignored.
+                if !previous_was_encoded {
+                    decoded.push_str(pending_whitespace);
+                }
+                decoded.push_str(&word);
+                previous_was_encoded = true;
+            }
+            None => {
+                decoded.push_str(pending_whitespace);
+                decoded.push_str(word);
+                previous_was_encoded = false;
+            }
+        }
+        pending_whitespace = whitespace;
+        rest = trimmed;
+    }
+    decoded
+}
+
+fn decode_encoded_word(word: &str) -> Option<String> {
+    let inner = word.strip_prefix("=?")?.strip_suffix("?=")?;
+    let mut parts = inner.splitn(3, '?');
+    let (charset, encoding, text) = (parts.next()?, parts.next()?, parts.next()?);
+    let bytes = if encoding.eq_ignore_ascii_case("q") {
+        decode_quoted_printable(text)?
+    } else if encoding.eq_ignore_ascii_case("b") {
+        decode_base64(text)?
+    } else {
+        return None;
+    };
+    if charset.eq_ignore_ascii_case("iso-8859-1") {
+        Some(bytes.iter().map(|&b| char::from(b)).collect())
+    } else {
+        Some(String::from_utf8_lossy(&bytes).into_owned())
+    }
+}
+
+fn decode_quoted_printable(text: &str) -> Option<Vec<u8>> {
+    let mut bytes = vec![];
+    let mut rest = text.as_bytes();
+    while let Some((&c, tail)) = rest.split_first() {
+        match c {
+            b'_' => bytes.push(b' '),
+            b'=' => {
+                let hex = tail.get(..2)?;
+                bytes.push(u8::from_str_radix(hex.to_str().ok()?, 16).ok()?);
+                rest = &tail[2..];
+                continue;
+            }
+            _ => bytes.push(c),
+        }
+        rest = tail;
+    }
+    Some(bytes)
+}
+
+fn decode_base64(text: &str) -> Option<Vec<u8>> {
+    let mut bytes = vec![];
+    let mut bits: u32 = 0;
+    let mut bit_count = 0;
+    for c in text.bytes().take_while(|&c| c != b'=') {
+        let value = match c {
+            b'A'..=b'Z' => c - b'A',
+            b'a'..=b'z' => c - b'a' + 26,
+            b'0'..=b'9' => c - b'0' + 52,
+            b'+' => 62,
+            b'/' => 63,
+            _ => return None,
+        };
+        bits = (bits << 6) | u32::from(value);
+        bit_count += 6;
+        if bit_count >= 8 {
+            bit_count -= 8;
+            bytes.push((bits >> bit_count) as u8);
+            bits &= (1 << bit_count) - 1;
+        }
+    }
+    Some(bytes)
+}
+
+/// Splits the `a/<old> b/<new>` paths of a `diff --git` line.
+///
+/// Unquoted paths may contain spaces, so they can only be split if they're
+/// the same. Otherwise, the paths have to be taken from other lines.
+fn split_header_paths(text: &[u8]) -> Option<(String, String)> {
+    let text = text.to_str().ok()?;
+    let (old, new) = if text.starts_with('"') {
+        let end = quoted_len(text)?;
+        let new = text[end..].strip_prefix(' ')?;
+        (
+            unquote_path(&text.as_bytes()[..end])?,
+            unquote_path(new.as_bytes())?,
+        )
+    } else if text.ends_with('"') {
+        let start = text.rfind(" \"")?;
+        (
+            text[..start].to_owned(),
+            unquote_path(&text.as_bytes()[start + 1..])?,
+        )
+    } else {
+        let len = text.len().checked_sub(1)? / 2;
+        if !text.is_char_boundary(len) || !text[len..].starts_with(' ') {
+            return None;
+        }
+        let (old, new) = (&text[..len], &text[len + 1..]);
+        if old.strip_prefix("a/")? != new.strip_prefix("b/")? {
+            return None;
+        }
+        (old.to_owned(), new.to_owned())
+    };
+    Some((
+        old.strip_prefix("a/")?.to_owned(),
+        new.strip_prefix("b/")?.to_owned(),
+    ))
+}
+
+/// Returns the length of the quoted string at the start of `text`.
+fn quoted_len(text: &str) -> Option<usize> {
+    let mut escaped = false;
+    for (i, c) in text.char_indices().skip(1) {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' => escaped = true,
+            '"' => return Some(i + 1),
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Removes Git's C-style quoting from a path, if quoted.
+fn unquote_path(text: &[u8]) -> Option<String> {
+    let Some(quoted) = text.strip_prefix(b"\"") else {
+        return Some(String::from_utf8_lossy(text).into_owned());
+    };
+    let quoted = quoted.strip_suffix(b"\"")?;
+    let mut bytes = vec![];
+    let mut rest = quoted;
+    while let Some((&c, tail)) = rest.split_first() {
+        rest = tail;
+        if c != b'\\' {
+            bytes.push(c);
+            continue;
+        }
+        let (&escape, tail) = rest.split_first()?;
+        rest = tail;
+        let byte = match escape {
+            b'a' => b'\x07',
+            b'b' => b'\x08',
+            b'f' => b'\x0c',
+            b'n' => b'\n',
+            b'r' => b'\r',
+            b't' => b'\t',
+            b'v' => b'\x0b',
+            b'"' | b'\\' => escape,
+            b'0'..=b'7' => {
+                let digits = [escape, *rest.first()?, *rest.get(1)?];
+                rest = &rest[2..];
+                u8::from_str_radix(digits.to_str().ok()?, 8).ok()?
+            }
+            _ => return None,
+        };
+        bytes.push(byte);
+    }
+    String::from_utf8(bytes).ok()
+}
+
+/// Parses `@@ -<start>[,<count>] +<start>[,<count>] @@`. Returns the old
+/// start and the old and new line counts.
+fn parse_hunk_header(header: &str) -> Option<(usize, usize, usize)> {
+    let rest = header.strip_prefix("@@ -")?;
+    let (ranges, _) = rest.split_once(" @@")?;
+    let (old, new) = ranges.split_once(" +")?;
+    let parse_range = |range: &str| -> Option<(usize, usize)> {
+        match range.split_once(',') {
+            Some((start, count)) => Some((start.parse().ok()?, count.parse().ok()?)),
+            None => Some((range.parse().ok()?, 1)),
+        }
+    };
+    let (old_start, old_count) = parse_range(old)?;
+    let (_, new_count) = parse_range(new)?;
+    Some((old_start, old_count, new_count))
+}
+
+#[cfg(test)]
+mod tests {
+    use assert_matches::assert_matches;
+    use indoc::indoc;
+
+    use super::*;
+
+    fn hunk(old_start: usize, lines: &[(char, &str)]) -> PatchHunk {
+        let lines = lines
+            .iter()
+            .map(|&(kind, text)| match kind {
+                ' ' => PatchLine::Context(text.into()),
+                '-' => PatchLine::Removed(text.into()),
+                '+' => PatchLine::Added(text.into()),
+                _ => panic!("unknown kind {kind}"),
+            })
+            .collect();
+        PatchHunk { old_start, lines }
+    }
+
+    #[test]
+    fn test_parse_mbox() {
+        let data = indoc! {r#"
+            From 1111111111111111111111111111111111111111 Mon Sep 17 00:00:00 2001
+            From: =?UTF-8?q?Jos=C3=A9=20Doe?= <jose@example.com>
+            Date: Tue, 4 Mar 2025 10:20:30 +0100
+            Subject: [PATCH 1/2] Add a file and
+             rename another
+
+            The body.
+
+            Signed-off-by: José Doe <jose@example.com>
+            ---
+             new    | 2 ++
+             2 files changed, 2 insertions(+)
+
+            diff --git a/new b/new
+            new file mode 100755
+            index 0000000..1111111
+            --- /dev/null
+            +++ b/new
+            @@ -0,0 +1,2 @@
+            +line 1
+            +line 2
+            \ No newline at end of file
+            diff --git a/old name b/new name
+            similarity index 100%
+            rename from old name
+            rename to new name
+            -- 
+            2.47.0
+
+            From 2222222222222222222222222222222222222222 Mon Sep 17 00:00:00 2001
+            From: Someone <someone@example.com>
+            Subject: [PATCH 2/2] Edit and delete
+
+            diff --git a/file b/file
+            index 1111111..2222222 100644
+            --- a/file
+            +++ b/file
+            @@ -1,3 +1,3 @@
+             a
+            -b
+            +B
+
+            diff --git "a/sp\303\251cial" "b/sp\303\251cial"
+            deleted file mode 100644
+            index 1111111..0000000
+            --- "a/sp\303\251cial"
+            +++ /dev/null
+            @@ -1 +0,0 @@
+            -gone
+        "#};
+        let emails = parse_mbox(data.as_bytes()).unwrap();
+        assert_eq!(emails.len(), 2);
+
+        assert_eq!(emails[0].author_name, "José Doe");
+        assert_eq!(emails[0].author_email, "jose@example.com");
+        assert_eq!(
+            emails[0].author_date,
+            Some(Timestamp {
+                timestamp: crate::backend::MillisSinceEpoch(1741080030000),
+                tz_offset: 60,
+            })
+        );
+        assert_eq!(
+            emails[0].description,
+            "Add a file and rename another\n\nThe body.\n\nSigned-off-by: José Doe \
+             <jose@example.com>\n"
+        );
+        assert_eq!(
+            emails[0].files,
+            [
+                FilePatch {
+                    old_path: None,
+                    new_path: Some("new".to_owned()),
+                    is_copy: false,
+                    new_mode: Some(PatchFileMode::Executable),
+                    hunks: vec![hunk(0, &[('+', "line 1\n"), ('+', "line 2")])],
+                },
+                FilePatch {
+                    old_path: Some("old name".to_owned()),
+                    new_path: Some("new name".to_owned()),
+                    is_copy: false,
+                    new_mode: None,
+                    hunks: vec![],
+                },
+            ]
+        );
+
+        assert_eq!(emails[1].author_name, "Someone");
+        assert_eq!(emails[1].author_date, None);
+        assert_eq!(emails[1].description, "Edit and delete\n");
+        assert_eq!(
+            emails[1].files,
+            [
+                FilePatch {
+                    old_path: Some("file".to_owned()),
+                    new_path: Some("file".to_owned()),
+                    is_copy: false,
+                    new_mode: None,
+                    hunks: vec![hunk(
+                        1,
+                        &[(' ', "a\n"), ('-', "b\n"), ('+', "B\n"), (' ', "\n")]
+                    )],
+                },
+                FilePatch {
+                    old_path: Some("spécial".to_owned()),
+                    new_path: None,
+                    is_copy: false,
+                    new_mode: None,
+                    hunks: vec![hunk(1, &[('-', "gone\n")])],
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_mbox_in_body_headers() {
+        let data = indoc! {"
+            From: Sender <sender@example.com>
+            Subject: [PATCH] Wrong subject
+
+            From: Author <author@example.com>
+            Subject: Right subject
+
+            Body.
+        "};
+        let emails = parse_mbox(data.as_bytes()).unwrap();
+        assert_eq!(emails.len(), 1);
+        assert_eq!(emails[0].author_name, "Author");
+        assert_eq!(emails[0].description, "Right subject\n\nBody.\n");
+        assert_eq!(emails[0].files, []);
+    }
+
+    #[test]
+    fn test_parse_mbox_errors() {
+        assert_matches!(parse_mbox(b""), Err(PatchParseError { line: 1, .. }));
+        assert_matches!(
+            parse_mbox(b"Subject: x\n\n"),
+            Err(PatchParseError { message, .. }) if message == "Missing From header"
+        );
+        let data = indoc! {"
+            From: A <a@example.com>
+            Subject: x
+
+            ---
+            diff --git a/bin b/bin
+            index 1111111..2222222 100644
+            GIT binary patch
+        "};
+        assert_matches!(
+            parse_mbox(data.as_bytes()),
+            Err(PatchParseError { line: 7, message }) if message.contains("Binary")
+        );
+        let data = indoc! {"
+            From: A <a@example.com>
+            Subject: x
+
+            ---
+            diff --git a/file b/file
+            --- a/file
+            +++ b/file
+            @@ -1,2 +1,2 @@
+             a
+        "};
+        assert_matches!(
+            parse_mbox(data.as_bytes()),
+            Err(PatchParseError { line: 10, .. })
+        );
+    }
+
+    #[test]
+    fn test_decode_encoded_words() {
+        assert_eq!(decode_encoded_words("plain text"), "plain text");
+        assert_eq!(
+            decode_encoded_words("=?utf-8?q?caf=C3=A9_au?= =?utf-8?b?bGFpdA==?= chaud"),
+            "café aulait chaud"
+        );
+        assert_eq!(decode_encoded_words("=?iso-8859-1?q?=E9?="), "é");
+        assert_eq!(decode_encoded_words("=?utf-8?x?bad?="), "=?utf-8?x?bad?=");
+    }
+
+    #[test]
+    fn test_apply_hunks() {
+        let old = "1\n2\n3\n4\n5\n6\n";
+        // Exact position
+        assert_eq!(
+            apply_hunks(
+                old.as_bytes(),
+                &[hunk(2, &[(' ', "2\n"), ('-', "3\n"), ('+', "three\n")])]
+            )
+            .unwrap(),
+            "1\n2\nthree\n4\n5\n6\n"
+        );
+        // Shifted hunks, with the offset carried over to the next hunk
+        assert_eq!(
+            apply_hunks(
+                old.as_bytes(),
+                &[
+                    hunk(4, &[('-', "2\n")]),
+                    hunk(7, &[(' ', "5\n"), ('+', "5.5\n")]),
+                ]
+            )
+            .unwrap(),
+            "1\n3\n4\n5\n5.5\n6\n"
+        );
+        // Insertion without context
+        assert_eq!(
+            apply_hunks(b"", &[hunk(0, &[('+', "new")])]).unwrap(),
+            "new"
+        );
+        assert_eq!(
+            apply_hunks(old.as_bytes(), &[hunk(6, &[('+', "7\n")])]).unwrap(),
+            "1\n2\n3\n4\n5\n6\n7\n"
+        );
+        // Missing newline
+        assert_eq!(
+            apply_hunks(b"a\nb", &[hunk(2, &[('-', "b"), ('+', "b\n")])]).unwrap(),
+            "a\nb\n"
+        );
+        // Mismatch
+        assert_matches!(
+            apply_hunks(
+                old.as_bytes(),
+                &[hunk(1, &[('-', "1\n")]), hunk(3, &[('-', "x\n")]),]
+            ),
+            Err(PatchApplyError { hunk_number: 2 })
+        );
+        // Hunks can't overlap
+        assert_matches!(
+            apply_hunks(
+                old.as_bytes(),
+                &[
+                    hunk(2, &[('-', "2\n"), (' ', "3\n")]),
+                    hunk(3, &[('-', "3\n")]),
+                ]
+            ),
+            Err(PatchApplyError { hunk_number: 2 })
+        );
+    }
+}
@@ -15,11 +15,14 @@
 #![allow(missing_docs)]
 
 use std::collections::hash_map::Entry;
+use std::collections::BTreeMap;
+use std::collections::BTreeSet;
 use std::collections::HashMap;
 use std::collections::HashSet;
 use std::fmt::Debug;
 use std::fmt::Formatter;
 use std::fs;
+use std::mem;
 use std::path::Path;
 use std::slice;
 use std::sync::Arc;
@@ -38,6 +41,8 @@ use crate::backend::BackendResult;
 use crate::backend::ChangeId;
 use crate::backend::CommitId;
 use crate::backend::MergedTreeId;
+use crate::backend::Timestamp;
+use crate::backend_policy::PolicyBackend;
 use crate::commit::Commit;
 use crate::commit::CommitByCommitterTimestamp;
 use crate::commit_builder::CommitBuilder;
@@ -64,6 +69,7 @@ use crate::op_heads_store::OpHeadResolutionError;
 use crate::op_heads_store::OpHeadsStore;
 use crate::op_heads_store::OpHeadsStoreError;
 use crate::op_store;
+use crate::op_store::BookmarkMetadata;
 use crate::op_store::OpStore;
 use crate::op_store::OpStoreError;
 use crate::op_store::OpStoreResult;
@@ -79,6 +85,9 @@ use crate::refs::diff_named_remote_refs;
 use crate::refs::merge_ref_targets;
 use crate::refs::merge_remote_refs;
 use crate::refs::RemoteRefSymbol;
+use crate::repo_format;
+use crate::repo_format::FormatVersionError;
+use crate::repo_format::RepoStoreKind;
 use crate::revset;
 use crate::revset::RevsetExpression;
 use crate::revset::RevsetIteratorExt;
@@ -193,7 +202,13 @@ impl ReadonlyRepo {
         let backend = backend_initializer(settings, &store_path)?;
         let backend_path = store_path.join("type");
         fs::write(&backend_path, backend.name()).context(&backend_path)?;
-        let store = Store::new(backend, signer);
+        let backend = PolicyBackend::wrap(backend, settings.backend_policy());
+        let store = Store::new(
+            backend,
+            signer,
+            settings.merge_normalization(),
+            Some(store_path.join("tree_merges")),
+        );
 
         let op_store_path = repo_path.join("op_store");
         fs::create_dir(&op_store_path).context(&op_store_path)?;
@@ -228,6 +243,10 @@ impl ReadonlyRepo {
             .context(&submodule_store_type_path)?;
         let submodule_store = Arc::from(submodule_store);
 
+        for kind in RepoStoreKind::ALL {
+            repo_format::write_format_version(&repo_path, kind, kind.current_version())?;
+        }
+
         let loader = RepoLoader {
             settings: settings.clone(),
             store,
@@ -465,6 +484,8 @@ pub enum StoreLoadError {
     Backend(#[from] BackendLoadError),
     #[error(transparent)]
     Signing(#[from] SignInitError),
+    #[error(transparent)]
+    FormatVersion(#[from] FormatVersionError),
 }
 
 impl StoreFactories {
@@ -663,9 +684,14 @@ impl RepoLoader {
         repo_path: &Path,
         store_factories: &StoreFactories,
     ) -> Result<Self, StoreLoadError> {
+        repo_format::check_repo_format(repo_path)?;
+        let store_path = repo_path.join("store");
+        let backend = store_factories.load_backend(settings, &store_path)?;
         let store = Store::new(
-            store_factories.load_backend(settings, &repo_path.join("store"))?,
+            PolicyBackend::wrap(backend, settings.backend_policy()),
             Signer::from_settings(settings)?,
+            settings.merge_normalization(),
+            Some(store_path.join("tree_merges")),
         );
         let root_op_data = RootOperationData {
             root_commit_id: store.root_commit_id().clone(),
@@ -853,6 +879,9 @@ pub struct MutableRepo {
     //   commits. However, if the type is `Abandoned`, a new working-copy commit should be created
     //   on top of all of the new commits instead.
     parent_mapping: HashMap<CommitId, Rewrite>,
+    // Local bookmarks moved in this transaction, not including the ones moved
+    // by merging concurrent operations.
+    moved_local_bookmarks: HashSet<String>,
 }
 
 impl MutableRepo {
@@ -868,6 +897,7 @@ impl MutableRepo {
             index: mut_index,
             view: DirtyCell::with_clean(mut_view),
             parent_mapping: Default::default(),
+            moved_local_bookmarks: Default::default(),
         }
     }
 
@@ -1533,7 +1563,10 @@ impl MutableRepo {
         for id in target.added_ids() {
             view.add_head(id);
         }
-        view.set_local_bookmark_target(name, target);
+        if *view.get_local_bookmark(name) != target {
+            self.moved_local_bookmarks.insert(name.to_owned());
+        }
+        self.view_mut().set_local_bookmark_target(name, target);
     }
 
     pub fn merge_local_bookmark(
@@ -1612,6 +1645,108 @@ impl MutableRepo {
         view.set_tag_target(name, new_target);
     }
 
+    pub fn get_topic(&self, name: &str) -> BTreeSet<ChangeId> {
+        self.view
+            .with_ref(|v| v.get_topic(name).cloned())
+            .unwrap_or_default()
+    }
+
+    pub fn set_topic_change_ids(&mut self, name: &str, change_ids: BTreeSet<ChangeId>) {
+        self.view_mut().set_topic_change_ids(name, change_ids);
+    }
+
+    /// Merges topic membership as sets: changes added on the other side are
+    /// added, and changes removed on the other side are removed.
+    pub fn merge_topic(
+        &mut self,
+        name: &str,
+        base_change_ids: &BTreeSet<ChangeId>,
+        other_change_ids: &BTreeSet<ChangeId>,
+    ) {
+        let mut change_ids = self.get_topic(name);
+        change_ids.retain(|id| !base_change_ids.contains(id) || other_change_ids.contains(id));
+        change_ids.extend(other_change_ids.difference(base_change_ids).cloned());
+        self.set_topic_change_ids(name, change_ids);
+    }
+
+    pub fn get_shelf(&self, name: &str) -> Option<CommitId> {
+        self.view.with_ref(|v| v.get_shelf(name).cloned())
+    }
+
+    pub fn set_shelf(&mut self, name: &str, commit_id: Option<CommitId>) {
+        self.view_mut().set_shelf(name, commit_id);
+    }
+
+    pub fn get_change_metadata(&self, change_id: &ChangeId, key: &str) -> Option<String> {
+        self.view.with_ref(|v| {
+            v.get_change_metadata(change_id)
+                .and_then(|values| values.get(key).cloned())
+        })
+    }
+
+    pub fn set_change_metadata(&mut self, change_id: &ChangeId, key: &str, value: Option<String>) {
+        self.view_mut().set_change_metadata(change_id, key, value);
+    }
+
+    pub fn get_bookmark_metadata(&self, name: &str) -> Option<BookmarkMetadata> {
+        self.view
+            .with_ref(|v| v.get_bookmark_metadata(name).cloned())
+    }
+
+    pub fn set_bookmark_metadata(&mut self, name: &str, metadata: Option<BookmarkMetadata>) {
+        self.view_mut().set_bookmark_metadata(name, metadata);
+    }
+
+    /// Merges bookmark metadata changed on the other side. If both sides
+    /// changed it, the metadata of the later move wins.
+    fn merge_bookmark_metadata(
+        &mut self,
+        name: &str,
+        base_metadata: Option<&BookmarkMetadata>,
+        other_metadata: Option<&BookmarkMetadata>,
+    ) {
+        let self_metadata = self.get_bookmark_metadata(name);
+        let new_metadata = match (&self_metadata, other_metadata) {
+            (self_metadata, _) if self_metadata.as_ref() == base_metadata => other_metadata,
+            (Some(self_metadata), Some(other_metadata))
+                if other_metadata.moved.timestamp > self_metadata.moved.timestamp =>
+            {
+                Some(other_metadata)
+            }
+            _ => return,
+        };
+        self.set_bookmark_metadata(name, new_metadata.cloned());
+    }
+
+    /// Records the creation and movement of the local bookmarks moved in this
+    /// transaction, as done at `time` by `moved_by`.
+    ///
+    /// Bookmarks moved by merging concurrent operations keep the metadata
+    /// recorded by these operations.
+    pub(crate) fn record_bookmark_metadata(&mut self, time: Timestamp, moved_by: &str) {
+        let base_repo = self.base_repo.clone();
+        let base_view = base_repo.view();
+        let moved_bookmarks = mem::take(&mut self.moved_local_bookmarks);
+        for name in moved_bookmarks.iter().sorted() {
+            let base_target = base_view.get_local_bookmark(name);
+            let new_target = self.get_local_bookmark(name);
+            if new_target == *base_target {
+                continue;
+            }
+            let metadata = new_target.is_present().then(|| BookmarkMetadata {
+                created: if base_target.is_absent() {
+                    Some(time)
+                } else {
+                    self.get_bookmark_metadata(name)
+                        .and_then(|metadata| metadata.created)
+                },
+                moved: time,
+                moved_by: moved_by.to_owned(),
+            });
+            self.set_bookmark_metadata(name, metadata);
+        }
+    }
+
     pub fn get_git_ref(&self, name: &str) -> RefTarget {
         self.view.with_ref(|v| v.get_git_ref(name).clone())
     }
@@ -1715,11 +1850,15 @@ impl MutableRepo {
             self.view_mut().add_head(added_head);
         }
 
+        // Bookmarks moved by the other operation weren't moved by this
+        // transaction.
+        let moved_local_bookmarks = mem::take(&mut self.moved_local_bookmarks);
         let changed_local_bookmarks =
             diff_named_ref_targets(base.local_bookmarks(), other.local_bookmarks());
         for (name, (base_target, other_target)) in changed_local_bookmarks {
             self.merge_local_bookmark(name, base_target, other_target);
         }
+        self.moved_local_bookmarks = moved_local_bookmarks;
 
         let changed_tags = diff_named_ref_targets(base.tags(), other.tags());
         for (name, (base_target, other_target)) in changed_tags {
@@ -1745,6 +1884,71 @@ impl MutableRepo {
         );
         self.set_git_head_target(new_git_head_target);
 
+        let bookmark_metadata_names: BTreeSet<&str> = itertools::chain(
+            base.bookmark_metadata().keys(),
+            other.bookmark_metadata().keys(),
+        )
+        .map(|name| name.as_ref())
+        .collect();
+        for name in bookmark_metadata_names {
+            let base_metadata = base.get_bookmark_metadata(name);
+            let other_metadata = other.get_bookmark_metadata(name);
+            if base_metadata != other_metadata {
+                self.merge_bookmark_metadata(name, base_metadata, other_metadata);
+            }
+        }
+
+        let topic_names: BTreeSet<&str> =
+            itertools::chain(base.topics().keys(), other.topics().keys())
+                .map(|name| name.as_ref())
+                .collect();
+        for name in topic_names {
+            let base_change_ids = base.get_topic(name).cloned().unwrap_or_default();
+            let other_change_ids = other.get_topic(name).cloned().unwrap_or_default();
+            if base_change_ids != other_change_ids {
+                self.merge_topic(name, &base_change_ids, &other_change_ids);
+            }
+        }
+
+        let shelf_names: BTreeSet<&str> =
+            itertools::chain(base.shelves().keys(), other.shelves().keys())
+                .map(|name| name.as_ref())
+                .collect();
+        for name in shelf_names {
+            let base_commit_id = base.get_shelf(name);
+            let other_commit_id = other.get_shelf(name);
+            // If both sides changed the same shelf, keep ours. The other
+            // commit is still reachable by id.
+            if base_commit_id != other_commit_id && self.get_shelf(name).as_ref() == base_commit_id
+            {
+                self.set_shelf(name, other_commit_id.cloned());
+            }
+        }
+
+        let change_ids: BTreeSet<&ChangeId> = itertools::chain(
+            base.change_metadata().keys(),
+            other.change_metadata().keys(),
+        )
+        .collect();
+        for change_id in change_ids {
+            let empty = BTreeMap::new();
+            let base_values = base.get_change_metadata(change_id).unwrap_or(&empty);
+            let other_values = other.get_change_metadata(change_id).unwrap_or(&empty);
+            let keys: BTreeSet<&str> = itertools::chain(base_values.keys(), other_values.keys())
+                .map(|key| key.as_ref())
+                .collect();
+            for key in keys {
+                let base_value = base_values.get(key);
+                let other_value = other_values.get(key);
+                // If both sides changed the same key, keep ours.
+                if base_value != other_value
+                    && self.get_change_metadata(change_id, key).as_ref() == base_value
+                {
+                    self.set_change_metadata(change_id, key, other_value.cloned());
+                }
+            }
+        }
+
         Ok(())
     }
 
@@ -0,0 +1,446 @@
+// Copyright 2025 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Format versions of the stores that make up a repository.
+//!
+//! Each store directory in `.jj/repo` records the version of its on-disk
+//! layout in a `format_version` file. Repos created before format versions
+//! were introduced don't have the file, which is interpreted as version 0.
+//! Loading a repo only checks the versions and never writes to the repo.
+//! Stores in an older format that this version of jj can still read are
+//! used as is. Stores in a format that is too old are rejected. Either way,
+//! stores are only migrated explicitly by [`upgrade_repo()`].
+
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::path::PathBuf;
+
+use thiserror::Error;
+
+use crate::default_index::DefaultIndexStore;
+use crate::file_util::IoResultExt as _;
+use crate::file_util::PathError;
+
+/// Name of the file recording the format version in each store directory.
+pub const FORMAT_VERSION_FILE_NAME: &str = "format_version";
+
+/// Store that makes up a repository.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum RepoStoreKind {
+    /// Commit backend in `store`.
+    Commit,
+    /// Operation store in `op_store`.
+    Operation,
+    /// Operation heads store in `op_heads`.
+    OperationHeads,
+    /// Index store in `index`.
+    Index,
+    /// Submodule store in `submodule_store`.
+    Submodule,
+}
+
+impl RepoStoreKind {
+    /// All stores in the order they are checked and upgraded.
+    pub const ALL: [Self; 5] = [
+        Self::Commit,
+        Self::Operation,
+        Self::OperationHeads,
+        Self::Index,
+        Self::Submodule,
+    ];
+
+    /// Name of the store directory in `.jj/repo`.
+    pub fn dir_name(self) -> &'static str {
+        match self {
+            Self::Commit => "store",
+            Self::Operation => "op_store",
+            Self::OperationHeads => "op_heads",
+            Self::Index => "index",
+            Self::Submodule => "submodule_store",
+        }
+    }
+
+    /// Description of the store used in messages.
+    pub fn description(self) -> &'static str {
+        match self {
+            Self::Commit => "commit",
+            Self::Operation => "operation",
+            Self::OperationHeads => "operation heads",
+            Self::Index => "index",
+            Self::Submodule => "submodule",
+        }
+    }
+
+    /// Format version written by this version of jj.
+    pub fn current_version(self) -> u32 {
+        match self {
+            Self::Commit
+            | Self::Operation
+            | Self::OperationHeads
+            | Self::Index
+            | Self::Submodule => 1,
+        }
+    }
+
+    /// Oldest format version which this version of jj can read without
+    /// migrating the store. Stores in an older format have to be upgraded by
+    /// [`upgrade_repo()`] before they can be used.
+    pub fn oldest_supported_version(self) -> u32 {
+        match self {
+            // The layout of unversioned stores is the same as version 1. The
+            // upgrade only records the version, and removes the legacy segment
+            // files that the index store ignores.
+            Self::Commit
+            | Self::Operation
+            | Self::OperationHeads
+            | Self::Index
+            | Self::Submodule => 0,
+        }
+    }
+}
+
+/// Error when reading or checking the format version of a store.
+#[derive(Debug, Error)]
+pub enum FormatVersionError {
+    /// The format version file couldn't be read.
+    #[error("Failed to read {store} store format version")]
+    Read {
+        /// Description of the store.
+        store: &'static str,
+        /// Underlying error.
+        source: PathError,
+    },
+    /// The format version file doesn't contain a version number.
+    #[error("Invalid {store} store format version '{content}'")]
+    Invalid {
+        /// Description of the store.
+        store: &'static str,
+        /// Content of the format version file.
+        content: String,
+    },
+    /// The store was written by a newer version of jj.
+    #[error(
+        "The {store} store has format version {version}, but this version of jj only supports \
+         up to version {supported}"
+    )]
+    TooNew {
+        /// Description of the store.
+        store: &'static str,
+        /// Format version of the store.
+        version: u32,
+        /// Latest format version supported by this version of jj.
+        supported: u32,
+    },
+    /// The store has to be upgraded before it can be used.
+    #[error(
+        "The {store} store has format version {version}, which is older than the current \
+         version {current}"
+    )]
+    Outdated {
+        /// Description of the store.
+        store: &'static str,
+        /// Format version of the store.
+        version: u32,
+        /// Format version written by this version of jj.
+        current: u32,
+    },
+}
+
+/// Error when upgrading a repo to the current format.
+#[derive(Debug, Error)]
+pub enum RepoUpgradeError {
+    /// The format version of a store couldn't be determined or is
+    /// unsupported.
+    #[error(transparent)]
+    FormatVersion(#[from] FormatVersionError),
+    /// The backup directory already exists.
+    #[error("Backup directory {} already exists", .0.display())]
+    BackupExists(PathBuf),
+    /// Failed to back up or migrate a store.
+    #[error(transparent)]
+    Path(#[from] PathError),
+}
+
+/// Migration of a store from one format version to another.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct FormatUpgrade {
+    /// Store to migrate.
+    pub kind: RepoStoreKind,
+    /// Format version before the migration.
+    pub from: u32,
+    /// Format version after the migration.
+    pub to: u32,
+}
+
+/// Reads the format version of the `kind` store of the repo at `repo_path`.
+pub fn read_format_version(
+    repo_path: &Path,
+    kind: RepoStoreKind,
+) -> Result<u32, FormatVersionError> {
+    let path = repo_path
+        .join(kind.dir_name())
+        .join(FORMAT_VERSION_FILE_NAME);
+    let content = match fs::read_to_string(&path) {
+        Ok(content) => content,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(0),
+        Err(err) => {
+            return Err(FormatVersionError::Read {
+                store: kind.description(),
+                source: PathError { path, error: err },
+            });
+        }
+    };
+    content
+        .trim()
+        .parse()
+        .map_err(|_| FormatVersionError::Invalid {
+            store: kind.description(),
+            content,
+        })
+}
+
+/// Records the format version of the `kind` store of the repo at `repo_path`.
+pub fn write_format_version(
+    repo_path: &Path,
+    kind: RepoStoreKind,
+    version: u32,
+) -> Result<(), PathError> {
+    let path = repo_path
+        .join(kind.dir_name())
+        .join(FORMAT_VERSION_FILE_NAME);
+    fs::write(&path, version.to_string()).context(&path)
+}
+
+/// Checks that all stores of the repo at `repo_path` can be used by this
+/// version of jj. Doesn't write to the repo.
+///
+/// Fails if any store was written in a format newer than this version of jj
+/// supports, or if it has to be upgraded by [`upgrade_repo()`] first.
+pub fn check_repo_format(repo_path: &Path) -> Result<(), FormatVersionError> {
+    let upgrades = pending_upgrades(repo_path)?;
+    if let Some(upgrade) = upgrades
+        .iter()
+        .find(|upgrade| upgrade.from < upgrade.kind.oldest_supported_version())
+    {
+        return Err(FormatVersionError::Outdated {
+            store: upgrade.kind.description(),
+            version: upgrade.from,
+            current: upgrade.to,
+        });
+    }
+    Ok(())
+}
+
+/// Returns the stores of the repo at `repo_path` that need to be upgraded.
+///
+/// Fails if any store was written in a format newer than this version of jj
+/// supports. Missing store directories are skipped, so that loading the store
+/// can report the broken repo.
+pub fn pending_upgrades(repo_path: &Path) -> Result<Vec<FormatUpgrade>, FormatVersionError> {
+    let mut upgrades = vec![];
+    for kind in RepoStoreKind::ALL {
+        if !repo_path.join(kind.dir_name()).is_dir() {
+            continue;
+        }
+        let version = read_format_version(repo_path, kind)?;
+        let current = kind.current_version();
+        if version > current {
+            return Err(FormatVersionError::TooNew {
+                store: kind.description(),
+                version,
+                supported: current,
+            });
+        } else if version < current {
+            upgrades.push(FormatUpgrade {
+                kind,
+                from: version,
+                to: current,
+            });
+        }
+    }
+    Ok(upgrades)
+}
+
+/// Migrates all stores of the repo at `repo_path` to the current format.
+///
+/// Before anything is migrated, the directories of the stores to be upgraded
+/// are copied recursively to `backup_path`, which must not exist. Each
+/// migration step records the new version, so an interrupted upgrade can be
+/// resumed. Returns the upgrades that were performed.
+pub fn upgrade_repo(
+    repo_path: &Path,
+    backup_path: &Path,
+) -> Result<Vec<FormatUpgrade>, RepoUpgradeError> {
+    let upgrades = pending_upgrades(repo_path)?;
+    if upgrades.is_empty() {
+        return Ok(upgrades);
+    }
+    if backup_path.exists() {
+        return Err(RepoUpgradeError::BackupExists(backup_path.to_owned()));
+    }
+    for upgrade in &upgrades {
+        let store_dir = upgrade.kind.dir_name();
+        copy_dir_recursively(&repo_path.join(store_dir), &backup_path.join(store_dir))?;
+    }
+    for upgrade in &upgrades {
+        apply_upgrade(repo_path, upgrade)?;
+    }
+    Ok(upgrades)
+}
+
+fn apply_upgrade(repo_path: &Path, upgrade: &FormatUpgrade) -> Result<(), PathError> {
+    for version in upgrade.from..upgrade.to {
+        migrate_store(repo_path, upgrade.kind, version)?;
+        write_format_version(repo_path, upgrade.kind, version + 1)?;
+    }
+    Ok(())
+}
+
+fn copy_dir_recursively(src: &Path, dest: &Path) -> Result<(), PathError> {
+    fs::create_dir_all(dest).context(dest)?;
+    for entry in src.read_dir().context(src)? {
+        let entry = entry.context(src)?;
+        let path = entry.path();
+        let dest_path = dest.join(entry.file_name());
+        if entry.file_type().context(&path)?.is_dir() {
+            copy_dir_recursively(&path, &dest_path)?;
+        } else {
+            fs::copy(&path, &dest_path).context(&dest_path)?;
+        }
+    }
+    Ok(())
+}
+
+/// Migrates the `kind` store from `version` to `version + 1`.
+fn migrate_store(repo_path: &Path, kind: RepoStoreKind, version: u32) -> Result<(), PathError> {
+    let store_path = repo_path.join(kind.dir_name());
+    match (kind, version) {
+        (RepoStoreKind::Index, 0) => {
+            let type_path = store_path.join("type");
+            let index_type = fs::read_to_string(&type_path).context(&type_path)?;
+            if index_type == DefaultIndexStore::name() {
+                DefaultIndexStore::load(&store_path).upgrade_legacy_layout()?;
+            }
+        }
+        // The other stores only start recording the version.
+        (_, 0) => {}
+        _ => unreachable!("no migration from {kind:?} store version {version}"),
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use assert_matches::assert_matches;
+
+    use super::*;
+
+    fn init_unversioned_repo(repo_path: &Path) {
+        for kind in RepoStoreKind::ALL {
+            let store_path = repo_path.join(kind.dir_name());
+            fs::create_dir(&store_path).unwrap();
+            fs::write(store_path.join("type"), "default").unwrap();
+        }
+    }
+
+    #[test]
+    fn test_check_repo_format() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let repo_path = temp_dir.path();
+        init_unversioned_repo(repo_path);
+        // Unversioned stores can be used as is, and aren't upgraded
+        assert!(check_repo_format(repo_path).is_ok());
+        for kind in RepoStoreKind::ALL {
+            assert_eq!(read_format_version(repo_path, kind).unwrap(), 0);
+        }
+        assert!(!repo_path.join("index").join("segments").exists());
+
+        write_format_version(repo_path, RepoStoreKind::Index, 100).unwrap();
+        assert_matches!(
+            check_repo_format(repo_path),
+            Err(FormatVersionError::TooNew {
+                store: "index",
+                version: 100,
+                supported: 1,
+            })
+        );
+
+        fs::write(repo_path.join("index").join(FORMAT_VERSION_FILE_NAME), "x").unwrap();
+        assert_matches!(
+            check_repo_format(repo_path),
+            Err(FormatVersionError::Invalid { store: "index", .. })
+        );
+    }
+
+    #[test]
+    fn test_upgrade_repo() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let repo_path = temp_dir.path().join("repo");
+        let backup_path = temp_dir.path().join("backup");
+        fs::create_dir(&repo_path).unwrap();
+        init_unversioned_repo(&repo_path);
+        write_format_version(&repo_path, RepoStoreKind::Commit, 1).unwrap();
+        // jj <= 0.14 created index segment files in the top directory
+        let legacy_segment_path = repo_path.join("index").join("0".repeat(128));
+        fs::write(&legacy_segment_path, "").unwrap();
+        let op_path = repo_path.join("op_store").join("operations").join("abc");
+        fs::create_dir(op_path.parent().unwrap()).unwrap();
+        fs::write(&op_path, "").unwrap();
+
+        let upgrades = upgrade_repo(&repo_path, &backup_path).unwrap();
+        assert_eq!(
+            upgrades
+                .iter()
+                .map(|upgrade| upgrade.kind)
+                .collect::<Vec<_>>(),
+            [
+                RepoStoreKind::Operation,
+                RepoStoreKind::OperationHeads,
+                RepoStoreKind::Index,
+                RepoStoreKind::Submodule,
+            ]
+        );
+        assert!(check_repo_format(&repo_path).is_ok());
+        assert!(!legacy_segment_path.exists());
+        assert!(repo_path.join("index").join("segments").is_dir());
+        assert!(backup_path.join("index").join("0".repeat(128)).is_file());
+        assert!(backup_path.join("op_store").join("type").is_file());
+        // Store directories are backed up recursively
+        assert!(backup_path
+            .join("op_store")
+            .join("operations")
+            .join("abc")
+            .is_file());
+        // Up-to-date stores aren't backed up
+        assert!(!backup_path.join("store").exists());
+
+        // Nothing to do
+        let upgrades = upgrade_repo(&repo_path, &backup_path).unwrap();
+        assert!(upgrades.is_empty());
+    }
+
+    #[test]
+    fn test_upgrade_repo_backup_exists() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let repo_path = temp_dir.path().join("repo");
+        fs::create_dir(&repo_path).unwrap();
+        init_unversioned_repo(&repo_path);
+        assert_matches!(
+            upgrade_repo(&repo_path, temp_dir.path()),
+            Err(RepoUpgradeError::BackupExists(_))
+        );
+        assert!(!pending_upgrades(&repo_path).unwrap().is_empty());
+    }
+}
@@ -30,6 +30,8 @@ use thiserror::Error;
 use crate::backend::BackendError;
 use crate::backend::ChangeId;
 use crate::backend::CommitId;
+use crate::backend::MergedTreeId;
+use crate::backend::TreeId;
 use crate::commit::Commit;
 use crate::dsl_util;
 use crate::dsl_util::collect_similar;
@@ -37,6 +39,7 @@ use crate::dsl_util::AliasExpandError as _;
 use crate::fileset;
 use crate::fileset::FilesetDiagnostics;
 use crate::fileset::FilesetExpression;
+use crate::fileset::FilesetParseContext;
 use crate::graph::GraphNode;
 use crate::hex_util::to_forward_hex;
 use crate::id_prefix::IdPrefixContext;
@@ -51,6 +54,7 @@ use crate::refs::RemoteRefSymbolBuf;
 use crate::repo::ReadonlyRepo;
 use crate::repo::Repo;
 use crate::repo::RepoLoaderError;
+use crate::repo_path::RepoPathBuf;
 use crate::repo_path::RepoPathUiConverter;
 use crate::revset_parser;
 pub use crate::revset_parser::expect_literal;
@@ -133,12 +137,19 @@ pub enum RevsetCommitRef {
     Symbol(String),
     RemoteSymbol(RemoteRefSymbolBuf),
     Bookmarks(StringPattern),
+    /// Local bookmarks matching the pattern which were last moved within the
+    /// date range.
+    BookmarksMoved {
+        bookmark_pattern: StringPattern,
+        moved: DatePattern,
+    },
     RemoteBookmarks {
         bookmark_pattern: StringPattern,
         remote_pattern: StringPattern,
         remote_ref_state: Option<RemoteRefState>,
     },
     Tags(StringPattern),
+    Topics(StringPattern),
     GitRefs,
     GitHead,
 }
@@ -180,6 +191,8 @@ pub enum RevsetFilterPredicate {
     },
     /// Commits with conflicts
     HasConflict,
+    /// Commits whose tree is one of the given trees.
+    Tree(Vec<MergedTreeId>),
     /// Custom predicates provided by extensions
     Extension(Rc<dyn RevsetFilterExtension>),
 }
@@ -257,11 +270,15 @@ pub enum RevsetExpression<St: ExpressionState> {
     Heads(Rc<Self>),
     Roots(Rc<Self>),
     ForkPoint(Rc<Self>),
+    CommonAncestors(Rc<Self>, Rc<Self>),
     Latest {
         candidates: Rc<Self>,
         count: usize,
     },
     Filter(RevsetFilterPredicate),
+    /// Commits having the same tree as any of the `commits`. This is a filter
+    /// node, which should be wrapped in `AsFilter`.
+    TreeEquals(Rc<Self>),
     /// Marker for subtree that should be intersected as filter.
     AsFilter(Rc<Self>),
     /// Resolves symbols and visibility at the specified operation.
@@ -343,6 +360,13 @@ impl<St: ExpressionState<CommitRef = RevsetCommitRef>> RevsetExpression<St> {
         Rc::new(Self::CommitRef(RevsetCommitRef::Bookmarks(pattern)))
     }
 
+    pub fn bookmarks_moved(bookmark_pattern: StringPattern, moved: DatePattern) -> Rc<Self> {
+        Rc::new(Self::CommitRef(RevsetCommitRef::BookmarksMoved {
+            bookmark_pattern,
+            moved,
+        }))
+    }
+
     pub fn remote_bookmarks(
         bookmark_pattern: StringPattern,
         remote_pattern: StringPattern,
@@ -359,6 +383,10 @@ impl<St: ExpressionState<CommitRef = RevsetCommitRef>> RevsetExpression<St> {
         Rc::new(Self::CommitRef(RevsetCommitRef::Tags(pattern)))
     }
 
+    pub fn topics(pattern: StringPattern) -> Rc<Self> {
+        Rc::new(Self::CommitRef(RevsetCommitRef::Topics(pattern)))
+    }
+
     pub fn git_refs() -> Rc<Self> {
         Rc::new(Self::CommitRef(RevsetCommitRef::GitRefs))
     }
@@ -440,6 +468,17 @@ impl<St: ExpressionState> RevsetExpression<St> {
         Rc::new(Self::ForkPoint(self.clone()))
     }
 
+    /// Commits having the same tree as any of the commits in `self`.
+    pub fn tree_equals(self: &Rc<Self>) -> Rc<Self> {
+        Rc::new(Self::AsFilter(Rc::new(Self::TreeEquals(self.clone()))))
+    }
+
+    /// Best common ancestors of `self` and `other`, equivalent to
+    /// `heads(::self & ::other)`.
+    pub fn common_ancestors(self: &Rc<Self>, other: &Rc<Self>) -> Rc<Self> {
+        Rc::new(Self::CommonAncestors(self.clone(), other.clone()))
+    }
+
     /// Filter all commits by `predicate` in `self`.
     pub fn filtered(self: &Rc<Self>, predicate: RevsetFilterPredicate) -> Rc<Self> {
         self.intersection(&Self::filter(predicate))
@@ -544,6 +583,7 @@ impl<St: ExpressionState<CommitRef = RevsetCommitRef>> RevsetExpression<St> {
 impl UserRevsetExpression {
     /// Resolve a user-provided expression. Symbols will be resolved using the
     /// provided `SymbolResolver`.
+    #[tracing::instrument(skip_all)]
     pub fn resolve_user_expression(
         &self,
         repo: &dyn Repo,
@@ -566,6 +606,7 @@ impl ResolvedRevsetExpression {
     ///
     /// Use this function if `self` is already optimized, or to debug
     /// optimization pass.
+    #[tracing::instrument(skip_all)]
     pub fn evaluate_unoptimized<'index>(
         &self,
         repo: &'index dyn Repo,
@@ -585,6 +626,8 @@ impl ResolvedRevsetExpression {
 pub enum ResolvedPredicateExpression {
     /// Pure filter predicate.
     Filter(RevsetFilterPredicate),
+    /// Commits having the same tree as any of the commits in the set.
+    TreeEquals(Box<ResolvedExpression>),
     /// Set expression to be evaluated as filter. This is typically a subtree
     /// node of `Union` with a pure filter predicate.
     Set(Box<ResolvedExpression>),
@@ -629,6 +672,7 @@ pub enum ResolvedExpression {
     Heads(Box<Self>),
     Roots(Box<Self>),
     ForkPoint(Box<Self>),
+    CommonAncestors(Box<Self>, Box<Self>),
     Latest {
         candidates: Box<Self>,
         count: usize,
@@ -710,6 +754,11 @@ static BUILTIN_FUNCTION_MAP: Lazy<HashMap<&'static str, RevsetFunction>> = Lazy:
         function.expect_no_arguments()?;
         Ok(RevsetExpression::working_copies())
     });
+    map.insert("visible_in_workspace", |diagnostics, function, _context| {
+        let [arg] = function.expect_exact_arguments()?;
+        let name: String = expect_literal(diagnostics, "string", arg)?;
+        Ok(RevsetExpression::working_copy(WorkspaceId::new(name)).ancestors())
+    });
     map.insert("heads", |diagnostics, function, context| {
         let [arg] = function.expect_exact_arguments()?;
         let candidates = lower_expression(diagnostics, arg, context)?;
@@ -728,14 +777,20 @@ static BUILTIN_FUNCTION_MAP: Lazy<HashMap<&'static str, RevsetFunction>> = Lazy:
         function.expect_no_arguments()?;
         Ok(RevsetExpression::root())
     });
-    map.insert("bookmarks", |diagnostics, function, _context| {
-        let ([], [opt_arg]) = function.expect_arguments()?;
+    map.insert("bookmarks", |diagnostics, function, context| {
+        let ([], [opt_arg, moved_opt_arg]) = function.expect_named_arguments(&["", "moved"])?;
         let pattern = if let Some(arg) = opt_arg {
             expect_string_pattern(diagnostics, arg)?
         } else {
             StringPattern::everything()
         };
-        Ok(RevsetExpression::bookmarks(pattern))
+        if let Some(moved_arg) = moved_opt_arg {
+            let moved =
+                expect_date_pattern(diagnostics, moved_arg, context.date_pattern_context())?;
+            Ok(RevsetExpression::bookmarks_moved(pattern, moved))
+        } else {
+            Ok(RevsetExpression::bookmarks(pattern))
+        }
     });
     map.insert("remote_bookmarks", |diagnostics, function, _context| {
         parse_remote_bookmarks_arguments(diagnostics, function, None)
@@ -761,6 +816,11 @@ static BUILTIN_FUNCTION_MAP: Lazy<HashMap<&'static str, RevsetFunction>> = Lazy:
         };
         Ok(RevsetExpression::tags(pattern))
     });
+    map.insert("topic", |diagnostics, function, _context| {
+        let [arg] = function.expect_exact_arguments()?;
+        let pattern = expect_string_pattern(diagnostics, arg)?;
+        Ok(RevsetExpression::topics(pattern))
+    });
     map.insert("git_refs", |_diagnostics, function, _context| {
         function.expect_no_arguments()?;
         Ok(RevsetExpression::git_refs())
@@ -780,9 +840,14 @@ static BUILTIN_FUNCTION_MAP: Lazy<HashMap<&'static str, RevsetFunction>> = Lazy:
         Ok(candidates.latest(count))
     });
     map.insert("fork_point", |diagnostics, function, context| {
-        let [expression_arg] = function.expect_exact_arguments()?;
+        let ([expression_arg], [other_opt_arg]) = function.expect_arguments()?;
         let expression = lower_expression(diagnostics, expression_arg, context)?;
-        Ok(RevsetExpression::fork_point(&expression))
+        if let Some(other_arg) = other_opt_arg {
+            let other = lower_expression(diagnostics, other_arg, context)?;
+            Ok(expression.common_ancestors(&other))
+        } else {
+            Ok(RevsetExpression::fork_point(&expression))
+        }
     });
     map.insert("merges", |_diagnostics, function, _context| {
         function.expect_no_arguments()?;
@@ -878,7 +943,7 @@ static BUILTIN_FUNCTION_MAP: Lazy<HashMap<&'static str, RevsetFunction>> = Lazy:
             )
         })?;
         let [arg] = function.expect_exact_arguments()?;
-        let expr = expect_fileset_expression(diagnostics, arg, ctx.path_converter)?;
+        let expr = expect_fileset_expression(diagnostics, arg, &ctx.fileset_parse_context())?;
         Ok(RevsetExpression::filter(RevsetFilterPredicate::File(expr)))
     });
     map.insert("diff_contains", |diagnostics, function, context| {
@@ -891,7 +956,7 @@ static BUILTIN_FUNCTION_MAP: Lazy<HashMap<&'static str, RevsetFunction>> = Lazy:
                     files_arg.span,
                 )
             })?;
-            expect_fileset_expression(diagnostics, files_arg, ctx.path_converter)?
+            expect_fileset_expression(diagnostics, files_arg, &ctx.fileset_parse_context())?
         } else {
             // TODO: defaults to CLI path arguments?
             // https://github.com/jj-vcs/jj/issues/2933#issuecomment-1925870731
@@ -905,6 +970,20 @@ static BUILTIN_FUNCTION_MAP: Lazy<HashMap<&'static str, RevsetFunction>> = Lazy:
         function.expect_no_arguments()?;
         Ok(RevsetExpression::filter(RevsetFilterPredicate::HasConflict))
     });
+    map.insert("exact_tree", |diagnostics, function, _context| {
+        let [arg] = function.expect_exact_arguments()?;
+        let hex: String = expect_literal(diagnostics, "string", arg)?;
+        let tree_id = TreeId::try_from_hex(&hex)
+            .map_err(|_| RevsetParseError::expression("Expected hexadecimal tree id", arg.span))?;
+        Ok(RevsetExpression::filter(RevsetFilterPredicate::Tree(vec![
+            MergedTreeId::resolved(tree_id),
+        ])))
+    });
+    map.insert("tree_equals", |diagnostics, function, context| {
+        let [arg] = function.expect_exact_arguments()?;
+        let expression = lower_expression(diagnostics, arg, context)?;
+        Ok(expression.tree_equals())
+    });
     map.insert("present", |diagnostics, function, context| {
         let [arg] = function.expect_exact_arguments()?;
         let expression = lower_expression(diagnostics, arg, context)?;
@@ -938,14 +1017,14 @@ static BUILTIN_FUNCTION_MAP: Lazy<HashMap<&'static str, RevsetFunction>> = Lazy:
 pub fn expect_fileset_expression(
     diagnostics: &mut RevsetDiagnostics,
     node: &ExpressionNode,
-    path_converter: &RepoPathUiConverter,
+    context: &FilesetParseContext,
 ) -> Result<FilesetExpression, RevsetParseError> {
     // Alias handling is a bit tricky. The outermost expression `alias` is
     // substituted, but inner expressions `x & alias` aren't. If this seemed
     // weird, we can either transform AST or turn off revset aliases completely.
     revset_parser::expect_expression_with(diagnostics, node, |diagnostics, node| {
         let mut inner_diagnostics = FilesetDiagnostics::new();
-        let expression = fileset::parse(&mut inner_diagnostics, node.span.as_str(), path_converter)
+        let expression = fileset::parse(&mut inner_diagnostics, node.span.as_str(), context)
             .map_err(|err| {
                 RevsetParseError::expression("In fileset expression", node.span).with_source(err)
             })?;
@@ -1235,12 +1314,22 @@ fn try_transform_expression<St: ExpressionState, E>(
             RevsetExpression::ForkPoint(expression) => {
                 transform_rec(expression, pre, post)?.map(RevsetExpression::ForkPoint)
             }
+            RevsetExpression::CommonAncestors(expression1, expression2) => {
+                transform_rec_pair((expression1, expression2), pre, post)?.map(
+                    |(expression1, expression2)| {
+                        RevsetExpression::CommonAncestors(expression1, expression2)
+                    },
+                )
+            }
             RevsetExpression::Latest { candidates, count } => transform_rec(candidates, pre, post)?
                 .map(|candidates| RevsetExpression::Latest {
                     candidates,
                     count: *count,
                 }),
             RevsetExpression::Filter(_) => None,
+            RevsetExpression::TreeEquals(commits) => {
+                transform_rec(commits, pre, post)?.map(RevsetExpression::TreeEquals)
+            }
             RevsetExpression::AsFilter(candidates) => {
                 transform_rec(candidates, pre, post)?.map(RevsetExpression::AsFilter)
             }
@@ -1429,12 +1518,21 @@ where
             let expression = folder.fold_expression(expression)?;
             RevsetExpression::ForkPoint(expression).into()
         }
+        RevsetExpression::CommonAncestors(expression1, expression2) => {
+            let expression1 = folder.fold_expression(expression1)?;
+            let expression2 = folder.fold_expression(expression2)?;
+            RevsetExpression::CommonAncestors(expression1, expression2).into()
+        }
         RevsetExpression::Latest { candidates, count } => {
             let candidates = folder.fold_expression(candidates)?;
             let count = *count;
             RevsetExpression::Latest { candidates, count }.into()
         }
         RevsetExpression::Filter(predicate) => RevsetExpression::Filter(predicate.clone()).into(),
+        RevsetExpression::TreeEquals(commits) => {
+            let commits = folder.fold_expression(commits)?;
+            RevsetExpression::TreeEquals(commits).into()
+        }
         RevsetExpression::AsFilter(candidates) => {
             let candidates = folder.fold_expression(candidates)?;
             RevsetExpression::AsFilter(candidates).into()
@@ -1765,6 +1863,7 @@ fn fold_generation<St: ExpressionState>(
 
 /// Rewrites the given `expression` tree to reduce evaluation cost. Returns new
 /// tree.
+#[tracing::instrument(skip_all)]
 pub fn optimize<St: ExpressionState>(
     expression: Rc<RevsetExpression<St>>,
 ) -> Rc<RevsetExpression<St>> {
@@ -2113,6 +2212,22 @@ fn resolve_commit_ref(
                 .collect();
             Ok(commit_ids)
         }
+        RevsetCommitRef::BookmarksMoved {
+            bookmark_pattern,
+            moved,
+        } => {
+            let view = repo.view();
+            let commit_ids = view
+                .local_bookmarks_matching(bookmark_pattern)
+                .filter(|(name, _)| {
+                    view.get_bookmark_metadata(name)
+                        .is_some_and(|metadata| moved.matches(&metadata.moved))
+                })
+                .flat_map(|(_, target)| target.added_ids())
+                .cloned()
+                .collect();
+            Ok(commit_ids)
+        }
         RevsetCommitRef::RemoteBookmarks {
             bookmark_pattern,
             remote_pattern,
@@ -2140,6 +2255,16 @@ fn resolve_commit_ref(
                 .collect();
             Ok(commit_ids)
         }
+        RevsetCommitRef::Topics(pattern) => {
+            let commit_ids = repo
+                .view()
+                .topics_matching(pattern)
+                .flat_map(|(_, change_ids)| change_ids)
+                .filter_map(|change_id| repo.resolve_change_id(change_id))
+                .flatten()
+                .collect();
+            Ok(commit_ids)
+        }
         RevsetCommitRef::GitRefs => {
             let mut commit_ids = vec![];
             for ref_target in repo.view().git_refs().values() {
@@ -2310,11 +2435,19 @@ impl VisibilityResolutionContext<'_> {
             RevsetExpression::ForkPoint(expression) => {
                 ResolvedExpression::ForkPoint(self.resolve(expression).into())
             }
+            RevsetExpression::CommonAncestors(expression1, expression2) => {
+                ResolvedExpression::CommonAncestors(
+                    self.resolve(expression1).into(),
+                    self.resolve(expression2).into(),
+                )
+            }
             RevsetExpression::Latest { candidates, count } => ResolvedExpression::Latest {
                 candidates: self.resolve(candidates).into(),
                 count: *count,
             },
-            RevsetExpression::Filter(_) | RevsetExpression::AsFilter(_) => {
+            RevsetExpression::Filter(_)
+            | RevsetExpression::TreeEquals(_)
+            | RevsetExpression::AsFilter(_) => {
                 // Top-level filter without intersection: e.g. "~author(_)" is represented as
                 // `AsFilter(NotIn(Filter(Author(_))))`.
                 ResolvedExpression::FilterWithin {
@@ -2415,12 +2548,16 @@ impl VisibilityResolutionContext<'_> {
             | RevsetExpression::Heads(_)
             | RevsetExpression::Roots(_)
             | RevsetExpression::ForkPoint(_)
+            | RevsetExpression::CommonAncestors(..)
             | RevsetExpression::Latest { .. } => {
                 ResolvedPredicateExpression::Set(self.resolve(expression).into())
             }
             RevsetExpression::Filter(predicate) => {
                 ResolvedPredicateExpression::Filter(predicate.clone())
             }
+            RevsetExpression::TreeEquals(commits) => {
+                ResolvedPredicateExpression::TreeEquals(self.resolve(commits).into())
+            }
             RevsetExpression::AsFilter(candidates) => self.resolve_predicate(candidates),
             RevsetExpression::AtOperation { operation, .. } => match *operation {},
             // Filters should be intersected with all() within the at-op repo.
@@ -2610,6 +2747,18 @@ impl<'a> RevsetParseContext<'a> {
 pub struct RevsetWorkspaceContext<'a> {
     pub path_converter: &'a RepoPathUiConverter,
     pub workspace_id: &'a WorkspaceId,
+    /// Recorded sparse patterns of the workspaces in the repo.
+    pub workspace_sparse_patterns: Option<&'a HashMap<WorkspaceId, Vec<RepoPathBuf>>>,
+}
+
+impl RevsetWorkspaceContext<'_> {
+    /// Returns context to resolve fileset expressions in this workspace.
+    pub fn fileset_parse_context(&self) -> FilesetParseContext<'_> {
+        FilesetParseContext {
+            path_converter: self.path_converter,
+            workspace_sparse_patterns: self.workspace_sparse_patterns,
+        }
+    }
 }
 
 /// Formats a string as symbol by quoting and escaping it if necessary.
@@ -2688,6 +2837,7 @@ mod tests {
         let workspace_ctx = RevsetWorkspaceContext {
             path_converter: &path_converter,
             workspace_id,
+            workspace_sparse_patterns: None,
         };
         let mut aliases_map = RevsetAliasesMap::new();
         for (decl, defn) in aliases {
@@ -2937,6 +3087,14 @@ mod tests {
         insta::assert_debug_snapshot!(
             parse_with_workspace("main@", &other_workspace_id).unwrap(),
             @r#"CommitRef(WorkingCopy(WorkspaceId("main")))"#);
+        insta::assert_debug_snapshot!(
+            parse("visible_in_workspace(main)").unwrap(),
+            @r#"
+            Ancestors {
+                heads: CommitRef(WorkingCopy(WorkspaceId("main"))),
+                generation: 0..18446744073709551615,
+            }
+            "#);
         // "@" in function argument must be quoted
         insta::assert_debug_snapshot!(
             parse("author_name(foo@)").unwrap_err().kind(),
@@ -23,6 +23,7 @@ use indexmap::IndexMap;
 use indexmap::IndexSet;
 use itertools::Itertools;
 use pollster::FutureExt;
+use serde::Deserialize;
 use tracing::instrument;
 
 use crate::backend::BackendError;
@@ -37,6 +38,7 @@ use crate::index::Index;
 use crate::index::IndexError;
 use crate::matchers::Matcher;
 use crate::matchers::Visit;
+use crate::merge::MergedTreeValue;
 use crate::merged_tree::MergedTree;
 use crate::merged_tree::MergedTreeBuilder;
 use crate::merged_tree::TreeDiffEntry;
@@ -118,6 +120,25 @@ pub fn restore_tree(
     }
 }
 
+/// Rewrites the values of the matching paths in the `tree`.
+///
+/// `rewrite_value` is called for each matching path with its current value,
+/// and returns the new value, or `None` to leave the path unchanged. Returns
+/// the id of the rewritten tree.
+pub fn rewrite_tree_values<E: From<BackendError>>(
+    tree: &MergedTree,
+    matcher: &dyn Matcher,
+    mut rewrite_value: impl FnMut(&RepoPath, MergedTreeValue) -> Result<Option<MergedTreeValue>, E>,
+) -> Result<MergedTreeId, E> {
+    let mut tree_builder = MergedTreeBuilder::new(tree.id());
+    for (repo_path, value) in tree.entries_matching(matcher) {
+        if let Some(new_value) = rewrite_value(&repo_path, value?)? {
+            tree_builder.set_or_remove(repo_path, new_value);
+        }
+    }
+    Ok(tree_builder.write_tree(tree.store())?)
+}
+
 pub fn rebase_commit(
     mut_repo: &mut MutableRepo,
     old_commit: Commit,
@@ -128,6 +149,36 @@ pub fn rebase_commit(
     builder.write()
 }
 
+/// Rebases `children` so that their edges to any of `old_parent_ids` go
+/// through `new_parent_id` instead.
+///
+/// The first matching parent of each child is replaced in place and any other
+/// matching parents are dropped, so the order of the remaining parents is
+/// preserved. Children that have none of `old_parent_ids` as parent get
+/// `new_parent_id` appended to their parents.
+pub fn rebase_onto_inserted_commit(
+    mut_repo: &mut MutableRepo,
+    children: Vec<Commit>,
+    old_parent_ids: &HashSet<CommitId>,
+    new_parent_id: &CommitId,
+) -> BackendResult<Vec<Commit>> {
+    let mut rebased = Vec::with_capacity(children.len());
+    for child in children {
+        let old_parents = child.parent_ids().to_vec();
+        let mut rewriter = CommitRewriter::new(mut_repo, child, old_parents.clone());
+        for old_parent_id in old_parents.iter().filter(|id| old_parent_ids.contains(id)) {
+            rewriter.replace_parent(old_parent_id, [new_parent_id]);
+        }
+        if !rewriter.new_parents().contains(new_parent_id) {
+            let mut new_parents = rewriter.new_parents().to_vec();
+            new_parents.push(new_parent_id.clone());
+            rewriter.set_new_parents(new_parents);
+        }
+        rebased.push(rewriter.rebase()?.write()?);
+    }
+    Ok(rebased)
+}
+
 /// Helps rewrite a commit.
 pub struct CommitRewriter<'repo> {
     mut_repo: &'repo mut MutableRepo,
@@ -365,6 +416,24 @@ pub enum EmptyBehaviour {
     AbandonAllEmpty,
 }
 
+/// Policy for abandoning commits which become empty when they are rewritten,
+/// configured by `rewrite.drop-empty`.
+///
+/// Merge commits and commits which were already empty are never abandoned.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, serde::Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum DropEmptyPolicy {
+    /// Keep commits which become empty.
+    Never,
+    /// Use the default behavior of each command. For example, `jj squash`
+    /// abandons the source commit if all of its changes are moved, whereas
+    /// `jj rebase` keeps commits which become empty.
+    #[default]
+    Auto,
+    /// Abandon commits which become empty.
+    Always,
+}
+
 /// Controls the configuration of a rebase.
 // If we wanted to add a flag similar to `git rebase --ignore-date`, then this
 // makes it much easier by ensuring that the only changes required are to
@@ -1048,6 +1117,23 @@ impl CommitWithSelection {
     }
 }
 
+/// How to combine the descriptions of the destination and the abandoned
+/// source commits of a squash.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum SquashDescriptionMode {
+    /// If only one of the descriptions is non-empty, use it. Otherwise, the
+    /// descriptions have to be combined by the user.
+    #[default]
+    Edit,
+    /// Concatenate the non-empty descriptions, starting with the
+    /// destination's.
+    Concatenate,
+    /// Use the destination's description. If it's empty, concatenate the
+    /// descriptions of the sources.
+    Destination,
+}
+
 /// Resulting commit builder and stats to be returned by [`squash_commits()`].
 #[must_use]
 pub struct SquashedCommit<'repo> {
@@ -1057,6 +1143,50 @@ pub struct SquashedCommit<'repo> {
     pub abandoned_commits: Vec<Commit>,
 }
 
+impl SquashedCommit<'_> {
+    /// Combines the descriptions of the `destination` and the abandoned
+    /// source commits. Concatenated descriptions are separated by `separator`.
+    ///
+    /// Returns `None` if the descriptions can't be combined without user
+    /// input.
+    pub fn combine_descriptions(
+        &self,
+        destination: &Commit,
+        mode: SquashDescriptionMode,
+        separator: &str,
+    ) -> Option<String> {
+        let sources = self
+            .abandoned_commits
+            .iter()
+            .map(|commit| commit.description())
+            .filter(|description| !description.is_empty());
+        let descriptions = if destination.description().is_empty() {
+            sources.collect_vec()
+        } else if mode == SquashDescriptionMode::Destination {
+            vec![destination.description()]
+        } else {
+            itertools::chain([destination.description()], sources).collect_vec()
+        };
+        match (mode, descriptions.as_slice()) {
+            (_, []) => Some(String::new()),
+            (_, [description]) => Some((*description).to_owned()),
+            (SquashDescriptionMode::Edit, [_, _, ..]) => None,
+            (_, [_, _, ..]) => Some(
+                descriptions
+                    .iter()
+                    .map(|description| {
+                        if description.ends_with('\n') {
+                            (*description).to_owned()
+                        } else {
+                            format!("{description}\n")
+                        }
+                    })
+                    .join(separator),
+            ),
+        }
+    }
+}
+
 /// Squash `sources` into `destination` and return a [`SquashedCommit`] for the
 /// resulting commit. Caller is responsible for setting the description and
 /// finishing the commit.
@@ -196,4 +196,12 @@ impl Backend for SecretBackend {
     fn gc(&self, index: &dyn Index, keep_newer: SystemTime) -> BackendResult<()> {
         self.inner.gc(index, keep_newer)
     }
+
+    fn prepare_gc(&self, index: &dyn Index, keep_newer: SystemTime) -> BackendResult<()> {
+        self.inner.prepare_gc(index, keep_newer)
+    }
+
+    fn finish_gc(&self, index: &dyn Index, keep_newer: SystemTime) -> BackendResult<()> {
+        self.inner.finish_gc(index, keep_newer)
+    }
 }
@@ -18,6 +18,7 @@ use std::path::PathBuf;
 use std::str::FromStr;
 use std::sync::Arc;
 use std::sync::Mutex;
+use std::time::Duration;
 
 use chrono::DateTime;
 use rand::prelude::*;
@@ -28,15 +29,19 @@ use crate::backend::ChangeId;
 use crate::backend::Commit;
 use crate::backend::Signature;
 use crate::backend::Timestamp;
+use crate::backend_policy::BackendPolicy;
 use crate::config::ConfigGetError;
 use crate::config::ConfigGetResultExt as _;
 use crate::config::ConfigTable;
 use crate::config::ConfigValue;
 use crate::config::StackedConfig;
 use crate::config::ToConfigNamePath;
+use crate::files::MergeNormalization;
 use crate::fmt_util::binary_prefix;
 use crate::fsmonitor::FsmonitorSettings;
+use crate::rewrite::DropEmptyPolicy;
 use crate::signing::SignBehavior;
+use crate::str_util::StringPattern;
 
 #[derive(Debug, Clone)]
 pub struct UserSettings {
@@ -55,6 +60,10 @@ struct UserSettingsData {
     operation_username: String,
     signing_behavior: SignBehavior,
     signing_key: Option<String>,
+    update_committer_timestamp: bool,
+    drop_empty: DropEmptyPolicy,
+    merge_normalization: MergeNormalization,
+    backend_policy: BackendPolicy,
 }
 
 #[derive(Debug, Clone)]
@@ -63,6 +72,19 @@ pub struct GitSettings {
     pub abandon_unreachable_commits: bool,
     pub subprocess: bool,
     pub executable_path: PathBuf,
+    /// Whether HTTP(S) remotes are fetched from and pushed to by jj itself,
+    /// without libgit2 or a Git subprocess.
+    pub native_http: bool,
+    pub detect_external_rewrites: bool,
+    /// Patterns of remote bookmarks to import. If unset, all remote bookmarks
+    /// are imported.
+    pub fetch_ref_filter: Option<Vec<StringPattern>>,
+    /// Which tags are fetched from remotes.
+    pub fetch_tags: GitFetchTags,
+    /// Pushes whose estimated pack size exceeds this many bytes are split into
+    /// multiple pushes. If unset, pushes are never split.
+    pub push_max_pack_size: Option<u64>,
+    pub http: GitHttpSettings,
 }
 
 impl GitSettings {
@@ -72,6 +94,17 @@ impl GitSettings {
             abandon_unreachable_commits: settings.get_bool("git.abandon-unreachable-commits")?,
             subprocess: settings.get_bool("git.subprocess")?,
             executable_path: settings.get("git.executable-path")?,
+            native_http: settings.get_bool("git.native-http")?,
+            detect_external_rewrites: settings.get_bool("git.detect-external-rewrites")?,
+            fetch_ref_filter: settings
+                .get_value_with("git.fetch-ref-filter", parse_string_patterns)
+                .optional()?,
+            fetch_tags: settings.get("git.fetch-tags")?,
+            push_max_pack_size: settings
+                .get_value_with("git.push-max-pack-size", TryInto::try_into)
+                .optional()?
+                .map(|HumanByteSize(size)| size),
+            http: GitHttpSettings::from_settings(settings)?,
         })
     }
 }
@@ -83,10 +116,71 @@ impl Default for GitSettings {
             abandon_unreachable_commits: true,
             subprocess: true,
             executable_path: PathBuf::from("git"),
+            native_http: true,
+            detect_external_rewrites: false,
+            fetch_ref_filter: None,
+            fetch_tags: GitFetchTags::default(),
+            push_max_pack_size: None,
+            http: GitHttpSettings::default(),
         }
     }
 }
 
+/// Which tags are fetched from Git remotes, configured by `git.fetch-tags`.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum GitFetchTags {
+    /// Don't fetch tags, like `git fetch --no-tags`.
+    None,
+    /// Fetch tags pointing to the fetched commits. This is the default of
+    /// `git fetch`.
+    #[default]
+    Reachable,
+    /// Fetch all tags, like `git fetch --tags`.
+    All,
+}
+
+/// Network settings used when talking to Git remotes over HTTP(S).
+#[derive(Debug, Clone, Default)]
+pub struct GitHttpSettings {
+    /// Proxy URL. If unset, the proxy is detected from the Git config and
+    /// environment variables.
+    pub proxy: Option<String>,
+    /// File containing the certificate authorities to trust. Only supported by
+    /// the Git subprocess. The native HTTP transport fails if this is set.
+    pub ssl_ca_info: Option<PathBuf>,
+    /// TLS client certificate. Only supported by the Git subprocess. The
+    /// native HTTP transport fails if this is set.
+    pub ssl_cert: Option<PathBuf>,
+    /// Private key of the TLS client certificate. Only supported by the Git
+    /// subprocess. The native HTTP transport fails if this is set.
+    pub ssl_key: Option<PathBuf>,
+}
+
+impl GitHttpSettings {
+    pub fn from_settings(settings: &UserSettings) -> Result<Self, ConfigGetError> {
+        Ok(GitHttpSettings {
+            proxy: settings.get("git.http.proxy").optional()?,
+            ssl_ca_info: settings.get("git.http.ssl-ca-info").optional()?,
+            ssl_cert: settings.get("git.http.ssl-cert").optional()?,
+            ssl_key: settings.get("git.http.ssl-key").optional()?,
+        })
+    }
+}
+
+fn parse_string_patterns(
+    value: ConfigValue,
+) -> Result<Vec<StringPattern>, Box<dyn std::error::Error + Send + Sync>> {
+    let items = value.as_array().ok_or("Expected an array of strings")?;
+    items
+        .iter()
+        .map(|item| {
+            let src = item.as_str().ok_or("Expected a string")?;
+            Ok(StringPattern::parse(src)?)
+        })
+        .collect()
+}
+
 /// Commit signing settings, describes how to and if to sign commits.
 #[derive(Debug, Clone)]
 pub struct SignSettings {
@@ -148,6 +242,29 @@ impl UserSettings {
         let operation_username = config.get("operation.username")?;
         let signing_behavior = config.get("signing.behavior")?;
         let signing_key = config.get("signing.key").optional()?;
+        let update_committer_timestamp = config.get("rewrite.update-committer-timestamp")?;
+        let drop_empty = config.get("rewrite.drop-empty")?;
+        let merge_normalization = MergeNormalization {
+            eol: config.get("merge.normalize-eol")?,
+            trailing_whitespace: config.get("merge.normalize-trailing-whitespace")?,
+        };
+        let backend_policy = BackendPolicy {
+            max_retries: config.get("backend.max-retries")?,
+            initial_backoff: Duration::from_millis(config.get("backend.retry-initial-backoff-ms")?),
+            max_backoff: Duration::from_millis(config.get("backend.retry-max-backoff-ms")?),
+            timeout: config
+                .get("backend.timeout-ms")
+                .optional()?
+                .map(Duration::from_millis),
+            retry_deadline: config
+                .get("backend.retry-deadline-ms")
+                .optional()?
+                .map(Duration::from_millis),
+            circuit_breaker_threshold: config.get("backend.circuit-breaker-threshold")?,
+            circuit_breaker_cooldown: Duration::from_millis(
+                config.get("backend.circuit-breaker-cooldown-ms")?,
+            ),
+        };
         let data = UserSettingsData {
             user_name,
             user_email,
@@ -157,6 +274,10 @@ impl UserSettings {
             operation_username,
             signing_behavior,
             signing_key,
+            update_committer_timestamp,
+            drop_empty,
+            merge_normalization,
+            backend_policy,
         };
         Ok(UserSettings {
             config: Arc::new(config),
@@ -200,6 +321,26 @@ impl UserSettings {
         self.data.commit_timestamp
     }
 
+    /// Whether rewritten commits should get a new committer timestamp.
+    pub fn update_committer_timestamp(&self) -> bool {
+        self.data.update_committer_timestamp
+    }
+
+    /// Policy for abandoning commits which become empty when rewritten.
+    pub fn drop_empty(&self) -> DropEmptyPolicy {
+        self.data.drop_empty
+    }
+
+    /// Differences in file contents to ignore when merging files.
+    pub fn merge_normalization(&self) -> MergeNormalization {
+        self.data.merge_normalization
+    }
+
+    /// Retries and circuit breaking for requests to the commit backend.
+    pub fn backend_policy(&self) -> &BackendPolicy {
+        &self.data.backend_policy
+    }
+
     pub fn operation_timestamp(&self) -> Option<Timestamp> {
         self.data.operation_timestamp
     }
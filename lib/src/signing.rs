@@ -15,6 +15,7 @@
 //! Generic APIs to work with cryptographic signatures created and verified by
 //! various backends.
 
+use std::fmt;
 use std::fmt::Debug;
 use std::fmt::Display;
 use std::sync::Mutex;
@@ -160,8 +161,11 @@ pub enum SignBehavior {
     Force,
 }
 
+/// A callback for the UI to display signing progress. It's called with the
+/// number of commits signed so far.
+pub type SignProgress = dyn Fn(usize) + Send + Sync;
+
 /// Wraps low-level signing backends and adds caching, similar to `Store`.
-#[derive(Debug)]
 pub struct Signer {
     /// The backend that is used for signing commits.
     /// Optional because signing might not be configured.
@@ -171,6 +175,16 @@ pub struct Signer {
     /// for ownership reasons.
     backends: Vec<Box<dyn SigningBackend>>,
     cache: Mutex<CLruCache<CommitId, Verification>>,
+    progress: Mutex<Option<(Box<SignProgress>, usize)>>,
+}
+
+impl Debug for Signer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Signer")
+            .field("main_backend", &self.main_backend)
+            .field("backends", &self.backends)
+            .finish_non_exhaustive()
+    }
 }
 
 impl Signer {
@@ -209,6 +223,7 @@ impl Signer {
             main_backend,
             backends: other_backends,
             cache: Mutex::new(CLruCache::new(COMMIT_CACHE_CAPACITY.try_into().unwrap())),
+            progress: Mutex::new(None),
         }
     }
 
@@ -220,10 +235,30 @@ impl Signer {
     /// This is just a pass-through to the main backend that unconditionally
     /// creates a signature.
     pub fn sign(&self, data: &[u8], key: Option<&str>) -> SignResult<Vec<u8>> {
-        self.main_backend
+        let signature = self
+            .main_backend
             .as_ref()
             .expect("tried to sign without checking can_sign first")
-            .sign(data, key)
+            .sign(data, key)?;
+        if let Some((progress, count)) = &mut *self.progress.lock().unwrap() {
+            *count += 1;
+            progress(*count);
+        }
+        Ok(signature)
+    }
+
+    /// Calls `progress` after each signature until
+    /// [`clear_progress()`](Self::clear_progress) is called. The count of
+    /// signed commits starts from zero.
+    pub fn set_progress(&self, progress: Box<SignProgress>) {
+        *self.progress.lock().unwrap() = Some((progress, 0));
+    }
+
+    /// Stops reporting signing progress.
+    pub fn clear_progress(&self) {
+        // Drop the callback outside of the lock.
+        let progress = self.progress.lock().unwrap().take();
+        drop(progress);
     }
 
     /// Looks for backend that can verify the signature and returns the result
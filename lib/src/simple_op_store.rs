@@ -33,6 +33,7 @@ use tempfile::NamedTempFile;
 use thiserror::Error;
 
 use crate::backend::BackendInitError;
+use crate::backend::ChangeId;
 use crate::backend::CommitId;
 use crate::backend::MillisSinceEpoch;
 use crate::backend::Timestamp;
@@ -46,6 +47,7 @@ use crate::object_id::HexPrefix;
 use crate::object_id::ObjectId;
 use crate::object_id::PrefixResolution;
 use crate::op_store;
+use crate::op_store::BookmarkMetadata;
 use crate::op_store::OpStore;
 use crate::op_store::OpStoreError;
 use crate::op_store::OpStoreResult;
@@ -504,6 +506,37 @@ fn view_to_proto(view: &View) -> crate::protos::op_store::View {
 
     proto.git_head = ref_target_to_proto(&view.git_head);
 
+    for (name, change_ids) in &view.topics {
+        proto.topics.push(crate::protos::op_store::Topic {
+            name: name.clone(),
+            change_ids: change_ids.iter().map(|id| id.to_bytes()).collect(),
+        });
+    }
+
+    for (name, metadata) in &view.bookmark_metadata {
+        proto
+            .bookmark_metadata
+            .push(crate::protos::op_store::BookmarkMetadata {
+                name: name.clone(),
+                created: metadata.created.as_ref().map(timestamp_to_proto),
+                moved: Some(timestamp_to_proto(&metadata.moved)),
+                moved_by: metadata.moved_by.clone(),
+            });
+    }
+
+    for (name, commit_id) in &view.shelves {
+        proto.shelves.insert(name.clone(), commit_id.to_bytes());
+    }
+
+    for (change_id, values) in &view.change_metadata {
+        proto
+            .change_metadata
+            .push(crate::protos::op_store::ChangeMetadata {
+                change_id: change_id.to_bytes(),
+                values: values.clone().into_iter().collect(),
+            });
+    }
+
     proto
 }
 
@@ -551,6 +584,33 @@ fn view_from_proto(proto: crate::protos::op_store::View) -> View {
         view.git_head = RefTarget::normal(CommitId::new(proto.git_head_legacy));
     }
 
+    for topic in proto.topics {
+        let change_ids = topic.change_ids.into_iter().map(ChangeId::new).collect();
+        view.topics.insert(topic.name, change_ids);
+    }
+
+    for metadata in proto.bookmark_metadata {
+        view.bookmark_metadata.insert(
+            metadata.name,
+            BookmarkMetadata {
+                created: metadata.created.map(timestamp_from_proto),
+                moved: timestamp_from_proto(metadata.moved.unwrap_or_default()),
+                moved_by: metadata.moved_by,
+            },
+        );
+    }
+
+    for (name, commit_id) in proto.shelves {
+        view.shelves.insert(name, CommitId::new(commit_id));
+    }
+
+    for metadata in proto.change_metadata {
+        view.change_metadata.insert(
+            ChangeId::new(metadata.change_id),
+            metadata.values.into_iter().collect(),
+        );
+    }
+
     view
 }
 
@@ -722,6 +782,7 @@ mod tests {
     use insta::assert_snapshot;
     use itertools::Itertools as _;
     use maplit::btreemap;
+    use maplit::btreeset;
     use maplit::hashmap;
     use maplit::hashset;
 
@@ -775,6 +836,33 @@ mod tests {
                 WorkspaceId::default() => default_wc_commit_id,
                 WorkspaceId::new("test".to_string()) => test_wc_commit_id,
             },
+            topics: btreemap! {
+                "feature".to_string() => btreeset! {
+                    ChangeId::from_hex("eee111"),
+                    ChangeId::from_hex("eee222"),
+                },
+            },
+            bookmark_metadata: btreemap! {
+                "main".to_string() => BookmarkMetadata {
+                    created: Some(Timestamp {
+                        timestamp: MillisSinceEpoch(123456789),
+                        tz_offset: 3600,
+                    }),
+                    moved: Timestamp {
+                        timestamp: MillisSinceEpoch(123456800),
+                        tz_offset: 3600,
+                    },
+                    moved_by: "someone@some.host.example.com".to_string(),
+                },
+            },
+            shelves: btreemap! {
+                "1".to_string() => CommitId::from_hex("ccc444"),
+            },
+            change_metadata: btreemap! {
+                ChangeId::from_hex("eee111") => btreemap! {
+                    "review-url".to_string() => "https://example.com/1".to_string(),
+                },
+            },
         }
     }
 
@@ -816,6 +904,19 @@ mod tests {
         // Test exact output so we detect regressions in compatibility
         assert_snapshot!(
             ViewId::new(blake2b_hash(&create_view()).to_vec()).hex(),
+            @"93d8ac35ab23d19c34c0ad11e2a5256440592cf1c859dd246c168b710fa7c2a3c18581c8140d109f1be06ea6f41d83dec0a5f43c07bf2f10cf345de181a00cde"
+        );
+        // Views without topics, bookmark metadata, shelves, or change metadata
+        // should hash the same as before these fields were added
+        let view = View {
+            topics: BTreeMap::new(),
+            bookmark_metadata: BTreeMap::new(),
+            shelves: BTreeMap::new(),
+            change_metadata: BTreeMap::new(),
+            ..create_view()
+        };
+        assert_snapshot!(
+            ViewId::new(blake2b_hash(&view).to_vec()).hex(),
             @"f426676b3a2f7c6b9ec8677cb05ed249d0d244ab7e86a7c51117e2d8a4829db65e55970c761231e2107d303bf3d33a1f2afdd4ed2181f223e99753674b20a35e"
         );
     }
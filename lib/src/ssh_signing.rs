@@ -22,6 +22,7 @@ use std::path::PathBuf;
 use std::process::Command;
 use std::process::ExitStatus;
 use std::process::Stdio;
+use std::sync::Mutex;
 
 use either::Either;
 use thiserror::Error;
@@ -38,6 +39,8 @@ use crate::signing::Verification;
 pub struct SshBackend {
     program: OsString,
     allowed_signers: Option<OsString>,
+    // Key file of the last signature, reused when rewriting many commits.
+    key_file: Mutex<Option<(String, Either<PathBuf, tempfile::TempPath>)>>,
 }
 
 #[derive(Debug, Error)]
@@ -116,6 +119,7 @@ impl SshBackend {
         Self {
             program,
             allowed_signers,
+            key_file: Mutex::new(None),
         }
     }
 
@@ -196,10 +200,17 @@ impl SigningBackend for SshBackend {
         //
         // As it expects a file and we might have an inlined public key instead, we need
         // to ensure it is written to a file first.
-        let pub_key_path = ensure_key_as_file(key)?;
+        let mut key_file = self.key_file.lock().unwrap();
+        if key_file
+            .as_ref()
+            .is_none_or(|(cached_key, _)| cached_key != key)
+        {
+            *key_file = Some((key.to_owned(), ensure_key_as_file(key)?));
+        }
+        let (_, pub_key_path) = key_file.as_ref().unwrap();
         let mut command = self.create_command();
 
-        let path = match &pub_key_path {
+        let path = match pub_key_path {
             either::Left(path) => path.as_os_str(),
             either::Right(path) => path.as_os_str(),
         };
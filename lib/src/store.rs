@@ -17,14 +17,23 @@
 use std::any::Any;
 use std::fmt::Debug;
 use std::fmt::Formatter;
+use std::fs;
+use std::io;
 use std::io::Read;
+use std::io::Write as _;
+use std::path::Path;
+use std::path::PathBuf;
 use std::sync::Arc;
 use std::sync::Mutex;
 use std::time::SystemTime;
 
+use blake2::Blake2b512;
+use blake2::Digest as _;
 use clru::CLruCache;
 use futures::stream::BoxStream;
+use itertools::Itertools as _;
 use pollster::FutureExt;
+use tempfile::NamedTempFile;
 
 use crate::backend;
 use crate::backend::Backend;
@@ -39,10 +48,14 @@ use crate::backend::SigningFn;
 use crate::backend::SymlinkId;
 use crate::backend::TreeId;
 use crate::commit::Commit;
+use crate::content_hash::ContentHash as _;
+use crate::file_util::persist_content_addressed_temp_file;
+use crate::files::MergeNormalization;
 use crate::index::Index;
 use crate::merge::Merge;
 use crate::merge::MergedTreeValue;
 use crate::merged_tree::MergedTree;
+use crate::object_id::ObjectId as _;
 use crate::repo_path::RepoPath;
 use crate::repo_path::RepoPathBuf;
 use crate::signing::Signer;
@@ -53,14 +66,43 @@ use crate::tree_builder::TreeBuilder;
 // commits.
 pub(crate) const COMMIT_CACHE_CAPACITY: usize = 100;
 const TREE_CACHE_CAPACITY: usize = 1000;
+// Merging conflicted trees is expensive, but the same merges tend to be
+// repeated, e.g. for the parent trees of a conflicted stack. The results are
+// also saved to disk to be reused by later commands.
+const TREE_MERGE_CACHE_CAPACITY: usize = 100;
 
 /// Wraps the low-level backend and makes it return more convenient types. Also
 /// adds caching.
 pub struct Store {
     backend: Box<dyn Backend>,
     signer: Signer,
+    merge_normalization: MergeNormalization,
     commit_cache: Mutex<CLruCache<CommitId, Arc<backend::Commit>>>,
     tree_cache: Mutex<CLruCache<(RepoPathBuf, TreeId), Arc<backend::Tree>>>,
+    tree_merge_cache: Mutex<CLruCache<Merge<TreeId>, Merge<TreeId>>>,
+    tree_merge_cache_dir: Option<PathBuf>,
+}
+
+/// Reads the tree ids saved by `write_tree_merge_file()`. Returns `None` if
+/// the file is malformed.
+fn read_tree_merge_file(path: &Path) -> io::Result<Option<Merge<TreeId>>> {
+    let content = fs::read_to_string(path)?;
+    let ids: Vec<_> = match content.lines().map(TreeId::try_from_hex).try_collect() {
+        Ok(ids) => ids,
+        Err(_) => return Ok(None),
+    };
+    Ok((ids.len() % 2 == 1).then(|| Merge::from_vec(ids)))
+}
+
+fn write_tree_merge_file(path: &Path, ids: &Merge<TreeId>) -> io::Result<()> {
+    let dir = path.parent().unwrap();
+    fs::create_dir_all(dir)?;
+    let mut temp_file = NamedTempFile::new_in(dir)?;
+    for id in ids.iter() {
+        writeln!(temp_file, "{}", id.hex())?;
+    }
+    persist_content_addressed_temp_file(temp_file, path)?;
+    Ok(())
 }
 
 impl Debug for Store {
@@ -72,12 +114,24 @@ impl Debug for Store {
 }
 
 impl Store {
-    pub fn new(backend: Box<dyn Backend>, signer: Signer) -> Arc<Self> {
+    /// Creates a store for the `backend`. If `tree_merge_cache_dir` is set,
+    /// the results of resolving conflicted trees are saved in that directory.
+    pub fn new(
+        backend: Box<dyn Backend>,
+        signer: Signer,
+        merge_normalization: MergeNormalization,
+        tree_merge_cache_dir: Option<PathBuf>,
+    ) -> Arc<Self> {
         Arc::new(Store {
             backend,
             signer,
+            merge_normalization,
             commit_cache: Mutex::new(CLruCache::new(COMMIT_CACHE_CAPACITY.try_into().unwrap())),
             tree_cache: Mutex::new(CLruCache::new(TREE_CACHE_CAPACITY.try_into().unwrap())),
+            tree_merge_cache: Mutex::new(CLruCache::new(
+                TREE_MERGE_CACHE_CAPACITY.try_into().unwrap(),
+            )),
+            tree_merge_cache_dir,
         })
     }
 
@@ -89,6 +143,11 @@ impl Store {
         &self.signer
     }
 
+    /// Differences in file contents to ignore when merging files.
+    pub fn merge_normalization(&self) -> MergeNormalization {
+        self.merge_normalization
+    }
+
     pub fn get_copy_records(
         &self,
         paths: Option<&[RepoPathBuf]>,
@@ -130,6 +189,12 @@ impl Store {
         self.get_commit(self.backend.root_commit_id()).unwrap()
     }
 
+    /// Forgets the cached commits so they are read from the backend again,
+    /// e.g. because the backend reports different parents for them now.
+    pub fn clear_commit_cache(&self) {
+        self.commit_cache.lock().unwrap().clear();
+    }
+
     pub fn get_commit(self: &Arc<Self>, id: &CommitId) -> BackendResult<Commit> {
         self.get_commit_async(id).block_on()
     }
@@ -202,6 +267,54 @@ impl Store {
         Ok(data)
     }
 
+    /// Looks up the result of a previous `MergedTree::resolve()` of the root
+    /// trees `ids`, possibly by an earlier command.
+    pub fn get_resolved_tree_merge(&self, ids: &Merge<TreeId>) -> Option<Merge<TreeId>> {
+        if let Some(resolved_ids) = self.tree_merge_cache.lock().unwrap().get(ids).cloned() {
+            return Some(resolved_ids);
+        }
+        let path = self.tree_merge_cache_path(ids)?;
+        let resolved_ids = match read_tree_merge_file(&path) {
+            Ok(resolved_ids) => resolved_ids?,
+            Err(err) => {
+                if err.kind() != io::ErrorKind::NotFound {
+                    tracing::warn!(?err, ?path, "failed to read cached tree merge");
+                }
+                return None;
+            }
+        };
+        self.tree_merge_cache
+            .lock()
+            .unwrap()
+            .put(ids.clone(), resolved_ids.clone());
+        Some(resolved_ids)
+    }
+
+    /// Records the result of `MergedTree::resolve()` of the root trees `ids`.
+    pub fn set_resolved_tree_merge(&self, ids: Merge<TreeId>, resolved_ids: Merge<TreeId>) {
+        if let Some(path) = self.tree_merge_cache_path(&ids) {
+            // The merge can be redone, so failing to save it isn't an error.
+            if let Err(err) = write_tree_merge_file(&path, &resolved_ids) {
+                tracing::warn!(?err, ?path, "failed to save tree merge");
+            }
+        }
+        self.tree_merge_cache.lock().unwrap().put(ids, resolved_ids);
+    }
+
+    fn tree_merge_cache_path(&self, ids: &Merge<TreeId>) -> Option<PathBuf> {
+        let dir = self.tree_merge_cache_dir.as_ref()?;
+        // The result depends on the normalization of the file contents.
+        let MergeNormalization {
+            eol,
+            trailing_whitespace,
+        } = self.merge_normalization;
+        let mut hasher = Blake2b512::new();
+        eol.hash(&mut hasher);
+        trailing_whitespace.hash(&mut hasher);
+        ids.hash(&mut hasher);
+        Some(dir.join(hex::encode(hasher.finalize())))
+    }
+
     pub fn get_root_tree(self: &Arc<Self>, id: &MergedTreeId) -> BackendResult<MergedTree> {
         match &id {
             MergedTreeId::Legacy(id) => {
@@ -242,6 +355,27 @@ impl Store {
         self.backend.read_file(path, id).await
     }
 
+    /// Returns whether the file object exists without reading its content.
+    pub fn contains_file(&self, path: &RepoPath, id: &FileId) -> BackendResult<bool> {
+        self.backend.contains_file(path, id).block_on()
+    }
+
+    /// Returns the size of the file content in bytes, without reading the
+    /// content if the backend supports it.
+    pub fn file_size(&self, path: &RepoPath, id: &FileId) -> BackendResult<u64> {
+        self.file_size_async(path, id).block_on()
+    }
+
+    pub async fn file_size_async(&self, path: &RepoPath, id: &FileId) -> BackendResult<u64> {
+        self.backend.file_size(path, id).await
+    }
+
+    /// Makes the files available for reading at once. See
+    /// [`Backend::prefetch_files()`].
+    pub async fn prefetch_files(&self, ids: &[FileId]) -> BackendResult<()> {
+        self.backend.prefetch_files(ids).await
+    }
+
     pub async fn write_file(
         &self,
         path: &RepoPath,
@@ -291,4 +425,12 @@ impl Store {
     pub fn gc(&self, index: &dyn Index, keep_newer: SystemTime) -> BackendResult<()> {
         self.backend.gc(index, keep_newer)
     }
+
+    pub fn prepare_gc(&self, index: &dyn Index, keep_newer: SystemTime) -> BackendResult<()> {
+        self.backend.prepare_gc(index, keep_newer)
+    }
+
+    pub fn finish_gc(&self, index: &dyn Index, keep_newer: SystemTime) -> BackendResult<()> {
+        self.backend.finish_gc(index, keep_newer)
+    }
 }
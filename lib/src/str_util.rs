@@ -18,6 +18,7 @@ use std::borrow::Borrow;
 use std::borrow::Cow;
 use std::collections::BTreeMap;
 use std::fmt;
+use std::ops::Bound;
 
 use either::Either;
 use thiserror::Error;
@@ -58,7 +59,8 @@ pub enum StringPattern {
     GlobI(glob::Pattern),
     /// Matches substrings with a regular expression.
     Regex(regex::Regex),
-    // TODO: Should we add RegexI and "regex-i" prefix?
+    /// Matches substrings with a case‐insensitive regular expression.
+    RegexI(regex::Regex),
 }
 
 impl StringPattern {
@@ -69,10 +71,10 @@ impl StringPattern {
 
     /// Parses the given string as a [`StringPattern`]. Everything before the
     /// first ":" is considered the string's prefix. If the prefix is
-    /// "exact[-i]:", "glob[-i]:", or "substring[-i]:", a pattern of the
-    /// specified kind is returned. Returns an error if the string has an
-    /// unrecognized prefix. Otherwise, a `StringPattern::Exact` is
-    /// returned.
+    /// "exact[-i]:", "glob[-i]:", "regex[-i]:", or "substring[-i]:", a
+    /// pattern of the specified kind is returned. Returns an error if the
+    /// string has an unrecognized prefix. Otherwise, a `StringPattern::Exact`
+    /// is returned.
     pub fn parse(src: &str) -> Result<StringPattern, StringPatternParseError> {
         if let Some((kind, pat)) = src.split_once(':') {
             StringPattern::from_str_kind(pat, kind)
@@ -120,6 +122,15 @@ impl StringPattern {
         Ok(StringPattern::Regex(pattern))
     }
 
+    /// Parses the given string as a case‐insensitive regular expression.
+    pub fn regex_i(src: &str) -> Result<Self, StringPatternParseError> {
+        let pattern = regex::RegexBuilder::new(src)
+            .case_insensitive(true)
+            .build()
+            .map_err(StringPatternParseError::Regex)?;
+        Ok(StringPattern::RegexI(pattern))
+    }
+
     /// Parses the given string as a pattern of the specified `kind`.
     pub fn from_str_kind(src: &str, kind: &str) -> Result<Self, StringPatternParseError> {
         match kind {
@@ -130,6 +141,7 @@ impl StringPattern {
             "glob" => StringPattern::glob(src),
             "glob-i" => StringPattern::glob_i(src),
             "regex" => StringPattern::regex(src),
+            "regex-i" => StringPattern::regex_i(src),
             _ => Err(StringPatternParseError::InvalidKind(kind.to_owned())),
         }
     }
@@ -152,6 +164,27 @@ impl StringPattern {
         }
     }
 
+    /// Returns a literal string all matching input strings should start with.
+    ///
+    /// This can be used to optimize map lookup by key range. The prefix may
+    /// be empty.
+    pub fn literal_prefix(&self) -> &str {
+        match self {
+            StringPattern::Exact(literal) => literal,
+            StringPattern::Glob(pattern) => {
+                let src = pattern.as_str();
+                let end = src.find(['*', '?', '[', '\\']).unwrap_or(src.len());
+                &src[..end]
+            }
+            StringPattern::ExactI(_)
+            | StringPattern::Substring(_)
+            | StringPattern::SubstringI(_)
+            | StringPattern::GlobI(_)
+            | StringPattern::Regex(_)
+            | StringPattern::RegexI(_) => "",
+        }
+    }
+
     /// Returns the original string of this pattern.
     pub fn as_str(&self) -> &str {
         match self {
@@ -162,6 +195,7 @@ impl StringPattern {
             StringPattern::Glob(pattern) => pattern.as_str(),
             StringPattern::GlobI(pattern) => pattern.as_str(),
             StringPattern::Regex(pattern) => pattern.as_str(),
+            StringPattern::RegexI(pattern) => pattern.as_str(),
         }
     }
 
@@ -185,6 +219,7 @@ impl StringPattern {
             StringPattern::SubstringI(_) => None,
             StringPattern::GlobI(_) => None,
             StringPattern::Regex(_) => None,
+            StringPattern::RegexI(_) => None,
         }
     }
 
@@ -224,6 +259,7 @@ impl StringPattern {
                 },
             ),
             StringPattern::Regex(pattern) => pattern.is_match(haystack),
+            StringPattern::RegexI(pattern) => pattern.is_match(haystack),
         }
     }
 
@@ -235,11 +271,25 @@ impl StringPattern {
         if let Some(key) = self.as_exact() {
             Either::Left(map.get_key_value(key).into_iter())
         } else {
-            Either::Right(map.iter().filter(|&(key, _)| self.matches(key.borrow())))
+            Either::Right(
+                btree_map_with_prefix(map, self.literal_prefix())
+                    .filter(|&(key, _)| self.matches(key.borrow())),
+            )
         }
     }
 }
 
+/// Iterates entries of the given `map` whose keys start with the `prefix`.
+///
+/// Since the map is sorted, only the matching range is scanned.
+pub fn btree_map_with_prefix<'a, 'b, K: Borrow<str> + Ord, V>(
+    map: &'a BTreeMap<K, V>,
+    prefix: &'b str,
+) -> impl Iterator<Item = (&'a K, &'a V)> + use<'a, 'b, K, V> {
+    map.range::<str, _>((Bound::Included(prefix), Bound::Unbounded))
+        .take_while(move |&(key, _)| key.borrow().starts_with(prefix))
+}
+
 impl fmt::Display for StringPattern {
     /// Shows the original string of this pattern.
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
@@ -272,6 +322,63 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_string_pattern_literal_prefix() {
+        assert_eq!(StringPattern::exact("a*").literal_prefix(), "a*");
+        assert_eq!(StringPattern::glob("*").unwrap().literal_prefix(), "");
+        assert_eq!(
+            StringPattern::glob("foo/*").unwrap().literal_prefix(),
+            "foo/"
+        );
+        assert_eq!(
+            StringPattern::glob("foo/b[ao]r").unwrap().literal_prefix(),
+            "foo/b"
+        );
+        assert_eq!(StringPattern::glob("foo").unwrap().literal_prefix(), "foo");
+        assert_eq!(StringPattern::substring("foo").literal_prefix(), "");
+        assert_eq!(StringPattern::regex("^foo").unwrap().literal_prefix(), "");
+    }
+
+    #[test]
+    fn test_filter_btree_map() {
+        let map = BTreeMap::from([
+            ("foo".to_owned(), 0),
+            ("foo-bar".to_owned(), 1),
+            ("foo/bar".to_owned(), 2),
+            ("foo/baz".to_owned(), 3),
+            ("fooo".to_owned(), 4),
+            ("qux".to_owned(), 5),
+        ]);
+        let filter = |pattern: StringPattern| {
+            pattern
+                .filter_btree_map(&map)
+                .map(|(_, &v)| v)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(filter(StringPattern::exact("foo")), [0]);
+        assert_eq!(filter(StringPattern::glob("foo/*").unwrap()), [2, 3]);
+        assert_eq!(
+            filter(StringPattern::glob("foo*").unwrap()),
+            [0, 1, 2, 3, 4]
+        );
+        assert_eq!(filter(StringPattern::glob("*ba?").unwrap()), [1, 2, 3]);
+        assert_eq!(filter(StringPattern::substring("o-")), [1]);
+        let values = btree_map_with_prefix(&map, "foo/")
+            .map(|(_, &v)| v)
+            .collect::<Vec<_>>();
+        assert_eq!(values, [2, 3]);
+    }
+
+    #[test]
+    fn test_string_pattern_matches_case_insensitive() {
+        assert!(StringPattern::glob_i("*.MD").unwrap().matches("README.md"));
+        assert!(!StringPattern::glob("*.MD").unwrap().matches("README.md"));
+        assert!(StringPattern::regex_i("^fo+$").unwrap().matches("FOO"));
+        assert!(!StringPattern::regex("^fo+$").unwrap().matches("FOO"));
+        // Only the matched substring has to match.
+        assert!(StringPattern::regex_i("BAR").unwrap().matches("foo-bar"));
+    }
+
     #[test]
     fn test_parse() {
         // Parse specific pattern kinds.
@@ -315,6 +422,14 @@ mod tests {
             StringPattern::from_str_kind("foo", "regex"),
             Ok(StringPattern::Regex(p)) if p.as_str() == "foo"
         );
+        assert_matches!(
+            StringPattern::parse("regex-i:foo"),
+            Ok(StringPattern::RegexI(p)) if p.as_str() == "foo"
+        );
+        assert_matches!(
+            StringPattern::from_str_kind("foo", "regex-i"),
+            Ok(StringPattern::RegexI(p)) if p.as_str() == "foo"
+        );
 
         // Parse a pattern that contains a : itself.
         assert_matches!(
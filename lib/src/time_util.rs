@@ -72,6 +72,22 @@ pub enum DatePatternParseError {
     ParseError(#[from] DateError),
 }
 
+/// Parses an absolute or relative date into a [`Timestamp`].
+///
+/// RFC 3339 dates (e.g. `2024-01-02T03:04:05+06:00`) keep their own time zone
+/// offset. Other dates (e.g. `yesterday` or `2 days ago`) are interpreted
+/// relative to `now`, in its time zone.
+pub fn parse_timestamp<Tz: TimeZone>(s: &str, now: DateTime<Tz>) -> Result<Timestamp, DateError>
+where
+    Tz::Offset: Copy,
+{
+    if let Ok(datetime) = DateTime::parse_from_rfc3339(s) {
+        return Ok(Timestamp::from_datetime(datetime));
+    }
+    let datetime = parse_date_string(s, now, Dialect::Us)?;
+    Ok(Timestamp::from_datetime(datetime.fixed_offset()))
+}
+
 /// Represents an range of dates that may be matched against.
 #[derive(Copy, Clone, Debug, Eq, PartialEq)]
 pub enum DatePattern {
@@ -189,4 +205,34 @@ mod tests {
         test_equal(now, "yesterday 10am", "2023-12-31T18:00:00Z");
         test_equal(now, "yesterday 10:30", "2023-12-31T18:30:00Z");
     }
+
+    #[test]
+    fn test_parse_timestamp() {
+        let now = DateTime::parse_from_rfc3339("2024-01-01T08:00:00-08:00").unwrap();
+        let parse = |s| parse_timestamp(s, now).unwrap();
+        // The offset of an RFC 3339 date is preserved
+        assert_eq!(
+            parse("2023-03-25T01:02:03+05:30"),
+            Timestamp {
+                timestamp: MillisSinceEpoch(1679686323000),
+                tz_offset: 330,
+            }
+        );
+        // Other dates use the offset of `now`
+        assert_eq!(
+            parse("2023-03-25 01:02:03"),
+            Timestamp {
+                timestamp: MillisSinceEpoch(1679734923000),
+                tz_offset: -480,
+            }
+        );
+        assert_eq!(
+            parse("2 hours ago"),
+            Timestamp {
+                timestamp: MillisSinceEpoch(1704117600000),
+                tz_offset: -480,
+            }
+        );
+        assert!(parse_timestamp("not a date", now).is_err());
+    }
 }
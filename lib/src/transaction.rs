@@ -114,12 +114,17 @@ impl Transaction {
     /// That means that a repo can be loaded at the operation, but the
     /// operation will not be seen when loading the repo at head.
     pub fn write(mut self, description: impl Into<String>) -> UnpublishedOperation {
-        let mut_repo = self.mut_repo;
+        let mut mut_repo = self.mut_repo;
         // TODO: Should we instead just do the rebasing here if necessary?
         assert!(
             !mut_repo.has_rewrites(),
             "BUG: Descendants have not been rebased after the last rewrites."
         );
+        let moved_by = format!(
+            "{}@{}",
+            self.op_metadata.username, self.op_metadata.hostname
+        );
+        mut_repo.record_bookmark_metadata(self.op_metadata.start_time, &moved_by);
         let base_repo = mut_repo.base_repo().clone();
         let (mut_index, view) = mut_repo.consume();
 
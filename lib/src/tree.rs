@@ -479,6 +479,20 @@ pub async fn try_resolve_file_conflict(
                 .await?;
             Ok(Some(TreeValue::File { id, executable }))
         }
-        MergeResult::Conflict(_) => Ok(None),
+        MergeResult::Conflict(_) => {
+            let normalization = store.merge_normalization();
+            let Some(resolution) = files::resolve_normalized(&contents, normalization) else {
+                return Ok(None);
+            };
+            tracing::debug!(
+                ?filename,
+                applied = ?resolution.applied,
+                "resolved conflict by normalization"
+            );
+            let id = store
+                .write_file(filename, &mut &*resolution.content)
+                .await?;
+            Ok(Some(TreeValue::File { id, executable }))
+        }
     }
 }
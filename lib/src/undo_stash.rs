@@ -0,0 +1,287 @@
+// Copyright 2025 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Stash of locally-modified working-copy files replaced by checkout.
+//!
+//! Each checkout that overwrites or removes files with unsnapshotted changes
+//! creates an entry directory named after the time of the checkout. The
+//! replaced files are copied into the entry at their repo-relative paths.
+
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::path::PathBuf;
+use std::time::Duration;
+use std::time::SystemTime;
+
+use crate::backend::MillisSinceEpoch;
+use crate::file_util::IoResultExt as _;
+use crate::file_util::PathError;
+use crate::repo_path::RepoPath;
+use crate::repo_path::RepoPathBuf;
+
+/// Directory containing stashed copies of replaced working-copy files.
+#[derive(Clone, Debug)]
+pub struct UndoStash {
+    dir: PathBuf,
+}
+
+impl UndoStash {
+    /// Creates stash backed by the given directory. The directory is created
+    /// lazily when the first file is stashed.
+    pub fn new(dir: PathBuf) -> Self {
+        UndoStash { dir }
+    }
+
+    /// Returns the stash entries, newest first.
+    pub fn entries(&self) -> Result<Vec<UndoStashEntry>, PathError> {
+        let dir_entries = match self.dir.read_dir() {
+            Ok(dir_entries) => dir_entries,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(vec![]),
+            Err(err) => return Err(err).context(&self.dir),
+        };
+        let mut entries = vec![];
+        for dir_entry in dir_entries {
+            let dir_entry = dir_entry.context(&self.dir)?;
+            let Some(id) = dir_entry.file_name().to_str().map(str::to_owned) else {
+                continue;
+            };
+            let Some(time) = parse_entry_time(&id) else {
+                continue;
+            };
+            entries.push(UndoStashEntry {
+                id,
+                time,
+                dir: dir_entry.path(),
+            });
+        }
+        entries.sort_unstable_by(|a, b| (b.time, &b.id).cmp(&(a.time, &a.id)));
+        Ok(entries)
+    }
+
+    /// Looks up entry by id.
+    pub fn get_entry(&self, id: &str) -> Result<Option<UndoStashEntry>, PathError> {
+        let entries = self.entries()?;
+        Ok(entries.into_iter().find(|entry| entry.id == id))
+    }
+
+    /// Deletes entries which were created more than `retention` before `now`.
+    pub fn prune(&self, retention: Duration, now: SystemTime) -> Result<(), PathError> {
+        let Some(cutoff) = now.checked_sub(retention) else {
+            return Ok(());
+        };
+        let cutoff = millis_since_epoch(cutoff);
+        for entry in self.entries()? {
+            if entry.time < cutoff {
+                fs::remove_dir_all(&entry.dir).context(&entry.dir)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Starts new entry. Nothing is written until a file is added.
+    pub(crate) fn start_entry(&self, now: SystemTime) -> UndoStashEntryWriter {
+        let time = millis_since_epoch(now);
+        UndoStashEntryWriter {
+            stash_dir: self.dir.clone(),
+            time,
+            entry_dir: None,
+        }
+    }
+}
+
+/// Set of files stashed by one checkout.
+#[derive(Clone, Debug)]
+pub struct UndoStashEntry {
+    id: String,
+    time: MillisSinceEpoch,
+    dir: PathBuf,
+}
+
+impl UndoStashEntry {
+    /// Identifier of the entry.
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    /// Time when the files were stashed.
+    pub fn time(&self) -> MillisSinceEpoch {
+        self.time
+    }
+
+    /// Returns the paths of the stashed files in sorted order.
+    pub fn files(&self) -> Result<Vec<RepoPathBuf>, PathError> {
+        let mut files = vec![];
+        collect_files(&self.dir, &self.dir, &mut files)?;
+        files.sort_unstable();
+        Ok(files)
+    }
+
+    /// Copies the stashed files back to the working copy, overwriting the
+    /// existing files. Returns the paths of the restored files.
+    pub fn restore(&self, working_copy_path: &Path) -> Result<Vec<RepoPathBuf>, PathError> {
+        let files = self.files()?;
+        for path in &files {
+            let src_path = path.to_fs_path_unchecked(&self.dir);
+            let dest_path = path
+                .to_fs_path(working_copy_path)
+                .map_err(|err| PathError {
+                    path: src_path.clone(),
+                    error: io::Error::new(io::ErrorKind::InvalidInput, err),
+                })?;
+            let parent_path = dest_path.parent().expect("stashed path shouldn't be root");
+            fs::create_dir_all(parent_path).context(parent_path)?;
+            // Replace symlink or read-only file rather than writing through it.
+            match fs::remove_file(&dest_path) {
+                Ok(()) => {}
+                Err(err) if err.kind() == io::ErrorKind::NotFound => {}
+                Err(err) => return Err(err).context(&dest_path),
+            }
+            fs::copy(&src_path, &dest_path).context(&dest_path)?;
+        }
+        Ok(files)
+    }
+}
+
+/// Lazily-created entry to which replaced files are copied.
+#[derive(Debug)]
+pub(crate) struct UndoStashEntryWriter {
+    stash_dir: PathBuf,
+    time: MillisSinceEpoch,
+    entry_dir: Option<PathBuf>,
+}
+
+impl UndoStashEntryWriter {
+    /// Copies the regular file at `disk_path` to the entry.
+    pub fn add_file(&mut self, path: &RepoPath, disk_path: &Path) -> Result<(), PathError> {
+        let entry_dir = match &self.entry_dir {
+            Some(entry_dir) => entry_dir,
+            None => self.entry_dir.insert(self.create_entry_dir()?),
+        };
+        let stashed_path = path.to_fs_path_unchecked(entry_dir);
+        let parent_path = stashed_path
+            .parent()
+            .expect("stashed path shouldn't be root");
+        fs::create_dir_all(parent_path).context(parent_path)?;
+        fs::copy(disk_path, &stashed_path).context(disk_path)?;
+        Ok(())
+    }
+
+    fn create_entry_dir(&self) -> Result<PathBuf, PathError> {
+        fs::create_dir_all(&self.stash_dir).context(&self.stash_dir)?;
+        for seq in 0.. {
+            let id = if seq == 0 {
+                format!("{:013}", self.time.0)
+            } else {
+                format!("{:013}-{seq}", self.time.0)
+            };
+            let entry_dir = self.stash_dir.join(id);
+            match fs::create_dir(&entry_dir) {
+                Ok(()) => return Ok(entry_dir),
+                Err(err) if err.kind() == io::ErrorKind::AlreadyExists => continue,
+                Err(err) => return Err(err).context(&entry_dir),
+            }
+        }
+        unreachable!()
+    }
+}
+
+fn parse_entry_time(id: &str) -> Option<MillisSinceEpoch> {
+    let (millis, _seq) = id.split_once('-').unwrap_or((id, ""));
+    millis.parse().ok().map(MillisSinceEpoch)
+}
+
+fn millis_since_epoch(time: SystemTime) -> MillisSinceEpoch {
+    let millis = time
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map_or(0, |duration| duration.as_millis());
+    MillisSinceEpoch(millis.try_into().unwrap_or(i64::MAX))
+}
+
+fn collect_files(
+    base_dir: &Path,
+    dir: &Path,
+    files: &mut Vec<RepoPathBuf>,
+) -> Result<(), PathError> {
+    for dir_entry in dir.read_dir().context(dir)? {
+        let dir_entry = dir_entry.context(dir)?;
+        let path = dir_entry.path();
+        if dir_entry.file_type().context(&path)?.is_dir() {
+            collect_files(base_dir, &path, files)?;
+        } else {
+            let relative_path = path.strip_prefix(base_dir).unwrap();
+            let repo_path =
+                RepoPathBuf::from_relative_path(relative_path).map_err(|err| PathError {
+                    path: path.clone(),
+                    error: io::Error::new(io::ErrorKind::InvalidData, err),
+                })?;
+            files.push(repo_path);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::new_temp_dir;
+
+    fn repo_path(value: &str) -> &RepoPath {
+        RepoPath::from_internal_string(value)
+    }
+
+    #[test]
+    fn test_stash_and_restore() {
+        let temp_dir = new_temp_dir();
+        let stash = UndoStash::new(temp_dir.path().join("stash"));
+        let wc_path = temp_dir.path().join("wc");
+        fs::create_dir_all(wc_path.join("dir")).unwrap();
+        fs::write(wc_path.join("a"), "a\n").unwrap();
+        fs::write(wc_path.join("dir/b"), "b\n").unwrap();
+        assert!(stash.entries().unwrap().is_empty());
+
+        // No entry is created unless a file is added
+        let now = SystemTime::UNIX_EPOCH + Duration::from_secs(1000);
+        stash.start_entry(now);
+        assert!(stash.entries().unwrap().is_empty());
+
+        let mut writer = stash.start_entry(now);
+        writer.add_file(repo_path("a"), &wc_path.join("a")).unwrap();
+        writer
+            .add_file(repo_path("dir/b"), &wc_path.join("dir/b"))
+            .unwrap();
+        let mut writer = stash.start_entry(now);
+        writer.add_file(repo_path("a"), &wc_path.join("a")).unwrap();
+        let entries = stash.entries().unwrap();
+        let ids = entries.iter().map(|entry| entry.id()).collect::<Vec<_>>();
+        assert_eq!(ids, ["0000001000000-1", "0000001000000"]);
+        assert_eq!(
+            entries[1].files().unwrap(),
+            [repo_path("a").to_owned(), repo_path("dir/b").to_owned()]
+        );
+
+        fs::remove_dir_all(&wc_path).unwrap();
+        let restored = entries[1].restore(&wc_path).unwrap();
+        assert_eq!(restored.len(), 2);
+        assert_eq!(fs::read_to_string(wc_path.join("a")).unwrap(), "a\n");
+        assert_eq!(fs::read_to_string(wc_path.join("dir/b")).unwrap(), "b\n");
+
+        // Entries older than the retention period are pruned
+        let day = Duration::from_secs(24 * 60 * 60);
+        stash.prune(day, now + day).unwrap();
+        assert_eq!(stash.entries().unwrap().len(), 2);
+        stash.prune(day, now + day * 2).unwrap();
+        assert!(stash.entries().unwrap().is_empty());
+    }
+}
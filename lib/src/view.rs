@@ -15,14 +15,17 @@
 #![allow(missing_docs)]
 
 use std::collections::BTreeMap;
+use std::collections::BTreeSet;
 use std::collections::HashMap;
 use std::collections::HashSet;
 
 use itertools::Itertools;
 use thiserror::Error;
 
+use crate::backend::ChangeId;
 use crate::backend::CommitId;
 use crate::op_store;
+use crate::op_store::BookmarkMetadata;
 use crate::op_store::BookmarkTarget;
 use crate::op_store::RefTarget;
 use crate::op_store::RefTargetOptionExt as _;
@@ -31,6 +34,7 @@ use crate::op_store::WorkspaceId;
 use crate::refs;
 use crate::refs::LocalAndRemoteRef;
 use crate::refs::RemoteRefSymbol;
+use crate::str_util;
 use crate::str_util::StringPattern;
 
 /// A wrapper around [`op_store::View`] that defines additional methods.
@@ -157,6 +161,19 @@ impl View {
             .map(|(name, target)| (name.as_ref(), target))
     }
 
+    /// Iterates local bookmark `(name, target)`s whose names start with the
+    /// given prefix, in lexicographical order.
+    ///
+    /// For example, prefix `"feature/"` lists the bookmarks in the `feature`
+    /// namespace.
+    pub fn local_bookmarks_with_prefix<'a, 'b>(
+        &'a self,
+        prefix: &'b str,
+    ) -> impl Iterator<Item = (&'a str, &'a RefTarget)> + use<'a, 'b> {
+        str_util::btree_map_with_prefix(&self.data.local_bookmarks, prefix)
+            .map(|(name, target)| (name.as_ref(), target))
+    }
+
     pub fn get_local_bookmark(&self, name: &str) -> &RefTarget {
         self.data.local_bookmarks.get(name).flatten()
     }
@@ -357,6 +374,115 @@ impl View {
         self.data.git_head = target;
     }
 
+    pub fn topics(&self) -> &BTreeMap<String, BTreeSet<ChangeId>> {
+        &self.data.topics
+    }
+
+    /// Returns the change ids labeled with the topic `name`.
+    pub fn get_topic(&self, name: &str) -> Option<&BTreeSet<ChangeId>> {
+        self.data.topics.get(name)
+    }
+
+    /// Iterates topics `(name, change_ids)`s matching the given pattern.
+    /// Entries are sorted by `name`.
+    pub fn topics_matching<'a, 'b>(
+        &'a self,
+        pattern: &'b StringPattern,
+    ) -> impl Iterator<Item = (&'a str, &'a BTreeSet<ChangeId>)> + use<'a, 'b> {
+        pattern
+            .filter_btree_map(&self.data.topics)
+            .map(|(name, change_ids)| (name.as_ref(), change_ids))
+    }
+
+    /// Iterates names of the topics the given change is labeled with.
+    pub fn topics_for_change_id<'a, 'b>(
+        &'a self,
+        change_id: &'b ChangeId,
+    ) -> impl Iterator<Item = &'a str> + use<'a, 'b> {
+        self.data
+            .topics
+            .iter()
+            .filter(move |(_, change_ids)| change_ids.contains(change_id))
+            .map(|(name, _)| name.as_ref())
+    }
+
+    /// Sets the changes labeled with the topic `name`. If `change_ids` is
+    /// empty, the topic will be removed.
+    pub fn set_topic_change_ids(&mut self, name: &str, change_ids: BTreeSet<ChangeId>) {
+        if change_ids.is_empty() {
+            self.data.topics.remove(name);
+        } else {
+            self.data.topics.insert(name.to_owned(), change_ids);
+        }
+    }
+
+    pub fn bookmark_metadata(&self) -> &BTreeMap<String, BookmarkMetadata> {
+        &self.data.bookmark_metadata
+    }
+
+    /// Returns when the local bookmark `name` was created and last moved, if
+    /// recorded.
+    pub fn get_bookmark_metadata(&self, name: &str) -> Option<&BookmarkMetadata> {
+        self.data.bookmark_metadata.get(name)
+    }
+
+    /// Sets the metadata of the local bookmark `name`. If `metadata` is
+    /// `None`, the metadata will be removed.
+    pub fn set_bookmark_metadata(&mut self, name: &str, metadata: Option<BookmarkMetadata>) {
+        if let Some(metadata) = metadata {
+            self.data
+                .bookmark_metadata
+                .insert(name.to_owned(), metadata);
+        } else {
+            self.data.bookmark_metadata.remove(name);
+        }
+    }
+
+    pub fn shelves(&self) -> &BTreeMap<String, CommitId> {
+        &self.data.shelves
+    }
+
+    /// Returns the hidden commit holding the changes shelved as `name`.
+    pub fn get_shelf(&self, name: &str) -> Option<&CommitId> {
+        self.data.shelves.get(name)
+    }
+
+    /// Sets the commit holding the changes shelved as `name`. If `commit_id`
+    /// is `None`, the shelf will be removed.
+    pub fn set_shelf(&mut self, name: &str, commit_id: Option<CommitId>) {
+        if let Some(commit_id) = commit_id {
+            self.data.shelves.insert(name.to_owned(), commit_id);
+        } else {
+            self.data.shelves.remove(name);
+        }
+    }
+
+    pub fn change_metadata(&self) -> &BTreeMap<ChangeId, BTreeMap<String, String>> {
+        &self.data.change_metadata
+    }
+
+    /// Returns the metadata attached to the given change.
+    pub fn get_change_metadata(&self, change_id: &ChangeId) -> Option<&BTreeMap<String, String>> {
+        self.data.change_metadata.get(change_id)
+    }
+
+    /// Sets the metadata `key` of the given change. If `value` is `None`, the
+    /// key will be removed.
+    pub fn set_change_metadata(&mut self, change_id: &ChangeId, key: &str, value: Option<String>) {
+        if let Some(value) = value {
+            self.data
+                .change_metadata
+                .entry(change_id.clone())
+                .or_default()
+                .insert(key.to_owned(), value);
+        } else if let Some(values) = self.data.change_metadata.get_mut(change_id) {
+            values.remove(key);
+            if values.is_empty() {
+                self.data.change_metadata.remove(change_id);
+            }
+        }
+    }
+
     /// Iterates all commit ids referenced by this view.
     ///
     /// This can include hidden commits referenced by remote bookmarks, previous
@@ -382,6 +508,10 @@ impl View {
             git_refs,
             git_head,
             wc_commit_ids,
+            topics: _,
+            bookmark_metadata: _,
+            shelves,
+            change_metadata: _,
         } = &self.data;
         itertools::chain!(
             head_ids,
@@ -395,7 +525,8 @@ impl View {
             }),
             git_refs.values().flat_map(ref_target_ids),
             ref_target_ids(git_head),
-            wc_commit_ids.values()
+            wc_commit_ids.values(),
+            shelves.values()
         )
     }
 
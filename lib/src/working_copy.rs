@@ -20,6 +20,7 @@ use std::collections::BTreeMap;
 use std::ffi::OsString;
 use std::path::PathBuf;
 use std::sync::Arc;
+use std::time::Duration;
 
 use itertools::Itertools;
 use thiserror::Error;
@@ -183,6 +184,13 @@ pub enum SnapshotError {
     /// Checking path with ignore patterns failed.
     #[error(transparent)]
     GitIgnoreError(#[from] GitIgnoreError),
+    /// A directory in the working copy is the root of another repository, and
+    /// the [`NestedRepositoryPolicy`] is to reject it.
+    #[error("Working copy contains a nested repository at {}", path.display())]
+    NestedRepository {
+        /// The root directory of the nested repository.
+        path: PathBuf,
+    },
     /// Some other error happened while snapshotting the working copy.
     #[error("{message}")]
     Other {
@@ -221,6 +229,9 @@ pub struct SnapshotOptions<'a> {
     pub max_new_file_size: u64,
     /// Expected conflict marker style for checking for changed files.
     pub conflict_marker_style: ConflictMarkerStyle,
+    /// How to snapshot directories that contain another Git or Jujutsu
+    /// repository.
+    pub nested_repository_policy: NestedRepositoryPolicy,
 }
 
 impl SnapshotOptions<'_> {
@@ -233,10 +244,31 @@ impl SnapshotOptions<'_> {
             start_tracking_matcher: &EverythingMatcher,
             max_new_file_size: u64::MAX,
             conflict_marker_style: ConflictMarkerStyle::default(),
+            nested_repository_policy: NestedRepositoryPolicy::default(),
         }
     }
 }
 
+/// Describes how to snapshot a directory containing a `.git` or `.jj`
+/// directory, i.e. the root of another repository nested in the working copy.
+///
+/// The policy doesn't apply to ignored directories, nor to the root of the
+/// working copy itself.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default, serde::Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum NestedRepositoryPolicy {
+    /// Don't start tracking files in the nested repository. Files that are
+    /// already tracked are still snapshotted.
+    #[default]
+    Ignore,
+    /// Fail the snapshot with [`SnapshotError::NestedRepository`].
+    Error,
+    /// Record the commit checked out in the nested Git repository as a
+    /// submodule pointer in place of the directory. Nested repositories
+    /// without a Git `HEAD` are ignored.
+    Submodule,
+}
+
 /// A callback for getting progress updates.
 pub type SnapshotProgress<'a> = dyn Fn(&RepoPath) + 'a + Sync;
 
@@ -245,6 +277,26 @@ pub type SnapshotProgress<'a> = dyn Fn(&RepoPath) + 'a + Sync;
 pub struct SnapshotStats {
     /// List of new (previously untracked) files which are still untracked.
     pub untracked_paths: BTreeMap<RepoPathBuf, UntrackedReason>,
+    /// Time spent in each phase of the snapshot. Only measured by
+    /// implementations that support it.
+    pub timings: SnapshotTimings,
+}
+
+/// Time spent in each phase of a snapshot operation.
+#[derive(Clone, Debug, Default)]
+pub struct SnapshotTimings {
+    /// Wall-clock time spent walking the working copy, including hashing
+    /// changed files.
+    pub traverse: Duration,
+    /// Time spent reading changed files and writing them to the store, summed
+    /// over all threads.
+    pub hash_files: Duration,
+    /// Number of files read and written to the store.
+    pub hashed_file_count: usize,
+    /// Time spent merging the changes into the file states and tree builder.
+    pub update_state: Duration,
+    /// Time spent writing the new trees to the store.
+    pub write_tree: Duration,
 }
 
 /// Reason why the new path isn't tracked.
@@ -266,6 +318,9 @@ pub enum UntrackedReason {
 pub struct CheckoutOptions {
     /// Conflict marker style to use when materializing files
     pub conflict_marker_style: ConflictMarkerStyle,
+    /// If set, locally-modified files replaced by the checkout are copied to
+    /// the undo stash, and stash entries older than this are deleted.
+    pub undo_stash_retention: Option<Duration>,
 }
 
 impl CheckoutOptions {
@@ -273,6 +328,7 @@ impl CheckoutOptions {
     pub fn empty_for_test() -> Self {
         CheckoutOptions {
             conflict_marker_style: ConflictMarkerStyle::default(),
+            undo_stash_retention: None,
         }
     }
 }
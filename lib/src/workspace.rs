@@ -14,6 +14,7 @@
 
 #![allow(missing_docs)]
 
+use std::collections::BTreeMap;
 use std::collections::HashMap;
 use std::fs;
 use std::fs::File;
@@ -23,6 +24,7 @@ use std::path::Path;
 use std::path::PathBuf;
 use std::sync::Arc;
 
+use tempfile::NamedTempFile;
 use thiserror::Error;
 
 use crate::backend::BackendInitError;
@@ -49,6 +51,7 @@ use crate::repo::RepoLoader;
 use crate::repo::StoreFactories;
 use crate::repo::StoreLoadError;
 use crate::repo::SubmoduleStoreInitializer;
+use crate::repo_path::RepoPathBuf;
 use crate::settings::UserSettings;
 use crate::signing::SignInitError;
 use crate::signing::Signer;
@@ -623,6 +626,76 @@ impl WorkspaceLoader for DefaultWorkspaceLoader {
     }
 }
 
+/// Sparse patterns of the workspaces of a repo.
+///
+/// Each workspace keeps its sparse patterns in its own working-copy state. They
+/// are also recorded here, in the repo directory, so they can be referenced
+/// from other workspaces. Workspaces which never had their sparse patterns
+/// recorded are assumed to check out all files.
+#[derive(Clone, Debug, Default, serde::Deserialize, serde::Serialize)]
+pub struct WorkspaceSparsePatterns {
+    /// Map of workspace name to sparse patterns.
+    workspaces: BTreeMap<String, Vec<String>>,
+}
+
+impl WorkspaceSparsePatterns {
+    const FILE_NAME: &str = "workspace_sparse_patterns.json";
+
+    /// Loads the sparse patterns stored in the `repo_path` directory.
+    pub fn load(repo_path: &Path) -> Result<Self, PathError> {
+        let path = repo_path.join(Self::FILE_NAME);
+        match fs::read(&path) {
+            Ok(data) => serde_json::from_slice(&data)
+                .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+                .context(&path),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(err) => Err(err).context(&path),
+        }
+    }
+
+    /// Saves the sparse patterns to the `repo_path` directory.
+    pub fn save(&self, repo_path: &Path) -> Result<(), PathError> {
+        let path = repo_path.join(Self::FILE_NAME);
+        let data = serde_json::to_vec(self).expect("sparse patterns should be serializable");
+        let mut temp_file = NamedTempFile::new_in(repo_path).context(repo_path)?;
+        temp_file.write_all(&data).context(temp_file.path())?;
+        temp_file
+            .persist(&path)
+            .map_err(|err| err.error)
+            .context(&path)?;
+        Ok(())
+    }
+
+    /// Records the sparse patterns of the workspace.
+    pub fn record(&mut self, workspace_id: &WorkspaceId, patterns: &[RepoPathBuf]) {
+        let patterns = patterns
+            .iter()
+            .map(|path| path.as_internal_file_string().to_owned())
+            .collect();
+        self.workspaces
+            .insert(workspace_id.as_str().to_owned(), patterns);
+    }
+
+    /// Removes the sparse patterns of the workspace.
+    pub fn forget(&mut self, workspace_id: &WorkspaceId) {
+        self.workspaces.remove(workspace_id.as_str());
+    }
+
+    /// Returns the recorded sparse patterns keyed by workspace.
+    pub fn to_map(&self) -> HashMap<WorkspaceId, Vec<RepoPathBuf>> {
+        self.workspaces
+            .iter()
+            .map(|(name, patterns)| {
+                let patterns = patterns
+                    .iter()
+                    .map(|path| RepoPathBuf::from_internal_string(path.clone()))
+                    .collect();
+                (WorkspaceId::new(name.clone()), patterns)
+            })
+            .collect()
+    }
+}
+
 pub fn default_working_copy_factories() -> WorkingCopyFactories {
     let mut factories = WorkingCopyFactories::new();
     factories.insert(
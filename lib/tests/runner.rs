@@ -7,6 +7,7 @@ fn test_no_forgotten_test_files() {
 }
 
 mod test_annotate;
+mod test_api;
 mod test_bad_locking;
 mod test_commit_builder;
 mod test_commit_concurrent;
@@ -0,0 +1,159 @@
+// Copyright 2025 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use jj_lib::api::ChangeKind;
+use jj_lib::api::DiffEntry;
+use jj_lib::api::Repository;
+use jj_lib::api::Settings;
+use jj_lib::repo::Repo as _;
+use jj_lib::repo_path::RepoPath;
+use testutils::create_tree;
+use testutils::TestRepoBackend;
+use testutils::TestWorkspace;
+
+fn api_settings() -> Settings {
+    Settings::from_toml(
+        r#"
+        user.name = "Test User"
+        user.email = "test.user@example.com"
+        "#,
+    )
+    .unwrap()
+}
+
+#[test]
+fn test_api_open_and_log() {
+    let test_workspace = TestWorkspace::init_with_backend(TestRepoBackend::Local);
+    let workspace_root = test_workspace.workspace.workspace_root();
+    let test_repo = &test_workspace.repo;
+    let wc_commit_id = test_repo
+        .view()
+        .wc_commit_ids()
+        .values()
+        .next()
+        .unwrap()
+        .clone();
+    let root_commit_id = test_repo.store().root_commit_id().clone();
+
+    // Add two commits on top of the working-copy commit before opening the
+    // repository, since the api module can't write file contents
+    let file1 = RepoPath::from_internal_string("file1");
+    let file2 = RepoPath::from_internal_string("file2");
+    let tree1 = create_tree(test_repo, &[(file1, "a"), (file2, "b")]);
+    let tree2 = create_tree(test_repo, &[(file1, "c")]);
+    let mut tx = test_repo.start_transaction();
+    let commit1 = tx
+        .repo_mut()
+        .new_commit(vec![wc_commit_id.clone()], tree1.id())
+        .write()
+        .unwrap();
+    let commit2 = tx
+        .repo_mut()
+        .new_commit(vec![commit1.id().clone()], tree2.id())
+        .set_description("second")
+        .write()
+        .unwrap();
+    tx.commit("add commits").unwrap();
+
+    let repo = Repository::open(&api_settings(), workspace_root).unwrap();
+    assert_eq!(repo.operation_description(), "add commits");
+    assert_eq!(
+        repo.evaluate_revset("@").unwrap(),
+        vec![wc_commit_id.clone()]
+    );
+
+    let commits = repo
+        .log("all()")
+        .unwrap()
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    assert_eq!(
+        commits.iter().map(|commit| commit.id()).collect::<Vec<_>>(),
+        [commit2.id(), commit1.id(), &wc_commit_id, &root_commit_id]
+    );
+    assert_eq!(commits[0].description(), "second");
+    assert_eq!(commits[0].parent_ids(), [commit1.id().clone()]);
+    assert_eq!(commits[0].parents().next().unwrap().unwrap(), commits[1]);
+    assert_eq!(
+        repo.evaluate_revset("description(second)").unwrap(),
+        vec![commit2.id().clone()]
+    );
+
+    let diff = repo.diff(&commits[1], &commits[0]).unwrap();
+    assert_eq!(
+        diff.iter()
+            .map(|DiffEntry { path, kind, .. }| (path.as_ref(), *kind))
+            .collect::<Vec<_>>(),
+        [(file1, ChangeKind::Modified), (file2, ChangeKind::Removed)]
+    );
+
+    // Errors are reported with the underlying cause
+    let err = repo.evaluate_revset("nonexistent").err().unwrap();
+    assert_eq!(err.to_string(), "Failed to resolve revset");
+    assert!(std::error::Error::source(&err).is_some());
+}
+
+#[test]
+fn test_api_transaction() {
+    let test_workspace = TestWorkspace::init_with_backend(TestRepoBackend::Local);
+    let workspace_root = test_workspace.workspace.workspace_root();
+
+    let mut repo = Repository::open(&api_settings(), workspace_root).unwrap();
+    let wc_commit = repo
+        .get_commit(&repo.evaluate_revset("@").unwrap()[0])
+        .unwrap();
+
+    let mut tx = repo.start_transaction();
+    let commit1 = tx
+        .new_commit(std::slice::from_ref(&wc_commit), "first\n")
+        .unwrap();
+    let commit2 = tx
+        .new_commit(std::slice::from_ref(&commit1), "second\n")
+        .unwrap();
+    repo.commit_transaction(tx, "add commits").unwrap();
+    assert_eq!(repo.operation_description(), "add commits");
+    assert_eq!(commit2.parent_ids(), [commit1.id().clone()]);
+    assert_eq!(commit2.author_email(), "test.user@example.com");
+    assert_eq!(
+        repo.evaluate_revset("@+::").unwrap(),
+        vec![commit2.id().clone(), commit1.id().clone()]
+    );
+
+    // Descendants are rebased when a commit is rewritten or abandoned
+    let mut tx = repo.start_transaction();
+    let new_commit1 = tx.describe(&commit1, "first, reworded\n").unwrap();
+    repo.commit_transaction(tx, "describe commit").unwrap();
+    let [new_commit2] = repo
+        .log("@++")
+        .unwrap()
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap()
+        .try_into()
+        .unwrap();
+    assert_eq!(new_commit2.change_id(), commit2.change_id());
+    assert_eq!(new_commit2.parent_ids(), [new_commit1.id().clone()]);
+
+    let mut tx = repo.start_transaction();
+    tx.abandon(&new_commit1);
+    repo.commit_transaction(tx, "abandon commit").unwrap();
+    let [rebased_commit2] = repo
+        .log("@+")
+        .unwrap()
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap()
+        .try_into()
+        .unwrap();
+    assert_eq!(rebased_commit2.change_id(), commit2.change_id());
+    assert_eq!(rebased_commit2.parent_ids(), [wc_commit.id().clone()]);
+}
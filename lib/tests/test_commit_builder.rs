@@ -350,6 +350,63 @@ fn test_rewrite_resets_author_timestamp(backend: TestRepoBackend) {
     assert_eq!(rewritten_commit_2.committer().timestamp, new_timestamp_2);
 }
 
+#[test_case(TestRepoBackend::Local ; "local backend")]
+#[test_case(TestRepoBackend::Git ; "git backend")]
+fn test_rewrite_keeps_committer_timestamp(backend: TestRepoBackend) {
+    let test_repo = TestRepo::init_with_backend(backend);
+    let test_env = &test_repo.env;
+
+    // Create discardable commit
+    let initial_timestamp = "2001-02-03T04:05:06+07:00";
+    let settings =
+        UserSettings::from_config(config_with_commit_timestamp(initial_timestamp)).unwrap();
+    let repo = test_env.load_repo_at_head(&settings, test_repo.repo_path());
+    let mut tx = repo.start_transaction();
+    let initial_commit = tx
+        .repo_mut()
+        .new_commit(
+            vec![repo.store().root_commit_id().clone()],
+            repo.store().empty_merged_tree_id(),
+        )
+        .write()
+        .unwrap();
+    tx.commit("test").unwrap();
+
+    // Neither the committer timestamp nor the author timestamp of the
+    // discardable commit should be updated
+    let mut config = config_with_commit_timestamp("2002-03-04T05:06:07+08:00");
+    let mut layer = ConfigLayer::empty(ConfigSource::User);
+    layer
+        .set_value("rewrite.update-committer-timestamp", false)
+        .unwrap();
+    config.add_layer(layer);
+    let settings = UserSettings::from_config(config).unwrap();
+    let repo = test_env.load_repo_at_head(&settings, test_repo.repo_path());
+    let initial_commit = repo.store().get_commit(initial_commit.id()).unwrap();
+    let mut tx = repo.start_transaction();
+    let rewritten_commit = tx
+        .repo_mut()
+        .rewrite_commit(&initial_commit)
+        .set_description("New description")
+        .write()
+        .unwrap();
+    tx.repo_mut().rebase_descendants().unwrap();
+    tx.commit("test").unwrap();
+
+    assert_eq!(rewritten_commit.author(), initial_commit.author());
+    assert_eq!(rewritten_commit.committer(), initial_commit.committer());
+
+    // A no-op rewrite produces the same commit
+    let mut tx = repo.start_transaction();
+    let noop_commit = tx
+        .repo_mut()
+        .rewrite_commit(&initial_commit)
+        .set_predecessors(initial_commit.store_commit().predecessors.clone())
+        .write()
+        .unwrap();
+    assert_eq!(noop_commit.id(), initial_commit.id());
+}
+
 #[test_case(TestRepoBackend::Local ; "local backend")]
 // #[test_case(TestRepoBackend::Git ; "git backend")]
 fn test_commit_builder_descendants(backend: TestRepoBackend) {
@@ -13,10 +13,12 @@
 // limitations under the License.
 
 use std::collections::BTreeMap;
+use std::collections::HashMap;
 use std::collections::HashSet;
 use std::fs;
 use std::io::Write;
 use std::iter;
+use std::num::NonZeroU32;
 use std::path::Path;
 use std::path::PathBuf;
 use std::sync::mpsc;
@@ -42,9 +44,12 @@ use jj_lib::git::GitBranchPushTargets;
 use jj_lib::git::GitExportError;
 use jj_lib::git::GitFetch;
 use jj_lib::git::GitFetchError;
+#[cfg(feature = "git-native-http")]
+use jj_lib::git::GitHttpError;
 use jj_lib::git::GitImportError;
 use jj_lib::git::GitPushError;
 use jj_lib::git::GitRefUpdate;
+use jj_lib::git::GitShallowOptions;
 use jj_lib::git::RefName;
 use jj_lib::git::SubmoduleConfig;
 use jj_lib::git_backend::GitBackend;
@@ -58,8 +63,14 @@ use jj_lib::refs::RemoteRefSymbol;
 use jj_lib::repo::MutableRepo;
 use jj_lib::repo::ReadonlyRepo;
 use jj_lib::repo::Repo;
+#[cfg(feature = "git-native-http")]
+use jj_lib::repo::RepoLoader;
+#[cfg(feature = "git-native-http")]
+use jj_lib::repo::StoreFactories;
 use jj_lib::repo_path::RepoPath;
 use jj_lib::repo_path::RepoPathBuf;
+#[cfg(feature = "git-native-http")]
+use jj_lib::settings::GitHttpSettings;
 use jj_lib::settings::GitSettings;
 use jj_lib::settings::UserSettings;
 use jj_lib::signing::Signer;
@@ -142,7 +153,7 @@ fn git_fetch(
         remote_name,
         branch_names,
         git::RemoteCallbacks::default(),
-        None,
+        &GitShallowOptions::default(),
     )?;
     let default_branch =
         git_fetch.get_default_branch(remote_name, git::RemoteCallbacks::default())?;
@@ -179,7 +190,7 @@ fn test_import_refs() {
     testutils::git::set_symbolic_reference(&git_repo, "HEAD", "refs/heads/main");
 
     let mut tx = repo.start_transaction();
-    git::import_head(tx.repo_mut()).unwrap();
+    git::import_head(tx.repo_mut(), &GitSettings::default()).unwrap();
     let stats = git::import_refs(tx.repo_mut(), &git_settings).unwrap();
     tx.repo_mut().rebase_descendants().unwrap();
     let repo = tx.commit("test").unwrap();
@@ -407,6 +418,199 @@ fn test_import_refs_reimport() {
     assert_eq!(view.get_git_ref("refs/heads/feature2"), &commit5_target);
 }
 
+#[test]
+fn test_import_refs_detect_external_rewrites() {
+    let git_settings = GitSettings {
+        auto_local_bookmark: true,
+        detect_external_rewrites: true,
+        ..Default::default()
+    };
+    let test_repo = TestRepo::init_with_backend(TestRepoBackend::Git);
+    let repo = &test_repo.repo;
+    let git_repo = get_git_repo(repo);
+
+    let commit1 = empty_git_commit(&git_repo, "refs/heads/main", &[]);
+    let mut tx = repo.start_transaction();
+    git::import_refs(tx.repo_mut(), &git_settings).unwrap();
+    let jj_commit1 = tx.repo().store().get_commit(&jj_id(commit1)).unwrap();
+    let child = create_random_commit(tx.repo_mut())
+        .set_parents(vec![jj_commit1.id().clone()])
+        .write()
+        .unwrap();
+    let repo = tx.commit("test").unwrap();
+
+    // Rewrite the commit with Git, which preserves the author
+    let commit2 = empty_git_commit(&git_repo, "refs/heads/temp", &[]);
+    delete_git_ref(&git_repo, "refs/heads/temp");
+    git_ref(&git_repo, "refs/heads/main", commit2);
+    let mut tx = repo.start_transaction();
+    let stats = git::import_refs(tx.repo_mut(), &git_settings).unwrap();
+    tx.repo_mut().rebase_descendants().unwrap();
+    let repo = tx.commit("test").unwrap();
+
+    // The rewritten commit is recorded as a successor of the original commit
+    assert_eq!(stats.abandoned_commits, vec![jj_id(commit1)]);
+    let jj_commit2 = repo.store().get_commit(&jj_id(commit2)).unwrap();
+    assert_eq!(jj_commit2.change_id(), jj_commit1.change_id());
+    assert_eq!(jj_commit2.predecessor_ids(), [jj_id(commit1)]);
+    let view = repo.view();
+    assert!(!view.heads().contains(child.id()));
+    let new_child_id = view.heads().iter().exactly_one().unwrap();
+    let new_child = repo.store().get_commit(new_child_id).unwrap();
+    assert_eq!(new_child.change_id(), child.change_id());
+    assert_eq!(new_child.parent_ids(), [jj_id(commit2)]);
+
+    // Commits aren't matched if the setting is disabled
+    let commit3 = empty_git_commit(&git_repo, "refs/heads/temp", &[]);
+    delete_git_ref(&git_repo, "refs/heads/temp");
+    git_ref(&git_repo, "refs/heads/main", commit3);
+    let mut tx = repo.start_transaction();
+    git::import_refs(tx.repo_mut(), &GitSettings::default()).unwrap();
+    let jj_commit3 = tx.repo().store().get_commit(&jj_id(commit3)).unwrap();
+    assert_ne!(jj_commit3.change_id(), jj_commit2.change_id());
+    assert!(jj_commit3.predecessor_ids().is_empty());
+}
+
+#[test]
+fn test_import_head_detect_external_rewrites() {
+    let git_settings = GitSettings {
+        detect_external_rewrites: true,
+        ..Default::default()
+    };
+    let test_repo = TestRepo::init_with_backend(TestRepoBackend::Git);
+    let repo = &test_repo.repo;
+    let git_repo = get_git_repo(repo);
+
+    // The old HEAD is pinned by a bookmark, like in a colocated repo
+    let commit1 = empty_git_commit(&git_repo, "refs/heads/main", &[]);
+    testutils::git::set_head_to_id(&git_repo, commit1);
+    let mut tx = repo.start_transaction();
+    git::import_head(tx.repo_mut(), &git_settings).unwrap();
+    git::import_refs(tx.repo_mut(), &git_settings).unwrap();
+    let repo = tx.commit("test").unwrap();
+
+    // Amend the HEAD commit without moving the bookmark
+    let commit2 = empty_git_commit(&git_repo, "refs/heads/temp", &[]);
+    delete_git_ref(&git_repo, "refs/heads/temp");
+    testutils::git::set_head_to_id(&git_repo, commit2);
+    let mut tx = repo.start_transaction();
+    git::import_head(tx.repo_mut(), &git_settings).unwrap();
+    tx.repo_mut().rebase_descendants().unwrap();
+    let repo = tx.commit("test").unwrap();
+
+    // The bookmark follows the rewritten commit
+    let jj_commit1 = repo.store().get_commit(&jj_id(commit1)).unwrap();
+    let jj_commit2 = repo.store().get_commit(&jj_id(commit2)).unwrap();
+    assert_eq!(jj_commit2.change_id(), jj_commit1.change_id());
+    assert_eq!(jj_commit2.predecessor_ids(), [jj_id(commit1)]);
+    let view = repo.view();
+    assert_eq!(
+        *view.get_local_bookmark("main"),
+        RefTarget::normal(jj_id(commit2))
+    );
+    assert_eq!(*view.heads(), hashset! {jj_id(commit2)});
+}
+
+#[test]
+fn test_import_refs_detect_external_rewrites_by_patch_id() {
+    let git_settings = GitSettings {
+        auto_local_bookmark: true,
+        detect_external_rewrites: true,
+        ..Default::default()
+    };
+    let test_repo = TestRepo::init_with_backend(TestRepoBackend::Git);
+    let repo = &test_repo.repo;
+    let git_repo = get_git_repo(repo);
+
+    // The commits have the same author, so they can't be told apart by it
+    let commit1 = testutils::git::add_commit(&git_repo, "refs/heads/main", "a", b"a\n", "a", &[]);
+    let commit2 = testutils::git::add_commit(
+        &git_repo,
+        "refs/heads/main",
+        "b",
+        b"b\n",
+        "b",
+        &[commit1.commit_id],
+    );
+    let mut tx = repo.start_transaction();
+    git::import_refs(tx.repo_mut(), &git_settings).unwrap();
+    let repo = tx.commit("test").unwrap();
+
+    // Rebase the second commit onto the root commit with Git
+    let commit3 = testutils::git::add_commit(&git_repo, "refs/heads/temp", "b", b"b\n", "b", &[]);
+    delete_git_ref(&git_repo, "refs/heads/temp");
+    git_ref(&git_repo, "refs/heads/main", commit3.commit_id);
+    let mut tx = repo.start_transaction();
+    let stats = git::import_refs(tx.repo_mut(), &git_settings).unwrap();
+    tx.repo_mut().rebase_descendants().unwrap();
+    let repo = tx.commit("test").unwrap();
+
+    // The rebased commit is matched by its changes
+    assert_eq!(
+        stats.abandoned_commits.iter().sorted().collect_vec(),
+        [&jj_id(commit1.commit_id), &jj_id(commit2.commit_id)]
+            .into_iter()
+            .sorted()
+            .collect_vec()
+    );
+    let jj_commit2 = repo.store().get_commit(&jj_id(commit2.commit_id)).unwrap();
+    let jj_commit3 = repo.store().get_commit(&jj_id(commit3.commit_id)).unwrap();
+    assert_eq!(jj_commit3.change_id(), jj_commit2.change_id());
+    assert_eq!(jj_commit3.predecessor_ids(), [jj_id(commit2.commit_id)]);
+}
+
+#[test]
+fn test_import_head_detect_external_rewrites_cherry_pick() {
+    let git_settings = GitSettings {
+        detect_external_rewrites: true,
+        ..Default::default()
+    };
+    let test_repo = TestRepo::init_with_backend(TestRepoBackend::Git);
+    let repo = &test_repo.repo;
+    let git_repo = get_git_repo(repo);
+
+    // Someone else reverts the first commit
+    let commit1 = testutils::git::add_commit(&git_repo, "refs/heads/main", "a", b"a\n", "a", &[]);
+    let other_signature = gix::actor::Signature {
+        name: "Someone Else".into(),
+        email: "someone.else@example.com".into(),
+        time: gix::date::Time::new(0, 0),
+    };
+    let commit2 = git_repo
+        .commit_as(
+            &other_signature,
+            &other_signature,
+            "refs/heads/main",
+            "revert a",
+            git_repo.empty_tree().id().detach(),
+            [commit1.commit_id],
+        )
+        .unwrap()
+        .detach();
+    testutils::git::set_head_to_id(&git_repo, commit2);
+    let mut tx = repo.start_transaction();
+    git::import_head(tx.repo_mut(), &git_settings).unwrap();
+    git::import_refs(tx.repo_mut(), &git_settings).unwrap();
+    let repo = tx.commit("test").unwrap();
+
+    // Cherry-pick the first commit onto the HEAD commit with Git, which
+    // preserves the author and the changes
+    let commit3 =
+        testutils::git::add_commit(&git_repo, "refs/heads/main", "a", b"a\n", "a", &[commit2]);
+    testutils::git::set_head_to_id(&git_repo, commit3.commit_id);
+    let mut tx = repo.start_transaction();
+    git::import_head(tx.repo_mut(), &git_settings).unwrap();
+    assert_eq!(tx.repo_mut().rebase_descendants().unwrap(), 0);
+    let repo = tx.commit("test").unwrap();
+
+    // The original commit is still reachable, so it wasn't rewritten
+    let jj_commit1 = repo.store().get_commit(&jj_id(commit1.commit_id)).unwrap();
+    let jj_commit3 = repo.store().get_commit(&jj_id(commit3.commit_id)).unwrap();
+    assert_ne!(jj_commit3.change_id(), jj_commit1.change_id());
+    assert!(jj_commit3.predecessor_ids().is_empty());
+    assert_eq!(*repo.view().heads(), hashset! {jj_id(commit3.commit_id)});
+}
+
 #[test]
 fn test_import_refs_reimport_head_removed() {
     // Test that re-importing refs doesn't cause a deleted head to come back
@@ -443,7 +647,7 @@ fn test_import_refs_reimport_git_head_does_not_count() {
     testutils::git::set_head_to_id(&git_repo, commit);
 
     let mut tx = repo.start_transaction();
-    git::import_head(tx.repo_mut()).unwrap();
+    git::import_head(tx.repo_mut(), &GitSettings::default()).unwrap();
     git::import_refs(tx.repo_mut(), &git_settings).unwrap();
     tx.repo_mut().rebase_descendants().unwrap();
 
@@ -454,7 +658,7 @@ fn test_import_refs_reimport_git_head_does_not_count() {
         .unwrap()
         .delete()
         .unwrap();
-    git::import_head(tx.repo_mut()).unwrap();
+    git::import_head(tx.repo_mut(), &GitSettings::default()).unwrap();
     git::import_refs(tx.repo_mut(), &git_settings).unwrap();
     tx.repo_mut().rebase_descendants().unwrap();
     assert!(!tx.repo().view().heads().contains(&jj_id(commit)));
@@ -475,7 +679,7 @@ fn test_import_refs_reimport_git_head_without_ref() {
     testutils::git::set_head_to_id(&git_repo, git_id(&commit1));
 
     // Import HEAD.
-    git::import_head(tx.repo_mut()).unwrap();
+    git::import_head(tx.repo_mut(), &GitSettings::default()).unwrap();
     git::import_refs(tx.repo_mut(), &git_settings).unwrap();
     tx.repo_mut().rebase_descendants().unwrap();
     assert!(tx.repo().view().heads().contains(commit1.id()));
@@ -488,7 +692,7 @@ fn test_import_refs_reimport_git_head_without_ref() {
     // would be moved by `git checkout` command. This isn't always true because the
     // detached HEAD commit could be rewritten by e.g. `git commit --amend` command,
     // but it should be safer than abandoning old checkout branch.
-    git::import_head(tx.repo_mut()).unwrap();
+    git::import_head(tx.repo_mut(), &GitSettings::default()).unwrap();
     git::import_refs(tx.repo_mut(), &git_settings).unwrap();
     tx.repo_mut().rebase_descendants().unwrap();
     assert!(tx.repo().view().heads().contains(commit1.id()));
@@ -518,7 +722,7 @@ fn test_import_refs_reimport_git_head_with_moved_ref() {
     testutils::git::set_head_to_id(&git_repo, git_id(&commit1));
 
     // Import HEAD and main.
-    git::import_head(tx.repo_mut()).unwrap();
+    git::import_head(tx.repo_mut(), &GitSettings::default()).unwrap();
     git::import_refs(tx.repo_mut(), &git_settings).unwrap();
     tx.repo_mut().rebase_descendants().unwrap();
     assert!(tx.repo().view().heads().contains(commit1.id()));
@@ -536,13 +740,13 @@ fn test_import_refs_reimport_git_head_with_moved_ref() {
     testutils::git::set_head_to_id(&git_repo, git_id(&commit2));
 
     // Reimport HEAD and main, which abandons the old main branch.
-    git::import_head(tx.repo_mut()).unwrap();
+    git::import_head(tx.repo_mut(), &GitSettings::default()).unwrap();
     git::import_refs(tx.repo_mut(), &git_settings).unwrap();
     tx.repo_mut().rebase_descendants().unwrap();
     assert!(!tx.repo().view().heads().contains(commit1.id()));
     assert!(tx.repo().view().heads().contains(commit2.id()));
     // Reimport HEAD and main, which abandons the old main bookmark.
-    git::import_head(tx.repo_mut()).unwrap();
+    git::import_head(tx.repo_mut(), &GitSettings::default()).unwrap();
     git::import_refs(tx.repo_mut(), &git_settings).unwrap();
     tx.repo_mut().rebase_descendants().unwrap();
     assert!(!tx.repo().view().heads().contains(commit1.id()));
@@ -1040,7 +1244,7 @@ fn test_import_refs_reimport_git_head_with_fixed_ref() {
     testutils::git::set_head_to_id(&git_repo, git_id(&commit1));
 
     // Import HEAD and main.
-    git::import_head(tx.repo_mut()).unwrap();
+    git::import_head(tx.repo_mut(), &GitSettings::default()).unwrap();
     git::import_refs(tx.repo_mut(), &git_settings).unwrap();
     tx.repo_mut().rebase_descendants().unwrap();
     assert!(tx.repo().view().heads().contains(commit1.id()));
@@ -1050,7 +1254,7 @@ fn test_import_refs_reimport_git_head_with_fixed_ref() {
     testutils::git::set_head_to_id(&git_repo, git_id(&commit2));
 
     // Reimport HEAD, which shouldn't abandon the old HEAD branch.
-    git::import_head(tx.repo_mut()).unwrap();
+    git::import_head(tx.repo_mut(), &GitSettings::default()).unwrap();
     git::import_refs(tx.repo_mut(), &git_settings).unwrap();
     tx.repo_mut().rebase_descendants().unwrap();
     assert!(tx.repo().view().heads().contains(commit1.id()));
@@ -1114,6 +1318,34 @@ fn test_import_refs_reimport_abandoning_disabled() {
     assert!(tx.repo().view().heads().contains(&jj_id(commit2)));
 }
 
+#[test]
+fn test_import_refs_reimport_with_external_keep_ref() {
+    // Test that commits kept by external tools aren't abandoned
+    let git_settings = GitSettings::default();
+    let test_repo = TestRepo::init_with_backend(TestRepoBackend::Git);
+    let repo = &test_repo.repo;
+    let git_repo = get_git_repo(repo);
+
+    let commit1 = empty_git_commit(&git_repo, "refs/heads/main", &[]);
+    let commit2 = empty_git_commit(&git_repo, "refs/heads/feature1", &[commit1]);
+    let commit3 = empty_git_commit(&git_repo, "refs/heads/feature2", &[commit1]);
+    let mut tx = repo.start_transaction();
+    git::import_refs(tx.repo_mut(), &git_settings).unwrap();
+    tx.repo_mut().rebase_descendants().unwrap();
+    let repo = tx.commit("test").unwrap();
+
+    // Flat refs are managed by jj, so they don't keep the commit
+    git_ref(&git_repo, "refs/jj/keep/ci/build-1", commit2);
+    git_ref(&git_repo, &format!("refs/jj/keep/{commit3}"), commit3);
+    delete_git_ref(&git_repo, "refs/heads/feature1");
+    delete_git_ref(&git_repo, "refs/heads/feature2");
+    let mut tx = repo.start_transaction();
+    let stats = git::import_refs(tx.repo_mut(), &git_settings).unwrap();
+    tx.repo_mut().rebase_descendants().unwrap();
+    assert_eq!(stats.abandoned_commits, vec![jj_id(commit3)]);
+    assert_eq!(*tx.repo().view().heads(), hashset! {jj_id(commit2)});
+}
+
 #[test]
 fn test_import_refs_reimport_conflicted_remote_bookmark() {
     let git_settings = GitSettings {
@@ -1483,7 +1715,7 @@ fn test_import_refs_missing_git_commit() {
         .unwrap();
     testutils::git::set_head_to_id(&git_repo, commit2);
     let mut tx = repo.start_transaction();
-    let result = git::import_head(tx.repo_mut());
+    let result = git::import_head(tx.repo_mut(), &GitSettings::default());
     assert_matches!(
         result,
         Err(GitImportError::MissingHeadTarget {
@@ -1520,7 +1752,7 @@ fn test_import_refs_missing_git_commit() {
     testutils::git::set_head_to_id(&git_repo, commit1);
     fs::rename(&object_file, &backup_object_file).unwrap();
     let mut tx = repo.start_transaction();
-    let result = git::import_head(tx.repo_mut());
+    let result = git::import_head(tx.repo_mut(), &GitSettings::default());
     assert!(result.is_ok());
 }
 
@@ -1540,7 +1772,7 @@ fn test_import_refs_detached_head() {
     testutils::git::set_head_to_id(&test_data.git_repo, commit1);
 
     let mut tx = test_data.repo.start_transaction();
-    git::import_head(tx.repo_mut()).unwrap();
+    git::import_head(tx.repo_mut(), &GitSettings::default()).unwrap();
     git::import_refs(tx.repo_mut(), &git_settings).unwrap();
     tx.repo_mut().rebase_descendants().unwrap();
     let repo = tx.commit("test").unwrap();
@@ -1562,7 +1794,7 @@ fn test_export_refs_no_detach() {
     testutils::git::set_symbolic_reference(&git_repo, "HEAD", "refs/heads/main");
     let mut tx = test_data.repo.start_transaction();
     let mut_repo = tx.repo_mut();
-    git::import_head(mut_repo).unwrap();
+    git::import_head(mut_repo, &GitSettings::default()).unwrap();
     git::import_refs(mut_repo, &git_settings).unwrap();
     mut_repo.rebase_descendants().unwrap();
 
@@ -1605,7 +1837,7 @@ fn test_export_refs_bookmark_changed() {
 
     let mut tx = test_data.repo.start_transaction();
     let mut_repo = tx.repo_mut();
-    git::import_head(mut_repo).unwrap();
+    git::import_head(mut_repo, &GitSettings::default()).unwrap();
     git::import_refs(mut_repo, &git_settings).unwrap();
     mut_repo.rebase_descendants().unwrap();
     assert!(git::export_refs(mut_repo).unwrap().is_empty());
@@ -1647,7 +1879,7 @@ fn test_export_refs_current_bookmark_changed() {
     testutils::git::set_symbolic_reference(&git_repo, "HEAD", "refs/heads/main");
     let mut tx = test_data.repo.start_transaction();
     let mut_repo = tx.repo_mut();
-    git::import_head(mut_repo).unwrap();
+    git::import_head(mut_repo, &GitSettings::default()).unwrap();
     git::import_refs(mut_repo, &git_settings).unwrap();
     mut_repo.rebase_descendants().unwrap();
     assert!(git::export_refs(mut_repo).unwrap().is_empty());
@@ -1685,7 +1917,7 @@ fn test_export_refs_unborn_git_bookmark(move_placeholder_ref: bool) {
     testutils::git::set_symbolic_reference(&git_repo, "HEAD", "refs/heads/main");
     let mut tx = test_data.repo.start_transaction();
     let mut_repo = tx.repo_mut();
-    git::import_head(mut_repo).unwrap();
+    git::import_head(mut_repo, &GitSettings::default()).unwrap();
     git::import_refs(mut_repo, &git_settings).unwrap();
     mut_repo.rebase_descendants().unwrap();
     assert!(git::export_refs(mut_repo).unwrap().is_empty());
@@ -1723,6 +1955,50 @@ fn test_export_refs_unborn_git_bookmark(move_placeholder_ref: bool) {
     assert!(git_repo.find_reference("refs/jj/root").is_err());
 }
 
+#[test]
+fn test_import_export_ref_storage() {
+    // Refs in reftable storage can't be accessed through gix, so import and
+    // export should fail early instead of seeing no refs.
+    let settings = testutils::user_settings();
+    let git_settings = GitSettings::default();
+    for (format, is_supported) in [("files", true), ("reftable", false)] {
+        let test_repo = TestRepo::init_with_backend(TestRepoBackend::Git);
+        let git_repo = get_git_repo(&test_repo.repo);
+        let mut config_file = fs::OpenOptions::new()
+            .append(true)
+            .open(git_repo.path().join("config"))
+            .unwrap();
+        writeln!(config_file, "[extensions]\n\trefStorage = {format}").unwrap();
+        let repo = test_repo
+            .env
+            .load_repo_at_head(&settings, test_repo.repo_path());
+
+        let mut tx = repo.start_transaction();
+        let mut_repo = tx.repo_mut();
+        let commit = write_random_commit(mut_repo);
+        mut_repo.set_local_bookmark_target("main", RefTarget::normal(commit.id().clone()));
+        let import_result = git::import_refs(mut_repo, &git_settings);
+        let export_result = git::export_refs(mut_repo);
+        let reset_result = git::reset_head(mut_repo, &commit);
+        if is_supported {
+            assert_matches!(import_result, Ok(_));
+            assert_matches!(export_result, Ok(failed) if failed.is_empty());
+            assert_matches!(reset_result, Ok(()));
+            assert_eq!(
+                get_git_repo(&repo)
+                    .find_reference("refs/heads/main")
+                    .unwrap()
+                    .id(),
+                git_id(&commit)
+            );
+        } else {
+            assert_matches!(import_result, Err(GitImportError::UnsupportedRefStorage(_)));
+            assert_matches!(export_result, Err(GitExportError::UnsupportedRefStorage(_)));
+            assert_matches!(reset_result, Err(GitExportError::UnsupportedRefStorage(_)));
+        }
+    }
+}
+
 #[test]
 fn test_export_import_sequence() {
     // Import a bookmark pointing to A, modify it in jj to point to B, export it,
@@ -1979,7 +2255,10 @@ fn test_export_partial_failure() {
     assert_eq!(failed[1].name, RefName::LocalBranch("HEAD".to_string()));
     assert_matches!(failed[1].reason, FailedRefExportReason::InvalidGitName);
     assert_eq!(failed[2].name, RefName::LocalBranch("main/sub".to_string()));
-    assert_matches!(failed[2].reason, FailedRefExportReason::FailedToSet(_));
+    assert_matches!(
+        &failed[2].reason,
+        FailedRefExportReason::ConflictingName(name) if name == "refs/heads/main"
+    );
 
     // The `main` bookmark should have succeeded but the other should have failed
     assert!(git_repo.find_reference("refs/heads/").is_err());
@@ -2052,6 +2331,46 @@ fn test_export_partial_failure() {
     );
 }
 
+#[test]
+fn test_export_conflicting_names() {
+    let test_data = GitRepoData::create();
+    let git_repo = test_data.git_repo;
+    let mut tx = test_data.repo.start_transaction();
+    let mut_repo = tx.repo_mut();
+    let commit_a = write_random_commit(mut_repo);
+    let target = RefTarget::normal(commit_a.id().clone());
+    mut_repo.set_local_bookmark_target("foo", target.clone());
+    mut_repo.set_local_bookmark_target("bar/baz", target.clone());
+    assert!(git::export_refs(mut_repo).unwrap().is_empty());
+
+    // New bookmarks conflicting with the existing refs can't be exported
+    mut_repo.set_local_bookmark_target("foo/new", target.clone());
+    mut_repo.set_local_bookmark_target("bar", target.clone());
+    // Deleted ref no longer conflicts, but the new refs conflict with each other
+    mut_repo.set_local_bookmark_target("bar/baz", RefTarget::absent());
+    mut_repo.set_local_bookmark_target("bar/baz/qux", target.clone());
+    let failed = git::export_refs(mut_repo).unwrap();
+    assert_eq!(failed.len(), 2);
+    assert_eq!(
+        failed[0].name,
+        RefName::LocalBranch("bar/baz/qux".to_string())
+    );
+    assert_matches!(
+        &failed[0].reason,
+        FailedRefExportReason::ConflictingName(name) if name == "refs/heads/bar"
+    );
+    assert_eq!(failed[1].name, RefName::LocalBranch("foo/new".to_string()));
+    assert_matches!(
+        &failed[1].reason,
+        FailedRefExportReason::ConflictingName(name) if name == "refs/heads/foo"
+    );
+    // New refs are exported in lexicographical order
+    assert!(git_repo.find_reference("refs/heads/bar").is_ok());
+    assert!(git_repo.find_reference("refs/heads/bar/baz").is_err());
+    assert!(git_repo.find_reference("refs/heads/bar/baz/qux").is_err());
+    assert!(git_repo.find_reference("refs/heads/foo/new").is_err());
+}
+
 #[test]
 fn test_export_reexport_transitions() {
     // Test exporting after making changes on the jj side, or the git side, or both
@@ -2379,7 +2698,7 @@ fn test_reset_head_detached_out_of_sync() {
     );
 
     // Import the HEAD moved by external process
-    git::import_head(tx.repo_mut()).unwrap();
+    git::import_head(tx.repo_mut(), &GitSettings::default()).unwrap();
     assert_eq!(
         tx.repo().git_head(),
         RefTarget::normal(commit5.id().clone())
@@ -3065,6 +3384,25 @@ fn test_fetch_no_such_remote(subprocess: bool) {
     assert!(matches!(result, Err(GitFetchError::NoSuchRemote(_))));
 }
 
+#[test]
+fn test_fetch_conflicting_shallow_options() {
+    let test_data = GitRepoData::create();
+    let git_settings = GitSettings::default();
+    let mut tx = test_data.repo.start_transaction();
+    let mut git_fetch = GitFetch::new(tx.repo_mut(), &git_settings).unwrap();
+    let shallow = GitShallowOptions {
+        deepen_not: vec!["main".to_owned()],
+        ..GitShallowOptions::with_depth(NonZeroU32::new(1).unwrap())
+    };
+    let result = git_fetch.fetch(
+        "origin",
+        &[StringPattern::everything()],
+        git::RemoteCallbacks::default(),
+        &shallow,
+    );
+    assert_matches!(result, Err(GitFetchError::ConflictingShallowOptions));
+}
+
 #[test]
 fn test_fetch_multiple_branches() {
     let test_data = GitRepoData::create();
@@ -3213,6 +3551,7 @@ fn test_push_bookmarks_success(subprocess: bool) {
                 new_target: Some(setup.child_of_main_commit.id().clone()),
             },
         )],
+        remote_names: HashMap::new(),
     };
     let result = git::push_branches(
         tx.repo_mut(),
@@ -3258,6 +3597,66 @@ fn test_push_bookmarks_success(subprocess: bool) {
     assert!(!tx.repo().has_changes());
 }
 
+#[test_case(false; "use git2 for remote calls")]
+#[test_case(true; "spawn a git subprocess for remote calls")]
+fn test_push_bookmarks_renamed(subprocess: bool) {
+    let settings = testutils::user_settings();
+    let temp_dir = testutils::new_temp_dir();
+    let setup = set_up_push_repos(&settings, &temp_dir);
+    let clone_repo = get_git_repo(&setup.jj_repo);
+    let mut tx = setup.jj_repo.start_transaction();
+    let git_settings = get_git_settings(subprocess);
+
+    // Push local "main" to a new remote branch "feature"
+    let targets = GitBranchPushTargets {
+        branch_updates: vec![(
+            "main".to_owned(),
+            BookmarkPushUpdate {
+                old_target: None,
+                new_target: Some(setup.child_of_main_commit.id().clone()),
+            },
+        )],
+        remote_names: HashMap::from([("main".to_owned(), "feature".to_owned())]),
+    };
+    let result = git::push_branches(
+        tx.repo_mut(),
+        &git_settings,
+        "origin",
+        &targets,
+        git::RemoteCallbacks::default(),
+    );
+    assert_matches!(result, Ok(()));
+
+    // Check that only the renamed ref got updated in the source repo
+    let source_repo = testutils::git::open(&setup.source_repo_dir);
+    let new_oid = git_id(&setup.child_of_main_commit);
+    let new_target = source_repo.find_reference("refs/heads/feature").unwrap();
+    assert_eq!(new_target.target().id(), new_oid);
+    let main_target = source_repo.find_reference("refs/heads/main").unwrap();
+    assert_eq!(main_target.target().id(), git_id(&setup.main_commit));
+    let new_target = clone_repo
+        .find_reference("refs/remotes/origin/feature")
+        .unwrap();
+    assert_eq!(new_target.target().id(), new_oid);
+
+    // The renamed remote bookmark isn't tracked by the local bookmark
+    let view = tx.repo().view();
+    assert_eq!(
+        *view.get_remote_bookmark(remote_symbol("feature", "origin")),
+        RemoteRef {
+            target: RefTarget::normal(setup.child_of_main_commit.id().clone()),
+            state: RemoteRefState::New,
+        },
+    );
+    assert_eq!(
+        *view.get_remote_bookmark(remote_symbol("main", "origin")),
+        RemoteRef {
+            target: RefTarget::normal(setup.main_commit.id().clone()),
+            state: RemoteRefState::Tracking,
+        },
+    );
+}
+
 #[test_case(false; "use git2 for remote calls")]
 #[test_case(true; "spawn a git subprocess for remote calls")]
 fn test_push_bookmarks_deletion(subprocess: bool) {
@@ -3280,6 +3679,7 @@ fn test_push_bookmarks_deletion(subprocess: bool) {
                 new_target: None,
             },
         )],
+        remote_names: HashMap::new(),
     };
     let result = git::push_branches(
         tx.repo_mut(),
@@ -3340,6 +3740,7 @@ fn test_push_bookmarks_mixed_deletion_and_addition(subprocess: bool) {
                 },
             ),
         ],
+        remote_names: HashMap::new(),
     };
     let result = git::push_branches(
         tx.repo_mut(),
@@ -3403,6 +3804,7 @@ fn test_push_bookmarks_not_fast_forward(subprocess: bool) {
                 new_target: Some(setup.sideways_commit.id().clone()),
             },
         )],
+        remote_names: HashMap::new(),
     };
     let result = git::push_branches(
         tx.repo_mut(),
@@ -3693,6 +4095,331 @@ fn test_push_updates_invalid_remote(subprocess: bool) {
     assert!(matches!(result, Err(GitPushError::NoSuchRemote(_))));
 }
 
+#[cfg(feature = "git-native-http")]
+fn get_native_http_git_settings() -> GitSettings {
+    GitSettings {
+        native_http: true,
+        ..Default::default()
+    }
+}
+
+#[cfg(feature = "git-native-http")]
+fn set_origin_url(repo: &ReadonlyRepo, jj_repo_dir: &Path, url: &str) -> Arc<ReadonlyRepo> {
+    git::set_remote_url(repo.store(), "origin", url).unwrap();
+    // Reload after Git configuration change.
+    RepoLoader::init_from_file_system(
+        &testutils::user_settings(),
+        jj_repo_dir,
+        &StoreFactories::default(),
+    )
+    .unwrap()
+    .load_at_head()
+    .unwrap()
+}
+
+#[cfg(feature = "git-native-http")]
+#[test]
+fn test_fetch_native_http() {
+    let mut test_data = GitRepoData::create();
+    let server = testutils::git::HttpServer::start(test_data.origin_repo.path().parent().unwrap());
+    test_data.repo = set_origin_url(
+        &test_data.repo,
+        &test_data._temp_dir.path().join("jj"),
+        &server.url("source"),
+    );
+    let git_settings = GitSettings {
+        auto_local_bookmark: true,
+        ..get_native_http_git_settings()
+    };
+    let initial_git_commit = empty_git_commit(&test_data.origin_repo, "refs/heads/main", &[]);
+    let feature_git_commit = empty_git_commit(
+        &test_data.origin_repo,
+        "refs/heads/feature",
+        &[initial_git_commit],
+    );
+    testutils::git::set_symbolic_reference(&test_data.origin_repo, "HEAD", "refs/heads/main");
+
+    let mut tx = test_data.repo.start_transaction();
+    let stats = git_fetch(
+        tx.repo_mut(),
+        "origin",
+        &[StringPattern::everything()],
+        &git_settings,
+    )
+    .unwrap();
+    assert_eq!(stats.default_branch, Some("main".to_string()));
+    assert!(stats.import_stats.abandoned_commits.is_empty());
+    assert_eq!(
+        *tx.repo().view().git_refs(),
+        btreemap! {
+            "refs/remotes/origin/feature".to_string() =>
+                RefTarget::normal(jj_id(feature_git_commit)),
+            "refs/remotes/origin/main".to_string() =>
+                RefTarget::normal(jj_id(initial_git_commit)),
+        }
+    );
+    test_data.repo = tx.commit("test").unwrap();
+
+    // Deleted branches are pruned on the next fetch
+    test_data
+        .origin_repo
+        .find_reference("refs/heads/feature")
+        .unwrap()
+        .delete()
+        .unwrap();
+    let mut tx = test_data.repo.start_transaction();
+    let stats = git_fetch(
+        tx.repo_mut(),
+        "origin",
+        &[StringPattern::everything()],
+        &git_settings,
+    )
+    .unwrap();
+    assert_eq!(
+        stats.import_stats.abandoned_commits,
+        vec![jj_id(feature_git_commit)]
+    );
+    assert!(tx.repo().get_local_bookmark("feature").is_absent());
+    assert!(tx
+        .repo()
+        .get_remote_bookmark(remote_symbol("feature", "origin"))
+        .is_absent());
+    assert!(tx.repo().get_local_bookmark("main").is_present());
+}
+
+#[cfg(feature = "git-native-http")]
+#[test]
+fn test_fetch_native_http_unsupported_tls_settings() {
+    let mut test_data = GitRepoData::create();
+    let server = testutils::git::HttpServer::start(test_data.origin_repo.path().parent().unwrap());
+    test_data.repo = set_origin_url(
+        &test_data.repo,
+        &test_data._temp_dir.path().join("jj"),
+        &server.url("source"),
+    );
+    let git_settings = GitSettings {
+        http: GitHttpSettings {
+            ssl_ca_info: Some("ca-bundle.pem".into()),
+            ..Default::default()
+        },
+        ..get_native_http_git_settings()
+    };
+    empty_git_commit(&test_data.origin_repo, "refs/heads/main", &[]);
+
+    // The TLS settings aren't silently ignored
+    let mut tx = test_data.repo.start_transaction();
+    let result = git_fetch(
+        tx.repo_mut(),
+        "origin",
+        &[StringPattern::everything()],
+        &git_settings,
+    );
+    assert_matches!(
+        result,
+        Err(GitFetchError::NativeHttp(GitHttpError::UnsupportedSetting(
+            "git.http.ssl-ca-info"
+        )))
+    );
+}
+
+#[cfg(feature = "git-native-http")]
+#[test]
+fn test_push_native_http() {
+    let settings = testutils::user_settings();
+    let temp_dir = testutils::new_temp_dir();
+    let mut setup = set_up_push_repos(&settings, &temp_dir);
+    let server = testutils::git::HttpServer::start(temp_dir.path());
+    setup.jj_repo = set_origin_url(
+        &setup.jj_repo,
+        &temp_dir.path().join("jj"),
+        &server.url("source"),
+    );
+    let clone_repo = get_git_repo(&setup.jj_repo);
+    let mut tx = setup.jj_repo.start_transaction();
+    let git_settings = get_native_http_git_settings();
+
+    let targets = GitBranchPushTargets {
+        branch_updates: vec![
+            (
+                "main".to_owned(),
+                BookmarkPushUpdate {
+                    old_target: Some(setup.main_commit.id().clone()),
+                    new_target: Some(setup.child_of_main_commit.id().clone()),
+                },
+            ),
+            (
+                "topic".to_owned(),
+                BookmarkPushUpdate {
+                    old_target: None,
+                    new_target: Some(setup.sideways_commit.id().clone()),
+                },
+            ),
+        ],
+        remote_names: HashMap::new(),
+    };
+    let result = git::push_branches(
+        tx.repo_mut(),
+        &git_settings,
+        "origin",
+        &targets,
+        git::RemoteCallbacks::default(),
+    );
+    assert_matches!(result, Ok(()));
+
+    // Check that the refs got updated in the source repo, and that the pushed
+    // objects are complete
+    let source_repo = testutils::git::open(&setup.source_repo_dir);
+    let new_target = source_repo.find_reference("refs/heads/main").unwrap();
+    assert_eq!(
+        new_target.target().id(),
+        git_id(&setup.child_of_main_commit)
+    );
+    let new_target = source_repo.find_reference("refs/heads/topic").unwrap();
+    let sideways_oid = git_id(&setup.sideways_commit);
+    assert_eq!(new_target.target().id(), sideways_oid);
+    source_repo
+        .find_commit(sideways_oid)
+        .unwrap()
+        .tree()
+        .unwrap();
+
+    // Check that the remote-tracking refs got updated in the cloned repo
+    let new_target = clone_repo
+        .find_reference("refs/remotes/origin/main")
+        .unwrap();
+    assert_eq!(
+        new_target.target().id(),
+        git_id(&setup.child_of_main_commit)
+    );
+
+    // Check that the repo view reflects the changes in the Git repo
+    setup.jj_repo = tx.commit("test").unwrap();
+    let mut tx = setup.jj_repo.start_transaction();
+    git::import_refs(tx.repo_mut(), &GitSettings::default()).unwrap();
+    assert!(!tx.repo().has_changes());
+
+    // Deleting a bookmark on the remote
+    let result = git::push_updates(
+        setup.jj_repo.as_ref(),
+        &git_settings,
+        "origin",
+        &[GitRefUpdate {
+            qualified_name: "refs/heads/topic".to_string(),
+            expected_current_target: Some(setup.sideways_commit.id().clone()),
+            new_target: None,
+        }],
+        git::RemoteCallbacks::default(),
+    );
+    assert_matches!(result, Ok(()));
+    let source_repo = testutils::git::open(&setup.source_repo_dir);
+    assert!(source_repo
+        .try_find_reference("refs/heads/topic")
+        .unwrap()
+        .is_none());
+    assert!(clone_repo
+        .try_find_reference("refs/remotes/origin/topic")
+        .unwrap()
+        .is_none());
+}
+
+#[cfg(feature = "git-native-http")]
+#[test]
+fn test_push_native_http_merge() {
+    let settings = testutils::user_settings();
+    let temp_dir = testutils::new_temp_dir();
+    let mut setup = set_up_push_repos(&settings, &temp_dir);
+    let server = testutils::git::HttpServer::start(temp_dir.path());
+    setup.jj_repo = set_origin_url(
+        &setup.jj_repo,
+        &temp_dir.path().join("jj"),
+        &server.url("source"),
+    );
+    let path1 = RepoPath::from_internal_string("dir/file1");
+    let path2 = RepoPath::from_internal_string("dir/file2");
+    let tree1 = testutils::create_tree(&setup.jj_repo, &[(path1, "1")]);
+    let tree2 = testutils::create_tree(&setup.jj_repo, &[(path2, "2")]);
+    let merged_tree = testutils::create_tree(&setup.jj_repo, &[(path1, "1"), (path2, "2")]);
+    let mut tx = setup.jj_repo.start_transaction();
+    let commit1 = tx
+        .repo_mut()
+        .new_commit(vec![setup.main_commit.id().clone()], tree1.id())
+        .write()
+        .unwrap();
+    let commit2 = tx
+        .repo_mut()
+        .new_commit(vec![setup.main_commit.id().clone()], tree2.id())
+        .write()
+        .unwrap();
+    let merge_commit = tx
+        .repo_mut()
+        .new_commit(
+            vec![commit1.id().clone(), commit2.id().clone()],
+            merged_tree.id(),
+        )
+        .write()
+        .unwrap();
+    setup.jj_repo = tx.commit("test").unwrap();
+
+    let result = git::push_updates(
+        setup.jj_repo.as_ref(),
+        &get_native_http_git_settings(),
+        "origin",
+        &[GitRefUpdate {
+            qualified_name: "refs/heads/main".to_string(),
+            expected_current_target: Some(setup.main_commit.id().clone()),
+            new_target: Some(merge_commit.id().clone()),
+        }],
+        git::RemoteCallbacks::default(),
+    );
+    assert_matches!(result, Ok(()));
+
+    // The objects added by both sides of the merge were sent
+    let source_repo = testutils::git::open(&setup.source_repo_dir);
+    let tree = source_repo
+        .find_commit(git_id(&merge_commit))
+        .unwrap()
+        .tree()
+        .unwrap();
+    for path in ["dir/file1", "dir/file2"] {
+        let entry = tree.lookup_entry_by_path(path).unwrap().unwrap();
+        let blob = entry.object().unwrap().into_blob();
+        assert_eq!(blob.data, path.strip_prefix("dir/file").unwrap().as_bytes());
+    }
+}
+
+#[cfg(feature = "git-native-http")]
+#[test]
+fn test_push_native_http_unexpectedly_moved_on_remote() {
+    let settings = testutils::user_settings();
+    let temp_dir = testutils::new_temp_dir();
+    let mut setup = set_up_push_repos(&settings, &temp_dir);
+    let server = testutils::git::HttpServer::start(temp_dir.path());
+    setup.jj_repo = set_origin_url(
+        &setup.jj_repo,
+        &temp_dir.path().join("jj"),
+        &server.url("source"),
+    );
+    let git_settings = get_native_http_git_settings();
+
+    let result = git::push_updates(
+        setup.jj_repo.as_ref(),
+        &git_settings,
+        "origin",
+        &[GitRefUpdate {
+            qualified_name: "refs/heads/main".to_string(),
+            expected_current_target: Some(setup.sideways_commit.id().clone()),
+            new_target: Some(setup.child_of_main_commit.id().clone()),
+        }],
+        git::RemoteCallbacks::default(),
+    );
+    assert_matches!(result, Err(GitPushError::RefInUnexpectedLocation(_)));
+
+    // The remote is left untouched
+    let source_repo = testutils::git::open(&setup.source_repo_dir);
+    let target = source_repo.find_reference("refs/heads/main").unwrap();
+    assert_eq!(target.target().id(), git_id(&setup.main_commit));
+}
+
 #[test]
 fn test_bulk_update_extra_on_import_refs() {
     let git_settings = GitSettings::default();
@@ -4138,8 +4865,25 @@ fn test_shallow_commits_lack_parents() {
         vec![jj_id(a)],
         "unshallowed commits have correct parents"
     );
-    // FIXME: new ancestors should be indexed
+    // The new ancestors aren't indexed until the index is rebuilt
     assert!(!repo.index().has_id(&jj_id(a)));
+    let (repo, stats) = git::deepen_shallow(&repo).unwrap();
+    assert_eq!(
+        stats.deepened_commits.into_iter().collect::<HashSet<_>>(),
+        hashset! {jj_id(b), jj_id(c)}
+    );
+    assert_eq!(stats.num_indexed_commits, Some(6));
+    assert!(repo.index().has_id(&jj_id(a)));
+    assert_eq!(
+        repo.index().common_ancestors(&[jj_id(d)], &[jj_id(e)]),
+        vec![jj_id(a)],
+        "deepened commits are indexed with their parents"
+    );
+
+    // Nothing to do if the index is up to date
+    let (_, stats) = git::deepen_shallow(&repo).unwrap();
+    assert!(stats.deepened_commits.is_empty());
+    assert_eq!(stats.num_indexed_commits, None);
 }
 
 #[test]
@@ -25,6 +25,7 @@ use jj_lib::backend::CommitId;
 use jj_lib::backend::CopyRecord;
 use jj_lib::commit::Commit;
 use jj_lib::git_backend::GitBackend;
+use jj_lib::object_id::ObjectId as _;
 use jj_lib::repo::ReadonlyRepo;
 use jj_lib::repo::Repo;
 use jj_lib::repo_path::RepoPath;
@@ -232,6 +233,86 @@ fn test_gc() {
     // All unreachable
     repo.store().gc(base_index.as_index(), now()).unwrap();
     assert_eq!(collect_no_gc_refs(git_repo_path), hashset! {});
+
+    // No-gc refs created by external tools are preserved
+    let git_repo = gix::open(git_repo_path).unwrap();
+    git_repo
+        .reference(
+            "refs/jj/keep/ci/build-1",
+            gix::ObjectId::from_bytes_or_panic(commit_g.id().as_bytes()),
+            gix::refs::transaction::PreviousValue::Any,
+            "",
+        )
+        .unwrap();
+    repo.store().gc(base_index.as_index(), now()).unwrap();
+    assert_eq!(
+        collect_no_gc_refs(git_repo_path),
+        hashset! {
+            commit_g.id().clone(),
+        },
+    );
+    let git_repo = gix::open(git_repo_path).unwrap();
+    assert!(git_repo
+        .find_commit(gix::ObjectId::from_bytes_or_panic(commit_g.id().as_bytes()))
+        .is_ok());
+}
+
+#[test]
+fn test_gc_keeps_commits_reachable_from_fresh_index() {
+    // TODO: Better way to disable the test if git command couldn't be executed
+    if Command::new("git").arg("--version").status().is_err() {
+        eprintln!("Skipping because git command might fail to run");
+        return;
+    }
+
+    let test_repo = TestRepo::init_with_backend(TestRepoBackend::Git);
+    let repo = test_repo.repo;
+    let git_repo_path = get_git_backend(&repo).git_repo_path();
+    let base_index = repo.readonly_index();
+
+    // Set up commits:
+    //
+    // B C
+    // |/
+    // A
+    let mut tx = repo.start_transaction();
+    let mut graph_builder = CommitGraphBuilder::new(tx.repo_mut());
+    let commit_a = graph_builder.initial_commit();
+    let commit_b = graph_builder.commit_with_parents(&[&commit_a]);
+    let commit_c = graph_builder.commit_with_parents(&[&commit_a]);
+    let repo = tx.commit("test").unwrap();
+
+    let now = || SystemTime::now() + Duration::from_secs(1);
+    let mut stale_index = base_index.start_modification();
+    stale_index.add_commit(&commit_a);
+    stale_index.add_commit(&commit_b);
+
+    // C isn't reachable from the stale index, but is from the fresh one
+    repo.store()
+        .prepare_gc(stale_index.as_index(), now())
+        .unwrap();
+    repo.store().finish_gc(repo.index(), now()).unwrap();
+    assert_eq!(
+        collect_no_gc_refs(git_repo_path),
+        hashset! {
+            commit_b.id().clone(),
+            commit_c.id().clone(),
+        },
+    );
+
+    // C is unreachable from both
+    repo.store()
+        .prepare_gc(stale_index.as_index(), now())
+        .unwrap();
+    repo.store()
+        .finish_gc(stale_index.as_index(), now())
+        .unwrap();
+    assert_eq!(
+        collect_no_gc_refs(git_repo_path),
+        hashset! {
+            commit_b.id().clone(),
+        },
+    );
 }
 
 #[test]
@@ -19,10 +19,12 @@ use std::os::unix::net::UnixListener;
 use std::path::Path;
 use std::path::PathBuf;
 use std::sync::Arc;
+use std::time::Duration;
 
 use assert_matches::assert_matches;
 use indoc::indoc;
 use itertools::Itertools;
+use jj_lib::backend::CommitId;
 use jj_lib::backend::MergedTreeId;
 use jj_lib::backend::TreeId;
 use jj_lib::backend::TreeValue;
@@ -46,6 +48,8 @@ use jj_lib::secret_backend::SecretBackend;
 use jj_lib::working_copy::CheckoutError;
 use jj_lib::working_copy::CheckoutOptions;
 use jj_lib::working_copy::CheckoutStats;
+use jj_lib::working_copy::NestedRepositoryPolicy;
+use jj_lib::working_copy::SnapshotError;
 use jj_lib::working_copy::SnapshotOptions;
 use jj_lib::working_copy::UntrackedReason;
 use jj_lib::working_copy::WorkingCopy;
@@ -788,6 +792,72 @@ fn test_checkout_discard() {
     assert!(!reloaded_wc.file_states().unwrap().contains_path(file2_path));
 }
 
+#[test]
+fn test_checkout_undo_stash() {
+    // Locally-modified files should be stashed before they are overwritten or
+    // removed by checkout. Clean files shouldn't.
+    let mut test_workspace = TestWorkspace::init();
+    let repo = &test_workspace.repo;
+    let workspace_root = test_workspace.workspace.workspace_root().to_owned();
+
+    let modified_path = RepoPath::from_internal_string("dir/modified");
+    let removed_path = RepoPath::from_internal_string("removed");
+    let clean_path = RepoPath::from_internal_string("clean");
+
+    let tree1 = create_tree(
+        repo,
+        &[
+            (modified_path, "contents"),
+            (removed_path, "contents"),
+            (clean_path, "contents"),
+        ],
+    );
+    let tree2 = create_tree(repo, &[(modified_path, "new contents")]);
+    let commit1 = commit_with_tree(repo.store(), tree1.id());
+    let commit2 = commit_with_tree(repo.store(), tree2.id());
+    let options = CheckoutOptions {
+        undo_stash_retention: Some(Duration::from_secs(24 * 60 * 60)),
+        ..CheckoutOptions::empty_for_test()
+    };
+
+    let ws = &mut test_workspace.workspace;
+    ws.check_out(repo.op_id().clone(), None, &commit1, &options)
+        .unwrap();
+    let wc: &LocalWorkingCopy = ws.working_copy().as_any().downcast_ref().unwrap();
+    assert!(wc.undo_stash().entries().unwrap().is_empty());
+
+    std::fs::write(
+        modified_path.to_fs_path_unchecked(&workspace_root),
+        "modified contents",
+    )
+    .unwrap();
+    std::fs::write(
+        removed_path.to_fs_path_unchecked(&workspace_root),
+        "removed contents",
+    )
+    .unwrap();
+    ws.check_out(repo.op_id().clone(), None, &commit2, &options)
+        .unwrap();
+    let wc: &LocalWorkingCopy = ws.working_copy().as_any().downcast_ref().unwrap();
+    let entries = wc.undo_stash().entries().unwrap();
+    assert_eq!(entries.len(), 1);
+    assert_eq!(
+        entries[0].files().unwrap(),
+        [modified_path.to_owned(), removed_path.to_owned()]
+    );
+
+    // Restoring the entry brings back the replaced contents
+    entries[0].restore(&workspace_root).unwrap();
+    assert_eq!(
+        std::fs::read_to_string(modified_path.to_fs_path_unchecked(&workspace_root)).unwrap(),
+        "modified contents"
+    );
+    assert_eq!(
+        std::fs::read_to_string(removed_path.to_fs_path_unchecked(&workspace_root)).unwrap(),
+        "removed contents"
+    );
+}
+
 #[test]
 fn test_snapshot_file_directory_transition() {
     let mut test_workspace = TestWorkspace::init();
@@ -983,6 +1053,75 @@ fn test_snapshot_racy_timestamps() {
     }
 }
 
+#[test]
+fn test_detect_changes() {
+    let mut test_workspace = TestWorkspace::init();
+    let repo = test_workspace.repo.clone();
+    let workspace_root = test_workspace.workspace.workspace_root().to_owned();
+    let checkout_options = CheckoutOptions::empty_for_test();
+    let to_ws_path = |path: &RepoPath| path.to_fs_path(&workspace_root).unwrap();
+
+    let modified_path = RepoPath::from_internal_string("modified");
+    let rewritten_path = RepoPath::from_internal_string("rewritten");
+    let deleted_path = RepoPath::from_internal_string("dir/deleted");
+    let added_path = RepoPath::from_internal_string("dir/added");
+    let tree = create_tree(
+        &repo,
+        &[
+            (modified_path, "a"),
+            (rewritten_path, "b"),
+            (deleted_path, "c"),
+        ],
+    );
+    let commit = commit_with_tree(repo.store(), tree.id());
+    let ws = &mut test_workspace.workspace;
+    ws.check_out(repo.op_id().clone(), None, &commit, &checkout_options)
+        .unwrap();
+
+    std::fs::write(to_ws_path(modified_path), "modified").unwrap();
+    // Same contents with new mtime
+    std::fs::remove_file(to_ws_path(rewritten_path)).unwrap();
+    std::fs::write(to_ws_path(rewritten_path), "b").unwrap();
+    std::fs::remove_file(to_ws_path(deleted_path)).unwrap();
+    std::fs::write(to_ws_path(added_path), "d").unwrap();
+
+    let wc: &LocalWorkingCopy = ws.working_copy().as_any().downcast_ref().unwrap();
+    let changes = wc
+        .detect_changes(&SnapshotOptions::empty_for_test())
+        .unwrap();
+    assert_eq!(
+        changes
+            .changed_paths
+            .iter()
+            .map(AsRef::as_ref)
+            .collect_vec(),
+        [added_path, modified_path]
+    );
+    assert_eq!(
+        changes
+            .deleted_paths
+            .iter()
+            .map(AsRef::as_ref)
+            .collect_vec(),
+        [deleted_path]
+    );
+    assert!(changes.untracked_paths.is_empty());
+    // The working-copy state isn't updated
+    assert_eq!(*wc.tree_id().unwrap(), tree.id());
+
+    // The changes are recorded by the next snapshot
+    let new_tree = test_workspace.snapshot().unwrap();
+    let expected_tree = create_tree(
+        &repo,
+        &[
+            (modified_path, "modified"),
+            (rewritten_path, "b"),
+            (added_path, "d"),
+        ],
+    );
+    assert_eq!(new_tree.id(), expected_tree.id());
+}
+
 #[cfg(unix)]
 #[test]
 fn test_snapshot_special_file() {
@@ -1447,6 +1586,133 @@ fn test_git_submodule(gitignore_content: &str) {
     assert_eq!(stats.skipped_files, 1);
 }
 
+#[test]
+fn test_nested_repository_ignore() {
+    let mut test_workspace = TestWorkspace::init();
+    let workspace_root = test_workspace.workspace.workspace_root().to_owned();
+    let tracked_path = RepoPath::from_internal_string("nested_git/tracked");
+    let untracked_path = RepoPath::from_internal_string("nested_git/untracked");
+    let jj_file_path = RepoPath::from_internal_string("nested_jj/file");
+    let regular_path = RepoPath::from_internal_string("regular/file");
+
+    // A file tracked before the nested repository was created
+    testutils::write_working_copy_file(&workspace_root, tracked_path, "initial");
+    test_workspace.snapshot().unwrap();
+
+    std::fs::create_dir(workspace_root.join("nested_git").join(".git")).unwrap();
+    testutils::write_working_copy_file(
+        &workspace_root,
+        RepoPath::from_internal_string("nested_git/.git/HEAD"),
+        "ref: refs/heads/main\n",
+    );
+    std::fs::create_dir_all(workspace_root.join("nested_jj").join(".jj")).unwrap();
+    testutils::write_working_copy_file(&workspace_root, tracked_path, "modified");
+    testutils::write_working_copy_file(&workspace_root, untracked_path, "untracked");
+    testutils::write_working_copy_file(&workspace_root, jj_file_path, "untracked");
+    testutils::write_working_copy_file(&workspace_root, regular_path, "regular");
+
+    let tree = test_workspace.snapshot().unwrap();
+    assert_eq!(
+        tree.entries().map(|(path, _)| path).collect_vec(),
+        [tracked_path.to_owned(), regular_path.to_owned()]
+    );
+    assert_eq!(
+        tree.path_value(tracked_path).unwrap(),
+        create_tree(&test_workspace.repo, &[(tracked_path, "modified")])
+            .path_value(tracked_path)
+            .unwrap()
+    );
+
+    // Deleted files in the nested repository are still noticed
+    std::fs::remove_file(tracked_path.to_fs_path_unchecked(&workspace_root)).unwrap();
+    let tree = test_workspace.snapshot().unwrap();
+    assert_eq!(
+        tree.entries().map(|(path, _)| path).collect_vec(),
+        [regular_path.to_owned()]
+    );
+}
+
+#[test]
+fn test_nested_repository_error() {
+    let mut test_workspace = TestWorkspace::init();
+    let workspace_root = test_workspace.workspace.workspace_root().to_owned();
+    let options = SnapshotOptions {
+        nested_repository_policy: NestedRepositoryPolicy::Error,
+        ..SnapshotOptions::empty_for_test()
+    };
+
+    std::fs::create_dir_all(workspace_root.join("sub").join("nested").join(".jj")).unwrap();
+    assert_matches!(
+        test_workspace.snapshot_with_options(&options),
+        Err(SnapshotError::NestedRepository { path })
+            if path == workspace_root.join("sub").join("nested")
+    );
+
+    // Ignored directories aren't checked
+    testutils::write_working_copy_file(
+        &workspace_root,
+        RepoPath::from_internal_string(".gitignore"),
+        "/sub/nested/\n",
+    );
+    assert!(test_workspace.snapshot_with_options(&options).is_ok());
+}
+
+#[test]
+fn test_nested_repository_submodule() {
+    let mut test_workspace = TestWorkspace::init_with_backend(TestRepoBackend::Git);
+    let workspace_root = test_workspace.workspace.workspace_root().to_owned();
+    let options = SnapshotOptions {
+        nested_repository_policy: NestedRepositoryPolicy::Submodule,
+        ..SnapshotOptions::empty_for_test()
+    };
+    let nested_path = RepoPath::from_internal_string("nested");
+    let tracked_path = RepoPath::from_internal_string("nested/file");
+
+    // A nested repository with unborn HEAD is ignored
+    testutils::write_working_copy_file(&workspace_root, tracked_path, "contents");
+    test_workspace.snapshot_with_options(&options).unwrap();
+    let nested_repo = testutils::git::init(nested_path.to_fs_path_unchecked(&workspace_root));
+    let (tree, _stats) = test_workspace.snapshot_with_options(&options).unwrap();
+    assert_eq!(
+        tree.entries().map(|(path, _)| path).collect_vec(),
+        [tracked_path.to_owned()]
+    );
+
+    // Once something is committed, the tracked files are replaced by a pointer
+    // to the HEAD commit
+    let commit1 = testutils::git::add_commit(&nested_repo, "HEAD", "file", b"contents", "1", &[]);
+    let (tree, _stats) = test_workspace.snapshot_with_options(&options).unwrap();
+    assert_eq!(
+        tree.entries()
+            .map(|(path, value)| (path, value.unwrap()))
+            .collect_vec(),
+        [(
+            nested_path.to_owned(),
+            Merge::normal(TreeValue::GitSubmodule(CommitId::from_bytes(
+                commit1.commit_id.as_bytes()
+            )))
+        )]
+    );
+    assert!(tracked_path.to_fs_path_unchecked(&workspace_root).exists());
+
+    // The pointer follows the nested HEAD
+    let commit2 = testutils::git::add_commit(
+        &nested_repo,
+        "HEAD",
+        "file",
+        b"modified",
+        "2",
+        &[commit1.commit_id],
+    );
+    let (tree, _stats) = test_workspace.snapshot_with_options(&options).unwrap();
+    assert_eq!(
+        tree.path_value(nested_path).unwrap(),
+        Merge::normal(TreeValue::GitSubmodule(CommitId::from_bytes(
+            commit2.commit_id.as_bytes()
+        )))
+    );
+}
+
 #[test]
 fn test_check_out_existing_file_cannot_be_removed() {
     let mut test_workspace = TestWorkspace::init();
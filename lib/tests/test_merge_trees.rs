@@ -15,12 +15,15 @@
 use itertools::Itertools;
 use jj_lib::backend::MergedTreeId;
 use jj_lib::backend::TreeValue;
+use jj_lib::config::ConfigLayer;
+use jj_lib::config::ConfigSource;
 use jj_lib::merge::Merge;
 use jj_lib::repo::Repo;
 use jj_lib::repo_path::RepoPath;
 use jj_lib::repo_path::RepoPathBuf;
 use jj_lib::repo_path::RepoPathComponent;
 use jj_lib::rewrite::rebase_commit;
+use jj_lib::settings::UserSettings;
 use jj_lib::tree::merge_trees;
 use jj_lib::tree::Tree;
 use testutils::create_single_tree;
@@ -626,6 +629,70 @@ fn test_simplify_conflict_after_resolving_parent() {
     }
 }
 
+#[test]
+fn test_merge_normalized_contents() {
+    let settings = {
+        let mut config = testutils::base_user_config();
+        let mut layer = ConfigLayer::empty(ConfigSource::User);
+        layer.set_value("merge.normalize-eol", true).unwrap();
+        layer
+            .set_value("merge.normalize-trailing-whitespace", true)
+            .unwrap();
+        config.add_layer(layer);
+        UserSettings::from_config(config).unwrap()
+    };
+    let test_repo = TestRepo::init_with_settings(&settings);
+    let repo = &test_repo.repo;
+
+    let eol_path = RepoPath::from_internal_string("eol");
+    let whitespace_path = RepoPath::from_internal_string("whitespace");
+    let conflict_path = RepoPath::from_internal_string("conflict");
+    let base = create_tree(
+        repo,
+        &[
+            (eol_path, "a\nb\n"),
+            (whitespace_path, "a\n"),
+            (conflict_path, "a\n"),
+        ],
+    );
+    // One side converts the line endings to CRLF, and adds trailing whitespace
+    let side1 = create_tree(
+        repo,
+        &[
+            (eol_path, "a\r\nb\r\n"),
+            (whitespace_path, "a \n"),
+            (conflict_path, "b\r\n"),
+        ],
+    );
+    let side2 = create_tree(
+        repo,
+        &[
+            (eol_path, "a\nB\n"),
+            (whitespace_path, "a\t\n"),
+            (conflict_path, "c\n"),
+        ],
+    );
+    let merged = side1.merge(&base, &side2).unwrap();
+
+    // The original content of the resolved side is used
+    let read_resolved = |path| match merged.path_value(path).unwrap().into_resolved() {
+        Ok(Some(TreeValue::File { id, .. })) => testutils::read_file(repo.store(), path, &id),
+        other => panic!("unexpected value: {other:#?}"),
+    };
+    assert_eq!(read_resolved(eol_path), b"a\nB\n");
+    assert_eq!(read_resolved(whitespace_path), b"a \n");
+    assert!(!merged.path_value(conflict_path).unwrap().is_resolved());
+
+    // Without normalization, the changes conflict
+    let test_repo = TestRepo::init();
+    let repo = &test_repo.repo;
+    let base = create_tree(repo, &[(eol_path, "a\nb\n")]);
+    let side1 = create_tree(repo, &[(eol_path, "a\r\nb\r\n")]);
+    let side2 = create_tree(repo, &[(eol_path, "a\nB\n")]);
+    let merged = side1.merge(&base, &side2).unwrap();
+    assert!(!merged.path_value(eol_path).unwrap().is_resolved());
+}
+
 // TODO: Add tests for simplification of multi-way conflicts. Both the content
 // and the executable bit need testing.
 
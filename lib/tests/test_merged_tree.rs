@@ -545,6 +545,49 @@ fn test_resolve_with_conflict() {
     );
 }
 
+#[test]
+fn test_resolve_cached() {
+    let test_repo = TestRepo::init();
+    let repo = &test_repo.repo;
+    let store = repo.store();
+
+    let trivial_path = RepoPath::from_internal_string("dir1/trivial");
+    let conflict_path = RepoPath::from_internal_string("dir2/file_conflict");
+    let base1 = create_single_tree(repo, &[(trivial_path, "base1"), (conflict_path, "base1")]);
+    let side1 = create_single_tree(repo, &[(trivial_path, "side1"), (conflict_path, "side1")]);
+    let side2 = create_single_tree(repo, &[(trivial_path, "base1"), (conflict_path, "side2")]);
+
+    let tree = MergedTree::new(Merge::from_removes_adds(vec![base1], vec![side1, side2]));
+    let tree_ids = tree.as_merge().map(|tree| tree.id().clone());
+    assert_eq!(store.get_resolved_tree_merge(&tree_ids), None);
+    let resolved_tree = tree.resolve().unwrap();
+    let resolved_ids = resolved_tree.as_merge().map(|tree| tree.id().clone());
+    assert_eq!(
+        store.get_resolved_tree_merge(&tree_ids),
+        Some(resolved_ids.clone())
+    );
+    // The cached result is returned
+    assert_eq!(tree.resolve().unwrap(), resolved_tree);
+
+    // The result is saved for later commands
+    let reloaded_repo = test_repo
+        .env
+        .load_repo_at_head(&testutils::user_settings(), test_repo.repo_path());
+    assert_eq!(
+        reloaded_repo.store().get_resolved_tree_merge(&tree_ids),
+        Some(resolved_ids)
+    );
+
+    // Resolving a subtree doesn't populate the cache
+    let sub_tree = tree
+        .sub_tree(RepoPathComponent::new("dir2"))
+        .unwrap()
+        .unwrap();
+    let sub_tree_ids = sub_tree.as_merge().map(|tree| tree.id().clone());
+    sub_tree.resolve().unwrap();
+    assert_eq!(store.get_resolved_tree_merge(&sub_tree_ids), None);
+}
+
 #[test]
 fn test_resolve_with_conflict_containing_empty_subtree() {
     let test_repo = TestRepo::init();
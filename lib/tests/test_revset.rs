@@ -53,6 +53,7 @@ use jj_lib::revset::RevsetWorkspaceContext;
 use jj_lib::revset::SymbolResolver;
 use jj_lib::revset::SymbolResolverExtension;
 use jj_lib::workspace::Workspace;
+use maplit::btreeset;
 use test_case::test_case;
 use testutils::create_random_commit;
 use testutils::create_tree;
@@ -927,6 +928,7 @@ fn resolve_commit_ids_in_workspace(
     let workspace_ctx = RevsetWorkspaceContext {
         path_converter: &path_converter,
         workspace_id: workspace.workspace_id(),
+        workspace_sparse_patterns: None,
     };
     let aliases_map = RevsetAliasesMap::default();
     let extensions = RevsetExtensions::default();
@@ -2339,6 +2341,53 @@ fn test_evaluate_expression_tags() {
     );
 }
 
+#[test]
+fn test_evaluate_expression_topics() {
+    let test_repo = TestRepo::init();
+    let repo = &test_repo.repo;
+
+    let mut tx = repo.start_transaction();
+    let mut_repo = tx.repo_mut();
+
+    let commit1 = write_random_commit(mut_repo);
+    let commit2 = write_random_commit(mut_repo);
+    let commit3 = write_random_commit(mut_repo);
+
+    // Can get topics when there are none
+    assert_eq!(resolve_commit_ids(mut_repo, "topic(foo)"), vec![]);
+    mut_repo.set_topic_change_ids(
+        "foo",
+        btreeset! {commit1.change_id().clone(), commit2.change_id().clone()},
+    );
+    mut_repo.set_topic_change_ids("foobar", btreeset! {commit3.change_id().clone()});
+    assert_eq!(
+        resolve_commit_ids(mut_repo, "topic(exact:foo)"),
+        vec![commit2.id().clone(), commit1.id().clone()]
+    );
+    assert_eq!(
+        resolve_commit_ids(mut_repo, "topic(foo)"),
+        vec![
+            commit3.id().clone(),
+            commit2.id().clone(),
+            commit1.id().clone()
+        ]
+    );
+    assert_eq!(resolve_commit_ids(mut_repo, "topic(baz)"), vec![]);
+
+    // Topics follow rewritten commits, and drop abandoned ones
+    let commit1_rewritten = mut_repo
+        .rewrite_commit(&commit1)
+        .set_description("rewritten")
+        .write()
+        .unwrap();
+    mut_repo.record_abandoned_commit(&commit2);
+    mut_repo.rebase_descendants().unwrap();
+    assert_eq!(
+        resolve_commit_ids(mut_repo, "topic(exact:foo)"),
+        vec![commit1_rewritten.id().clone()]
+    );
+}
+
 #[test]
 fn test_evaluate_expression_latest() {
     let test_repo = TestRepo::init();
@@ -2544,6 +2593,82 @@ fn test_evaluate_expression_fork_point() {
     );
 }
 
+#[test]
+fn test_evaluate_expression_fork_point_two_args() {
+    let test_repo = TestRepo::init();
+    let repo = &test_repo.repo;
+
+    // 5 6
+    // |/|
+    // 4 |
+    // | |
+    // 1 2 3
+    // | |/
+    // |/
+    // 0
+    let mut tx = repo.start_transaction();
+    let mut_repo = tx.repo_mut();
+    let mut graph_builder = CommitGraphBuilder::new(mut_repo);
+    let root_commit = repo.store().root_commit();
+    let commit1 = graph_builder.initial_commit();
+    let commit2 = graph_builder.initial_commit();
+    let commit3 = graph_builder.initial_commit();
+    let commit4 = graph_builder.commit_with_parents(&[&commit1]);
+    let commit5 = graph_builder.commit_with_parents(&[&commit4]);
+    let commit6 = graph_builder.commit_with_parents(&[&commit4, &commit2]);
+
+    assert_eq!(
+        resolve_commit_ids(mut_repo, &format!("fork_point({}, none())", commit5.id())),
+        vec![]
+    );
+    assert_eq!(
+        resolve_commit_ids(
+            mut_repo,
+            &format!("fork_point({}, {})", commit5.id(), commit5.id())
+        ),
+        vec![commit5.id().clone()]
+    );
+    assert_eq!(
+        resolve_commit_ids(
+            mut_repo,
+            &format!("fork_point({}, {})", commit5.id(), commit6.id())
+        ),
+        vec![commit4.id().clone()]
+    );
+    assert_eq!(
+        resolve_commit_ids(
+            mut_repo,
+            &format!("fork_point({}, {})", commit3.id(), commit6.id())
+        ),
+        vec![root_commit.id().clone()]
+    );
+    // Unlike fork_point(x | y), commits in x don't have to share ancestors
+    assert_eq!(
+        resolve_commit_ids(
+            mut_repo,
+            &format!(
+                "fork_point({} | {}, {})",
+                commit1.id(),
+                commit2.id(),
+                commit4.id()
+            )
+        ),
+        vec![commit1.id().clone()]
+    );
+    assert_eq!(
+        resolve_commit_ids(
+            mut_repo,
+            &format!(
+                "fork_point({} | {}, {})",
+                commit5.id(),
+                commit2.id(),
+                commit6.id()
+            )
+        ),
+        vec![commit4.id().clone(), commit2.id().clone()]
+    );
+}
+
 #[test]
 fn test_evaluate_expression_fork_point_criss_cross() {
     let test_repo = TestRepo::init();
@@ -3925,6 +4050,72 @@ fn test_evaluate_expression_conflict() {
     );
 }
 
+#[test]
+fn test_evaluate_expression_tree() {
+    let test_repo = TestRepo::init();
+    let repo = &test_repo.repo;
+
+    let mut tx = repo.start_transaction();
+    let mut_repo = tx.repo_mut();
+
+    let file_path = RepoPath::from_internal_string("file");
+    let tree1 = create_tree(repo, &[(file_path, "1")]);
+    let tree2 = create_tree(repo, &[(file_path, "2")]);
+
+    // commit3 and commit4 are copies of commit1 and commit2 on another branch
+    let mut create_commit =
+        |parent_ids, tree_id| mut_repo.new_commit(parent_ids, tree_id).write().unwrap();
+    let commit1 = create_commit(vec![repo.store().root_commit_id().clone()], tree1.id());
+    let commit2 = create_commit(vec![commit1.id().clone()], tree2.id());
+    let commit3 = create_commit(vec![repo.store().root_commit_id().clone()], tree1.id());
+    let commit4 = create_commit(vec![commit3.id().clone()], tree2.id());
+
+    assert_eq!(
+        resolve_commit_ids(mut_repo, &format!("tree_equals({})", commit1.id())),
+        vec![commit3.id().clone(), commit1.id().clone()]
+    );
+    assert_eq!(
+        resolve_commit_ids(
+            mut_repo,
+            &format!("tree_equals({id}) & ~{id}", id = commit2.id())
+        ),
+        vec![commit4.id().clone()]
+    );
+    assert_eq!(
+        resolve_commit_ids(
+            mut_repo,
+            &format!("tree_equals({} | {})", commit1.id(), commit4.id())
+        ),
+        vec![
+            commit4.id().clone(),
+            commit3.id().clone(),
+            commit2.id().clone(),
+            commit1.id().clone(),
+        ]
+    );
+    assert_eq!(
+        resolve_commit_ids(
+            mut_repo,
+            &format!("{}:: & ~tree_equals(root())", commit3.id())
+        ),
+        vec![commit4.id().clone(), commit3.id().clone()]
+    );
+    assert_eq!(resolve_commit_ids(mut_repo, "tree_equals(none())"), vec![]);
+
+    let tree2_hex = tree2.id().to_merge().as_resolved().unwrap().hex();
+    assert_eq!(
+        resolve_commit_ids(mut_repo, &format!("exact_tree({tree2_hex})")),
+        vec![commit4.id().clone(), commit2.id().clone()]
+    );
+    assert_eq!(
+        resolve_commit_ids(
+            mut_repo,
+            &format!("exact_tree({tree2_hex}) & {}", commit2.id())
+        ),
+        vec![commit2.id().clone()]
+    );
+}
+
 #[test]
 fn test_reverse_graph() {
     let test_repo = TestRepo::init();
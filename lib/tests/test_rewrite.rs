@@ -13,6 +13,7 @@
 // limitations under the License.
 
 use itertools::Itertools as _;
+use jj_lib::backend::BackendError;
 use jj_lib::commit::Commit;
 use jj_lib::matchers::EverythingMatcher;
 use jj_lib::matchers::FilesMatcher;
@@ -27,6 +28,7 @@ use jj_lib::repo::Repo;
 use jj_lib::repo_path::RepoPath;
 use jj_lib::rewrite::rebase_commit_with_options;
 use jj_lib::rewrite::restore_tree;
+use jj_lib::rewrite::rewrite_tree_values;
 use jj_lib::rewrite::CommitRewriter;
 use jj_lib::rewrite::CommitWithSelection;
 use jj_lib::rewrite::EmptyBehaviour;
@@ -82,6 +84,41 @@ fn test_restore_tree() {
     assert_eq!(restored, expected.id());
 }
 
+#[test]
+fn test_rewrite_tree_values() {
+    let test_repo = TestRepo::init();
+    let repo = &test_repo.repo;
+
+    let path1 = RepoPath::from_internal_string("file1");
+    let path2 = RepoPath::from_internal_string("dir1/file2");
+    let path3 = RepoPath::from_internal_string("dir1/file3");
+    let tree = create_tree(repo, &[(path1, "a"), (path2, "b"), (path3, "c")]);
+    let other_tree = create_tree(repo, &[(path1, "x"), (path2, "y"), (path3, "z")]);
+
+    // Rewrite some of the matching paths
+    let mut visited_paths = vec![];
+    let rewritten =
+        rewrite_tree_values(&tree, &FilesMatcher::new([path1, path2]), |path, _value| {
+            visited_paths.push(path.to_owned());
+            if path == path1 {
+                Ok::<_, BackendError>(Some(other_tree.path_value(path)?))
+            } else {
+                Ok(None)
+            }
+        })
+        .unwrap();
+    assert_eq!(visited_paths, [path2.to_owned(), path1.to_owned()]);
+    let expected = create_tree(repo, &[(path1, "x"), (path2, "b"), (path3, "c")]);
+    assert_eq!(rewritten, expected.id());
+
+    // Remove all paths
+    let rewritten = rewrite_tree_values(&tree, &EverythingMatcher, |_path, _value| {
+        Ok::<_, BackendError>(Some(Merge::absent()))
+    })
+    .unwrap();
+    assert_eq!(rewritten, repo.store().empty_merged_tree_id());
+}
+
 #[test]
 fn test_rebase_descendants_sideways() {
     let test_repo = TestRepo::init();
@@ -14,6 +14,10 @@
 
 use std::collections::BTreeMap;
 
+use itertools::Itertools as _;
+use jj_lib::backend::MillisSinceEpoch;
+use jj_lib::backend::Timestamp;
+use jj_lib::op_store::BookmarkMetadata;
 use jj_lib::op_store::BookmarkTarget;
 use jj_lib::op_store::RefTarget;
 use jj_lib::op_store::RemoteRef;
@@ -22,6 +26,7 @@ use jj_lib::op_store::WorkspaceId;
 use jj_lib::refs::RemoteRefSymbol;
 use jj_lib::repo::Repo;
 use maplit::btreemap;
+use maplit::btreeset;
 use maplit::hashset;
 use test_case::test_case;
 use testutils::commit_transactions;
@@ -340,6 +345,259 @@ fn test_merge_views_tags() {
     );
 }
 
+#[test]
+fn test_merge_views_topics() {
+    // Tests merging of topics (by performing divergent operations). Topics are
+    // merged as sets of change ids.
+    let test_repo = TestRepo::init();
+    let repo = &test_repo.repo;
+
+    let mut tx = repo.start_transaction();
+    let mut_repo = tx.repo_mut();
+    let commit1 = write_random_commit(mut_repo);
+    let commit2 = write_random_commit(mut_repo);
+    let commit3 = write_random_commit(mut_repo);
+    let commit4 = write_random_commit(mut_repo);
+    mut_repo.set_topic_change_ids(
+        "feature",
+        btreeset! {commit1.change_id().clone(), commit2.change_id().clone()},
+    );
+    mut_repo.set_topic_change_ids("old", btreeset! {commit1.change_id().clone()});
+    let repo = tx.commit("test").unwrap();
+
+    let mut tx1 = repo.start_transaction();
+    tx1.repo_mut().set_topic_change_ids(
+        "feature",
+        btreeset! {commit2.change_id().clone(), commit3.change_id().clone()},
+    );
+
+    let mut tx2 = repo.start_transaction();
+    tx2.repo_mut().set_topic_change_ids(
+        "feature",
+        btreeset! {
+            commit1.change_id().clone(),
+            commit2.change_id().clone(),
+            commit4.change_id().clone(),
+        },
+    );
+    tx2.repo_mut().set_topic_change_ids("old", btreeset! {});
+
+    let repo = commit_transactions(vec![tx1, tx2]);
+    assert_eq!(
+        repo.view().topics(),
+        &btreemap! {
+            "feature".to_string() => btreeset! {
+                commit2.change_id().clone(),
+                commit3.change_id().clone(),
+                commit4.change_id().clone(),
+            },
+        }
+    );
+}
+
+#[test]
+fn test_record_bookmark_metadata() {
+    let test_repo = TestRepo::init();
+    let repo = &test_repo.repo;
+
+    let mut tx = repo.start_transaction();
+    let commit1 = write_random_commit(tx.repo_mut());
+    let commit2 = write_random_commit(tx.repo_mut());
+    tx.repo_mut()
+        .set_local_bookmark_target("main", RefTarget::normal(commit1.id().clone()));
+    let repo = tx.commit("test").unwrap();
+    let created_metadata = repo.view().get_bookmark_metadata("main").unwrap().clone();
+    assert_eq!(
+        created_metadata.created.as_ref(),
+        Some(&created_metadata.moved)
+    );
+    assert_eq!(
+        created_metadata.moved,
+        repo.operation().metadata().start_time
+    );
+    assert!(!created_metadata.moved_by.is_empty());
+
+    // Moving the bookmark keeps the creation time
+    let mut tx = repo.start_transaction();
+    tx.repo_mut()
+        .set_local_bookmark_target("main", RefTarget::normal(commit2.id().clone()));
+    let repo = tx.commit("test").unwrap();
+    let moved_metadata = repo.view().get_bookmark_metadata("main").unwrap();
+    assert_eq!(moved_metadata.created, created_metadata.created);
+    assert_eq!(moved_metadata.moved, repo.operation().metadata().start_time);
+
+    // Operations not touching the bookmark leave the metadata alone
+    let mut tx = repo.start_transaction();
+    write_random_commit(tx.repo_mut());
+    let repo = tx.commit("test").unwrap();
+    assert_eq!(
+        repo.view().get_bookmark_metadata("main"),
+        Some(moved_metadata)
+    );
+
+    // Deleting the bookmark removes the metadata
+    let mut tx = repo.start_transaction();
+    tx.repo_mut()
+        .set_local_bookmark_target("main", RefTarget::absent());
+    let repo = tx.commit("test").unwrap();
+    assert_eq!(repo.view().get_bookmark_metadata("main"), None);
+}
+
+#[test]
+fn test_merge_views_bookmark_metadata_not_recorded() {
+    // Bookmarks moved by a concurrent operation aren't recorded as moved by the
+    // operation merging it.
+    let test_repo = TestRepo::init();
+    let repo = &test_repo.repo;
+
+    let mut tx = repo.start_transaction();
+    let commit1 = write_random_commit(tx.repo_mut());
+    let commit2 = write_random_commit(tx.repo_mut());
+    tx.repo_mut()
+        .set_local_bookmark_target("main", RefTarget::normal(commit1.id().clone()));
+    let repo = tx.commit("test").unwrap();
+    let metadata = repo.view().get_bookmark_metadata("main").cloned();
+
+    let mut tx1 = repo.start_transaction();
+    write_random_commit(tx1.repo_mut());
+
+    // Moves the bookmark without recording metadata, as older versions of jj
+    // would
+    let mut tx2 = repo.start_transaction();
+    let mut view = tx2.repo_mut().view().store_view().clone();
+    view.local_bookmarks
+        .insert("main".to_owned(), RefTarget::normal(commit2.id().clone()));
+    tx2.repo_mut().set_view(view);
+
+    let repo = commit_transactions(vec![tx1, tx2]);
+    assert_eq!(
+        repo.view().get_local_bookmark("main"),
+        &RefTarget::normal(commit2.id().clone())
+    );
+    assert_eq!(repo.view().get_bookmark_metadata("main").cloned(), metadata);
+}
+
+#[test]
+fn test_merge_views_bookmark_metadata() {
+    // Tests merging of bookmark metadata (by performing divergent operations).
+    // If both sides changed the metadata, the later move wins.
+    let test_repo = TestRepo::init();
+    let repo = &test_repo.repo;
+    let metadata = |millis| BookmarkMetadata {
+        created: None,
+        moved: Timestamp {
+            timestamp: MillisSinceEpoch(millis),
+            tz_offset: 0,
+        },
+        moved_by: format!("user{millis}"),
+    };
+
+    let mut tx = repo.start_transaction();
+    let mut_repo = tx.repo_mut();
+    mut_repo.set_bookmark_metadata("both", Some(metadata(0)));
+    mut_repo.set_bookmark_metadata("one-side", Some(metadata(0)));
+    mut_repo.set_bookmark_metadata("deleted", Some(metadata(0)));
+    let repo = tx.commit("test").unwrap();
+
+    let mut tx1 = repo.start_transaction();
+    tx1.repo_mut()
+        .set_bookmark_metadata("both", Some(metadata(2)));
+
+    let mut tx2 = repo.start_transaction();
+    tx2.repo_mut()
+        .set_bookmark_metadata("both", Some(metadata(1)));
+    tx2.repo_mut()
+        .set_bookmark_metadata("one-side", Some(metadata(1)));
+    tx2.repo_mut().set_bookmark_metadata("deleted", None);
+
+    let repo = commit_transactions(vec![tx1, tx2]);
+    assert_eq!(
+        repo.view().bookmark_metadata(),
+        &btreemap! {
+            "both".to_string() => metadata(2),
+            "one-side".to_string() => metadata(1),
+        }
+    );
+}
+
+#[test]
+fn test_merge_views_shelves() {
+    // Tests merging of shelves (by performing divergent operations). If both
+    // sides changed the same shelf, one of them is kept.
+    let test_repo = TestRepo::init();
+    let repo = &test_repo.repo;
+
+    let mut tx = repo.start_transaction();
+    let mut_repo = tx.repo_mut();
+    let commit1 = write_random_commit(mut_repo);
+    let commit2 = write_random_commit(mut_repo);
+    let commit3 = write_random_commit(mut_repo);
+    mut_repo.set_shelf("both", Some(commit1.id().clone()));
+    mut_repo.set_shelf("deleted", Some(commit1.id().clone()));
+    let repo = tx.commit("test").unwrap();
+
+    let mut tx1 = repo.start_transaction();
+    tx1.repo_mut().set_shelf("both", Some(commit2.id().clone()));
+
+    let mut tx2 = repo.start_transaction();
+    tx2.repo_mut().set_shelf("both", Some(commit3.id().clone()));
+    tx2.repo_mut().set_shelf("deleted", None);
+    tx2.repo_mut()
+        .set_shelf("added", Some(commit3.id().clone()));
+
+    let repo = commit_transactions(vec![tx1, tx2]);
+    let shelves = repo.view().shelves();
+    assert_eq!(shelves.keys().collect_vec(), ["added", "both"]);
+    assert_eq!(shelves["added"], *commit3.id());
+    assert!([commit2.id(), commit3.id()].contains(&&shelves["both"]));
+}
+
+#[test]
+fn test_merge_views_change_metadata() {
+    // Tests merging of change metadata (by performing divergent operations).
+    // Keys are merged independently. If both sides changed the same key, ours
+    // is kept.
+    let test_repo = TestRepo::init();
+    let repo = &test_repo.repo;
+
+    let mut tx = repo.start_transaction();
+    let mut_repo = tx.repo_mut();
+    let commit1 = write_random_commit(mut_repo);
+    let commit2 = write_random_commit(mut_repo);
+    let change_id1 = commit1.change_id();
+    let change_id2 = commit2.change_id();
+    mut_repo.set_change_metadata(change_id1, "both", Some("base".to_owned()));
+    mut_repo.set_change_metadata(change_id1, "deleted", Some("base".to_owned()));
+    mut_repo.set_change_metadata(change_id1, "kept", Some("base".to_owned()));
+    let repo = tx.commit("test").unwrap();
+
+    let mut tx1 = repo.start_transaction();
+    tx1.repo_mut()
+        .set_change_metadata(change_id1, "both", Some("1".to_owned()));
+    tx1.repo_mut()
+        .set_change_metadata(change_id2, "added1", Some("1".to_owned()));
+
+    let mut tx2 = repo.start_transaction();
+    tx2.repo_mut()
+        .set_change_metadata(change_id1, "both", Some("2".to_owned()));
+    tx2.repo_mut()
+        .set_change_metadata(change_id1, "deleted", None);
+    tx2.repo_mut()
+        .set_change_metadata(change_id2, "added2", Some("2".to_owned()));
+
+    let repo = commit_transactions(vec![tx1, tx2]);
+    let metadata = repo.view().change_metadata();
+    assert_eq!(metadata[change_id1].keys().collect_vec(), ["both", "kept"]);
+    assert!(["1", "2"].contains(&metadata[change_id1]["both"].as_str()));
+    assert_eq!(
+        metadata[change_id2],
+        btreemap! {
+            "added1".to_owned() => "1".to_owned(),
+            "added2".to_owned() => "2".to_owned(),
+        }
+    );
+}
+
 #[test]
 fn test_merge_views_git_refs() {
     // Tests merging of git refs (by performing divergent operations). See
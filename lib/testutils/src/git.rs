@@ -399,3 +399,163 @@ pub fn fetch(repo_dir: impl AsRef<Path>, remote: &str) {
         bstr::BString::from(output.stderr),
     );
 }
+
+/// Minimal HTTP server that serves the Git repositories under a directory by
+/// running `git http-backend` for each request.
+///
+/// Each connection serves a single request and is then closed. Pushes are
+/// enabled for all repositories.
+pub struct HttpServer {
+    addr: std::net::SocketAddr,
+    stop: std::sync::Arc<std::sync::atomic::AtomicBool>,
+}
+
+impl HttpServer {
+    pub fn start(project_root: impl Into<PathBuf>) -> Self {
+        let project_root = project_root.into();
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let stop = std::sync::Arc::new(std::sync::atomic::AtomicBool::new(false));
+        let stop_flag = stop.clone();
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                if stop_flag.load(std::sync::atomic::Ordering::SeqCst) {
+                    break;
+                }
+                let Ok(stream) = stream else {
+                    continue;
+                };
+                let project_root = project_root.clone();
+                std::thread::spawn(move || serve_http_backend(stream, &project_root));
+            }
+        });
+        Self { addr, stop }
+    }
+
+    /// Returns the URL of the repository at `name` relative to the project
+    /// root.
+    pub fn url(&self, name: &str) -> String {
+        format!("http://{}/{name}", self.addr)
+    }
+}
+
+impl Drop for HttpServer {
+    fn drop(&mut self) {
+        self.stop.store(true, std::sync::atomic::Ordering::SeqCst);
+        // Wake up the accept loop so it can observe the stop flag.
+        let _ = std::net::TcpStream::connect(self.addr);
+    }
+}
+
+fn serve_http_backend(stream: std::net::TcpStream, project_root: &Path) {
+    use std::io::BufRead as _;
+    use std::io::Read as _;
+    use std::io::Write as _;
+
+    let mut reader = std::io::BufReader::new(stream.try_clone().unwrap());
+    let mut request_line = String::new();
+    if reader.read_line(&mut request_line).unwrap_or(0) == 0 {
+        return;
+    }
+    let mut parts = request_line.split_whitespace();
+    let method = parts.next().unwrap_or_default().to_owned();
+    let target = parts.next().unwrap_or_default().to_owned();
+    let (path, query) = target.split_once('?').unwrap_or((&target, ""));
+
+    let mut headers = Vec::new();
+    loop {
+        let mut line = String::new();
+        reader.read_line(&mut line).unwrap();
+        let line = line.trim_end();
+        if line.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            headers.push((name.trim().to_ascii_lowercase(), value.trim().to_owned()));
+        }
+    }
+    let header = |name: &str| {
+        headers
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, v)| v.as_str())
+    };
+
+    let mut body = Vec::new();
+    if header("transfer-encoding").is_some_and(|v| v.eq_ignore_ascii_case("chunked")) {
+        loop {
+            let mut size_line = String::new();
+            reader.read_line(&mut size_line).unwrap();
+            let size_str = size_line.trim().split(';').next().unwrap();
+            let size = usize::from_str_radix(size_str, 16).unwrap();
+            let mut chunk = vec![0; size + 2];
+            reader.read_exact(&mut chunk).unwrap();
+            if size == 0 {
+                break;
+            }
+            body.extend_from_slice(&chunk[..size]);
+        }
+    } else if let Some(len) = header("content-length") {
+        body.resize(len.parse().unwrap(), 0);
+        reader.read_exact(&mut body).unwrap();
+    }
+
+    let mut command = std::process::Command::new("git");
+    command
+        .arg("http-backend")
+        .env("GIT_PROJECT_ROOT", project_root)
+        .env("GIT_HTTP_EXPORT_ALL", "1")
+        .env("GIT_CONFIG_COUNT", "1")
+        .env("GIT_CONFIG_KEY_0", "http.receivepack")
+        .env("GIT_CONFIG_VALUE_0", "true")
+        .env("REQUEST_METHOD", &method)
+        .env("PATH_INFO", path)
+        .env("QUERY_STRING", query)
+        .env("CONTENT_LENGTH", body.len().to_string())
+        .stdin(std::process::Stdio::piped())
+        .stdout(std::process::Stdio::piped())
+        .stderr(std::process::Stdio::null());
+    if let Some(content_type) = header("content-type") {
+        command.env("CONTENT_TYPE", content_type);
+    }
+    if let Some(encoding) = header("content-encoding") {
+        command.env("HTTP_CONTENT_ENCODING", encoding);
+    }
+    if let Some(protocol) = header("git-protocol") {
+        command.env("GIT_PROTOCOL", protocol);
+    }
+    let mut child = command.spawn().unwrap();
+    let mut stdin = child.stdin.take().unwrap();
+    let writer = std::thread::spawn(move || stdin.write_all(&body));
+    let output = child.wait_with_output().unwrap();
+    writer.join().unwrap().unwrap();
+
+    // Translate the CGI response into an HTTP response.
+    let stdout = output.stdout;
+    let (cgi_headers, cgi_body) = match stdout.windows(4).position(|w| w == b"\r\n\r\n") {
+        Some(pos) => (&stdout[..pos], &stdout[pos + 4..]),
+        None => (&stdout[..], &[][..]),
+    };
+    let mut status = "200 OK".to_owned();
+    let mut response_headers = String::new();
+    for line in String::from_utf8_lossy(cgi_headers).lines() {
+        match line.split_once(':') {
+            Some((name, value)) if name.eq_ignore_ascii_case("status") => {
+                status = value.trim().to_owned();
+            }
+            _ => {
+                response_headers.push_str(line);
+                response_headers.push_str("\r\n");
+            }
+        }
+    }
+    let mut stream = stream;
+    write!(
+        stream,
+        "HTTP/1.1 {status}\r\n{response_headers}Content-Length: {}\r\nConnection: close\r\n\r\n",
+        cgi_body.len()
+    )
+    .unwrap();
+    stream.write_all(cgi_body).unwrap();
+    stream.flush().unwrap();
+}